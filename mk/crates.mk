@@ -63,7 +63,7 @@ DEPS_rustuv := std native:uv native:uv_support
 DEPS_native := std
 DEPS_syntax := std term serialize collections log fmt_macros
 DEPS_rustc := syntax native:rustllvm flate arena serialize sync getopts \
-              collections time log
+              collections time log graphviz
 DEPS_rustdoc := rustc native:hoedown serialize sync getopts collections \
                 test time
 DEPS_flate := std native:miniz
@@ -73,7 +73,7 @@ DEPS_glob := std
 DEPS_serialize := std collections log
 DEPS_term := std collections
 DEPS_semver := std
-DEPS_uuid := std serialize rand
+DEPS_uuid := std serialize rand time
 DEPS_sync := std
 DEPS_getopts := std
 DEPS_collections := std rand
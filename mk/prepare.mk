@@ -141,6 +141,8 @@ prepare-target-$(2)-host-$(3)-$(1)-$(4): prepare-maybe-clean-$(4) \
             $$(foreach crate,$$(HOST_CRATES),\
               $$(call PREPARE_LIB,$$(call CFG_LIB_GLOB_$(2),$$(crate)))),)\
           $$(call PREPARE_LIB,libmorestack.a) \
+          $$(foreach rt,$$(COMPRT_RUNTIMES_$(2)),\
+            $$(call PREPARE_LIB,libcompiler-rt-$$(rt).a)) \
           $$(call PREPARE_LIB,libcompiler-rt.a),),),)
 endef
 
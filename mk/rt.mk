@@ -251,6 +251,43 @@ $$(COMPRT_LIB_$(1)): $$(COMPRT_DEPS) $$(MKFILE_DEPS)
 		triple-builtins
 	$$(Q)cp $$(COMPRT_BUILD_DIR_$(1))/triple/builtins/libcompiler_rt.a $$(COMPRT_LIB_$(1))
 
+################################################################################
+# compiler-rt runtimes
+#
+# The runtimes linked into programs built with `-Z sanitizer` or `-Z profile`.
+# They're only built when configured with --enable-sanitizers or
+# --enable-profiler, and only for x86_64 Linux, which is the one target rustc
+# supports them on. The run-make tests which need them are skipped otherwise.
+################################################################################
+
+COMPRT_RUNTIMES_$(1) :=
+ifeq ($(1),x86_64-unknown-linux-gnu)
+ifdef CFG_ENABLE_SANITIZERS
+COMPRT_RUNTIMES_$(1) += asan tsan msan
+endif
+ifdef CFG_ENABLE_PROFILER
+COMPRT_RUNTIMES_$(1) += profile
+endif
+endif
+
+COMPRT_RUNTIMES_BUILD_DIR_$(1) := $$(RT_OUTPUT_DIR_$(1))/compiler-rt-runtimes
+
+$$(COMPRT_RUNTIMES_BUILD_DIR_$(1))/clang_linux/%-x86_64/libcompiler_rt.a: \
+	    $$(COMPRT_DEPS) $$(MKFILE_DEPS)
+	@$$(call E, make: compiler-rt $$*)
+	$$(Q)$$(MAKE) -C "$(S)src/compiler-rt" \
+		ProjSrcRoot="$(S)src/compiler-rt" \
+		ProjObjRoot="$$(abspath $$(COMPRT_RUNTIMES_BUILD_DIR_$(1)))" \
+		CC="$$(CC_$(1))" \
+		AR="$$(AR_$(1))" \
+		RANLIB="$$(AR_$(1)) s" \
+		clang_linux
+
+$$(RT_OUTPUT_DIR_$(1))/$$(call CFG_STATIC_LIB_NAME_$(1),compiler-rt-%): \
+	    $$(COMPRT_RUNTIMES_BUILD_DIR_$(1))/clang_linux/%-x86_64/libcompiler_rt.a
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+
 ################################################################################
 # libbacktrace
 #
@@ -273,6 +273,7 @@ tidy:
 		| grep '^$(S)src/compiler-rt' -v \
 		| grep '^$(S)src/libbacktrace' -v \
 		| xargs $(CFG_PYTHON) $(S)src/etc/check-binaries.py
+		$(Q)$(CFG_PYTHON) $(S)src/etc/errorck.py $(S)src/librustc
 
 endif
 
@@ -0,0 +1,145 @@
+# Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution and at
+# http://rust-lang.org/COPYRIGHT.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+# Reports the line and branch coverage of a program built with `-Z profile`.
+#
+# Build and run the program, typically a `--test` build:
+#
+#     rustc -Z profile --test --out-dir build foo.rs
+#     ./build/foo
+#
+# which leaves the notes (`foo.gcno`) and counters (`foo.gcda`) of the crate
+# in `build`. Then, from the directory rustc was run in, run
+#
+#     coverage.py build [source-prefix...]
+#
+# which prints, for each source file, how many of its lines and branches were
+# executed, followed by the lines that never were. Only the files under one of
+# the given prefixes are reported, if any are given.
+#
+# The counters are read with `llvm-cov gcov`, as the ones of the system's gcov
+# may not understand the files written by LLVM. Set GCOV to use another tool,
+# for example `GCOV=gcov-4.8`.
+
+import os, shlex, shutil, subprocess, sys, tempfile
+
+if len(sys.argv) < 2:
+    print("usage: coverage.py <dir> [source-prefix...]")
+    sys.exit(1)
+
+data_dir = os.path.abspath(sys.argv[1])
+prefixes = [os.path.abspath(p) for p in sys.argv[2:]]
+gcov = shlex.split(os.environ.get("GCOV", "llvm-cov gcov"))
+
+# For each source file, the execution count of each executable line and the
+# number of times each branch was taken, keyed by (line, branch index).
+lines = {}
+branches = {}
+
+def parse_gcov(path):
+    source = None
+    lineno = 0
+    index = 0
+    with open(path) as f:
+        for line in f:
+            if line.startswith("branch"):
+                # "branch  0 taken 3" or "branch  1 never executed"
+                words = line.split()
+                taken = 0
+                if words[2] == "taken":
+                    taken = int(words[3])
+                key = (lineno, index)
+                counts = branches.setdefault(source, {})
+                counts[key] = counts.get(key, 0) + taken
+                index += 1
+                continue
+            parts = line.split(":", 2)
+            if len(parts) < 3:
+                # "call", "function" and "unconditional" summaries
+                continue
+            count = parts[0].strip().rstrip("*")
+            lineno = int(parts[1].strip())
+            index = 0
+            if lineno == 0:
+                if parts[2].startswith("Source:"):
+                    # relative to the directory rustc was run in
+                    source = os.path.abspath(parts[2][len("Source:"):].strip())
+                continue
+            if count == "-":
+                continue
+            if count in ("#####", "====="):
+                count = 0
+            counts = lines.setdefault(source, {})
+            counts[lineno] = counts.get(lineno, 0) + int(count)
+
+def percent(covered, total):
+    if total == 0:
+        return "100.0%"
+    return "%.1f%%" % (100.0 * covered / total)
+
+def ranges(numbers):
+    # 3, 4, 5, 9 -> "3-5, 9"
+    out = []
+    for n in numbers:
+        if out and out[-1][1] == n - 1:
+            out[-1][1] = n
+        else:
+            out.append([n, n])
+    return ", ".join(str(a) if a == b else "%d-%d" % (a, b) for a, b in out)
+
+gcdas = []
+for root, dirs, files in os.walk(data_dir):
+    gcdas.extend(os.path.join(root, f) for f in files if f.endswith(".gcda"))
+if not gcdas:
+    print("coverage.py: no .gcda files in %s, was the program run?" % data_dir)
+    sys.exit(1)
+
+# gcov writes its reports to the working directory, so run it in a scratch
+# one. `-p` keeps the reports of same-named sources from overwriting each
+# other.
+work_dir = tempfile.mkdtemp()
+try:
+    for gcda in sorted(gcdas):
+        cmd = gcov + ["-b", "-c", "-p", "-o", os.path.dirname(gcda), gcda]
+        with open(os.devnull, "w") as devnull:
+            ret = subprocess.call(cmd, cwd=work_dir, stdout=devnull)
+        if ret != 0:
+            print("coverage.py: `%s` failed" % " ".join(cmd))
+            sys.exit(1)
+        for report in os.listdir(work_dir):
+            parse_gcov(os.path.join(work_dir, report))
+            os.remove(os.path.join(work_dir, report))
+finally:
+    shutil.rmtree(work_dir)
+
+total_lines = total_covered_lines = 0
+total_branches = total_covered_branches = 0
+for source in sorted(lines):
+    if prefixes and not any(source.startswith(p) for p in prefixes):
+        continue
+    counts = lines[source]
+    covered = len([n for n in counts.values() if n > 0])
+    taken = branches.get(source, {})
+    covered_branches = len([n for n in taken.values() if n > 0])
+    print("%s: lines %d/%d (%s), branches %d/%d (%s)" %
+          (source, covered, len(counts), percent(covered, len(counts)),
+           covered_branches, len(taken), percent(covered_branches, len(taken))))
+    missed = sorted(l for l, n in counts.items() if n == 0)
+    if missed:
+        print("    not executed: %s" % ranges(missed))
+    total_lines += len(counts)
+    total_covered_lines += covered
+    total_branches += len(taken)
+    total_covered_branches += covered_branches
+
+print("total: lines %d/%d (%s), branches %d/%d (%s)" %
+      (total_covered_lines, total_lines, percent(total_covered_lines, total_lines),
+       total_covered_branches, total_branches,
+       percent(total_covered_branches, total_branches)))
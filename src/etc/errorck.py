@@ -0,0 +1,70 @@
+# Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution and at
+# http://rust-lang.org/COPYRIGHT.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+# Checks that every error code used with span_err! or resolve_err! in the
+# given source tree is registered in its diagnostics.rs, and that each code
+# is registered exactly once and used exactly once.
+#
+# Usage: errorck.py src/librustc
+
+import sys, os, re
+
+src_dir = sys.argv[1]
+registry = os.path.join(src_dir, "diagnostics.rs")
+
+errors = False
+
+def report(s):
+    global errors
+    print(s)
+    errors = True
+
+registered = {}
+lineno = 0
+for line in open(registry):
+    lineno += 1
+    m = re.match(r'\s*\("(E\d{4})",', line)
+    if m:
+        code = m.group(1)
+        if code in registered:
+            report("%s:%d: %s is already registered on line %d" %
+                   (registry, lineno, code, registered[code]))
+        else:
+            registered[code] = lineno
+
+# The code is the third argument of the macro, but the earlier ones may
+# contain commas, so take the first code after the macro name
+use_re = re.compile(r'\b(?:span_err|resolve_err)!\(.*?\b(E\d{4})\b', re.S)
+
+used = {}
+for (dirpath, dirnames, filenames) in os.walk(src_dir):
+    for name in filenames:
+        path = os.path.join(dirpath, name)
+        if not name.endswith(".rs") or path == registry:
+            continue
+        contents = open(path).read()
+        for m in use_re.finditer(contents):
+            code = m.group(1)
+            lineno = contents.count("\n", 0, m.start(1)) + 1
+            where = "%s:%d" % (path, lineno)
+            if code in used:
+                report("%s: %s is already used at %s" % (where, code, used[code]))
+            else:
+                used[code] = where
+            if code not in registered:
+                report("%s: %s is not registered in %s" % (where, code, registry))
+
+for code in sorted(registered):
+    if code not in used:
+        report("%s:%d: %s is registered but never used" %
+               (registry, registered[code], code))
+
+if errors:
+    sys.exit(1)
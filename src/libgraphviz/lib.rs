@@ -439,6 +439,27 @@ impl<'a> LabelText<'a> {
             &EscStr(ref s) => LabelText::escape_str(s.as_slice()).into_owned(),
         }
     }
+
+    /// Returns text that renders the same as `self` when used in an
+    /// `EscStr`.
+    fn pre_escaped_content(self) -> str::MaybeOwned<'a> {
+        match self {
+            EscStr(s) => s,
+            LabelStr(s) => if s.as_slice().contains_char('\\') {
+                str::Owned(s.as_slice().replace("\\", "\\\\"))
+            } else {
+                s
+            },
+        }
+    }
+
+    /// Puts `suffix` on a line of its own below this label.
+    pub fn suffix_line(self, suffix: LabelText) -> LabelText<'static> {
+        let mut text = StrBuf::from_str(self.pre_escaped_content().as_slice());
+        text.push_str("\\n");
+        text.push_str(suffix.pre_escaped_content().as_slice());
+        EscStr(str::Owned(text.into_owned()))
+    }
 }
 
 pub type Nodes<'a,N> = MaybeOwnedVector<'a,N>;
@@ -779,4 +800,13 @@ r#"digraph syntax_tree {
 }
 "#);
     }
+
+    #[test]
+    fn suffix_line() {
+        let label = LabelStr(str::Slice("a \\ \"b\"")).suffix_line(EscStr(str::Slice("c\\l")));
+        assert_eq!(label.escape().as_slice(), r#"a \\ \"b\"\nc\l"#);
+
+        let label = EscStr(str::Slice("a\\l")).suffix_line(LabelStr(str::Slice("b")));
+        assert_eq!(label.escape().as_slice(), r#"a\l\nb"#);
+    }
 }
@@ -235,12 +235,14 @@ mod test {
 
     use basic;
     use PoolConfig;
+    use stack::StackPolicy;
     use SchedPool;
 
     fn pool() -> SchedPool {
         SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: basic::event_loop,
+            stacks: StackPolicy::new(),
         })
     }
 
@@ -271,6 +273,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2,
             event_loop_factory: basic::event_loop,
+            stacks: StackPolicy::new(),
         });
 
         for _ in range(0, 20) {
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use stack::Stack;
+use stack::{Stack, record_guard};
 use std::uint;
 use std::cast::{transmute, transmute_mut_unsafe};
 use std::rt::stack;
@@ -25,6 +25,8 @@ pub struct Context {
     regs: Box<Registers>,
     /// Lower bound and upper bound for the stack
     stack_bounds: Option<(uint, uint)>,
+    /// Lower bound and upper bound of the stack's guard pages
+    guard: Option<(uint, uint)>,
 }
 
 pub type InitFn = extern "C" fn(uint, *(), *()) -> !;
@@ -34,6 +36,7 @@ impl Context {
         Context {
             regs: new_regs(),
             stack_bounds: None,
+            guard: None,
         }
     }
 
@@ -67,14 +70,15 @@ impl Context {
         // overflow). Additionally, their coroutine stacks are listed as being
         // zero-length, so that's how we detect what's what here.
         let stack_base: *uint = stack.start();
-        let bounds = if sp as uint == stack_base as uint {
-            None
+        let (bounds, guard) = if sp as uint == stack_base as uint {
+            (None, None)
         } else {
-            Some((stack_base as uint, sp as uint))
+            (Some((stack_base as uint, sp as uint)), Some(stack.guard()))
         };
         return Context {
             regs: regs,
             stack_bounds: bounds,
+            guard: guard,
         }
     }
 
@@ -96,6 +100,13 @@ impl Context {
         rtdebug!("noting the stack limit and doing raw swap");
 
         unsafe {
+            // Faults in the guard pages of the new context's stack are
+            // reported as an overflow of that task.
+            match in_context.guard {
+                Some((lo, hi)) => record_guard(lo, hi),
+                None => record_guard(0, 0),
+            }
+
             // Right before we switch to the new context, set the new context's
             // stack limit in the OS-specified TLS slot. This also  means that
             // we cannot call any more rust functions after record_stack_bounds
@@ -227,7 +227,7 @@ use std::sync::arc::UnsafeArc;
 
 use sched::{Shutdown, Scheduler, SchedHandle, TaskFromFriend, NewNeighbor};
 use sleeper_list::SleeperList;
-use stack::StackPool;
+use stack::{StackPool, StackPolicy};
 use task::GreenTask;
 
 mod macros;
@@ -341,6 +341,9 @@ pub struct PoolConfig {
     /// A factory function used to create new event loops. If this is not
     /// specified then the default event loop factory is used.
     pub event_loop_factory: fn() -> Box<rtio::EventLoop:Send>,
+    /// How the schedulers in this pool allocate, guard and cache the stacks
+    /// of their tasks.
+    pub stacks: StackPolicy,
 }
 
 impl PoolConfig {
@@ -350,6 +353,7 @@ impl PoolConfig {
         PoolConfig {
             threads: rt::default_sched_threads(),
             event_loop_factory: basic::event_loop,
+            stacks: StackPolicy::new(),
         }
     }
 }
@@ -389,7 +393,8 @@ impl SchedPool {
 
         let PoolConfig {
             threads: nscheds,
-            event_loop_factory: factory
+            event_loop_factory: factory,
+            stacks: stacks
         } = config;
         assert!(nscheds > 0);
 
@@ -401,7 +406,7 @@ impl SchedPool {
             stealers: vec![],
             id: unsafe { POOL_ID.fetch_add(1, SeqCst) },
             sleepers: SleeperList::new(),
-            stack_pool: StackPool::new(),
+            stack_pool: StackPool::with_policy(stacks),
             deque_pool: deque::BufferPool::new(),
             next_friend: 0,
             factory: factory,
@@ -433,6 +438,7 @@ impl SchedPool {
                                             pool.stealers.clone(),
                                             pool.sleepers.clone(),
                                             pool.task_state.clone());
+            sched.stack_pool = pool.new_stack_pool();
             pool.handles.push(sched.make_handle());
             let sched = sched;
             pool.threads.push(Thread::start(proc() { sched.bootstrap(); }));
@@ -494,6 +500,7 @@ impl SchedPool {
                                         self.stealers.clone(),
                                         self.sleepers.clone(),
                                         self.task_state.clone());
+        sched.stack_pool = self.new_stack_pool();
         let ret = sched.make_handle();
         self.handles.push(sched.make_handle());
         let sched = sched;
@@ -502,6 +509,13 @@ impl SchedPool {
         return ret;
     }
 
+    /// Creates an empty stack pool for a scheduler of this pool. Stacks are
+    /// recycled into whichever scheduler a task exits on, so all of them need
+    /// to share the pool's policy.
+    fn new_stack_pool(&self) -> StackPool {
+        StackPool::with_policy(self.stack_pool.policy().clone())
+    }
+
     /// Consumes the pool of schedulers, waiting for all tasks to exit and all
     /// schedulers to shut down.
     ///
@@ -23,7 +23,7 @@ use TaskState;
 use context::Context;
 use coroutine::Coroutine;
 use sleeper_list::SleeperList;
-use stack::StackPool;
+use stack::{StackPool, install_guard_handler};
 use task::{TypeSched, GreenTask, HomeSched, AnySched};
 use msgq = message_queue;
 
@@ -182,6 +182,9 @@ impl Scheduler {
     // scheduler task and bootstrap into it.
     pub fn bootstrap(mut ~self) {
 
+        // Report overflows into the guard pages of our tasks' stacks.
+        install_guard_handler();
+
         // Build an Idle callback.
         let cb = box SchedRunner as Box<Callback:Send>;
         self.idle_callback = Some(self.event_loop.pausable_idle_callback(cb));
@@ -1024,6 +1027,7 @@ mod test {
     use std::rt::local::Local;
 
     use {TaskState, PoolConfig, SchedPool};
+    use stack::StackPolicy;
     use basic;
     use sched::{TaskFromFriend, PinnedTask};
     use task::{GreenTask, HomeSched};
@@ -1032,6 +1036,7 @@ mod test {
         SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: basic::event_loop,
+            stacks: StackPolicy::new(),
         })
     }
 
@@ -1277,6 +1282,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2,
             event_loop_factory: rustuv::event_loop,
+            stacks: StackPolicy::new(),
         });
 
         // This is a regression test that when there are no schedulable tasks in
@@ -1428,6 +1434,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2, // this must be > 1
             event_loop_factory: basic::event_loop,
+            stacks: StackPolicy::new(),
         });
         pool.spawn(TaskOpts::new(), proc() {
             let (tx, rx) = channel();
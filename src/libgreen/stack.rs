@@ -11,12 +11,18 @@
 use std::rt::env::max_cached_stacks;
 use std::os::{errno, page_size, MemoryMap, MapReadable, MapWritable,
               MapNonStandardFlags, MapVirtual};
+use std::rt::local::Local;
+use std::rt::stack;
+use std::rt::task::Task;
+use std::intrinsics;
 use libc;
 
 /// A task's stack. The name "Stack" is a vestige of segmented stacks.
 pub struct Stack {
     buf: MemoryMap,
     min_size: uint,
+    guard_size: uint,
+    lazy: bool,
     valgrind_id: libc::c_uint,
 }
 
@@ -32,25 +38,46 @@ static STACK_FLAGS: libc::c_int = libc::MAP_PRIVATE | libc::MAP_ANON;
 #[cfg(windows)]
 static STACK_FLAGS: libc::c_int = 0;
 
+// Lazily committed stacks ask the kernel not to reserve swap for the whole
+// mapping up front, so that many large, mostly untouched stacks only account
+// for the pages that they actually use.
+#[cfg(target_os = "linux")] #[cfg(target_os = "android")]
+static LAZY_FLAGS: libc::c_int = libc::MAP_NONRESERVE;
+#[cfg(not(target_os = "linux"), not(target_os = "android"))]
+static LAZY_FLAGS: libc::c_int = 0;
+
 impl Stack {
-    /// Allocate a new stack of `size`. If size = 0, this will fail. Use
-    /// `dummy_stack` if you want a zero-sized stack.
+    /// Allocate a new stack of `size` with a single guard page. If size = 0,
+    /// this will fail. Use `dummy_stack` if you want a zero-sized stack.
     pub fn new(size: uint) -> Stack {
+        Stack::with_options(size, 1, false)
+    }
+
+    /// Allocate a new stack of `size` usable bytes, preceded by `guard_pages`
+    /// inaccessible pages. If `lazy` is set, the stack's memory is not
+    /// reserved up front and is handed back to the OS whenever the stack is
+    /// cached in a `StackPool`.
+    pub fn with_options(size: uint, guard_pages: uint, lazy: bool) -> Stack {
+        let guard_size = guard_pages * page_size();
+        let flags = if lazy {STACK_FLAGS | LAZY_FLAGS} else {STACK_FLAGS};
+
         // Map in a stack. Eventually we might be able to handle stack
         // allocation failure, which would fail to spawn the task. But there's
         // not many sensible things to do on OOM.  Failure seems fine (and is
         // what the old stack allocation did).
-        let stack = match MemoryMap::new(size, [MapReadable, MapWritable,
-                                         MapNonStandardFlags(STACK_FLAGS)]) {
+        let stack = match MemoryMap::new(size + guard_size,
+                                         [MapReadable, MapWritable,
+                                          MapNonStandardFlags(flags)]) {
             Ok(map) => map,
             Err(e) => fail!("mmap for stack of size {} failed: {}", size, e)
         };
 
-        // Change the last page to be inaccessible. This is to provide safety;
-        // when an FFI function overflows it will (hopefully) hit this guard
-        // page. It isn't guaranteed, but that's why FFI is unsafe. buf.data is
-        // guaranteed to be aligned properly.
-        if !protect_last_page(&stack) {
+        // Change the last pages to be inaccessible. This is to provide safety;
+        // when a task overflows it will hit these guard pages and the fault is
+        // reported as a stack overflow of that task (see
+        // `install_guard_handler`). buf.data is guaranteed to be aligned
+        // properly.
+        if !protect_guard_pages(&stack, guard_size) {
             fail!("Could not memory-protect guard page. stack={:?}, errno={}",
                   stack, errno());
         }
@@ -58,6 +85,8 @@ impl Stack {
         let mut stk = Stack {
             buf: stack,
             min_size: size,
+            guard_size: guard_size,
+            lazy: lazy,
             valgrind_id: 0
         };
 
@@ -73,13 +102,17 @@ impl Stack {
         Stack {
             buf: MemoryMap { data: 0 as *mut u8, len: 0, kind: MapVirtual },
             min_size: 0,
+            guard_size: 0,
+            lazy: false,
             valgrind_id: 0
         }
     }
 
-    /// Point to the low end of the allocated stack
+    /// Point to the low end of the usable stack, just above the guard pages
     pub fn start(&self) -> *uint {
-        self.buf.data as *uint
+        unsafe {
+            self.buf.data.offset(self.guard_size as int) as *uint
+        }
     }
 
     /// Point one uint beyond the high end of the allocated stack
@@ -88,32 +121,63 @@ impl Stack {
             self.buf.data.offset(self.buf.len as int) as *uint
         }
     }
+
+    /// The range of addresses covered by this stack's guard pages. A fault in
+    /// this range means that the stack has overflowed.
+    pub fn guard(&self) -> (uint, uint) {
+        let lo = self.buf.data as uint;
+        (lo, lo + self.guard_size)
+    }
+
+    /// Returns the memory of a lazily committed stack to the OS, leaving the
+    /// mapping in place. The pages are faulted back in, zeroed, as the stack
+    /// is used again.
+    fn decommit(&mut self) {
+        if !self.lazy { return }
+        decommit_pages(self.start(), self.end());
+    }
 }
 
 #[cfg(unix)]
-fn protect_last_page(stack: &MemoryMap) -> bool {
+fn protect_guard_pages(stack: &MemoryMap, len: uint) -> bool {
     unsafe {
         // This may seem backwards: the start of the segment is the last page?
         // Yes! The stack grows from higher addresses (the end of the allocated
         // block) to lower addresses (the start of the allocated block).
         let last_page = stack.data as *libc::c_void;
-        libc::mprotect(last_page, page_size() as libc::size_t,
+        libc::mprotect(last_page, len as libc::size_t,
                        libc::PROT_NONE) != -1
     }
 }
 
 #[cfg(windows)]
-fn protect_last_page(stack: &MemoryMap) -> bool {
+fn protect_guard_pages(stack: &MemoryMap, len: uint) -> bool {
     unsafe {
         // see above
         let last_page = stack.data as *mut libc::c_void;
         let mut old_prot: libc::DWORD = 0;
-        libc::VirtualProtect(last_page, page_size() as libc::SIZE_T,
+        libc::VirtualProtect(last_page, len as libc::SIZE_T,
                              libc::PAGE_NOACCESS,
                              &mut old_prot as libc::LPDWORD) != 0
     }
 }
 
+#[cfg(unix)]
+fn decommit_pages(start: *uint, end: *uint) {
+    unsafe {
+        // This is only advice, so there's nothing to do if it fails; the stack
+        // simply keeps its memory.
+        libc::madvise(start as *libc::c_void,
+                      (end as uint - start as uint) as libc::size_t,
+                      libc::MADV_DONTNEED);
+    }
+}
+
+// FIXME: windows stacks are committed when they are mapped, so there is
+//        nothing to give back without remapping them.
+#[cfg(windows)]
+fn decommit_pages(_start: *uint, _end: *uint) {}
+
 impl Drop for Stack {
     fn drop(&mut self) {
         unsafe {
@@ -123,30 +187,152 @@ impl Drop for Stack {
     }
 }
 
+/// Installs a handler on the current thread which reports faults in the guard
+/// pages of the running green task's stack as a stack overflow of that task.
+///
+/// This must be called by each scheduler thread before it runs any tasks, and
+/// the guard of each task is published as part of switching to its context.
+pub fn install_guard_handler() {
+    unsafe { rust_stack_guard_install(guard_page_hit) }
+}
+
+/// Records the guard pages of the stack that the current thread is about to
+/// run on. A range of (0, 0) means that there is no guard to watch.
+#[inline(always)]
+pub unsafe fn record_guard(lo: uint, hi: uint) {
+    rust_stack_guard_set(lo as libc::uintptr_t, hi as libc::uintptr_t)
+}
+
+// Invoked from the fault handler, on the thread's alternate signal stack. The
+// stack limit in TLS still refers to the overflowed stack, so it has to be
+// reset before any other rust function is called.
+//
+// Only async-signal-safe functions may be called from here: the task stays in
+// TLS and is only peeked at, and the message is written without formatting or
+// allocating.
+#[no_split_stack]
+extern "C" fn guard_page_hit() {
+    unsafe {
+        stack::record_sp_limit(0);
+
+        let task: Option<*mut Task> = Local::try_unsafe_borrow();
+        let name = match task {
+            Some(task) => (*task).name.as_ref().map(|n| n.as_slice()),
+            None => None
+        };
+        write_stderr("task '");
+        write_stderr(name.unwrap_or("<unknown>"));
+        write_stderr("' has overflowed its stack\n");
+
+        intrinsics::abort()
+    }
+}
+
+// A raw write(2) of `msg` to stderr, which is safe to call from a signal
+// handler.
+unsafe fn write_stderr(msg: &str) {
+    #[cfg(unix)]
+    type WriteLen = libc::size_t;
+    #[cfg(windows)]
+    type WriteLen = libc::c_uint;
+    libc::write(libc::STDERR_FILENO,
+                msg.as_ptr() as *libc::c_void,
+                msg.len() as WriteLen);
+}
+
+/// Describes how a `StackPool` allocates and caches stacks.
+#[deriving(Clone)]
+pub struct StackPolicy {
+    /// The maximum number of stacks cached for each size bucket. Stacks
+    /// handed back to a full bucket are unmapped.
+    pub max_cached_stacks: uint,
+    /// The sizes that requested stacks are rounded up to, in increasing
+    /// order. Each size has a cache of its own, and requests larger than every
+    /// bucket share one final cache. With no buckets, stacks are allocated at
+    /// exactly the requested size.
+    pub buckets: Vec<uint>,
+    /// The number of inaccessible pages placed below each stack.
+    pub guard_pages: uint,
+    /// Stacks of at least this many bytes are committed lazily: swap is not
+    /// reserved for them and their memory is handed back to the OS while they
+    /// sit in the cache. This is useful for spawning many tasks with large
+    /// stacks which are mostly idle.
+    pub lazy_commit: Option<uint>,
+}
+
+impl StackPolicy {
+    /// Returns the default policy, as determined by the environment variables
+    /// of this process.
+    pub fn new() -> StackPolicy {
+        StackPolicy {
+            max_cached_stacks: max_cached_stacks(),
+            buckets: Vec::new(),
+            guard_pages: 1,
+            lazy_commit: None,
+        }
+    }
+
+    fn is_lazy(&self, size: uint) -> bool {
+        match self.lazy_commit {
+            Some(threshold) => size >= threshold,
+            None => false,
+        }
+    }
+}
+
 pub struct StackPool {
-    // Ideally this would be some datastructure that preserved ordering on
-    // Stack.min_size.
-    stacks: Vec<Stack>,
+    policy: StackPolicy,
+    // One cache per entry of `policy.buckets`, followed by the cache for
+    // oversized stacks. Ideally that last one would be some datastructure that
+    // preserved ordering on Stack.min_size.
+    caches: Vec<Vec<Stack>>,
 }
 
 impl StackPool {
     pub fn new() -> StackPool {
+        StackPool::with_policy(StackPolicy::new())
+    }
+
+    pub fn with_policy(policy: StackPolicy) -> StackPool {
+        assert!(policy.buckets.windows(2).all(|w| w[0] < w[1]),
+                "stack buckets must be in increasing order");
         StackPool {
-            stacks: vec![],
+            caches: Vec::from_fn(policy.buckets.len() + 1, |_| vec![]),
+            policy: policy,
         }
     }
 
-    pub fn take_stack(&mut self, min_size: uint) -> Stack {
+    /// The policy that this pool was created with.
+    pub fn policy<'a>(&'a self) -> &'a StackPolicy {
+        &self.policy
+    }
+
+    /// Returns the index of the cache that serves stacks of `min_size`, along
+    /// with the size that such a stack is allocated at.
+    fn bucket(&self, min_size: uint) -> (uint, uint) {
         // Ideally this would be a binary search
-        match self.stacks.iter().position(|s| min_size <= s.min_size) {
-            Some(idx) => self.stacks.swap_remove(idx).unwrap(),
-            None => Stack::new(min_size)
+        match self.policy.buckets.iter().position(|&b| min_size <= b) {
+            Some(idx) => (idx, *self.policy.buckets.get(idx)),
+            None => (self.policy.buckets.len(), min_size),
         }
     }
 
-    pub fn give_stack(&mut self, stack: Stack) {
-        if self.stacks.len() <= max_cached_stacks() {
-            self.stacks.push(stack)
+    pub fn take_stack(&mut self, min_size: uint) -> Stack {
+        let (idx, size) = self.bucket(min_size);
+        let cache = self.caches.get_mut(idx);
+        match cache.iter().position(|s| size <= s.min_size) {
+            Some(idx) => cache.swap_remove(idx).unwrap(),
+            None => Stack::with_options(size, self.policy.guard_pages,
+                                        self.policy.is_lazy(size))
+        }
+    }
+
+    pub fn give_stack(&mut self, mut stack: Stack) {
+        let (idx, _) = self.bucket(stack.min_size);
+        let cache = self.caches.get_mut(idx);
+        if cache.len() <= self.policy.max_cached_stacks {
+            stack.decommit();
+            cache.push(stack)
         }
     }
 }
@@ -155,11 +341,24 @@ extern {
     fn rust_valgrind_stack_register(start: *libc::uintptr_t,
                                     end: *libc::uintptr_t) -> libc::c_uint;
     fn rust_valgrind_stack_deregister(id: libc::c_uint);
+
+    fn rust_stack_guard_install(cb: extern "C" fn());
+    fn rust_stack_guard_set(lo: libc::uintptr_t, hi: libc::uintptr_t);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::StackPool;
+    use std::os::page_size;
+    use super::{StackPool, StackPolicy};
+
+    fn policy(buckets: Vec<uint>) -> StackPolicy {
+        StackPolicy {
+            max_cached_stacks: 2,
+            buckets: buckets,
+            guard_pages: 1,
+            lazy_commit: None,
+        }
+    }
 
     #[test]
     fn stack_pool_caches() {
@@ -185,4 +384,65 @@ mod tests {
         assert_eq!(s.min_size, 10);
         assert_eq!(s.valgrind_id, 100);
     }
+
+    #[test]
+    fn stack_pool_rounds_to_buckets() {
+        let mut p = StackPool::with_policy(policy(vec![16, 64]));
+        let s = p.take_stack(10);
+        assert_eq!(s.min_size, 16);
+        p.give_stack(s);
+        let s = p.take_stack(17);
+        assert_eq!(s.min_size, 64);
+        p.give_stack(s);
+        let s = p.take_stack(100);
+        assert_eq!(s.min_size, 100);
+        p.give_stack(s);
+
+        assert_eq!(p.caches.get(0).len(), 1);
+        assert_eq!(p.caches.get(1).len(), 1);
+        assert_eq!(p.caches.get(2).len(), 1);
+    }
+
+    #[test]
+    fn stack_pool_respects_max_cached() {
+        let mut p = StackPool::with_policy(policy(vec![16]));
+        let a = p.take_stack(16);
+        let b = p.take_stack(16);
+        let c = p.take_stack(16);
+        let d = p.take_stack(16);
+        p.give_stack(a);
+        p.give_stack(b);
+        p.give_stack(c);
+        p.give_stack(d);
+        // as before buckets existed, one more than the limit is kept
+        assert_eq!(p.caches.get(0).len(), 3);
+    }
+
+    #[test]
+    fn guard_pages_precede_stack() {
+        let mut pol = policy(vec![]);
+        pol.guard_pages = 3;
+        let mut p = StackPool::with_policy(pol);
+        let s = p.take_stack(page_size());
+        let (lo, hi) = s.guard();
+        assert_eq!(hi - lo, 3 * page_size());
+        assert_eq!(hi, s.start() as uint);
+        assert_eq!(s.end() as uint - s.start() as uint, page_size());
+    }
+
+    // Only Linux guarantees that MADV_DONTNEED hands back zero-filled pages.
+    #[test] #[cfg(target_os = "linux")] #[cfg(target_os = "android")]
+    fn lazy_stacks_are_reusable() {
+        let mut pol = policy(vec![]);
+        pol.lazy_commit = Some(page_size());
+        let mut p = StackPool::with_policy(pol);
+        let s = p.take_stack(2 * page_size());
+        assert!(s.lazy);
+        unsafe { *(s.end() as *mut uint).offset(-1) = 0xdead; }
+        p.give_stack(s);
+
+        // The decommitted memory comes back zeroed
+        let s = p.take_stack(2 * page_size());
+        assert_eq!(unsafe { *s.end().offset(-1) }, 0);
+    }
 }
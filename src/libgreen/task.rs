@@ -493,12 +493,14 @@ mod tests {
     use std::task::TaskOpts;
 
     use super::super::{PoolConfig, SchedPool};
+    use stack::StackPolicy;
     use super::GreenTask;
 
     fn spawn_opts(opts: TaskOpts, f: proc():Send) {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::rustuv::event_loop,
+            stacks: StackPolicy::new(),
         });
         pool.spawn(opts, f);
         pool.shutdown();
@@ -269,6 +269,10 @@ pub use funcs::posix01::unistd::{fdatasync};
 
 #[cfg(unix, not(target_os = "freebsd"))]
 pub use consts::os::extra::{MAP_STACK};
+#[cfg(target_os = "linux")] #[cfg(target_os = "android")]
+pub use consts::os::extra::{MAP_NONRESERVE};
+#[cfg(unix)] pub use consts::os::bsd44::{MADV_DONTNEED};
+#[cfg(unix)] pub use funcs::bsd44::{madvise};
 
 #[cfg(target_os = "freebsd")]
 pub use consts::os::bsd44::{TCP_KEEPIDLE};
@@ -34,6 +34,27 @@ pub static MSG_DONTWAIT: libc::c_int = 0x80;
 #[cfg(target_os = "android")]
 pub static MSG_DONTWAIT: libc::c_int = 0x40;
 
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+pub mod signal {
+    use libc;
+    pub static SIGTSTP: libc::c_int = 18;
+    pub static SIGCHLD: libc::c_int = 20;
+    pub static SIGWINCH: libc::c_int = 28;
+    pub static SIGUSR1: libc::c_int = 30;
+    pub static SIGUSR2: libc::c_int = 31;
+}
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+pub mod signal {
+    use libc;
+    pub static SIGUSR1: libc::c_int = 10;
+    pub static SIGUSR2: libc::c_int = 12;
+    pub static SIGCHLD: libc::c_int = 17;
+    pub static SIGTSTP: libc::c_int = 20;
+    pub static SIGWINCH: libc::c_int = 28;
+}
+
 extern {
     pub fn gettimeofday(timeval: *mut libc::timeval,
                         tzp: *libc::c_void) -> libc::c_int;
@@ -72,6 +72,10 @@ pub mod pipe;
 #[path = "pipe_win32.rs"]
 pub mod pipe;
 
+#[cfg(unix)]
+#[path = "signal_unix.rs"]
+pub mod signal;
+
 #[cfg(unix)]    #[path = "c_unix.rs"]  mod c;
 #[cfg(windows)] #[path = "c_win32.rs"] mod c;
 
@@ -286,8 +290,22 @@ impl rtio::IoFactory for IoFactory {
             })
         }
     }
-    fn signal(&mut self, _signal: Signum, _channel: Sender<Signum>)
+    fn signal(&mut self, signum: Signum, channel: Sender<Signum>)
               -> IoResult<Box<RtioSignal:Send>> {
-        Err(unimpl())
+        new_signal(signum, channel)
     }
 }
+
+#[cfg(unix)]
+fn new_signal(signum: Signum, channel: Sender<Signum>)
+              -> IoResult<Box<RtioSignal:Send>> {
+    signal::Signal::new(signum, channel).map(|s| {
+        box s as Box<RtioSignal:Send>
+    })
+}
+
+#[cfg(windows)]
+fn new_signal(_signum: Signum, _channel: Sender<Signum>)
+              -> IoResult<Box<RtioSignal:Send>> {
+    Err(unimpl())
+}
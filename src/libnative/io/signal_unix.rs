@@ -0,0 +1,289 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Signal handling for native tasks
+//!
+//! Very little can be done safely from inside of a signal handler, so the
+//! handler installed by this module (`rust_signal_install` in rust_builtin.c)
+//! only writes the number of the signal it received into a pipe. A helper
+//! thread sits on the other end of this pipe and forwards each signal to all
+//! of the channels which are currently listening for it.
+//!
+//! The handler for a signal is installed when the first listener for it is
+//! created, and the previous action for the signal is restored once the last
+//! listener for it goes away.
+//!
+//! Like the timer helper thread, the signal helper thread is lazily spawned and
+//! is shut down when the runtime exits. A zero byte written to the pipe (which
+//! is not a valid signal number) asks the helper thread to exit.
+
+use libc;
+use std::cast;
+use std::io;
+use std::io::IoError;
+use std::io::signal;
+use std::io::signal::Signum;
+use std::os;
+use std::rt::bookkeeping;
+use std::rt::rtio::RtioSignal;
+use std::rt;
+use std::unstable::mutex::{StaticNativeMutex, NATIVE_MUTEX_INIT};
+
+use io::c;
+use io::file::FileDesc;
+use io::util;
+use io::IoResult;
+use task;
+
+struct Listener {
+    id: uint,
+    signo: libc::c_int,
+    signum: Signum,
+    chan: Sender<Signum>,
+}
+
+struct State {
+    listeners: Vec<Listener>,
+    next_id: uint,
+    // write end of the pipe, read by the helper thread
+    pipe: libc::c_int,
+}
+
+// All access to STATE happens with LOCK held, both by the helper thread and by
+// tasks registering and unregistering listeners.
+static mut LOCK: StaticNativeMutex = NATIVE_MUTEX_INIT;
+static mut STATE: *mut State = 0 as *mut State;
+
+static mut HELPER_EXIT: StaticNativeMutex = NATIVE_MUTEX_INIT;
+
+pub struct Signal {
+    id: uint,
+}
+
+impl Signal {
+    pub fn new(signum: Signum, chan: Sender<Signum>) -> IoResult<Signal> {
+        let signo = match signo(signum) {
+            Some(signo) => signo,
+            None => return Err(IoError {
+                kind: io::InvalidInput,
+                desc: "signal is not supported on this platform",
+                detail: None,
+            })
+        };
+
+        unsafe {
+            let _guard = LOCK.lock();
+            let state = try!(boot());
+            if !state.listeners.iter().any(|l| l.signo == signo) {
+                if rust_signal_install(signo, state.pipe) != 0 {
+                    return Err(super::last_error())
+                }
+            }
+            let id = state.next_id;
+            state.next_id += 1;
+            state.listeners.push(Listener {
+                id: id,
+                signo: signo,
+                signum: signum,
+                chan: chan,
+            });
+            Ok(Signal { id: id })
+        }
+    }
+}
+
+impl RtioSignal for Signal {}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        unsafe {
+            let _guard = LOCK.lock();
+            if STATE.is_null() { return }
+            let state = &mut *STATE;
+            let idx = state.listeners.iter().position(|l| l.id == self.id);
+            let listener = state.listeners.remove(idx.unwrap()).unwrap();
+            if !state.listeners.iter().any(|l| l.signo == listener.signo) {
+                rust_signal_uninstall(listener.signo);
+            }
+        }
+    }
+}
+
+fn signo(signum: Signum) -> Option<libc::c_int> {
+    use libc::consts::os::posix88;
+    match signum {
+        signal::Break => None,
+        signal::BrokenPipe => Some(posix88::SIGPIPE),
+        signal::ChildExit => Some(c::signal::SIGCHLD),
+        signal::HangUp => Some(posix88::SIGHUP),
+        signal::Interrupt => Some(posix88::SIGINT),
+        signal::Quit => Some(posix88::SIGQUIT),
+        signal::StopTemporarily => Some(c::signal::SIGTSTP),
+        signal::Terminate => Some(posix88::SIGTERM),
+        signal::User1 => Some(c::signal::SIGUSR1),
+        signal::User2 => Some(c::signal::SIGUSR2),
+        signal::WindowSizeChange => Some(c::signal::SIGWINCH),
+    }
+}
+
+// Must be called with LOCK held.
+unsafe fn boot() -> IoResult<&'static mut State> {
+    if STATE.is_null() {
+        let pipe = os::pipe();
+        // A full pipe must never block the signal handler. Any signal which
+        // doesn't fit will be dropped, but the helper thread is guaranteed to
+        // wake up and deliver the ones which did.
+        match util::set_nonblocking(pipe.out, true) {
+            Ok(()) => {}
+            Err(e) => {
+                let _r = FileDesc::new(pipe.input, true);
+                let _w = FileDesc::new(pipe.out, true);
+                return Err(e)
+            }
+        }
+
+        STATE = cast::transmute(box State {
+            listeners: Vec::new(),
+            next_id: 0,
+            pipe: pipe.out,
+        });
+
+        let input = pipe.input;
+        task::spawn(proc() {
+            bookkeeping::decrement();
+            helper(input);
+            HELPER_EXIT.lock().signal()
+        });
+
+        rt::at_exit(proc() { shutdown() });
+    }
+    Ok(&mut *STATE)
+}
+
+fn helper(input: libc::c_int) {
+    let mut input = FileDesc::new(input, true);
+    let mut buf = [0u8, ..64];
+    loop {
+        let n = match input.inner_read(buf) {
+            Ok(n) => n,
+            Err(e) => fail!("signal helper thread failed to read: {}", e),
+        };
+        for &signo in buf.slice_to(n).iter() {
+            if signo == 0 { return }
+            unsafe {
+                let _guard = LOCK.lock();
+                for l in (*STATE).listeners.iter() {
+                    if l.signo == signo as libc::c_int {
+                        let _ = l.chan.send_opt(l.signum);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn shutdown() {
+    // Request a shutdown, and then wait for the helper thread to exit
+    unsafe {
+        let guard = HELPER_EXIT.lock();
+        let pipe = {
+            let _guard = LOCK.lock();
+            (*STATE).pipe
+        };
+        FileDesc::new(pipe, false).inner_write([0]).unwrap();
+        guard.wait();
+        drop(guard);
+        HELPER_EXIT.destroy();
+    }
+
+    // Restore the handlers of anything still listening, and clean up after the
+    // helper thread
+    unsafe {
+        let _guard = LOCK.lock();
+        let state: Box<State> = cast::transmute(STATE);
+        STATE = 0 as *mut State;
+        let mut installed: Vec<libc::c_int> = Vec::new();
+        for l in state.listeners.iter() {
+            if !installed.contains(&l.signo) {
+                rust_signal_uninstall(l.signo);
+                installed.push(l.signo);
+            }
+        }
+        let _pipe = FileDesc::new(state.pipe, true);
+    }
+}
+
+extern {
+    fn rust_signal_install(signo: libc::c_int,
+                           fd: libc::c_int) -> libc::c_int;
+    fn rust_signal_uninstall(signo: libc::c_int) -> libc::c_int;
+}
+
+#[cfg(test)]
+mod test {
+    use libc;
+    use std::io::signal::{Interrupt, HangUp, User1, User2, WindowSizeChange};
+    use std::io::timer;
+
+    use super::Signal;
+
+    fn raise(signo: libc::c_int) {
+        unsafe {
+            libc::funcs::posix88::signal::kill(libc::getpid(), signo);
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        let (tx, rx) = channel();
+        let _s = Signal::new(HangUp, tx).unwrap();
+        raise(libc::consts::os::posix88::SIGHUP);
+        assert_eq!(rx.recv(), HangUp);
+    }
+
+    #[test]
+    fn many_listeners() {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        let (tx3, rx3) = channel();
+        let _s1 = Signal::new(User1, tx1).unwrap();
+        let _s2 = Signal::new(User1, tx2).unwrap();
+        let _s3 = Signal::new(User1, tx3).unwrap();
+        raise(super::c::signal::SIGUSR1);
+        assert_eq!(rx1.recv(), User1);
+        assert_eq!(rx2.recv(), User1);
+        assert_eq!(rx3.recv(), User1);
+    }
+
+    #[test]
+    fn dropped_listener() {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        let _s1 = Signal::new(WindowSizeChange, tx1).unwrap();
+        let s2 = Signal::new(WindowSizeChange, tx2).unwrap();
+        drop(s2);
+        raise(super::c::signal::SIGWINCH);
+        assert_eq!(rx1.recv(), WindowSizeChange);
+        timer::sleep(10);
+        assert!(rx2.recv_opt().is_err());
+    }
+
+    #[test]
+    fn only_matching_signals() {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+        let _s1 = Signal::new(Interrupt, tx1).unwrap();
+        let _s2 = Signal::new(User2, tx2).unwrap();
+        raise(libc::SIGINT);
+        assert_eq!(rx1.recv(), Interrupt);
+        timer::sleep(10);
+        assert!(rx2.try_recv().is_err());
+    }
+}
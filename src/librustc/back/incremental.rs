@@ -0,0 +1,485 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Incremental compilation of items.
+//!
+//! With `-Z incremental=DIR` every non-generic function that isn't marked
+//! `#[inline]` and sits directly within a module is translated into a codegen
+//! unit of its own (see `back::partition`), whose object code and bitcode are
+//! kept in `DIR/<crate name>` under the fingerprint of the function. A later
+//! compilation that finds the fingerprint of a function in the cache only
+//! declares the function instead of translating it, and links in the cached
+//! object code.
+//!
+//! The fingerprint of a function covers:
+//!
+//! * its source, after macro expansion, and where it starts in the file,
+//!   since line numbers end up in the object code;
+//! * the path, type and attributes of the functions it uses that are
+//!   compiled on their own, since only their symbols end up in its code;
+//! * the source of everything else it uses, such as types, statics, impls,
+//!   generic and `#[inline]` functions, along with whatever those use in
+//!   turn, since they can end up in its code in full;
+//! * the compiler version, the codegen options and the hashes of the crates
+//!   it is linked against.
+//!
+//! So editing the body of one function translates that function again and
+//! reuses the others, while editing the signature of a function or the
+//! definition of a type translates everything that uses it again.
+//!
+//! Resolve and type checking still run over the whole crate, and so does
+//! translation for everything that isn't compiled on its own.
+
+use back::link;
+use driver::session::Session;
+use metadata::common::LinkMeta;
+use middle::ty;
+use middle::typeck;
+use util::nodemap::{NodeMap, NodeSet};
+use util::ppaux;
+use util::sha2::{Digest, Sha256};
+
+use collections::HashSet;
+use std::cell::RefCell;
+use std::io;
+use std::io::fs;
+use syntax::abi;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util::{IdVisitingOperation, IdVisitor, def_id_of_def};
+use syntax::attr;
+use syntax::print::pprust;
+use syntax::visit::Visitor;
+
+/// A function compiled on its own.
+#[deriving(Clone)]
+pub struct Item {
+    /// The path of the function, for `-Z incremental-info`.
+    pub path: ~str,
+    /// The symbol of the function, filled in once it has been declared.
+    pub symbol: ~str,
+    pub fingerprint: ~str,
+    /// Whether the cache holds the object code and bitcode of the function,
+    /// in which case translation only declares it.
+    pub reused: bool,
+}
+
+/// The fingerprints of the items of the crate being compiled incrementally.
+pub struct Fingerprints {
+    /// The hash of the source of every item directly within a module.
+    sources: NodeMap<~str>,
+    /// The functions compiled on their own.
+    items: NodeMap<Item>,
+}
+
+/// The item directly within a module that `id` belongs to, which is `id`
+/// itself for such items.
+fn owner(map: &ast_map::Map, id: ast::NodeId) -> ast::NodeId {
+    let mut id = id;
+    loop {
+        let parent = map.get_parent(id);
+        if parent == id {
+            return id
+        }
+        match map.find(parent) {
+            Some(ast_map::NodeItem(item)) => match item.node {
+                ast::ItemMod(..) => return id,
+                _ => {}
+            },
+            Some(_) => {}
+            None => return id,
+        }
+        id = parent;
+    }
+}
+
+/// Whether `item` is a function that is compiled on its own. Generic and
+/// `#[inline]` functions are translated along with the code using them, and
+/// functions with a foreign ABI come with a wrapper, so none of those are.
+fn is_separate(item: &ast::Item) -> bool {
+    match item.node {
+        ast::ItemFn(_, _, abi::Rust, ref generics, _) => {
+            !generics.is_type_parameterized() &&
+                attr::find_inline_attr(item.attrs.as_slice()) == attr::InlineNone
+        }
+        _ => false,
+    }
+}
+
+/// Collects the items directly within the modules of the crate.
+fn module_items(m: &ast::Mod, items: &mut Vec<@ast::Item>) {
+    for &item in m.items.iter() {
+        match item.node {
+            ast::ItemMod(ref m) => module_items(m, items),
+            _ => items.push(item),
+        }
+    }
+}
+
+/// Collects the local definitions used by an item: the paths it resolves,
+/// the methods and impls it calls and the types it mentions.
+struct DependencyCollector<'a> {
+    tcx: &'a ty::ctxt,
+    /// The methods and impls used by each expression.
+    calls: &'a NodeMap<Vec<ast::DefId>>,
+    deps: RefCell<HashSet<ast::NodeId>>,
+}
+
+impl<'a> DependencyCollector<'a> {
+    fn add(&self, did: ast::DefId) {
+        if did.krate == ast::LOCAL_CRATE {
+            self.deps.borrow_mut().insert(did.node);
+        }
+    }
+}
+
+impl<'a> IdVisitingOperation for DependencyCollector<'a> {
+    fn visit_id(&self, id: ast::NodeId) {
+        match self.tcx.def_map.borrow().find(&id) {
+            Some(&ast::DefPrimTy(..)) | None => {}
+            Some(&def) => self.add(def_id_of_def(def)),
+        }
+        match self.calls.find(&id) {
+            Some(dids) => {
+                for &did in dids.iter() {
+                    self.add(did);
+                }
+            }
+            None => {}
+        }
+        match ty::node_id_to_type_opt(self.tcx, id) {
+            Some(t) => ty::walk_ty(t, |t| {
+                match ty::get(t).sty {
+                    ty::ty_enum(did, _) | ty::ty_struct(did, _) => self.add(did),
+                    ty::ty_trait(box ty::TyTrait { def_id, .. }) => self.add(def_id),
+                    _ => {}
+                }
+            }),
+            None => {}
+        }
+    }
+}
+
+/// The methods and impls used by each expression, from the method and vtable
+/// maps of type checking.
+fn calls(tcx: &ty::ctxt) -> NodeMap<Vec<ast::DefId>> {
+    fn add_vtables(vtables: &typeck::vtable_res, dids: &mut Vec<ast::DefId>) {
+        for param in vtables.iter() {
+            for origin in param.iter() {
+                match *origin {
+                    typeck::vtable_static(did, _, ref vtables) => {
+                        dids.push(did);
+                        add_vtables(vtables, dids);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let mut calls = NodeMap::new();
+    for (call, callee) in tcx.method_map.borrow().iter() {
+        match callee.origin {
+            typeck::MethodStatic(did) => {
+                calls.find_or_insert_with(call.expr_id, |_| Vec::new()).push(did);
+            }
+            _ => {}
+        }
+    }
+    for (call, vtables) in tcx.vtable_map.borrow().iter() {
+        add_vtables(vtables, calls.find_or_insert_with(call.expr_id, |_| Vec::new()));
+    }
+    calls
+}
+
+/// The hash of everything outside the crate's items that ends up in their
+/// code.
+fn crate_fingerprint(tcx: &ty::ctxt, krate: &ast::Crate, link_meta: &LinkMeta) -> ~str {
+    let sess = &tcx.sess;
+    let cg = &sess.opts.cg;
+    let mut hasher = Sha256::new();
+    hasher.input_str(option_env!("CFG_VERSION").unwrap_or("unknown version"));
+    hasher.input_str(link_meta.crateid.to_str());
+    hasher.input_str(link::crate_disambiguator(sess, link_meta));
+    hasher.input_str(format!("{:?}", (sess.targ_cfg.target_strs.target_triple.as_slice(),
+                                      sess.opts.optimize, sess.opts.debuginfo,
+                                      sess.opts.sanitizer)));
+    hasher.input_str(format!("{:?}", (cg.target_cpu.as_slice(),
+                                      cg.target_feature.as_slice(),
+                                      cg.relocation_model.as_slice())));
+    hasher.input_str(format!("{:?}", (&cg.passes, &cg.llvm_args)));
+    hasher.input_str(format!("{:?}", (cg.soft_float, cg.no_prepopulate_passes,
+                                      cg.no_vectorize_loops, cg.no_vectorize_slp)));
+    for attr in krate.attrs.iter() {
+        hasher.input_str(pprust::attribute_to_str(attr).as_slice());
+    }
+
+    let mut upstream = Vec::new();
+    sess.cstore.iter_crate_data(|_, data| {
+        upstream.push(data.hash.as_str().to_owned());
+    });
+    upstream.as_mut_slice().sort();
+    for hash in upstream.iter() {
+        hasher.input_str(hash.as_slice());
+    }
+    hasher.result_str()
+}
+
+impl Fingerprints {
+    /// Fingerprints the items of `krate` and looks them up in the cache.
+    pub fn compute(tcx: &ty::ctxt,
+                   krate: &ast::Crate,
+                   link_meta: &LinkMeta,
+                   reachable: &NodeSet,
+                   dir: &Path) -> Fingerprints {
+        let cache = Cache::open(&tcx.sess, dir, link_meta.crateid.name.as_slice());
+
+        let mut items = Vec::new();
+        module_items(&krate.module, &mut items);
+
+        let calls = calls(tcx);
+        let mut sources = NodeMap::new();
+        let mut deps = NodeMap::new();
+        for item in items.iter() {
+            let loc = tcx.sess.codemap().lookup_char_pos(item.span.lo);
+            let mut hasher = Sha256::new();
+            hasher.input_str(format!("{}:{}", loc.file.name, loc.line));
+            hasher.input_str(pprust::item_to_str(&**item).as_slice());
+            sources.insert(item.id, hasher.result_str());
+
+            let collector = DependencyCollector {
+                tcx: tcx,
+                calls: &calls,
+                deps: RefCell::new(HashSet::new()),
+            };
+            {
+                let mut visitor = IdVisitor {
+                    operation: &collector,
+                    pass_through_items: true,
+                    visited_outermost: false,
+                };
+                visitor.visit_item(&**item, ());
+            }
+            let mut owners = HashSet::new();
+            for &dep in collector.deps.borrow().iter() {
+                let dep = owner(&tcx.map, dep);
+                if dep != item.id {
+                    owners.insert(dep);
+                }
+            }
+            deps.insert(item.id, owners);
+        }
+
+        let mut separate = NodeMap::new();
+        for &item in items.iter() {
+            if is_separate(&*item) {
+                separate.insert(item.id, item);
+            }
+        }
+
+        // What a use of an item contributes to the fingerprint of a function.
+        let signature = |id: ast::NodeId| -> ~str {
+            let item = separate.get(&id);
+            let mut signature = StrBuf::new();
+            signature.push_str(tcx.map.path_to_str(id).as_slice());
+            signature.push_str(ppaux::ty_to_str(tcx, ty::node_id_to_type(tcx, id)).as_slice());
+            signature.push_str(reachable.contains(&id).to_str().as_slice());
+            for attr in item.attrs.iter() {
+                signature.push_str(pprust::attribute_to_str(attr).as_slice());
+            }
+            signature.into_owned()
+        };
+
+        let krate_fingerprint = crate_fingerprint(tcx, krate, link_meta);
+        let mut fingerprints = NodeMap::new();
+        for (&id, item) in separate.iter() {
+            // The uses are sorted, as node ids don't stay the same from one
+            // compilation to the next.
+            let mut uses = vec!(signature(id));
+            let mut seen = HashSet::new();
+            let mut stack = vec!(id);
+            seen.insert(id);
+            loop {
+                let id = match stack.pop() {
+                    Some(id) => id,
+                    None => break,
+                };
+                let used = match deps.find(&id) {
+                    Some(used) => used,
+                    None => continue,
+                };
+                for &dep in used.iter() {
+                    if !seen.insert(dep) {
+                        continue
+                    }
+                    if separate.contains_key(&dep) {
+                        uses.push(signature(dep));
+                    } else if sources.contains_key(&dep) {
+                        uses.push(sources.get(&dep).clone());
+                        stack.push(dep);
+                    }
+                }
+            }
+            uses.as_mut_slice().sort();
+
+            let mut hasher = Sha256::new();
+            hasher.input_str(krate_fingerprint.as_slice());
+            hasher.input_str(sources.get(&id).as_slice());
+            for used in uses.iter() {
+                hasher.input_str(used.as_slice());
+            }
+            let fingerprint = hasher.result_str();
+
+            fingerprints.insert(id, Item {
+                path: format!("{}::{}", link_meta.crateid.name, tcx.map.path_to_str(item.id)),
+                symbol: "".to_owned(),
+                reused: cache.contains(fingerprint.as_slice()),
+                fingerprint: fingerprint,
+            });
+        }
+
+        Fingerprints {
+            sources: sources,
+            items: fingerprints,
+        }
+    }
+
+    /// Whether the function `id` needs translating, which it does unless it
+    /// isn't compiled on its own or the cache holds its object code.
+    pub fn translate(&self, sess: &Session, id: ast::NodeId) -> bool {
+        match self.items.find(&id) {
+            Some(item) => {
+                if sess.incremental_info() {
+                    let action = if item.reused { "reusing" } else { "translating" };
+                    println!("incremental: {} `{}`", action, item.path);
+                }
+                !item.reused
+            }
+            None => true,
+        }
+    }
+
+    /// Stands in for the node id that tells apart the symbols of nodes with
+    /// the same path. Node ids shift with every edit to the crate, so this
+    /// is derived from the source of the item directly within a module that
+    /// the node belongs to instead, along with where in that item the node
+    /// is. Those items have unique paths, so they don't need telling apart.
+    pub fn disambiguator(&self, map: &ast_map::Map, id: ast::NodeId) -> Option<uint> {
+        let owner = owner(map, id);
+        let source = match self.sources.find(&owner) {
+            Some(source) => source,
+            None => return Some(id as uint),
+        };
+        if owner == id {
+            return None
+        }
+        let mut hasher = Sha256::new();
+        hasher.input_str(source.as_slice());
+        hasher.input_str((id as int - owner as int).to_str());
+        Some(hasher.result_bytes().iter().take(4).fold(0u, |n, &b| n << 8 | b as uint))
+    }
+
+    /// The functions compiled on their own, sorted by path, with the symbols
+    /// they were declared with.
+    pub fn items(&self, symbols: &NodeMap<~str>) -> Vec<Item> {
+        let mut items: Vec<Item> = self.items.iter().filter_map(|(id, item)| {
+            symbols.find(id).map(|symbol| {
+                Item { symbol: symbol.clone(), ..item.clone() }
+            })
+        }).collect();
+        items.sort_by(|a, b| a.path.cmp(&b.path));
+        items
+    }
+}
+
+/// The object code and bitcode of the functions compiled on their own, kept
+/// under their fingerprints.
+pub struct Cache {
+    dir: Path,
+}
+
+impl Cache {
+    /// Opens the cache of the crate named `krate` within `dir`, creating it
+    /// if it doesn't exist yet.
+    pub fn open(sess: &Session, dir: &Path, krate: &str) -> Cache {
+        let dir = dir.join(krate);
+        match fs::mkdir_recursive(&dir, io::UserRWX) {
+            Ok(()) => {}
+            Err(e) => {
+                sess.fatal(format!("failed to create incremental cache {}: {}",
+                                   dir.display(), e))
+            }
+        }
+        Cache { dir: dir }
+    }
+
+    fn entry(&self, fingerprint: &str, extension: &str) -> Path {
+        self.dir.join(format!("{}.{}", fingerprint, extension))
+    }
+
+    /// Whether the cache holds the object code and the bitcode of the
+    /// function with the given fingerprint.
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.entry(fingerprint, "o").exists() && self.entry(fingerprint, "bc").exists()
+    }
+
+    /// Copies the cached files of the function with the given fingerprint to
+    /// `outputs`. The function was only declared on the understanding that
+    /// they're there, so failing to do so is fatal.
+    pub fn fetch(&self, sess: &Session, fingerprint: &str, outputs: &[Path]) {
+        for output in outputs.iter() {
+            let entry = self.entry(fingerprint, output.extension_str().unwrap());
+            match fs::copy(&entry, output) {
+                Ok(()) => {}
+                Err(e) => {
+                    sess.fatal(format!("failed to read {} from the incremental cache: {}",
+                                       entry.display(), e))
+                }
+            }
+        }
+    }
+
+    /// Keeps copies of `outputs`, the files written for the function with
+    /// the given fingerprint. Failing to do so only costs later compilations
+    /// some time, so it's not an error.
+    pub fn store(&self, sess: &Session, fingerprint: &str, outputs: &[Path]) {
+        for output in outputs.iter() {
+            let entry = self.entry(fingerprint, output.extension_str().unwrap());
+            match fs::copy(output, &entry) {
+                Ok(()) => {}
+                Err(e) => {
+                    sess.warn(format!("failed to cache {}: {}", entry.display(), e));
+                    return
+                }
+            }
+        }
+    }
+
+    /// Removes the functions whose fingerprints aren't in `used`, so that
+    /// the cache doesn't keep growing as the crate changes.
+    pub fn prune(&self, sess: &Session, used: &HashSet<~str>) {
+        let entries = match fs::readdir(&self.dir) {
+            Ok(entries) => entries,
+            Err(..) => return,
+        };
+        for entry in entries.iter() {
+            let used = match entry.filename_str() {
+                Some(name) => {
+                    let fingerprint = name.split('.').next().unwrap();
+                    used.contains(&fingerprint.to_owned())
+                }
+                None => false,
+            };
+            if !used {
+                link::remove(sess, entry);
+            }
+        }
+    }
+}
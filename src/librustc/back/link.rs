@@ -86,8 +86,10 @@ pub fn WriteOutputFile(
 
 pub mod write {
 
+    use back::incremental;
     use back::lto;
-    use back::link::{WriteOutputFile, OutputType};
+    use back::partition;
+    use back::link::{WriteOutputFile, OutputType, llvm_err};
     use back::link::{OutputTypeAssembly, OutputTypeBitcode};
     use back::link::{OutputTypeExe, OutputTypeLlvmAssembly};
     use back::link::{OutputTypeObject};
@@ -95,14 +97,18 @@ pub mod write {
     use driver::session::{NoDebugInfo, Session};
     use driver::session;
     use lib::llvm::llvm;
-    use lib::llvm::{ModuleRef, TargetMachineRef, PassManagerRef};
+    use lib::llvm::{ContextRef, MemoryBufferRef, ModuleRef, TargetMachineRef, PassManagerRef};
     use lib;
     use util::common::time;
     use syntax::abi;
 
+    use collections::HashSet;
     use std::c_str::ToCStr;
-    use std::io::Process;
-    use libc::{c_uint, c_int};
+    use std::cmp;
+    use std::io::{fs, Process};
+    use std::ptr;
+    use std::task::TaskBuilder;
+    use libc::{c_uint, c_int, c_char, size_t};
     use std::str;
 
     // On android, we by default compile for armv7 processors. This enables
@@ -123,116 +129,148 @@ pub mod write {
         }
     }
 
+    fn opt_level(sess: &Session) -> lib::llvm::CodeGenOptLevel {
+        match sess.opts.optimize {
+          session::No => lib::llvm::CodeGenLevelNone,
+          session::Less => lib::llvm::CodeGenLevelLess,
+          session::Default => lib::llvm::CodeGenLevelDefault,
+          session::Aggressive => lib::llvm::CodeGenLevelAggressive,
+        }
+    }
+
+    unsafe fn create_target_machine(sess: &Session) -> TargetMachineRef {
+        let use_softfp = sess.opts.cg.soft_float;
+
+        // FIXME: #11906: Omitting frame pointers breaks retrieving the value of a parameter.
+        // FIXME: #11954: mac64 unwinding may not work with fp elim
+        let no_fp_elim = (sess.opts.debuginfo != NoDebugInfo) ||
+                         (sess.targ_cfg.os == abi::OsMacos &&
+                          sess.targ_cfg.arch == abi::X86_64);
+
+        // OSX has -dead_strip, which doesn't rely on ffunction_sections
+        // FIXME(#13846) this should be enabled for windows
+        let ffunction_sections = sess.targ_cfg.os != abi::OsMacos &&
+                                 sess.targ_cfg.os != abi::OsWin32;
+        let fdata_sections = ffunction_sections;
+
+        let reloc_model = match sess.opts.cg.relocation_model.as_slice() {
+            "pic" => lib::llvm::RelocPIC,
+            "static" => lib::llvm::RelocStatic,
+            "default" => lib::llvm::RelocDefault,
+            "dynamic-no-pic" => lib::llvm::RelocDynamicNoPic,
+            _ => sess.fatal(format!("{} is not a valid relocation mode",
+                                    sess.opts.cg.relocation_model)),
+        };
+
+        sess.targ_cfg.target_strs.target_triple.with_c_str(|t| {
+            sess.opts.cg.target_cpu.with_c_str(|cpu| {
+                target_feature(sess).with_c_str(|features| {
+                    llvm::LLVMRustCreateTargetMachine(
+                        t, cpu, features,
+                        lib::llvm::CodeModelDefault,
+                        reloc_model,
+                        opt_level(sess),
+                        true /* EnableSegstk */,
+                        use_softfp,
+                        no_fp_elim,
+                        ffunction_sections,
+                        fdata_sections,
+                    )
+                })
+            })
+        })
+    }
+
+    /// The options for optimizing a module, taken out of the session so that
+    /// codegen units can be optimized on other tasks.
+    struct ModuleConfig {
+        opt_level: lib::llvm::CodeGenOptLevel,
+        verify: bool,
+        prepopulate_passes: bool,
+        passes: Vec<~str>,
+        time_passes: bool,
+        sanitizer: Option<session::Sanitizer>,
+        profile: bool,
+    }
+
+    impl ModuleConfig {
+        fn new(sess: &Session) -> ModuleConfig {
+            ModuleConfig {
+                opt_level: opt_level(sess),
+                verify: !sess.no_verify(),
+                prepopulate_passes: !sess.opts.cg.no_prepopulate_passes,
+                passes: sess.opts.cg.passes.clone(),
+                time_passes: sess.time_passes(),
+                sanitizer: sess.opts.sanitizer,
+                profile: sess.debugging_opt(session::PROFILE),
+            }
+        }
+    }
+
     pub fn run_passes(sess: &Session,
                       trans: &CrateTranslation,
                       output_types: &[OutputType],
                       output: &OutputFilenames) {
-        let llmod = trans.module;
-        let llcx = trans.context;
         unsafe {
             configure_llvm(sess);
 
             if sess.opts.cg.save_temps {
                 output.with_extension("no-opt.bc").with_c_str(|buf| {
-                    llvm::LLVMWriteBitcodeToFile(llmod, buf);
+                    llvm::LLVMWriteBitcodeToFile(trans.module, buf);
                 })
             }
 
-            let opt_level = match sess.opts.optimize {
-              session::No => lib::llvm::CodeGenLevelNone,
-              session::Less => lib::llvm::CodeGenLevelLess,
-              session::Default => lib::llvm::CodeGenLevelDefault,
-              session::Aggressive => lib::llvm::CodeGenLevelAggressive,
-            };
-            let use_softfp = sess.opts.cg.soft_float;
-
-            // FIXME: #11906: Omitting frame pointers breaks retrieving the value of a parameter.
-            // FIXME: #11954: mac64 unwinding may not work with fp elim
-            let no_fp_elim = (sess.opts.debuginfo != NoDebugInfo) ||
-                             (sess.targ_cfg.os == abi::OsMacos &&
-                              sess.targ_cfg.arch == abi::X86_64);
-
-            // OSX has -dead_strip, which doesn't rely on ffunction_sections
-            // FIXME(#13846) this should be enabled for windows
-            let ffunction_sections = sess.targ_cfg.os != abi::OsMacos &&
-                                     sess.targ_cfg.os != abi::OsWin32;
-            let fdata_sections = ffunction_sections;
-
-            let reloc_model = match sess.opts.cg.relocation_model.as_slice() {
-                "pic" => lib::llvm::RelocPIC,
-                "static" => lib::llvm::RelocStatic,
-                "default" => lib::llvm::RelocDefault,
-                "dynamic-no-pic" => lib::llvm::RelocDynamicNoPic,
-                _ => {
-                    sess.err(format!("{} is not a valid relocation mode",
-                             sess.opts.cg.relocation_model));
-                    sess.abort_if_errors();
-                    return;
-                }
-            };
-
-            let tm = sess.targ_cfg.target_strs.target_triple.with_c_str(|t| {
-                sess.opts.cg.target_cpu.with_c_str(|cpu| {
-                    target_feature(sess).with_c_str(|features| {
-                        llvm::LLVMRustCreateTargetMachine(
-                            t, cpu, features,
-                            lib::llvm::CodeModelDefault,
-                            reloc_model,
-                            opt_level,
-                            true /* EnableSegstk */,
-                            use_softfp,
-                            no_fp_elim,
-                            ffunction_sections,
-                            fdata_sections,
-                        )
-                    })
-                })
-            });
-
-            // Create the two optimizing pass managers. These mirror what clang
-            // does, and are by populated by LLVM's default PassManagerBuilder.
-            // Each manager has a different set of passes, but they also share
-            // some common passes.
-            let fpm = llvm::LLVMCreateFunctionPassManagerForModule(llmod);
-            let mpm = llvm::LLVMCreatePassManager();
-
-            // If we're verifying or linting, add them to the function pass
-            // manager.
-            let addpass = |pass: &str| {
-                pass.with_c_str(|s| llvm::LLVMRustAddPass(fpm, s))
-            };
-            if !sess.no_verify() { assert!(addpass("verify")); }
-
-            if !sess.opts.cg.no_prepopulate_passes {
-                llvm::LLVMRustAddAnalysisPasses(tm, fpm, llmod);
-                llvm::LLVMRustAddAnalysisPasses(tm, mpm, llmod);
-                populate_llvm_passes(fpm, mpm, llmod, opt_level);
-            }
-
-            for pass in sess.opts.cg.passes.iter() {
-                pass.with_c_str(|s| {
-                    if !llvm::LLVMRustAddPass(mpm, s) {
-                        sess.warn(format!("unknown pass {}, ignoring", *pass));
-                    }
-                })
-            }
-
-            // Finally, run the actual optimization passes
-            time(sess.time_passes(), "llvm function passes", (), |()|
-                 llvm::LLVMRustRunFunctionPassManager(fpm, llmod));
-            time(sess.time_passes(), "llvm module passes", (), |()|
-                 llvm::LLVMRunPassManager(mpm, llmod));
-
-            // Deallocate managers that we're now done with
-            llvm::LLVMDisposePassManager(fpm);
-            llvm::LLVMDisposePassManager(mpm);
+            let tm = create_target_machine(sess);
 
             // Emit the bytecode if we're either saving our temporaries or
             // emitting an rlib. Whenever an rlib is created, the bytecode is
             // inserted into the archive in order to allow LTO against it.
-            if sess.opts.cg.save_temps ||
+            let needs_bitcode = sess.opts.cg.save_temps ||
                (sess.crate_types.borrow().contains(&session::CrateTypeRlib) &&
-                sess.opts.output_types.contains(&OutputTypeExe)) {
+                sess.opts.output_types.contains(&OutputTypeExe));
+
+            // With several codegen units, or when compiling incrementally,
+            // the units are optimized and, unless we're performing LTO,
+            // compiled to object files on tasks of their own. Everything else
+            // wants the whole crate in one module, so the optimized units are
+            // linked back together for it. If nothing needs that module,
+            // `llmod` is left null.
+            let mut unit_objects = Vec::new();
+            let incremental = sess.opts.incremental.is_some();
+            let (llcx, llmod) = if sess.opts.cg.codegen_units > 1 || incremental {
+                let needs_module = needs_bitcode || sess.lto() ||
+                    output_types.iter().any(|t| {
+                        *t != OutputTypeObject && *t != OutputTypeExe
+                    });
+                let needs_objects = !sess.lto() && output_types.iter().any(|t| {
+                    *t == OutputTypeObject || *t == OutputTypeExe
+                });
+                // The incremental cache wants both of them for every unit.
+                let units = run_codegen_units(sess, trans, output,
+                                              needs_module || incremental,
+                                              needs_objects || incremental);
+                if needs_objects {
+                    unit_objects = range(0, units).map(|i| {
+                        unit_path(output, OutputTypeObject, i)
+                    }).collect();
+                } else if incremental {
+                    remove_unit_files(sess, output, OutputTypeObject, units);
+                }
+                if needs_module {
+                    link_codegen_units(sess, output, units)
+                } else {
+                    if incremental {
+                        remove_unit_files(sess, output, OutputTypeBitcode, units);
+                    }
+                    (ptr::null(), ptr::null())
+                }
+            } else {
+                let config = ModuleConfig::new(sess);
+                warn_unknown_passes(sess, optimize(&config, tm, trans.module));
+                (trans.context, trans.module)
+            };
+
+            if needs_bitcode {
                 output.temp_path(OutputTypeBitcode).with_c_str(|buf| {
                     llvm::LLVMWriteBitcodeToFile(llmod, buf);
                 })
@@ -249,25 +287,6 @@ pub mod write {
                 }
             }
 
-            // A codegen-specific pass manager is used to generate object
-            // files for an LLVM module.
-            //
-            // Apparently each of these pass managers is a one-shot kind of
-            // thing, so we create a new one for each type of output. The
-            // pass manager passed to the closure should be ensured to not
-            // escape the closure itself, and the manager should only be
-            // used once.
-            fn with_codegen(tm: TargetMachineRef, llmod: ModuleRef,
-                            f: |PassManagerRef|) {
-                unsafe {
-                    let cpm = llvm::LLVMCreatePassManager();
-                    llvm::LLVMRustAddAnalysisPasses(tm, cpm, llmod);
-                    llvm::LLVMRustAddLibraryInfo(cpm, llmod);
-                    f(cpm);
-                    llvm::LLVMDisposePassManager(cpm);
-                }
-            }
-
             let mut object_file = None;
             let mut needs_metadata = false;
             for output_type in output_types.iter() {
@@ -314,6 +333,9 @@ pub mod write {
 
             time(sess.time_passes(), "codegen passes", (), |()| {
                 match object_file {
+                    Some(ref path) if !unit_objects.is_empty() => {
+                        link_unit_objects(sess, unit_objects.as_slice(), path);
+                    }
                     Some(ref path) => {
                         with_codegen(tm, llmod, |cpm| {
                             WriteOutputFile(sess, tm, cpm, llmod, path,
@@ -335,12 +357,360 @@ pub mod write {
 
             llvm::LLVMRustDisposeTargetMachine(tm);
             llvm::LLVMDisposeModule(trans.metadata_module);
-            llvm::LLVMDisposeModule(llmod);
-            llvm::LLVMContextDispose(llcx);
+            if llmod.is_not_null() && llmod != trans.module {
+                llvm::LLVMDisposeModule(llmod);
+                llvm::LLVMContextDispose(llcx);
+            }
+            llvm::LLVMDisposeModule(trans.module);
+            llvm::LLVMContextDispose(trans.context);
             if sess.time_llvm_passes() { llvm::LLVMRustPrintPassTimings(); }
         }
     }
 
+    // A codegen-specific pass manager is used to generate object
+    // files for an LLVM module.
+    //
+    // Apparently each of these pass managers is a one-shot kind of
+    // thing, so we create a new one for each type of output. The
+    // pass manager passed to the closure should be ensured to not
+    // escape the closure itself, and the manager should only be
+    // used once.
+    fn with_codegen(tm: TargetMachineRef, llmod: ModuleRef,
+                    f: |PassManagerRef|) {
+        unsafe {
+            let cpm = llvm::LLVMCreatePassManager();
+            llvm::LLVMRustAddAnalysisPasses(tm, cpm, llmod);
+            llvm::LLVMRustAddLibraryInfo(cpm, llmod);
+            f(cpm);
+            llvm::LLVMDisposePassManager(cpm);
+        }
+    }
+
+    /// Runs the optimization passes over `llmod`, returning the extra passes
+    /// that LLVM didn't recognize.
+    unsafe fn optimize(config: &ModuleConfig, tm: TargetMachineRef,
+                       llmod: ModuleRef) -> Vec<~str> {
+        // Create the two optimizing pass managers. These mirror what clang
+        // does, and are by populated by LLVM's default PassManagerBuilder.
+        // Each manager has a different set of passes, but they also share
+        // some common passes.
+        let fpm = llvm::LLVMCreateFunctionPassManagerForModule(llmod);
+        let mpm = llvm::LLVMCreatePassManager();
+
+        // If we're verifying or linting, add them to the function pass
+        // manager.
+        let addpass = |pass: &str| {
+            pass.with_c_str(|s| llvm::LLVMRustAddPass(fpm, s))
+        };
+        if config.verify { assert!(addpass("verify")); }
+
+        // The counters of `-Z profile` go in front of the module optimization
+        // passes, as clang does for `--coverage`, so that they count the lines
+        // and branches of the code as written rather than what is left of it
+        // after inlining.
+        if config.profile {
+            assert!("insert-gcov-profiling".with_c_str(|s| llvm::LLVMRustAddPass(mpm, s)));
+        }
+
+        if config.prepopulate_passes {
+            llvm::LLVMRustAddAnalysisPasses(tm, fpm, llmod);
+            llvm::LLVMRustAddAnalysisPasses(tm, mpm, llmod);
+            populate_llvm_passes(fpm, mpm, llmod, config.opt_level);
+        }
+
+        let mut unknown_passes = Vec::new();
+        for pass in config.passes.iter() {
+            pass.with_c_str(|s| {
+                if !llvm::LLVMRustAddPass(mpm, s) {
+                    unknown_passes.push(pass.clone());
+                }
+            })
+        }
+
+        // Like clang, instrument the code for the sanitizer only once it has
+        // been optimized, which keeps the instrumentation from getting in the
+        // way of the optimizations and avoids instrumenting dead code.
+        match config.sanitizer {
+            Some(sanitizer) => {
+                if !config.prepopulate_passes {
+                    llvm::LLVMRustAddAnalysisPasses(tm, mpm, llmod);
+                }
+                let mut passes = vec!(sanitizer.short_name());
+                if sanitizer == session::AddressSanitizer {
+                    passes.push("asan-module");
+                }
+                for pass in passes.iter() {
+                    assert!(pass.with_c_str(|s| llvm::LLVMRustAddPass(mpm, s)));
+                }
+            }
+            None => {}
+        }
+
+        // Finally, run the actual optimization passes
+        time(config.time_passes, "llvm function passes", (), |()|
+             llvm::LLVMRustRunFunctionPassManager(fpm, llmod));
+        time(config.time_passes, "llvm module passes", (), |()|
+             llvm::LLVMRunPassManager(mpm, llmod));
+
+        // Deallocate managers that we're now done with
+        llvm::LLVMDisposePassManager(fpm);
+        llvm::LLVMDisposePassManager(mpm);
+
+        unknown_passes
+    }
+
+    fn warn_unknown_passes(sess: &Session, passes: Vec<~str>) {
+        for pass in passes.iter() {
+            sess.warn(format!("unknown pass {}, ignoring", *pass));
+        }
+    }
+
+    /// The temporary file holding one output of a codegen unit, such as
+    /// `foo.0.o` for the object file of the first unit.
+    fn unit_path(output: &OutputFilenames, flavor: OutputType, unit: uint) -> Path {
+        let path = output.temp_path(flavor);
+        let extension = format!("{}.{}", unit, path.extension_str().unwrap());
+        path.with_extension(extension)
+    }
+
+    /// Removes the given output of every codegen unit, unless we're saving
+    /// our temporaries.
+    fn remove_unit_files(sess: &Session, output: &OutputFilenames,
+                         flavor: OutputType, units: uint) {
+        if !sess.opts.cg.save_temps {
+            for i in range(0, units) {
+                super::remove(sess, &unit_path(output, flavor, i));
+            }
+        }
+    }
+
+    /// Splits the crate into codegen units and creates and optimizes them on
+    /// up to `-C codegen-units` tasks, writing out the bitcode and the object
+    /// file of each unit if asked to. When compiling incrementally, the units
+    /// of the functions reused from the cache are copied out of it after
+    /// those, and the units of the functions translated this time are added
+    /// to it. Returns the number of units.
+    unsafe fn run_codegen_units(sess: &Session,
+                                trans: &CrateTranslation,
+                                output: &OutputFilenames,
+                                emit_bitcode: bool,
+                                emit_object: bool) -> uint {
+        let cache = sess.opts.incremental.as_ref().map(|dir| {
+            incremental::Cache::open(sess, dir, trans.link.crateid.name.as_slice())
+        });
+        let partition = time(sess.time_passes(), "splitting into codegen units", (), |()| {
+            match cache {
+                Some(..) => {
+                    let salt = super::crate_disambiguator(sess, &trans.link);
+                    partition::split_by_item(trans, salt.as_slice())
+                }
+                None => partition::split(trans, sess.opts.cg.codegen_units),
+            }
+        });
+
+        // Each task works through its share of the units with a target
+        // machine of its own.
+        let ntasks = cmp::min(sess.opts.cg.codegen_units, partition.actions.len());
+        let mut shares = Vec::from_fn(ntasks, |_| Vec::new());
+        for (i, actions) in partition.actions.iter().enumerate() {
+            let bitcode = if emit_bitcode {
+                Some(unit_path(output, OutputTypeBitcode, i))
+            } else {
+                None
+            };
+            let object = if emit_object {
+                Some(unit_path(output, OutputTypeObject, i))
+            } else {
+                None
+            };
+            shares.get_mut(i % ntasks).push((actions.clone(), bitcode, object));
+        }
+
+        // The tasks all read the crate's bitcode, which is only disposed of
+        // along with `partition` once they're done.
+        let mut results = Vec::new();
+        for (i, share) in shares.move_iter().enumerate() {
+            let config = ModuleConfig::new(sess);
+            let tm = create_target_machine(sess);
+            let llbc = partition.bitcode;
+
+            let mut task = TaskBuilder::new().named(format!("codegen-{}", i));
+            let future = task.future_result();
+            let (tx, rx) = channel();
+            task.spawn(proc() {
+                let mut result = Ok(Vec::new());
+                for (actions, bitcode, object) in share.move_iter() {
+                    let unit_result = run_codegen_unit(&config, tm, llbc, actions.as_slice(),
+                                                       bitcode, object);
+                    if result.is_ok() {
+                        result = unit_result;
+                    }
+                }
+                llvm::LLVMRustDisposeTargetMachine(tm);
+                tx.send(result);
+            });
+            results.push((future, rx));
+        }
+
+        for (i, (future, rx)) in results.move_iter().enumerate() {
+            match future.recv() {
+                Ok(()) => {
+                    match rx.recv() {
+                        // All units were given the same passes
+                        Ok(unknown_passes) => if i == 0 {
+                            warn_unknown_passes(sess, unknown_passes);
+                        },
+                        Err(msg) => sess.err(msg),
+                    }
+                }
+                Err(..) => sess.err(format!("failed to optimize codegen units on task {}", i)),
+            }
+        }
+        sess.abort_if_errors();
+
+        let mut units = partition.actions.len();
+        match cache {
+            Some(ref cache) => {
+                let outputs = |unit: uint| {
+                    vec!(unit_path(output, OutputTypeBitcode, unit),
+                         unit_path(output, OutputTypeObject, unit))
+                };
+                let mut used = HashSet::new();
+                let mut translated = 1;
+                for item in trans.incremental.iter() {
+                    if item.reused {
+                        cache.fetch(sess, item.fingerprint.as_slice(),
+                                    outputs(units).as_slice());
+                        units += 1;
+                    } else {
+                        cache.store(sess, item.fingerprint.as_slice(),
+                                    outputs(translated).as_slice());
+                        translated += 1;
+                    }
+                    used.insert(item.fingerprint.clone());
+                }
+                cache.prune(sess, &used);
+            }
+            None => {}
+        }
+        units
+    }
+
+    /// Creates a codegen unit, optimizes it and writes out what's been asked
+    /// for, then disposes of it. This runs on a codegen task, so errors are
+    /// handed back instead of being reported to the session.
+    unsafe fn run_codegen_unit(config: &ModuleConfig,
+                               tm: TargetMachineRef,
+                               llbc: MemoryBufferRef,
+                               actions: &[partition::Action],
+                               bitcode: Option<Path>,
+                               object: Option<Path>) -> Result<Vec<~str>, ~str> {
+        let (llcx, llmod) = match partition::create_unit(llbc, actions) {
+            Ok(unit) => unit,
+            Err(msg) => return Err(format!("failed to create codegen unit: {}", msg)),
+        };
+        let unknown_passes = optimize(config, tm, llmod);
+
+        match bitcode {
+            Some(ref path) => {
+                path.with_c_str(|buf| {
+                    llvm::LLVMWriteBitcodeToFile(llmod, buf);
+                })
+            }
+            None => {}
+        }
+
+        let mut written = true;
+        match object {
+            Some(ref path) => {
+                with_codegen(tm, llmod, |cpm| {
+                    written = path.with_c_str(|output| {
+                        llvm::LLVMRustWriteOutputFile(tm, cpm, llmod, output,
+                                                      lib::llvm::ObjectFile)
+                    });
+                });
+            }
+            None => {}
+        }
+
+        llvm::LLVMDisposeModule(llmod);
+        llvm::LLVMContextDispose(llcx);
+
+        match object {
+            Some(ref path) if !written => {
+                Err(format!("could not write output to {}", path.display()))
+            }
+            _ => Ok(unknown_passes),
+        }
+    }
+
+    /// Links the optimized bitcode of the codegen units back into a single
+    /// module.
+    unsafe fn link_codegen_units(sess: &Session, output: &OutputFilenames,
+                                 units: uint) -> (ContextRef, ModuleRef) {
+        let llcx = llvm::LLVMContextCreate();
+        let llmod = output.out_filestem.with_c_str(|name| {
+            llvm::LLVMModuleCreateWithNameInContext(name, llcx)
+        });
+        for i in range(0, units) {
+            let path = unit_path(output, OutputTypeBitcode, i);
+            let bc = match fs::File::open(&path).read_to_end() {
+                Ok(bc) => bc,
+                Err(e) => sess.fatal(format!("failed to read {}: {}",
+                                             path.display(), e)),
+            };
+            if !llvm::LLVMRustLinkInExternalBitcode(llmod,
+                                                    bc.as_ptr() as *c_char,
+                                                    bc.len() as size_t) {
+                llvm_err(sess, format!("failed to link codegen unit {}", i));
+            }
+            if !sess.opts.cg.save_temps {
+                super::remove(sess, &path);
+            }
+        }
+        (llcx, llmod)
+    }
+
+    /// Combines the object files of the codegen units into the one object
+    /// file that the rest of the compilation expects.
+    fn link_unit_objects(sess: &Session, objects: &[Path], output: &Path) {
+        let cc = super::get_cc_prog(sess);
+
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        let mut args = sess.targ_cfg.target_strs.cc_args.clone();
+        args.push_all_move(vec!("-nostdlib".to_owned(),
+                                "-Wl,-r".to_owned(),
+                                "-o".to_owned(),
+                                output.as_str().unwrap().to_owned()));
+        for object in objects.iter() {
+            args.push(object.as_str().unwrap().to_owned());
+        }
+
+        debug!("{} '{}'", cc, args.connect("' '"));
+        match Process::output(cc, args.as_slice()) {
+            Ok(prog) => {
+                if !prog.status.success() {
+                    sess.err(format!("linking with `{}` failed: {}", cc, prog.status));
+                    sess.note(format!("{} arguments: '{}'", cc, args.connect("' '")));
+                    let mut note = prog.error.clone();
+                    note.push_all(prog.output.as_slice());
+                    sess.note(str::from_utf8(note.as_slice()).unwrap().to_owned());
+                    sess.abort_if_errors();
+                }
+            },
+            Err(e) => {
+                sess.err(format!("could not exec the linker `{}`: {}", cc, e));
+                sess.abort_if_errors();
+            }
+        }
+
+        if !sess.opts.cg.save_temps {
+            for object in objects.iter() {
+                super::remove(sess, object);
+            }
+        }
+    }
+
     pub fn run_assembler(sess: &Session, outputs: &OutputFilenames) {
         let cc = super::get_cc_prog(sess);
         let assembly = outputs.temp_path(OutputTypeAssembly);
@@ -556,6 +926,24 @@ fn truncated_hash_result(symbol_hasher: &mut Sha256) -> ~str {
 }
 
 
+/// What keeps the symbols of this crate apart from those of other crates with
+/// the same name. That's the crate hash, except when compiling incrementally:
+/// the crate hash changes with every edit, which would change every symbol
+/// and leave nothing in the cache to reuse, so the path of the crate's source
+/// file is hashed instead.
+pub fn crate_disambiguator(sess: &Session, link_meta: &LinkMeta) -> ~str {
+    if sess.opts.incremental.is_none() {
+        return link_meta.crate_hash.as_str().to_owned()
+    }
+    let mut hasher = Sha256::new();
+    hasher.input_str(link_meta.crateid.to_str());
+    match sess.local_crate_source_file {
+        Some(ref path) => hasher.input(path.as_vec()),
+        None => {}
+    }
+    truncated_hash_result(&mut hasher)
+}
+
 // This calculates STH for a symbol, as defined above
 fn symbol_hash(tcx: &ty::ctxt,
                symbol_hasher: &mut Sha256,
@@ -568,7 +956,7 @@ fn symbol_hash(tcx: &ty::ctxt,
     symbol_hasher.reset();
     symbol_hasher.input_str(link_meta.crateid.name.as_slice());
     symbol_hasher.input_str("-");
-    symbol_hasher.input_str(link_meta.crate_hash.as_str());
+    symbol_hasher.input_str(crate_disambiguator(&tcx.sess, link_meta));
     symbol_hasher.input_str("-");
     symbol_hasher.input_str(encoder::encoded_ty(tcx, t));
     // Prefix with 'h' so that it never blends into adjacent digits
@@ -700,19 +1088,30 @@ pub fn mangle_exported_name(ccx: &CrateContext, path: PathElems,
     // e.g. `fn foo() { { fn a() {} } { fn a() {} } }`, so we
     // generate unique characters from the node id. For now
     // hopefully 3 characters is enough to avoid collisions.
+    //
+    // Node ids shift with every edit, so incremental compilation asks the
+    // fingerprints of the crate's items for something to use instead.
     static EXTRA_CHARS: &'static str =
         "abcdefghijklmnopqrstuvwxyz\
          ABCDEFGHIJKLMNOPQRSTUVWXYZ\
          0123456789";
-    let id = id as uint;
-    let extra1 = id % EXTRA_CHARS.len();
-    let id = id / EXTRA_CHARS.len();
-    let extra2 = id % EXTRA_CHARS.len();
-    let id = id / EXTRA_CHARS.len();
-    let extra3 = id % EXTRA_CHARS.len();
-    hash.push_char(EXTRA_CHARS[extra1] as char);
-    hash.push_char(EXTRA_CHARS[extra2] as char);
-    hash.push_char(EXTRA_CHARS[extra3] as char);
+    let id = match ccx.incremental {
+        Some(ref fingerprints) => fingerprints.disambiguator(&ccx.tcx.map, id),
+        None => Some(id as uint),
+    };
+    match id {
+        Some(id) => {
+            let extra1 = id % EXTRA_CHARS.len();
+            let id = id / EXTRA_CHARS.len();
+            let extra2 = id % EXTRA_CHARS.len();
+            let id = id / EXTRA_CHARS.len();
+            let extra3 = id % EXTRA_CHARS.len();
+            hash.push_char(EXTRA_CHARS[extra1] as char);
+            hash.push_char(EXTRA_CHARS[extra2] as char);
+            hash.push_char(EXTRA_CHARS[extra3] as char);
+        }
+        None => {}
+    }
 
     exported_name(path,
                   hash.as_slice(),
@@ -783,7 +1182,7 @@ fn get_system_tool(sess: &Session, tool: &str) -> ~str {
     }
 }
 
-fn remove(sess: &Session, path: &Path) {
+pub fn remove(sess: &Session, path: &Path) {
     match fs::unlink(path) {
         Ok(..) => {}
         Err(e) => {
@@ -1021,7 +1420,7 @@ fn link_staticlib(sess: &Session, obj_filename: &Path, out_filename: &Path) {
             }
         };
         a.add_rlib(&p, name, sess.lto()).unwrap();
-        let native_libs = csearch::get_native_libraries(&sess.cstore, cnum);
+        let native_libs = csearch::get_native_libraries(sess, cnum);
         for &(kind, ref lib) in native_libs.iter() {
             let name = match kind {
                 cstore::NativeStatic => "static library",
@@ -1134,6 +1533,47 @@ fn link_args(sess: &Session,
         args.push(metadata.as_str().unwrap().to_owned());
     }
 
+    // The runtime of the sanitizer is linked into executables whole and ahead
+    // of the other libraries, so that its replacements for malloc, free,
+    // pthread_create and the like are the ones used by the entire program,
+    // the precompiled standard library included. Its symbols are exported
+    // for the interceptors to find them. Libraries leave the runtime to the
+    // executables they end up in.
+    if !dylib {
+        match sess.opts.sanitizer {
+            Some(sanitizer) => {
+                args.push_all([
+                    "-Wl,--whole-archive".to_owned(),
+                    format!("-lcompiler-rt-{}", sanitizer.short_name()),
+                    "-Wl,--no-whole-archive".to_owned(),
+                    "-Wl,--export-dynamic".to_owned(),
+                    "-lpthread".to_owned(),
+                    "-lrt".to_owned(),
+                    "-ldl".to_owned()]);
+                // The thread and memory sanitizers keep their shadow memory
+                // where a position dependent executable would be loaded.
+                if sanitizer != session::AddressSanitizer {
+                    args.push("-pie".to_owned());
+                }
+                // Only native threads are known to the runtime, green tasks
+                // switch stacks behind its back.
+                sess.cstore.iter_crate_data(|_, data| {
+                    if data.name.as_slice() == "green" {
+                        sess.warn(format!("the {} sanitizer doesn't support the \
+                                           tasks of libgreen", sanitizer.name()));
+                    }
+                });
+            }
+            None => {}
+        }
+
+        // The counters of `-Z profile` are written out at exit by the profile
+        // runtime of compiler-rt.
+        if sess.debugging_opt(session::PROFILE) {
+            args.push("-lcompiler-rt-profile".to_owned());
+        }
+    }
+
     // We want to prevent the compiler from accidentally leaking in any system
     // libraries, so we explicitly ask gcc to not link to any libraries by
     // default. Note that this does not happen for windows because windows pulls
@@ -1502,7 +1942,7 @@ fn add_upstream_native_libraries(args: &mut Vec<~str>, sess: &Session) {
     // the paths.
     let crates = sess.cstore.get_used_crates(cstore::RequireStatic);
     for (cnum, _) in crates.move_iter() {
-        let libs = csearch::get_native_libraries(&sess.cstore, cnum);
+        let libs = csearch::get_native_libraries(sess, cnum);
         for &(kind, ref lib) in libs.iter() {
             match kind {
                 cstore::NativeUnknown => args.push("-l" + *lib),
@@ -0,0 +1,479 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Splitting the crate's LLVM module into codegen units.
+//!
+//! With `-C codegen-units=N` the module built by translation is divided into
+//! at most N modules, each in its own LLVM context, so that they can be
+//! optimized and compiled to object code on separate threads. Every function
+//! and global variable defined in the module ends up in one of these forms:
+//!
+//! * Most items are defined in exactly one unit. The other units using them
+//!   get a declaration instead, and if the item was local to the crate it is
+//!   turned into a hidden external symbol with a crate-unique name, so that
+//!   the units still link together.
+//! * Local functions marked `#[inline]` and local constants are copied into
+//!   every unit that uses them, keeping their internal linkage, so that LLVM
+//!   can still inline and fold them.
+//! * Exported `#[inline]` functions are defined in one unit and made
+//!   `available_externally` in the other units that use them.
+//!
+//! Functions are spread over the units by their number of instructions, and
+//! each global variable goes to the unit of the first function that uses it.
+//!
+//! With `-Z incremental` the module is split by item instead, so that the
+//! units of unchanged functions can be reused from the cache of
+//! `back::incremental`; see `split_by_item`.
+
+use driver::driver::CrateTranslation;
+use lib::llvm::{llvm, ContextRef, MemoryBufferRef, ModuleRef, ValueRef, True};
+use lib;
+
+use collections::{HashMap, HashSet};
+use libc::{c_uint, c_ulonglong};
+use std::cmp;
+use std::str::raw::from_c_str;
+
+#[deriving(Eq)]
+enum Distribution {
+    /// Not defined by this crate; every unit keeps the declaration.
+    Declared,
+    /// Defined in one unit and declared in the others.
+    Placed,
+    /// Copied into every unit that uses it.
+    Copied,
+    /// Defined in one unit and `available_externally` in the others that
+    /// use it.
+    Shared,
+}
+
+struct Item {
+    llval: ValueRef,
+    dist: Distribution,
+    /// Whether the item had internal or private linkage.
+    local: bool,
+    /// Whether the item is one of LLVM's appending arrays, such as
+    /// `llvm.global_ctors`, which only one unit may define.
+    appending: bool,
+    /// The items used by the body or initializer of this item.
+    refs: Vec<uint>,
+    /// The number of instructions in the body of this item.
+    weight: uint,
+}
+
+/// Collects the items used by a function body or initializer, looking
+/// through constant expressions.
+struct RefCollector<'a> {
+    indices: &'a HashMap<uint, uint>,
+    seen: HashSet<uint>,
+    refs: Vec<uint>,
+}
+
+impl<'a> RefCollector<'a> {
+    unsafe fn operand(&mut self, llval: ValueRef) {
+        if llval.is_null() || !self.seen.insert(llval as uint) {
+            return
+        }
+        if llvm::LLVMIsAGlobalValue(llval).is_not_null() {
+            match self.indices.find(&(llval as uint)) {
+                Some(&i) => self.refs.push(i),
+                None => {}
+            }
+        } else if llvm::LLVMIsAConstant(llval).is_not_null() {
+            self.operands(llval);
+        }
+    }
+
+    unsafe fn operands(&mut self, llval: ValueRef) {
+        for i in range(0, llvm::LLVMGetNumOperands(llval)) {
+            self.operand(llvm::LLVMGetOperand(llval, i as c_uint));
+        }
+    }
+}
+
+/// Returns the functions of `llmod` followed by its global variables, in the
+/// order in which LLVM keeps them, along with the number of functions.
+unsafe fn global_values(llmod: ModuleRef) -> (Vec<ValueRef>, uint) {
+    let mut values = Vec::new();
+    let mut llfn = llvm::LLVMGetFirstFunction(llmod);
+    while llfn.is_not_null() {
+        values.push(llfn);
+        llfn = llvm::LLVMGetNextFunction(llfn);
+    }
+    let nfns = values.len();
+    let mut llglobal = llvm::LLVMGetFirstGlobal(llmod);
+    while llglobal.is_not_null() {
+        values.push(llglobal);
+        llglobal = llvm::LLVMGetNextGlobal(llglobal);
+    }
+    (values, nfns)
+}
+
+unsafe fn value_name(llval: ValueRef) -> ~str {
+    from_c_str(llvm::LLVMGetValueName(llval))
+}
+
+unsafe fn analyze(llmod: ModuleRef) -> Vec<Item> {
+    let (values, nfns) = global_values(llmod);
+    let mut indices = HashMap::new();
+    for (i, llval) in values.iter().enumerate() {
+        indices.insert(*llval as uint, i);
+    }
+
+    let inline_attrs = lib::llvm::InlineHintAttribute as c_ulonglong |
+                       lib::llvm::AlwaysInlineAttribute as c_ulonglong;
+
+    values.iter().enumerate().map(|(i, &llval)| {
+        let is_fn = i < nfns;
+        let linkage = llvm::LLVMGetLinkage(llval);
+        let local = linkage == lib::llvm::InternalLinkage as c_uint ||
+                    linkage == lib::llvm::PrivateLinkage as c_uint;
+        let available = linkage == lib::llvm::AvailableExternallyLinkage as c_uint;
+        let appending = linkage == lib::llvm::AppendingLinkage as c_uint;
+
+        let mut collector = RefCollector {
+            indices: &indices,
+            seen: HashSet::new(),
+            refs: Vec::new(),
+        };
+        let mut weight = 0;
+        let dist = if llvm::LLVMIsDeclaration(llval) == True {
+            Declared
+        } else if is_fn {
+            let mut llbb = llvm::LLVMGetFirstBasicBlock(llval);
+            while llbb.is_not_null() {
+                let mut llinst = llvm::LLVMGetFirstInstruction(llbb);
+                while llinst.is_not_null() {
+                    weight += 1;
+                    collector.operands(llinst);
+                    llinst = llvm::LLVMGetNextInstruction(llinst);
+                }
+                llbb = llvm::LLVMGetNextBasicBlock(llbb);
+            }
+
+            let inline = llvm::LLVMGetFunctionAttr(llval) & inline_attrs != 0;
+            if available || (local && inline) {
+                Copied
+            } else if inline && linkage == lib::llvm::ExternalLinkage as c_uint {
+                Shared
+            } else {
+                Placed
+            }
+        } else {
+            collector.operand(llvm::LLVMGetInitializer(llval));
+
+            if available || (local && llvm::LLVMIsGlobalConstant(llval) == True) {
+                Copied
+            } else {
+                Placed
+            }
+        };
+
+        Item {
+            llval: llval,
+            dist: dist,
+            local: local,
+            appending: appending,
+            refs: collector.refs,
+            weight: weight,
+        }
+    }).collect()
+}
+
+/// Picks the unit that defines each placed or shared item.
+fn assign_homes(items: &[Item], units: uint) -> Vec<Option<uint>> {
+    fn is_defined(item: &Item) -> bool {
+        item.dist == Placed || item.dist == Shared
+    }
+
+    let mut homes = Vec::from_elem(items.len(), None);
+
+    // Hand out the functions, biggest first, to the least loaded unit.
+    let mut fns: Vec<uint> = range(0, items.len()).filter(|&i| {
+        is_defined(&items[i]) && items[i].weight > 0
+    }).collect();
+    fns.sort_by(|&a, &b| items[b].weight.cmp(&items[a].weight));
+    let mut loads = Vec::from_elem(units, 0u);
+    for &i in fns.iter() {
+        let unit = loads.iter().enumerate().min_by(|&(_, &load)| load)
+                        .map(|(unit, _)| unit).unwrap();
+        *homes.get_mut(i) = Some(unit);
+        *loads.get_mut(unit) += items[i].weight;
+    }
+
+    // Global variables go along with the first function using them.
+    for (i, item) in items.iter().enumerate() {
+        let home = match *homes.get(i) {
+            Some(home) if item.weight > 0 => home,
+            _ => continue,
+        };
+        for &r in item.refs.iter() {
+            if is_defined(&items[r]) && homes.get(r).is_none() {
+                *homes.get_mut(r) = Some(home);
+            }
+        }
+    }
+
+    // Everything else, such as globals only used by other globals, ends up
+    // in the first unit.
+    for (i, item) in items.iter().enumerate() {
+        if is_defined(item) && homes.get(i).is_none() {
+            *homes.get_mut(i) = Some(0);
+        }
+    }
+    homes
+}
+
+/// What a codegen unit does with an item of the crate's module.
+#[deriving(Clone, Eq)]
+pub enum Action {
+    /// Keep the item as it is.
+    Keep,
+    /// Keep the definition as `available_externally`.
+    MakeAvailableExternally,
+    /// Keep only a declaration of the item.
+    Declare,
+    /// Remove the item altogether.
+    Remove,
+}
+
+/// The crate's module divided into codegen units. Each unit starts out as a
+/// copy of the module, made from `bitcode` by `create_unit`, and then does
+/// what `actions` says with every item of the module.
+pub struct Partition {
+    pub bitcode: MemoryBufferRef,
+    pub actions: Vec<Vec<Action>>,
+}
+
+impl Drop for Partition {
+    fn drop(&mut self) {
+        unsafe {
+            llvm::LLVMDisposeMemoryBuffer(self.bitcode);
+        }
+    }
+}
+
+/// Works out which items each unit needs a definition of, following the uses
+/// of the items it defines through the copies it gets. Also returns which
+/// placed items are used by units other than their own.
+fn memberships(items: &[Item], homes: &[Option<uint>],
+               units: uint) -> (Vec<Vec<bool>>, Vec<bool>) {
+    let mut exported = Vec::from_elem(items.len(), false);
+    let mut members = Vec::new();
+    for unit in range(0, units) {
+        let mut member = Vec::from_elem(items.len(), false);
+        let mut stack = Vec::new();
+        for (i, home) in homes.iter().enumerate() {
+            if *home == Some(unit) {
+                *member.get_mut(i) = true;
+                stack.push(i);
+            }
+        }
+        loop {
+            let i = match stack.pop() {
+                Some(i) => i,
+                None => break,
+            };
+            for &r in items[i].refs.iter() {
+                match items[r].dist {
+                    Declared => {}
+                    Placed => {
+                        if homes[r] != Some(unit) {
+                            *exported.get_mut(r) = true;
+                        }
+                    }
+                    Copied | Shared => {
+                        if !*member.get(r) {
+                            *member.get_mut(r) = true;
+                            stack.push(r);
+                        }
+                    }
+                }
+            }
+        }
+        members.push(member);
+    }
+    (members, exported)
+}
+
+/// Turns a local item into a hidden symbol that other units can use, with
+/// `salt` appended to its name to keep it apart from the local items of
+/// other crates.
+unsafe fn export(item: &Item, salt: &str) {
+    let name = format!("{}.{}", value_name(item.llval), salt);
+    name.with_c_str(|buf| llvm::LLVMSetValueName(item.llval, buf));
+    lib::llvm::SetLinkage(item.llval, lib::llvm::ExternalLinkage);
+    llvm::LLVMSetVisibility(item.llval, lib::llvm::HiddenVisibility as c_uint);
+}
+
+fn actions(items: &[Item], homes: &[Option<uint>], members: &[Vec<bool>],
+           exported: &[bool]) -> Vec<Vec<Action>> {
+    members.iter().enumerate().map(|(unit, member)| {
+        items.iter().enumerate().map(|(i, item)| {
+            match item.dist {
+                Declared => Keep,
+                _ if homes[i] == Some(unit) => Keep,
+                Shared if *member.get(i) => MakeAvailableExternally,
+                Copied if *member.get(i) => Keep,
+                // Local items this unit doesn't use can't be left behind
+                // as declarations, and neither can the appending arrays
+                // that another unit defines.
+                _ if (item.local && !exported[i]) || item.appending => Remove,
+                _ => Declare,
+            }
+        }).collect()
+    }).collect()
+}
+
+/// Splits the module of `trans` into at most `units` codegen units. Local
+/// items used across units are made external in the module of `trans` along
+/// the way.
+///
+/// The module is only serialized once here. Making the copies for the units
+/// is left to `create_unit`, so that it happens on the tasks optimizing them.
+pub fn split(trans: &CrateTranslation, units: uint) -> Partition {
+    unsafe {
+        let items = analyze(trans.module);
+
+        // There's no point in having units without any functions.
+        let nfns = items.iter().count(|item| {
+            item.weight > 0 && item.dist != Copied
+        });
+        let units = cmp::max(cmp::min(units, nfns), 1);
+
+        let homes = assign_homes(items.as_slice(), units);
+        let (members, exported) = memberships(items.as_slice(), homes.as_slice(), units);
+
+        // Local items used by other units become hidden symbols, with the
+        // crate's hash appended.
+        let hash = trans.link.crate_hash.as_str();
+        for (i, item) in items.iter().enumerate() {
+            if *exported.get(i) && item.local {
+                export(item, hash);
+            }
+        }
+
+        Partition {
+            actions: actions(items.as_slice(), homes.as_slice(), members.as_slice(),
+                             exported.as_slice()),
+            bitcode: llvm::LLVMRustWriteBitcodeToMemoryBuffer(trans.module),
+        }
+    }
+}
+
+/// Splits the module of `trans` for incremental compilation. Every function
+/// of `trans.incremental` that was translated gets a unit of its own, in
+/// order, after a first unit holding everything else that is defined once.
+///
+/// A unit compiled in one compilation is linked against the units of later
+/// ones, so the symbols its code uses must stay the same between them. That's
+/// why local functions that aren't compiled on their own are copied into
+/// every unit using them, and the functions compiled on their own as well as
+/// the global variables that are defined once all become external, with
+/// `salt` appended to the names of the local ones. Unlike the crate hash,
+/// `salt` doesn't change from one compilation to the next.
+pub fn split_by_item(trans: &CrateTranslation, salt: &str) -> Partition {
+    unsafe {
+        let mut items = analyze(trans.module);
+        let mut indices = HashMap::new();
+        for (i, item) in items.iter().enumerate() {
+            indices.insert(value_name(item.llval), i);
+        }
+
+        let mut separate = Vec::from_elem(items.len(), false);
+        let mut homes = Vec::from_elem(items.len(), None);
+        let mut units = 1;
+        for func in trans.incremental.iter() {
+            let i = match indices.find(&func.symbol) {
+                Some(&i) => i,
+                None => fail!("`{}` is missing from the crate's module", func.symbol),
+            };
+            *separate.get_mut(i) = true;
+            if !func.reused {
+                *homes.get_mut(i) = Some(units);
+                units += 1;
+            }
+        }
+
+        for (i, item) in items.mut_iter().enumerate() {
+            if item.dist == Placed && item.local && item.weight > 0 && !*separate.get(i) {
+                item.dist = Copied;
+            }
+        }
+        for (i, item) in items.iter().enumerate() {
+            if (item.dist == Placed || item.dist == Shared) && homes.get(i).is_none() {
+                *homes.get_mut(i) = Some(0);
+            }
+        }
+
+        let (members, _) = memberships(items.as_slice(), homes.as_slice(), units);
+
+        // Whether or not other units use them this time, they may well do so
+        // in later compilations.
+        let exported: Vec<bool> = items.iter().map(|item| item.dist == Placed).collect();
+        for (i, item) in items.iter().enumerate() {
+            if (*exported.get(i) || *separate.get(i)) && item.local {
+                export(item, salt);
+            }
+        }
+
+        Partition {
+            actions: actions(items.as_slice(), homes.as_slice(), members.as_slice(),
+                             exported.as_slice()),
+            bitcode: llvm::LLVMRustWriteBitcodeToMemoryBuffer(trans.module),
+        }
+    }
+}
+
+/// Creates a codegen unit in a context of its own from the `bitcode` of a
+/// `Partition` and the `actions` of the unit. This runs on the task that
+/// optimizes the unit, so errors are handed back instead of being reported
+/// to the session.
+pub unsafe fn create_unit(bitcode: MemoryBufferRef,
+                          actions: &[Action]) -> Result<(ContextRef, ModuleRef), ~str> {
+    let llcx = llvm::LLVMContextCreate();
+    let llmod = llvm::LLVMRustParseBitcodeInContext(llcx, bitcode);
+    if llmod.is_null() {
+        llvm::LLVMContextDispose(llcx);
+        return Err("could not read back the bitcode of the crate".to_owned())
+    }
+
+    let (values, _) = global_values(llmod);
+    if values.len() != actions.len() {
+        llvm::LLVMDisposeModule(llmod);
+        llvm::LLVMContextDispose(llcx);
+        return Err("codegen unit does not match the crate's module".to_owned())
+    }
+
+    // Drop the definitions that don't belong here first, so that the items
+    // removed below are not used by any body any more.
+    for (&llval, &action) in values.iter().zip(actions.iter()) {
+        match action {
+            Keep => {}
+            MakeAvailableExternally => {
+                lib::llvm::SetLinkage(llval, lib::llvm::AvailableExternallyLinkage);
+            }
+            Declare | Remove => llvm::LLVMRustDropDefinition(llval),
+        }
+    }
+    for (&llval, &action) in values.iter().zip(actions.iter()) {
+        if action == Remove && !llvm::LLVMRustEraseUnusedGlobal(llval) {
+            let name = value_name(llval);
+            llvm::LLVMDisposeModule(llmod);
+            llvm::LLVMContextDispose(llcx);
+            return Err(format!("`{}` is still used in a codegen unit", name))
+        }
+    }
+
+    debug!("codegen unit: {} of {} items",
+           actions.iter().count(|&a| a == Keep || a == MakeAvailableExternally),
+           actions.len());
+    Ok((llcx, llmod))
+}
@@ -0,0 +1,2111 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Error codes and their extended explanations
+//!
+//! Errors reported with `span_err!` carry a code such as `E0001`, which is
+//! printed after the message and can be looked up with `rustc --explain`.
+//! Every code must appear exactly once in `DIAGNOSTICS` below and be used
+//! exactly once in the compiler; `src/etc/errorck.py`, run as part of `make
+//! tidy`, checks both. To add an error, take the next unused code, add it to
+//! the end of the registry with an explanation that includes an example, and
+//! use it in a single `span_err!`. Codes of errors that are removed are not
+//! reused.
+
+#![macro_escape]
+
+/// Reports an error with a code from `DIAGNOSTICS`, formatting the message
+/// like `format!`: `span_err!(tcx.sess, sp, E0001, "bad {}", thing)`.
+macro_rules! span_err(
+    ($session:expr, $span:expr, $code:ident, $($message:tt)*) => (
+        $session.span_err_with_code($span, format!($($message)*).as_slice(),
+                                    stringify!($code))
+    )
+)
+
+/// Every error code, in order, with its explanation.
+pub static DIAGNOSTICS: &'static [(&'static str, &'static str)] = &[
+    ("E0001", r#"
+Two items with the same name were defined in the same namespace of a module.
+Types, traits and modules share one namespace, and functions, statics and
+enum variants share another, so for example a struct and a trait can't have
+the same name:
+
+    struct Foo;
+    trait Foo {}      // error: duplicate definition of type or module `Foo`
+
+Rename one of the items, or move it into a separate module.
+"#),
+    ("E0002", r#"
+An import couldn't be resolved. This error follows another one that says
+why, such as a module in the path that doesn't exist:
+
+    use foo::bar;    // error: failed to resolve import `foo::bar`
+
+Fix the error reported before this one.
+"#),
+    ("E0003", r#"
+An import named a module that exists, but the module has no item with the
+given name in any namespace:
+
+    mod foo {
+        pub fn bar() {}
+    }
+
+    use foo::baz;    // error: unresolved import: there is no `baz` in `foo`
+
+Check the spelling of the item. A note suggests a similarly named item, if
+there is one.
+"#),
+    ("E0004", r#"
+The first segment of an import path didn't name anything in the crate root.
+Paths in `use` declarations are relative to the crate root, so an import
+from another crate needs an `extern crate` declaration there first:
+
+    use collections::HashMap;     // error: unresolved import
+
+    extern crate collections;
+    use collections::HashMap;     // ok
+"#),
+    ("E0005", r#"
+A module in the middle of an import path couldn't be found in the module
+before it, or is private:
+
+    mod foo {
+        pub fn bar() {}
+    }
+
+    use foo::baz::qux;    // error: unresolved import: could not find `baz` in
+                          // `foo`.
+
+Check the spelling of the module, and that it is declared `pub mod`.
+"#),
+    ("E0006", r#"
+A path used a name as a module, but the name refers to a type that isn't a
+module, such as a struct or an enum:
+
+    struct Foo;
+
+    use Foo::bar;    // error: not a module `Foo`
+
+Only modules can contain items that are imported by path.
+"#),
+    ("E0007", r#"
+An import named an item inside a trait or an implementation. Methods of a
+trait or of a type can't be imported; they are called through a value or
+through the type:
+
+    trait Shape {
+        fn new() -> Self;
+    }
+
+    use Shape::new;    // error: cannot import from a trait or type
+                       // implementation
+"#),
+    ("E0008", r#"
+A path used a name as a module, but the name refers only to a value, such as
+a function or a static:
+
+    fn foo() {}
+
+    use foo::bar;    // error: not a module `foo`
+"#),
+    ("E0009", r#"
+The `self` or `super` prefix of an import path couldn't be resolved, for
+example because `super` was used more times than there are modules above the
+current one:
+
+    mod a {
+        use super::super::foo;    // error: the crate root has no parent
+    }
+"#),
+    ("E0010", r#"
+A name in an import or a path couldn't be found in the module it was looked
+up in. Paths in `use` declarations are relative to the crate root, while
+paths in expressions are relative to the current module; prefix a path with
+`self::` or `super::` to make it relative to the current module or its
+parent.
+"#),
+    ("E0011", r#"
+An import in a module couldn't be resolved, so resolution stopped there:
+
+    use foo::bar;    // error: unresolved import
+
+Check that every module in the path exists and is spelled correctly. Paths
+in `use` declarations are relative to the crate root.
+"#),
+    ("E0012", r#"
+An import of a single name, without a path, couldn't be resolved. A `use`
+declaration needs the path of the item from the crate root:
+
+    use HashMap;    // error: unresolved import (maybe you meant
+                    // `HashMap::*`?)
+
+Write the full path, such as `use collections::HashMap;`.
+"#),
+    ("E0013", r#"
+A method of an `impl` declared inside a function used a local variable of
+that function. Items declared inside a function are independent of it, so
+they can't capture its variables:
+
+    fn foo() {
+        let y = 5;
+        struct Bar;
+        impl Bar {
+            fn get(&self) -> int { y }    // error
+        }
+    }
+
+Pass the value in as an argument, or store it in the struct.
+"#),
+    ("E0014", r#"
+A method of an `impl` declared inside a function used a type parameter of
+that function. Items declared inside a function are independent of it, so
+they can't refer to its type parameters:
+
+    fn outer<T>(x: T) {
+        struct Foo;
+        impl Foo {
+            fn inner(&self, y: T) {}    // error
+        }
+    }
+
+Give the method its own type parameter instead:
+
+    fn outer<T>(x: T) {
+        struct Foo;
+        impl Foo {
+            fn inner<U>(&self, y: U) {}
+        }
+    }
+"#),
+    ("E0015", r#"
+A function declared inside another function used a local variable of the
+outer one. Functions can't capture their environment; closures can:
+
+    fn foo() {
+        let y = 5;
+        fn bar() -> int { y }    // error: can't capture dynamic environment
+                                 // in a fn item
+    }
+
+Use a closure instead, as in `let bar = || y;`.
+"#),
+    ("E0016", r#"
+A function declared inside another function used a type parameter of the
+outer one. Items declared inside a function are independent of it, so they
+can't refer to its type parameters:
+
+    fn outer<T>(x: T) {
+        fn inner(y: T) {}    // error
+    }
+
+Give the inner function its own type parameter instead:
+
+    fn outer<T>(x: T) {
+        fn inner<U>(y: U) {}
+    }
+"#),
+    ("E0017", r#"
+A `static` declared inside a generic function used a type parameter of the
+function. Statics have a single type and value, so they can't depend on the
+type parameters of the function they are declared in:
+
+    fn foo<T>() {
+        static NONE: Option<T> = None;    // error
+    }
+"#),
+    ("E0018", r#"
+The initializer of a `static` or the size of a fixed-length array referred
+to a local variable or another value that isn't a compile-time constant:
+
+    fn f(n: uint) {
+        let a = [0, ..n];    // error
+    }
+
+Use a `static` or a literal instead, or a vector whose length is known only
+at runtime, such as `Vec::from_elem(n, 0)`.
+"#),
+    ("E0019", r#"
+A trait used in a bound, an `impl` or a `#[deriving]` attribute couldn't be
+found:
+
+    impl Shape for int {}    // error: attempt to implement a nonexistent
+                             // trait `Shape`
+
+Check the spelling of the trait and that it is imported with `use`.
+"#),
+    ("E0020", r#"
+A struct inherited from a struct defined in another crate. Struct
+inheritance is experimental, and only works with structs from the same
+crate:
+
+    #![feature(struct_inherit)]
+    extern crate foo;
+
+    struct Bar : foo::Base {}    // error: super-struct is defined in a
+                                 // different crate
+"#),
+    ("E0021", r#"
+A struct inherited from a type that isn't a struct:
+
+    #![feature(struct_inherit)]
+    enum Base { A, B }
+
+    struct Bar : Base {}    // error: super-struct is not a struct type
+"#),
+    ("E0022", r#"
+A struct inherited from a name that couldn't be resolved to any type:
+
+    #![feature(struct_inherit)]
+    struct Bar : Base {}    // error: super-struct could not be resolved
+
+Check the spelling of the super-struct and that it is imported with `use`.
+"#),
+    ("E0023", r#"
+A variable was bound in some, but not all, of the alternatives of an
+or-pattern. Each alternative must bind the same set of variables, since the
+arm's body may use any of them:
+
+    match x {
+        Some(y) | None => {}    // error: `y` is not bound in pattern #2
+    }
+"#),
+    ("E0024", r#"
+A variable was bound by value in one alternative of an or-pattern and by
+reference in another. Since the arm's body uses one variable, every
+alternative must bind it the same way:
+
+    match x {
+        (ref a, 0) | (a, _) => {}    // error: variable `a` is bound with
+                                     // different mode in pattern #2 than in
+                                     // pattern #1
+    }
+"#),
+    ("E0025", r#"
+A variable was bound in a later alternative of an or-pattern, but not in the
+first one. Each alternative must bind the same set of variables, since the
+arm's body may use any of them:
+
+    match x {
+        None | Some(y) => {}    // error: variable `y` from pattern #2 is not
+                                // bound in pattern #1
+    }
+"#),
+    ("E0026", r#"
+A primitive type was given lifetime parameters, which it doesn't take:
+
+    let x: int<'a> = 5;    // error: lifetime parameters are not allowed on
+                           // this type
+"#),
+    ("E0027", r#"
+A primitive type was given type parameters, which it doesn't take:
+
+    let x: int<u8> = 5;    // error: type parameters are not allowed on this
+                           // type
+"#),
+    ("E0028", r#"
+A type name didn't refer to any type in scope:
+
+    fn foo(x: Strnig) {}    // error: use of undeclared type name `Strnig`
+
+Check the spelling of the type and that it is imported with `use`. If a
+type in scope is spelled similarly, a note suggests it.
+"#),
+    ("E0029", r#"
+A `let` or function argument pattern used the name of an enum variant or a
+unit-like struct that is in scope as if it were a new variable. The pattern
+would match the variant rather than bind a variable:
+
+    enum Color { Red, Green }
+
+    let Red = 5;    // error: declaration of `Red` shadows an enum variant or
+                    // unit-like struct in scope
+
+Choose a different name for the variable.
+"#),
+    ("E0030", r#"
+A `let` declaration or a function argument used the name of a `static` in
+scope as its pattern. That pattern would only match the static's value, but
+only patterns that match every value can be used there:
+
+    static FOO: int = 1;
+
+    let FOO = 5;    // error: only irrefutable patterns allowed here
+
+Choose a different name for the variable.
+"#),
+    ("E0031", r#"
+The same identifier was bound twice in one pattern:
+
+    let (a, a) = (1, 2);    // error
+
+Give each binding a different name, or use `_` for a value that isn't
+needed.
+"#),
+    ("E0032", r#"
+A path in a pattern named something other than an enum variant or a
+constant, such as a function:
+
+    mod m {
+        pub fn f() {}
+    }
+
+    match x {
+        m::f => {}    // error: `f` is not an enum variant or constant
+        _ => {}
+    }
+"#),
+    ("E0033", r#"
+A path in a pattern couldn't be resolved to an enum variant or a constant:
+
+    match x {
+        Color::Purple => {}    // error: unresolved enum variant
+    }
+
+Check the spelling of the variant and that its enum is imported.
+"#),
+    ("E0034", r#"
+A path used in a pattern with arguments, like `Foo(x)`, named something
+other than an enum variant, a tuple struct or a constant:
+
+    fn foo(x: int) -> int { x }
+
+    match y {
+        foo(x) => {}    // error: `foo` is not an enum variant, struct or
+                        // const
+    }
+"#),
+    ("E0035", r#"
+A path used in a pattern with arguments, like `Foo(x)`, couldn't be
+resolved:
+
+    match y {
+        Sum(x) => {}    // error: unresolved enum variant, struct or const
+                        // `Sum`
+    }
+
+Check the spelling of the variant or struct and that it is imported.
+"#),
+    ("E0036", r#"
+A struct pattern, like `Foo { x, y }`, named something that isn't a struct
+or a struct-like enum variant:
+
+    match p {
+        Point { x, y } => {}    // error: `Point` does not name a structure
+    }
+
+This is also reported when the name couldn't be found at all; check its
+spelling and that it is imported.
+"#),
+    ("E0037", r#"
+A path referred to a module that couldn't be found from the current module:
+
+    fn main() {
+        let x = foo::bar();    // error: use of undeclared module `foo`
+    }
+
+Paths in expressions and types are relative to the current module. Import
+the module with `use`, or declare it with `mod` or `extern crate`.
+"#),
+    ("E0038", r#"
+A path starting with `::`, which is relative to the crate root, referred to a
+module that isn't declared there:
+
+    fn main() {
+        let x = ::foo::bar();    // error: use of undeclared module `::foo`
+    }
+"#),
+    ("E0039", r#"
+A method was named with a path, as if it were a function, in a place that
+requires a value. Methods can't be used as first-class values:
+
+    trait Foo { fn bar(&self); }
+
+    let f = Foo::bar;    // error: first-class methods are not supported
+
+Use a closure that calls the method instead: `let f = |x: &int| x.bar();`.
+"#),
+    ("E0040", r#"
+The name of a struct with named fields was called like a function:
+
+    struct Point { x: int, y: int }
+
+    let p = Point(1, 2);    // error: `Point` is a structure name, but this
+                            // expression uses it like a function name
+
+Write a struct expression instead: `Point { x: 1, y: 2 }`.
+"#),
+    ("E0042", r#"
+A name used in an expression didn't refer to any local variable or item in
+scope:
+
+    fn main() {
+        println!("{}", x);    // error: unresolved name `x`.
+    }
+
+Check the spelling of the name, that it is declared before it is used, and
+that any item from another module is imported with `use`. If a name in scope
+is spelled similarly, a note suggests it.
+"#),
+    ("E0043", r#"
+A struct expression, like `Foo { x: 1 }`, named something that isn't a
+struct or a struct-like enum variant:
+
+    let p = Pointt { x: 1, y: 2 };    // error: `Pointt` does not name a
+                                      // structure
+
+This is also reported when the name couldn't be found at all; check its
+spelling and that it is imported.
+"#),
+    ("E0044", r#"
+A `break` or `continue` named a label that isn't declared on an enclosing
+loop:
+
+    'outer: loop {
+        loop {
+            break 'outr;    // error: use of undeclared label `'outr`
+        }
+    }
+"#),
+    ("E0045", r#"
+A pattern used `ref` with the name of a `static`. The name matches the
+static's value rather than binding a variable, so there is nothing to take a
+reference to:
+
+    static FOO: int = 1;
+
+    match x {
+        ref FOO => {}    // error: cannot use `ref` binding mode with a
+                         // constant
+        _ => {}
+    }
+"#),
+    ("E0046", r#"
+A reference type was written without a lifetime in a place where none can be
+inferred, such as a field of a struct:
+
+    struct Foo {
+        x: &int,    // error: missing lifetime specifier
+    }
+
+Give the struct a lifetime parameter and use it:
+
+    struct Foo<'a> {
+        x: &'a int,
+    }
+"#),
+    ("E0047", r#"
+A path to a type gave it a different number of lifetime parameters than the
+type declares:
+
+    struct Foo<'a> { x: &'a int }
+
+    fn f(foo: Foo<'static, 'static>) {}    // error: expected 1 but found 2
+"#),
+    ("E0048", r#"
+A type was written without some of its type parameters, relying on their
+defaults. Default type parameters are experimental and must be enabled in
+the crate:
+
+    struct Foo<T = int> { x: T }
+
+    fn f(foo: Foo) {}    // error: default type parameters are experimental
+
+Add `#![feature(default_type_params)]` to the crate attributes, or give the
+type parameters explicitly, as in `Foo<int>`.
+"#),
+    ("E0049", r#"
+A type parameter was given type parameters of its own, which it can't take:
+
+    fn foo<T>(x: T<int>) {}    // error: type parameters are not allowed on
+                               // this type
+"#),
+    ("E0050", r#"
+A type parameter was given lifetime parameters, which it can't take:
+
+    fn foo<'a, T>(x: T<'a>) {}    // error: region parameters are not allowed
+                                  // on this type
+"#),
+    ("E0051", r#"
+The `f128` type was used without enabling it. Quadruple precision floats
+aren't fully supported by the runtime yet:
+
+    let x: f128 = 1.0;    // error
+
+Add `#![feature(quad_precision_float)]` to the crate attributes to use it.
+"#),
+    ("E0052", r#"
+The type `str` was used on its own. A string's size isn't known, so it can
+only be used behind a pointer:
+
+    fn foo(s: str) {}    // error: bare `str` is not a type
+
+Use a string slice, `&str`, or an owned string, `~str` or `StrBuf`.
+"#),
+    ("E0053", r#"
+`Box` was given more than one type parameter:
+
+    let x: Box<int, int> = box 5;    // error: `Box` has only one type
+                                     // parameter
+"#),
+    ("E0054", r#"
+`Box<str>` was used as a type. An owned string is written `~str`:
+
+    let s: Box<str> = "hello".to_owned();    // error
+
+    let s: ~str = "hello".to_owned();    // ok
+"#),
+    ("E0055", r#"
+`Box<[T]>` was used as a type. An owned vector is written `~[T]`, though a
+`Vec<T>` is usually what is wanted:
+
+    let v: Box<[int]> = ~[1, 2, 3];    // error
+
+    let v: Vec<int> = vec!(1, 2, 3);    // ok
+"#),
+    ("E0056", r#"
+A string was put behind a managed pointer. Managed strings are no longer
+supported:
+
+    let s: @str = "hello";    // error: managed strings are not supported
+
+Use an owned string, `~str` or `StrBuf`, or put one in an `Rc`.
+"#),
+    ("E0057", r#"
+A trait object was put behind a pointer other than `~` or `&`, such as a
+managed pointer:
+
+    trait Foo {}
+
+    fn f(x: @Foo) {}    // error: ~trait or &trait are the only supported
+                        // forms of casting-to-trait
+
+Use `~Foo`, `Box<Foo>` or `&Foo` instead.
+"#),
+    ("E0058", r#"
+The type `[T]` was used on its own. A vector's size isn't known, so it can
+only be used behind a pointer:
+
+    fn foo(v: [int]) {}    // error: bare `[]` is not a type
+
+Use a slice, `&[int]`, or a `Vec<int>`.
+"#),
+    ("E0059", r#"
+A bare function type was declared variadic, with `...`, but doesn't use the C
+calling convention. Only C functions can take a variable number of
+arguments:
+
+    type F = fn(x: int, ...);    // error
+
+    type F = extern "C" fn(x: int, ...);    // ok
+"#),
+    ("E0060", r#"
+A type that isn't a trait was given kind bounds, such as `:Send`. Only trait
+objects and closures can have bounds:
+
+    fn foo(x: Box<int:Send>) {}    // error: kind bounds can only be used on
+                                   // trait types
+"#),
+    ("E0061", r#"
+A trait was used where a type is expected. A trait on its own isn't a type;
+it can only be used as a type behind a pointer, as a trait object:
+
+    trait Shape {}
+
+    fn draw(s: Shape) {}    // error: reference to trait `Shape` where a type
+                            // is expected
+
+Use `~Shape` or `&Shape` to take a trait object, or a type parameter bounded
+by the trait, as in `fn draw<S: Shape>(s: S)`.
+"#),
+    ("E0062", r#"
+A trait object or closure type was given a lifetime bound other than
+`'static`, which isn't supported yet:
+
+    trait Shape {}
+
+    fn foo<'a>(s: Box<Shape:'a>) {}    // error: only the 'static lifetime
+                                       // is accepted here.
+"#),
+    ("E0063", r#"
+A pattern for an enum variant or a tuple struct had a different number of
+fields than the variant or struct has:
+
+    enum Shape { Rect(int, int) }
+
+    match s {
+        Rect(w) => {}    // error: this pattern has 1 field, but the
+                         // corresponding variant has 2 fields
+    }
+
+Use `_` for fields that aren't needed, or `Rect(..)` to ignore all of them.
+"#),
+    ("E0064", r#"
+A pattern for an enum variant or a struct that has no fields gave it some:
+
+    enum Shape { Empty }
+
+    match s {
+        Empty(x) => {}    // error: this pattern has 1 field, but the
+                          // corresponding variant has no fields
+    }
+"#),
+    ("E0065", r#"
+A struct pattern mentioned the same field twice:
+
+    struct Point { x: int, y: int }
+
+    let Point { x: a, x: b } = p;    // error: field `x` bound twice in
+                                     // pattern
+"#),
+    ("E0066", r#"
+A struct pattern mentioned a field that the struct doesn't have:
+
+    struct Point { x: int, y: int }
+
+    let Point { x, z } = p;    // error: struct `Point` does not have a field
+                               // named `z`
+"#),
+    ("E0067", r#"
+A struct pattern didn't mention every field of the struct:
+
+    struct Point { x: int, y: int }
+
+    let Point { x } = p;    // error: pattern does not mention field `y`
+
+Add `..` to the end of the pattern to ignore the remaining fields:
+
+    let Point { x, .. } = p;
+"#),
+    ("E0068", r#"
+A pattern doesn't have the same type as the value it is matched against:
+
+    let x: Option<int> = None;
+    match x {
+        Ok(y) => {}    // error: expected `std::option::Option<int>` but
+                       // found `std::result::Result`
+        _ => {}
+    }
+"#),
+    ("E0069", r#"
+A struct pattern matched against an enum named a struct, or a variant of a
+different enum:
+
+    enum Shape { Circle { r: int } }
+    struct Point { x: int, y: int }
+
+    match shape {
+        Point { x, y } => {}    // error: mismatched types: expected `Shape`
+                                // but found `Point`
+    }
+"#),
+    ("E0070", r#"
+A range pattern was used with a type that isn't a number or a character.
+Only numeric and character ranges can be matched:
+
+    match s {
+        "a" .. "z" => {}    // error: non-numeric type used in range
+        _ => {}
+    }
+"#),
+    ("E0071", r#"
+The lower bound of a range pattern was greater than its upper bound, so it
+matches nothing:
+
+    match x {
+        10 .. 1 => {}    // error: lower range bound must be less than upper
+        _ => {}
+    }
+"#),
+    ("E0072", r#"
+The two ends of a range pattern had different types:
+
+    match x {
+        1 .. 'z' => {}    // error: mismatched types in range
+        _ => {}
+    }
+"#),
+    ("E0073", r#"
+A method call matched methods of several traits that are in scope and
+implemented for the receiver's type, so it is ambiguous:
+
+    trait Foo { fn go(&self); }
+    trait Bar { fn go(&self); }
+    impl Foo for int { fn go(&self) {} }
+    impl Bar for int { fn go(&self) {} }
+
+    let x: int = 5;
+    x.go();    // error: multiple applicable methods in scope
+
+Call the method through its trait instead, with a function that takes the
+trait as a bound, or remove one of the traits from scope.
+"#),
+    ("E0074", r#"
+A method call gave type parameters to a method that doesn't have any:
+
+    struct Foo;
+    impl Foo { fn bar(&self) {} }
+
+    Foo.bar::<int>();    // error: this method does not take type parameters
+"#),
+    ("E0075", r#"
+A method call gave a different number of type parameters than the method
+declares:
+
+    struct Foo;
+    impl Foo { fn bar<T>(&self, x: T) {} }
+
+    Foo.bar::<int, int>(1);    // error: incorrect number of type parameters
+                               // given for this method
+"#),
+    ("E0076", r#"
+A static method, which has no `self` argument, was called through a trait
+object. There is no value to take the method from:
+
+    trait Foo { fn new() -> int; }
+
+    fn f(x: &Foo) {
+        x.new();    // error: cannot call a method without a receiver through
+                    // an object
+    }
+"#),
+    ("E0077", r#"
+A method that takes `self` by value was called through a trait object. The
+object's value can't be moved out from behind the pointer:
+
+    trait Foo { fn consume(self); }
+
+    fn f(x: &Foo) {
+        x.consume();    // error: cannot call a method with a by-value
+                        // receiver through an object
+    }
+
+Take `~self` or `&self` instead.
+"#),
+    ("E0078", r#"
+A method whose argument or return types mention `Self` was called through a
+trait object. The object's type isn't known, so the method's type isn't
+either:
+
+    trait Foo { fn same(&self, other: &Self) -> bool; }
+
+    fn f(x: &Foo, y: &Foo) {
+        x.same(y);    // error: cannot call a method whose type contains a
+                      // self-type through an object
+    }
+"#),
+    ("E0079", r#"
+A method with type parameters was called through a trait object. Methods of
+an object are looked up at runtime, and a generic method would need a
+different implementation for every type:
+
+    trait Foo { fn bar<T>(&self, x: T); }
+
+    fn f(x: &Foo) {
+        x.bar(5);    // error: cannot call a generic method through an object
+    }
+"#),
+    ("E0080", r#"
+The `drop` method of the `Drop` trait was called explicitly. Destructors run
+automatically when a value goes out of scope:
+
+    struct Foo;
+    impl Drop for Foo { fn drop(&mut self) {} }
+
+    Foo.drop();    // error: explicit call to destructor
+
+To destroy a value early, pass it to `drop`, as in `drop(foo)`.
+"#),
+    ("E0081", r#"
+A struct that inherits from a virtual struct declared a field with the same
+name as a field of the super-struct:
+
+    #![feature(struct_inherit)]
+    virtual struct Base { x: int }
+
+    struct Derived : Base { x: int }    // error: field `x` hides field
+                                        // declared in super-struct
+"#),
+    ("E0082", r#"
+A field of a struct other than the last had a dynamically sized type. The
+offset of later fields couldn't be known:
+
+    struct Foo<type T> {
+        x: T,      // error: type `x` is dynamically sized
+        y: int,
+    }
+
+Move the field to the end of the struct.
+"#),
+    ("E0083", r#"
+A field of a tuple struct other than the last had a dynamically sized type.
+The offset of later fields couldn't be known:
+
+    struct Foo<type T>(T, int);    // error: dynamically sized type in field
+
+Move the field to the end of the struct.
+"#),
+    ("E0084", r#"
+A function in an `extern` block declared type parameters. Foreign functions
+are compiled elsewhere and can't be generic:
+
+    extern {
+        fn foo<T>(x: T);    // error: foreign items may not have type
+                            // parameters
+    }
+"#),
+    ("E0085", r#"
+A function in an `extern` block with a calling convention other than C was
+declared variadic, with `...`. Only C functions can take a variable number
+of arguments:
+
+    extern "stdcall" {
+        fn foo(x: int, ...);    // error
+    }
+"#),
+    ("E0086", r#"
+An implementation of a trait defined a method that the trait doesn't
+declare:
+
+    trait Foo {
+        fn foo(&self);
+    }
+
+    impl Foo for int {
+        fn foo(&self) {}
+        fn bar(&self) {}    // error: method `bar` is not a member of trait `Foo`
+    }
+
+Move the extra method into an inherent `impl int { ... }` block, or add it
+to the trait.
+"#),
+    ("E0087", r#"
+An implementation of a trait didn't define every method of the trait that
+has no default implementation:
+
+    trait Foo {
+        fn foo(&self);
+        fn bar(&self);
+    }
+
+    impl Foo for int {    // error: missing: bar
+        fn foo(&self) {}
+    }
+"#),
+    ("E0088", r#"
+A method in an implementation of a trait takes `self`, but the trait's
+declaration of it doesn't:
+
+    trait Foo {
+        fn new() -> Self;
+    }
+
+    impl Foo for int {
+        fn new(&self) -> int { 0 }    // error: method `new` has a `&self`
+                                      // declaration in the impl, but not in
+                                      // the trait
+    }
+"#),
+    ("E0089", r#"
+A method in an implementation of a trait doesn't take `self`, but the
+trait's declaration of it does:
+
+    trait Foo {
+        fn get(&self) -> int;
+    }
+
+    impl Foo for int {
+        fn get() -> int { 0 }    // error: method `get` has a `&self`
+                                 // declaration in the trait, but not in the
+                                 // impl
+    }
+"#),
+    ("E0090", r#"
+A method in an implementation of a trait has a different number of type
+parameters than the trait's declaration of it:
+
+    trait Foo {
+        fn foo<T>(&self, x: T);
+    }
+
+    impl Foo for int {
+        fn foo(&self, x: int) {}    // error: method `foo` has 0 type
+                                    // parameters, but its trait declaration
+                                    // has 1 type parameter
+    }
+"#),
+    ("E0091", r#"
+A method in an implementation of a trait takes a different number of
+arguments than the trait's declaration of it:
+
+    trait Foo {
+        fn foo(&self, x: int);
+    }
+
+    impl Foo for int {
+        fn foo(&self) {}    // error
+    }
+
+The method must have the same signature as in the trait.
+"#),
+    ("E0092", r#"
+A type parameter of a method in an implementation of a trait has a bound
+that the trait's declaration of the method doesn't. Callers only know about
+the bounds in the trait:
+
+    trait Foo {
+        fn foo<T>(&self, x: T);
+    }
+
+    impl Foo for int {
+        fn foo<T: Clone>(&self, x: T) {}    // error: type parameter 0
+                                            // requires `Clone`, which is not
+                                            // required by the trait
+    }
+"#),
+    ("E0093", r#"
+A type parameter of a method in an implementation of a trait has a
+different number of bounds than in the trait's declaration of the method:
+
+    trait Foo {
+        fn foo<T: Clone + Eq>(&self, x: T);
+    }
+
+    impl Foo for int {
+        fn foo<T: Clone>(&self, x: T) {}    // error: type parameter 0 has 1
+                                            // trait bound, but the trait
+                                            // declaration has 2
+    }
+"#),
+    ("E0094", r#"
+A method in an implementation of a trait has a different type than the
+trait's declaration of it, for example an argument or the return type
+differs, or one takes `&mut self` where the other takes `&self`:
+
+    trait Foo {
+        fn foo(&self) -> int;
+    }
+
+    impl Foo for int {
+        fn foo(&self) -> uint { 0 }    // error
+    }
+"#),
+    ("E0095", r#"
+Dereferencing a value to find a field or a method went through so many
+pointers, or implementations of `Deref`, that the compiler gave up. This
+usually means a type dereferences to itself:
+
+    struct Foo;
+    impl Deref<Foo> for Foo {
+        fn deref<'a>(&'a self) -> &'a Foo { self }
+    }
+
+    Foo.bar();    // error: reached the recursion limit while
+                  // auto-dereferencing Foo
+"#),
+    ("E0096", r#"
+A path to a static method gave lifetime parameters to a segment other than
+the last two, such as a module:
+
+    let x = foo<'a>::Bar::new();    // error: lifetime parameters may not
+                                    // appear here
+"#),
+    ("E0097", r#"
+A path to a static method gave type parameters to a segment other than the
+last two, such as a module:
+
+    let x = std::<int>::vec::Vec::new();    // error: type parameters may not
+                                            // appear here
+"#),
+    ("E0098", r#"
+A path to a static method gave the trait or type it is declared in a
+different number of lifetime parameters than it declares:
+
+    struct Foo<'a> { x: &'a int }
+    impl<'a> Foo<'a> { fn new(x: &'a int) -> Foo<'a> { Foo { x: x } } }
+
+    Foo::<'a, 'b>::new(&5);    // error: expected 1 lifetime parameter,
+                               // found 2 lifetime parameters
+"#),
+    ("E0099", r#"
+A path to a static method gave the trait or type it is declared in fewer
+type parameters than it needs:
+
+    trait Make<T, U> { fn make() -> (T, U); }
+
+    let x = Make::<int>::make();    // error: the trait referenced by this
+                                    // path needs 2 type parameters, but 1
+                                    // type parameter was supplied
+"#),
+    ("E0100", r#"
+A path to a static method gave the trait or type it is declared in more
+type parameters than it declares:
+
+    trait Make<T> { fn make() -> T; }
+
+    let x = Make::<int, int>::make();    // error: the trait referenced by
+                                         // this path needs 1 type parameter,
+                                         // but 2 type parameters were
+                                         // supplied
+"#),
+    ("E0101", r#"
+A path to a function gave lifetime parameters to the module the function is
+in:
+
+    let x = foo::<'a>::bar();    // error: lifetime parameters may not appear
+                                 // here
+"#),
+    ("E0102", r#"
+A path to a function gave type parameters to the module the function is in:
+
+    let x = foo::<int>::bar();    // error: type parameters may not appear
+                                  // here
+
+Give the type parameters to the function instead: `foo::bar::<int>()`.
+"#),
+    ("E0103", r#"
+A variadic function was called with fewer arguments than the ones it
+declares before the `...`:
+
+    extern { fn printf(fmt: *u8, ...) -> int; }
+
+    unsafe { printf(); }    // error: this function takes at least 1
+                            // parameter but 0 parameters were supplied
+"#),
+    ("E0104", r#"
+A function was called with a different number of arguments than it
+declares:
+
+    fn foo(x: int) {}
+
+    foo(1, 2);    // error: this function takes 1 parameter but 2 parameters
+                  // were supplied
+"#),
+    ("E0105", r#"
+A struct expression gave a value for the same field twice:
+
+    struct Point { x: int, y: int }
+
+    let p = Point { x: 1, x: 2, y: 3 };    // error: field `x` specified more
+                                           // than once
+"#),
+    ("E0106", r#"
+A struct expression didn't give a value for every field of the struct:
+
+    struct Point { x: int, y: int }
+
+    let p = Point { x: 1 };    // error: missing field: y
+
+Give every field a value, or use functional record update syntax to take
+the remaining fields from another value: `Point { x: 1, .. origin }`.
+"#),
+    ("E0107", r#"
+A string literal was written with a `&` or `~` in front of it. String
+literals are already `&'static str`, and the prefixed forms were removed:
+
+    let s = ~"hello";    // error: `~"string"` has been removed; use
+                         // `"string".to_owned()` instead
+
+Write `"hello"` for a slice or `"hello".to_owned()` for an owned string.
+"#),
+    ("E0108", r#"
+A managed box, `box(GC) expr`, was created in a crate where the `gc` lang
+item isn't defined, which usually means the crate doesn't link to `std`:
+
+    #![no_std]
+
+    let x = box(GC) 5;    // error: requires `gc` lang_item
+
+Link to `std`, or avoid managed boxes in crates that don't.
+"#),
+    ("E0109", r#"
+A `box` expression named a place other than the exchange heap or the managed
+heap. Those are the only places a value can be boxed in:
+
+    let x = box(foo) 5;    // error: only the managed heap and exchange heap
+                           // are currently supported
+
+Use `box 5` for a `Box<int>`, or `box(GC) 5` for a managed box.
+"#),
+    ("E0110", r#"
+The left-hand side of a compound assignment, such as `+=`, isn't a place
+that can be assigned to:
+
+    fn foo() -> int { 1 }
+
+    foo() += 1;    // error: illegal left-hand side expression
+
+Assign to a variable, a field, an index or a dereference instead.
+"#),
+    ("E0111", r#"
+A tuple struct with a single field was dereferenced with `*` to reach the
+field. That used to be allowed, but the field must now be named by
+destructuring:
+
+    struct Meters(int);
+
+    let m = Meters(5);
+    let x = *m;    // error: single-field tuple-structs can no longer be
+                   // dereferenced
+
+    let Meters(x) = m;    // ok
+"#),
+    ("E0112", r#"
+A function with a return type other than `()` used `return` without a
+value:
+
+    fn foo() -> int {
+        return;    // error: `return;` in function returning non-nil
+    }
+"#),
+    ("E0113", r#"
+The left-hand side of an assignment isn't a place that can be assigned to:
+
+    fn foo() -> int { 1 }
+
+    foo() = 1;    // error: illegal left-hand side expression
+
+Assign to a variable, a field, an index or a dereference instead.
+"#),
+    ("E0114", r#"
+A value was cast to `bool`. Numbers can't be converted to `bool` with `as`:
+
+    let b = x as bool;    // error: cannot cast as `bool`, compare with zero
+                          // instead
+
+    let b = x != 0;    // ok
+"#),
+    ("E0115", r#"
+A struct or an enum contained itself directly, so it would have infinite
+size:
+
+    enum List {
+        Cons(int, List),    // error: illegal recursive enum type
+        Nil,
+    }
+
+Put the recursive value behind a pointer, which has a known size:
+
+    enum List {
+        Cons(int, Box<List>),
+        Nil,
+    }
+"#),
+    ("E0116", r#"
+A struct contained itself, behind a pointer, in a way that means a value of
+it can never be built, since every value needs another one first:
+
+    struct Foo {
+        next: Box<Foo>,    // error: this type cannot be instantiated without
+                           // an instance of itself
+    }
+
+Make the recursion optional: `next: Option<Box<Foo>>`.
+"#),
+    ("E0117", r#"
+A struct marked `#[simd]` had type parameters. SIMD vectors must have one
+concrete element type:
+
+    #[simd]
+    struct Vec4<T>(T, T, T, T);    // error: SIMD vector cannot be generic
+"#),
+    ("E0118", r#"
+A struct marked `#[simd]` had no fields:
+
+    #[simd]
+    struct Empty;    // error: SIMD vector cannot be empty
+"#),
+    ("E0119", r#"
+The fields of a struct marked `#[simd]` had different types. Every element
+of a SIMD vector has the same type:
+
+    #[simd]
+    struct Mixed(f32, f64);    // error: SIMD vector should be homogeneous
+"#),
+    ("E0120", r#"
+The fields of a struct marked `#[simd]` had a type that isn't a machine
+integer or float, such as a struct:
+
+    struct Point { x: int, y: int }
+
+    #[simd]
+    struct Points(Point, Point);    // error: SIMD vector element type should
+                                    // be machine type
+"#),
+    ("E0121", r#"
+An argument of an enum variant other than the last had a dynamically sized
+type. The offset of later arguments couldn't be known:
+
+    enum Foo<type T> {
+        Bar(T, int),    // error: type `T` is dynamically sized
+    }
+
+Move the argument to the end of the variant.
+"#),
+    ("E0122", r#"
+The discriminant of an enum variant was a constant that isn't an integer:
+
+    enum Foo {
+        A = 1.5,    // error: expected signed integer constant
+    }
+"#),
+    ("E0123", r#"
+The discriminant of an enum variant couldn't be evaluated at compile time:
+
+    fn one() -> int { 1 }
+
+    enum Foo {
+        A = one(),    // error: expected constant: ...
+    }
+
+Use a literal or an expression of `static` values.
+"#),
+    ("E0124", r#"
+Two variants of an enum had the same discriminant:
+
+    enum Foo {
+        A = 1,
+        B = 1,    // error: discriminant value already exists
+    }
+
+This includes variants without an explicit value, which take the previous
+variant's value plus one.
+"#),
+    ("E0125", r#"
+The discriminant of an enum variant doesn't fit in the integer type given
+with `#[repr]`:
+
+    #[repr(u8)]
+    enum Foo {
+        A = 256,    // error: discriminant value outside specified type
+    }
+"#),
+    ("E0126", r#"
+An enum with one variant or none was given a `#[repr]` attribute. Such an
+enum has no discriminant to represent:
+
+    #[repr(u8)]
+    enum Foo {    // error: unsupported representation for univariant enum
+        A,
+    }
+"#),
+    ("E0127", r#"
+A path to a value, such as a function or an enum variant, gave a type a
+different number of lifetime parameters than it declares:
+
+    struct Foo<'a> { x: &'a int }
+    fn new<'a>(x: &'a int) -> Foo<'a> { Foo { x: x } }
+
+    let f = new::<'a, 'b>(&5);    // error: expected 1 lifetime parameter,
+                                  // found 2 lifetime parameters
+"#),
+    ("E0128", r#"
+A generic function or type was used without some of its type parameters,
+relying on their defaults, in an expression. Default type parameters are
+experimental and must be enabled in the crate:
+
+    struct Foo<T, U = int> { x: T, y: U }
+
+    let f = Foo::<u8> { x: 1, y: 2 };    // error: default type parameters
+                                         // are experimental
+
+Add `#![feature(default_type_params)]` to the crate attributes, or give all
+the type parameters.
+"#),
+    ("E0129", r#"
+A type alias declared a type parameter that its definition doesn't use:
+
+    type Foo<T> = int;    // error: type parameter `T` is unused
+
+Remove the type parameter.
+"#),
+    ("E0130", r#"
+A function declared in an `extern "rust-intrinsic"` block with a name
+starting with `atomic_` isn't one of the atomic operations the compiler
+knows:
+
+    extern "rust-intrinsic" {
+        fn atomic_foo<T>(dst: *mut T, src: T) -> T;    // error: unrecognized
+                                                       // atomic operation
+                                                       // function: `foo`
+    }
+"#),
+    ("E0131", r#"
+A function declared in an `extern "rust-intrinsic"` block isn't an intrinsic
+the compiler knows:
+
+    extern "rust-intrinsic" {
+        fn foo();    // error: unrecognized intrinsic function: `foo`
+    }
+
+Intrinsics are declared in `std::intrinsics`; use them from there.
+"#),
+    ("E0132", r#"
+An intrinsic was declared with a different number of type parameters than
+the compiler expects for it:
+
+    extern "rust-intrinsic" {
+        fn size_of() -> uint;    // error: intrinsic has wrong number of type
+                                 // parameters: found 0, expected 1
+    }
+"#),
+    ("E0133", r#"
+A value was used where an implementation of a trait is required, but the
+implementation found has different type parameters than the ones needed.
+This happens, for example, when a closure or an object is used with a trait
+that is implemented for another instance of a generic trait:
+
+    trait Convert<T> { fn convert(&self) -> T; }
+    impl Convert<int> for u8 { fn convert(&self) -> int { *self as int } }
+
+    let x: &Convert<uint> = &1u8;    // error: expected Convert<uint>, but
+                                     // found Convert<int>
+"#),
+    ("E0134", r#"
+Several implementations of a trait matched the type that a call needs the
+trait for, so it's ambiguous which one to use:
+
+    trait Show2 { fn show(&self); }
+    impl<T> Show2 for Option<T> { fn show(&self) {} }
+    impl Show2 for Option<int> { fn show(&self) {} }
+
+    fn f<T: Show2>(x: T) { x.show() }
+
+    f(Some(5));    // error: multiple applicable methods in scope
+
+Remove one of the overlapping implementations.
+"#),
+    ("E0135", r#"
+An immutable reference was cast to a mutable trait object:
+
+    trait Foo {}
+    impl Foo for int {}
+
+    let x = 5;
+    let o = &x as &mut Foo;    // error: types differ in mutability
+
+Take a mutable reference, `&mut x`, or cast to `&Foo`.
+"#),
+    ("E0136", r#"
+A value was cast to an owned trait object, `Box<Trait>`, but isn't an owned
+box:
+
+    trait Foo {}
+    impl Foo for int {}
+
+    let o = 5 as Box<Foo>;    // error: can only cast an boxed pointer to a
+                              // boxed object, not a int
+
+Box the value first: `box 5 as Box<Foo>`.
+"#),
+    ("E0137", r#"
+A value was cast to a borrowed trait object, `&Trait`, but isn't a reference:
+
+    trait Foo {}
+    impl Foo for int {}
+
+    let o = 5 as &Foo;    // error: can only cast an &-pointer to an
+                          // &-object, not a int
+
+Borrow the value first: `&5 as &Foo`.
+"#),
+    ("E0138", r#"
+A function pointer that is stored in a variable was used where a closure is
+expected. Only functions named directly can be turned into closures:
+
+    fn foo(x: int) -> int { x }
+    fn call(f: |int| -> int) -> int { f(1) }
+
+    let g = foo;
+    call(g);    // error: cannot coerce non-statically resolved bare fn
+
+Pass the function directly, as in `call(foo)`, or wrap it in a closure:
+`call(|x| g(x))`.
+"#),
+    ("E0139", r#"
+The type of an expression couldn't be inferred, because nothing constrains
+it:
+
+    let v = Vec::new().len();    // error: cannot determine a type for this
+                                 // expression
+
+Annotate a type, as in `Vec::<int>::new()`.
+"#),
+    ("E0140", r#"
+The type of a local variable couldn't be inferred, because nothing
+constrains it:
+
+    let v = Vec::new();    // error: cannot determine a type for this local
+                           // variable
+
+Annotate the variable's type, as in `let v: Vec<int> = Vec::new();`.
+"#),
+    ("E0141", r#"
+The type of a variable bound in a pattern couldn't be inferred, because
+nothing constrains it:
+
+    let (a, b) = (Vec::new(), 5);    // error: cannot determine a type for
+                                     // this pattern binding
+
+Annotate the type of the value being matched.
+"#),
+    ("E0142", r#"
+A variable captured by a closure is borrowed, but no lifetime for that
+borrow satisfies every place the closure is used. This usually accompanies
+a lifetime error reported for the same closure:
+
+    let mut x = 5;
+    let f = || x += 1;    // error: cannot resolve lifetime for captured
+                          // variable `x`
+"#),
+    ("E0143", r#"
+An inherent `impl`, one without a trait, was given for a type defined in
+another crate. Only the crate that defines a type can add methods to it
+directly:
+
+    // error: cannot associate methods with a type outside the crate the
+    // type is defined in
+    impl Vec<int> {
+        fn sum(&self) -> int { 0 }
+    }
+
+Define a trait with the methods and implement it for the type:
+
+    trait Sum { fn sum(&self) -> int; }
+    impl Sum for Vec<int> { fn sum(&self) -> int { 0 } }
+"#),
+    ("E0144", r#"
+An implementation of a trait was given for a type, but neither the trait
+nor the type is defined in the current crate:
+
+    impl Clone for ~str { ... }    // error
+
+Allowing this would let two crates provide conflicting implementations. To
+implement a foreign trait for a foreign type, wrap the type in a new type
+defined in this crate:
+
+    struct MyStr(~str);
+    impl Clone for MyStr { ... }
+"#),
+    ("E0145", r#"
+An inherent `impl`, one without a trait, was given for a type that can't
+have methods of its own, such as a primitive type or a tuple:
+
+    // error: no base type found for inherent implementation
+    impl (int, int) {
+        fn sum(&self) -> int { 0 }
+    }
+
+Define a trait with the methods and implement it for the type, or wrap the
+type in a new struct.
+"#),
+    ("E0146", r#"
+A trait was implemented more than once for the same type, which would make
+calls to its methods ambiguous:
+
+    trait Foo { fn foo(&self); }
+
+    impl Foo for int { fn foo(&self) {} }
+    impl Foo for int { fn foo(&self) {} }    // error
+
+This includes generic implementations that overlap with another one, such
+as `impl<T> Foo for T` and `impl Foo for int`.
+"#),
+    ("E0147", r#"
+The `Drop` trait was implemented for a type other than a struct, such as an
+enum. Only structs can have destructors:
+
+    enum Foo { A, B }
+
+    // error: the Drop trait may only be implemented on structures
+    impl Drop for Foo {
+        fn drop(&mut self) {}
+    }
+"#),
+    ("E0148", r#"
+The type placeholder `_` was used in the signature of an item. Item
+signatures must be written out, since they are checked before the items'
+bodies:
+
+    fn foo() -> _ { 5 }    // error: the type placeholder `_` is not allowed
+                           // within types on item signatures.
+"#),
+    ("E0149", r#"
+A trait listed the same supertrait twice:
+
+    trait Foo : Clone + Clone {}    // error: duplicate supertrait in trait
+                                    // declaration
+"#),
+    ("E0150", r#"
+An implementation of a trait defined the same method twice:
+
+    trait Foo { fn foo(&self); }
+
+    impl Foo for int {
+        fn foo(&self) {}
+        fn foo(&self) {}    // error: duplicate method in trait impl
+    }
+"#),
+    ("E0151", r#"
+A type parameter of a struct, an enum or a type alias had trait bounds.
+Bounds go on the functions and impls that need them:
+
+    struct Foo<T: Clone> { x: T }    // error: trait bounds are not allowed in
+                                     // structure definitions
+
+    struct Foo<T> { x: T }
+    impl<T: Clone> Foo<T> { ... }    // ok
+"#),
+    ("E0152", r#"
+A function in an `extern` block declared type parameters. Foreign functions
+are compiled elsewhere and can't be generic:
+
+    extern {
+        fn foo<T>(x: T);    // error: foreign functions may not use type
+                            // parameters
+    }
+"#),
+    ("E0153", r#"
+A struct declared the same field twice:
+
+    struct Point {
+        x: int,
+        x: int,    // error: field `x` is already declared
+    }
+"#),
+    ("E0154", r#"
+A struct inherited from a struct that isn't declared `virtual`:
+
+    #![feature(struct_inherit)]
+    struct Base { x: int }
+
+    struct Derived : Base { y: int }    // error: struct inheritance is only
+                                        // allowed from virtual structs
+
+Declare the super-struct as `virtual struct Base { x: int }`.
+"#),
+    ("E0155", r#"
+The default of a type parameter referred to a type parameter declared after
+it:
+
+    struct Foo<T = U, U = int> { x: T, y: U }    // error: type parameters
+                                                 // with a default cannot use
+                                                 // forward declared
+                                                 // identifiers
+
+Reorder the parameters so that the ones a default uses come first.
+"#),
+    ("E0156", r#"
+A type parameter was given a lifetime bound other than `'static`, which
+isn't supported yet:
+
+    fn foo<'a, T: 'a>(x: T) {}    // error: only the 'static lifetime is
+                                  // accepted here.
+"#),
+    ("E0157", r#"
+A type parameter declared with `type`, which allows dynamically sized types,
+was also bounded by a trait that requires its implementors to be sized:
+
+    trait Foo : Sized {}
+
+    fn foo<type T: Foo>(x: &T) {}    // error: incompatible bounds on type
+                                     // parameter T, bound Foo does not allow
+                                     // unsized type
+"#),
+    ("E0158", r#"
+A function in an `extern` block used a pattern other than a name for an
+argument. Foreign functions have no body that could use the pattern:
+
+    extern {
+        fn foo((a, b): (int, int));    // error: patterns aren't allowed in
+                                       // foreign function declarations
+    }
+"#),
+    ("E0159", r#"
+An expression had a type other than the one required by the context it is
+used in, for example an argument of the wrong type or a function body of the
+wrong return type:
+
+    fn foo(x: int) {}
+
+    foo("hello");    // error: mismatched types: expected `int` but found
+                     // `&'static str`
+
+The message names the expected and found types. There are no implicit
+conversions between numeric types, so an `as` cast is needed to pass a
+`uint` where an `int` is expected.
+"#),
+    ("E0160", r#"
+A reference was reborrowed for longer than the reference it was taken from
+is valid. The notes say how long each of them is valid for:
+
+    fn first<'a, 'b>(x: &'a &'b int) -> &'b int {
+        &**x    // error: lifetime of reference outlines lifetime of
+                // borrowed content...
+    }
+
+Here the result can only live as long as `'a`, the shorter of the two.
+"#),
+    ("E0161", r#"
+A closure returned or stored a reference into a variable it captured, which
+would outlive the variable:
+
+    fn make() -> &int {
+        let x = 5;
+        let f = || &x;    // error: lifetime of borrowed pointer outlives
+        f()               // lifetime of captured variable `x`...
+    }
+"#),
+    ("E0162", r#"
+A stack closure, `||`, was stored or returned somewhere that lives longer
+than the stack frame it was created in:
+
+    fn make<'a>() -> ||:'a -> int {
+        let x = 5;
+        || x    // error: closure outlives stack frame
+    }
+
+Use a `proc`, which owns what it captures, to return a closure.
+"#),
+    ("E0163", r#"
+A stack closure was called in a place where it isn't valid any more. This
+follows from the lifetime of the reference the closure was reached through:
+
+    fn call<'a>(f: &'a ||) -> &'a || { f }
+
+    (*call(&|| {}))();    // error: cannot invoke closure outside of its
+                          // lifetime
+"#),
+    ("E0164", r#"
+A reference was dereferenced in a place where it isn't valid any more, which
+can happen when a reference is stored in a structure that outlives what it
+points at:
+
+    let r;
+    {
+        let x = 5;
+        r = &x;
+    }
+    let y = *r;    // error: dereference of reference outside its lifetime
+"#),
+    ("E0165", r#"
+A closure that captures a variable by reference was stored or returned
+somewhere that lives longer than the variable:
+
+    fn make<'a>() -> ||:'a -> int {
+        let x = 5;
+        || x + 1    // error: captured variable `x` does not outlive the
+                    // enclosing closure
+    }
+"#),
+    ("E0166", r#"
+A slice was indexed in a place where it isn't valid any more, which can
+happen when the slice borrows a vector that has gone out of scope:
+
+    let s;
+    {
+        let v = vec!(1, 2, 3);
+        s = v.as_slice();
+    }
+    let x = s[0];    // error: index of slice outside its lifetime
+"#),
+    ("E0167", r#"
+A reference was turned into a trait object whose lifetime bound requires it
+to live longer than the reference does:
+
+    trait Foo {}
+    impl Foo for int {}
+
+    fn f<'a>(x: &'a int) -> &'static Foo {
+        x as &Foo    // error: lifetime of the source pointer does not
+                     // outlive lifetime bound of the object type
+    }
+"#),
+    ("E0168", r#"
+A method was called on a borrowed value that isn't valid for the whole
+call:
+
+    let s = {
+        let v = vec!(1, 2, 3);
+        v.as_slice()    // error: lifetime of method receiver does not
+    };                  // outlive the method call
+
+Keep the vector alive for as long as the result of the call is used.
+"#),
+    ("E0169", r#"
+A function was called with a reference argument that isn't valid for as
+long as the function's signature requires:
+
+    fn keep(x: &'static int) {}
+
+    let x = 5;
+    keep(&x);    // error: lifetime of function argument does not outlive the
+                 // function call
+"#),
+    ("E0170", r#"
+The result of a function call was used for longer than the lifetimes in the
+function's signature allow it to be valid:
+
+    fn id<'a>(x: &'a int) -> &'a int { x }
+
+    let r: &'static int = id(&5);    // error: lifetime of return value does
+                                     // not outlive the function call
+"#),
+    ("E0171", r#"
+A value was borrowed with `&` for longer than the value lives:
+
+    let r: &int;
+    {
+        let x = 5;
+        r = &x;    // error: reference is not valid at the time of borrow
+    }
+"#),
+    ("E0172", r#"
+A value was borrowed automatically, for a method call or a coercion, for
+longer than the value lives:
+
+    let r: &[int];
+    {
+        let v = ~[1, 2, 3];
+        r = v;    // error: automatically reference is not valid at the time
+    }             // of borrow
+"#),
+    ("E0173", r#"
+A variable's type contains a lifetime that doesn't include the place where
+the variable is declared:
+
+    fn f<'a>(x: &'a int) {
+        let y: &'static int = x;    // error: lifetime of variable does not
+                                    // enclose its declaration
+    }
+"#),
+    ("E0174", r#"
+A type contained a reference that is valid for longer than the data it
+points at, such as a `&'static` reference to data with a shorter lifetime:
+
+    fn f<'a>(x: &'static &'a int) {}    // error: in type
+                                        // `&'static &'a int`, pointer has a
+                                        // longer lifetime than the data it
+                                        // references
+"#),
+    ("E0175", r#"
+The compiler couldn't find a lifetime for a reference that satisfies all the
+places it is used. The notes say which requirements conflict:
+
+    fn longest<'a, 'b>(x: &'a str, y: &'b str) -> &'a str {
+        if x.len() > y.len() { x } else { y }    // error: cannot infer an
+                                                 // appropriate lifetime due
+                                                 // to conflicting
+                                                 // requirements
+    }
+
+Usually one lifetime parameter in the signature is needed where there are
+two, as in `fn longest<'a>(x: &'a str, y: &'a str) -> &'a str`.
+"#),
+    ("E0176", r#"
+An expression had a type that can't be used the way it was used, for
+example a method that isn't implemented for the type, or an operator the
+type doesn't support:
+
+    struct Foo;
+
+    let x = Foo + Foo;    // error: binary operation `+` cannot be applied
+                          // to type `Foo`
+
+The message names the type. Implement the trait for the operator or method,
+here `Add`, or convert the value to a type that supports it.
+"#),
+    ("E0177", r#"
+A pattern had a different shape than the type of the value it is matched
+against, such as a tuple pattern for a value that isn't a tuple, or a
+pointer pattern for a value that isn't a pointer:
+
+    let x = 5;
+    let (a, b) = x;    // error: mismatched types: expected `int` but found
+                       // tuple
+
+The message names the expected type and the kind of pattern found.
+"#),
+    ("E0178", r#"
+A function with a special role, such as `main` or a `#[start]` function, had
+a different type than that role requires:
+
+    fn main() -> int { 0 }    // error: main function expects type: `fn()`
+
+`main` takes no arguments and returns `()`. To set the exit status, use
+`std::os::set_exit_status`.
+"#),
+    ("E0179", r#"
+The `main` function declared type parameters. It is called by the runtime,
+which has no types to give them:
+
+    fn main<T>() {}    // error: main function is not allowed to have type
+                       // parameters
+"#),
+    ("E0180", r#"
+The function marked `#[start]` declared type parameters. It is called by the
+runtime, which has no types to give them:
+
+    #[start]
+    fn start<T>(argc: int, argv: **u8) -> int { 0 }    // error
+"#),
+    ("E0181", r#"
+An item has the `#[rustc_variance]` attribute, which is only used to test
+the compiler. The error reports the variance computed for the item's
+parameters:
+
+    #[rustc_variance]
+    struct Foo<'a, T> { x: &'a T }    // error: ItemVariances(...)
+
+Remove the attribute.
+"#),
+    ("E0182", r#"
+A value was borrowed mutably while a mutable borrow of it was still in use.
+Only one `&mut` reference to a value may exist at a time:
+
+    let mut v = vec!(1, 2, 3);
+    let a = &mut v;
+    let b = &mut v;    // error: cannot borrow `v` as mutable more than
+                       // once at a time
+
+Restrict the first borrow to a smaller block so that it ends before the
+second one begins.
+"#),
+    ("E0183", r#"
+A closure that needs unique access to a variable, because it mutates it, was
+created while the variable was borrowed:
+
+    let mut x = 5;
+    let r = &x;
+    let f = || x += 1;    // error: closure requires unique access to `x` but
+                          // it is already borrowed
+"#),
+    ("E0184", r#"
+A variable was borrowed while a closure that needs unique access to it, as
+it mutates the variable, still exists:
+
+    let mut x = 5;
+    let f = || x += 1;
+    let r = &x;    // error: cannot borrow `x` as immutable because previous
+                   // closure requires unique access
+"#),
+    ("E0185", r#"
+A value was borrowed in a way that conflicts with an earlier borrow that is
+still in use: it was borrowed mutably while it was borrowed immutably, or
+the other way around:
+
+    let mut x = 5;
+    let a = &x;
+    let b = &mut x;    // error: cannot borrow `x` as mutable because it is
+                       // also borrowed as immutable
+"#),
+    ("E0186", r#"
+A value that can't be changed, such as an immutable local variable captured
+by a closure or a field of one, was assigned to:
+
+    let x = 5;
+    let f = || x = 6;    // error: cannot assign to immutable captured outer
+                         // variable in a heap closure `x`
+
+Declare the variable with `let mut`.
+"#),
+    ("E0187", r#"
+A value reached through something that isn't mutable, such as a `&`
+reference or the result of a function call, was assigned to:
+
+    fn f(x: &int) {
+        *x = 6;    // error: cannot assign to immutable dereference of
+                   // `&`-pointer
+    }
+
+Take a `&mut` reference instead.
+"#),
+    ("E0188", r#"
+A value was assigned to while it was borrowed, which would change the data
+the reference points at:
+
+    let mut x = 5;
+    let a = &x;
+    x = 6;    // error: cannot assign to `x` because it is borrowed
+"#),
+    ("E0189", r#"
+A value was moved while it was borrowed, which would leave the reference
+pointing at a value that no longer exists:
+
+    let v = vec!(1, 2, 3);
+    let r = &v;
+    let w = v;    // error: cannot move out of `v` because it is borrowed
+"#),
+    ("E0190", r#"
+A variable was moved into a closure, such as a `proc`, while it was
+borrowed:
+
+    let v = vec!(1, 2, 3);
+    let r = &v;
+    let p = proc() { println!("{}", v); };    // error: cannot move `v` into
+                                              // closure because it is
+                                              // borrowed
+"#),
+    ("E0191", r#"
+A value was borrowed in a way its type or declaration doesn't allow, or a
+reference to it was kept for longer than the value lives. For example, an
+immutable variable was borrowed mutably:
+
+    let v = vec!(1, 2, 3);
+    v.push(4);    // error: cannot borrow immutable local variable `v` as
+                  // mutable
+
+Declare the variable with `let mut`. When the message says that a value
+does not live long enough, move its declaration to an outer scope.
+"#),
+    ("E0192", r#"
+A variable was used before it was given a value on every path to the use:
+
+    let x: int;
+    if cond { x = 1; }
+    println!("{}", x);    // error: use of possibly uninitialized variable:
+                          // `x`
+
+Give the variable a value on every path, or when it is declared.
+"#),
+    ("E0193", r#"
+A variable was used after its value had been moved out of it. Assigning,
+passing or returning a value whose type isn't `Copy` moves it, and the
+source can't be used afterwards:
+
+    let a = ~5;
+    let b = a;
+    println!("{}", a);    // error: use of moved value: `a`
+
+Borrow the value instead of moving it, with `let b = &a;`, or copy it
+explicitly with `a.clone()`.
+"#),
+    ("E0194", r#"
+A variable that isn't declared `mut` was assigned to after its
+initialization:
+
+    let x = 5;
+    x = 6;    // error: re-assignment of immutable variable `x`
+
+Declare it as `let mut x = 5;`.
+"#),
+    ("E0195", r#"
+Data that may be reachable through other references was mutated or borrowed
+mutably, for example through a `&` reference inside a structure:
+
+    fn f(x: &Vec<int>) {
+        x.push(1);    // error: cannot borrow data mutably in an aliasable
+                      // location
+    }
+
+Take a `&mut` reference instead.
+"#),
+    ("E0196", r#"
+A `static` was borrowed mutably. Statics are shared by the whole program, so
+they can't be changed through a reference:
+
+    static mut COUNT: int = 0;
+
+    let r = &mut COUNT;    // error: cannot borrow data mutably in a static
+                           // location
+"#),
+    ("E0197", r#"
+Data inside a managed box, `@T`, was borrowed mutably. There may be other
+pointers to the box, so its contents can't be changed:
+
+    let x = @5;
+    let r = &mut *x;    // error: cannot borrow data mutably in a `@` pointer
+
+Store a `RefCell` in the box to mutate its contents.
+"#),
+    ("E0198", r#"
+Data reached through a `&` reference was borrowed mutably. There may be
+other references to it, so it can't be changed:
+
+    fn f(v: &Vec<int>) {
+        let r = &mut *v;    // error: cannot borrow data mutably in a `&`
+                            // reference
+    }
+
+Take a `&mut` reference instead.
+"#),
+    ("E0199", r#"
+A `static` whose type contains an `Unsafe` cell, such as an atomic, was
+borrowed in a way that could let it be mutated without `unsafe`:
+
+    static FLAG: AtomicBool = INIT_ATOMIC_BOOL;
+
+    let f = &FLAG;    // error: borrow of immutable static items with unsafe
+                      // interior is not allowed
+
+Declare it `static mut` and access it in an `unsafe` block.
+"#),
+    ("E0200", r#"
+A value was moved out of a place that doesn't own it, such as a borrowed
+reference, a managed box or an index into a vector:
+
+    let v = vec!(~1, ~2);
+    let x = *v.get(0);    // error: cannot move out of dereference of
+                          // `&`-pointer
+
+Borrow the value instead, as in `let x = v.get(0);`, or clone it.
+"#),
+    ("E0201", r#"
+A field was moved out of a value whose type implements `Drop`. The
+destructor needs the whole value when it runs:
+
+    struct Foo { s: ~str }
+    impl Drop for Foo { fn drop(&mut self) {} }
+
+    let foo = Foo { s: "a".to_owned() };
+    let s = foo.s;    // error: cannot move out of type `Foo`, which defines
+                      // the `Drop` trait
+
+Clone the field, or borrow it, as in `let s = &foo.s;`.
+"#),
+];
+
+/// Looks up the explanation of an error code, returning `None` if the code
+/// isn't registered.
+pub fn lookup(code: &str) -> Option<&'static str> {
+    DIAGNOSTICS.iter().find(|&&(c, _)| c == code).map(|&(_, description)| description)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DIAGNOSTICS, lookup};
+
+    #[test]
+    fn test_registry_is_sorted() {
+        // which also means no code is registered twice
+        for pair in DIAGNOSTICS.windows(2) {
+            let ((a, _), (b, _)) = (pair[0], pair[1]);
+            assert!(a < b, "{} is registered after {}", b, a);
+        }
+    }
+
+    #[test]
+    fn test_explanations_have_examples() {
+        // Examples are indented by four spaces, as in rustdoc
+        for &(code, description) in DIAGNOSTICS.iter() {
+            assert!(description.lines().any(|line| line.starts_with("    ")),
+                    "the explanation of {} has no example", code);
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert!(lookup("E0001").unwrap().contains("same name"));
+        assert!(lookup("E0201").is_some());
+        assert!(lookup("E9999").is_none());
+        assert!(lookup("e0001").is_none());
+    }
+}
@@ -9,6 +9,7 @@
 // except according to those terms.
 
 
+use back::incremental;
 use back::link;
 use back::{arm, x86, x86_64, mips};
 use driver::session::{Aggressive, CrateTypeExecutable, CrateType,
@@ -24,12 +25,17 @@ use metadata::cstore::CStore;
 use metadata::creader::Loader;
 use metadata;
 use middle::{trans, freevars, kind, ty, typeck, lint, reachable};
+use middle::borrowck;
+use borrowck_dot = middle::borrowck::graphviz;
+use middle::cfg;
+use middle::cfg::graphviz::LabelledCFG;
 use middle::dependency_format;
 use middle;
 use util::common::time;
 use util::ppaux;
 use util::nodemap::{NodeMap, NodeSet};
 
+use dot = graphviz;
 use serialize::{json, Encodable};
 
 use std::cell::{Cell, RefCell};
@@ -40,6 +46,7 @@ use std::os;
 use getopts::{optopt, optmulti, optflag, optflagopt};
 use getopts;
 use syntax::ast;
+use syntax::ast_map;
 use syntax::abi;
 use syntax::attr;
 use syntax::attr::{AttrMetaMethods};
@@ -52,6 +59,8 @@ use syntax::parse;
 use syntax::parse::token::InternedString;
 use syntax::parse::token;
 use syntax::print::{pp, pprust};
+use syntax::visit;
+use syntax::visit::Visitor;
 use syntax;
 
 pub enum PpMode {
@@ -59,7 +68,14 @@ pub enum PpMode {
     PpmExpanded,
     PpmTyped,
     PpmIdentified,
-    PpmExpandedIdentified
+    PpmExpandedIdentified,
+    PpmFlowGraph(UserIdentifiedItem),
+}
+
+/// A function named on the command line, by node id or by path.
+pub enum UserIdentifiedItem {
+    ItemViaNode(ast::NodeId),
+    ItemViaPath(~str),
 }
 
 /**
@@ -387,6 +403,19 @@ pub fn phase_3_run_analysis_passes(sess: Session,
     }
 }
 
+/// Write out the definitions and references found by analysis, if
+/// `-Z save-analysis` was given.
+pub fn phase_save_analysis(sess: &Session,
+                           krate: &ast::Crate,
+                           analysis: &CrateAnalysis,
+                           outputs: &OutputFilenames) {
+    if !sess.debugging_opt(session::SAVE_ANALYSIS) {
+        return;
+    }
+    time(sess.time_passes(), "save analysis", krate, |krate|
+         middle::save::process_crate(sess, krate, analysis, outputs));
+}
+
 pub struct CrateTranslation {
     pub context: ContextRef,
     pub module: ModuleRef,
@@ -395,6 +424,9 @@ pub struct CrateTranslation {
     pub metadata: Vec<u8>,
     pub reachable: Vec<~str>,
     pub crate_formats: dependency_format::Dependencies,
+    /// The items translated or reused on their own when compiling
+    /// incrementally.
+    pub incremental: Vec<incremental::Item>,
 }
 
 /// Run the translation phase to LLVM, after which the AST and analysis can
@@ -573,6 +605,7 @@ pub fn compile_input(sess: Session, cfg: ast::CrateConfig, input: &Input,
         if stop_after_phase_2(&sess) { return; }
 
         let analysis = phase_3_run_analysis_passes(sess, &expanded_crate, ast_map);
+        phase_save_analysis(&analysis.ty_cx.sess, &expanded_crate, &analysis, &outputs);
         if stop_after_phase_3(&analysis.ty_cx.sess) { return; }
         let (tcx, trans) = phase_4_translate_to_llvm(expanded_crate,
                                                      analysis, &outputs);
@@ -663,7 +696,7 @@ pub fn pretty_print_input(sess: Session,
     let id = link::find_crate_id(krate.attrs.as_slice(), input.filestem());
 
     let (krate, ast_map, is_expanded) = match ppm {
-        PpmExpanded | PpmExpandedIdentified | PpmTyped => {
+        PpmExpanded | PpmExpandedIdentified | PpmTyped | PpmFlowGraph(..) => {
             let loader = &mut Loader::new(&sess);
             let (krate, ast_map) = phase_2_configure_and_expand(&sess, loader,
                                                                 krate, &id);
@@ -713,6 +746,11 @@ pub fn pretty_print_input(sess: Session,
                                 &annotation,
                                 is_expanded)
         }
+        PpmFlowGraph(ref item) => {
+            let ast_map = ast_map.expect("--pretty flowgraph missing ast_map");
+            let analysis = phase_3_run_analysis_passes(sess, &krate, ast_map);
+            print_flowgraph(analysis, &krate, item, out)
+        }
         _ => {
             pprust::print_crate(sess.codemap(),
                                 sess.diagnostic(),
@@ -727,6 +765,130 @@ pub fn pretty_print_input(sess: Session,
 
 }
 
+/// Collects the functions and methods whose path is `path`.
+struct PathFinder<'a> {
+    map: &'a ast_map::Map,
+    path: &'a str,
+    found: Vec<ast::NodeId>,
+}
+
+impl<'a> Visitor<()> for PathFinder<'a> {
+    fn visit_fn(&mut self, fk: &visit::FnKind, fd: &ast::FnDecl,
+                b: &ast::Block, s: codemap::Span, id: ast::NodeId, _: ()) {
+        match *fk {
+            visit::FkItemFn(..) | visit::FkMethod(..) => {
+                if self.map.path_to_str(id).as_slice() == self.path {
+                    self.found.push(id);
+                }
+            }
+            visit::FkFnBlock => {}
+        }
+        visit::walk_fn(self, fk, fd, b, s, id, ());
+    }
+}
+
+/// Writes out the control-flow graph of the function, method or closure
+/// given to `--pretty flowgraph` in DOT format.
+fn print_flowgraph<W:io::Writer>(analysis: CrateAnalysis,
+                                 krate: &ast::Crate,
+                                 item: &UserIdentifiedItem,
+                                 mut out: W) -> io::IoResult<()> {
+    let tcx = &analysis.ty_cx;
+    let id = match *item {
+        ItemViaNode(id) => id,
+        ItemViaPath(ref path) => {
+            let mut finder = PathFinder {
+                map: &tcx.map,
+                path: path.as_slice(),
+                found: Vec::new(),
+            };
+            visit::walk_crate(&mut finder, krate, ());
+            match finder.found.len() {
+                1 => *finder.found.get(0),
+                0 => tcx.sess.fatal(format!("no function found at path `{}`", *path)),
+                _ => tcx.sess.fatal(format!("more than one function found at path `{}`",
+                                            *path)),
+            }
+        }
+    };
+
+    match tcx.map.find(id) {
+        Some(ast_map::NodeItem(item)) => match item.node {
+            ast::ItemFn(decl, fn_style, abi, ref generics, body) => {
+                let fk = visit::FkItemFn(item.ident, generics, fn_style, abi);
+                return print_fn_flowgraph(tcx, &fk, decl, body, item.span, id, &mut out);
+            }
+            _ => {}
+        },
+        Some(ast_map::NodeMethod(m)) => {
+            let fk = visit::FkMethod(m.ident, &m.generics, m);
+            return print_fn_flowgraph(tcx, &fk, m.decl, m.body, m.span, id, &mut out);
+        }
+        Some(ast_map::NodeTraitMethod(tm)) => match *tm {
+            ast::Provided(m) => {
+                let fk = visit::FkMethod(m.ident, &m.generics, m);
+                return print_fn_flowgraph(tcx, &fk, m.decl, m.body, m.span, id, &mut out);
+            }
+            ast::Required(..) => {}
+        },
+        Some(ast_map::NodeExpr(expr)) => match expr.node {
+            ast::ExprFnBlock(decl, body) | ast::ExprProc(decl, body) => {
+                return print_fn_flowgraph(tcx, &visit::FkFnBlock, decl, body,
+                                          expr.span, id, &mut out);
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+    tcx.sess.fatal(format!("--pretty flowgraph needs a function, method or closure, \
+                            not {}", tcx.map.node_to_str(id)))
+}
+
+fn print_fn_flowgraph<W:io::Writer>(tcx: &ty::ctxt,
+                                    fk: &visit::FnKind,
+                                    decl: &ast::FnDecl,
+                                    body: &ast::Block,
+                                    sp: codemap::Span,
+                                    id: ast::NodeId,
+                                    out: &mut W) -> io::IoResult<()> {
+    let cfg = cfg::CFG::new(tcx, body);
+    let lcfg = LabelledCFG {
+        ast_map: &tcx.map,
+        cfg: &cfg,
+        name: format!("node_{}", id),
+    };
+
+    let variants = if tcx.sess.debugging_opt(session::FLOWGRAPH_PRINT_ALL) {
+        vec!(borrowck_dot::Loans, borrowck_dot::Moves, borrowck_dot::Assigns)
+    } else {
+        let mut variants = Vec::new();
+        if tcx.sess.debugging_opt(session::FLOWGRAPH_PRINT_LOANS) {
+            variants.push(borrowck_dot::Loans);
+        }
+        if tcx.sess.debugging_opt(session::FLOWGRAPH_PRINT_MOVES) {
+            variants.push(borrowck_dot::Moves);
+        }
+        if tcx.sess.debugging_opt(session::FLOWGRAPH_PRINT_ASSIGNS) {
+            variants.push(borrowck_dot::Assigns);
+        }
+        variants
+    };
+
+    if variants.is_empty() {
+        return dot::render(&lcfg, out);
+    }
+
+    let (bccx, analysis_data) =
+        borrowck::build_borrowck_dataflow_data_for_fn(tcx, fk, decl, body, sp, id);
+    let labeller = borrowck_dot::DataflowLabeller {
+        inner: lcfg,
+        variants: variants,
+        borrowck_ctxt: &bccx,
+        analysis_data: &analysis_data,
+    };
+    dot::render(&labeller, out)
+}
+
 pub fn get_os(triple: &str) -> Option<abi::Os> {
     for &(name, os) in os_names.iter() {
         if triple.contains(name) { return Some(os) }
@@ -853,9 +1015,25 @@ pub fn build_session_options(matches: &getopts::Matches) -> session::Options {
     }
 
     let mut debugging_opts = 0;
+    let mut sanitizer = None;
+    let mut incremental = None;
     let debug_flags = matches.opt_strs("Z");
     let debug_map = session::debugging_opts_map();
     for debug_flag in debug_flags.iter() {
+        if debug_flag.starts_with("sanitizer=") {
+            sanitizer = match debug_flag.slice_from("sanitizer=".len()) {
+                "address" => Some(session::AddressSanitizer),
+                "thread" => Some(session::ThreadSanitizer),
+                "memory" => Some(session::MemorySanitizer),
+                kind => early_error(format!("unknown sanitizer: `{}`, expected one of \
+                                             `address`, `thread` or `memory`", kind)),
+            };
+            continue
+        }
+        if debug_flag.starts_with("incremental=") {
+            incremental = Some(Path::new(debug_flag.slice_from("incremental=".len())));
+            continue
+        }
         let mut this_bit = 0;
         for tuple in debug_map.iter() {
             let (name, bit) = match *tuple { (ref a, _, b) => (a, b) };
@@ -866,6 +1044,11 @@ pub fn build_session_options(matches: &getopts::Matches) -> session::Options {
         }
         debugging_opts |= this_bit;
     }
+    // `-C instrument-coverage` is another name for `-Z profile`
+    let cg = build_codegen_options(matches);
+    if cg.instrument_coverage {
+        debugging_opts |= session::PROFILE;
+    }
 
     if debugging_opts & session::DEBUG_LLVM != 0 {
         unsafe { llvm::LLVMSetDebug(1); }
@@ -940,6 +1123,14 @@ pub fn build_session_options(matches: &getopts::Matches) -> session::Options {
     } else {
         NoDebugInfo
     };
+    // The GCOV pass of `-Z profile` maps its counters back to source lines
+    // through the debuginfo line tables.
+    let debuginfo = if debuginfo == NoDebugInfo &&
+                       debugging_opts & session::PROFILE != 0 {
+        LimitedDebugInfo
+    } else {
+        debuginfo
+    };
 
     let addl_lib_search_paths = matches.opt_strs("L").iter().map(|s| {
         Path::new(s.as_slice())
@@ -953,7 +1144,20 @@ pub fn build_session_options(matches: &getopts::Matches) -> session::Options {
     let print_metas = (matches.opt_present("crate-id"),
                        matches.opt_present("crate-name"),
                        matches.opt_present("crate-file-name"));
-    let cg = build_codegen_options(matches);
+    let error_format = match error_format(matches) {
+        Ok(format) => format,
+        Err(arg) => {
+            early_error(format!("argument for --error-format must be human or json \
+                                 (instead was `{}`)", arg));
+        }
+    };
+    if debugging_opts & session::PROFILE != 0 {
+        // Every codegen unit would write its counters to the same `.gcda`.
+        if cg.codegen_units > 1 || incremental.is_some() {
+            early_error("-Z profile cannot be used with several codegen units \
+                         or with -Z incremental");
+        }
+    }
 
     session::Options {
         crate_types: crate_types,
@@ -974,6 +1178,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> session::Options {
         write_dependency_info: write_dependency_info,
         print_metas: print_metas,
         cg: cg,
+        error_format: error_format,
+        sanitizer: sanitizer,
+        incremental: incremental,
     }
 }
 
@@ -1005,6 +1212,9 @@ pub fn build_codegen_options(matches: &getopts::Matches)
             early_error(format!("unknown codegen option: `{}`", key));
         }
     }
+    if cg.codegen_units == 0 {
+        early_error("value for codegen units must be a positive nonzero integer");
+    }
     return cg;
 }
 
@@ -1013,7 +1223,7 @@ pub fn build_session(sopts: session::Options,
                      -> Session {
     let codemap = codemap::CodeMap::new();
     let diagnostic_handler =
-        diagnostic::default_handler();
+        diagnostic::mk_handler(mk_emitter(sopts.error_format));
     let span_diagnostic_handler =
         diagnostic::mk_span_handler(diagnostic_handler, codemap);
 
@@ -1025,6 +1235,14 @@ pub fn build_session_(sopts: session::Options,
                       span_diagnostic: diagnostic::SpanHandler)
                       -> Session {
     let target_cfg = build_target_config(&sopts);
+    match sopts.sanitizer {
+        Some(sanitizer) if target_cfg.os != abi::OsLinux ||
+                           target_cfg.arch != abi::X86_64 => {
+            early_error(format!("the {} sanitizer is only supported on x86_64 Linux",
+                                sanitizer.name()))
+        }
+        _ => {}
+    }
     let p_s = parse::new_parse_sess_special_handler(span_diagnostic);
     let default_sysroot = match sopts.maybe_sysroot {
         Some(_) => None,
@@ -1067,10 +1285,17 @@ pub fn parse_pretty(sess: &Session, name: &str) -> PpMode {
         "typed" => PpmTyped,
         "expanded,identified" => PpmExpandedIdentified,
         "identified" => PpmIdentified,
+        _ if name.starts_with("flowgraph=") => {
+            let item = name.slice_from("flowgraph=".len());
+            PpmFlowGraph(match from_str(item) {
+                Some(id) => ItemViaNode(id),
+                None => ItemViaPath(item.to_owned()),
+            })
+        }
         _ => {
             sess.fatal("argument to `pretty` must be one of `normal`, \
-                        `expanded`, `typed`, `identified`, \
-                        or `expanded,identified`");
+                        `expanded`, `flowgraph=<nodeid or path>`, `typed`, \
+                        `identified`, or `expanded,identified`");
         }
     }
 }
@@ -1107,8 +1332,10 @@ pub fn optgroups() -> Vec<getopts::OptGroup> {
               valid types are: normal (un-annotated source),
               expanded (crates expanded),
               typed (crates expanded, with type annotations),
-              or identified (fully parenthesized,
-              AST nodes and blocks with IDs)", "TYPE"),
+              identified (fully parenthesized,
+              AST nodes and blocks with IDs),
+              or flowgraph=<nodeid or path> (graphviz formatted
+              control-flow graph of a function)", "TYPE"),
   optflagopt("", "dep-info",
              "Output dependency info to <filename> after compiling, \
               in a format suitable for use by Makefiles", "FILENAME"),
@@ -1123,6 +1350,8 @@ pub fn optgroups() -> Vec<getopts::OptGroup> {
   optmulti("F", "forbid", "Set lint forbidden", "OPT"),
   optmulti("C", "codegen", "Set a codegen option", "OPT[=VALUE]"),
   optmulti("Z", "", "Set internal debugging options", "FLAG"),
+  optopt("", "error-format", "How errors and other messages are produced", "human|json"),
+  optopt("", "explain", "Provide a detailed explanation of an error message", "CODE"),
   optflag( "v", "version", "Print version info and exit"))
 }
 
@@ -1209,9 +1438,50 @@ pub fn build_output_filenames(input: &Input,
     }
 }
 
+// The format of errors reported before there is a session. Tasks spawned
+// afterwards, such as the one running the compiler, inherit it.
+local_data_key!(inherited early_error_format: session::ErrorOutputType)
+
+/// Reads the `--error-format` argument, returning the argument if it is not a
+/// known format.
+pub fn error_format(matches: &getopts::Matches)
+                    -> Result<session::ErrorOutputType, ~str> {
+    match matches.opt_str("error-format") {
+        None => Ok(session::HumanReadable),
+        Some(arg) => match arg.as_slice() {
+            "human" => Ok(session::HumanReadable),
+            "json" => Ok(session::Json),
+            _ => Err(arg),
+        },
+    }
+}
+
+fn mk_emitter(format: session::ErrorOutputType) -> Box<Emitter:Send> {
+    match format {
+        session::HumanReadable => box diagnostic::EmitterWriter::stderr(),
+        session::Json => box syntax::json::JsonEmitter::stderr(),
+    }
+}
+
+/// Selects the format of the errors reported by `early_error` and
+/// `early_emitter`, before the session options have been built.
+pub fn set_early_error_format(format: session::ErrorOutputType) {
+    early_error_format.replace(Some(format));
+}
+
+/// An emitter for errors which are reported without a session, in the format
+/// selected with `set_early_error_format`.
+pub fn early_emitter() -> Box<Emitter:Send> {
+    let format = match early_error_format.get() {
+        Some(format) => (*format).clone(),
+        None => session::HumanReadable,
+    };
+    mk_emitter(format)
+}
+
 pub fn early_error(msg: &str) -> ! {
-    let mut emitter = diagnostic::EmitterWriter::stderr();
-    emitter.emit(None, msg, diagnostic::Fatal);
+    let mut emitter = early_emitter();
+    emitter.emit(None, msg, None, diagnostic::Fatal);
     fail!(diagnostic::FatalError);
 }
 
@@ -1226,6 +1496,7 @@ mod test {
 
     use driver::driver::{build_configuration, build_session};
     use driver::driver::{build_session_options, optgroups};
+    use driver::session;
 
     use getopts::getopts;
     use syntax::attr;
@@ -1265,4 +1536,19 @@ mod test {
         assert!(test_items.next().is_some());
         assert!(test_items.next().is_none());
     }
+
+    #[test]
+    fn test_error_format() {
+        let matches =
+            &match getopts(["--error-format=json".to_owned()], optgroups().as_slice()) {
+              Ok(m) => m,
+              Err(f) => fail!("test_error_format: {}", f.to_err_msg())
+            };
+        let sessopts = build_session_options(matches);
+        assert!(sessopts.error_format == session::Json);
+
+        let matches = &getopts([], optgroups().as_slice()).unwrap();
+        let sessopts = build_session_options(matches);
+        assert!(sessopts.error_format == session::HumanReadable);
+    }
 }
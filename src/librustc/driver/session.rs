@@ -69,7 +69,14 @@ debugging_opts!(
         LTO,
         AST_JSON,
         AST_JSON_NOEXPAND,
-        LS
+        LS,
+        FLOWGRAPH_PRINT_LOANS,
+        FLOWGRAPH_PRINT_MOVES,
+        FLOWGRAPH_PRINT_ASSIGNS,
+        FLOWGRAPH_PRINT_ALL,
+        SAVE_ANALYSIS,
+        PROFILE,
+        INCREMENTAL_INFO
     ]
     0
 )
@@ -103,7 +110,20 @@ pub fn debugging_opts_map() -> Vec<(&'static str, &'static str, u64)> {
      ("lto", "Perform LLVM link-time optimizations", LTO),
      ("ast-json", "Print the AST as JSON and halt", AST_JSON),
      ("ast-json-noexpand", "Print the pre-expansion AST as JSON and halt", AST_JSON_NOEXPAND),
-     ("ls", "List the symbols defined by a library crate", LS))
+     ("ls", "List the symbols defined by a library crate", LS),
+     ("flowgraph-print-loans", "Include loan analysis data in \
+                       --pretty flowgraph output", FLOWGRAPH_PRINT_LOANS),
+     ("flowgraph-print-moves", "Include move analysis data in \
+                       --pretty flowgraph output", FLOWGRAPH_PRINT_MOVES),
+     ("flowgraph-print-assigns", "Include assignment analysis data in \
+                       --pretty flowgraph output", FLOWGRAPH_PRINT_ASSIGNS),
+     ("flowgraph-print-all", "Include all dataflow analysis data in \
+                       --pretty flowgraph output", FLOWGRAPH_PRINT_ALL),
+     ("save-analysis", "Write the definitions and references found by analysis \
+                       for use by external tools", SAVE_ANALYSIS),
+     ("profile", "Insert gcov profiling code to measure test coverage", PROFILE),
+     ("incremental-info", "Print which items are translated and which are reused \
+                       from the incremental cache", INCREMENTAL_INFO))
 }
 
 #[deriving(Clone, Eq)]
@@ -121,6 +141,12 @@ pub enum DebugInfoLevel {
     FullDebugInfo,
 }
 
+#[deriving(Clone, Eq)]
+pub enum ErrorOutputType {
+    HumanReadable, // --error-format=human, the default
+    Json, // --error-format=json, one JSON object per diagnostic
+}
+
 #[deriving(Clone)]
 pub struct Options {
     // The crate config requested for the session, which may be combined
@@ -153,6 +179,43 @@ pub struct Options {
     /// Crate id-related things to maybe print. It's (crate_id, crate_name, crate_file_name).
     pub print_metas: (bool, bool, bool),
     pub cg: CodegenOptions,
+    /// How diagnostics are written to stderr.
+    pub error_format: ErrorOutputType,
+    /// The sanitizer instrumenting the generated code, if any.
+    pub sanitizer: Option<Sanitizer>,
+    /// The directory caching translated items between compilations, if any.
+    pub incremental: Option<Path>,
+}
+
+/// The sanitizers of compiler-rt that `-Z sanitizer` can instrument code for.
+#[deriving(Clone, Eq, Show)]
+pub enum Sanitizer {
+    AddressSanitizer,
+    ThreadSanitizer,
+    /// Only memory written by instrumented code is known to be initialized.
+    /// The standard library is precompiled without instrumentation, so reads
+    /// of memory that it initialized may be reported as uninitialized.
+    MemorySanitizer,
+}
+
+impl Sanitizer {
+    /// The name of the sanitizer, as given to `-Z sanitizer`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AddressSanitizer => "address",
+            ThreadSanitizer => "thread",
+            MemorySanitizer => "memory",
+        }
+    }
+
+    /// The short name of the sanitizer used by LLVM and compiler-rt.
+    pub fn short_name(&self) -> &'static str {
+        match *self {
+            AddressSanitizer => "asan",
+            ThreadSanitizer => "tsan",
+            MemorySanitizer => "msan",
+        }
+    }
 }
 
 // The type of entry function, so
@@ -208,6 +271,9 @@ impl Session {
     pub fn span_err(&self, sp: Span, msg: &str) {
         self.diagnostic().span_err(sp, msg)
     }
+    pub fn span_err_with_code(&self, sp: Span, msg: &str, code: &str) {
+        self.diagnostic().span_err_with_code(sp, msg, code)
+    }
     pub fn err(&self, msg: &str) {
         self.diagnostic().handler().err(msg)
     }
@@ -317,6 +383,9 @@ impl Session {
     pub fn show_span(&self) -> bool {
         self.debugging_opt(SHOW_SPAN)
     }
+    pub fn incremental_info(&self) -> bool {
+        self.debugging_opt(INCREMENTAL_INFO)
+    }
     pub fn sysroot<'a>(&'a self) -> &'a Path {
         match self.opts.maybe_sysroot {
             Some (ref sysroot) => sysroot,
@@ -359,6 +428,9 @@ pub fn basic_options() -> Options {
         write_dependency_info: (false, None),
         print_metas: (false, false, false),
         cg: basic_codegen_options(),
+        error_format: HumanReadable,
+        sanitizer: None,
+        incremental: None,
     }
 }
 
@@ -417,6 +489,13 @@ macro_rules! cgoptions(
             }
         }
 
+        fn parse_uint(slot: &mut uint, v: Option<&str>) -> bool {
+            match v.and_then(|s| from_str(s)) {
+                Some(i) => { *slot = i; true },
+                None => false,
+            }
+        }
+
         fn parse_list(slot: &mut Vec<~str>, v: Option<&str>)
                       -> bool {
             match v {
@@ -468,6 +547,10 @@ cgoptions!(
         "use an external assembler rather than LLVM's integrated one"),
     relocation_model: ~str = ("pic".to_owned(), parse_string,
          "choose the relocation model to use (llc -relocation-model for details)"),
+    codegen_units: uint = (1, parse_uint,
+        "divide crate into N units to optimize in parallel"),
+    instrument_coverage: bool = (false, parse_bool,
+        "instrument the code with gcov coverage counters (same as -Z profile)"),
 )
 
 // Seems out of place, but it uses session, so I'm putting it here
@@ -37,6 +37,7 @@ extern crate syntax;
 extern crate serialize;
 extern crate sync;
 extern crate getopts;
+extern crate graphviz;
 extern crate collections;
 extern crate time;
 extern crate libc;
@@ -61,6 +62,8 @@ use syntax::diagnostic::Emitter;
 use syntax::diagnostic;
 use syntax::parse;
 
+pub mod diagnostics;
+
 pub mod middle {
     pub mod trans;
     pub mod ty;
@@ -94,6 +97,7 @@ pub mod middle {
     pub mod dead;
     pub mod expr_use_visitor;
     pub mod dependency_format;
+    pub mod save;
 }
 
 pub mod front {
@@ -109,9 +113,11 @@ pub mod back {
     pub mod abi;
     pub mod archive;
     pub mod arm;
+    pub mod incremental;
     pub mod link;
     pub mod lto;
     pub mod mips;
+    pub mod partition;
     pub mod rpath;
     pub mod svh;
     pub mod target_strs;
@@ -205,6 +211,14 @@ pub fn describe_debug_flags() {
             }
         }
     }
+    println!("    -Z {:>20s} -- {}", "sanitizer=KIND",
+             "instrument the generated code with the `address`, `thread` or \
+              `memory` sanitizer");
+    println!("    {:>23s}    {}", "",
+             "(`memory` only knows about memory initialized by instrumented \
+              code, which the standard library isn't)");
+    println!("    -Z {:>20s} -- {}", "incremental=DIR",
+             "cache translated items in DIR and reuse them in later compilations");
 }
 
 pub fn describe_codegen_flags() {
@@ -242,6 +256,17 @@ pub fn run_compiler(args: &[~str]) {
         return;
     }
 
+    match matches.opt_str("explain") {
+        Some(code) => {
+            match diagnostics::lookup(code.as_slice()) {
+                Some(description) => println!("{}", description.trim()),
+                None => d::early_error(format!("{} is not a valid error code", code)),
+            }
+            return;
+        }
+        None => {}
+    }
+
     let lint_flags = matches.opt_strs("W").move_iter().collect::<Vec<_>>().append(
                                     matches.opt_strs("warn").as_slice());
     if lint_flags.iter().any(|x| x == &"help".to_owned()) {
@@ -396,7 +421,7 @@ pub fn monitor(f: proc():Send) {
         Err(value) => {
             // Task failed without emitting a fatal diagnostic
             if !value.is::<diagnostic::FatalError>() {
-                let mut emitter = diagnostic::EmitterWriter::stderr();
+                let mut emitter = d::early_emitter();
 
                 // a .span_bug or .bug call has already printed what
                 // it wants to print.
@@ -404,6 +429,7 @@ pub fn monitor(f: proc():Send) {
                     emitter.emit(
                         None,
                         "unexpected failure",
+                        None,
                         diagnostic::Bug);
                 }
 
@@ -413,13 +439,14 @@ pub fn monitor(f: proc():Send) {
                     "run with `RUST_BACKTRACE=1` for a backtrace".to_owned(),
                 ];
                 for note in xs.iter() {
-                    emitter.emit(None, *note, diagnostic::Note)
+                    emitter.emit(None, *note, None, diagnostic::Note)
                 }
 
                 match r.read_to_str() {
                     Ok(s) => println!("{}", s),
                     Err(e) => emitter.emit(None,
                                            format!("failed to read internal stderr: {}", e),
+                                           None,
                                            diagnostic::Error),
                 }
             }
@@ -439,6 +466,19 @@ pub fn main() {
 
 pub fn main_args(args: &[~str]) -> int {
     let owned_args = args.to_owned();
+
+    // Errors from before the session exists, and the report of an unexpected
+    // failure, are written in the requested format as well. An invalid format
+    // is reported once the compiler is running.
+    if args.len() > 1 {
+        match getopts::getopts(args.tail(), d::optgroups().as_slice()) {
+            Ok(ref matches) => match d::error_format(matches) {
+                Ok(format) => d::set_early_error_format(format),
+                Err(..) => {}
+            },
+            Err(..) => {}
+        }
+    }
     monitor(proc() run_compiler(owned_args));
     0
 }
@@ -207,6 +207,15 @@ pub enum CodeGenOptLevel {
     CodeGenLevelAggressive = 3,
 }
 
+/// The function attributes enabling the sanitizers, which are missing from
+/// the LLVM C API.
+#[repr(C)]
+pub enum SanitizeAttribute {
+    SanitizeAddressAttribute = 0,
+    SanitizeThreadAttribute = 1,
+    SanitizeMemoryAttribute = 2,
+}
+
 #[repr(C)]
 pub enum RelocMode {
     RelocDefault = 0,
@@ -716,6 +725,7 @@ pub mod llvm {
         pub fn LLVMRemoveReturnAttribute(Fn: ValueRef, PA: c_uint);
 
         pub fn LLVMAddColdAttribute(Fn: ValueRef);
+        pub fn LLVMRustAddSanitizeAttribute(Fn: ValueRef, Kind: SanitizeAttribute);
 
         pub fn LLVMRemoveFunctionAttr(Fn: ValueRef,
                                       PA: c_ulonglong,
@@ -1279,6 +1289,8 @@ pub mod llvm {
         /* Selected entries from the downcasts. */
         pub fn LLVMIsATerminatorInst(Inst: ValueRef) -> ValueRef;
         pub fn LLVMIsAStoreInst(Inst: ValueRef) -> ValueRef;
+        pub fn LLVMIsAConstant(Val: ValueRef) -> ValueRef;
+        pub fn LLVMIsAGlobalValue(Val: ValueRef) -> ValueRef;
 
         /** Writes a module to the specified path. Returns 0 on success. */
         pub fn LLVMWriteBitcodeToFile(M: ModuleRef, Path: *c_char) -> c_int;
@@ -1514,7 +1526,8 @@ pub mod llvm {
                                               isOptimized: bool,
                                               Flags: *c_char,
                                               RuntimeVer: c_uint,
-                                              SplitName: *c_char);
+                                              SplitName: *c_char)
+                                              -> DIDescriptor;
 
         pub fn LLVMDIBuilderCreateFile(Builder: DIBuilderRef,
                                        Filename: *c_char,
@@ -1778,6 +1791,11 @@ pub mod llvm {
         pub fn LLVMRustRunRestrictionPass(M: ModuleRef,
                                           syms: **c_char,
                                           len: size_t);
+        pub fn LLVMRustWriteBitcodeToMemoryBuffer(M: ModuleRef) -> MemoryBufferRef;
+        pub fn LLVMRustParseBitcodeInContext(C: ContextRef,
+                                             Buf: MemoryBufferRef) -> ModuleRef;
+        pub fn LLVMRustDropDefinition(V: ValueRef);
+        pub fn LLVMRustEraseUnusedGlobal(V: ValueRef) -> bool;
         pub fn LLVMRustMarkAllFunctionsNounwind(M: ModuleRef);
 
         pub fn LLVMRustOpenArchive(path: *c_char) -> ArchiveRef;
@@ -69,16 +69,16 @@ impl<'a> visit::Visitor<()> for Env<'a> {
 fn dump_crates(cstore: &CStore) {
     debug!("resolved crates:");
     cstore.iter_crate_data(|_, data| {
-        debug!("crate_id: {}", data.crate_id());
+        debug!("crate_id: {}", data.crate_id);
         debug!("  cnum: {}", data.cnum);
-        debug!("  hash: {}", data.hash());
+        debug!("  hash: {}", data.hash);
     })
 }
 
 fn warn_if_multiple_versions(diag: &SpanHandler, cstore: &CStore) {
     let mut map = HashMap::new();
     cstore.iter_crate_data(|cnum, data| {
-        let crateid = data.crate_id();
+        let crateid = &data.crate_id;
         let key = (crateid.name.clone(), crateid.path.clone());
         map.find_or_insert_with(key, |_| Vec::new()).push(cnum);
     });
@@ -90,7 +90,7 @@ fn warn_if_multiple_versions(diag: &SpanHandler, cstore: &CStore) {
         for dupe in dupes.move_iter() {
             let data = cstore.get_crate_data(dupe);
             diag.span_note(data.span, "used here");
-            loader::note_crateid_attr(diag, &data.crate_id());
+            loader::note_crateid_attr(diag, &data.crate_id);
         }
     }
 }
@@ -252,11 +252,9 @@ fn existing_match(e: &Env, crate_id: &CrateId,
                   hash: Option<&Svh>) -> Option<ast::CrateNum> {
     let mut ret = None;
     e.sess.cstore.iter_crate_data(|cnum, data| {
-        let other_id = data.crate_id();
-        if crate_id.matches(&other_id) {
-            let other_hash = data.hash();
+        if crate_id.matches(&data.crate_id) {
             match hash {
-                Some(hash) if *hash != other_hash => {}
+                Some(hash) if *hash != data.hash => {}
                 Some(..) | None => { ret = Some(cnum); }
             }
         }
@@ -289,12 +287,19 @@ fn register_crate<'a>(e: &mut Env,
     // Maintain a reference to the top most crate.
     let root = if root.is_some() { root } else { &crate_paths };
 
-    let cnum_map = resolve_crate_deps(e, root, lib.metadata.as_slice(), span);
+    let name = crate_id.name.as_slice();
+    let cnum_map = resolve_crate_deps(e, root, name, lib.metadata.as_slice(), span);
+    let data_crate_id = decoder::expect_decoded(e.sess, name,
+                                                decoder::get_crate_id(lib.metadata.as_slice()));
+    let hash = decoder::expect_decoded(e.sess, name,
+                                       decoder::get_crate_hash(lib.metadata.as_slice()));
 
     let loader::Library{ dylib, rlib, metadata } = lib;
 
     let cmeta = Rc::new( cstore::crate_metadata {
         name: crate_id.name.to_owned(),
+        crate_id: data_crate_id,
+        hash: hash,
         data: metadata,
         cnum_map: cnum_map,
         cnum: cnum,
@@ -349,12 +354,14 @@ fn resolve_crate<'a>(e: &mut Env,
 // Go through the crate metadata and load any crates that it references
 fn resolve_crate_deps(e: &mut Env,
                       root: &Option<CratePaths>,
+                      name: &str,
                       cdata: &[u8], span : Span)
                    -> cstore::cnum_map {
     debug!("resolving deps of external crate");
     // The map from crate numbers in the crate we're resolving to local crate
     // numbers
-    decoder::get_crate_deps(cdata).iter().map(|dep| {
+    let deps = decoder::expect_decoded(e.sess, name, decoder::get_crate_deps(cdata));
+    deps.iter().map(|dep| {
         debug!("resolving dep crate {} hash: `{}`", dep.crate_id, dep.hash);
         let (local_cnum, _, _) = resolve_crate(e, root,
                                                dep.crate_id.name.as_slice(),
@@ -411,7 +418,10 @@ impl<'a> CrateLoader for Loader<'a> {
                 load_ctxt.os = session::sess_os_to_meta_os(self.env.sess.targ_cfg.os);
                 load_ctxt.filesearch = self.env.sess.target_filesearch();
                 let lib = load_ctxt.load_library_crate();
-                if decoder::get_macro_registrar_fn(lib.metadata.as_slice()).is_some() {
+                let registrar = decoder::get_macro_registrar_fn(lib.metadata.as_slice());
+                let registrar = decoder::expect_decoded(self.env.sess, info.ident.as_slice(),
+                                                        registrar);
+                if registrar.is_some() {
                     let message = format!("crate `{}` contains a macro_registrar fn but \
                                   only a version for triple `{}` could be found (need {})",
                                   info.ident, target_triple, driver::host_triple());
@@ -426,10 +436,18 @@ impl<'a> CrateLoader for Loader<'a> {
             }
             None => { load_ctxt.report_load_errs(); unreachable!() },
         };
-        let macros = decoder::get_exported_macros(library.metadata.as_slice());
-        let registrar = decoder::get_macro_registrar_fn(library.metadata.as_slice()).map(|id| {
-            decoder::get_symbol(library.metadata.as_slice(), id).to_strbuf()
-        });
+        let (macros, registrar) = {
+            let sess = self.env.sess;
+            let ident = info.ident.as_slice();
+            let data = library.metadata.as_slice();
+            let macros = decoder::expect_decoded(sess, ident,
+                                                 decoder::get_exported_macros(data));
+            let registrar = decoder::expect_decoded(sess, ident,
+                                                    decoder::get_macro_registrar_fn(data));
+            (macros, registrar.map(|id| {
+                decoder::expect_decoded(sess, ident, decoder::get_symbol(data, id)).to_strbuf()
+            }))
+        };
         let mc = MacroCrate {
             lib: library.dylib.clone(),
             macros: macros.move_iter().map(|x| x.to_strbuf()).collect(),
@@ -12,6 +12,7 @@
 
 #![allow(non_camel_case_types)]
 
+use driver::session::Session;
 use metadata::common::*;
 use metadata::cstore;
 use metadata::decoder;
@@ -19,6 +20,7 @@ use middle::ty;
 use middle::typeck;
 
 use reader = serialize::ebml::reader;
+use serialize::ebml::reader::DecodeResult;
 use std::rc::Rc;
 use syntax::ast;
 use syntax::ast_map;
@@ -32,57 +34,65 @@ pub struct StaticMethodInfo {
     pub vis: ast::Visibility,
 }
 
-pub fn get_symbol(cstore: &cstore::CStore, def: ast::DefId) -> ~str {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_symbol(cdata.data(), def.node)
+// Returns the value decoded from the metadata of `cdata`, reporting malformed
+// metadata as a fatal error.
+fn decoded<T>(sess: &Session, cdata: &cstore::crate_metadata, r: DecodeResult<T>) -> T {
+    decoder::expect_decoded(sess, cdata.name.as_slice(), r)
+}
+
+pub fn get_symbol(sess: &Session, def: ast::DefId) -> ~str {
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata, decoder::get_symbol(cdata.data(), def.node))
 }
 
 /// Iterates over all the language items in the given crate.
-pub fn each_lang_item(cstore: &cstore::CStore,
+pub fn each_lang_item(sess: &Session,
                       cnum: ast::CrateNum,
                       f: |ast::NodeId, uint| -> bool)
                       -> bool {
-    let crate_data = cstore.get_crate_data(cnum);
-    decoder::each_lang_item(&*crate_data, f)
+    let crate_data = sess.cstore.get_crate_data(cnum);
+    decoded(sess, &*crate_data, decoder::each_lang_item(&*crate_data, f))
 }
 
 /// Iterates over each child of the given item.
-pub fn each_child_of_item(cstore: &cstore::CStore,
+pub fn each_child_of_item(sess: &Session,
                           def_id: ast::DefId,
                           callback: |decoder::DefLike,
                                      ast::Ident,
                                      ast::Visibility|) {
-    let crate_data = cstore.get_crate_data(def_id.krate);
+    let crate_data = sess.cstore.get_crate_data(def_id.krate);
     let get_crate_data: decoder::GetCrateDataCb = |cnum| {
-        cstore.get_crate_data(cnum)
+        sess.cstore.get_crate_data(cnum)
     };
-    decoder::each_child_of_item(cstore.intr.clone(),
-                                &*crate_data,
-                                def_id.node,
-                                get_crate_data,
-                                callback)
+    decoded(sess, &*crate_data,
+            decoder::each_child_of_item(sess.cstore.intr.clone(),
+                                        &*crate_data,
+                                        def_id.node,
+                                        get_crate_data,
+                                        callback))
 }
 
 /// Iterates over each top-level crate item.
-pub fn each_top_level_item_of_crate(cstore: &cstore::CStore,
+pub fn each_top_level_item_of_crate(sess: &Session,
                                     cnum: ast::CrateNum,
                                     callback: |decoder::DefLike,
                                                ast::Ident,
                                                ast::Visibility|) {
-    let crate_data = cstore.get_crate_data(cnum);
+    let crate_data = sess.cstore.get_crate_data(cnum);
     let get_crate_data: decoder::GetCrateDataCb = |cnum| {
-        cstore.get_crate_data(cnum)
+        sess.cstore.get_crate_data(cnum)
     };
-    decoder::each_top_level_item_of_crate(cstore.intr.clone(),
-                                          &*crate_data,
-                                          get_crate_data,
-                                          callback)
+    decoded(sess, &*crate_data,
+            decoder::each_top_level_item_of_crate(sess.cstore.intr.clone(),
+                                                  &*crate_data,
+                                                  get_crate_data,
+                                                  callback))
 }
 
 pub fn get_item_path(tcx: &ty::ctxt, def: ast::DefId) -> Vec<ast_map::PathElem> {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    let path = decoder::get_item_path(&*cdata, def.node);
+    let path = decoded(&tcx.sess, &*cdata, decoder::get_item_path(&*cdata, def.node));
 
     // FIXME #1920: This path is not always correct if the crate is not linked
     // into the root namespace.
@@ -103,46 +113,51 @@ pub fn maybe_get_item_ast(tcx: &ty::ctxt, def: ast::DefId,
                        -> found_ast {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::maybe_get_item_ast(&*cdata, tcx, def.node, decode_inlined_item)
+    decoded(&tcx.sess, &*cdata,
+            decoder::maybe_get_item_ast(&*cdata, tcx, def.node, decode_inlined_item))
 }
 
 pub fn get_enum_variants(tcx: &ty::ctxt, def: ast::DefId)
                       -> Vec<Rc<ty::VariantInfo>> {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_enum_variants(cstore.intr.clone(), &*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata,
+            decoder::get_enum_variants(cstore.intr.clone(), &*cdata, def.node, tcx))
 }
 
 /// Returns information about the given implementation.
-pub fn get_impl_methods(cstore: &cstore::CStore, impl_def_id: ast::DefId)
+pub fn get_impl_methods(sess: &Session, impl_def_id: ast::DefId)
                         -> Vec<ast::DefId> {
-    let cdata = cstore.get_crate_data(impl_def_id.krate);
-    decoder::get_impl_methods(&*cdata, impl_def_id.node)
+    let cdata = sess.cstore.get_crate_data(impl_def_id.krate);
+    decoded(sess, &*cdata, decoder::get_impl_methods(&*cdata, impl_def_id.node))
 }
 
 pub fn get_method(tcx: &ty::ctxt, def: ast::DefId) -> ty::Method {
     let cdata = tcx.sess.cstore.get_crate_data(def.krate);
-    decoder::get_method(tcx.sess.cstore.intr.clone(), &*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata,
+            decoder::get_method(tcx.sess.cstore.intr.clone(), &*cdata, def.node, tcx))
 }
 
-pub fn get_method_name_and_explicit_self(cstore: &cstore::CStore,
+pub fn get_method_name_and_explicit_self(sess: &Session,
                                          def: ast::DefId)
                                      -> (ast::Ident, ast::ExplicitSelf_)
 {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_method_name_and_explicit_self(cstore.intr.clone(), &*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata,
+            decoder::get_method_name_and_explicit_self(sess.cstore.intr.clone(),
+                                                       &*cdata, def.node))
 }
 
-pub fn get_trait_method_def_ids(cstore: &cstore::CStore,
+pub fn get_trait_method_def_ids(sess: &Session,
                                 def: ast::DefId) -> Vec<ast::DefId> {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_trait_method_def_ids(&*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata, decoder::get_trait_method_def_ids(&*cdata, def.node))
 }
 
-pub fn get_item_variances(cstore: &cstore::CStore,
+pub fn get_item_variances(sess: &Session,
                           def: ast::DefId) -> ty::ItemVariances {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_item_variances(&*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata, decoder::get_item_variances(&*cdata, def.node))
 }
 
 pub fn get_provided_trait_methods(tcx: &ty::ctxt,
@@ -150,40 +165,43 @@ pub fn get_provided_trait_methods(tcx: &ty::ctxt,
                                -> Vec<Rc<ty::Method>> {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_provided_trait_methods(cstore.intr.clone(), &*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata,
+            decoder::get_provided_trait_methods(cstore.intr.clone(), &*cdata, def.node, tcx))
 }
 
 pub fn get_supertraits(tcx: &ty::ctxt, def: ast::DefId) -> Vec<Rc<ty::TraitRef>> {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_supertraits(&*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata, decoder::get_supertraits(&*cdata, def.node, tcx))
 }
 
-pub fn get_type_name_if_impl(cstore: &cstore::CStore, def: ast::DefId)
+pub fn get_type_name_if_impl(sess: &Session, def: ast::DefId)
                           -> Option<ast::Ident> {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_type_name_if_impl(&*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata, decoder::get_type_name_if_impl(&*cdata, def.node))
 }
 
-pub fn get_static_methods_if_impl(cstore: &cstore::CStore,
+pub fn get_static_methods_if_impl(sess: &Session,
                                   def: ast::DefId)
                                -> Option<Vec<StaticMethodInfo> > {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_static_methods_if_impl(cstore.intr.clone(), &*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata,
+            decoder::get_static_methods_if_impl(sess.cstore.intr.clone(), &*cdata, def.node))
 }
 
-pub fn get_item_attrs(cstore: &cstore::CStore,
+pub fn get_item_attrs(sess: &Session,
                       def_id: ast::DefId,
                       f: |Vec<@ast::MetaItem> |) {
-    let cdata = cstore.get_crate_data(def_id.krate);
-    decoder::get_item_attrs(&*cdata, def_id.node, f)
+    let cdata = sess.cstore.get_crate_data(def_id.krate);
+    decoded(sess, &*cdata, decoder::get_item_attrs(&*cdata, def_id.node, f))
 }
 
-pub fn get_struct_fields(cstore: &cstore::CStore,
+pub fn get_struct_fields(sess: &Session,
                          def: ast::DefId)
                       -> Vec<ty::field_ty> {
-    let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_struct_fields(cstore.intr.clone(), &*cdata, def.node)
+    let cdata = sess.cstore.get_crate_data(def.krate);
+    decoded(sess, &*cdata,
+            decoder::get_struct_fields(sess.cstore.intr.clone(), &*cdata, def.node))
 }
 
 pub fn get_type(tcx: &ty::ctxt,
@@ -191,34 +209,36 @@ pub fn get_type(tcx: &ty::ctxt,
              -> ty::ty_param_bounds_and_ty {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_type(&*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata, decoder::get_type(&*cdata, def.node, tcx))
 }
 
 pub fn get_trait_def(tcx: &ty::ctxt, def: ast::DefId) -> ty::TraitDef {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_trait_def(&*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata, decoder::get_trait_def(&*cdata, def.node, tcx))
 }
 
 pub fn get_field_type(tcx: &ty::ctxt, class_id: ast::DefId,
                       def: ast::DefId) -> ty::ty_param_bounds_and_ty {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(class_id.krate);
-    let all_items = reader::get_doc(reader::Doc(cdata.data()), tag_items);
+    let all_items = decoded(&tcx.sess, &*cdata,
+                            reader::get_doc(reader::Doc(cdata.data()), tag_items));
     let class_doc = expect(tcx.sess.diagnostic(),
-                           decoder::maybe_find_item(class_id.node, all_items),
+                           decoded(&tcx.sess, &*cdata,
+                                   decoder::maybe_find_item(class_id.node, all_items)),
                            || {
         (format!("get_field_type: class ID {:?} not found",
                  class_id)).to_strbuf()
     });
     let the_field = expect(tcx.sess.diagnostic(),
-        decoder::maybe_find_item(def.node, class_doc),
+        decoded(&tcx.sess, &*cdata, decoder::maybe_find_item(def.node, class_doc)),
         || {
             (format!("get_field_type: in class {:?}, field ID {:?} not found",
                     class_id,
                     def)).to_strbuf()
         });
-    let ty = decoder::item_type(def, the_field, tcx, &*cdata);
+    let ty = decoded(&tcx.sess, &*cdata, decoder::item_type(def, the_field, tcx, &*cdata));
     ty::ty_param_bounds_and_ty {
         generics: ty::Generics {type_param_defs: Rc::new(Vec::new()),
                                 region_param_defs: Rc::new(Vec::new())},
@@ -232,7 +252,7 @@ pub fn get_impl_trait(tcx: &ty::ctxt,
                       def: ast::DefId) -> Option<Rc<ty::TraitRef>> {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_impl_trait(&*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata, decoder::get_impl_trait(&*cdata, def.node, tcx))
 }
 
 // Given a def_id for an impl, return information about its vtables
@@ -240,60 +260,63 @@ pub fn get_impl_vtables(tcx: &ty::ctxt,
                         def: ast::DefId) -> typeck::impl_res {
     let cstore = &tcx.sess.cstore;
     let cdata = cstore.get_crate_data(def.krate);
-    decoder::get_impl_vtables(&*cdata, def.node, tcx)
+    decoded(&tcx.sess, &*cdata, decoder::get_impl_vtables(&*cdata, def.node, tcx))
 }
 
-pub fn get_native_libraries(cstore: &cstore::CStore,
+pub fn get_native_libraries(sess: &Session,
                             crate_num: ast::CrateNum)
                                 -> Vec<(cstore::NativeLibaryKind, ~str)> {
-    let cdata = cstore.get_crate_data(crate_num);
-    decoder::get_native_libraries(&*cdata)
+    let cdata = sess.cstore.get_crate_data(crate_num);
+    decoded(sess, &*cdata, decoder::get_native_libraries(&*cdata))
 }
 
-pub fn each_impl(cstore: &cstore::CStore,
+pub fn each_impl(sess: &Session,
                  crate_num: ast::CrateNum,
                  callback: |ast::DefId|) {
-    let cdata = cstore.get_crate_data(crate_num);
-    decoder::each_impl(&*cdata, callback)
+    let cdata = sess.cstore.get_crate_data(crate_num);
+    decoded(sess, &*cdata, decoder::each_impl(&*cdata, callback))
 }
 
-pub fn each_implementation_for_type(cstore: &cstore::CStore,
+pub fn each_implementation_for_type(sess: &Session,
                                     def_id: ast::DefId,
                                     callback: |ast::DefId|) {
-    let cdata = cstore.get_crate_data(def_id.krate);
-    decoder::each_implementation_for_type(&*cdata, def_id.node, callback)
+    let cdata = sess.cstore.get_crate_data(def_id.krate);
+    decoded(sess, &*cdata,
+            decoder::each_implementation_for_type(&*cdata, def_id.node, callback))
 }
 
-pub fn each_implementation_for_trait(cstore: &cstore::CStore,
+pub fn each_implementation_for_trait(sess: &Session,
                                      def_id: ast::DefId,
                                      callback: |ast::DefId|) {
-    let cdata = cstore.get_crate_data(def_id.krate);
-    decoder::each_implementation_for_trait(&*cdata, def_id.node, callback)
+    let cdata = sess.cstore.get_crate_data(def_id.krate);
+    decoded(sess, &*cdata,
+            decoder::each_implementation_for_trait(&*cdata, def_id.node, callback))
 }
 
 /// If the given def ID describes a method belonging to a trait (either a
 /// default method or an implementation of a trait method), returns the ID of
 /// the trait that the method belongs to. Otherwise, returns `None`.
-pub fn get_trait_of_method(cstore: &cstore::CStore,
+pub fn get_trait_of_method(sess: &Session,
                            def_id: ast::DefId,
                            tcx: &ty::ctxt)
                            -> Option<ast::DefId> {
-    let cdata = cstore.get_crate_data(def_id.krate);
-    decoder::get_trait_of_method(&*cdata, def_id.node, tcx)
+    let cdata = sess.cstore.get_crate_data(def_id.krate);
+    decoded(sess, &*cdata, decoder::get_trait_of_method(&*cdata, def_id.node, tcx))
 }
 
-pub fn get_tuple_struct_definition_if_ctor(cstore: &cstore::CStore,
+pub fn get_tuple_struct_definition_if_ctor(sess: &Session,
                                            def_id: ast::DefId)
     -> Option<ast::DefId>
 {
-    let cdata = cstore.get_crate_data(def_id.krate);
-    decoder::get_tuple_struct_definition_if_ctor(&*cdata, def_id.node)
+    let cdata = sess.cstore.get_crate_data(def_id.krate);
+    decoded(sess, &*cdata,
+            decoder::get_tuple_struct_definition_if_ctor(&*cdata, def_id.node))
 }
 
-pub fn get_dylib_dependency_formats(cstore: &cstore::CStore,
+pub fn get_dylib_dependency_formats(sess: &Session,
                                     cnum: ast::CrateNum)
     -> Vec<(ast::CrateNum, cstore::LinkagePreference)>
 {
-    let cdata = cstore.get_crate_data(cnum);
-    decoder::get_dylib_dependency_formats(&*cdata)
+    let cdata = sess.cstore.get_crate_data(cnum);
+    decoded(sess, &*cdata, decoder::get_dylib_dependency_formats(&*cdata))
 }
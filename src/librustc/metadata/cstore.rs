@@ -14,7 +14,6 @@
 // crates and libraries
 
 use back::svh::Svh;
-use metadata::decoder;
 use metadata::loader;
 
 use std::cell::RefCell;
@@ -39,6 +38,8 @@ pub enum MetadataBlob {
 
 pub struct crate_metadata {
     pub name: ~str,
+    pub crate_id: CrateId,
+    pub hash: Svh,
     pub data: MetadataBlob,
     pub cnum_map: cnum_map,
     pub cnum: ast::CrateNum,
@@ -100,8 +101,7 @@ impl CStore {
     }
 
     pub fn get_crate_hash(&self, cnum: ast::CrateNum) -> Svh {
-        let cdata = self.get_crate_data(cnum);
-        decoder::get_crate_hash(cdata.data())
+        self.get_crate_data(cnum).hash.clone()
     }
 
     pub fn set_crate_data(&self, cnum: ast::CrateNum, data: Rc<crate_metadata>) {
@@ -212,8 +212,6 @@ impl CStore {
 
 impl crate_metadata {
     pub fn data<'a>(&'a self) -> &'a [u8] { self.data.as_slice() }
-    pub fn crate_id(&self) -> CrateId { decoder::get_crate_id(self.data()) }
-    pub fn hash(&self) -> Svh { decoder::get_crate_hash(self.data()) }
 }
 
 impl MetadataBlob {
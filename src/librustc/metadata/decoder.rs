@@ -13,6 +13,7 @@
 #![allow(non_camel_case_types)]
 
 use back::svh::Svh;
+use driver::session::Session;
 use metadata::cstore::crate_metadata;
 use metadata::common::*;
 use metadata::csearch::StaticMethodInfo;
@@ -34,6 +35,7 @@ use std::io::extensions::u64_from_be_bytes;
 use std::option;
 use std::rc::Rc;
 use serialize::ebml::reader;
+use serialize::ebml::reader::DecodeResult;
 use serialize::ebml;
 use serialize::Decodable;
 use syntax::ast_map;
@@ -47,6 +49,47 @@ use syntax::crateid::CrateId;
 
 pub type Cmd<'a> = &'a crate_metadata;
 
+/// Returns the value decoded from the metadata of the crate `name`, reporting
+/// a fatal error if the metadata is malformed.
+pub fn expect_decoded<T>(sess: &Session, name: &str, r: DecodeResult<T>) -> T {
+    match r {
+        Ok(t) => t,
+        Err(e) => sess.fatal(format!("malformed metadata in crate `{}`: {}", name, e)),
+    }
+}
+
+// Like `reader::tagged_docs`, but `it` may fail, which stops the iteration.
+fn each_tagged_doc<'a>(d: ebml::Doc<'a>, tg: uint,
+                       it: |ebml::Doc<'a>| -> DecodeResult<bool>) -> DecodeResult<bool> {
+    let mut err = None;
+    let ret = try!(reader::tagged_docs(d, tg, |doc| {
+        match it(doc) {
+            Ok(more) => more,
+            Err(e) => { err = Some(e); false }
+        }
+    }));
+    match err {
+        Some(e) => Err(e),
+        None => Ok(ret)
+    }
+}
+
+// Like `reader::docs`, but `it` may fail, which stops the iteration.
+fn each_doc<'a>(d: ebml::Doc<'a>,
+                it: |uint, ebml::Doc<'a>| -> DecodeResult<bool>) -> DecodeResult<bool> {
+    let mut err = None;
+    let ret = try!(reader::docs(d, |tag, doc| {
+        match it(tag, doc) {
+            Ok(more) => more,
+            Err(e) => { err = Some(e); false }
+        }
+    }));
+    match err {
+        Some(e) => Err(e),
+        None => Ok(ret)
+    }
+}
+
 // A function that takes a def_id relative to the crate being searched and
 // returns a def_id relative to the compilation environment, i.e. if we hit a
 // def_id for an item defined in another crate, somebody needs to figure out
@@ -54,30 +97,30 @@ pub type Cmd<'a> = &'a crate_metadata;
 // build.
 
 fn lookup_hash<'a>(d: ebml::Doc<'a>, eq_fn: |&[u8]| -> bool,
-                   hash: u64) -> Option<ebml::Doc<'a>> {
-    let index = reader::get_doc(d, tag_index);
-    let table = reader::get_doc(index, tag_index_table);
+                   hash: u64) -> DecodeResult<Option<ebml::Doc<'a>>> {
+    let index = try!(reader::get_doc(d, tag_index));
+    let table = try!(reader::get_doc(index, tag_index_table));
     let hash_pos = table.start + (hash % 256 * 4) as uint;
     let pos = u64_from_be_bytes(d.data, hash_pos, 4) as uint;
-    let tagged_doc = reader::doc_at(d.data, pos).unwrap();
+    let tagged_doc = try!(reader::doc_at(d.data, pos));
 
     let belt = tag_index_buckets_bucket_elt;
 
     let mut ret = None;
-    reader::tagged_docs(tagged_doc.doc, belt, |elt| {
+    try!(each_tagged_doc(tagged_doc.doc, belt, |elt| {
         let pos = u64_from_be_bytes(elt.data, elt.start, 4) as uint;
         if eq_fn(elt.data.slice(elt.start + 4, elt.end)) {
-            ret = Some(reader::doc_at(d.data, pos).unwrap().doc);
-            false
+            ret = Some(try!(reader::doc_at(d.data, pos)).doc);
+            Ok(false)
         } else {
-            true
+            Ok(true)
         }
-    });
-    ret
+    }));
+    Ok(ret)
 }
 
 pub fn maybe_find_item<'a>(item_id: ast::NodeId,
-                           items: ebml::Doc<'a>) -> Option<ebml::Doc<'a>> {
+                           items: ebml::Doc<'a>) -> DecodeResult<Option<ebml::Doc<'a>>> {
     fn eq_item(bytes: &[u8], item_id: ast::NodeId) -> bool {
         return u64_from_be_bytes(
             bytes.slice(0u, 4u), 0u, 4u) as ast::NodeId
@@ -88,17 +131,17 @@ pub fn maybe_find_item<'a>(item_id: ast::NodeId,
                 hash::hash(&(item_id as i64)))
 }
 
-fn find_item<'a>(item_id: ast::NodeId, items: ebml::Doc<'a>) -> ebml::Doc<'a> {
-    match maybe_find_item(item_id, items) {
+fn find_item<'a>(item_id: ast::NodeId, items: ebml::Doc<'a>) -> DecodeResult<ebml::Doc<'a>> {
+    match try!(maybe_find_item(item_id, items)) {
        None => fail!("lookup_item: id not found: {}", item_id),
-       Some(d) => d
+       Some(d) => Ok(d)
     }
 }
 
 // Looks up an item in the given metadata and returns an ebml doc pointing
 // to the item data.
-fn lookup_item<'a>(item_id: ast::NodeId, data: &'a [u8]) -> ebml::Doc<'a> {
-    let items = reader::get_doc(reader::Doc(data), tag_items);
+fn lookup_item<'a>(item_id: ast::NodeId, data: &'a [u8]) -> DecodeResult<ebml::Doc<'a>> {
+    let items = try!(reader::get_doc(reader::Doc(data), tag_items));
     find_item(item_id, items)
 }
 
@@ -124,9 +167,9 @@ enum Family {
     InheritedField         // N
 }
 
-fn item_family(item: ebml::Doc) -> Family {
-    let fam = reader::get_doc(item, tag_items_data_item_family);
-    match reader::doc_as_u8(fam) as char {
+fn item_family(item: ebml::Doc) -> DecodeResult<Family> {
+    let fam = try!(reader::get_doc(item, tag_items_data_item_family));
+    Ok(match try!(reader::doc_as_u8(fam)) as char {
       'c' => ImmStatic,
       'b' => MutStatic,
       'f' => Fn,
@@ -146,98 +189,98 @@ fn item_family(item: ebml::Doc) -> Family {
       'g' => PublicField,
       'N' => InheritedField,
        c => fail!("unexpected family char: {}", c)
-    }
+    })
 }
 
-fn item_visibility(item: ebml::Doc) -> ast::Visibility {
-    match reader::maybe_get_doc(item, tag_items_data_item_visibility) {
+fn item_visibility(item: ebml::Doc) -> DecodeResult<ast::Visibility> {
+    Ok(match try!(reader::maybe_get_doc(item, tag_items_data_item_visibility)) {
         None => ast::Public,
         Some(visibility_doc) => {
-            match reader::doc_as_u8(visibility_doc) as char {
+            match try!(reader::doc_as_u8(visibility_doc)) as char {
                 'y' => ast::Public,
                 'i' => ast::Inherited,
                 _ => fail!("unknown visibility character")
             }
         }
-    }
+    })
 }
 
-fn item_sized(item: ebml::Doc) -> ast::Sized {
-    match reader::maybe_get_doc(item, tag_items_data_item_sized) {
+fn item_sized(item: ebml::Doc) -> DecodeResult<ast::Sized> {
+    Ok(match try!(reader::maybe_get_doc(item, tag_items_data_item_sized)) {
         None => ast::StaticSize,
         Some(sized_doc) => {
-            match reader::doc_as_u8(sized_doc) as char {
+            match try!(reader::doc_as_u8(sized_doc)) as char {
                 'd' => ast::DynSize,
                 's' => ast::StaticSize,
                 _ => fail!("unknown sized-ness character")
             }
         }
-    }
+    })
 }
 
-fn item_method_sort(item: ebml::Doc) -> char {
+fn item_method_sort(item: ebml::Doc) -> DecodeResult<char> {
     let mut ret = 'r';
-    reader::tagged_docs(item, tag_item_trait_method_sort, |doc| {
-        ret = doc.as_str_slice()[0] as char;
-        false
-    });
-    ret
+    try!(each_tagged_doc(item, tag_item_trait_method_sort, |doc| {
+        ret = try!(doc.as_str_slice())[0] as char;
+        Ok(false)
+    }));
+    Ok(ret)
 }
 
-fn item_symbol(item: ebml::Doc) -> ~str {
-    reader::get_doc(item, tag_items_data_item_symbol).as_str()
+fn item_symbol(item: ebml::Doc) -> DecodeResult<~str> {
+    try!(reader::get_doc(item, tag_items_data_item_symbol)).as_str()
 }
 
-fn item_parent_item(d: ebml::Doc) -> Option<ast::DefId> {
+fn item_parent_item(d: ebml::Doc) -> DecodeResult<Option<ast::DefId>> {
     let mut ret = None;
-    reader::tagged_docs(d, tag_items_data_parent_item, |did| {
+    try!(reader::tagged_docs(d, tag_items_data_parent_item, |did| {
         ret = Some(reader::with_doc_data(did, parse_def_id));
         false
-    });
-    ret
+    }));
+    Ok(ret)
 }
 
 fn item_reqd_and_translated_parent_item(cnum: ast::CrateNum,
-                                        d: ebml::Doc) -> ast::DefId {
-    let trait_did = item_parent_item(d).expect("item without parent");
-    ast::DefId { krate: cnum, node: trait_did.node }
+                                        d: ebml::Doc) -> DecodeResult<ast::DefId> {
+    let trait_did = try!(item_parent_item(d)).expect("item without parent");
+    Ok(ast::DefId { krate: cnum, node: trait_did.node })
 }
 
-fn item_def_id(d: ebml::Doc, cdata: Cmd) -> ast::DefId {
-    let tagdoc = reader::get_doc(d, tag_def_id);
-    return translate_def_id(cdata, reader::with_doc_data(tagdoc, parse_def_id));
+fn item_def_id(d: ebml::Doc, cdata: Cmd) -> DecodeResult<ast::DefId> {
+    let tagdoc = try!(reader::get_doc(d, tag_def_id));
+    Ok(translate_def_id(cdata, reader::with_doc_data(tagdoc, parse_def_id)))
 }
 
-fn get_provided_source(d: ebml::Doc, cdata: Cmd) -> Option<ast::DefId> {
-    reader::maybe_get_doc(d, tag_item_method_provided_source).map(|doc| {
+fn get_provided_source(d: ebml::Doc, cdata: Cmd) -> DecodeResult<Option<ast::DefId>> {
+    Ok(try!(reader::maybe_get_doc(d, tag_item_method_provided_source)).map(|doc| {
         translate_def_id(cdata, reader::with_doc_data(doc, parse_def_id))
-    })
+    }))
 }
 
-fn each_reexport(d: ebml::Doc, f: |ebml::Doc| -> bool) -> bool {
-    reader::tagged_docs(d, tag_items_data_item_reexport, f)
+fn each_reexport(d: ebml::Doc, f: |ebml::Doc| -> DecodeResult<bool>) -> DecodeResult<bool> {
+    each_tagged_doc(d, tag_items_data_item_reexport, f)
 }
 
-fn variant_disr_val(d: ebml::Doc) -> Option<ty::Disr> {
-    reader::maybe_get_doc(d, tag_disr_val).and_then(|val_doc| {
+fn variant_disr_val(d: ebml::Doc) -> DecodeResult<Option<ty::Disr>> {
+    Ok(try!(reader::maybe_get_doc(d, tag_disr_val)).and_then(|val_doc| {
         reader::with_doc_data(val_doc, |data| u64::parse_bytes(data, 10u))
-    })
+    }))
 }
 
-fn doc_type(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> ty::t {
-    let tp = reader::get_doc(doc, tag_items_data_item_type);
-    parse_ty_data(tp.data, cdata.cnum, tp.start, tcx,
-                  |_, did| translate_def_id(cdata, did))
+fn doc_type(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> DecodeResult<ty::t> {
+    let tp = try!(reader::get_doc(doc, tag_items_data_item_type));
+    Ok(parse_ty_data(tp.data, cdata.cnum, tp.start, tcx,
+                     |_, did| translate_def_id(cdata, did)))
 }
 
-fn doc_method_fty(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> ty::BareFnTy {
-    let tp = reader::get_doc(doc, tag_item_method_fty);
-    parse_bare_fn_ty_data(tp.data, cdata.cnum, tp.start, tcx,
-                          |_, did| translate_def_id(cdata, did))
+fn doc_method_fty(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> DecodeResult<ty::BareFnTy> {
+    let tp = try!(reader::get_doc(doc, tag_item_method_fty));
+    Ok(parse_bare_fn_ty_data(tp.data, cdata.cnum, tp.start, tcx,
+                             |_, did| translate_def_id(cdata, did)))
 }
 
 pub fn item_type(_item_id: ast::DefId, item: ebml::Doc,
-                 tcx: &ty::ctxt, cdata: Cmd) -> ty::t {
+                 tcx: &ty::ctxt, cdata: Cmd) -> DecodeResult<ty::t> {
     doc_type(item, tcx, cdata)
 }
 
@@ -246,92 +289,92 @@ fn doc_trait_ref(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> ty::TraitRef {
                          |_, did| translate_def_id(cdata, did))
 }
 
-fn item_trait_ref(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> ty::TraitRef {
-    let tp = reader::get_doc(doc, tag_item_trait_ref);
-    doc_trait_ref(tp, tcx, cdata)
+fn item_trait_ref(doc: ebml::Doc, tcx: &ty::ctxt, cdata: Cmd) -> DecodeResult<ty::TraitRef> {
+    let tp = try!(reader::get_doc(doc, tag_item_trait_ref));
+    Ok(doc_trait_ref(tp, tcx, cdata))
 }
 
 fn item_ty_param_defs(item: ebml::Doc,
                       tcx: &ty::ctxt,
                       cdata: Cmd,
                       tag: uint)
-                      -> Rc<Vec<ty::TypeParameterDef> > {
+                      -> DecodeResult<Rc<Vec<ty::TypeParameterDef> >> {
     let mut bounds = Vec::new();
-    reader::tagged_docs(item, tag, |p| {
+    try!(reader::tagged_docs(item, tag, |p| {
         let bd = parse_type_param_def_data(
             p.data, p.start, cdata.cnum, tcx,
             |_, did| translate_def_id(cdata, did));
         bounds.push(bd);
         true
-    });
-    Rc::new(bounds)
+    }));
+    Ok(Rc::new(bounds))
 }
 
 fn item_region_param_defs(item_doc: ebml::Doc, cdata: Cmd)
-                          -> Rc<Vec<ty::RegionParameterDef> > {
+                          -> DecodeResult<Rc<Vec<ty::RegionParameterDef> >> {
     let mut v = Vec::new();
-    reader::tagged_docs(item_doc, tag_region_param_def, |rp_doc| {
-            let ident_str_doc = reader::get_doc(rp_doc,
-                                                tag_region_param_def_ident);
-            let ident = item_name(&*token::get_ident_interner(), ident_str_doc);
-            let def_id_doc = reader::get_doc(rp_doc,
-                                             tag_region_param_def_def_id);
+    try!(each_tagged_doc(item_doc, tag_region_param_def, |rp_doc| {
+            let ident_str_doc = try!(reader::get_doc(rp_doc,
+                                                     tag_region_param_def_ident));
+            let ident = try!(item_name(&*token::get_ident_interner(), ident_str_doc));
+            let def_id_doc = try!(reader::get_doc(rp_doc,
+                                                  tag_region_param_def_def_id));
             let def_id = reader::with_doc_data(def_id_doc, parse_def_id);
             let def_id = translate_def_id(cdata, def_id);
             v.push(ty::RegionParameterDef { name: ident.name,
                                             def_id: def_id });
-            true
-        });
-    Rc::new(v)
+            Ok(true)
+        }));
+    Ok(Rc::new(v))
 }
 
-fn enum_variant_ids(item: ebml::Doc, cdata: Cmd) -> Vec<ast::DefId> {
+fn enum_variant_ids(item: ebml::Doc, cdata: Cmd) -> DecodeResult<Vec<ast::DefId>> {
     let mut ids: Vec<ast::DefId> = Vec::new();
     let v = tag_items_data_item_variant;
-    reader::tagged_docs(item, v, |p| {
+    try!(reader::tagged_docs(item, v, |p| {
         let ext = reader::with_doc_data(p, parse_def_id);
         ids.push(ast::DefId { krate: cdata.cnum, node: ext.node });
         true
-    });
-    return ids;
+    }));
+    return Ok(ids);
 }
 
-fn item_path(item_doc: ebml::Doc) -> Vec<ast_map::PathElem> {
-    let path_doc = reader::get_doc(item_doc, tag_path);
+fn item_path(item_doc: ebml::Doc) -> DecodeResult<Vec<ast_map::PathElem>> {
+    let path_doc = try!(reader::get_doc(item_doc, tag_path));
 
-    let len_doc = reader::get_doc(path_doc, tag_path_len);
-    let len = reader::doc_as_u32(len_doc) as uint;
+    let len_doc = try!(reader::get_doc(path_doc, tag_path_len));
+    let len = try!(reader::doc_as_u32(len_doc)) as uint;
 
     let mut result = Vec::with_capacity(len);
-    reader::docs(path_doc, |tag, elt_doc| {
+    try!(each_doc(path_doc, |tag, elt_doc| {
         if tag == tag_path_elem_mod {
-            let s = elt_doc.as_str_slice();
+            let s = try!(elt_doc.as_str_slice());
             result.push(ast_map::PathMod(token::intern(s)));
         } else if tag == tag_path_elem_name {
-            let s = elt_doc.as_str_slice();
+            let s = try!(elt_doc.as_str_slice());
             result.push(ast_map::PathName(token::intern(s)));
         } else {
             // ignore tag_path_len element
         }
-        true
-    });
+        Ok(true)
+    }));
 
-    result
+    Ok(result)
 }
 
-fn item_name(intr: &IdentInterner, item: ebml::Doc) -> ast::Ident {
-    let name = reader::get_doc(item, tag_paths_data_name);
-    let string = name.as_str_slice();
-    match intr.find_equiv(&string) {
+fn item_name(intr: &IdentInterner, item: ebml::Doc) -> DecodeResult<ast::Ident> {
+    let name = try!(reader::get_doc(item, tag_paths_data_name));
+    let string = try!(name.as_str_slice());
+    Ok(match intr.find_equiv(&string) {
         None => token::str_to_ident(string),
         Some(val) => ast::Ident::new(val as ast::Name),
-    }
+    })
 }
 
 fn item_to_def_like(item: ebml::Doc, did: ast::DefId, cnum: ast::CrateNum)
-    -> DefLike {
-    let fam = item_family(item);
-    match fam {
+    -> DecodeResult<DefLike> {
+    let fam = try!(item_family(item));
+    Ok(match fam {
         ImmStatic => DlDef(ast::DefStatic(did, false)),
         MutStatic => DlDef(ast::DefStatic(did, true)),
         Struct    => DlDef(ast::DefStruct(did)),
@@ -345,13 +388,13 @@ fn item_to_def_like(item: ebml::Doc, did: ast::DefId, cnum: ast::CrateNum)
             // So we need to detect whether this is in a trait or not, which
             // we do through the mildly hacky way of checking whether there is
             // a trait_method_sort.
-            let provenance = if reader::maybe_get_doc(
-                  item, tag_item_trait_method_sort).is_some() {
-                ast::FromTrait(item_reqd_and_translated_parent_item(cnum,
-                                                                    item))
+            let provenance = if try!(reader::maybe_get_doc(
+                  item, tag_item_trait_method_sort)).is_some() {
+                ast::FromTrait(try!(item_reqd_and_translated_parent_item(cnum,
+                                                                         item)))
             } else {
-                ast::FromImpl(item_reqd_and_translated_parent_item(cnum,
-                                                                   item))
+                ast::FromImpl(try!(item_reqd_and_translated_parent_item(cnum,
+                                                                        item)))
             };
             DlDef(ast::DefStaticMethod(did, provenance, fn_style))
         }
@@ -359,40 +402,40 @@ fn item_to_def_like(item: ebml::Doc, did: ast::DefId, cnum: ast::CrateNum)
         Mod => DlDef(ast::DefMod(did)),
         ForeignMod => DlDef(ast::DefForeignMod(did)),
         StructVariant => {
-            let enum_did = item_reqd_and_translated_parent_item(cnum, item);
+            let enum_did = try!(item_reqd_and_translated_parent_item(cnum, item));
             DlDef(ast::DefVariant(enum_did, did, true))
         }
         TupleVariant => {
-            let enum_did = item_reqd_and_translated_parent_item(cnum, item);
+            let enum_did = try!(item_reqd_and_translated_parent_item(cnum, item));
             DlDef(ast::DefVariant(enum_did, did, false))
         }
         Trait => DlDef(ast::DefTrait(did)),
         Enum => DlDef(ast::DefTy(did)),
         Impl => DlImpl(did),
         PublicField | InheritedField => DlField,
-    }
+    })
 }
 
 pub fn get_trait_def(cdata: Cmd,
                      item_id: ast::NodeId,
-                     tcx: &ty::ctxt) -> ty::TraitDef
+                     tcx: &ty::ctxt) -> DecodeResult<ty::TraitDef>
 {
-    let item_doc = lookup_item(item_id, cdata.data());
-    let tp_defs = item_ty_param_defs(item_doc, tcx, cdata,
-                                     tag_items_data_item_ty_param_bounds);
-    let rp_defs = item_region_param_defs(item_doc, cdata);
-    let sized = item_sized(item_doc);
+    let item_doc = try!(lookup_item(item_id, cdata.data()));
+    let tp_defs = try!(item_ty_param_defs(item_doc, tcx, cdata,
+                                          tag_items_data_item_ty_param_bounds));
+    let rp_defs = try!(item_region_param_defs(item_doc, cdata));
+    let sized = try!(item_sized(item_doc));
     let mut bounds = ty::EmptyBuiltinBounds();
     // Collect the builtin bounds from the encoded supertraits.
     // FIXME(#8559): They should be encoded directly.
-    reader::tagged_docs(item_doc, tag_item_super_trait_ref, |trait_doc| {
+    try!(reader::tagged_docs(item_doc, tag_item_super_trait_ref, |trait_doc| {
         // NB. Bypasses real supertraits. See get_supertraits() if you wanted them.
         let trait_ref = doc_trait_ref(trait_doc, tcx, cdata);
         tcx.lang_items.to_builtin_kind(trait_ref.def_id).map(|bound| {
             bounds.add(bound);
         });
         true
-    });
+    }));
     // Turn sized into a bound, FIXME(#8559).
     if sized == ast::StaticSize {
         tcx.lang_items.to_builtin_kind(tcx.lang_items.sized_trait().unwrap()).map(|bound| {
@@ -400,59 +443,60 @@ pub fn get_trait_def(cdata: Cmd,
         });
     }
 
-    ty::TraitDef {
+    Ok(ty::TraitDef {
         generics: ty::Generics {type_param_defs: tp_defs,
                                 region_param_defs: rp_defs},
         bounds: bounds,
-        trait_ref: Rc::new(item_trait_ref(item_doc, tcx, cdata))
-    }
+        trait_ref: Rc::new(try!(item_trait_ref(item_doc, tcx, cdata)))
+    })
 }
 
 pub fn get_type(cdata: Cmd, id: ast::NodeId, tcx: &ty::ctxt)
-    -> ty::ty_param_bounds_and_ty {
+    -> DecodeResult<ty::ty_param_bounds_and_ty> {
 
-    let item = lookup_item(id, cdata.data());
+    let item = try!(lookup_item(id, cdata.data()));
 
-    let t = item_type(ast::DefId { krate: cdata.cnum, node: id }, item, tcx,
-                      cdata);
+    let t = try!(item_type(ast::DefId { krate: cdata.cnum, node: id }, item, tcx,
+                           cdata));
 
-    let tp_defs = item_ty_param_defs(item, tcx, cdata, tag_items_data_item_ty_param_bounds);
-    let rp_defs = item_region_param_defs(item, cdata);
+    let tp_defs = try!(item_ty_param_defs(item, tcx, cdata,
+                                          tag_items_data_item_ty_param_bounds));
+    let rp_defs = try!(item_region_param_defs(item, cdata));
 
-    ty::ty_param_bounds_and_ty {
+    Ok(ty::ty_param_bounds_and_ty {
         generics: ty::Generics {type_param_defs: tp_defs,
                                 region_param_defs: rp_defs},
         ty: t
-    }
+    })
 }
 
 pub fn get_impl_trait(cdata: Cmd,
                       id: ast::NodeId,
-                      tcx: &ty::ctxt) -> Option<Rc<ty::TraitRef>>
+                      tcx: &ty::ctxt) -> DecodeResult<Option<Rc<ty::TraitRef>>>
 {
-    let item_doc = lookup_item(id, cdata.data());
-    reader::maybe_get_doc(item_doc, tag_item_trait_ref).map(|tp| {
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    Ok(try!(reader::maybe_get_doc(item_doc, tag_item_trait_ref)).map(|tp| {
         Rc::new(doc_trait_ref(tp, tcx, cdata))
-    })
+    }))
 }
 
 pub fn get_impl_vtables(cdata: Cmd,
                         id: ast::NodeId,
-                        tcx: &ty::ctxt) -> typeck::impl_res
+                        tcx: &ty::ctxt) -> DecodeResult<typeck::impl_res>
 {
-    let item_doc = lookup_item(id, cdata.data());
-    let vtables_doc = reader::get_doc(item_doc, tag_item_impl_vtables);
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    let vtables_doc = try!(reader::get_doc(item_doc, tag_item_impl_vtables));
     let mut decoder = reader::Decoder(vtables_doc);
 
-    typeck::impl_res {
+    Ok(typeck::impl_res {
         trait_vtables: decoder.read_vtable_res(tcx, cdata),
         self_vtables: decoder.read_vtable_param_res(tcx, cdata)
-    }
+    })
 }
 
 
-pub fn get_symbol(data: &[u8], id: ast::NodeId) -> ~str {
-    return item_symbol(lookup_item(id, data));
+pub fn get_symbol(data: &[u8], id: ast::NodeId) -> DecodeResult<~str> {
+    return item_symbol(try!(lookup_item(id, data)));
 }
 
 // Something that a name can resolve to.
@@ -464,17 +508,17 @@ pub enum DefLike {
 }
 
 /// Iterates over the language items in the given crate.
-pub fn each_lang_item(cdata: Cmd, f: |ast::NodeId, uint| -> bool) -> bool {
+pub fn each_lang_item(cdata: Cmd, f: |ast::NodeId, uint| -> bool) -> DecodeResult<bool> {
     let root = reader::Doc(cdata.data());
-    let lang_items = reader::get_doc(root, tag_lang_items);
-    reader::tagged_docs(lang_items, tag_lang_items_item, |item_doc| {
-        let id_doc = reader::get_doc(item_doc, tag_lang_items_item_id);
-        let id = reader::doc_as_u32(id_doc) as uint;
-        let node_id_doc = reader::get_doc(item_doc,
-                                          tag_lang_items_item_node_id);
-        let node_id = reader::doc_as_u32(node_id_doc) as ast::NodeId;
-
-        f(node_id, id)
+    let lang_items = try!(reader::get_doc(root, tag_lang_items));
+    each_tagged_doc(lang_items, tag_lang_items_item, |item_doc| {
+        let id_doc = try!(reader::get_doc(item_doc, tag_lang_items_item_id));
+        let id = try!(reader::doc_as_u32(id_doc)) as uint;
+        let node_id_doc = try!(reader::get_doc(item_doc,
+                                               tag_lang_items_item_node_id));
+        let node_id = try!(reader::doc_as_u32(node_id_doc)) as ast::NodeId;
+
+        Ok(f(node_id, id))
     })
 }
 
@@ -486,9 +530,10 @@ fn each_child_of_item_or_crate(intr: Rc<IdentInterner>,
                                get_crate_data: GetCrateDataCb,
                                callback: |DefLike,
                                           ast::Ident,
-                                          ast::Visibility|) {
+                                          ast::Visibility|)
+                               -> DecodeResult<()> {
     // Iterate over all children.
-    let _ = reader::tagged_docs(item_doc, tag_mod_child, |child_info_doc| {
+    try!(each_tagged_doc(item_doc, tag_mod_child, |child_info_doc| {
         let child_def_id = reader::with_doc_data(child_info_doc,
                                                  parse_def_id);
         let child_def_id = translate_def_id(cdata, child_def_id);
@@ -505,87 +550,88 @@ fn each_child_of_item_or_crate(intr: Rc<IdentInterner>,
             None => cdata
         };
 
-        let other_crates_items = reader::get_doc(reader::Doc(crate_data.data()), tag_items);
+        let other_crates_items = try!(reader::get_doc(reader::Doc(crate_data.data()),
+                                                      tag_items));
 
         // Get the item.
-        match maybe_find_item(child_def_id.node, other_crates_items) {
+        match try!(maybe_find_item(child_def_id.node, other_crates_items)) {
             None => {}
             Some(child_item_doc) => {
                 // Hand off the item to the callback.
-                let child_name = item_name(&*intr, child_item_doc);
-                let def_like = item_to_def_like(child_item_doc,
-                                                child_def_id,
-                                                cdata.cnum);
-                let visibility = item_visibility(child_item_doc);
+                let child_name = try!(item_name(&*intr, child_item_doc));
+                let def_like = try!(item_to_def_like(child_item_doc,
+                                                     child_def_id,
+                                                     cdata.cnum));
+                let visibility = try!(item_visibility(child_item_doc));
                 callback(def_like, child_name, visibility);
 
             }
         }
 
-        true
-    });
+        Ok(true)
+    }));
 
     // As a special case, iterate over all static methods of
     // associated implementations too. This is a bit of a botch.
     // --pcwalton
-    let _ = reader::tagged_docs(item_doc,
-                                tag_items_data_item_inherent_impl,
-                                |inherent_impl_def_id_doc| {
-        let inherent_impl_def_id = item_def_id(inherent_impl_def_id_doc,
-                                               cdata);
-        let items = reader::get_doc(reader::Doc(cdata.data()), tag_items);
-        match maybe_find_item(inherent_impl_def_id.node, items) {
+    try!(each_tagged_doc(item_doc,
+                         tag_items_data_item_inherent_impl,
+                         |inherent_impl_def_id_doc| {
+        let inherent_impl_def_id = try!(item_def_id(inherent_impl_def_id_doc,
+                                                    cdata));
+        let items = try!(reader::get_doc(reader::Doc(cdata.data()), tag_items));
+        match try!(maybe_find_item(inherent_impl_def_id.node, items)) {
             None => {}
             Some(inherent_impl_doc) => {
-                let _ = reader::tagged_docs(inherent_impl_doc,
-                                            tag_item_impl_method,
-                                            |impl_method_def_id_doc| {
+                try!(each_tagged_doc(inherent_impl_doc,
+                                     tag_item_impl_method,
+                                     |impl_method_def_id_doc| {
                     let impl_method_def_id =
                         reader::with_doc_data(impl_method_def_id_doc,
                                               parse_def_id);
                     let impl_method_def_id =
                         translate_def_id(cdata, impl_method_def_id);
-                    match maybe_find_item(impl_method_def_id.node, items) {
+                    match try!(maybe_find_item(impl_method_def_id.node, items)) {
                         None => {}
                         Some(impl_method_doc) => {
-                            match item_family(impl_method_doc) {
+                            match try!(item_family(impl_method_doc)) {
                                 StaticMethod | UnsafeStaticMethod => {
                                     // Hand off the static method
                                     // to the callback.
                                     let static_method_name =
-                                        item_name(&*intr, impl_method_doc);
+                                        try!(item_name(&*intr, impl_method_doc));
                                     let static_method_def_like =
-                                        item_to_def_like(impl_method_doc,
-                                                         impl_method_def_id,
-                                                         cdata.cnum);
+                                        try!(item_to_def_like(impl_method_doc,
+                                                              impl_method_def_id,
+                                                              cdata.cnum));
                                     callback(static_method_def_like,
                                              static_method_name,
-                                             item_visibility(impl_method_doc));
+                                             try!(item_visibility(impl_method_doc)));
                                 }
                                 _ => {}
                             }
                         }
                     }
 
-                    true
-                });
+                    Ok(true)
+                }));
             }
         }
 
-        true
-    });
+        Ok(true)
+    }));
 
     // Iterate over all reexports.
-    let _ = each_reexport(item_doc, |reexport_doc| {
-        let def_id_doc = reader::get_doc(reexport_doc,
-                                         tag_items_data_item_reexport_def_id);
+    try!(each_reexport(item_doc, |reexport_doc| {
+        let def_id_doc = try!(reader::get_doc(reexport_doc,
+                                              tag_items_data_item_reexport_def_id));
         let child_def_id = reader::with_doc_data(def_id_doc,
                                                  parse_def_id);
         let child_def_id = translate_def_id(cdata, child_def_id);
 
-        let name_doc = reader::get_doc(reexport_doc,
-                                       tag_items_data_item_reexport_name);
-        let name = name_doc.as_str_slice();
+        let name_doc = try!(reader::get_doc(reexport_doc,
+                                            tag_items_data_item_reexport_name));
+        let name = try!(name_doc.as_str_slice());
 
         // This reexport may be in yet another crate.
         let crate_data = if child_def_id.krate == cdata.cnum {
@@ -598,24 +644,26 @@ fn each_child_of_item_or_crate(intr: Rc<IdentInterner>,
             None => cdata
         };
 
-        let other_crates_items = reader::get_doc(reader::Doc(crate_data.data()), tag_items);
+        let other_crates_items = try!(reader::get_doc(reader::Doc(crate_data.data()),
+                                                      tag_items));
 
         // Get the item.
-        match maybe_find_item(child_def_id.node, other_crates_items) {
+        match try!(maybe_find_item(child_def_id.node, other_crates_items)) {
             None => {}
             Some(child_item_doc) => {
                 // Hand off the item to the callback.
-                let def_like = item_to_def_like(child_item_doc,
-                                                child_def_id,
-                                                child_def_id.krate);
+                let def_like = try!(item_to_def_like(child_item_doc,
+                                                     child_def_id,
+                                                     child_def_id.krate));
                 // These items have a public visibility because they're part of
                 // a public re-export.
                 callback(def_like, token::str_to_ident(name), ast::Public);
             }
         }
 
-        true
-    });
+        Ok(true)
+    }));
+    Ok(())
 }
 
 /// Iterates over each child of the given item.
@@ -623,12 +671,13 @@ pub fn each_child_of_item(intr: Rc<IdentInterner>,
                           cdata: Cmd,
                           id: ast::NodeId,
                           get_crate_data: GetCrateDataCb,
-                          callback: |DefLike, ast::Ident, ast::Visibility|) {
+                          callback: |DefLike, ast::Ident, ast::Visibility|)
+                          -> DecodeResult<()> {
     // Find the item.
     let root_doc = reader::Doc(cdata.data());
-    let items = reader::get_doc(root_doc, tag_items);
-    let item_doc = match maybe_find_item(id, items) {
-        None => return,
+    let items = try!(reader::get_doc(root_doc, tag_items));
+    let item_doc = match try!(maybe_find_item(id, items)) {
+        None => return Ok(()),
         Some(item_doc) => item_doc,
     };
 
@@ -645,11 +694,12 @@ pub fn each_top_level_item_of_crate(intr: Rc<IdentInterner>,
                                     get_crate_data: GetCrateDataCb,
                                     callback: |DefLike,
                                                ast::Ident,
-                                               ast::Visibility|) {
+                                               ast::Visibility|)
+                                    -> DecodeResult<()> {
     let root_doc = reader::Doc(cdata.data());
-    let misc_info_doc = reader::get_doc(root_doc, tag_misc_info);
-    let crate_items_doc = reader::get_doc(misc_info_doc,
-                                          tag_misc_info_crate_items);
+    let misc_info_doc = try!(reader::get_doc(root_doc, tag_misc_info));
+    let crate_items_doc = try!(reader::get_doc(misc_info_doc,
+                                               tag_misc_info_crate_items));
 
     each_child_of_item_or_crate(intr,
                                 cdata,
@@ -658,8 +708,8 @@ pub fn each_top_level_item_of_crate(intr: Rc<IdentInterner>,
                                 callback)
 }
 
-pub fn get_item_path(cdata: Cmd, id: ast::NodeId) -> Vec<ast_map::PathElem> {
-    item_path(lookup_item(id, cdata.data()))
+pub fn get_item_path(cdata: Cmd, id: ast::NodeId) -> DecodeResult<Vec<ast_map::PathElem>> {
+    item_path(try!(lookup_item(id, cdata.data())))
 }
 
 pub type DecodeInlinedItem<'a> = |cdata: Cmd,
@@ -670,17 +720,17 @@ pub type DecodeInlinedItem<'a> = |cdata: Cmd,
 
 pub fn maybe_get_item_ast(cdata: Cmd, tcx: &ty::ctxt, id: ast::NodeId,
                           decode_inlined_item: DecodeInlinedItem)
-                          -> csearch::found_ast {
+                          -> DecodeResult<csearch::found_ast> {
     debug!("Looking up item: {}", id);
-    let item_doc = lookup_item(id, cdata.data());
-    let path = Vec::from_slice(item_path(item_doc).init());
-    match decode_inlined_item(cdata, tcx, path, item_doc) {
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    let path = Vec::from_slice(try!(item_path(item_doc)).init());
+    Ok(match decode_inlined_item(cdata, tcx, path, item_doc) {
         Ok(ref ii) => csearch::found(*ii),
         Err(path) => {
-            match item_parent_item(item_doc) {
+            match try!(item_parent_item(item_doc)) {
                 Some(did) => {
                     let did = translate_def_id(cdata, did);
-                    let parent_item = lookup_item(did.node, cdata.data());
+                    let parent_item = try!(lookup_item(did.node, cdata.data()));
                     match decode_inlined_item(cdata, tcx, path, parent_item) {
                         Ok(ref ii) => csearch::found_parent(did, *ii),
                         Err(_) => csearch::not_found
@@ -689,31 +739,32 @@ pub fn maybe_get_item_ast(cdata: Cmd, tcx: &ty::ctxt, id: ast::NodeId,
                 None => csearch::not_found
             }
         }
-    }
+    })
 }
 
 pub fn get_enum_variants(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId,
-                     tcx: &ty::ctxt) -> Vec<Rc<ty::VariantInfo>> {
+                     tcx: &ty::ctxt) -> DecodeResult<Vec<Rc<ty::VariantInfo>>> {
     let data = cdata.data();
-    let items = reader::get_doc(reader::Doc(data), tag_items);
-    let item = find_item(id, items);
+    let items = try!(reader::get_doc(reader::Doc(data), tag_items));
+    let item = try!(find_item(id, items));
     let mut disr_val = 0;
-    enum_variant_ids(item, cdata).iter().map(|did| {
-        let item = find_item(did.node, items);
-        let ctor_ty = item_type(ast::DefId { krate: cdata.cnum, node: id},
-                                item, tcx, cdata);
-        let name = item_name(&*intr, item);
+    let mut variants = Vec::new();
+    for did in try!(enum_variant_ids(item, cdata)).iter() {
+        let item = try!(find_item(did.node, items));
+        let ctor_ty = try!(item_type(ast::DefId { krate: cdata.cnum, node: id},
+                                     item, tcx, cdata));
+        let name = try!(item_name(&*intr, item));
         let arg_tys = match ty::get(ctor_ty).sty {
             ty::ty_bare_fn(ref f) => f.sig.inputs.clone(),
             _ => Vec::new(), // Nullary enum variant.
         };
-        match variant_disr_val(item) {
+        match try!(variant_disr_val(item)) {
             Some(val) => { disr_val = val; }
             _         => { /* empty */ }
         }
         let old_disr_val = disr_val;
         disr_val += 1;
-        Rc::new(ty::VariantInfo {
+        variants.push(Rc::new(ty::VariantInfo {
             args: arg_tys,
             arg_names: None,
             ctor_ty: ctor_ty,
@@ -723,11 +774,12 @@ pub fn get_enum_variants(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId,
             id: *did,
             disr_val: old_disr_val,
             vis: ast::Inherited
-        })
-    }).collect()
+        }));
+    }
+    Ok(variants)
 }
 
-fn get_explicit_self(item: ebml::Doc) -> ast::ExplicitSelf_ {
+fn get_explicit_self(item: ebml::Doc) -> DecodeResult<ast::ExplicitSelf_> {
     fn get_mutability(ch: u8) -> ast::Mutability {
         match ch as char {
             'i' => ast::MutImmutable,
@@ -736,68 +788,68 @@ fn get_explicit_self(item: ebml::Doc) -> ast::ExplicitSelf_ {
         }
     }
 
-    let explicit_self_doc = reader::get_doc(item, tag_item_trait_method_explicit_self);
-    let string = explicit_self_doc.as_str_slice();
+    let explicit_self_doc = try!(reader::get_doc(item, tag_item_trait_method_explicit_self));
+    let string = try!(explicit_self_doc.as_str_slice());
 
     let explicit_self_kind = string[0];
-    match explicit_self_kind as char {
+    Ok(match explicit_self_kind as char {
         's' => ast::SelfStatic,
         'v' => ast::SelfValue,
         '~' => ast::SelfUniq,
         // FIXME(#4846) expl. region
         '&' => ast::SelfRegion(None, get_mutability(string[1])),
         _ => fail!("unknown self type code: `{}`", explicit_self_kind as char)
-    }
+    })
 }
 
 /// Returns information about the given implementation.
-pub fn get_impl_methods(cdata: Cmd, impl_id: ast::NodeId) -> Vec<ast::DefId> {
+pub fn get_impl_methods(cdata: Cmd, impl_id: ast::NodeId) -> DecodeResult<Vec<ast::DefId>> {
     let mut methods = Vec::new();
-    reader::tagged_docs(lookup_item(impl_id, cdata.data()),
-                        tag_item_impl_method, |doc| {
+    try!(reader::tagged_docs(try!(lookup_item(impl_id, cdata.data())),
+                             tag_item_impl_method, |doc| {
         let m_did = reader::with_doc_data(doc, parse_def_id);
         methods.push(translate_def_id(cdata, m_did));
         true
-    });
+    }));
 
-    methods
+    Ok(methods)
 }
 
 pub fn get_method_name_and_explicit_self(
     intr: Rc<IdentInterner>,
     cdata: Cmd,
-    id: ast::NodeId) -> (ast::Ident, ast::ExplicitSelf_)
+    id: ast::NodeId) -> DecodeResult<(ast::Ident, ast::ExplicitSelf_)>
 {
-    let method_doc = lookup_item(id, cdata.data());
-    let name = item_name(&*intr, method_doc);
-    let explicit_self = get_explicit_self(method_doc);
-    (name, explicit_self)
+    let method_doc = try!(lookup_item(id, cdata.data()));
+    let name = try!(item_name(&*intr, method_doc));
+    let explicit_self = try!(get_explicit_self(method_doc));
+    Ok((name, explicit_self))
 }
 
 pub fn get_method(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId,
-                  tcx: &ty::ctxt) -> ty::Method
+                  tcx: &ty::ctxt) -> DecodeResult<ty::Method>
 {
-    let method_doc = lookup_item(id, cdata.data());
-    let def_id = item_def_id(method_doc, cdata);
+    let method_doc = try!(lookup_item(id, cdata.data()));
+    let def_id = try!(item_def_id(method_doc, cdata));
 
-    let container_id = item_reqd_and_translated_parent_item(cdata.cnum,
-                                                            method_doc);
-    let container_doc = lookup_item(container_id.node, cdata.data());
-    let container = match item_family(container_doc) {
+    let container_id = try!(item_reqd_and_translated_parent_item(cdata.cnum,
+                                                                 method_doc));
+    let container_doc = try!(lookup_item(container_id.node, cdata.data()));
+    let container = match try!(item_family(container_doc)) {
         Trait => TraitContainer(container_id),
         _ => ImplContainer(container_id),
     };
 
-    let name = item_name(&*intr, method_doc);
-    let type_param_defs = item_ty_param_defs(method_doc, tcx, cdata,
-                                             tag_item_method_tps);
-    let rp_defs = item_region_param_defs(method_doc, cdata);
-    let fty = doc_method_fty(method_doc, tcx, cdata);
-    let vis = item_visibility(method_doc);
-    let explicit_self = get_explicit_self(method_doc);
-    let provided_source = get_provided_source(method_doc, cdata);
+    let name = try!(item_name(&*intr, method_doc));
+    let type_param_defs = try!(item_ty_param_defs(method_doc, tcx, cdata,
+                                                  tag_item_method_tps));
+    let rp_defs = try!(item_region_param_defs(method_doc, cdata));
+    let fty = try!(doc_method_fty(method_doc, tcx, cdata));
+    let vis = try!(item_visibility(method_doc));
+    let explicit_self = try!(get_explicit_self(method_doc));
+    let provided_source = try!(get_provided_source(method_doc, cdata));
 
-    ty::Method::new(
+    Ok(ty::Method::new(
         name,
         ty::Generics {
             type_param_defs: type_param_defs,
@@ -809,55 +861,55 @@ pub fn get_method(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId,
         def_id,
         container,
         provided_source
-    )
+    ))
 }
 
 pub fn get_trait_method_def_ids(cdata: Cmd,
-                                id: ast::NodeId) -> Vec<ast::DefId> {
+                                id: ast::NodeId) -> DecodeResult<Vec<ast::DefId>> {
     let data = cdata.data();
-    let item = lookup_item(id, data);
+    let item = try!(lookup_item(id, data));
     let mut result = Vec::new();
-    reader::tagged_docs(item, tag_item_trait_method, |mth| {
-        result.push(item_def_id(mth, cdata));
-        true
-    });
-    result
+    try!(each_tagged_doc(item, tag_item_trait_method, |mth| {
+        result.push(try!(item_def_id(mth, cdata)));
+        Ok(true)
+    }));
+    Ok(result)
 }
 
-pub fn get_item_variances(cdata: Cmd, id: ast::NodeId) -> ty::ItemVariances {
+pub fn get_item_variances(cdata: Cmd, id: ast::NodeId) -> DecodeResult<ty::ItemVariances> {
     let data = cdata.data();
-    let item_doc = lookup_item(id, data);
-    let variance_doc = reader::get_doc(item_doc, tag_item_variances);
+    let item_doc = try!(lookup_item(id, data));
+    let variance_doc = try!(reader::get_doc(item_doc, tag_item_variances));
     let mut decoder = reader::Decoder(variance_doc);
-    Decodable::decode(&mut decoder).unwrap()
+    Decodable::decode(&mut decoder)
 }
 
 pub fn get_provided_trait_methods(intr: Rc<IdentInterner>, cdata: Cmd,
                                   id: ast::NodeId, tcx: &ty::ctxt)
-                                  -> Vec<Rc<ty::Method>> {
+                                  -> DecodeResult<Vec<Rc<ty::Method>>> {
     let data = cdata.data();
-    let item = lookup_item(id, data);
+    let item = try!(lookup_item(id, data));
     let mut result = Vec::new();
 
-    reader::tagged_docs(item, tag_item_trait_method, |mth_id| {
-        let did = item_def_id(mth_id, cdata);
-        let mth = lookup_item(did.node, data);
+    try!(each_tagged_doc(item, tag_item_trait_method, |mth_id| {
+        let did = try!(item_def_id(mth_id, cdata));
+        let mth = try!(lookup_item(did.node, data));
 
-        if item_method_sort(mth) == 'p' {
-            result.push(Rc::new(get_method(intr.clone(), cdata, did.node, tcx)));
+        if try!(item_method_sort(mth)) == 'p' {
+            result.push(Rc::new(try!(get_method(intr.clone(), cdata, did.node, tcx))));
         }
-        true
-    });
+        Ok(true)
+    }));
 
-    return result;
+    return Ok(result);
 }
 
 /// Returns the supertraits of the given trait.
 pub fn get_supertraits(cdata: Cmd, id: ast::NodeId, tcx: &ty::ctxt)
-                    -> Vec<Rc<ty::TraitRef>> {
+                    -> DecodeResult<Vec<Rc<ty::TraitRef>>> {
     let mut results = Vec::new();
-    let item_doc = lookup_item(id, cdata.data());
-    reader::tagged_docs(item_doc, tag_item_super_trait_ref, |trait_doc| {
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    try!(reader::tagged_docs(item_doc, tag_item_super_trait_ref, |trait_doc| {
         // NB. Only reads the ones that *aren't* builtin-bounds. See also
         // get_trait_def() for collecting the builtin bounds.
         // FIXME(#8559): The builtin bounds shouldn't be encoded in the first place.
@@ -866,106 +918,107 @@ pub fn get_supertraits(cdata: Cmd, id: ast::NodeId, tcx: &ty::ctxt)
             results.push(Rc::new(trait_ref));
         }
         true
-    });
-    return results;
+    }));
+    return Ok(results);
 }
 
 pub fn get_type_name_if_impl(cdata: Cmd,
-                             node_id: ast::NodeId) -> Option<ast::Ident> {
-    let item = lookup_item(node_id, cdata.data());
-    if item_family(item) != Impl {
-        return None;
+                             node_id: ast::NodeId) -> DecodeResult<Option<ast::Ident>> {
+    let item = try!(lookup_item(node_id, cdata.data()));
+    if try!(item_family(item)) != Impl {
+        return Ok(None);
     }
 
     let mut ret = None;
-    reader::tagged_docs(item, tag_item_impl_type_basename, |doc| {
-        ret = Some(token::str_to_ident(doc.as_str_slice()));
-        false
-    });
+    try!(each_tagged_doc(item, tag_item_impl_type_basename, |doc| {
+        ret = Some(token::str_to_ident(try!(doc.as_str_slice())));
+        Ok(false)
+    }));
 
-    ret
+    Ok(ret)
 }
 
 pub fn get_static_methods_if_impl(intr: Rc<IdentInterner>,
                                   cdata: Cmd,
                                   node_id: ast::NodeId)
-                               -> Option<Vec<StaticMethodInfo> > {
-    let item = lookup_item(node_id, cdata.data());
-    if item_family(item) != Impl {
-        return None;
+                               -> DecodeResult<Option<Vec<StaticMethodInfo> >> {
+    let item = try!(lookup_item(node_id, cdata.data()));
+    if try!(item_family(item)) != Impl {
+        return Ok(None);
     }
 
     // If this impl implements a trait, don't consider it.
-    let ret = reader::tagged_docs(item, tag_item_trait_ref, |_doc| {
+    let ret = try!(reader::tagged_docs(item, tag_item_trait_ref, |_doc| {
         false
-    });
+    }));
 
-    if !ret { return None }
+    if !ret { return Ok(None) }
 
     let mut impl_method_ids = Vec::new();
-    reader::tagged_docs(item, tag_item_impl_method, |impl_method_doc| {
+    try!(reader::tagged_docs(item, tag_item_impl_method, |impl_method_doc| {
         impl_method_ids.push(reader::with_doc_data(impl_method_doc, parse_def_id));
         true
-    });
+    }));
 
     let mut static_impl_methods = Vec::new();
     for impl_method_id in impl_method_ids.iter() {
-        let impl_method_doc = lookup_item(impl_method_id.node, cdata.data());
-        let family = item_family(impl_method_doc);
+        let impl_method_doc = try!(lookup_item(impl_method_id.node, cdata.data()));
+        let family = try!(item_family(impl_method_doc));
         match family {
             StaticMethod | UnsafeStaticMethod => {
                 let fn_style;
-                match item_family(impl_method_doc) {
+                match family {
                     StaticMethod => fn_style = ast::NormalFn,
                     UnsafeStaticMethod => fn_style = ast::UnsafeFn,
                     _ => fail!()
                 }
 
                 static_impl_methods.push(StaticMethodInfo {
-                    ident: item_name(&*intr, impl_method_doc),
-                    def_id: item_def_id(impl_method_doc, cdata),
+                    ident: try!(item_name(&*intr, impl_method_doc)),
+                    def_id: try!(item_def_id(impl_method_doc, cdata)),
                     fn_style: fn_style,
-                    vis: item_visibility(impl_method_doc),
+                    vis: try!(item_visibility(impl_method_doc)),
                 });
             }
             _ => {}
         }
     }
 
-    return Some(static_impl_methods);
+    return Ok(Some(static_impl_methods));
 }
 
 /// If node_id is the constructor of a tuple struct, retrieve the NodeId of
 /// the actual type definition, otherwise, return None
 pub fn get_tuple_struct_definition_if_ctor(cdata: Cmd,
                                            node_id: ast::NodeId)
-    -> Option<ast::DefId>
+    -> DecodeResult<Option<ast::DefId>>
 {
-    let item = lookup_item(node_id, cdata.data());
+    let item = try!(lookup_item(node_id, cdata.data()));
     let mut ret = None;
-    reader::tagged_docs(item, tag_items_data_item_is_tuple_struct_ctor, |_| {
-        ret = Some(item_reqd_and_translated_parent_item(cdata.cnum, item));
-        false
-    });
-    ret
+    try!(each_tagged_doc(item, tag_items_data_item_is_tuple_struct_ctor, |_| {
+        ret = Some(try!(item_reqd_and_translated_parent_item(cdata.cnum, item)));
+        Ok(false)
+    }));
+    Ok(ret)
 }
 
 pub fn get_item_attrs(cdata: Cmd,
                       orig_node_id: ast::NodeId,
-                      f: |Vec<@ast::MetaItem> |) {
+                      f: |Vec<@ast::MetaItem> |) -> DecodeResult<()> {
     // The attributes for a tuple struct are attached to the definition, not the ctor;
     // we assume that someone passing in a tuple struct ctor is actually wanting to
     // look at the definition
-    let node_id = get_tuple_struct_definition_if_ctor(cdata, orig_node_id);
+    let node_id = try!(get_tuple_struct_definition_if_ctor(cdata, orig_node_id));
     let node_id = node_id.map(|x| x.node).unwrap_or(orig_node_id);
-    let item = lookup_item(node_id, cdata.data());
-    reader::tagged_docs(item, tag_attributes, |attributes| {
-        reader::tagged_docs(attributes, tag_attribute, |attribute| {
-            f(get_meta_items(attribute));
-            true
-        });
-        true
-    });
+    let item = try!(lookup_item(node_id, cdata.data()));
+    try!(each_tagged_doc(item, tag_attributes, |attributes| {
+        try!(each_tagged_doc(attributes, tag_attribute, |attribute| {
+            f(try!(get_meta_items(attribute)));
+            Ok(true)
+        }));
+        Ok(true)
+    }));
+    Ok(())
 }
 
 fn struct_field_family_to_visibility(family: Family) -> ast::Visibility {
@@ -977,17 +1030,17 @@ fn struct_field_family_to_visibility(family: Family) -> ast::Visibility {
 }
 
 pub fn get_struct_fields(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId)
-    -> Vec<ty::field_ty> {
+    -> DecodeResult<Vec<ty::field_ty>> {
     let data = cdata.data();
-    let item = lookup_item(id, data);
+    let item = try!(lookup_item(id, data));
     let mut result = Vec::new();
-    reader::tagged_docs(item, tag_item_field, |an_item| {
-        let f = item_family(an_item);
+    try!(each_tagged_doc(item, tag_item_field, |an_item| {
+        let f = try!(item_family(an_item));
         if f == PublicField || f == InheritedField {
             // FIXME #6993: name should be of type Name, not Ident
-            let name = item_name(&*intr, an_item);
-            let did = item_def_id(an_item, cdata);
-            let tagdoc = reader::get_doc(an_item, tag_item_field_origin);
+            let name = try!(item_name(&*intr, an_item));
+            let did = try!(item_def_id(an_item, cdata));
+            let tagdoc = try!(reader::get_doc(an_item, tag_item_field_origin));
             let origin_id =  translate_def_id(cdata, reader::with_doc_data(tagdoc, parse_def_id));
             result.push(ty::field_ty {
                 name: name.name,
@@ -996,12 +1049,12 @@ pub fn get_struct_fields(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId)
                 origin: origin_id,
             });
         }
-        true
-    });
-    reader::tagged_docs(item, tag_item_unnamed_field, |an_item| {
-        let did = item_def_id(an_item, cdata);
-        let tagdoc = reader::get_doc(an_item, tag_item_field_origin);
-        let f = item_family(an_item);
+        Ok(true)
+    }));
+    try!(each_tagged_doc(item, tag_item_unnamed_field, |an_item| {
+        let did = try!(item_def_id(an_item, cdata));
+        let tagdoc = try!(reader::get_doc(an_item, tag_item_field_origin));
+        let f = try!(item_family(an_item));
         let origin_id =  translate_def_id(cdata, reader::with_doc_data(tagdoc, parse_def_id));
         result.push(ty::field_ty {
             name: special_idents::unnamed_field.name,
@@ -1009,45 +1062,45 @@ pub fn get_struct_fields(intr: Rc<IdentInterner>, cdata: Cmd, id: ast::NodeId)
             vis: struct_field_family_to_visibility(f),
             origin: origin_id,
         });
-        true
-    });
-    result
+        Ok(true)
+    }));
+    Ok(result)
 }
 
-fn get_meta_items(md: ebml::Doc) -> Vec<@ast::MetaItem> {
+fn get_meta_items(md: ebml::Doc) -> DecodeResult<Vec<@ast::MetaItem>> {
     let mut items: Vec<@ast::MetaItem> = Vec::new();
-    reader::tagged_docs(md, tag_meta_item_word, |meta_item_doc| {
-        let nd = reader::get_doc(meta_item_doc, tag_meta_item_name);
-        let n = token::intern_and_get_ident(nd.as_str_slice());
+    try!(each_tagged_doc(md, tag_meta_item_word, |meta_item_doc| {
+        let nd = try!(reader::get_doc(meta_item_doc, tag_meta_item_name));
+        let n = token::intern_and_get_ident(try!(nd.as_str_slice()));
         items.push(attr::mk_word_item(n));
-        true
-    });
-    reader::tagged_docs(md, tag_meta_item_name_value, |meta_item_doc| {
-        let nd = reader::get_doc(meta_item_doc, tag_meta_item_name);
-        let vd = reader::get_doc(meta_item_doc, tag_meta_item_value);
-        let n = token::intern_and_get_ident(nd.as_str_slice());
-        let v = token::intern_and_get_ident(vd.as_str_slice());
+        Ok(true)
+    }));
+    try!(each_tagged_doc(md, tag_meta_item_name_value, |meta_item_doc| {
+        let nd = try!(reader::get_doc(meta_item_doc, tag_meta_item_name));
+        let vd = try!(reader::get_doc(meta_item_doc, tag_meta_item_value));
+        let n = token::intern_and_get_ident(try!(nd.as_str_slice()));
+        let v = token::intern_and_get_ident(try!(vd.as_str_slice()));
         // FIXME (#623): Should be able to decode MetaNameValue variants,
         // but currently the encoder just drops them
         items.push(attr::mk_name_value_item_str(n, v));
-        true
-    });
-    reader::tagged_docs(md, tag_meta_item_list, |meta_item_doc| {
-        let nd = reader::get_doc(meta_item_doc, tag_meta_item_name);
-        let n = token::intern_and_get_ident(nd.as_str_slice());
-        let subitems = get_meta_items(meta_item_doc);
+        Ok(true)
+    }));
+    try!(each_tagged_doc(md, tag_meta_item_list, |meta_item_doc| {
+        let nd = try!(reader::get_doc(meta_item_doc, tag_meta_item_name));
+        let n = token::intern_and_get_ident(try!(nd.as_str_slice()));
+        let subitems = try!(get_meta_items(meta_item_doc));
         items.push(attr::mk_list_item(n, subitems.move_iter().collect()));
-        true
-    });
-    return items;
+        Ok(true)
+    }));
+    return Ok(items);
 }
 
-fn get_attributes(md: ebml::Doc) -> Vec<ast::Attribute> {
+fn get_attributes(md: ebml::Doc) -> DecodeResult<Vec<ast::Attribute>> {
     let mut attrs: Vec<ast::Attribute> = Vec::new();
-    match reader::maybe_get_doc(md, tag_attributes) {
+    match try!(reader::maybe_get_doc(md, tag_attributes)) {
       option::Some(attrs_d) => {
-        reader::tagged_docs(attrs_d, tag_attribute, |attr_doc| {
-            let meta_items = get_meta_items(attr_doc);
+        try!(each_tagged_doc(attrs_d, tag_attribute, |attr_doc| {
+            let meta_items = try!(get_meta_items(attr_doc));
             // Currently it's only possible to have a single meta item on
             // an attribute
             assert_eq!(meta_items.len(), 1u);
@@ -1061,27 +1114,26 @@ fn get_attributes(md: ebml::Doc) -> Vec<ast::Attribute> {
                     },
                     span: codemap::DUMMY_SP
                 });
-            true
-        });
+            Ok(true)
+        }));
       }
       option::None => ()
     }
-    return attrs;
+    return Ok(attrs);
 }
 
-fn list_crate_attributes(md: ebml::Doc, hash: &Svh,
+fn list_crate_attributes(attrs: &[ast::Attribute], hash: &Svh,
                          out: &mut io::Writer) -> io::IoResult<()> {
     try!(write!(out, "=Crate Attributes ({})=\n", *hash));
 
-    let r = get_attributes(md);
-    for attr in r.iter() {
+    for attr in attrs.iter() {
         try!(write!(out, "{}\n", pprust::attribute_to_str(attr)));
     }
 
     write!(out, "\n\n")
 }
 
-pub fn get_crate_attributes(data: &[u8]) -> Vec<ast::Attribute> {
+pub fn get_crate_attributes(data: &[u8]) -> DecodeResult<Vec<ast::Attribute>> {
     get_attributes(reader::Doc(data))
 }
 
@@ -1092,75 +1144,85 @@ pub struct CrateDep {
     pub hash: Svh,
 }
 
-pub fn get_crate_deps(data: &[u8]) -> Vec<CrateDep> {
+pub fn get_crate_deps(data: &[u8]) -> DecodeResult<Vec<CrateDep>> {
     let mut deps: Vec<CrateDep> = Vec::new();
     let cratedoc = reader::Doc(data);
-    let depsdoc = reader::get_doc(cratedoc, tag_crate_deps);
+    let depsdoc = try!(reader::get_doc(cratedoc, tag_crate_deps));
     let mut crate_num = 1;
-    fn docstr(doc: ebml::Doc, tag_: uint) -> ~str {
-        let d = reader::get_doc(doc, tag_);
-        d.as_str_slice().to_str()
+    fn docstr(doc: ebml::Doc, tag_: uint) -> DecodeResult<~str> {
+        let d = try!(reader::get_doc(doc, tag_));
+        Ok(try!(d.as_str_slice()).to_str())
     }
-    reader::tagged_docs(depsdoc, tag_crate_dep, |depdoc| {
-        let crate_id = from_str(docstr(depdoc, tag_crate_dep_crateid)).unwrap();
-        let hash = Svh::new(docstr(depdoc, tag_crate_dep_hash));
+    try!(each_tagged_doc(depsdoc, tag_crate_dep, |depdoc| {
+        let crate_id = from_str(try!(docstr(depdoc, tag_crate_dep_crateid))).unwrap();
+        let hash = Svh::new(try!(docstr(depdoc, tag_crate_dep_hash)));
         deps.push(CrateDep {
             cnum: crate_num,
             crate_id: crate_id,
             hash: hash,
         });
         crate_num += 1;
-        true
-    });
-    return deps;
+        Ok(true)
+    }));
+    return Ok(deps);
 }
 
-fn list_crate_deps(data: &[u8], out: &mut io::Writer) -> io::IoResult<()> {
+fn list_crate_deps(deps: &[CrateDep], out: &mut io::Writer) -> io::IoResult<()> {
     try!(write!(out, "=External Dependencies=\n"));
-    for dep in get_crate_deps(data).iter() {
+    for dep in deps.iter() {
         try!(write!(out, "{} {}-{}\n", dep.cnum, dep.crate_id, dep.hash));
     }
     try!(write!(out, "\n"));
     Ok(())
 }
 
-pub fn maybe_get_crate_hash(data: &[u8]) -> Option<Svh> {
+pub fn maybe_get_crate_hash(data: &[u8]) -> DecodeResult<Option<Svh>> {
     let cratedoc = reader::Doc(data);
-    reader::maybe_get_doc(cratedoc, tag_crate_hash).map(|doc| {
-        Svh::new(doc.as_str_slice())
-    })
+    match try!(reader::maybe_get_doc(cratedoc, tag_crate_hash)) {
+        Some(doc) => Ok(Some(Svh::new(try!(doc.as_str_slice())))),
+        None => Ok(None)
+    }
 }
 
-pub fn get_crate_hash(data: &[u8]) -> Svh {
+pub fn get_crate_hash(data: &[u8]) -> DecodeResult<Svh> {
     let cratedoc = reader::Doc(data);
-    let hashdoc = reader::get_doc(cratedoc, tag_crate_hash);
-    Svh::new(hashdoc.as_str_slice())
+    let hashdoc = try!(reader::get_doc(cratedoc, tag_crate_hash));
+    Ok(Svh::new(try!(hashdoc.as_str_slice())))
 }
 
-pub fn maybe_get_crate_id(data: &[u8]) -> Option<CrateId> {
+pub fn maybe_get_crate_id(data: &[u8]) -> DecodeResult<Option<CrateId>> {
     let cratedoc = reader::Doc(data);
-    reader::maybe_get_doc(cratedoc, tag_crate_crateid).map(|doc| {
-        from_str(doc.as_str_slice()).unwrap()
-    })
+    match try!(reader::maybe_get_doc(cratedoc, tag_crate_crateid)) {
+        Some(doc) => Ok(Some(from_str(try!(doc.as_str_slice())).unwrap())),
+        None => Ok(None)
+    }
 }
 
-pub fn get_crate_triple(data: &[u8]) -> ~str {
+pub fn get_crate_triple(data: &[u8]) -> DecodeResult<~str> {
     let cratedoc = reader::Doc(data);
-    let triple_doc = reader::maybe_get_doc(cratedoc, tag_crate_triple);
+    let triple_doc = try!(reader::maybe_get_doc(cratedoc, tag_crate_triple));
     triple_doc.expect("No triple in crate").as_str()
 }
 
-pub fn get_crate_id(data: &[u8]) -> CrateId {
+pub fn get_crate_id(data: &[u8]) -> DecodeResult<CrateId> {
     let cratedoc = reader::Doc(data);
-    let hashdoc = reader::get_doc(cratedoc, tag_crate_crateid);
-    from_str(hashdoc.as_str_slice()).unwrap()
+    let hashdoc = try!(reader::get_doc(cratedoc, tag_crate_crateid));
+    Ok(from_str(try!(hashdoc.as_str_slice())).unwrap())
 }
 
 pub fn list_crate_metadata(bytes: &[u8], out: &mut io::Writer) -> io::IoResult<()> {
-    let hash = get_crate_hash(bytes);
-    let md = reader::Doc(bytes);
-    try!(list_crate_attributes(md, &hash, out));
-    list_crate_deps(bytes, out)
+    let decoded = get_crate_hash(bytes).and_then(|hash| {
+        let attrs = try!(get_crate_attributes(bytes));
+        let deps = try!(get_crate_deps(bytes));
+        Ok((hash, attrs, deps))
+    });
+    match decoded {
+        Ok((hash, attrs, deps)) => {
+            try!(list_crate_attributes(attrs.as_slice(), &hash, out));
+            list_crate_deps(deps.as_slice(), out)
+        }
+        Err(e) => write!(out, "malformed metadata: {}\n", e)
+    }
 }
 
 // Translates a def_id from an external crate to a def_id for the current
@@ -1184,102 +1246,111 @@ pub fn translate_def_id(cdata: Cmd, did: ast::DefId) -> ast::DefId {
     }
 }
 
-pub fn each_impl(cdata: Cmd, callback: |ast::DefId|) {
-    let impls_doc = reader::get_doc(reader::Doc(cdata.data()), tag_impls);
-    let _ = reader::tagged_docs(impls_doc, tag_impls_impl, |impl_doc| {
-        callback(item_def_id(impl_doc, cdata));
-        true
-    });
+pub fn each_impl(cdata: Cmd, callback: |ast::DefId|) -> DecodeResult<()> {
+    let impls_doc = try!(reader::get_doc(reader::Doc(cdata.data()), tag_impls));
+    try!(each_tagged_doc(impls_doc, tag_impls_impl, |impl_doc| {
+        callback(try!(item_def_id(impl_doc, cdata)));
+        Ok(true)
+    }));
+    Ok(())
 }
 
 pub fn each_implementation_for_type(cdata: Cmd,
                                     id: ast::NodeId,
-                                    callback: |ast::DefId|) {
-    let item_doc = lookup_item(id, cdata.data());
-    reader::tagged_docs(item_doc,
-                        tag_items_data_item_inherent_impl,
-                        |impl_doc| {
-        let implementation_def_id = item_def_id(impl_doc, cdata);
+                                    callback: |ast::DefId|) -> DecodeResult<()> {
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    try!(each_tagged_doc(item_doc,
+                         tag_items_data_item_inherent_impl,
+                         |impl_doc| {
+        let implementation_def_id = try!(item_def_id(impl_doc, cdata));
         callback(implementation_def_id);
-        true
-    });
+        Ok(true)
+    }));
+    Ok(())
 }
 
 pub fn each_implementation_for_trait(cdata: Cmd,
                                      id: ast::NodeId,
-                                     callback: |ast::DefId|) {
-    let item_doc = lookup_item(id, cdata.data());
+                                     callback: |ast::DefId|) -> DecodeResult<()> {
+    let item_doc = try!(lookup_item(id, cdata.data()));
 
-    let _ = reader::tagged_docs(item_doc,
-                                tag_items_data_item_extension_impl,
-                                |impl_doc| {
-        let implementation_def_id = item_def_id(impl_doc, cdata);
+    try!(each_tagged_doc(item_doc,
+                         tag_items_data_item_extension_impl,
+                         |impl_doc| {
+        let implementation_def_id = try!(item_def_id(impl_doc, cdata));
         callback(implementation_def_id);
-        true
-    });
+        Ok(true)
+    }));
+    Ok(())
 }
 
 pub fn get_trait_of_method(cdata: Cmd, id: ast::NodeId, tcx: &ty::ctxt)
-                           -> Option<ast::DefId> {
-    let item_doc = lookup_item(id, cdata.data());
-    let parent_item_id = match item_parent_item(item_doc) {
-        None => return None,
+                           -> DecodeResult<Option<ast::DefId>> {
+    let item_doc = try!(lookup_item(id, cdata.data()));
+    let parent_item_id = match try!(item_parent_item(item_doc)) {
+        None => return Ok(None),
         Some(item_id) => item_id,
     };
     let parent_item_id = translate_def_id(cdata, parent_item_id);
-    let parent_item_doc = lookup_item(parent_item_id.node, cdata.data());
-    match item_family(parent_item_doc) {
-        Trait => Some(item_def_id(parent_item_doc, cdata)),
+    let parent_item_doc = try!(lookup_item(parent_item_id.node, cdata.data()));
+    Ok(match try!(item_family(parent_item_doc)) {
+        Trait => Some(try!(item_def_id(parent_item_doc, cdata))),
         Impl => {
-            reader::maybe_get_doc(parent_item_doc, tag_item_trait_ref)
-                .map(|_| item_trait_ref(parent_item_doc, tcx, cdata).def_id)
+            match try!(reader::maybe_get_doc(parent_item_doc, tag_item_trait_ref)) {
+                Some(_) => Some(try!(item_trait_ref(parent_item_doc, tcx, cdata)).def_id),
+                None => None
+            }
         }
         _ => None
-    }
+    })
 }
 
 
-pub fn get_native_libraries(cdata: Cmd) -> Vec<(cstore::NativeLibaryKind, ~str)> {
-    let libraries = reader::get_doc(reader::Doc(cdata.data()),
-                                    tag_native_libraries);
+pub fn get_native_libraries(cdata: Cmd)
+                            -> DecodeResult<Vec<(cstore::NativeLibaryKind, ~str)>> {
+    let libraries = try!(reader::get_doc(reader::Doc(cdata.data()),
+                                         tag_native_libraries));
     let mut result = Vec::new();
-    reader::tagged_docs(libraries, tag_native_libraries_lib, |lib_doc| {
-        let kind_doc = reader::get_doc(lib_doc, tag_native_libraries_kind);
-        let name_doc = reader::get_doc(lib_doc, tag_native_libraries_name);
+    try!(each_tagged_doc(libraries, tag_native_libraries_lib, |lib_doc| {
+        let kind_doc = try!(reader::get_doc(lib_doc, tag_native_libraries_kind));
+        let name_doc = try!(reader::get_doc(lib_doc, tag_native_libraries_name));
         let kind: cstore::NativeLibaryKind =
-            FromPrimitive::from_u32(reader::doc_as_u32(kind_doc)).unwrap();
-        let name = name_doc.as_str();
+            FromPrimitive::from_u32(try!(reader::doc_as_u32(kind_doc))).unwrap();
+        let name = try!(name_doc.as_str());
         result.push((kind, name));
-        true
-    });
-    return result;
+        Ok(true)
+    }));
+    return Ok(result);
 }
 
-pub fn get_macro_registrar_fn(data: &[u8]) -> Option<ast::NodeId> {
-    reader::maybe_get_doc(reader::Doc(data), tag_macro_registrar_fn)
-        .map(|doc| FromPrimitive::from_u32(reader::doc_as_u32(doc)).unwrap())
+pub fn get_macro_registrar_fn(data: &[u8]) -> DecodeResult<Option<ast::NodeId>> {
+    match try!(reader::maybe_get_doc(reader::Doc(data), tag_macro_registrar_fn)) {
+        Some(doc) => Ok(Some(FromPrimitive::from_u32(try!(reader::doc_as_u32(doc))).unwrap())),
+        None => Ok(None)
+    }
 }
 
-pub fn get_exported_macros(data: &[u8]) -> Vec<~str> {
-    let macros = reader::get_doc(reader::Doc(data),
-                                 tag_exported_macros);
+pub fn get_exported_macros(data: &[u8]) -> DecodeResult<Vec<~str>> {
+    let macros = try!(reader::get_doc(reader::Doc(data),
+                                      tag_exported_macros));
     let mut result = Vec::new();
-    reader::tagged_docs(macros, tag_macro_def, |macro_doc| {
-        result.push(macro_doc.as_str());
-        true
-    });
-    result
+    try!(each_tagged_doc(macros, tag_macro_def, |macro_doc| {
+        result.push(try!(macro_doc.as_str()));
+        Ok(true)
+    }));
+    Ok(result)
 }
 
 pub fn get_dylib_dependency_formats(cdata: Cmd)
-    -> Vec<(ast::CrateNum, cstore::LinkagePreference)>
+    -> DecodeResult<Vec<(ast::CrateNum, cstore::LinkagePreference)>>
 {
-    let formats = reader::get_doc(reader::Doc(cdata.data()),
-                                  tag_dylib_dependency_formats);
+    let formats = try!(reader::get_doc(reader::Doc(cdata.data()),
+                                       tag_dylib_dependency_formats));
+    let formats = try!(formats.as_str_slice());
     let mut result = Vec::new();
 
-    debug!("found dylib deps: {}", formats.as_str_slice());
-    for spec in formats.as_str_slice().split(',') {
+    debug!("found dylib deps: {}", formats);
+    for spec in formats.split(',') {
         if spec.len() == 0 { continue }
         let cnum = spec.split(':').nth(0).unwrap();
         let link = spec.split(':').nth(1).unwrap();
@@ -1294,5 +1365,5 @@ pub fn get_dylib_dependency_formats(cdata: Cmd)
             cstore::RequireStatic
         }));
     }
-    return result;
+    return Ok(result);
 }
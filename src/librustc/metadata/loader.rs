@@ -287,7 +287,9 @@ impl<'a> Context<'a> {
                         None => {}
                     }
                     let data = lib.metadata.as_slice();
-                    let crate_id = decoder::get_crate_id(data);
+                    let crate_id = decoder::expect_decoded(self.sess,
+                                                           self.crate_id.name.as_slice(),
+                                                           decoder::get_crate_id(data));
                     note_crateid_attr(self.sess.diagnostic(), &crate_id);
                 }
                 None
@@ -396,18 +398,21 @@ impl<'a> Context<'a> {
     }
 
     fn crate_matches(&mut self, crate_data: &[u8], libpath: &Path) -> bool {
-        match decoder::maybe_get_crate_id(crate_data) {
+        let (sess, crate_id) = (self.sess, self.crate_id);
+        let name = crate_id.name.as_slice();
+        match decoder::expect_decoded(sess, name, decoder::maybe_get_crate_id(crate_data)) {
             Some(ref id) if self.crate_id.matches(id) => {}
             _ => { info!("Rejecting via crate_id"); return false }
         }
-        let hash = match decoder::maybe_get_crate_hash(crate_data) {
+        let hash = match decoder::expect_decoded(sess, name,
+                                                 decoder::maybe_get_crate_hash(crate_data)) {
             Some(hash) => hash, None => {
                 info!("Rejecting via lack of crate hash");
                 return false;
             }
         };
 
-        let triple = decoder::get_crate_triple(crate_data);
+        let triple = decoder::expect_decoded(sess, name, decoder::get_crate_triple(crate_data));
         if triple.as_slice() != self.triple {
             info!("Rejecting via crate triple: expected {} got {}", self.triple, triple);
             self.rejected_via_triple.push(CrateMismatch{ path: libpath.clone(),
@@ -41,6 +41,7 @@ use std::rc::Rc;
 use std::strbuf::StrBuf;
 
 use serialize::ebml::reader;
+use serialize::ebml::reader::DecodeResult;
 use serialize::ebml;
 use serialize;
 use serialize::{Encoder, Encodable, EncoderHelpers, DecoderHelpers};
@@ -55,6 +56,14 @@ struct DecodeContext<'a> {
     tcx: &'a ty::ctxt,
 }
 
+impl<'a> DecodeContext<'a> {
+    // Returns the value decoded from the metadata of the crate the item is
+    // inlined from, reporting malformed metadata as a fatal error.
+    fn decoded<T>(&self, r: DecodeResult<T>) -> T {
+        decoder::expect_decoded(&self.tcx.sess, self.cdata.name.as_slice(), r)
+    }
+}
+
 struct ExtendedDecodeContext<'a> {
     dcx: &'a DecodeContext<'a>,
     from_id_range: ast_util::IdRange,
@@ -109,7 +118,7 @@ pub fn decode_inlined_item(cdata: &cstore::crate_metadata,
         cdata: cdata,
         tcx: tcx,
     };
-    match par_doc.opt_child(c::tag_ast) {
+    match dcx.decoded(par_doc.opt_child(c::tag_ast)) {
       None => Err(path),
       Some(ast_doc) => {
         let mut path_as_str = None;
@@ -128,7 +137,7 @@ pub fn decode_inlined_item(cdata: &cstore::crate_metadata,
             from_id_range: from_id_range,
             to_id_range: to_id_range
         };
-        let raw_ii = decode_ast(ast_doc);
+        let raw_ii = decode_ast(dcx, ast_doc);
         let ii = renumber_and_map_ast(xcx, &dcx.tcx.map, path, raw_ii);
         let ident = match ii {
             ast::IIItem(i) => i.ident,
@@ -347,8 +356,8 @@ fn simplify_ast(ii: e::InlinedItemRef) -> ast::InlinedItem {
     }
 }
 
-fn decode_ast(par_doc: ebml::Doc) -> ast::InlinedItem {
-    let chi_doc = par_doc.get(c::tag_tree as uint);
+fn decode_ast(dcx: &DecodeContext, par_doc: ebml::Doc) -> ast::InlinedItem {
+    let chi_doc = dcx.decoded(par_doc.get(c::tag_tree as uint));
     let mut d = reader::Decoder(chi_doc);
     Decodable::decode(&mut d).unwrap()
 }
@@ -1058,13 +1067,13 @@ fn encode_side_tables_for_id(ecx: &e::EncodeContext,
 }
 
 trait doc_decoder_helpers {
-    fn as_int(&self) -> int;
-    fn opt_child(&self, tag: c::astencode_tag) -> Option<Self>;
+    fn as_int(&self) -> DecodeResult<int>;
+    fn opt_child(&self, tag: c::astencode_tag) -> DecodeResult<Option<Self>>;
 }
 
 impl<'a> doc_decoder_helpers for ebml::Doc<'a> {
-    fn as_int(&self) -> int { reader::doc_as_u64(*self) as int }
-    fn opt_child(&self, tag: c::astencode_tag) -> Option<ebml::Doc<'a>> {
+    fn as_int(&self) -> DecodeResult<int> { reader::doc_as_u64(*self).map(|n| n as int) }
+    fn opt_child(&self, tag: c::astencode_tag) -> DecodeResult<Option<ebml::Doc<'a>>> {
         reader::maybe_get_doc(*self, tag as uint)
     }
 }
@@ -1282,9 +1291,9 @@ impl<'a> ebml_decoder_decoder_helpers for reader::Decoder<'a> {
 fn decode_side_tables(xcx: &ExtendedDecodeContext,
                       ast_doc: ebml::Doc) {
     let dcx = xcx.dcx;
-    let tbl_doc = ast_doc.get(c::tag_table as uint);
-    reader::docs(tbl_doc, |tag, entry_doc| {
-        let id0 = entry_doc.get(c::tag_table_id as uint).as_int();
+    let tbl_doc = dcx.decoded(ast_doc.get(c::tag_table as uint));
+    dcx.decoded(reader::docs(tbl_doc, |tag, entry_doc| {
+        let id0 = dcx.decoded(entry_doc.get(c::tag_table_id as uint).and_then(|d| d.as_int()));
         let id = xcx.tr_id(id0 as ast::NodeId);
 
         debug!(">> Side table document with tag 0x{:x} \
@@ -1297,7 +1306,7 @@ fn decode_side_tables(xcx: &ExtendedDecodeContext,
                     format!("unknown tag found in side tables: {:x}", tag));
             }
             Some(value) => {
-                let val_doc = entry_doc.get(c::tag_table_val as uint);
+                let val_doc = dcx.decoded(entry_doc.get(c::tag_table_val as uint));
                 let mut val_dsr = reader::Decoder(val_doc);
                 let val_dsr = &mut val_dsr;
 
@@ -1363,7 +1372,7 @@ fn decode_side_tables(xcx: &ExtendedDecodeContext,
 
         debug!(">< Side table doc loaded");
         true
-    });
+    }));
 }
 
 // ______________________________________________________________________
@@ -1378,7 +1387,7 @@ fn encode_item_ast(ebml_w: &mut Encoder, item: @ast::Item) {
 
 #[cfg(test)]
 fn decode_item_ast(par_doc: ebml::Doc) -> @ast::Item {
-    let chi_doc = par_doc.get(c::tag_tree as uint);
+    let chi_doc = par_doc.get(c::tag_tree as uint).unwrap();
     let mut d = reader::Decoder(chi_doc);
     @Decodable::decode(&mut d).unwrap()
 }
@@ -251,40 +251,36 @@ impl<'a> CheckLoanCtxt<'a> {
 
             match (new_loan.kind, old_loan.kind) {
                 (ty::MutBorrow, ty::MutBorrow) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}` as mutable \
-                                more than once at a time",
-                                self.bccx.loan_path_to_str(&*new_loan.loan_path)));
+                    span_err!(self.bccx.tcx.sess, new_loan.span, E0182,
+                        "cannot borrow `{}` as mutable \
+                        more than once at a time",
+                        self.bccx.loan_path_to_str(&*new_loan.loan_path));
                 }
 
                 (ty::UniqueImmBorrow, _) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("closure requires unique access to `{}` \
-                                but {} is already borrowed",
-                                self.bccx.loan_path_to_str(&*new_loan.loan_path),
-                                old_pronoun));
+                    span_err!(self.bccx.tcx.sess, new_loan.span, E0183,
+                        "closure requires unique access to `{}` \
+                        but {} is already borrowed",
+                        self.bccx.loan_path_to_str(&*new_loan.loan_path),
+                        old_pronoun);
                 }
 
                 (_, ty::UniqueImmBorrow) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}` as {} because \
-                                previous closure requires unique access",
-                                self.bccx.loan_path_to_str(&*new_loan.loan_path),
-                                new_loan.kind.to_user_str()));
+                    span_err!(self.bccx.tcx.sess, new_loan.span, E0184,
+                        "cannot borrow `{}` as {} because \
+                        previous closure requires unique access",
+                        self.bccx.loan_path_to_str(&*new_loan.loan_path),
+                        new_loan.kind.to_user_str());
                 }
 
                 (_, _) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}` as {} because \
-                                {} is also borrowed as {}",
-                                self.bccx.loan_path_to_str(&*new_loan.loan_path),
-                                new_loan.kind.to_user_str(),
-                                old_pronoun,
-                                old_loan.kind.to_user_str()));
+                    span_err!(self.bccx.tcx.sess, new_loan.span, E0185,
+                        "cannot borrow `{}` as {} because \
+                        {} is also borrowed as {}",
+                        self.bccx.loan_path_to_str(&*new_loan.loan_path),
+                        new_loan.kind.to_user_str(),
+                        old_pronoun,
+                        old_loan.kind.to_user_str());
                 }
             }
 
@@ -423,19 +419,17 @@ impl<'a> CheckLoanCtxt<'a> {
         // Otherwise, just a plain error.
         match opt_loan_path(&cmt) {
             Some(lp) => {
-                self.bccx.span_err(
-                    expr.span,
-                    format!("cannot assign to {} {} `{}`",
-                            cmt.mutbl.to_user_str(),
-                            self.bccx.cmt_to_str(&*cmt),
-                            self.bccx.loan_path_to_str(&*lp)));
+                span_err!(self.bccx.tcx.sess, expr.span, E0186,
+                    "cannot assign to {} {} `{}`",
+                    cmt.mutbl.to_user_str(),
+                    self.bccx.cmt_to_str(&*cmt),
+                    self.bccx.loan_path_to_str(&*lp));
             }
             None => {
-                self.bccx.span_err(
-                    expr.span,
-                    format!("cannot assign to {} {}",
-                            cmt.mutbl.to_user_str(),
-                            self.bccx.cmt_to_str(&*cmt)));
+                span_err!(self.bccx.tcx.sess, expr.span, E0187,
+                    "cannot assign to {} {}",
+                    cmt.mutbl.to_user_str(),
+                    self.bccx.cmt_to_str(&*cmt));
             }
         }
         return;
@@ -669,10 +663,9 @@ impl<'a> CheckLoanCtxt<'a> {
                                    expr: &ast::Expr,
                                    loan_path: &LoanPath,
                                    loan: &Loan) {
-        self.bccx.span_err(
-            expr.span,
-            format!("cannot assign to `{}` because it is borrowed",
-                 self.bccx.loan_path_to_str(loan_path)));
+        span_err!(self.bccx.tcx.sess, expr.span, E0188,
+            "cannot assign to `{}` because it is borrowed",
+            self.bccx.loan_path_to_str(loan_path));
         self.bccx.span_note(
             loan.span,
             format!("borrow of `{}` occurs here",
@@ -698,11 +691,10 @@ impl<'a> CheckLoanCtxt<'a> {
             match self.analyze_move_out_from(id, move_path) {
                 MoveOk => {}
                 MoveWhileBorrowed(loan_path, loan_span) => {
-                    self.bccx.span_err(
-                        span,
-                        format!("cannot move out of `{}` \
-                                because it is borrowed",
-                             self.bccx.loan_path_to_str(move_path)));
+                    span_err!(self.bccx.tcx.sess, span, E0189,
+                        "cannot move out of `{}` \
+                        because it is borrowed",
+                        self.bccx.loan_path_to_str(move_path));
                     self.bccx.span_note(
                         loan_span,
                         format!("borrow of `{}` occurs here",
@@ -741,11 +733,10 @@ impl<'a> CheckLoanCtxt<'a> {
             match move_err {
                 MoveOk => {}
                 MoveWhileBorrowed(loan_path, loan_span) => {
-                    this.bccx.span_err(
-                        freevar.span,
-                        format!("cannot move `{}` into closure \
-                                because it is borrowed",
-                                this.bccx.loan_path_to_str(move_path)));
+                    span_err!(this.bccx.tcx.sess, freevar.span, E0190,
+                        "cannot move `{}` into closure \
+                        because it is borrowed",
+                        this.bccx.loan_path_to_str(move_path));
                     this.bccx.span_note(
                         loan_span,
                         format!("borrow of `{}` occurs here",
@@ -173,9 +173,9 @@ fn check_aliasability(bccx: &BorrowckCtxt,
                     // In such cases, we cannot permit it to be borrowed, because the
                     // static item resides in immutable memory and mutating it would
                     // cause segfaults.
-                    bccx.tcx.sess.span_err(borrow_span,
-                                           format!("borrow of immutable static items with \
-                                                    unsafe interior is not allowed"));
+                    span_err!(bccx.tcx.sess, borrow_span, E0199,
+                              "borrow of immutable static items with \
+                               unsafe interior is not allowed");
                     Err(())
                 }
                 mc::InteriorSafe => {
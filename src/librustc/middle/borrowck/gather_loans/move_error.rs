@@ -128,10 +128,9 @@ fn report_cannot_move_out_of(bccx: &BorrowckCtxt, move_from: mc::cmt) {
         mc::cat_deref(_, _, mc::UnsafePtr(..)) |
         mc::cat_upvar(..) | mc::cat_static_item |
         mc::cat_copied_upvar(mc::CopiedUpvar { onceness: ast::Many, .. }) => {
-            bccx.span_err(
-                move_from.span,
-                format!("cannot move out of {}",
-                        bccx.cmt_to_str(&*move_from)));
+            span_err!(bccx.tcx.sess, move_from.span, E0200,
+                "cannot move out of {}",
+                bccx.cmt_to_str(&*move_from));
         }
 
         mc::cat_downcast(ref b) |
@@ -139,11 +138,10 @@ fn report_cannot_move_out_of(bccx: &BorrowckCtxt, move_from: mc::cmt) {
             match ty::get(b.ty).sty {
                 ty::ty_struct(did, _)
                 | ty::ty_enum(did, _) if ty::has_dtor(bccx.tcx, did) => {
-                    bccx.span_err(
-                        move_from.span,
-                        format!("cannot move out of type `{}`, \
-                                 which defines the `Drop` trait",
-                                b.ty.user_string(bccx.tcx)));
+                    span_err!(bccx.tcx.sess, move_from.span, E0201,
+                        "cannot move out of type `{}`, \
+                         which defines the `Drop` trait",
+                        b.ty.user_string(bccx.tcx));
                 },
                 _ => fail!("this path should not cause illegal move")
             }
@@ -0,0 +1,132 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Rendering of a control-flow graph along with the borrow checker's data
+flow, for `--pretty flowgraph` with one of the `-Z flowgraph-print-*`
+flags. Each node lists the loans, moves or assignments that are in
+effect on entry to it.
+
+*/
+
+/// For clarity, rename the graphviz crate locally to dot.
+use dot = graphviz;
+pub use middle::cfg::graphviz::{Node, Edge};
+use cfg_dot = middle::cfg::graphviz;
+
+use middle::borrowck::{AnalysisData, BorrowckCtxt, LoanPath};
+use middle::dataflow::{DataFlowContext, DataFlowOperator};
+use syntax::ast;
+
+use std::rc::Rc;
+use std::str;
+
+#[deriving(Eq)]
+pub enum Variant {
+    Loans,
+    Moves,
+    Assigns,
+}
+
+impl Variant {
+    pub fn short_name(&self) -> &'static str {
+        match *self {
+            Loans   => "loans",
+            Moves   => "moves",
+            Assigns => "assigns",
+        }
+    }
+}
+
+pub struct DataflowLabeller<'a> {
+    pub inner: cfg_dot::LabelledCFG<'a>,
+    pub variants: Vec<Variant>,
+    pub borrowck_ctxt: &'a BorrowckCtxt<'a>,
+    pub analysis_data: &'a AnalysisData<'a>,
+}
+
+impl<'a> DataflowLabeller<'a> {
+    fn dataflow_for(&self, id: ast::NodeId) -> ~str {
+        let mut sets = StrBuf::new();
+        for &variant in self.variants.iter() {
+            sets.push_str(variant.short_name());
+            sets.push_str(": ");
+            sets.push_str(self.dataflow_for_variant(id, variant));
+            sets.push_str("\\l");
+        }
+        sets.into_owned()
+    }
+
+    fn dataflow_for_variant(&self, id: ast::NodeId, variant: Variant) -> ~str {
+        let data = self.analysis_data;
+        match variant {
+            Loans => {
+                self.build_set(id, &data.loans, |loan_index| {
+                    data.all_loans.get(loan_index).loan_path.clone()
+                })
+            }
+            Moves => {
+                let move_data = &data.move_data.move_data;
+                self.build_set(id, &data.move_data.dfcx_moves, |move_index| {
+                    let path = move_data.moves.borrow().get(move_index).path;
+                    move_data.path_loan_path(path)
+                })
+            }
+            Assigns => {
+                let move_data = &data.move_data.move_data;
+                self.build_set(id, &data.move_data.dfcx_assign, |assign_index| {
+                    let path = move_data.var_assignments.borrow().get(assign_index).path;
+                    move_data.path_loan_path(path)
+                })
+            }
+        }
+    }
+
+    fn build_set<O:DataFlowOperator>(&self,
+                                     id: ast::NodeId,
+                                     dfcx: &DataFlowContext<'a, O>,
+                                     to_lp: |uint| -> Rc<LoanPath>) -> ~str {
+        let mut indices = Vec::new();
+        dfcx.each_bit_on_entry_frozen(id, |index| {
+            indices.push(index);
+            true
+        });
+
+        let mut set = StrBuf::from_str("{");
+        for (i, &index) in indices.iter().enumerate() {
+            if i > 0 {
+                set.push_str(", ");
+            }
+            let lp = to_lp(index);
+            set.push_str(self.borrowck_ctxt.loan_path_to_str(&*lp));
+        }
+        set.push_str("}");
+        set.into_owned()
+    }
+}
+
+impl<'a> dot::Labeller<'a, Node<'a>, Edge<'a>> for DataflowLabeller<'a> {
+    fn graph_id(&'a self) -> dot::Id<'a> { self.inner.graph_id() }
+    fn node_id(&'a self, n: &Node<'a>) -> dot::Id<'a> { self.inner.node_id(n) }
+    fn node_label(&'a self, n: &Node<'a>) -> dot::LabelText<'a> {
+        let &(_, node) = n;
+        let sets = self.dataflow_for(node.data.id);
+        self.inner.node_label(n).suffix_line(dot::EscStr(str::Owned(sets)))
+    }
+    fn edge_label(&'a self, e: &Edge<'a>) -> dot::LabelText<'a> { self.inner.edge_label(e) }
+}
+
+impl<'a> dot::GraphWalk<'a, Node<'a>, Edge<'a>> for DataflowLabeller<'a> {
+    fn nodes(&'a self) -> dot::Nodes<'a, Node<'a>> { self.inner.nodes() }
+    fn edges(&'a self) -> dot::Edges<'a, Edge<'a>> { self.inner.edges() }
+    fn source(&'a self, edge: &Edge<'a>) -> Node<'a> { self.inner.source(edge) }
+    fn target(&'a self, edge: &Edge<'a>) -> Node<'a> { self.inner.target(edge) }
+}
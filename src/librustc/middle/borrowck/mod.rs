@@ -49,6 +49,8 @@ pub mod gather_loans;
 
 pub mod move_data;
 
+pub mod graphviz;
+
 pub struct LoanDataFlowOperator;
 
 /// FIXME(pcwalton): Should just be #[deriving(Clone)], but that doesn't work
@@ -74,15 +76,7 @@ impl<'a> Visitor<()> for BorrowckCtxt<'a> {
 
 pub fn check_crate(tcx: &ty::ctxt,
                    krate: &ast::Crate) {
-    let mut bccx = BorrowckCtxt {
-        tcx: tcx,
-        stats: @BorrowStats {
-            loaned_paths_same: Cell::new(0),
-            loaned_paths_imm: Cell::new(0),
-            stable_paths: Cell::new(0),
-            guaranteed_paths: Cell::new(0),
-        }
-    };
+    let mut bccx = BorrowckCtxt::new(tcx);
 
     visit::walk_crate(&mut bccx, krate, ());
 
@@ -120,6 +114,13 @@ fn borrowck_item(this: &mut BorrowckCtxt, item: &ast::Item) {
     }
 }
 
+/// The loans and moves of a function, flowed through its body.
+pub struct AnalysisData<'a> {
+    pub all_loans: Vec<Loan>,
+    pub loans: LoanDataFlow<'a>,
+    pub move_data: move_data::FlowedMoveData<'a>,
+}
+
 fn borrowck_fn(this: &mut BorrowckCtxt,
                fk: &FnKind,
                decl: &ast::FnDecl,
@@ -129,6 +130,21 @@ fn borrowck_fn(this: &mut BorrowckCtxt,
     debug!("borrowck_fn(id={})", id);
 
     // Check the body of fn items.
+    let AnalysisData { all_loans, loans: loan_dfcx, move_data: flowed_moves } =
+        build_borrowck_dataflow_data(this, fk, decl, body, sp, id);
+
+    check_loans::check_loans(this, &loan_dfcx, flowed_moves,
+                             all_loans.as_slice(), body);
+
+    visit::walk_fn(this, fk, decl, body, sp, id, ());
+}
+
+fn build_borrowck_dataflow_data<'a>(this: &BorrowckCtxt<'a>,
+                                    fk: &FnKind,
+                                    decl: &ast::FnDecl,
+                                    body: &ast::Block,
+                                    sp: Span,
+                                    id: ast::NodeId) -> AnalysisData<'a> {
     let id_range = ast_util::compute_id_range_for_fn_body(fk, decl, body, sp, id);
     let (all_loans, move_data) =
         gather_loans::gather_loans_in_fn(this, decl, body);
@@ -148,10 +164,25 @@ fn borrowck_fn(this: &mut BorrowckCtxt,
                                                       id_range,
                                                       body);
 
-    check_loans::check_loans(this, &loan_dfcx, flowed_moves,
-                             all_loans.as_slice(), body);
+    AnalysisData {
+        all_loans: all_loans,
+        loans: loan_dfcx,
+        move_data: flowed_moves,
+    }
+}
 
-    visit::walk_fn(this, fk, decl, body, sp, id, ());
+/// Computes the loans and moves of a single function outside of the borrow
+/// checking of the crate, for `--pretty flowgraph`.
+pub fn build_borrowck_dataflow_data_for_fn<'a>(tcx: &'a ty::ctxt,
+                                               fk: &FnKind,
+                                               decl: &ast::FnDecl,
+                                               body: &ast::Block,
+                                               sp: Span,
+                                               id: ast::NodeId)
+                                               -> (BorrowckCtxt<'a>, AnalysisData<'a>) {
+    let bccx = BorrowckCtxt::new(tcx);
+    let data = build_borrowck_dataflow_data(&bccx, fk, decl, body, sp, id);
+    (bccx, data)
 }
 
 // ----------------------------------------------------------------------
@@ -344,6 +375,18 @@ pub enum MovedValueUseKind {
 // Misc
 
 impl<'a> BorrowckCtxt<'a> {
+    pub fn new(tcx: &'a ty::ctxt) -> BorrowckCtxt<'a> {
+        BorrowckCtxt {
+            tcx: tcx,
+            stats: @BorrowStats {
+                loaned_paths_same: Cell::new(0),
+                loaned_paths_imm: Cell::new(0),
+                stable_paths: Cell::new(0),
+                guaranteed_paths: Cell::new(0),
+            }
+        }
+    }
+
     pub fn is_subregion_of(&self, r_sub: ty::Region, r_sup: ty::Region)
                            -> bool {
         self.tcx.region_maps.is_subregion_of(r_sub, r_sup)
@@ -445,9 +488,8 @@ impl<'a> BorrowckCtxt<'a> {
     }
 
     pub fn report(&self, err: BckError) {
-        self.span_err(
-            err.span,
-            self.bckerr_to_str(&err));
+        span_err!(self.tcx.sess, err.span, E0191,
+            "{}", self.bckerr_to_str(&err));
         self.note_and_explain_bckerr(err);
     }
 
@@ -464,20 +506,18 @@ impl<'a> BorrowckCtxt<'a> {
 
         match move.kind {
             move_data::Declared => {
-                self.tcx.sess.span_err(
-                    use_span,
-                    format!("{} of possibly uninitialized variable: `{}`",
-                         verb,
-                         self.loan_path_to_str(lp)));
+                span_err!(self.tcx.sess, use_span, E0192,
+                    "{} of possibly uninitialized variable: `{}`",
+                    verb,
+                    self.loan_path_to_str(lp));
             }
             _ => {
                 let partially = if lp == moved_lp {""} else {"partially "};
-                self.tcx.sess.span_err(
-                    use_span,
-                    format!("{} of {}moved value: `{}`",
-                         verb,
-                         partially,
-                         self.loan_path_to_str(lp)));
+                span_err!(self.tcx.sess, use_span, E0193,
+                    "{} of {}moved value: `{}`",
+                    verb,
+                    partially,
+                    self.loan_path_to_str(lp));
             }
         }
 
@@ -551,19 +591,14 @@ impl<'a> BorrowckCtxt<'a> {
                                                 lp: &LoanPath,
                                                 assign:
                                                 &move_data::Assignment) {
-        self.tcx.sess.span_err(
-            span,
-            format!("re-assignment of immutable variable `{}`",
-                 self.loan_path_to_str(lp)));
+        span_err!(self.tcx.sess, span, E0194,
+            "re-assignment of immutable variable `{}`",
+            self.loan_path_to_str(lp));
         self.tcx.sess.span_note(
             assign.span,
             format!("prior assignment occurs here"));
     }
 
-    pub fn span_err(&self, s: Span, m: &str) {
-        self.tcx.sess.span_err(s, m);
-    }
-
     pub fn span_note(&self, s: Span, m: &str) {
         self.tcx.sess.span_note(s, m);
     }
@@ -653,25 +688,21 @@ impl<'a> BorrowckCtxt<'a> {
 
         match cause {
             mc::AliasableOther => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("{} in an aliasable location", prefix));
+                span_err!(self.tcx.sess, span, E0195,
+                    "{} in an aliasable location", prefix);
             }
             mc::AliasableStatic(..) |
             mc::AliasableStaticMut(..) => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("{} in a static location", prefix));
+                span_err!(self.tcx.sess, span, E0196,
+                    "{} in a static location", prefix);
             }
             mc::AliasableManaged => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("{} in a `@` pointer", prefix));
+                span_err!(self.tcx.sess, span, E0197,
+                    "{} in a `@` pointer", prefix);
             }
             mc::AliasableBorrowed => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("{} in a `&` reference", prefix));
+                span_err!(self.tcx.sess, span, E0198,
+                    "{} in a `&` reference", prefix);
             }
         }
     }
@@ -0,0 +1,107 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Rendering of a control-flow graph with libgraphviz, as used by
+`--pretty flowgraph`. Each node is labelled with the expression,
+pattern or block it stands for, and each edge with the scopes it
+exits.
+
+*/
+
+/// For clarity, rename the graphviz crate locally to dot.
+use dot = graphviz;
+
+use middle::cfg;
+use middle::graph;
+use syntax::ast_map;
+
+use std::str;
+
+pub type Node<'a> = (cfg::CFGIndex, &'a cfg::CFGNode);
+pub type Edge<'a> = &'a cfg::CFGEdge;
+
+pub struct LabelledCFG<'a> {
+    pub ast_map: &'a ast_map::Map,
+    pub cfg: &'a cfg::CFG,
+    /// The name of the graph, which must be a valid DOT identifier.
+    pub name: ~str,
+}
+
+/// Left-aligns each line of `s` in the rendered label.
+fn replace_newline_with_backslash_l(s: &str) -> ~str {
+    // Left-alignment applies to the line that precedes `\l`, so the last
+    // line needs one too.
+    let s = s.replace("\n", "\\l");
+    if s.ends_with("\\l") {
+        s
+    } else {
+        s + "\\l"
+    }
+}
+
+impl<'a> dot::Labeller<'a, Node<'a>, Edge<'a>> for LabelledCFG<'a> {
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new(self.name.as_slice())
+    }
+
+    fn node_id(&'a self, &(i, _): &Node<'a>) -> dot::Id<'a> {
+        dot::Id::new(format!("N{:u}", i.node_id()))
+    }
+
+    fn node_label(&'a self, &(i, n): &Node<'a>) -> dot::LabelText<'a> {
+        if i == self.cfg.entry {
+            dot::LabelStr(str::Slice("entry"))
+        } else if i == self.cfg.exit {
+            dot::LabelStr(str::Slice("exit"))
+        } else if n.data.id == 0 {
+            dot::LabelStr(str::Slice("(dummy_node)"))
+        } else {
+            let s = self.ast_map.node_to_str(n.data.id);
+            dot::EscStr(str::Owned(replace_newline_with_backslash_l(s.as_slice())))
+        }
+    }
+
+    fn edge_label(&'a self, e: &Edge<'a>) -> dot::LabelText<'a> {
+        let mut label = StrBuf::new();
+        for (i, &node_id) in e.data.exiting_scopes.iter().enumerate() {
+            if i > 0 {
+                label.push_str(",\\l");
+            }
+            let s = self.ast_map.node_to_str(node_id);
+            label.push_str(format!("exiting scope_{} {}", i,
+                                   replace_newline_with_backslash_l(s.as_slice())));
+        }
+        dot::EscStr(str::Owned(label.into_owned()))
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, Node<'a>, Edge<'a>> for LabelledCFG<'a> {
+    fn nodes(&'a self) -> dot::Nodes<'a, Node<'a>> {
+        self.cfg.graph.all_nodes().iter().enumerate().map(|(i, n)| {
+            (graph::NodeIndex(i), n)
+        }).collect()
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, Edge<'a>> {
+        self.cfg.graph.all_edges().iter().collect()
+    }
+
+    fn source(&'a self, edge: &Edge<'a>) -> Node<'a> {
+        let i = edge.source();
+        (i, self.cfg.graph.node(i))
+    }
+
+    fn target(&'a self, edge: &Edge<'a>) -> Node<'a> {
+        let i = edge.target();
+        (i, self.cfg.graph.node(i))
+    }
+}
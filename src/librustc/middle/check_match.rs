@@ -20,9 +20,10 @@ use util::ppaux::ty_to_str;
 use std::cmp;
 use std::iter;
 use syntax::ast::*;
-use syntax::ast_util::{unguarded_pat, walk_pat};
-use syntax::codemap::{DUMMY_SP, Span};
+use syntax::ast_util::{ident_to_path, unguarded_pat, walk_pat};
+use syntax::codemap::{DUMMY_SP, Span, dummy_spanned};
 use syntax::parse::token;
+use syntax::print::pprust::pat_to_str;
 use syntax::visit;
 use syntax::visit::{Visitor, FnKind};
 
@@ -94,11 +95,7 @@ fn check_expr(cx: &mut MatchCheckCtxt, ex: &Expr) {
                                .filter_map(unguarded_pat)
                                .flat_map(|pats| pats.move_iter())
                                .collect();
-       if pats.is_empty() {
-           cx.tcx.sess.span_err(ex.span, "non-exhaustive patterns");
-       } else {
-           check_exhaustive(cx, ex.span, pats);
-       }
+       check_exhaustive(cx, ex.span, pats);
      }
      _ => ()
     }
@@ -108,6 +105,10 @@ fn check_expr(cx: &mut MatchCheckCtxt, ex: &Expr) {
 fn check_arms(cx: &MatchCheckCtxt, arms: &[Arm]) {
     let mut seen = Vec::new();
     for arm in arms.iter() {
+        // The alternatives of an arm with a guard can't be added to `seen`,
+        // since the guard may fail, but they still shadow the later
+        // alternatives of the same arm.
+        let mut alternatives = Vec::new();
         for pat in arm.pats.iter() {
 
             // Check that we do not match against a static NaN (#6804)
@@ -134,14 +135,17 @@ fn check_arms(cx: &MatchCheckCtxt, arms: &[Arm]) {
             });
 
             let v = vec!(*pat);
-            match is_useful(cx, &seen, v.as_slice()) {
+            let mut rows = seen.clone();
+            rows.push_all(alternatives.as_slice());
+            match is_useful(cx, &rows, v.as_slice(), 1) {
               not_useful => {
                 cx.tcx.sess.span_err(pat.span, "unreachable pattern");
               }
               _ => ()
             }
-            if arm.guard.is_none() { seen.push(v); }
+            alternatives.push(v);
         }
+        if arm.guard.is_none() { seen.push_all_move(alternatives); }
     }
 }
 
@@ -152,60 +156,41 @@ fn raw_pat(p: @Pat) -> @Pat {
     }
 }
 
-fn check_exhaustive(cx: &MatchCheckCtxt, sp: Span, pats: Vec<@Pat> ) {
-    assert!((!pats.is_empty()));
-    let ext = match is_useful(cx, &pats.iter().map(|p| vec!(*p)).collect(), [wild()]) {
+// The most missing patterns listed in a non-exhaustive match error
+static MAX_WITNESSES: uint = 3;
+
+fn check_exhaustive(cx: &MatchCheckCtxt, sp: Span, pats: Vec<@Pat>) {
+    let m = pats.iter().map(|p| vec!(*p)).collect();
+    // Look for one more witness than is listed, to know if there are others
+    let witnesses = match is_useful(cx, &m, [wild()], MAX_WITNESSES + 1) {
+        useful(witnesses) => witnesses,
         not_useful => {
             // This is good, wildcard pattern isn't reachable
             return;
         }
-        useful_ => None,
-        useful(ty, ref ctor) => {
-            match ty::get(ty).sty {
-                ty::ty_bool => {
-                    match *ctor {
-                        val(const_bool(true)) => Some("true".to_owned()),
-                        val(const_bool(false)) => Some("false".to_owned()),
-                        _ => None
-                    }
-                }
-                ty::ty_enum(id, _) => {
-                    let vid = match *ctor {
-                        variant(id) => id,
-                        _ => fail!("check_exhaustive: non-variant ctor"),
-                    };
-                    let variants = ty::enum_variants(cx.tcx, id);
-
-                    match variants.iter().find(|v| v.id == vid) {
-                        Some(v) => Some(token::get_ident(v.name).get().to_str()),
-                        None => {
-                            fail!("check_exhaustive: bad variant in ctor")
-                        }
-                    }
-                }
-                ty::ty_vec(..) | ty::ty_rptr(..) => {
-                    match *ctor {
-                        vec(n) => Some(format!("vectors of length {}", n)),
-                        _ => None
-                    }
-                }
-                _ => None
-            }
-        }
     };
-    let msg = "non-exhaustive patterns".to_owned() + match ext {
-        Some(ref s) => format!(": {} not covered",  *s),
-        None => "".to_owned()
+    let mut missing = witnesses.iter().take(MAX_WITNESSES).map(|w| {
+        format!("`{}`", pat_to_str(*w.get(0)))
+    }).collect::<Vec<~str>>();
+    let missing = if witnesses.len() > MAX_WITNESSES {
+        format!("{} and more", missing.as_slice().connect(", "))
+    } else {
+        let last = missing.pop().unwrap();
+        if missing.is_empty() {
+            last
+        } else {
+            format!("{} and {}", missing.as_slice().connect(", "), last)
+        }
     };
-    cx.tcx.sess.span_err(sp, msg);
+    cx.tcx.sess.span_err(sp, format!("non-exhaustive patterns: {} not covered", missing));
 }
 
 type matrix = Vec<Vec<@Pat> > ;
 
-#[deriving(Clone)]
 enum useful {
-    useful(ty::t, ctor),
-    useful_,
+    // Witnesses: rows of patterns, one per column, that match values which
+    // `v` matches but no row of the matrix does
+    useful(Vec<Vec<@Pat>>),
     not_useful,
 }
 
@@ -215,7 +200,10 @@ enum ctor {
     variant(DefId),
     val(const_val),
     range(const_val, const_val),
-    vec(uint)
+    vec(uint),
+    // Every slice of at least this many elements. This is only ever missing
+    // from a match, never specialized on.
+    vec_rest(uint)
 }
 
 // Algorithm from http://moscova.inria.fr/~maranget/papers/warn/index.html
@@ -228,12 +216,16 @@ enum ctor {
 // relation to preceding patterns, it is not reachable) and exhaustiveness
 // checking (if a wildcard pattern is useful in relation to a matrix, the
 // matrix isn't exhaustive).
+//
+// When `v` is useful, up to `limit` witnesses are built along the way, each a
+// row of patterns for the values that `v` matches but `m` doesn't. For a
+// wildcard, these are the patterns missing from a non-exhaustive match.
 
 // Note: is_useful doesn't work on empty types, as the paper notes.
 // So it assumes that v is non-empty.
-fn is_useful(cx: &MatchCheckCtxt, m: &matrix, v: &[@Pat]) -> useful {
+fn is_useful(cx: &MatchCheckCtxt, m: &matrix, v: &[@Pat], limit: uint) -> useful {
     if m.len() == 0u {
-        return useful_;
+        return useful(vec!(Vec::from_elem(v.len(), wild())));
     }
     if m.get(0).len() == 0u {
         return not_useful
@@ -246,79 +238,64 @@ fn is_useful(cx: &MatchCheckCtxt, m: &matrix, v: &[@Pat]) -> useful {
 
     match pat_ctor_id(cx, v[0]) {
       None => {
-        match missing_ctor(cx, m, left_ty) {
-          None => {
-            match ty::get(left_ty).sty {
-              ty::ty_bool => {
-                  match is_useful_specialized(cx, m, v,
-                                              val(const_bool(true)),
-                                              0u, left_ty){
-                      not_useful => {
-                          is_useful_specialized(cx, m, v,
-                                                val(const_bool(false)),
-                                                0u, left_ty)
-                      }
-                      ref u => (*u).clone(),
-                  }
-              }
-              ty::ty_enum(eid, _) => {
-                  for va in (*ty::enum_variants(cx.tcx, eid)).iter() {
-                      match is_useful_specialized(cx, m, v, variant(va.id),
-                                                  va.args.len(), left_ty) {
-                        not_useful => (),
-                        ref u => return (*u).clone(),
-                      }
-                  }
-                  not_useful
-              }
-              ty::ty_vec(_, Some(n)) => {
-                  is_useful_specialized(cx, m, v, vec(n), n, left_ty)
-              }
-              ty::ty_vec(..) => fail!("impossible case"),
-              ty::ty_rptr(_, ty::mt{ty: ty, ..}) | ty::ty_uniq(ty) => match ty::get(ty).sty {
-                  ty::ty_vec(_, None) => {
-                      let max_len = m.iter().rev().fold(0, |max_len, r| {
-                          match r.get(0).node {
-                              PatVec(ref before, _, ref after) => {
-                                  cmp::max(before.len() + after.len(), max_len)
-                              }
-                              _ => max_len
-                          }
-                      });
-                      for n in iter::range(0u, max_len + 1) {
-                          match is_useful_specialized(cx, m, v, vec(n), n, left_ty) {
-                              not_useful => (),
-                              ref u => return (*u).clone(),
-                          }
-                      }
-                      not_useful
-                  }
-                  _ => {
-                      let arity = ctor_arity(cx, &single, left_ty);
-                      is_useful_specialized(cx, m, v, single, arity, left_ty)
-                  }
-              },
-              _ => {
-                  let arity = ctor_arity(cx, &single, left_ty);
-                  is_useful_specialized(cx, m, v, single, arity, left_ty)
-              }
+        let missing = missing_ctors(cx, m, left_ty);
+        if missing.is_empty() {
+            // Every constructor appears in the first column, so `v` is useful
+            // if it is for one of them
+            let mut witnesses = Vec::new();
+            for ctor in all_ctors(cx, m, left_ty).move_iter() {
+                let arity = ctor_arity(cx, &ctor, left_ty);
+                match is_useful_specialized(cx, m, v, ctor, arity, left_ty,
+                                            limit - witnesses.len()) {
+                    useful(found) => witnesses.push_all_move(found),
+                    not_useful => {}
+                }
+                if witnesses.len() >= limit {
+                    break;
+                }
             }
-          }
-          Some(ref ctor) => {
-            match is_useful(cx,
-                            &m.iter().filter_map(|r| {
-                                default(cx, r.as_slice())
-                            }).collect::<matrix>(),
-                            v.tail()) {
-              useful_ => useful(left_ty, (*ctor).clone()),
-              ref u => (*u).clone(),
+            if witnesses.is_empty() { not_useful } else { useful(witnesses) }
+        } else {
+            // Only the rows that start with a wildcard match values built
+            // with a missing constructor
+            let dm = m.iter().filter_map(|r| {
+                default(cx, r.as_slice())
+            }).collect::<matrix>();
+            match is_useful(cx, &dm, v.tail(), limit) {
+                useful(rests) => {
+                    // If no row names a constructor, `_` says it more simply
+                    // than listing every one of them
+                    let firsts = if dm.len() == m.len() {
+                        vec!(wild())
+                    } else {
+                        missing.iter().map(|ctor| {
+                            match *ctor {
+                                single => wild(),
+                                _ => {
+                                    let arity = ctor_arity(cx, ctor, left_ty);
+                                    construct_witness(cx, ctor,
+                                                      Vec::from_elem(arity, wild()),
+                                                      left_ty)
+                                }
+                            }
+                        }).collect()
+                    };
+                    let mut witnesses = Vec::new();
+                    for &first in firsts.iter() {
+                        for rest in rests.iter() {
+                            witnesses.push(vec!(first).append(rest.as_slice()));
+                        }
+                    }
+                    witnesses.truncate(limit);
+                    useful(witnesses)
+                }
+                not_useful => not_useful,
             }
-          }
         }
       }
-      Some(ref v0_ctor) => {
-        let arity = ctor_arity(cx, v0_ctor, left_ty);
-        is_useful_specialized(cx, m, v, (*v0_ctor).clone(), arity, left_ty)
+      Some(v0_ctor) => {
+        let arity = ctor_arity(cx, &v0_ctor, left_ty);
+        is_useful_specialized(cx, m, v, v0_ctor, arity, left_ty, limit)
       }
     }
 }
@@ -328,19 +305,124 @@ fn is_useful_specialized(cx: &MatchCheckCtxt,
                              v: &[@Pat],
                              ctor: ctor,
                              arity: uint,
-                             lty: ty::t)
+                             lty: ty::t,
+                             limit: uint)
                              -> useful {
     let ms = m.iter().filter_map(|r| {
         specialize(cx, r.as_slice(), &ctor, arity, lty)
     }).collect::<matrix>();
     let could_be_useful = is_useful(
-        cx, &ms, specialize(cx, v, &ctor, arity, lty).unwrap().as_slice());
+        cx, &ms, specialize(cx, v, &ctor, arity, lty).unwrap().as_slice(), limit);
     match could_be_useful {
-      useful_ => useful(lty, ctor),
-      ref u => (*u).clone(),
+      useful(witnesses) => {
+        // Rebuild the first column from the `arity` columns it was
+        // specialized into
+        useful(witnesses.move_iter().map(|w| {
+            let args = Vec::from_slice(w.slice_to(arity));
+            vec!(construct_witness(cx, &ctor, args, lty)).append(w.slice_from(arity))
+        }).collect())
+      }
+      not_useful => not_useful,
+    }
+}
+
+// Every constructor of `left_ty`, for when the first column of `m` covers
+// them all
+fn all_ctors(cx: &MatchCheckCtxt, m: &matrix, left_ty: ty::t) -> Vec<ctor> {
+    match ty::get(left_ty).sty {
+      ty::ty_bool => vec!(val(const_bool(true)), val(const_bool(false))),
+      ty::ty_enum(eid, _) => {
+          ty::enum_variants(cx.tcx, eid).iter().map(|va| variant(va.id)).collect()
+      }
+      ty::ty_vec(_, Some(n)) => vec!(vec(n)),
+      ty::ty_vec(..) => fail!("impossible case"),
+      ty::ty_rptr(_, ty::mt{ty: ty, ..}) | ty::ty_uniq(ty) => match ty::get(ty).sty {
+          ty::ty_vec(_, None) => {
+              let max_len = m.iter().rev().fold(0, |max_len, r| {
+                  match r.get(0).node {
+                      PatVec(ref before, _, ref after) => {
+                          cmp::max(before.len() + after.len(), max_len)
+                      }
+                      _ => max_len
+                  }
+              });
+              iter::range(0u, max_len + 1).map(|n| vec(n)).collect()
+          }
+          _ => vec!(single)
+      },
+      _ => vec!(single)
     }
 }
 
+// Builds a pattern of type `ty` that applies `ctor` to `pats`, which has
+// one pattern for each of its arguments
+fn construct_witness(cx: &MatchCheckCtxt, ctor: &ctor, pats: Vec<@Pat>, ty: ty::t) -> @Pat {
+    // Only the fields that aren't wildcards are shown, followed by `..` if
+    // any were left out
+    fn struct_pat(path: Path, names: Vec<Ident>, pats: Vec<@Pat>) -> Pat_ {
+        let num_fields = names.len();
+        let fields = names.move_iter().zip(pats.move_iter()).filter(|&(_, p)| {
+            match p.node { PatWild => false, _ => true }
+        }).map(|(name, p)| FieldPat { ident: name, pat: p }).collect::<Vec<FieldPat>>();
+        let etc = fields.len() < num_fields;
+        PatStruct(path, fields, etc)
+    }
+
+    let node = match ty::get(ty).sty {
+        ty::ty_tup(_) => PatTup(pats),
+        ty::ty_enum(eid, _) => match *ctor {
+            variant(vid) => {
+                let variants = ty::enum_variants(cx.tcx, eid);
+                let v = match variants.iter().find(|v| v.id == vid) {
+                    Some(v) => v,
+                    None => fail!("construct_witness: bad variant in ctor"),
+                };
+                let path = ident_to_path(DUMMY_SP, v.name);
+                match v.arg_names {
+                    Some(ref names) => struct_pat(path, names.clone(), pats),
+                    None => PatEnum(path, Some(pats)),
+                }
+            }
+            // A constant of the enum type
+            _ => PatWild,
+        },
+        ty::ty_struct(cid, _) => {
+            let name = ty::with_path(cx.tcx, cid, |mut path| path.last().unwrap().name());
+            let path = ident_to_path(DUMMY_SP, Ident::new(name));
+            let fields = ty::lookup_struct_fields(cx.tcx, cid);
+            if fields.iter().all(|f| f.name == token::special_idents::unnamed_field.name) {
+                PatEnum(path, Some(pats))
+            } else {
+                let names = fields.iter().map(|f| Ident::new(f.name)).collect();
+                struct_pat(path, names, pats)
+            }
+        }
+        ty::ty_uniq(inner) | ty::ty_rptr(_, ty::mt{ty: inner, ..}) => {
+            match ty::get(inner).sty {
+                ty::ty_vec(_, None) => match *ctor {
+                    vec_rest(_) => PatVec(pats, Some(wild_multi()), Vec::new()),
+                    _ => PatVec(pats, None, Vec::new()),
+                },
+                ty::ty_str => PatWild,
+                _ => match ty::get(ty).sty {
+                    ty::ty_uniq(_) => PatUniq(*pats.get(0)),
+                    _ => PatRegion(*pats.get(0)),
+                },
+            }
+        }
+        ty::ty_vec(_, Some(_)) => PatVec(pats, None, Vec::new()),
+        ty::ty_bool => match *ctor {
+            val(const_bool(b)) => {
+                let lit = @dummy_spanned(LitBool(b));
+                PatLit(@Expr { id: 0, node: ExprLit(lit), span: DUMMY_SP })
+            }
+            _ => PatWild,
+        },
+        _ => PatWild,
+    };
+    @Pat { id: 0, node: node, span: DUMMY_SP }
+}
+
 fn pat_ctor_id(cx: &MatchCheckCtxt, p: @Pat) -> Option<ctor> {
     let pat = raw_pat(p);
     match pat.node {
@@ -392,11 +474,14 @@ fn is_wild(cx: &MatchCheckCtxt, p: @Pat) -> bool {
     }
 }
 
-fn missing_ctor(cx: &MatchCheckCtxt,
-                m: &matrix,
-                left_ty: ty::t)
-                -> Option<ctor> {
-    return match ty::get(left_ty).sty {
+// The constructors of `left_ty` that don't appear in the first column of `m`.
+// If it has a wildcard in every row, that is a single `single` for types
+// with just one constructor, or that have too many to list.
+fn missing_ctors(cx: &MatchCheckCtxt,
+                 m: &matrix,
+                 left_ty: ty::t)
+                 -> Vec<ctor> {
+    let missing = match ty::get(left_ty).sty {
       ty::ty_box(_) | ty::ty_tup(_) |
       ty::ty_struct(..) => check_matrix_for_wild(cx, m),
       ty::ty_uniq(ty) | ty::ty_rptr(_, ty::mt{ty: ty, ..}) => match ty::get(ty).sty {
@@ -415,14 +500,9 @@ fn missing_ctor(cx: &MatchCheckCtxt,
             }
         }
         let variants = ty::enum_variants(cx.tcx, eid);
-        if found.len() != (*variants).len() {
-            for v in (*variants).iter() {
-                if !found.iter().any(|x| x == &(variant(v.id))) {
-                    return Some(variant(v.id));
-                }
-            }
-            fail!();
-        } else { None }
+        return variants.iter().map(|v| variant(v.id)).filter(|ctor| {
+            !found.contains(ctor)
+        }).collect();
       }
       ty::ty_nil => None,
       ty::ty_bool => {
@@ -436,9 +516,10 @@ fn missing_ctor(cx: &MatchCheckCtxt,
               _ => fail!("impossible case")
             }
         }
-        if true_found && false_found { None }
-        else if true_found { Some(val(const_bool(false))) }
-        else { Some(val(const_bool(true))) }
+        let mut missing = Vec::new();
+        if !true_found { missing.push(val(const_bool(true))); }
+        if !false_found { missing.push(val(const_bool(false))); }
+        return missing;
       }
       ty::ty_vec(_, Some(n)) => {
         let mut missing = true;
@@ -466,6 +547,7 @@ fn missing_ctor(cx: &MatchCheckCtxt,
       ty::ty_vec(..) => fail!("impossible case"),
       _ => Some(single)
     };
+    return missing.move_iter().collect();
 
     fn check_matrix_for_wild(cx: &MatchCheckCtxt, m: &matrix) -> Option<ctor> {
         for r in m.iter() {
@@ -499,11 +581,10 @@ fn missing_ctor(cx: &MatchCheckCtxt,
 
         let mut found_slice = false;
         let mut next = 0;
-        let mut missing = None;
         for &(length, slice) in vec_pat_lens.iter() {
             if length != next {
-                missing = Some(next);
-                break;
+                // No pattern has exactly `next` elements
+                return Some(vec(next));
             }
             if slice {
                 found_slice = true;
@@ -513,21 +594,16 @@ fn missing_ctor(cx: &MatchCheckCtxt,
         }
 
         // We found patterns of all lengths within <0, next), yet there was no
-        // pattern with a slice - therefore, we report vec(next) as missing.
-        if !found_slice {
-            missing = Some(next);
-        }
-        match missing {
-          Some(k) => Some(vec(k)),
-          None => None
-        }
+        // pattern with a slice - therefore, every vector of at least `next`
+        // elements is missing.
+        if found_slice { None } else { Some(vec_rest(next)) }
     }
 }
 
 fn ctor_arity(cx: &MatchCheckCtxt, ctor: &ctor, ty: ty::t) -> uint {
     fn vec_ctor_arity(ctor: &ctor) -> uint {
         match *ctor {
-            vec(n) => n,
+            vec(n) | vec_rest(n) => n,
             _ => 0u
         }
     }
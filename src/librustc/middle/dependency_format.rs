@@ -136,7 +136,7 @@ fn calculate_type(sess: &session::Session,
         if src.dylib.is_some() {
             add_library(sess, cnum, cstore::RequireDynamic, &mut formats);
             debug!("adding dylib: {}", data.name);
-            let deps = csearch::get_dylib_dependency_formats(&sess.cstore, cnum);
+            let deps = csearch::get_dylib_dependency_formats(sess, cnum);
             for &(depnum, style) in deps.iter() {
                 add_library(sess, depnum, style, &mut formats);
                 debug!("adding {}: {}", style,
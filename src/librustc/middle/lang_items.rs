@@ -165,9 +165,9 @@ impl<'a> LanguageItemCollector<'a> {
     }
 
     pub fn collect_external_language_items(&mut self) {
-        let crate_store = &self.session.cstore;
-        crate_store.iter_crate_data(|crate_number, _crate_metadata| {
-            each_lang_item(crate_store, crate_number, |node_id, item_index| {
+        let sess = self.session;
+        sess.cstore.iter_crate_data(|crate_number, _crate_metadata| {
+            each_lang_item(sess, crate_number, |node_id, item_index| {
                 let def_id = ast::DefId { krate: crate_number, node: node_id };
                 self.collect_item(item_index, def_id);
                 true
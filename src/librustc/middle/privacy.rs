@@ -847,7 +847,7 @@ impl<'a> Visitor<()> for PrivacyVisitor<'a> {
                             // the unwrap_or to the same struct id.
                             let maybe_did =
                                 csearch::get_tuple_struct_definition_if_ctor(
-                                    &self.tcx.sess.cstore, did);
+                                    &self.tcx.sess, did);
                             maybe_did.unwrap_or(did)
                         })
                     }
@@ -855,7 +855,7 @@ impl<'a> Visitor<()> for PrivacyVisitor<'a> {
                     // DefFn types, so we explicitly handle that case here.
                     Some(&ast::DefFn(did, _)) if !is_local(did) => {
                         match csearch::get_tuple_struct_definition_if_ctor(
-                                    &self.tcx.sess.cstore, did) {
+                                    &self.tcx.sess, did) {
                             Some(did) => guard(did),
                             None => {}
                         }
@@ -16,6 +16,7 @@ use metadata::decoder::{DefLike, DlDef, DlField, DlImpl};
 use middle::lang_items::LanguageItems;
 use middle::lint::{UnnecessaryQualification, UnusedImports};
 use middle::pat_util::pat_bindings;
+use util::common::find_best_match_for_name;
 use util::nodemap::{NodeMap, DefIdSet, FnvHashMap};
 
 use syntax::ast::*;
@@ -36,7 +37,16 @@ use std::cell::{Cell, RefCell};
 use std::mem::replace;
 use std::rc::{Rc, Weak};
 use std::strbuf::StrBuf;
-use std::uint;
+
+// Reports an error with a code, like `span_err!`, unless errors are being
+// suppressed by `with_no_errors`
+macro_rules! resolve_err(
+    ($this:expr, $span:expr, $code:ident, $($message:tt)*) => (
+        if $this.emit_errors {
+            span_err!($this.session, $span, $code, $($message)*)
+        }
+    )
+)
 
 // Definition mapping
 pub type DefMap = RefCell<NodeMap<Def>>;
@@ -1074,10 +1084,10 @@ impl<'a> Resolver<'a> {
                     // Return an error here by looking up the namespace that
                     // had the duplicate.
                     let ns = ns.unwrap();
-                    self.resolve_error(sp,
-                        format!("duplicate definition of {} `{}`",
-                             namespace_error_to_str(duplicate_type),
-                             token::get_ident(name)));
+                    resolve_err!(self, sp, E0001,
+                        "duplicate definition of {} `{}`",
+                        namespace_error_to_str(duplicate_type),
+                        token::get_ident(name));
                     {
                         let r = child.span_for_namespace(ns);
                         for sp in r.iter() {
@@ -1649,10 +1659,10 @@ impl<'a> Resolver<'a> {
               // to the trait info.
 
               let method_def_ids =
-                csearch::get_trait_method_def_ids(&self.session.cstore, def_id);
+                csearch::get_trait_method_def_ids(self.session, def_id);
               for &method_def_id in method_def_ids.iter() {
                   let (method_name, explicit_self) =
-                      csearch::get_method_name_and_explicit_self(&self.session.cstore,
+                      csearch::get_method_name_and_explicit_self(self.session,
                                                                  method_def_id);
 
                   debug!("(building reduced graph for \
@@ -1689,7 +1699,7 @@ impl<'a> Resolver<'a> {
                     crate) building type and value for {}",
                    final_ident);
             child_name_bindings.define_type(def, DUMMY_SP, is_public);
-            if csearch::get_struct_fields(&self.session.cstore, def_id).len() == 0 {
+            if csearch::get_struct_fields(self.session, def_id).len() == 0 {
                 child_name_bindings.define_value(def, DUMMY_SP, is_public);
             }
             self.structs.insert(def_id);
@@ -1721,7 +1731,7 @@ impl<'a> Resolver<'a> {
                     DefForeignMod(def_id) => {
                         // Foreign modules have no names. Recur and populate
                         // eagerly.
-                        csearch::each_child_of_item(&self.session.cstore,
+                        csearch::each_child_of_item(self.session,
                                                     def_id,
                                                     |def_like,
                                                      child_ident,
@@ -1751,11 +1761,11 @@ impl<'a> Resolver<'a> {
             }
             DlImpl(def) => {
                 // We only process static methods of impls here.
-                match csearch::get_type_name_if_impl(&self.session.cstore, def) {
+                match csearch::get_type_name_if_impl(self.session, def) {
                     None => {}
                     Some(final_ident) => {
                         let static_methods_opt =
-                            csearch::get_static_methods_if_impl(&self.session.cstore, def);
+                            csearch::get_static_methods_if_impl(self.session, def);
                         match static_methods_opt {
                             Some(ref static_methods) if
                                 static_methods.len() >= 1 => {
@@ -1857,7 +1867,7 @@ impl<'a> Resolver<'a> {
             Some(def_id) => def_id,
         };
 
-        csearch::each_child_of_item(&self.session.cstore,
+        csearch::each_child_of_item(self.session,
                                     def_id,
                                     |def_like, child_ident, visibility| {
             debug!("(populating external module) ... found ident: {}",
@@ -1882,7 +1892,7 @@ impl<'a> Resolver<'a> {
     /// Builds the reduced graph rooted at the 'use' directive for an external
     /// crate.
     fn build_reduced_graph_for_external_crate(&mut self, root: Rc<Module>) {
-        csearch::each_top_level_item_of_crate(&self.session.cstore,
+        csearch::each_top_level_item_of_crate(self.session,
                                               root.def_id
                                                   .get()
                                                   .unwrap()
@@ -2030,7 +2040,7 @@ impl<'a> Resolver<'a> {
                                        import_directive.module_path
                                                        .as_slice(),
                                        import_directive.subclass));
-                    self.resolve_error(import_directive.span, msg);
+                    resolve_err!(self, import_directive.span, E0002, "{}", msg);
                 }
                 Indeterminate => {
                     // Bail out. We'll come around next time.
@@ -2360,6 +2370,17 @@ impl<'a> Resolver<'a> {
             }
         }
 
+        if value_result.is_unbound() && type_result.is_unbound() {
+            let msg = format!("unresolved import: there is no \
+                               `{}` in `{}`",
+                              token::get_ident(source),
+                              self.module_to_str(&*containing_module));
+            resolve_err!(self, directive.span, E0003, "{}", msg);
+            self.note_import_suggestion(directive.span, containing_module, source,
+                                        [TypeNS, ValueNS]);
+            return Failed;
+        }
+
         // We've successfully resolved the import. Write the results in.
         let mut import_resolutions = module_.import_resolutions.borrow_mut();
         let import_resolution = import_resolutions.get_mut(&target.name);
@@ -2392,14 +2413,6 @@ impl<'a> Resolver<'a> {
             }
         }
 
-        if value_result.is_unbound() && type_result.is_unbound() {
-            let msg = format!("unresolved import: there is no \
-                               `{}` in `{}`",
-                              token::get_ident(source),
-                              self.module_to_str(&*containing_module));
-            self.resolve_error(directive.span, msg);
-            return Failed;
-        }
         let value_used_public = value_used_reexport || value_used_public;
         let type_used_public = type_used_reexport || type_used_public;
 
@@ -2620,15 +2633,17 @@ impl<'a> Resolver<'a> {
                             hi: span.lo + Pos::from_uint(segment_name.get().len()),
                             expn_info: span.expn_info,
                         };
-                        self.resolve_error(span,
-                                              format!("unresolved import. maybe \
-                                                    a missing `extern crate \
-                                                    {}`?",
-                                                    segment_name));
+                        resolve_err!(self, span, E0004,
+                                     "unresolved import. maybe \
+                                     a missing `extern crate \
+                                     {}`?",
+                                     segment_name);
                         return Failed;
                     }
-                    self.resolve_error(span, format!("unresolved import: could not find `{}` in \
-                                                     `{}`.", segment_name, module_name));
+                    resolve_err!(self, span, E0005,
+                                 "unresolved import: could not find `{}` in \
+                                 `{}`.", segment_name, module_name);
+                    self.note_import_suggestion(span, search_module, name, [TypeNS]);
                     return Failed;
                 }
                 Indeterminate => {
@@ -2645,8 +2660,8 @@ impl<'a> Resolver<'a> {
                             match type_def.module_def {
                                 None => {
                                     // Not a module.
-                                    self.resolve_error(span, format!("not a module `{}`",
-                                                                 token::get_ident(name)));
+                                    resolve_err!(self, span, E0006, "not a module `{}`",
+                                                                    token::get_ident(name));
                                     return Failed;
                                 }
                                 Some(ref module_def) => {
@@ -2657,8 +2672,7 @@ impl<'a> Resolver<'a> {
                                            module_def.kind.get()) {
                                         (ImportSearch, TraitModuleKind) |
                                         (ImportSearch, ImplModuleKind) => {
-                                            self.resolve_error(
-                                                span,
+                                            resolve_err!(self, span, E0007,
                                                 "cannot import from a trait \
                                                  or type implementation");
                                             return Failed;
@@ -2687,9 +2701,9 @@ impl<'a> Resolver<'a> {
                         }
                         None => {
                             // There are no type bindings at all.
-                            self.resolve_error(span,
-                                                  format!("not a module `{}`",
-                                                       token::get_ident(name)));
+                            resolve_err!(self, span, E0008,
+                                         "not a module `{}`",
+                                         token::get_ident(name));
                             return Failed;
                         }
                     }
@@ -2734,12 +2748,13 @@ impl<'a> Resolver<'a> {
                 let mpath = self.idents_to_str(module_path);
                 match mpath.rfind(':') {
                     Some(idx) => {
-                        self.resolve_error(span, format!("unresolved import: could not find `{}` \
-                                                         in `{}`",
-                                                         // idx +- 1 to account for the colons
-                                                         // on either side
-                                                         mpath.slice_from(idx + 1),
-                                                         mpath.slice_to(idx - 1)));
+                        resolve_err!(self, span, E0009,
+                                     "unresolved import: could not find `{}` \
+                                     in `{}`",
+                                     // idx +- 1 to account for the colons
+                                     // on either side
+                                     mpath.slice_from(idx + 1),
+                                     mpath.slice_to(idx - 1));
                     },
                     None => (),
                 };
@@ -2771,7 +2786,7 @@ impl<'a> Resolver<'a> {
                             module_path[0]);
                         match result {
                             Failed => {
-                                self.resolve_error(span, "unresolved name");
+                                resolve_err!(self, span, E0010, "unresolved name");
                                 return Failed;
                             }
                             Indeterminate => {
@@ -3170,12 +3185,12 @@ impl<'a> Resolver<'a> {
                          .span_to_snippet(imports.get(index).span)
                          .unwrap();
             if sn.as_slice().contains("::") {
-                self.resolve_error(imports.get(index).span,
-                                   "unresolved import");
+                resolve_err!(self, imports.get(index).span, E0011,
+                             "unresolved import");
             } else {
                 let err = format!("unresolved import (maybe you meant `{}::*`?)",
                                   sn.as_slice().slice(0, sn.len()));
-                self.resolve_error(imports.get(index).span, err);
+                resolve_err!(self, imports.get(index).span, E0012, "{}", err);
             }
         }
 
@@ -3430,18 +3445,17 @@ impl<'a> Resolver<'a> {
                         // named function item. This is not allowed, so we
                         // report an error.
 
-                        self.resolve_error(
-                            span,
+                        resolve_err!(self, span, E0013,
                             "can't capture dynamic environment in a fn item; \
                             use the || { ... } closure form instead");
                     } else {
                         // This was an attempt to use a type parameter outside
                         // its scope.
 
-                        self.resolve_error(span,
-                                              "can't use type parameters from \
-                                              outer function; try using a local \
-                                              type parameter instead");
+                        resolve_err!(self, span, E0014,
+                                     "can't use type parameters from \
+                                     outer function; try using a local \
+                                     type parameter instead");
                     }
 
                     return None;
@@ -3454,18 +3468,17 @@ impl<'a> Resolver<'a> {
                         // named function item. This is not allowed, so we
                         // report an error.
 
-                        self.resolve_error(
-                            span,
+                        resolve_err!(self, span, E0015,
                             "can't capture dynamic environment in a fn item; \
                             use the || { ... } closure form instead");
                     } else {
                         // This was an attempt to use a type parameter outside
                         // its scope.
 
-                        self.resolve_error(span,
-                                              "can't use type parameters from \
-                                              outer function; try using a local \
-                                              type parameter instead");
+                        resolve_err!(self, span, E0016,
+                                     "can't use type parameters from \
+                                     outer function; try using a local \
+                                     type parameter instead");
                     }
 
                     return None;
@@ -3473,14 +3486,14 @@ impl<'a> Resolver<'a> {
                 ConstantItemRibKind => {
                     if is_ty_param {
                         // see #9186
-                        self.resolve_error(span,
-                                              "cannot use an outer type \
-                                               parameter in this context");
+                        resolve_err!(self, span, E0017,
+                                     "cannot use an outer type \
+                                      parameter in this context");
                     } else {
                         // Still doesn't deal with upvars
-                        self.resolve_error(span,
-                                              "attempt to use a non-constant \
-                                               value in a constant");
+                        resolve_err!(self, span, E0018,
+                                     "attempt to use a non-constant \
+                                      value in a constant");
                     }
 
                 }
@@ -3845,7 +3858,7 @@ impl<'a> Resolver<'a> {
                 };
 
                 let msg = format!("attempt to {} a nonexistent trait `{}`", usage_str, path_str);
-                self.resolve_error(trait_reference.path.span, msg);
+                resolve_err!(self, trait_reference.path.span, E0019, "{}", msg);
             }
             Some(def) => {
                 debug!("(resolving trait) found trait def: {:?}", def);
@@ -3886,14 +3899,14 @@ impl<'a> Resolver<'a> {
                                 this.record_def(path_id, (def, lp));
                             }
                             Some((DefStruct(_), _)) => {
-                                this.session.span_err(t.span,
-                                                      "super-struct is defined \
-                                                       in a different crate")
+                                span_err!(this.session, t.span, E0020,
+                                          "super-struct is defined \
+                                           in a different crate")
                             },
-                            Some(_) => this.session.span_err(t.span,
-                                                             "super-struct is not a struct type"),
-                            None => this.session.span_err(t.span,
-                                                          "super-struct could not be resolved"),
+                            Some(_) => span_err!(this.session, t.span, E0021,
+                                                 "super-struct is not a struct type"),
+                            None => span_err!(this.session, t.span, E0022,
+                                              "super-struct could not be resolved"),
                         }
                     },
                     _ => this.session.span_bug(t.span, "path not mapped to a TyPath")
@@ -4049,21 +4062,19 @@ impl<'a> Resolver<'a> {
             for (&key, &binding_0) in map_0.iter() {
                 match map_i.find(&key) {
                   None => {
-                    self.resolve_error(
-                        p.span,
-                        format!("variable `{}` from pattern \\#1 is \
-                                  not bound in pattern \\#{}",
-                                token::get_name(key),
-                                i + 1));
+                    resolve_err!(self, p.span, E0023,
+                        "variable `{}` from pattern \\#1 is \
+                          not bound in pattern \\#{}",
+                        token::get_name(key),
+                        i + 1);
                   }
                   Some(binding_i) => {
                     if binding_0.binding_mode != binding_i.binding_mode {
-                        self.resolve_error(
-                            binding_i.span,
-                            format!("variable `{}` is bound with different \
-                                      mode in pattern \\#{} than in pattern \\#1",
-                                    token::get_name(key),
-                                    i + 1));
+                        resolve_err!(self, binding_i.span, E0024,
+                            "variable `{}` is bound with different \
+                              mode in pattern \\#{} than in pattern \\#1",
+                            token::get_name(key),
+                            i + 1);
                     }
                   }
                 }
@@ -4071,12 +4082,11 @@ impl<'a> Resolver<'a> {
 
             for (&key, &binding) in map_i.iter() {
                 if !map_0.contains_key(&key) {
-                    self.resolve_error(
-                        binding.span,
-                        format!("variable `{}` from pattern \\#{} is \
-                                  not bound in pattern \\#1",
-                                token::get_name(key),
-                                i + 1));
+                    resolve_err!(self, binding.span, E0025,
+                        "variable `{}` from pattern \\#{} is \
+                          not bound in pattern \\#1",
+                        token::get_name(key),
+                        i + 1);
                 }
             }
         }
@@ -4152,17 +4162,17 @@ impl<'a> Resolver<'a> {
                             if path.segments
                                    .iter()
                                    .any(|s| !s.lifetimes.is_empty()) {
-                                self.session.span_err(path.span,
-                                                      "lifetime parameters \
-                                                       are not allowed on \
-                                                       this type")
+                                span_err!(self.session, path.span, E0026,
+                                          "lifetime parameters \
+                                           are not allowed on \
+                                           this type")
                             } else if path.segments
                                           .iter()
                                           .any(|s| s.types.len() > 0) {
-                                self.session.span_err(path.span,
-                                                      "type parameters are \
-                                                       not allowed on this \
-                                                       type")
+                                span_err!(self.session, path.span, E0027,
+                                          "type parameters are \
+                                           not allowed on this \
+                                           type")
                             }
                         }
                         None => {
@@ -4203,7 +4213,8 @@ impl<'a> Resolver<'a> {
                     None => {
                         let msg = format!("use of undeclared type name `{}`",
                                           self.path_idents_to_str(path));
-                        self.resolve_error(ty.span, msg);
+                        resolve_err!(self, ty.span, E0028, "{}", msg);
+                        self.note_similar_name(ty.span, path, TypeNS);
                     }
                 }
 
@@ -4268,12 +4279,12 @@ impl<'a> Resolver<'a> {
                             self.record_def(pattern.id, (def, lp));
                         }
                         FoundStructOrEnumVariant(..) => {
-                            self.resolve_error(pattern.span,
-                                                  format!("declaration of `{}` \
-                                                        shadows an enum \
-                                                        variant or unit-like \
-                                                        struct in scope",
-                                                        token::get_name(renamed)));
+                            resolve_err!(self, pattern.span, E0029,
+                                         "declaration of `{}` \
+                                         shadows an enum \
+                                         variant or unit-like \
+                                         struct in scope",
+                                         token::get_name(renamed));
                         }
                         FoundConst(def, lp) if mode == RefutableMode => {
                             debug!("(resolving pattern) resolving `{}` to \
@@ -4287,9 +4298,9 @@ impl<'a> Resolver<'a> {
                             self.record_def(pattern.id, (def, lp));
                         }
                         FoundConst(..) => {
-                            self.resolve_error(pattern.span,
-                                                  "only irrefutable patterns \
-                                                   allowed here");
+                            resolve_err!(self, pattern.span, E0030,
+                                         "only irrefutable patterns \
+                                          allowed here");
                         }
                         BareIdentifierPatternUnresolved => {
                             debug!("(resolving pattern) binding `{}`",
@@ -4341,10 +4352,10 @@ impl<'a> Resolver<'a> {
                                       // Then this is a duplicate variable
                                       // in the same disjunct, which is an
                                       // error
-                                     self.resolve_error(pattern.span,
-                                       format!("identifier `{}` is bound more \
-                                             than once in the same pattern",
-                                            path_to_str(path)));
+                                     resolve_err!(self, pattern.span, E0031,
+                                       "identifier `{}` is bound more \
+                                       than once in the same pattern",
+                                       path_to_str(path));
                                   }
                                   // Not bound in the same pattern: do nothing
                                 }
@@ -4386,15 +4397,14 @@ impl<'a> Resolver<'a> {
                             self.record_def(pattern.id, def);
                         }
                         Some(_) => {
-                            self.resolve_error(
-                                path.span,
-                                format!("`{}` is not an enum variant or constant",
-                                     token::get_ident(
-                                         path.segments.last().unwrap().identifier)))
+                            resolve_err!(self, path.span, E0032,
+                                "`{}` is not an enum variant or constant",
+                                token::get_ident(
+                                    path.segments.last().unwrap().identifier))
                         }
                         None => {
-                            self.resolve_error(path.span,
-                                                  "unresolved enum variant");
+                            resolve_err!(self, path.span, E0033,
+                                         "unresolved enum variant");
                         }
                     }
 
@@ -4416,18 +4426,19 @@ impl<'a> Resolver<'a> {
                             self.record_def(pattern.id, def);
                         }
                         Some(_) => {
-                            self.resolve_error(path.span,
-                                format!("`{}` is not an enum variant, struct or const",
-                                    token::get_ident(path.segments
-                                                         .last().unwrap()
-                                                         .identifier)));
+                            resolve_err!(self, path.span, E0034,
+                                "`{}` is not an enum variant, struct or const",
+                                token::get_ident(path.segments
+                                                     .last().unwrap()
+                                                     .identifier));
                         }
                         None => {
-                            self.resolve_error(path.span,
-                                format!("unresolved enum variant, struct or const `{}`",
-                                    token::get_ident(path.segments
-                                                         .last().unwrap()
-                                                         .identifier)));
+                            resolve_err!(self, path.span, E0035,
+                                "unresolved enum variant, struct or const `{}`",
+                                token::get_ident(path.segments
+                                                     .last().unwrap()
+                                                     .identifier));
+                            self.note_similar_name(path.span, path, ValueNS);
                         }
                     }
 
@@ -4468,7 +4479,7 @@ impl<'a> Resolver<'a> {
                                     def: {:?}", result);
                             let msg = format!("`{}` does not name a structure",
                                               self.path_idents_to_str(path));
-                            self.resolve_error(path.span, msg);
+                            resolve_err!(self, path.span, E0036, "{}", msg);
                         }
                     }
                 }
@@ -4688,7 +4699,7 @@ impl<'a> Resolver<'a> {
             Failed => {
                 let msg = format!("use of undeclared module `{}`",
                                   self.idents_to_str(module_path_idents.as_slice()));
-                self.resolve_error(path.span, msg);
+                resolve_err!(self, path.span, E0037, "{}", msg);
                 return None;
             }
 
@@ -4759,7 +4770,7 @@ impl<'a> Resolver<'a> {
             Failed => {
                 let msg = format!("use of undeclared module `::{}`",
                                   self.idents_to_str(module_path_idents.as_slice()));
-                self.resolve_error(path.span, msg);
+                resolve_err!(self, path.span, E0038, "{}", msg);
                 return None;
             }
 
@@ -4868,50 +4879,202 @@ impl<'a> Resolver<'a> {
         rs
     }
 
-    fn resolve_error(&self, span: Span, s: &str) {
-        if self.emit_errors {
-            self.session.span_err(span, s);
+    /// Adds the names bound in `namespace` in `module_`, by items, imports
+    /// or `extern crate`, to `names`.
+    fn names_in_module(&mut self,
+                       module_: &Rc<Module>,
+                       namespace: Namespace,
+                       names: &mut Vec<Name>) {
+        self.populate_module_if_necessary(module_);
+        for (&name, bindings) in module_.children.borrow().iter() {
+            if bindings.defined_in_namespace(namespace) {
+                names.push(name);
+            }
+        }
+        for (&name, resolution) in module_.import_resolutions.borrow().iter() {
+            if resolution.target_for_namespace(namespace).is_some() {
+                names.push(name);
+            }
+        }
+        if namespace == TypeNS {
+            for (&name, _) in module_.external_module_children.borrow().iter() {
+                names.push(name);
+            }
         }
     }
 
-    fn find_best_match_for_name(&mut self, name: &str, max_distance: uint)
+    /// Finds the name in scope that is spelled most like the last segment
+    /// of `path`, which failed to resolve, and returns `path` with it in
+    /// place of that segment.
+    fn find_best_match_for_name(&mut self, path: &Path, namespace: Namespace)
                                 -> Option<~str> {
-        let this = &mut *self;
+        let idents = path.segments.iter().map(|seg| seg.identifier).collect::<Vec<_>>();
+        let name = *idents.last().unwrap();
+        let module_path = idents.init();
 
-        let mut maybes: Vec<token::InternedString> = Vec::new();
-        let mut values: Vec<uint> = Vec::new();
+        let mut names = Vec::new();
+        if module_path.is_empty() && !path.global {
+            // Local variables and type parameters
+            {
+                let ribs = match namespace {
+                    ValueNS => &self.value_ribs,
+                    TypeNS => &self.type_ribs,
+                };
+                for rib in ribs.borrow().iter() {
+                    for (&name, _) in rib.bindings.borrow().iter() {
+                        names.push(name);
+                    }
+                }
+            }
 
-        let mut j = this.value_ribs.borrow().len();
-        while j != 0 {
-            j -= 1;
-            let value_ribs = this.value_ribs.borrow();
-            let bindings = value_ribs.get(j).bindings.borrow();
-            for (&k, _) in bindings.iter() {
-                maybes.push(token::get_name(k));
-                values.push(uint::MAX);
+            // Items and imports of the enclosing blocks and module; the
+            // prelude is among the imports
+            let mut module_ = self.current_module.clone();
+            loop {
+                self.names_in_module(&module_, namespace, &mut names);
+                let parent = match module_.parent_link {
+                    BlockParentLink(ref parent, _) => parent.upgrade(),
+                    _ => None,
+                };
+                match parent {
+                    Some(parent) => module_ = parent,
+                    None => break,
+                }
+            }
+        } else {
+            let containing_module = self.with_no_errors(|this| {
+                if path.global {
+                    let root = this.graph_root.get_module();
+                    this.resolve_module_path_from_root(root, module_path, 0, path.span,
+                                                       PathSearch, LastMod(AllPublic))
+                } else {
+                    let module_ = this.current_module.clone();
+                    this.resolve_module_path(module_, module_path, UseLexicalScope,
+                                             path.span, PathSearch)
+                }
+            });
+            match containing_module {
+                Success((module_, _)) => self.names_in_module(&module_, namespace, &mut names),
+                Failed | Indeterminate => return None,
             }
         }
 
-        let mut smallest = 0;
-        for (i, other) in maybes.iter().enumerate() {
-            *values.get_mut(i) = name.lev_distance(other.get());
+        let found = find_best_match_for_name(
+            names.iter().map(|&name| token::get_name(name)),
+            token::get_ident(name).get());
+        match found {
+            Some(found) if module_path.is_empty() => {
+                Some(format!("{}{}", if path.global { "::" } else { "" }, found))
+            }
+            Some(found) => {
+                Some(format!("{}{}::{}", if path.global { "::" } else { "" },
+                             self.idents_to_str(module_path), found))
+            }
+            None => None,
+        }
+    }
 
-            if *values.get(i) <= *values.get(smallest) {
-                smallest = i;
+    /// Follows an error about `path` not resolving with a note suggesting
+    /// the name in scope that is spelled most like it, if there is one.
+    fn note_similar_name(&mut self, span: Span, path: &Path, namespace: Namespace) {
+        if !self.emit_errors {
+            return;
+        }
+        match self.find_best_match_for_name(path, namespace) {
+            Some(name) => {
+                self.session.span_note(span, format!("did you mean `{}`?", name));
             }
+            None => {}
         }
+    }
 
-        if values.len() > 0 &&
-            *values.get(smallest) != uint::MAX &&
-            *values.get(smallest) < name.len() + 2 &&
-            *values.get(smallest) <= max_distance &&
-            name != maybes.get(smallest).get() {
+    /// Follows an error about an import of `name` from `containing_module`
+    /// with a note suggesting what was meant: a name bound there in one of
+    /// `namespaces` that is spelled like it, or else the path of another
+    /// module that has an item called `name`.
+    fn note_import_suggestion(&mut self,
+                              span: Span,
+                              containing_module: Rc<Module>,
+                              name: Ident,
+                              namespaces: &[Namespace]) {
+        if !self.emit_errors {
+            return;
+        }
+        let mut names = Vec::new();
+        for &namespace in namespaces.iter() {
+            self.names_in_module(&containing_module, namespace, &mut names);
+        }
+        let found = find_best_match_for_name(names.iter().map(|&name| token::get_name(name)),
+                                             token::get_ident(name).get());
+        let suggestion = match found {
+            Some(found) => {
+                let module_name = self.module_to_str(&*containing_module);
+                if "???" == module_name {
+                    Some(found.get().to_owned())
+                } else {
+                    Some(format!("{}::{}", module_name, found))
+                }
+            }
+            None => self.find_module_path_with_name(name.name),
+        };
+        match suggestion {
+            Some(path) => self.session.span_note(span, format!("did you mean `{}`?", path)),
+            None => {}
+        }
+    }
 
-            Some(maybes.get(smallest).get().to_str())
+    /// Searches the modules reachable from the crate root, nearest first,
+    /// for one that defines or publicly imports `name`, and returns the
+    /// path of that item. Modules of other crates are only searched if they
+    /// are public.
+    fn find_module_path_with_name(&mut self, name: Name) -> Option<~str> {
+        let mut queue = vec!((self.graph_root.get_module(), Vec::new()));
+        let mut seen = DefIdSet::new();
+        let mut i = 0;
+        while i < queue.len() {
+            let (module_, path) = queue.get(i).clone();
+            i += 1;
+            self.populate_module_if_necessary(&module_);
 
-        } else {
-            None
+            let defined = module_.children.borrow().contains_key(&name) ||
+                module_.external_module_children.borrow().contains_key(&name) ||
+                module_.import_resolutions.borrow().find(&name).map_or(false, |resolution| {
+                    resolution.is_public
+                });
+            if defined {
+                let mut path = path.iter().map(|&name| {
+                    token::get_name(name).get().to_owned()
+                }).collect::<Vec<~str>>();
+                path.push(token::get_name(name).get().to_owned());
+                return Some(path.connect("::"));
+            }
+
+            let mut children = Vec::new();
+            for (&child_name, child) in module_.children.borrow().iter() {
+                match child.get_module_if_available() {
+                    Some(child) => children.push((child_name, child)),
+                    None => {}
+                }
+            }
+            for (&child_name, child) in module_.external_module_children.borrow().iter() {
+                children.push((child_name, child.clone()));
+            }
+            for (child_name, child) in children.move_iter() {
+                match child.kind.get() {
+                    NormalModuleKind | ExternModuleKind => {}
+                    _ => continue,
+                }
+                match child.def_id.get() {
+                    Some(did) if did.krate != LOCAL_CRATE && !child.is_public => continue,
+                    Some(did) if !seen.insert(did) => continue,
+                    _ => {}
+                }
+                let mut child_path = path.clone();
+                child_path.push(child_name);
+                queue.push((child, child_path));
+            }
         }
+        None
     }
 
     fn resolve_expr(&mut self, expr: &Expr) {
@@ -4939,9 +5102,9 @@ impl<'a> Resolver<'a> {
                         // out here.
                         match def {
                             (DefMethod(..), _) => {
-                                self.resolve_error(expr.span,
-                                                      "first-class methods \
-                                                       are not supported");
+                                resolve_err!(self, expr.span, E0039,
+                                             "first-class methods \
+                                              are not supported");
                                 self.session.span_note(expr.span,
                                                        "call the method \
                                                         using the `.` \
@@ -4962,11 +5125,11 @@ impl<'a> Resolver<'a> {
                             this.resolve_path(expr.id, path, TypeNS, false)) {
                             Some((DefTy(struct_id), _))
                               if self.structs.contains(&struct_id) => {
-                                self.resolve_error(expr.span,
-                                        format!("`{}` is a structure name, but \
-                                                 this expression \
-                                                 uses it like a function name",
-                                                wrong_name));
+                                resolve_err!(self, expr.span, E0040,
+                                             "`{}` is a structure name, but \
+                                              this expression \
+                                              uses it like a function name",
+                                             wrong_name);
 
                                 self.session.span_note(expr.span,
                                     format!("Did you mean to write: \
@@ -4974,22 +5137,11 @@ impl<'a> Resolver<'a> {
                                             wrong_name));
 
                             }
-                            _ =>
-                               // limit search to 5 to reduce the number
-                               // of stupid suggestions
-                               match self.find_best_match_for_name(wrong_name, 5) {
-                                   Some(m) => {
-                                       self.resolve_error(expr.span,
-                                           format!("unresolved name `{}`. \
-                                                    Did you mean `{}`?",
-                                                    wrong_name, m));
-                                   }
-                                   None => {
-                                       self.resolve_error(expr.span,
-                                            format!("unresolved name `{}`.",
-                                                    wrong_name));
-                                   }
-                               }
+                            _ => {
+                                resolve_err!(self, expr.span, E0042,
+                                             "unresolved name `{}`.", wrong_name);
+                                self.note_similar_name(expr.span, path, ValueNS);
+                            }
                         }
                     }
                 }
@@ -5021,7 +5173,7 @@ impl<'a> Resolver<'a> {
                                 def: {:?}", result);
                         let msg = format!("`{}` does not name a structure",
                                           self.path_idents_to_str(path));
-                        self.resolve_error(path.span, msg);
+                        resolve_err!(self, path.span, E0043, "{}", msg);
                     }
                 }
 
@@ -5051,9 +5203,9 @@ impl<'a> Resolver<'a> {
                 match self.search_ribs(self.label_ribs.borrow().as_slice(),
                                        renamed, expr.span) {
                     None =>
-                        self.resolve_error(expr.span,
-                                              format!("use of undeclared label `{}`",
-                                                   token::get_ident(label))),
+                        resolve_err!(self, expr.span, E0044,
+                                     "use of undeclared label `{}`",
+                                     token::get_ident(label)),
                     Some(DlDef(def @ DefLabel(_))) => {
                         // Since this def is a label, it is never read.
                         self.record_def(expr.id, (def, LastMod(AllPublic)))
@@ -5198,10 +5350,9 @@ impl<'a> Resolver<'a> {
         match pat_binding_mode {
             BindByValue(_) => {}
             BindByRef(..) => {
-                self.resolve_error(
-                    pat.span,
-                    format!("cannot use `ref` binding mode with {}",
-                         descr));
+                resolve_err!(self, pat.span, E0045,
+                    "cannot use `ref` binding mode with {}",
+                    descr);
             }
         }
     }
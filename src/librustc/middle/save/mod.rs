@@ -0,0 +1,414 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Dumping of the results of name resolution and type checking for use by
+external tools, enabled with `-Z save-analysis`.
+
+After analysis, the crate is walked and one record is written to
+`save-analysis/<crate name>.csv` in the output directory for
+
+* every definition: functions, methods, statics, modules, types, traits,
+  structs and their fields, enums and their variants, and local
+  variables, along with the definition's qualified path and type;
+* every reference to a definition: paths in expressions, types, patterns
+  and `use` declarations, method calls (as resolved by typeck), field
+  accesses and the fields of struct expressions.
+
+Definitions are identified by their node id, and references by the
+`DefId` they resolved to; the `external_crate` records give the names of
+the crates the `DefId`s point into, so that references into other crates
+can be matched up with the records written for those crates. The format
+of a record is described in `recorder`.
+
+*/
+
+use back::link;
+use driver::driver::{CrateAnalysis, OutputFilenames};
+use driver::session::Session;
+use middle::save::recorder::{Recorder, Value, Str, Num, span_fields, ref_fields};
+use middle::ty;
+use middle::typeck;
+use util::ppaux;
+
+use std::io;
+use std::io::fs;
+use syntax::ast;
+use syntax::ast_util::{local_def, is_local};
+use syntax::codemap;
+use syntax::codemap::Span;
+use syntax::parse;
+use syntax::parse::lexer;
+use syntax::parse::lexer::Reader;
+use syntax::parse::token;
+use syntax::visit;
+use syntax::visit::Visitor;
+
+mod recorder;
+
+struct SaveVisitor<'a> {
+    tcx: &'a ty::ctxt,
+    crate_name: ~str,
+    recorder: Recorder,
+    // Used to re-lex source snippets when looking for the spans of names
+    snippet_sess: parse::ParseSess,
+}
+
+impl<'a> SaveVisitor<'a> {
+    fn record(&mut self, kind: &str, span: Span, fields: Vec<(&'static str, Value)>) {
+        // Nodes made up by the compiler can't be found in the source.
+        if span == codemap::DUMMY_SP {
+            return
+        }
+        let mut all = span_fields(self.tcx.sess.codemap(), span);
+        all.push_all_move(fields);
+        self.recorder.record(kind, all.as_slice());
+    }
+
+    /// The span of the identifier `name` in `span`: its first occurrence, or
+    /// its last one if `last` is set. This is `span` itself if the name can't
+    /// be found, for example in code generated by a macro.
+    fn ident_span(&self, span: Span, name: ast::Name, last: bool) -> Span {
+        let snippet = match self.tcx.sess.codemap().span_to_snippet(span) {
+            Some(snippet) => snippet,
+            None => return span,
+        };
+        let fm = parse::string_to_filemap(&self.snippet_sess, snippet,
+                                          "<save-analysis>".to_strbuf());
+        let start = fm.start_pos;
+        let mut lexer = lexer::new_string_reader(&self.snippet_sess.span_diagnostic, fm);
+        let mut found = None;
+        loop {
+            let next = lexer.next_token();
+            match next.tok {
+                token::EOF => break,
+                token::IDENT(ident, _) if ident.name == name => {
+                    found = Some(next.sp);
+                    if !last { break }
+                }
+                _ => {}
+            }
+        }
+        match found {
+            Some(sp) => Span {
+                lo: span.lo + (sp.lo - start),
+                hi: span.lo + (sp.hi - start),
+                expn_info: span.expn_info,
+            },
+            None => span,
+        }
+    }
+
+    /// The path of the local item `id`, starting with the name of the crate.
+    fn qualname(&self, id: ast::NodeId) -> ~str {
+        format!("{}::{}", self.crate_name, self.tcx.map.path_to_str(id))
+    }
+
+    /// The path of the item `def_id`, which may be in another crate.
+    fn def_qualname(&self, def_id: ast::DefId) -> ~str {
+        if is_local(def_id) {
+            self.qualname(def_id.node)
+        } else {
+            ty::item_path_str(self.tcx, def_id)
+        }
+    }
+
+    fn record_def(&mut self, kind: &str, span: Span, id: ast::NodeId,
+                  qualname: ~str, sig: Option<ty::t>) {
+        let sig = match sig {
+            Some(t) => ppaux::ty_to_str(self.tcx, t),
+            None => "".to_owned(),
+        };
+        self.record(kind, span, vec!(("id", Num(id as uint)),
+                                     ("qualname", Str(qualname)),
+                                     ("sig", Str(sig))));
+    }
+
+    /// Records the definition of the item `id` named `name`, declared at
+    /// `span`, whose type is known if `typed` is set.
+    fn record_item(&mut self, kind: &str, span: Span, name: ast::Ident, id: ast::NodeId,
+                   typed: bool) {
+        let span = self.ident_span(span, name.name, false);
+        let qualname = self.qualname(id);
+        let sig = if typed {
+            Some(ty::lookup_item_type(self.tcx, local_def(id)).ty)
+        } else {
+            None
+        };
+        self.record_def(kind, span, id, qualname, sig);
+    }
+
+    fn record_fields(&mut self, def: &ast::StructDef, parent: ast::NodeId) {
+        for field in def.fields.iter() {
+            match field.node.kind {
+                ast::NamedField(ident, _) => {
+                    let qualname = format!("{}::{}", self.qualname(parent),
+                                           token::get_ident(ident));
+                    let sig = ty::lookup_item_type(self.tcx, local_def(field.node.id)).ty;
+                    let span = self.ident_span(field.span, ident.name, false);
+                    self.record_def("field", span, field.node.id, qualname, Some(sig));
+                }
+                ast::UnnamedField(..) => {}
+            }
+        }
+    }
+
+    fn record_ref(&mut self, kind: &str, span: Span, def_id: ast::DefId,
+                  qualname: Option<~str>) {
+        let mut fields = ref_fields(def_id);
+        match qualname {
+            Some(qualname) => fields.push(("refqualname", Str(qualname))),
+            None => {}
+        }
+        self.record(kind, span, fields);
+    }
+
+    fn record_item_ref(&mut self, kind: &str, span: Span, def_id: ast::DefId) {
+        let qualname = self.def_qualname(def_id);
+        self.record_ref(kind, span, def_id, Some(qualname));
+    }
+
+    /// Records the path at `span` ending in `name` which resolved to the
+    /// definition of `id` in the def map, or the variable it defines if it
+    /// is a binding.
+    fn record_path(&mut self, span: Span, id: ast::NodeId, name: ast::Ident) {
+        let def = match self.tcx.def_map.borrow().find(&id) {
+            Some(&def) => def,
+            None => return,
+        };
+        match def {
+            ast::DefLocal(var, _) | ast::DefArg(var, _) |
+            ast::DefBinding(var, _) if var == id => {
+                let sig = ty::node_id_to_type(self.tcx, id);
+                let name = token::get_ident(name).get().to_owned();
+                self.record_def("variable", span, id, name, Some(sig));
+            }
+            ast::DefLocal(var, _) | ast::DefArg(var, _) |
+            ast::DefBinding(var, _) | ast::DefUpvar(var, _, _, _) => {
+                self.record_ref("var_ref", span, local_def(var), None);
+            }
+            ast::DefStatic(def_id, _) => self.record_item_ref("var_ref", span, def_id),
+            ast::DefFn(def_id, _) |
+            ast::DefStaticMethod(def_id, _, _) |
+            ast::DefMethod(def_id, _) => self.record_item_ref("fn_ref", span, def_id),
+            ast::DefMod(def_id) |
+            ast::DefForeignMod(def_id) => self.record_item_ref("mod_ref", span, def_id),
+            ast::DefTy(def_id) |
+            ast::DefTrait(def_id) |
+            ast::DefStruct(def_id) |
+            ast::DefVariant(_, def_id, _) => self.record_item_ref("type_ref", span, def_id),
+            ast::DefTyParam(def_id, _) => self.record_ref("type_ref", span, def_id, None),
+            // Primitive types, `Self`, lifetimes and labels have nothing to
+            // point to.
+            _ => {}
+        }
+    }
+
+    fn record_field_ref(&mut self, span: Span, struct_id: ast::DefId, name: ast::Name) {
+        let fields = ty::lookup_struct_fields(self.tcx, struct_id);
+        for field in fields.iter() {
+            if field.name == name {
+                let qualname = format!("{}::{}", self.def_qualname(struct_id),
+                                       token::get_name(name));
+                self.record_ref("field_ref", span, field.id, Some(qualname));
+                return
+            }
+        }
+    }
+
+    /// The struct whose field is accessed on a value of type `t`, looking
+    /// through pointers.
+    fn struct_of(&self, mut t: ty::t) -> Option<ast::DefId> {
+        loop {
+            match ty::get(t).sty {
+                ty::ty_struct(def_id, _) => return Some(def_id),
+                _ => match ty::deref(t, true) {
+                    Some(mt) => t = mt.ty,
+                    None => return None,
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<()> for SaveVisitor<'a> {
+    fn visit_item(&mut self, item: &ast::Item, _: ()) {
+        match item.node {
+            ast::ItemFn(..) => self.record_item("function", item.span, item.ident, item.id, true),
+            ast::ItemStatic(..) => self.record_item("static", item.span, item.ident, item.id, true),
+            ast::ItemMod(..) => self.record_item("mod", item.span, item.ident, item.id, false),
+            ast::ItemTy(..) => self.record_item("type", item.span, item.ident, item.id, true),
+            ast::ItemTrait(..) => self.record_item("trait", item.span, item.ident, item.id, false),
+            ast::ItemStruct(def, _) => {
+                self.record_item("struct", item.span, item.ident, item.id, false);
+                self.record_fields(&*def, item.id);
+            }
+            ast::ItemEnum(ref def, _) => {
+                self.record_item("enum", item.span, item.ident, item.id, false);
+                for variant in def.variants.iter() {
+                    let id = variant.node.id;
+                    self.record_item("variant", variant.span, variant.node.name, id, true);
+                    match variant.node.kind {
+                        ast::StructVariantKind(def) => self.record_fields(&*def, id),
+                        ast::TupleVariantKind(..) => {}
+                    }
+                }
+            }
+            ast::ItemForeignMod(..) | ast::ItemImpl(..) | ast::ItemMac(..) => {}
+        }
+        visit::walk_item(self, item, ());
+    }
+
+    fn visit_foreign_item(&mut self, item: &ast::ForeignItem, _: ()) {
+        let kind = match item.node {
+            ast::ForeignItemFn(..) => "function",
+            ast::ForeignItemStatic(..) => "static",
+        };
+        self.record_item(kind, item.span, item.ident, item.id, true);
+        visit::walk_foreign_item(self, item, ());
+    }
+
+    fn visit_fn(&mut self, fk: &visit::FnKind, fd: &ast::FnDecl,
+                b: &ast::Block, s: Span, id: ast::NodeId, _: ()) {
+        match *fk {
+            visit::FkMethod(_, _, method) => {
+                self.record_item("method", method.span, method.ident, id, true);
+            }
+            visit::FkItemFn(..) | visit::FkFnBlock => {}
+        }
+        visit::walk_fn(self, fk, fd, b, s, id, ());
+    }
+
+    fn visit_ty_method(&mut self, method: &ast::TypeMethod, _: ()) {
+        self.record_item("method", method.span, method.ident, method.id, true);
+        visit::walk_ty_method(self, method, ());
+    }
+
+    fn visit_view_item(&mut self, item: &ast::ViewItem, _: ()) {
+        match item.node {
+            ast::ViewItemExternCrate(_, _, id) => {
+                match self.tcx.sess.cstore.find_extern_mod_stmt_cnum(id) {
+                    Some(cnum) => {
+                        let name = self.tcx.sess.cstore.get_crate_data(cnum).name.clone();
+                        self.record("extern_crate", item.span,
+                                    vec!(("id", Num(id as uint)),
+                                         ("crate", Num(cnum as uint)),
+                                         ("name", Str(name))));
+                    }
+                    None => {}
+                }
+            }
+            ast::ViewItemUse(ref path) => match path.node {
+                // The other kinds of `use` are recorded by `visit_path`.
+                ast::ViewPathList(_, ref list, _) => {
+                    for ident in list.iter() {
+                        self.record_path(ident.span, ident.node.id, ident.node.name);
+                    }
+                }
+                ast::ViewPathSimple(..) | ast::ViewPathGlob(..) => {}
+            },
+        }
+        visit::walk_view_item(self, item, ());
+    }
+
+    fn visit_path(&mut self, path: &ast::Path, id: ast::NodeId, _: ()) {
+        let name = path.segments.last().unwrap().identifier;
+        self.record_path(path.span, id, name);
+        visit::walk_path(self, path, ());
+    }
+
+    fn visit_expr(&mut self, ex: &ast::Expr, _: ()) {
+        match ex.node {
+            ast::ExprMethodCall(ident, _, _) => {
+                let method_call = typeck::MethodCall::expr(ex.id);
+                let origin = self.tcx.method_map.borrow().find(&method_call)
+                                 .map(|callee| callee.origin.clone());
+                let def_id = match origin {
+                    Some(typeck::MethodStatic(def_id)) => Some(def_id),
+                    Some(typeck::MethodParam(ref param)) => {
+                        Some(ty::trait_method(self.tcx, param.trait_id,
+                                              param.method_num).def_id)
+                    }
+                    Some(typeck::MethodObject(ref object)) => {
+                        Some(ty::trait_method(self.tcx, object.trait_id,
+                                              object.method_num).def_id)
+                    }
+                    None => None,
+                };
+                match def_id {
+                    Some(def_id) => self.record_item_ref("method_call", ident.span, def_id),
+                    None => {}
+                }
+            }
+            ast::ExprField(base, ident, _) => {
+                let t = ty::expr_ty_adjusted(self.tcx, base);
+                match self.struct_of(t) {
+                    Some(def_id) => {
+                        let span = self.ident_span(ex.span, ident.name, true);
+                        self.record_field_ref(span, def_id, ident.name)
+                    }
+                    None => {}
+                }
+            }
+            ast::ExprStruct(_, ref fields, _) => {
+                match self.struct_of(ty::expr_ty(self.tcx, ex)) {
+                    Some(def_id) => {
+                        for field in fields.iter() {
+                            self.record_field_ref(field.ident.span, def_id,
+                                                  field.ident.node.name);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, ex, ());
+    }
+}
+
+pub fn process_crate(sess: &Session,
+                     krate: &ast::Crate,
+                     analysis: &CrateAnalysis,
+                     outputs: &OutputFilenames) {
+    let crate_name = link::find_crate_id(krate.attrs.as_slice(),
+                                         outputs.out_filestem).name.into_owned();
+
+    let dir = outputs.out_directory.join("save-analysis");
+    match fs::mkdir_recursive(&dir, io::UserRWX) {
+        Ok(()) => {}
+        Err(e) => sess.fatal(format!("could not create directory {}: {}",
+                                     dir.display(), e)),
+    }
+    let path = dir.join(format!("{}.csv", crate_name));
+    let out = match io::File::create(&path) {
+        Ok(f) => box f as Box<Writer>,
+        Err(e) => sess.fatal(format!("could not open {}: {}", path.display(), e)),
+    };
+
+    let mut visitor = SaveVisitor {
+        tcx: &analysis.ty_cx,
+        crate_name: crate_name.clone(),
+        recorder: Recorder::new(out),
+        snippet_sess: parse::new_parse_sess(),
+    };
+    visitor.recorder.record("crate", [("name", Str(crate_name))]);
+    sess.cstore.iter_crate_data(|cnum, data| {
+        visitor.recorder.record("external_crate", [("name", Str(data.name.clone())),
+                                                   ("crate", Num(cnum as uint))]);
+    });
+    visit::walk_crate(&mut visitor, krate, ());
+
+    match visitor.recorder.error {
+        Some(ref e) => sess.err(format!("error writing {}: {}", path.display(), e)),
+        None => {}
+    }
+}
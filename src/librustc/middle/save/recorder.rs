@@ -0,0 +1,104 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Writing of the records of `-Z save-analysis`.
+//!
+//! Each record is one line of comma separated values: the kind of the
+//! record followed by pairs of field names and values, for example
+//!
+//! ```notrust
+//! function,file_name,"foo.rs",file_line,3,...,qualname,"foo::bar"
+//! ```
+//!
+//! Strings are quoted, with any `"` in them doubled.
+
+use syntax::ast;
+use syntax::codemap::{CodeMap, Pos, Span};
+
+use std::io;
+
+pub struct Recorder {
+    out: Box<Writer>,
+    /// The first error that happened while writing, after which nothing
+    /// more is written.
+    pub error: Option<io::IoError>,
+}
+
+/// The value of a field of a record.
+pub enum Value {
+    Str(~str),
+    Num(uint),
+}
+
+impl Recorder {
+    pub fn new(out: Box<Writer>) -> Recorder {
+        Recorder { out: out, error: None }
+    }
+
+    pub fn record(&mut self, kind: &str, fields: &[(&str, Value)]) {
+        if self.error.is_some() {
+            return
+        }
+        let mut line = StrBuf::from_str(kind);
+        for &(name, ref value) in fields.iter() {
+            line.push_char(',');
+            line.push_str(name);
+            line.push_char(',');
+            match *value {
+                Str(ref s) => line.push_str(quote(s.as_slice())),
+                Num(n) => line.push_str(n.to_str()),
+            }
+        }
+        line.push_char('\n');
+        match self.out.write_str(line.as_slice()) {
+            Ok(()) => {}
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// Quotes `s` as a CSV value.
+pub fn quote(s: &str) -> ~str {
+    format!("\"{}\"", s.replace("\"", "\"\""))
+}
+
+/// The fields locating `span` in its source file: the file name, the line
+/// and column of its start and end, and its byte offsets within the file.
+pub fn span_fields(cm: &CodeMap, span: Span) -> Vec<(&'static str, Value)> {
+    let lo = cm.lookup_char_pos(span.lo);
+    let hi = cm.lookup_char_pos(span.hi);
+    let start = lo.file.start_pos.to_uint();
+    vec!(("file_name", Str(lo.file.name.as_slice().to_owned())),
+         ("file_line", Num(lo.line)),
+         ("file_col", Num(lo.col.to_uint())),
+         ("file_line_end", Num(hi.line)),
+         ("file_col_end", Num(hi.col.to_uint())),
+         ("byte_start", Num(span.lo.to_uint() - start)),
+         ("byte_end", Num(span.hi.to_uint() - start)))
+}
+
+/// The fields identifying the definition `def_id`, whose crate is named by
+/// the `external_crate` records.
+pub fn ref_fields(def_id: ast::DefId) -> Vec<(&'static str, Value)> {
+    vec!(("refid", Num(def_id.node as uint)),
+         ("refidcrate", Num(def_id.krate as uint)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::quote;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("foo::bar"), "\"foo::bar\"".to_owned());
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"".to_owned());
+        assert_eq!(quote(""), "\"\"".to_owned());
+    }
+}
@@ -26,7 +26,7 @@
 #![allow(non_camel_case_types)]
 
 use back::link::{mangle_exported_name};
-use back::{link, abi};
+use back::{incremental, link, abi};
 use driver::session;
 use driver::session::{Session, NoDebugInfo, FullDebugInfo};
 use driver::driver::OutputFilenames;
@@ -225,7 +225,7 @@ fn get_extern_rust_fn(ccx: &CrateContext, inputs: &[ty::t], output: ty::t,
     }
 
     let f = decl_rust_fn(ccx, false, inputs, output, name);
-    csearch::get_item_attrs(&ccx.sess().cstore, did, |meta_items| {
+    csearch::get_item_attrs(ccx.sess(), did, |meta_items| {
         set_llvm_fn_attrs(meta_items.iter().map(|&x| attr::mk_attr(x)).collect::<~[_]>(), f)
     });
 
@@ -452,6 +452,18 @@ pub fn set_always_inline(f: ValueRef) {
     lib::llvm::SetFunctionAttribute(f, lib::llvm::AlwaysInlineAttribute)
 }
 
+/// Marks `f` to be instrumented by the sanitizer given to `-Z sanitizer`,
+/// if any.
+pub fn set_sanitize(ccx: &CrateContext, f: ValueRef) {
+    let attr = match ccx.sess().opts.sanitizer {
+        Some(session::AddressSanitizer) => lib::llvm::SanitizeAddressAttribute,
+        Some(session::ThreadSanitizer) => lib::llvm::SanitizeThreadAttribute,
+        Some(session::MemorySanitizer) => lib::llvm::SanitizeMemoryAttribute,
+        None => return,
+    };
+    unsafe { llvm::LLVMRustAddSanitizeAttribute(f, attr) }
+}
+
 pub fn set_split_stack(f: ValueRef) {
     "split-stack".with_c_str(|buf| {
         unsafe { llvm::LLVMAddFunctionAttrString(f, buf); }
@@ -501,7 +513,7 @@ pub fn get_res_dtor(ccx: &CrateContext,
         get_item_val(ccx, did.node)
     } else {
         let tcx = ccx.tcx();
-        let name = csearch::get_symbol(&ccx.sess().cstore, did);
+        let name = csearch::get_symbol(ccx.sess(), did);
         let class_ty = ty::subst_tps(tcx,
                                      substs,
                                      None,
@@ -842,7 +854,7 @@ pub fn fail_if_zero<'a>(
 }
 
 pub fn trans_external_path(ccx: &CrateContext, did: ast::DefId, t: ty::t) -> ValueRef {
-    let name = csearch::get_symbol(&ccx.sess().cstore, did);
+    let name = csearch::get_symbol(ccx.sess(), did);
     match ty::get(t).sty {
         ty::ty_bare_fn(ref fn_ty) => {
             match fn_ty.abi.for_target(ccx.sess().targ_cfg.os,
@@ -1163,6 +1175,7 @@ pub fn new_fn_ctxt<'a>(ccx: &'a CrateContext,
     };
     let uses_outptr = type_of::return_uses_outptr(ccx, substd_output_type);
     let debug_context = debuginfo::create_function_debug_context(ccx, id, param_substs, llfndecl);
+    set_sanitize(ccx, llfndecl);
 
     let mut fcx = FunctionContext {
           llfn: llfndecl,
@@ -1598,13 +1611,21 @@ pub fn trans_item(ccx: &CrateContext, item: &ast::Item) {
                 ccx, decl, body, item.attrs.as_slice(), llfndecl, item.id);
         } else if !generics.is_type_parameterized() {
             let llfn = get_item_val(ccx, item.id);
-            trans_fn(ccx,
-                     decl,
-                     body,
-                     llfn,
-                     None,
-                     item.id,
-                     item.attrs.as_slice());
+            // Functions whose object code comes out of the incremental cache
+            // are only declared.
+            let translate = match ccx.incremental {
+                Some(ref fingerprints) => fingerprints.translate(ccx.sess(), item.id),
+                None => true,
+            };
+            if translate {
+                trans_fn(ccx,
+                         decl,
+                         body,
+                         llfn,
+                         None,
+                         item.id,
+                         item.attrs.as_slice());
+            }
         } else {
             // Be sure to travel more than just one layer deep to catch nested
             // items in blocks and such.
@@ -1868,7 +1889,7 @@ pub fn get_item_val(ccx: &CrateContext, id: ast::NodeId) -> ValueRef {
                         match ccx.external_srcs.borrow().find(&i.id) {
                             Some(&did) => {
                                 debug!("but found in other crate...");
-                                (csearch::get_symbol(&ccx.sess().cstore,
+                                (csearch::get_symbol(ccx.sess(),
                                                      did), false)
                             }
                             None => (sym, true)
@@ -2168,8 +2189,18 @@ pub fn trans_crate(krate: ast::Crate,
     let mut llmod_id = link_meta.crateid.name.clone();
     llmod_id.push_str(".rs");
 
-    let ccx = CrateContext::new(llmod_id.as_slice(), tcx, exp_map2,
-                                Sha256::new(), link_meta, reachable);
+    let mut ccx = CrateContext::new(llmod_id.as_slice(), tcx, exp_map2,
+                                    Sha256::new(), link_meta, reachable);
+    let incremental_dir = ccx.sess().opts.incremental.clone();
+    match incremental_dir {
+        Some(dir) => {
+            let fingerprints = incremental::Fingerprints::compute(&ccx.tcx, &krate,
+                                                                  &ccx.link_meta,
+                                                                  &ccx.reachable, &dir);
+            ccx.incremental = Some(fingerprints);
+        }
+        None => {}
+    }
     {
         let _icx = push_ctxt("text");
         trans_mod(&ccx, &krate.module);
@@ -2177,7 +2208,13 @@ pub fn trans_crate(krate: ast::Crate,
 
     glue::emit_tydescs(&ccx);
     if ccx.sess().opts.debuginfo != NoDebugInfo {
-        debuginfo::finalize(&ccx);
+        match debuginfo::finalize(&ccx) {
+            Some(compile_unit) if ccx.sess().debugging_opt(session::PROFILE) => {
+                debuginfo::add_gcov_metadata(&ccx, compile_unit,
+                                             &output.with_extension("gcno"));
+            }
+            _ => {}
+        }
     }
 
     // Translate the metadata.
@@ -2234,6 +2271,10 @@ pub fn trans_crate(krate: ast::Crate,
 
     let metadata_module = ccx.metadata_llmod;
     let formats = ccx.tcx.dependency_formats.borrow().clone();
+    let incremental = match ccx.incremental {
+        Some(ref fingerprints) => fingerprints.items(&*ccx.item_symbols.borrow()),
+        None => Vec::new(),
+    };
 
     (ccx.tcx, CrateTranslation {
         context: llcx,
@@ -2243,5 +2284,6 @@ pub fn trans_crate(krate: ast::Crate,
         metadata: metadata,
         reachable: reachable,
         crate_formats: formats,
+        incremental: incremental,
     })
 }
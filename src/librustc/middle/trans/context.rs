@@ -9,6 +9,7 @@
 // except according to those terms.
 
 
+use back::incremental;
 use driver::session::NoDebugInfo;
 use driver::session::Session;
 use lib::llvm::{ContextRef, ModuleRef, ValueRef};
@@ -121,6 +122,8 @@ pub struct CrateContext {
     /// is not emitted by LLVM's GC pass when no functions use GC.
     pub uses_gc: bool,
     pub dbg_cx: Option<debuginfo::CrateDebugContext>,
+    /// The fingerprints of the crate's items when compiling incrementally.
+    pub incremental: Option<incremental::Fingerprints>,
 
     intrinsics: RefCell<HashMap<&'static str, ValueRef>>,
 }
@@ -210,6 +213,7 @@ impl CrateContext {
                 builder: BuilderRef_res(llvm::LLVMCreateBuilderInContext(llcx)),
                 uses_gc: false,
                 dbg_cx: dbg_cx,
+                incremental: None,
                 intrinsics: RefCell::new(HashMap::new()),
             };
 
@@ -147,7 +147,7 @@ use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 use collections::HashMap;
 use collections::HashSet;
-use libc::{c_uint, c_ulonglong, c_longlong};
+use libc::{c_char, c_uint, c_ulonglong, c_longlong};
 use std::ptr;
 use std::strbuf::StrBuf;
 use std::sync::atomics;
@@ -260,14 +260,14 @@ enum VariableKind {
     CapturedVariable,
 }
 
-/// Create any deferred debug metadata nodes
-pub fn finalize(cx: &CrateContext) {
+/// Create any deferred debug metadata nodes, returning the compile unit
+pub fn finalize(cx: &CrateContext) -> Option<DIDescriptor> {
     if cx.dbg_cx.is_none() {
-        return;
+        return None;
     }
 
     debug!("finalize");
-    compile_unit_metadata(cx);
+    let compile_unit = compile_unit_metadata(cx);
     unsafe {
         llvm::LLVMDIBuilderFinalize(DIB(cx));
         llvm::LLVMDIBuilderDispose(DIB(cx));
@@ -292,6 +292,26 @@ pub fn finalize(cx: &CrateContext) {
             |s| llvm::LLVMRustAddModuleFlag(cx.llmod, s,
                                             llvm::LLVMRustDebugMetadataVersion));
     };
+    Some(compile_unit)
+}
+
+/// Tells the GCOV profiling pass of `-Z profile` where to write the notes
+/// (`.gcno`) and counters (`.gcda`) of `compile_unit`. The pass takes the
+/// directory and stem of `path` and replaces its extension.
+pub fn add_gcov_metadata(cx: &CrateContext, compile_unit: DIDescriptor, path: &Path) {
+    let path = path.as_vec();
+    unsafe {
+        let file = llvm::LLVMMDStringInContext(cx.llcx,
+                                               path.as_ptr() as *c_char,
+                                               path.len() as c_uint);
+        let operands = [file, compile_unit];
+        let node = llvm::LLVMMDNodeInContext(cx.llcx,
+                                             operands.as_ptr(),
+                                             operands.len() as c_uint);
+        "llvm.gcov".with_c_str(|name| {
+            llvm::LLVMAddNamedMetadataOperand(cx.llmod, name, node)
+        });
+    }
 }
 
 /// Creates debug information for the given global variable.
@@ -953,7 +973,7 @@ fn create_DIArray(builder: DIBuilderRef, arr: &[DIDescriptor]) -> DIArray {
     };
 }
 
-fn compile_unit_metadata(cx: &CrateContext) {
+fn compile_unit_metadata(cx: &CrateContext) -> DIDescriptor {
     let work_dir = &cx.sess().working_dir;
     let compile_unit_name = match cx.sess().local_crate_source_file {
         None => fallback_path(cx),
@@ -987,7 +1007,7 @@ fn compile_unit_metadata(cx: &CrateContext) {
     let producer = format!("rustc version {}",
                            (option_env!("CFG_VERSION")).expect("CFG_VERSION"));
 
-    compile_unit_name.with_ref(|compile_unit_name| {
+    return compile_unit_name.with_ref(|compile_unit_name| {
         work_dir.as_vec().with_c_str(|work_dir| {
             producer.with_c_str(|producer| {
                 "".with_c_str(|flags| {
@@ -1002,7 +1022,7 @@ fn compile_unit_metadata(cx: &CrateContext) {
                                 cx.sess().opts.optimize != session::No,
                                 flags,
                                 0,
-                                split_name);
+                                split_name)
                         }
                     })
                 })
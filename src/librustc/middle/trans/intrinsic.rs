@@ -11,6 +11,8 @@
 #![allow(non_uppercase_pattern_statics)]
 
 use arena::TypedArena;
+use back::link;
+use back::svh::Svh;
 use lib::llvm::{SequentiallyConsistent, Acquire, Release, Xchg};
 use lib::llvm::{ValueRef, Pointer, Array, Struct};
 use lib;
@@ -337,10 +339,11 @@ pub fn trans_intrinsic(ccx: &CrateContext,
             Ret(bcx, td);
         }
         "type_id" => {
+            let svh = Svh::new(link::crate_disambiguator(ccx.sess(), &ccx.link_meta));
             let hash = ty::hash_crate_independent(
                 ccx.tcx(),
                 *substs.tys.get(0),
-                &ccx.link_meta.crate_hash);
+                &svh);
             // NB: This needs to be kept in lockstep with the TypeId struct in
             //     libstd/unstable/intrinsics.rs
             let val = C_named_struct(type_of::type_of(ccx, output_type),
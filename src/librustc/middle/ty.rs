@@ -3555,7 +3555,7 @@ pub fn trait_method_def_ids(cx: &ctxt, id: ast::DefId) -> Rc<Vec<DefId>> {
                                      id,
                                      &mut *cx.trait_method_def_ids.borrow_mut(),
                                      || {
-        Rc::new(csearch::get_trait_method_def_ids(&cx.sess.cstore, id))
+        Rc::new(csearch::get_trait_method_def_ids(&cx.sess, id))
     })
 }
 
@@ -3898,7 +3898,7 @@ pub fn each_attr(tcx: &ctxt, did: DefId, f: |@ast::MetaItem| -> bool) -> bool {
         item.attrs.iter().advance(|attr| f(attr.node.value))
     } else {
         let mut cont = true;
-        csearch::get_item_attrs(&tcx.sess.cstore, did, |meta_items| {
+        csearch::get_item_attrs(&tcx.sess, did, |meta_items| {
             if cont {
                 cont = meta_items.iter().advance(|ptrptr| f(*ptrptr));
             }
@@ -4012,7 +4012,7 @@ pub fn lookup_struct_fields(cx: &ctxt, did: ast::DefId) -> Vec<field_ty> {
         assert!(result.len() == len);
         result
     } else {
-        csearch::get_struct_fields(&cx.sess.cstore, did)
+        csearch::get_struct_fields(&cx.sess, did)
     }
 }
 
@@ -4311,7 +4311,7 @@ pub fn visitor_object_ty(tcx: &ctxt,
 pub fn item_variances(tcx: &ctxt, item_id: ast::DefId) -> Rc<ItemVariances> {
     lookup_locally_or_in_crate_store(
         "item_variance_map", item_id, &mut *tcx.item_variance_map.borrow_mut(),
-        || Rc::new(csearch::get_item_variances(&tcx.sess.cstore, item_id)))
+        || Rc::new(csearch::get_item_variances(&tcx.sess, item_id)))
 }
 
 /// Records a trait-to-implementation mapping.
@@ -4339,9 +4339,9 @@ pub fn populate_implementations_for_type_if_necessary(tcx: &ctxt,
         return
     }
 
-    csearch::each_implementation_for_type(&tcx.sess.cstore, type_id,
+    csearch::each_implementation_for_type(&tcx.sess, type_id,
             |impl_def_id| {
-        let methods = csearch::get_impl_methods(&tcx.sess.cstore, impl_def_id);
+        let methods = csearch::get_impl_methods(&tcx.sess, impl_def_id);
 
         // Record the trait->implementation mappings, if applicable.
         let associated_traits = csearch::get_impl_trait(tcx, impl_def_id);
@@ -4389,9 +4389,9 @@ pub fn populate_implementations_for_trait_if_necessary(
         return
     }
 
-    csearch::each_implementation_for_trait(&tcx.sess.cstore, trait_id,
+    csearch::each_implementation_for_trait(&tcx.sess, trait_id,
             |implementation_def_id| {
-        let methods = csearch::get_impl_methods(&tcx.sess.cstore, implementation_def_id);
+        let methods = csearch::get_impl_methods(&tcx.sess, implementation_def_id);
 
         // Record the trait->implementation mapping.
         record_trait_implementation(tcx, trait_id, implementation_def_id);
@@ -4438,7 +4438,7 @@ pub fn trait_id_of_impl(tcx: &ctxt,
 pub fn trait_of_method(tcx: &ctxt, def_id: ast::DefId)
                        -> Option<ast::DefId> {
     if def_id.krate != LOCAL_CRATE {
-        return csearch::get_trait_of_method(&tcx.sess.cstore, def_id, tcx);
+        return csearch::get_trait_of_method(&tcx.sess, def_id, tcx);
     }
     match tcx.methods.borrow().find_copy(&def_id) {
         Some(method) => {
@@ -4503,7 +4503,13 @@ pub fn hash_crate_independent(tcx: &ctxt, t: t, svh: &Svh) -> u64 {
             tcx.sess.cstore.get_crate_hash(did.krate)
         };
         h.as_str().hash(state);
-        did.node.hash(state);
+        // Node ids shift with every edit, which would give the crate's types
+        // new ids in every incremental compilation.
+        if ast_util::is_local(did) && tcx.sess.opts.incremental.is_some() {
+            tcx.map.path_to_str(did.node).as_slice().hash(state);
+        } else {
+            did.node.hash(state);
+        }
     };
     let mt = |state: &mut sip::SipState, mt: mt| {
         mt.mutbl.hash(state);
@@ -128,8 +128,8 @@ pub fn opt_ast_region_to_region<AC:AstConv,RS:RegionScope>(
             match rscope.anon_regions(default_span, 1) {
                 Err(()) => {
                     debug!("optional region in illegal location");
-                    this.tcx().sess.span_err(
-                        default_span, "missing lifetime specifier");
+                    span_err!(this.tcx().sess, default_span, E0046,
+                        "missing lifetime specifier");
                     ty::ReStatic
                 }
 
@@ -175,12 +175,11 @@ fn ast_path_substs<AC:AstConv,RS:RegionScope>(
             rscope.anon_regions(path.span, expected_num_region_params);
 
         if supplied_num_region_params != 0 || anon_regions.is_err() {
-            tcx.sess.span_err(
-                path.span,
-                format!("wrong number of lifetime parameters: \
-                        expected {} but found {}",
-                        expected_num_region_params,
-                        supplied_num_region_params));
+            span_err!(tcx.sess, path.span, E0047,
+                "wrong number of lifetime parameters: \
+                expected {} but found {}",
+                expected_num_region_params,
+                supplied_num_region_params);
         }
 
         match anon_regions {
@@ -218,8 +217,8 @@ fn ast_path_substs<AC:AstConv,RS:RegionScope>(
 
     if supplied_ty_param_count > required_ty_param_count
         && !this.tcx().sess.features.default_type_params.get() {
-        this.tcx().sess.span_err(path.span, "default type parameters are \
-                                             experimental and possibly buggy");
+        span_err!(this.tcx().sess, path.span, E0048, "default type parameters are \
+                                                      experimental and possibly buggy");
         this.tcx().sess.span_note(path.span, "add #![feature(default_type_params)] \
                                               to the crate attributes to enable");
     }
@@ -298,16 +297,14 @@ fn check_path_args(tcx: &ty::ctxt,
                    flags: uint) {
     if (flags & NO_TPS) != 0u {
         if !path.segments.iter().all(|s| s.types.is_empty()) {
-            tcx.sess.span_err(
-                path.span,
+            span_err!(tcx.sess, path.span, E0049,
                 "type parameters are not allowed on this type");
         }
     }
 
     if (flags & NO_REGIONS) != 0u {
         if !path.segments.last().unwrap().lifetimes.is_empty() {
-            tcx.sess.span_err(
-                path.span,
+            span_err!(tcx.sess, path.span, E0050,
                 "region parameters are not allowed on this type");
         }
     }
@@ -342,8 +339,9 @@ pub fn ast_ty_to_prim_ty(tcx: &ty::ctxt, ast_ty: &ast::Ty) -> Option<ty::t> {
                         }
                         ast::TyFloat(ft) => {
                             if ft == ast::TyF128 && !tcx.sess.features.quad_precision_float.get() {
-                                tcx.sess.span_err(path.span, "quadruple precision floats are \
-                                                              missing complete runtime support");
+                                span_err!(tcx.sess, path.span, E0051,
+                                          "quadruple precision floats are \
+                                           missing complete runtime support");
                                 tcx.sess.span_note(path.span, "add \
                                                                #[feature(quad_precision_float)] \
                                                                to the crate attributes to enable");
@@ -352,8 +350,8 @@ pub fn ast_ty_to_prim_ty(tcx: &ty::ctxt, ast_ty: &ast::Ty) -> Option<ty::t> {
                             Some(ty::mk_mach_float(ft))
                         }
                         ast::TyStr => {
-                            tcx.sess.span_err(ast_ty.span,
-                                              "bare `str` is not a type");
+                            span_err!(tcx.sess, ast_ty.span, E0052,
+                                      "bare `str` is not a type");
                             // return /something/ so they can at least get more errors
                             Some(ty::mk_uniq(tcx, ty::mk_str(tcx)))
                         }
@@ -396,10 +394,8 @@ pub fn ast_ty_to_builtin_ty<AC:AstConv,
                            .iter()
                            .flat_map(|s| s.types.iter())
                            .len() > 1 {
-                        this.tcx()
-                            .sess
-                            .span_err(path.span,
-                                      "`Box` has only one type parameter")
+                        span_err!(this.tcx().sess, path.span, E0053,
+                                  "`Box` has only one type parameter")
                     }
 
                     for inner_ast_type in path.segments
@@ -416,17 +412,13 @@ pub fn ast_ty_to_builtin_ty<AC:AstConv,
                                                |typ| {
                             match ty::get(typ).sty {
                                 ty::ty_str => {
-                                    this.tcx()
-                                        .sess
-                                        .span_err(path.span,
-                                                  "`Box<str>` is not a type");
+                                    span_err!(this.tcx().sess, path.span, E0054,
+                                              "`Box<str>` is not a type");
                                     ty::mk_err()
                                 }
                                 ty::ty_vec(_, None) => {
-                                    this.tcx()
-                                        .sess
-                                        .span_err(path.span,
-                                                  "`Box<[T]>` is not a type");
+                                    span_err!(this.tcx().sess, path.span, E0055,
+                                              "`Box<[T]>` is not a type");
                                     ty::mk_err()
                                 }
                                 _ => ty::mk_uniq(this.tcx(), typ),
@@ -492,8 +484,8 @@ fn mk_pointer<AC:AstConv,
                         RPtr(r) => {
                             return ty::mk_str_slice(tcx, r, ast::MutImmutable);
                         }
-                        _ => tcx.sess.span_err(path.span,
-                                               format!("managed strings are not supported")),
+                        _ => span_err!(tcx.sess, path.span, E0056,
+                                       "managed strings are not supported"),
                     }
                 }
                 Some(&ast::DefTrait(trait_def_id)) => {
@@ -505,8 +497,7 @@ fn mk_pointer<AC:AstConv,
                             ty::RegionTraitStore(r, a_seq_ty.mutbl)
                         }
                         _ => {
-                            tcx.sess.span_err(
-                                path.span,
+                            span_err!(tcx.sess, path.span, E0057,
                                 "~trait or &trait are the only supported \
                                  forms of casting-to-trait");
                             return ty::mk_err();
@@ -563,7 +554,7 @@ pub fn ast_ty_to_ty<AC:AstConv, RS:RegionScope>(
                            |ty| ty::mk_uniq(tcx, ty))
             }
             ast::TyVec(ty) => {
-                tcx.sess.span_err(ast_ty.span, "bare `[]` is not a type");
+                span_err!(tcx.sess, ast_ty.span, E0058, "bare `[]` is not a type");
                 // return /something/ so they can at least get more errors
                 let vec_ty = ty::mk_vec(tcx, ast_ty_to_mt(this, rscope, ty), None);
                 ty::mk_uniq(tcx, vec_ty)
@@ -588,8 +579,8 @@ pub fn ast_ty_to_ty<AC:AstConv, RS:RegionScope>(
             }
             ast::TyBareFn(ref bf) => {
                 if bf.decl.variadic && bf.abi != abi::C {
-                    tcx.sess.span_err(ast_ty.span,
-                                      "variadic function must have C calling convention");
+                    span_err!(tcx.sess, ast_ty.span, E0059,
+                              "variadic function must have C calling convention");
                 }
                 ty::mk_bare_fn(tcx, ty_of_bare_fn(this, ast_ty.id, bf.fn_style,
                                                   bf.abi, bf.decl))
@@ -643,17 +634,16 @@ pub fn ast_ty_to_ty<AC:AstConv, RS:RegionScope>(
                     // But don't emit the error if the user meant to do a trait anyway.
                     ast::DefTrait(..) => { },
                     _ if bounds.is_some() =>
-                        tcx.sess.span_err(ast_ty.span,
-                                          "kind bounds can only be used on trait types"),
+                        span_err!(tcx.sess, ast_ty.span, E0060,
+                                  "kind bounds can only be used on trait types"),
                     _ => { },
                 }
                 match a_def {
                     ast::DefTrait(_) => {
                         let path_str = path_to_str(path);
-                        tcx.sess.span_err(
-                            ast_ty.span,
-                            format!("reference to trait `{name}` where a type is expected; \
-                                    try `~{name}` or `&{name}`", name=path_str));
+                        span_err!(tcx.sess, ast_ty.span, E0061,
+                            "reference to trait `{name}` where a type is expected; \
+                            try `~{name}` or `&{name}`", name=path_str);
                         ty::mk_err()
                     }
                     ast::DefTy(did) | ast::DefStruct(did) => {
@@ -904,10 +894,9 @@ fn conv_builtin_bounds(tcx: &ty::ctxt, ast_bounds: &Option<OwnedSlice<ast::TyPar
                     }
                     ast::OtherRegionTyParamBound(span) => {
                         if !tcx.sess.features.issue_5723_bootstrap.get() {
-                            tcx.sess.span_err(
-                                span,
-                                format!("only the 'static lifetime is \
-                                         accepted here."));
+                            span_err!(tcx.sess, span, E0062,
+                                "only the 'static lifetime is \
+                                 accepted here.");
                         }
                     }
                 }
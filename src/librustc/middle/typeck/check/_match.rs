@@ -18,6 +18,7 @@ use middle::typeck::check::{instantiate_path, lookup_def};
 use middle::typeck::check::{structure_of, valid_range_bounds};
 use middle::typeck::infer;
 use middle::typeck::require_same_types;
+use util::common::find_best_match_for_name;
 
 use collections::{HashMap, HashSet};
 use syntax::ast;
@@ -253,7 +254,7 @@ pub fn check_pat_variant(pcx: &pat_ctxt, pat: &ast::Pat, path: &ast::Path,
                          npat = subpats_len,
                          kind = kind_name,
                          narg = arg_len);
-            tcx.sess.span_err(pat.span, s);
+            span_err!(tcx.sess, pat.span, E0063, "{}", s);
             error_happened = true;
         }
 
@@ -265,12 +266,12 @@ pub fn check_pat_variant(pcx: &pat_ctxt, pat: &ast::Pat, path: &ast::Path,
             }
         }
     } else if subpats_len > 0 {
-        tcx.sess.span_err(pat.span,
-                          format!("this pattern has \
-                                   {npat, plural, =1{# field} other{# fields}}, \
-                                   but the corresponding {kind} has no fields",
-                               npat = subpats_len,
-                               kind = kind_name));
+        span_err!(tcx.sess, pat.span, E0064,
+                  "this pattern has \
+                   {npat, plural, =1{# field} other{# fields}}, \
+                   but the corresponding {kind} has no fields",
+                  npat = subpats_len,
+                  kind = kind_name);
         error_happened = true;
     }
 
@@ -312,9 +313,9 @@ pub fn check_struct_pat_fields(pcx: &pat_ctxt,
     for field in fields.iter() {
         match field_map.find_mut(&field.ident.name) {
             Some(&(_, true)) => {
-                tcx.sess.span_err(span,
-                    format!("field `{}` bound twice in pattern",
-                            token::get_ident(field.ident)));
+                span_err!(tcx.sess, span, E0065,
+                    "field `{}` bound twice in pattern",
+                    token::get_ident(field.ident));
             }
             Some(&(index, ref mut used)) => {
                 *used = true;
@@ -331,10 +332,15 @@ pub fn check_struct_pat_fields(pcx: &pat_ctxt,
                 // Check the pattern anyway, so that attempts to look
                 // up its type won't fail
                 check_pat(pcx, field.pat, ty::mk_err());
-                tcx.sess.span_err(span,
-                    format!("struct `{}` does not have a field named `{}`",
-                         name,
-                         token::get_ident(field.ident)));
+                span_err!(tcx.sess, span, E0066,
+                    "struct `{}` does not have a field named `{}`",
+                    name,
+                    token::get_ident(field.ident));
+                let field_names = class_fields.iter().map(|f| token::get_name(f.name));
+                match find_best_match_for_name(field_names, token::get_ident(field.ident).get()) {
+                    Some(found) => tcx.sess.span_note(span, format!("did you mean `{}`?", found)),
+                    None => {}
+                }
             }
         }
     }
@@ -346,9 +352,9 @@ pub fn check_struct_pat_fields(pcx: &pat_ctxt,
                 continue;
             }
 
-            tcx.sess.span_err(span,
-                              format!("pattern does not mention field `{}`",
-                                  token::get_name(field.name)));
+            span_err!(tcx.sess, span, E0067,
+                      "pattern does not mention field `{}`",
+                      token::get_name(field.name));
         }
     }
 }
@@ -371,10 +377,10 @@ pub fn check_struct_pat(pcx: &pat_ctxt, pat_id: ast::NodeId, span: Span,
         }
         Some(&ast::DefStruct(..)) | Some(&ast::DefVariant(..)) => {
             let name = pprust::path_to_str(path);
-            tcx.sess.span_err(span,
-                              format!("mismatched types: expected `{}` but found `{}`",
-                                   fcx.infcx().ty_to_str(expected),
-                                   name));
+            span_err!(tcx.sess, span, E0068,
+                      "mismatched types: expected `{}` but found `{}`",
+                      fcx.infcx().ty_to_str(expected),
+                      name);
         }
         _ => {
             tcx.sess.span_bug(span, "resolve didn't write in struct ID");
@@ -409,11 +415,11 @@ pub fn check_struct_like_enum_variant_pat(pcx: &pat_ctxt,
         }
         Some(&ast::DefStruct(..)) | Some(&ast::DefVariant(..)) => {
             let name = pprust::path_to_str(path);
-            tcx.sess.span_err(span,
-                              format!("mismatched types: expected `{}` but \
-                                    found `{}`",
-                                   fcx.infcx().ty_to_str(expected),
-                                   name));
+            span_err!(tcx.sess, span, E0069,
+                      "mismatched types: expected `{}` but \
+                      found `{}`",
+                      fcx.infcx().ty_to_str(expected),
+                      name);
         }
         _ => {
             tcx.sess.span_bug(span, "resolve didn't write in variant");
@@ -450,15 +456,15 @@ pub fn check_pat(pcx: &pat_ctxt, pat: &ast::Pat, expected: ty::t) {
         {
             // no-op
         } else if !ty::type_is_numeric(b_ty) && !ty::type_is_char(b_ty) {
-            tcx.sess.span_err(pat.span, "non-numeric type used in range");
+            span_err!(tcx.sess, pat.span, E0070, "non-numeric type used in range");
         } else {
             match valid_range_bounds(fcx.ccx, begin, end) {
                 Some(false) => {
-                    tcx.sess.span_err(begin.span,
+                    span_err!(tcx.sess, begin.span, E0071,
                         "lower range bound must be less than upper");
                 },
                 None => {
-                    tcx.sess.span_err(begin.span,
+                    span_err!(tcx.sess, begin.span, E0072,
                         "mismatched types in range");
                 },
                 _ => { },
@@ -207,6 +207,55 @@ pub fn lookup_in_trait<'a>(
     lcx.search(self_ty)
 }
 
+/// The names of the methods that `lookup` considers for a call on a
+/// receiver of type `self_ty` in `expr`, whether or not they apply to it:
+/// the methods of the inherent impls and objects that the receiver
+/// autoderefs to, and those of the traits in scope. Used to suggest a method
+/// when there is none with the name that was called.
+pub fn candidate_method_names(fcx: &FnCtxt,
+                              expr: &ast::Expr,
+                              self_ty: ty::t)
+                              -> Vec<ast::Name> {
+    fn push_inherent_impl_methods(tcx: &ty::ctxt, did: DefId, names: &mut Vec<ast::Name>) {
+        ty::populate_implementations_for_type_if_necessary(tcx, did);
+        let impl_methods = tcx.impl_methods.borrow();
+        for impl_infos in tcx.inherent_impls.borrow().find(&did).iter() {
+            for impl_did in impl_infos.borrow().iter() {
+                for &method_did in impl_methods.get(impl_did).iter() {
+                    names.push(ty::method(tcx, method_did).ident.name);
+                }
+            }
+        }
+    }
+
+    let tcx = fcx.tcx();
+    let mut names = Vec::new();
+    check::autoderef(fcx, expr.span, self_ty, None, PreferMutLvalue, |self_ty, _| {
+        match get(self_ty).sty {
+            ty_trait(box TyTrait { def_id, .. }) => {
+                for method in ty::trait_methods(tcx, def_id).iter() {
+                    names.push(method.ident.name);
+                }
+                push_inherent_impl_methods(tcx, def_id, &mut names);
+            }
+            ty_enum(did, _) | ty_struct(did, _) => {
+                push_inherent_impl_methods(tcx, did, &mut names);
+            }
+            _ => {}
+        }
+        None::<()>
+    });
+
+    for traits in fcx.ccx.trait_map.find(&expr.id).move_iter() {
+        for &trait_did in traits.iter() {
+            for method in ty::trait_methods(tcx, trait_did).iter() {
+                names.push(method.ident.name);
+            }
+        }
+    }
+    names
+}
+
 // Determine the index of a method in the list of all methods belonging
 // to a trait and its supertraits.
 fn get_method_index(tcx: &ty::ctxt,
@@ -1062,8 +1111,7 @@ impl<'a> LookupContext<'a> {
         }
 
         if relevant_candidates.len() > 1 {
-            self.tcx().sess.span_err(
-                self.span,
+            span_err!(self.tcx().sess, self.span, E0073,
                 "multiple applicable methods in scope");
             for (idx, candidate) in relevant_candidates.iter().enumerate() {
                 self.report_candidate(idx, &candidate.origin);
@@ -1150,13 +1198,11 @@ impl<'a> LookupContext<'a> {
             if num_supplied_tps == 0u {
                 self.fcx.infcx().next_ty_vars(num_method_tps)
             } else if num_method_tps == 0u {
-                tcx.sess.span_err(
-                    self.span,
+                span_err!(tcx.sess, self.span, E0074,
                     "this method does not take type parameters");
                 self.fcx.infcx().next_ty_vars(num_method_tps)
             } else if num_supplied_tps != num_method_tps {
-                tcx.sess.span_err(
-                    self.span,
+                span_err!(tcx.sess, self.span, E0075,
                     "incorrect number of type \
                      parameters given for this method");
                 self.fcx.infcx().next_ty_vars(num_method_tps)
@@ -1270,15 +1316,13 @@ impl<'a> LookupContext<'a> {
 
         match candidate.method_ty.explicit_self {
             ast::SelfStatic => { // reason (a) above
-                self.tcx().sess.span_err(
-                    self.span,
+                span_err!(self.tcx().sess, self.span, E0076,
                     "cannot call a method without a receiver \
                      through an object");
             }
 
             ast::SelfValue => { // reason (a) above
-                self.tcx().sess.span_err(
-                    self.span,
+                span_err!(self.tcx().sess, self.span, E0077,
                     "cannot call a method with a by-value receiver \
                      through an object");
             }
@@ -1289,8 +1333,7 @@ impl<'a> LookupContext<'a> {
         // reason (a) above
         let check_for_self_ty = |ty| {
             if ty::type_has_self(ty) {
-                self.tcx().sess.span_err(
-                    self.span,
+                span_err!(self.tcx().sess, self.span, E0078,
                     "cannot call a method whose type contains a \
                      self-type through an object");
                 true
@@ -1311,8 +1354,7 @@ impl<'a> LookupContext<'a> {
         }
 
         if candidate.method_ty.generics.has_type_params() { // reason (b) above
-            self.tcx().sess.span_err(
-                self.span,
+            span_err!(self.tcx().sess, self.span, E0079,
                 "cannot call a generic method through an object");
         }
     }
@@ -1334,8 +1376,8 @@ impl<'a> LookupContext<'a> {
         }
 
         if bad {
-            self.tcx().sess.span_err(self.span,
-                                     "explicit call to destructor");
+            span_err!(self.tcx().sess, self.span, E0080,
+                      "explicit call to destructor");
         }
     }
 
@@ -110,7 +110,7 @@ use middle::typeck::no_params;
 use middle::typeck::{require_same_types, vtable_map};
 use middle::typeck::{MethodCall, MethodMap};
 use middle::lang_items::TypeIdLangItem;
-use util::common::{block_query, indenter, loop_query};
+use util::common::{block_query, find_best_match_for_name, indenter, loop_query};
 use util::ppaux;
 use util::ppaux::{UserString, Repr};
 use util::nodemap::{FnvHashMap, NodeMap};
@@ -130,6 +130,7 @@ use syntax::attr;
 use syntax::codemap::Span;
 use syntax::codemap;
 use syntax::owned_slice::OwnedSlice;
+use syntax::parse::token::InternedString;
 use syntax::parse::token;
 use syntax::print::pprust;
 use syntax::visit;
@@ -568,9 +569,9 @@ fn check_for_field_shadowing(tcx: &ty::ctxt,
             for f in fields.iter() {
                 match super_fields.iter().find(|sf| f.name == sf.name) {
                     Some(prev_field) => {
-                        tcx.sess.span_err(span_for_field(tcx, f, id),
-                            format!("field `{}` hides field declared in super-struct",
-                                    token::get_name(f.name)));
+                        span_err!(tcx.sess, span_for_field(tcx, f, id), E0081,
+                            "field `{}` hides field declared in super-struct",
+                            token::get_name(f.name));
                         tcx.sess.span_note(span_for_field(tcx, prev_field, parent_id),
                             "previously declared here");
                     },
@@ -593,14 +594,14 @@ fn check_fields_sized(tcx: &ty::ctxt,
         if !ty::type_is_sized(tcx, t) {
             match f.node.kind {
                 ast::NamedField(ident, _) => {
-                    tcx.sess.span_err(f.span, format!("type `{}` is dynamically sized. \
-                                                       dynamically sized types may only \
-                                                       appear as the type of the final \
-                                                       field in a struct",
-                                                      token::get_ident(ident)));
+                    span_err!(tcx.sess, f.span, E0082, "type `{}` is dynamically sized. \
+                                                        dynamically sized types may only \
+                                                        appear as the type of the final \
+                                                        field in a struct",
+                                                       token::get_ident(ident));
                 }
                 ast::UnnamedField(_) => {
-                    tcx.sess.span_err(f.span, "dynamically sized type in field");
+                    span_err!(tcx.sess, f.span, E0083, "dynamically sized type in field");
                 }
             }
         }
@@ -722,14 +723,15 @@ pub fn check_item(ccx: &CrateCtxt, it: &ast::Item) {
             for item in m.items.iter() {
                 let tpt = ty::lookup_item_type(ccx.tcx, local_def(item.id));
                 if tpt.generics.has_type_params() {
-                    ccx.tcx.sess.span_err(item.span, "foreign items may not have type parameters");
+                    span_err!(ccx.tcx.sess, item.span, E0084,
+                              "foreign items may not have type parameters");
                 }
 
                 match item.node {
                     ast::ForeignItemFn(ref fn_decl, _) => {
                         if fn_decl.variadic && m.abi != abi::C {
-                            ccx.tcx.sess.span_err(
-                                item.span, "variadic function must have C calling convention");
+                            span_err!(ccx.tcx.sess, item.span, E0085,
+                                "variadic function must have C calling convention");
                         }
                     }
                     _ => {}
@@ -812,11 +814,10 @@ fn check_impl_methods_against_trait(ccx: &CrateCtxt,
                                     &impl_trait_ref.substs);
             }
             None => {
-                tcx.sess.span_err(
-                    impl_method.span,
-                    format!("method `{}` is not a member of trait `{}`",
-                            token::get_ident(impl_method_ty.ident),
-                            pprust::path_to_str(&ast_trait_ref.path)));
+                span_err!(tcx.sess, impl_method.span, E0086,
+                    "method `{}` is not a member of trait `{}`",
+                    token::get_ident(impl_method_ty.ident),
+                    pprust::path_to_str(&ast_trait_ref.path));
             }
         }
     }
@@ -839,10 +840,9 @@ fn check_impl_methods_against_trait(ccx: &CrateCtxt,
     }
 
     if !missing_methods.is_empty() {
-        tcx.sess.span_err(
-            impl_span,
-            format!("not all trait methods implemented, missing: {}",
-                    missing_methods.connect(", ")));
+        span_err!(tcx.sess, impl_span, E0087,
+            "not all trait methods implemented, missing: {}",
+            missing_methods.connect(", "));
     }
 }
 
@@ -881,21 +881,19 @@ fn compare_impl_method(tcx: &ty::ctxt,
     match (&trait_m.explicit_self, &impl_m.explicit_self) {
         (&ast::SelfStatic, &ast::SelfStatic) => {}
         (&ast::SelfStatic, _) => {
-            tcx.sess.span_err(
-                impl_m_span,
-                format!("method `{}` has a `{}` declaration in the impl, \
-                        but not in the trait",
-                        token::get_ident(trait_m.ident),
-                        pprust::explicit_self_to_str(impl_m.explicit_self)));
+            span_err!(tcx.sess, impl_m_span, E0088,
+                "method `{}` has a `{}` declaration in the impl, \
+                but not in the trait",
+                token::get_ident(trait_m.ident),
+                pprust::explicit_self_to_str(impl_m.explicit_self));
             return;
         }
         (_, &ast::SelfStatic) => {
-            tcx.sess.span_err(
-                impl_m_span,
-                format!("method `{}` has a `{}` declaration in the trait, \
-                        but not in the impl",
-                        token::get_ident(trait_m.ident),
-                        pprust::explicit_self_to_str(trait_m.explicit_self)));
+            span_err!(tcx.sess, impl_m_span, E0089,
+                "method `{}` has a `{}` declaration in the trait, \
+                but not in the impl",
+                token::get_ident(trait_m.ident),
+                pprust::explicit_self_to_str(trait_m.explicit_self));
             return;
         }
         _ => {
@@ -906,28 +904,26 @@ fn compare_impl_method(tcx: &ty::ctxt,
     let num_impl_m_type_params = impl_m.generics.type_param_defs().len();
     let num_trait_m_type_params = trait_m.generics.type_param_defs().len();
     if num_impl_m_type_params != num_trait_m_type_params {
-        tcx.sess.span_err(
-            impl_m_span,
-            format!("method `{method}` has {nimpl, plural, =1{# type parameter} \
-                                                        other{# type parameters}}, \
-                     but its trait declaration has {ntrait, plural, =1{# type parameter} \
-                                                                 other{# type parameters}}",
-                    method = token::get_ident(trait_m.ident),
-                    nimpl = num_impl_m_type_params,
-                    ntrait = num_trait_m_type_params));
+        span_err!(tcx.sess, impl_m_span, E0090,
+            "method `{method}` has {nimpl, plural, =1{# type parameter} \
+                                                other{# type parameters}}, \
+             but its trait declaration has {ntrait, plural, =1{# type parameter} \
+                                                         other{# type parameters}}",
+            method = token::get_ident(trait_m.ident),
+            nimpl = num_impl_m_type_params,
+            ntrait = num_trait_m_type_params);
         return;
     }
 
     if impl_m.fty.sig.inputs.len() != trait_m.fty.sig.inputs.len() {
-        tcx.sess.span_err(
-            impl_m_span,
-            format!("method `{method}` has {nimpl, plural, =1{# parameter} \
-                                                        other{# parameters}} \
-                     but the declaration in trait `{trait}` has {ntrait}",
-                 method = token::get_ident(trait_m.ident),
-                 nimpl = impl_m.fty.sig.inputs.len(),
-                 trait = ty::item_path_str(tcx, trait_m.def_id),
-                 ntrait = trait_m.fty.sig.inputs.len()));
+        span_err!(tcx.sess, impl_m_span, E0091,
+            "method `{method}` has {nimpl, plural, =1{# parameter} \
+                                                other{# parameters}} \
+             but the declaration in trait `{trait}` has {ntrait}",
+            method = token::get_ident(trait_m.ident),
+            nimpl = impl_m.fty.sig.inputs.len(),
+            trait = ty::item_path_str(tcx, trait_m.def_id),
+            ntrait = trait_m.fty.sig.inputs.len());
         return;
     }
 
@@ -941,16 +937,15 @@ fn compare_impl_method(tcx: &ty::ctxt,
             impl_param_def.bounds.builtin_bounds -
             trait_param_def.bounds.builtin_bounds;
         if !extra_bounds.is_empty() {
-           tcx.sess.span_err(
-               impl_m_span,
-               format!("in method `{}`, \
-                       type parameter {} requires `{}`, \
-                       which is not required by \
-                       the corresponding type parameter \
-                       in the trait declaration",
-                       token::get_ident(trait_m.ident),
-                       i,
-                       extra_bounds.user_string(tcx)));
+           span_err!(tcx.sess, impl_m_span, E0092,
+               "in method `{}`, \
+               type parameter {} requires `{}`, \
+               which is not required by \
+               the corresponding type parameter \
+               in the trait declaration",
+               token::get_ident(trait_m.ident),
+               i,
+               extra_bounds.user_string(tcx));
            return;
         }
 
@@ -960,18 +955,17 @@ fn compare_impl_method(tcx: &ty::ctxt,
         if impl_param_def.bounds.trait_bounds.len() !=
             trait_param_def.bounds.trait_bounds.len()
         {
-            tcx.sess.span_err(
-                impl_m_span,
-                format!("in method `{method}`, \
-                        type parameter {typaram} has \
-                        {nimpl, plural, =1{# trait bound} other{# trait bounds}}, \
-                        but the corresponding type parameter in \
-                        the trait declaration has \
-                        {ntrait, plural, =1{# trait bound} other{# trait bounds}}",
-                        method = token::get_ident(trait_m.ident),
-                        typaram = i,
-                        nimpl = impl_param_def.bounds.trait_bounds.len(),
-                        ntrait = trait_param_def.bounds.trait_bounds.len()));
+            span_err!(tcx.sess, impl_m_span, E0093,
+                "in method `{method}`, \
+                type parameter {typaram} has \
+                {nimpl, plural, =1{# trait bound} other{# trait bounds}}, \
+                but the corresponding type parameter in \
+                the trait declaration has \
+                {ntrait, plural, =1{# trait bound} other{# trait bounds}}",
+                method = token::get_ident(trait_m.ident),
+                typaram = i,
+                nimpl = impl_param_def.bounds.trait_bounds.len(),
+                ntrait = trait_param_def.bounds.trait_bounds.len());
             return;
         }
     }
@@ -1036,11 +1030,10 @@ fn compare_impl_method(tcx: &ty::ctxt,
                           impl_fty, trait_fty) {
         Ok(()) => {}
         Err(ref terr) => {
-            tcx.sess.span_err(
-                impl_m_span,
-                format!("method `{}` has an incompatible type for trait: {}",
-                        token::get_ident(trait_m.ident),
-                        ty::type_err_to_str(tcx, terr)));
+            span_err!(tcx.sess, impl_m_span, E0094,
+                "method `{}` has an incompatible type for trait: {}",
+                token::get_ident(trait_m.ident),
+                ty::type_err_to_str(tcx, terr));
             ty::note_and_explain_type_err(tcx, terr);
         }
     }
@@ -1348,9 +1341,9 @@ pub fn autoderef<T>(fcx: &FnCtxt, sp: Span, base_ty: ty::t,
     }
 
     // We've reached the recursion limit, error gracefully.
-    fcx.tcx().sess.span_err(sp,
-        format!("reached the recursion limit while auto-dereferencing {}",
-                base_ty.repr(fcx.tcx())));
+    span_err!(fcx.tcx().sess, sp, E0095,
+        "reached the recursion limit while auto-dereferencing {}",
+        base_ty.repr(fcx.tcx()));
     (ty::mk_err(), 0, None)
 }
 
@@ -1552,19 +1545,15 @@ fn check_type_parameter_positions_in_path(function_context: &FnCtxt,
     // except the final two elements of the path.
     for i in range(0, path.segments.len() - 2) {
         for lifetime in path.segments.get(i).lifetimes.iter() {
-            function_context.tcx()
-                .sess
-                .span_err(lifetime.span,
-                          "lifetime parameters may not \
-                          appear here");
+            span_err!(function_context.tcx().sess, lifetime.span, E0096,
+                      "lifetime parameters may not \
+                      appear here");
             break;
         }
 
         for typ in path.segments.get(i).types.iter() {
-            function_context.tcx()
-                            .sess
-                            .span_err(typ.span,
-                                      "type parameters may not appear here");
+            span_err!(function_context.tcx().sess, typ.span, E0097,
+                      "type parameters may not appear here");
             break;
         }
     }
@@ -1599,15 +1588,13 @@ fn check_type_parameter_positions_in_path(function_context: &FnCtxt,
             let supplied_region_parameter_count = trait_segment.lifetimes.len();
             if trait_region_parameter_count != supplied_region_parameter_count
                 && supplied_region_parameter_count != 0 {
-                function_context.tcx()
-                    .sess
-                    .span_err(path.span,
-                              format!("expected {nexpected, plural, =1{# lifetime parameter} \
-                                                                 other{# lifetime parameters}}, \
-                                       found {nsupplied, plural, =1{# lifetime parameter} \
-                                                              other{# lifetime parameters}}",
-                                      nexpected = trait_region_parameter_count,
-                                      nsupplied = supplied_region_parameter_count));
+                span_err!(function_context.tcx().sess, path.span, E0098,
+                          "expected {nexpected, plural, =1{# lifetime parameter} \
+                                                     other{# lifetime parameters}}, \
+                           found {nsupplied, plural, =1{# lifetime parameter} \
+                                                  other{# lifetime parameters}}",
+                          nexpected = trait_region_parameter_count,
+                          nsupplied = supplied_region_parameter_count);
             }
 
             // Make sure the number of type parameters supplied on the trait
@@ -1638,7 +1625,7 @@ fn check_type_parameter_positions_in_path(function_context: &FnCtxt,
                             nexpected = required_ty_param_count,
                             nsupplied = supplied_ty_param_count)
                 };
-                function_context.tcx().sess.span_err(path.span, msg)
+                span_err!(function_context.tcx().sess, path.span, E0099, "{}", msg)
             } else if supplied_ty_param_count > formal_ty_param_count {
                 let msg = if required_ty_param_count < generics.type_param_defs().len() {
                     format!("the {trait_or_impl} referenced by this path needs at most \
@@ -1659,7 +1646,7 @@ fn check_type_parameter_positions_in_path(function_context: &FnCtxt,
                             nexpected = formal_ty_param_count,
                             nsupplied = supplied_ty_param_count)
                 };
-                function_context.tcx().sess.span_err(path.span, msg)
+                span_err!(function_context.tcx().sess, path.span, E0100, "{}", msg)
             }
         }
         _ => {
@@ -1667,25 +1654,38 @@ fn check_type_parameter_positions_in_path(function_context: &FnCtxt,
             // the penultimate segment of the path.
             let segment = &path.segments.get(path.segments.len() - 2);
             for lifetime in segment.lifetimes.iter() {
-                function_context.tcx()
-                    .sess
-                    .span_err(lifetime.span,
-                              "lifetime parameters may not
+                span_err!(function_context.tcx().sess, lifetime.span, E0101,
+                          "lifetime parameters may not
                               appear here");
                 break;
             }
             for typ in segment.types.iter() {
-                function_context.tcx()
-                                .sess
-                                .span_err(typ.span,
-                                          "type parameters may not appear \
-                                           here");
+                span_err!(function_context.tcx().sess, typ.span, E0102,
+                          "type parameters may not appear \
+                           here");
                 break;
             }
         }
     }
 }
 
+// Follows an error about there being no field or method `name` on `ty` with
+// a note suggesting the candidate that is spelled most like it, if any.
+fn note_similar_name<T: Iterator<InternedString>>(fcx: &FnCtxt,
+                                                  sp: Span,
+                                                  ty: ty::t,
+                                                  name: ast::Name,
+                                                  candidates: T) {
+    // No error was reported about an erroneous type
+    if ty::type_is_error(fcx.infcx().resolve_type_vars_if_possible(ty)) {
+        return;
+    }
+    match find_best_match_for_name(candidates, token::get_name(name).get()) {
+        Some(found) => fcx.tcx().sess.span_note(sp, format!("did you mean `{}`?", found)),
+        None => {}
+    }
+}
+
 /// Invariant:
 /// If an expression has any sub-expressions that result in a type error,
 /// inspecting that expression's type with `ty::type_is_error` will return
@@ -1768,7 +1768,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                      nexpected = expected_arg_count,
                      nsupplied = supplied_arg_count);
 
-                tcx.sess.span_err(sp, msg);
+                span_err!(tcx.sess, sp, E0103, "{}", msg);
 
                 err_args(supplied_arg_count)
             }
@@ -1781,7 +1781,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                  nexpected = expected_arg_count,
                  nsupplied = supplied_arg_count);
 
-            tcx.sess.span_err(sp, msg);
+            span_err!(tcx.sess, sp, E0104, "{}", msg);
 
             err_args(supplied_arg_count)
         };
@@ -1979,6 +1979,9 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                   },
                   expr_t,
                   None);
+                let method_names = method::candidate_method_names(fcx, expr, expr_t);
+                note_similar_name(fcx, method_name.span, expr_t, method_name.node.name,
+                                  method_names.iter().map(|&name| token::get_name(name)));
 
                 // Add error type for the result
                 fcx.write_error(expr.id);
@@ -2353,6 +2356,9 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
         check_expr_with_lvalue_pref(fcx, base, lvalue_pref);
         let expr_t = structurally_resolved_type(fcx, expr.span,
                                                 fcx.expr_ty(base));
+        // The fields of every struct on the way, to suggest one if there's
+        // no field with this name
+        let mut field_names = Vec::new();
         // FIXME(eddyb) #12808 Integrate privacy into this auto-deref loop.
         let (_, autoderefs, field_ty) =
             autoderef(fcx, expr.span, expr_t, Some(base.id), lvalue_pref, |base_t, _| {
@@ -2360,6 +2366,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                     ty::ty_struct(base_id, ref substs) => {
                         debug!("struct named {}", ppaux::ty_to_str(tcx, base_t));
                         let fields = ty::lookup_struct_fields(tcx, base_id);
+                        field_names.extend(fields.iter().map(|f| f.name));
                         lookup_field_ty(tcx, base_id, fields.as_slice(), field, &(*substs))
                     }
                     _ => None
@@ -2407,6 +2414,8 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                                 token::get_name(field), actual)
                     },
                     expr_t, None);
+                note_similar_name(fcx, expr.span, expr_t, field,
+                                  field_names.iter().map(|&name| token::get_name(name)));
             }
         }
 
@@ -2445,13 +2454,14 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                           format!("structure `{}` has no field named `{}`",
                                   actual, token::get_ident(field.ident.node))
                     }, struct_ty, None);
+                    note_similar_name(fcx, field.ident.span, struct_ty, field.ident.node.name,
+                                      field_types.iter().map(|f| token::get_name(f.name)));
                     error_happened = true;
                 }
                 Some((_, true)) => {
-                    tcx.sess.span_err(
-                        field.ident.span,
-                        format!("field `{}` specified more than once",
-                            token::get_ident(field.ident.node)));
+                    span_err!(tcx.sess, field.ident.span, E0105,
+                        "field `{}` specified more than once",
+                        token::get_ident(field.ident.node));
                     error_happened = true;
                 }
                 Some((field_id, false)) => {
@@ -2488,10 +2498,10 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                     }
                 }
 
-                tcx.sess.span_err(span,
-                    format!("missing {nfields, plural, =1{field} other{fields}}: {fields}",
-                            nfields = missing_fields.len(),
-                            fields = missing_fields.connect(", ")));
+                span_err!(tcx.sess, span, E0106,
+                    "missing {nfields, plural, =1{field} other{fields}}: {fields}",
+                    nfields = missing_fields.len(),
+                    fields = missing_fields.connect(", "));
              }
         }
 
@@ -2660,7 +2670,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                     } else {
                         "`~\"string\"` has been removed; use `\"string\".to_owned()` instead"
                     };
-                    tcx.sess.span_err(expr.span, error);
+                    span_err!(tcx.sess, expr.span, E0107, "{}", error);
                     ty::mk_err()
                 }
                 _ => tcx.sess.span_bug(expr.span, "vstore modifier on non-sequence"),
@@ -2701,7 +2711,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                                            .require(GcLangItem) {
                                       Ok(id) => id,
                                       Err(msg) => {
-                                          tcx.sess.span_err(expr.span, msg);
+                                          span_err!(tcx.sess, expr.span, E0108, "{}", msg);
                                           ast::DefId {
                                               krate: ast::CRATE_NODE_ID,
                                               node: ast::DUMMY_NODE_ID,
@@ -2731,9 +2741,9 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
           }
 
           if !checked {
-              tcx.sess.span_err(expr.span,
-                                "only the managed heap and exchange heap are \
-                                 currently supported")
+              span_err!(tcx.sess, expr.span, E0109,
+                        "only the managed heap and exchange heap are \
+                         currently supported")
           }
       }
 
@@ -2764,7 +2774,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
 
         let tcx = fcx.tcx();
         if !ty::expr_is_lval(tcx, lhs) {
-            tcx.sess.span_err(lhs.span, "illegal left-hand side expression");
+            span_err!(tcx.sess, lhs.span, E0110, "illegal left-hand side expression");
         }
 
         // Overwrite result of check_binop...this preserves existing behavior
@@ -2820,7 +2830,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                                 };
                                 if is_newtype {
                                     // This is an obsolete struct deref
-                                    tcx.sess.span_err(expr.span,
+                                    span_err!(tcx.sess, expr.span, E0111,
                                         "single-field tuple-structs can \
                                          no longer be dereferenced");
                                 } else {
@@ -2921,8 +2931,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                                     ret_ty, ty::mk_nil()) {
             Ok(_) => { /* fall through */ }
             Err(_) => {
-                tcx.sess.span_err(
-                    expr.span,
+                span_err!(tcx.sess, expr.span, E0112,
                     "`return;` in function returning non-nil");
             }
           },
@@ -2941,7 +2950,7 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
 
         let tcx = fcx.tcx();
         if !ty::expr_is_lval(tcx, lhs) {
-            tcx.sess.span_err(lhs.span, "illegal left-hand side expression");
+            span_err!(tcx.sess, lhs.span, E0113, "illegal left-hand side expression");
         }
 
         let lhs_ty = fcx.expr_ty(lhs);
@@ -3096,8 +3105,8 @@ fn check_expr_with_unifier(fcx: &FnCtxt,
                             }, t_e, None);
                         }
                     } else if ty::get(t1).sty == ty::ty_bool {
-                        fcx.tcx().sess.span_err(expr.span,
-                                                "cannot cast as `bool`, compare with zero instead");
+                        span_err!(fcx.tcx().sess, expr.span, E0114,
+                                  "cannot cast as `bool`, compare with zero instead");
                     } else if type_is_region_ptr(fcx, expr.span, t_e) &&
                         type_is_unsafe_ptr(fcx, expr.span, t_1) {
 
@@ -3520,10 +3529,10 @@ pub fn check_representable(tcx: &ty::ctxt,
     // caught by case 1.
     match ty::is_type_representable(tcx, sp, rty) {
       ty::SelfRecursive => {
-        tcx.sess.span_err(
-          sp, format!("illegal recursive {} type; \
-                       wrap the inner value in a box to make it representable",
-                      designation));
+        span_err!(tcx.sess, sp, E0115,
+          "illegal recursive {} type; \
+           wrap the inner value in a box to make it representable",
+          designation);
         return false
       }
       ty::Representable | ty::ContainsRecursive => (),
@@ -3548,10 +3557,10 @@ pub fn check_instantiable(tcx: &ty::ctxt,
                           -> bool {
     let item_ty = ty::node_id_to_type(tcx, item_id);
     if !ty::is_instantiable(tcx, item_ty) {
-        tcx.sess.span_err(sp, format!("this type cannot be instantiated \
-                  without an instance of itself; \
-                  consider using `Option<{}>`",
-                                   ppaux::ty_to_str(tcx, item_ty)));
+        span_err!(tcx.sess, sp, E0116, "this type cannot be instantiated \
+                                       without an instance of itself; \
+                                       consider using `Option<{}>`",
+                                       ppaux::ty_to_str(tcx, item_ty));
         false
     } else {
         true
@@ -3561,25 +3570,25 @@ pub fn check_instantiable(tcx: &ty::ctxt,
 pub fn check_simd(tcx: &ty::ctxt, sp: Span, id: ast::NodeId) {
     let t = ty::node_id_to_type(tcx, id);
     if ty::type_needs_subst(t) {
-        tcx.sess.span_err(sp, "SIMD vector cannot be generic");
+        span_err!(tcx.sess, sp, E0117, "SIMD vector cannot be generic");
         return;
     }
     match ty::get(t).sty {
         ty::ty_struct(did, ref substs) => {
             let fields = ty::lookup_struct_fields(tcx, did);
             if fields.is_empty() {
-                tcx.sess.span_err(sp, "SIMD vector cannot be empty");
+                span_err!(tcx.sess, sp, E0118, "SIMD vector cannot be empty");
                 return;
             }
             let e = ty::lookup_field_type(tcx, did, fields.get(0).id, substs);
             if !fields.iter().all(
                          |f| ty::lookup_field_type(tcx, did, f.id, substs) == e) {
-                tcx.sess.span_err(sp, "SIMD vector should be homogeneous");
+                span_err!(tcx.sess, sp, E0119, "SIMD vector should be homogeneous");
                 return;
             }
             if !ty::type_is_machine(e) {
-                tcx.sess.span_err(sp, "SIMD vector element type should be \
-                                       machine type");
+                span_err!(tcx.sess, sp, E0120, "SIMD vector element type should be \
+                                                machine type");
                 return;
             }
         }
@@ -3604,11 +3613,11 @@ pub fn check_enum_variants_sized(ccx: &CrateCtxt,
                     // A struct value with an unsized final field is itself
                     // unsized and we must track this in the type system.
                     if !ty::type_is_sized(ccx.tcx, *t) {
-                        ccx.tcx.sess.span_err(args.get(i).ty.span,
-                                              format!("type `{}` is dynamically sized. \
-                                                       dynamically sized types may only \
-                                                       appear as the final type in a variant",
-                                                      ppaux::ty_to_str(ccx.tcx, *t)));
+                        span_err!(ccx.tcx.sess, args.get(i).ty.span, E0121,
+                                  "type `{}` is dynamically sized. \
+                                   dynamically sized types may only \
+                                   appear as the final type in a variant",
+                                  ppaux::ty_to_str(ccx.tcx, *t));
                     }
                 }
             },
@@ -3686,10 +3695,12 @@ pub fn check_enum_variants(ccx: &CrateCtxt,
                         Ok(const_eval::const_int(val)) => current_disr_val = val as Disr,
                         Ok(const_eval::const_uint(val)) => current_disr_val = val as Disr,
                         Ok(_) => {
-                            ccx.tcx.sess.span_err(e.span, "expected signed integer constant");
+                            span_err!(ccx.tcx.sess, e.span, E0122,
+                                      "expected signed integer constant");
                         }
                         Err(ref err) => {
-                            ccx.tcx.sess.span_err(e.span, format!("expected constant: {}", *err));
+                            span_err!(ccx.tcx.sess, e.span, E0123,
+                                      "expected constant: {}", *err);
                         }
                     }
                 },
@@ -3698,15 +3709,15 @@ pub fn check_enum_variants(ccx: &CrateCtxt,
 
             // Check for duplicate discriminant values
             if disr_vals.contains(&current_disr_val) {
-                ccx.tcx.sess.span_err(v.span, "discriminant value already exists");
+                span_err!(ccx.tcx.sess, v.span, E0124, "discriminant value already exists");
             }
             // Check for unrepresentable discriminant values
             match hint {
                 attr::ReprAny | attr::ReprExtern => (),
                 attr::ReprInt(sp, ity) => {
                     if !disr_in_range(ccx, ity, current_disr_val) {
-                        ccx.tcx.sess.span_err(v.span,
-                                              "discriminant value outside specified type");
+                        span_err!(ccx.tcx.sess, v.span, E0125,
+                                  "discriminant value outside specified type");
                         ccx.tcx.sess.span_note(sp, "discriminant type specified here");
                     }
                 }
@@ -3730,7 +3741,7 @@ pub fn check_enum_variants(ccx: &CrateCtxt,
         } else {
             "unsupported representation for zero-variant enum"
         };
-        ccx.tcx.sess.span_err(sp, msg)
+        span_err!(ccx.tcx.sess, sp, E0126, "{}", msg)
     }
 
     let variants = do_check(ccx, vs, id, hint);
@@ -3833,14 +3844,13 @@ pub fn instantiate_path(fcx: &FnCtxt,
             |l| ast_region_to_region(fcx.tcx(), l)).collect())
     } else {
         if num_supplied_regions != 0 {
-            fcx.ccx.tcx.sess.span_err(
-                span,
-                format!("expected {nexpected, plural, =1{# lifetime parameter} \
-                                                   other{# lifetime parameters}}, \
-                         found {nsupplied, plural, =1{# lifetime parameter} \
-                                                other{# lifetime parameters}}",
-                        nexpected = num_expected_regions,
-                        nsupplied = num_supplied_regions));
+            span_err!(fcx.ccx.tcx.sess, span, E0127,
+                "expected {nexpected, plural, =1{# lifetime parameter} \
+                                           other{# lifetime parameters}}, \
+                 found {nsupplied, plural, =1{# lifetime parameter} \
+                                        other{# lifetime parameters}}",
+                nexpected = num_expected_regions,
+                nsupplied = num_supplied_regions);
         }
 
         fcx.infcx().region_vars_for_defs(span, tpt.generics.region_param_defs.as_slice())
@@ -3895,8 +3905,8 @@ pub fn instantiate_path(fcx: &FnCtxt,
     } else {
         if ty_substs_len > user_ty_param_req
             && !fcx.tcx().sess.features.default_type_params.get() {
-            fcx.tcx().sess.span_err(pth.span, "default type parameters are \
-                                               experimental and possibly buggy");
+            span_err!(fcx.tcx().sess, pth.span, E0128, "default type parameters are \
+                                                        experimental and possibly buggy");
             fcx.tcx().sess.span_note(pth.span, "add #![feature(default_type_params)] \
                                                 to the crate attributes to enable");
         }
@@ -4114,9 +4124,9 @@ pub fn check_bounds_are_used(ccx: &CrateCtxt,
 
     for (i, b) in tps_used.iter().enumerate() {
         if !*b {
-            ccx.tcx.sess.span_err(
-                span, format!("type parameter `{}` is unused",
-                              token::get_ident(tps.get(i).ident)));
+            span_err!(ccx.tcx.sess, span, E0129,
+                "type parameter `{}` is unused",
+                token::get_ident(tps.get(i).ident));
         }
     }
 }
@@ -4152,9 +4162,9 @@ pub fn check_intrinsic_type(ccx: &CrateCtxt, it: &ast::ForeignItem) {
                 (0, Vec::new(), ty::mk_nil())
             }
             op => {
-                tcx.sess.span_err(it.span,
-                                  format!("unrecognized atomic operation function: `{}`",
-                                       op));
+                span_err!(tcx.sess, it.span, E0130,
+                          "unrecognized atomic operation function: `{}`",
+                          op);
                 return;
             }
         }
@@ -4377,9 +4387,9 @@ pub fn check_intrinsic_type(ccx: &CrateCtxt, it: &ast::ForeignItem) {
                 ty::mk_tup(tcx, vec!(ty::mk_u64(), ty::mk_bool()))),
 
             ref other => {
-                tcx.sess.span_err(it.span,
-                                  format!("unrecognized intrinsic function: `{}`",
-                                       *other));
+                span_err!(tcx.sess, it.span, E0131,
+                          "unrecognized intrinsic function: `{}`",
+                          *other);
                 return;
             }
         }
@@ -4395,9 +4405,9 @@ pub fn check_intrinsic_type(ccx: &CrateCtxt, it: &ast::ForeignItem) {
     let i_ty = ty::lookup_item_type(ccx.tcx, local_def(it.id));
     let i_n_tps = i_ty.generics.type_param_defs().len();
     if i_n_tps != n_tps {
-        tcx.sess.span_err(it.span, format!("intrinsic has wrong number \
-                                         of type parameters: found {}, \
-                                         expected {}", i_n_tps, n_tps));
+        span_err!(tcx.sess, it.span, E0132, "intrinsic has wrong number \
+                                            of type parameters: found {}, \
+                                            expected {}", i_n_tps, n_tps);
     } else {
         require_same_types(
             tcx, None, false, it.span, i_ty.ty, fty,
@@ -203,11 +203,11 @@ fn relate_trait_refs(vcx: &VtableContext,
                 !ty::trait_ref_contains_error(&r_exp_trait_ref)
             {
                 let tcx = vcx.tcx();
-                tcx.sess.span_err(span,
-                    format!("expected {}, but found {} ({})",
-                         ppaux::trait_ref_to_str(tcx, &r_exp_trait_ref),
-                         ppaux::trait_ref_to_str(tcx, &r_act_trait_ref),
-                         ty::type_err_to_str(tcx, err)));
+                span_err!(tcx.sess, span, E0133,
+                    "expected {}, but found {} ({})",
+                    ppaux::trait_ref_to_str(tcx, &r_exp_trait_ref),
+                    ppaux::trait_ref_to_str(tcx, &r_act_trait_ref),
+                    ty::type_err_to_str(tcx, err));
             }
         }
     }
@@ -452,7 +452,7 @@ fn search_for_vtable(vcx: &VtableContext,
         1 => return Some(found.get(0).clone()),
         _ => {
             if !is_early {
-                vcx.tcx().sess.span_err(span, "multiple applicable methods in scope");
+                span_err!(vcx.tcx().sess, span, E0134, "multiple applicable methods in scope");
             }
             return Some(found.get(0).clone());
         }
@@ -549,8 +549,8 @@ pub fn early_resolve_expr(ex: &ast::Expr, fcx: &FnCtxt, is_early: bool) {
               match (&ty::get(ty).sty, store) {
                   (&ty::ty_rptr(_, mt), ty::RegionTraitStore(_, mutbl))
                     if !mutability_allowed(mt.mutbl, mutbl) => {
-                      fcx.tcx().sess.span_err(ex.span,
-                                              format!("types differ in mutability"));
+                      span_err!(fcx.tcx().sess, ex.span, E0135,
+                                "types differ in mutability");
                   }
 
                   (&ty::ty_uniq(..), ty::UniqTraitStore) |
@@ -604,19 +604,17 @@ pub fn early_resolve_expr(ex: &ast::Expr, fcx: &FnCtxt, is_early: bool) {
                   }
 
                   (_, ty::UniqTraitStore) => {
-                      fcx.ccx.tcx.sess.span_err(
-                          ex.span,
-                          format!("can only cast an boxed pointer \
-                                   to a boxed object, not a {}",
-                               ty::ty_sort_str(fcx.tcx(), ty)));
+                      span_err!(fcx.ccx.tcx.sess, ex.span, E0136,
+                          "can only cast an boxed pointer \
+                           to a boxed object, not a {}",
+                          ty::ty_sort_str(fcx.tcx(), ty));
                   }
 
                   (_, ty::RegionTraitStore(..)) => {
-                      fcx.ccx.tcx.sess.span_err(
-                          ex.span,
-                          format!("can only cast an &-pointer \
-                                to an &-object, not a {}",
-                               ty::ty_sort_str(fcx.tcx(), ty)));
+                      span_err!(fcx.ccx.tcx.sess, ex.span, E0137,
+                          "can only cast an &-pointer \
+                          to an &-object, not a {}",
+                          ty::ty_sort_str(fcx.tcx(), ty));
                   }
               }
           }
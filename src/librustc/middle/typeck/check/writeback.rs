@@ -227,8 +227,7 @@ impl<'cx> WritebackCx<'cx> {
                             Some(&ast::DefStruct(_)) => {
                             }
                             _ => {
-                                self.tcx().sess.span_err(
-                                    reason.span(self.fcx),
+                                span_err!(self.tcx().sess, reason.span(self.fcx), E0138,
                                     "cannot coerce non-statically resolved bare fn")
                             }
                         }
@@ -417,39 +416,35 @@ impl<'cx> Resolver<'cx> {
         if !self.tcx().sess.has_errors() {
             match self.reason {
                 ResolvingExpr(span) => {
-                    self.tcx().sess.span_err(
-                        span,
-                        format!("cannot determine a type for \
-                                 this expression: {}",
-                                infer::fixup_err_to_str(e)))
+                    span_err!(self.tcx().sess, span, E0139,
+                        "cannot determine a type for \
+                         this expression: {}",
+                        infer::fixup_err_to_str(e))
                 }
 
                 ResolvingLocal(span) => {
-                    self.tcx().sess.span_err(
-                        span,
-                        format!("cannot determine a type for \
-                                 this local variable: {}",
-                                infer::fixup_err_to_str(e)))
+                    span_err!(self.tcx().sess, span, E0140,
+                        "cannot determine a type for \
+                         this local variable: {}",
+                        infer::fixup_err_to_str(e))
                 }
 
                 ResolvingPattern(span) => {
-                    self.tcx().sess.span_err(
-                        span,
-                        format!("cannot determine a type for \
-                                 this pattern binding: {}",
-                                infer::fixup_err_to_str(e)))
+                    span_err!(self.tcx().sess, span, E0141,
+                        "cannot determine a type for \
+                         this pattern binding: {}",
+                        infer::fixup_err_to_str(e))
                 }
 
                 ResolvingUpvar(upvar_id) => {
                     let span = self.reason.span(self.fcx);
-                    self.tcx().sess.span_err(
-                        span,
-                        format!("cannot resolve lifetime for \
-                                 captured variable `{}`: {}",
-                                ty::local_var_name_str(
-                                    self.tcx(),
-                                    upvar_id.var_id).get().to_str(),
-                                infer::fixup_err_to_str(e)));
+                    span_err!(self.tcx().sess, span, E0142,
+                        "cannot resolve lifetime for \
+                         captured variable `{}`: {}",
+                        ty::local_var_name_str(
+                            self.tcx(),
+                            upvar_id.var_id).get().to_str(),
+                        infer::fixup_err_to_str(e));
                 }
             }
         }
@@ -187,10 +187,10 @@ impl<'a> visit::Visitor<()> for PrivilegedScopeVisitor<'a> {
                 if !self.cc.ast_type_is_defined_in_local_crate(ast_ty) {
                     // This is an error.
                     let session = &self.cc.crate_context.tcx.sess;
-                    session.span_err(item.span,
-                                     "cannot associate methods with a type outside the \
-                                     crate the type is defined in; define and implement \
-                                     a trait or new type instead");
+                    span_err!(session, item.span, E0143,
+                              "cannot associate methods with a type outside the \
+                              crate the type is defined in; define and implement \
+                              a trait or new type instead");
                 }
             }
             ItemImpl(_, Some(ref trait_ref), _, _) => {
@@ -208,9 +208,9 @@ impl<'a> visit::Visitor<()> for PrivilegedScopeVisitor<'a> {
 
                     if trait_def_id.krate != LOCAL_CRATE {
                         let session = &self.cc.crate_context.tcx.sess;
-                        session.span_err(item.span,
-                                "cannot provide an extension implementation \
-                                where both trait and type are not defined in this crate");
+                        span_err!(session, item.span, E0144,
+                                  "cannot provide an extension implementation \
+                                  where both trait and type are not defined in this crate");
                     }
                 }
 
@@ -266,9 +266,9 @@ impl<'a> CoherenceChecker<'a> {
                                        self_type.ty) {
                 None => {
                     let session = &self.crate_context.tcx.sess;
-                    session.span_err(item.span,
-                                     "no base type found for inherent implementation; \
-                                      implement a trait or new type instead");
+                    span_err!(session, item.span, E0145,
+                              "no base type found for inherent implementation; \
+                               implement a trait or new type instead");
                 }
                 Some(_) => {
                     // Nothing to do.
@@ -409,11 +409,10 @@ impl<'a> CoherenceChecker<'a> {
 
                     if self.polytypes_unify(polytype_a.clone(), polytype_b) {
                         let session = &self.crate_context.tcx.sess;
-                        session.span_err(
-                            self.span_of_impl(impl_a),
-                            format!("conflicting implementations for trait `{}`",
-                                 ty::item_path_str(self.crate_context.tcx,
-                                                   trait_def_id)));
+                        span_err!(session, self.span_of_impl(impl_a), E0146,
+                            "conflicting implementations for trait `{}`",
+                            ty::item_path_str(self.crate_context.tcx,
+                                              trait_def_id));
                         if impl_b.krate == LOCAL_CRATE {
                             session.span_note(self.span_of_impl(impl_b),
                                               "note conflicting implementation here");
@@ -436,8 +435,8 @@ impl<'a> CoherenceChecker<'a> {
             return;
         }
 
-        let crate_store = &self.crate_context.tcx.sess.cstore;
-        csearch::each_implementation_for_trait(crate_store, trait_def_id, |impl_def_id| {
+        let sess = &self.crate_context.tcx.sess;
+        csearch::each_implementation_for_trait(sess, trait_def_id, |impl_def_id| {
             // Is this actually necessary?
             let _ = lookup_item_type(self.crate_context.tcx, impl_def_id);
             f(impl_def_id);
@@ -600,7 +599,7 @@ impl<'a> CoherenceChecker<'a> {
                          impls_seen: &mut HashSet<DefId>,
                          impl_def_id: DefId) {
         let tcx = self.crate_context.tcx;
-        let methods = csearch::get_impl_methods(&tcx.sess.cstore, impl_def_id);
+        let methods = csearch::get_impl_methods(&tcx.sess, impl_def_id);
 
         // Make sure we don't visit the same implementation multiple times.
         if !impls_seen.insert(impl_def_id) {
@@ -636,9 +635,9 @@ impl<'a> CoherenceChecker<'a> {
     fn add_external_crates(&self) {
         let mut impls_seen = HashSet::new();
 
-        let crate_store = &self.crate_context.tcx.sess.cstore;
-        crate_store.iter_crate_data(|crate_number, _crate_metadata| {
-            each_impl(crate_store, crate_number, |def_id| {
+        let sess = &self.crate_context.tcx.sess;
+        sess.cstore.iter_crate_data(|crate_number, _crate_metadata| {
+            each_impl(sess, crate_number, |def_id| {
                 assert_eq!(crate_number, def_id.krate);
                 self.add_external_impl(&mut impls_seen, def_id)
             })
@@ -683,10 +682,10 @@ impl<'a> CoherenceChecker<'a> {
                         {
                             match tcx.map.find(impl_did.node) {
                                 Some(ast_map::NodeItem(item)) => {
-                                    tcx.sess.span_err((*item).span,
-                                                      "the Drop trait may \
-                                                       only be implemented \
-                                                       on structures");
+                                    span_err!(tcx.sess, (*item).span, E0147,
+                                              "the Drop trait may \
+                                               only be implemented \
+                                               on structures");
                                 }
                                 _ => {
                                     tcx.sess.bug("didn't find impl in ast \
@@ -133,8 +133,8 @@ impl<'a> AstConv for CrateCtxt<'a> {
     }
 
     fn ty_infer(&self, span: Span) -> ty::t {
-        self.tcx.sess.span_err(span, "the type placeholder `_` is not \
-                                      allowed within types on item signatures.");
+        span_err!(self.tcx.sess, span, E0148, "the type placeholder `_` is not \
+                                               allowed within types on item signatures.");
         ty::mk_err()
     }
 }
@@ -429,7 +429,7 @@ pub fn ensure_supertraits(ccx: &CrateCtxt,
             if ty_trait_refs.iter().any(|other_trait| other_trait.def_id == trait_ref.def_id) {
                 // This means a trait inherited from the same supertrait more
                 // than once.
-                tcx.sess.span_err(sp, "duplicate supertrait in trait declaration");
+                span_err!(tcx.sess, sp, E0149, "duplicate supertrait in trait declaration");
                 break;
             } else {
                 ty_trait_refs.push(trait_ref);
@@ -492,7 +492,7 @@ fn convert_methods(ccx: &CrateCtxt,
     let mut seen_methods = HashSet::new();
     for m in ms.iter() {
         if !seen_methods.insert(m.ident.repr(ccx.tcx)) {
-            tcx.sess.span_err(m.span, "duplicate method in trait impl");
+            span_err!(tcx.sess, m.span, E0150, "duplicate method in trait impl");
         }
 
         let num_rcvr_ty_params = rcvr_ty_generics.type_param_defs().len();
@@ -569,9 +569,8 @@ pub fn ensure_no_ty_param_bounds(ccx: &CrateCtxt,
                                  thing: &'static str) {
     for ty_param in generics.ty_params.iter() {
         if ty_param.bounds.len() > 0 {
-            ccx.tcx.sess.span_err(
-                span,
-                format!("trait bounds are not allowed in {} definitions", thing));
+            span_err!(ccx.tcx.sess, span, E0151,
+                "trait bounds are not allowed in {} definitions", thing);
         }
     }
 }
@@ -582,8 +581,8 @@ fn ensure_generics_abi(ccx: &CrateCtxt,
                        generics: &ast::Generics) {
     if generics.ty_params.len() > 0 &&
        !(abi == abi::Rust || abi == abi::RustIntrinsic) {
-        ccx.tcx.sess.span_err(span,
-                              "foreign functions may not use type parameters");
+        span_err!(ccx.tcx.sess, span, E0152,
+                  "foreign functions may not use type parameters");
     }
 }
 
@@ -709,8 +708,8 @@ pub fn convert_struct(ccx: &CrateCtxt,
         if result.name != special_idents::unnamed_field.name {
             let dup = match seen_fields.find(&result.name) {
                 Some(prev_span) => {
-                    tcx.sess.span_err(f.span,
-                        format!("field `{}` is already declared", token::get_name(result.name)));
+                    span_err!(tcx.sess, f.span, E0153,
+                        "field `{}` is already declared", token::get_name(result.name));
                     tcx.sess.span_note(*prev_span,
                         "previously declared here");
                     true
@@ -741,7 +740,7 @@ pub fn convert_struct(ccx: &CrateCtxt,
                             Some(ast_map::NodeItem(i)) => match i.node {
                                 ast::ItemStruct(struct_def, _) => {
                                     if !struct_def.is_virtual {
-                                        tcx.sess.span_err(t.span,
+                                        span_err!(tcx.sess, t.span, E0154,
                                             "struct inheritance is only \
                                              allowed from virtual structs");
                                     }
@@ -1047,9 +1046,9 @@ fn ty_generics(ccx: &CrateCtxt,
                     ty::walk_ty(ty, |t| {
                         match ty::get(t).sty {
                             ty::ty_param(p) => if p.idx > cur_idx {
-                                ccx.tcx.sess.span_err(path.span,
-                                                        "type parameters with a default cannot use \
-                                                        forward declared identifiers")
+                                span_err!(ccx.tcx.sess, path.span, E0155,
+                                          "type parameters with a default cannot use \
+                                          forward declared identifiers")
                             },
                             _ => {}
                         }
@@ -1109,10 +1108,9 @@ fn ty_generics(ccx: &CrateCtxt,
 
                 OtherRegionTyParamBound(span) => {
                     if !ccx.tcx.sess.features.issue_5723_bootstrap.get() {
-                        ccx.tcx.sess.span_err(
-                            span,
-                            format!("only the 'static lifetime is \
-                                     accepted here."));
+                        span_err!(ccx.tcx.sess, span, E0156,
+                            "only the 'static lifetime is \
+                             accepted here.");
                     }
                 }
             }
@@ -1145,11 +1143,11 @@ fn ty_generics(ccx: &CrateCtxt,
                                                  |trait_ref| {
                 let trait_def = ty::lookup_trait_def(tcx, trait_ref.def_id);
                 if trait_def.bounds.contains_elem(ty::BoundSized) {
-                    tcx.sess.span_err(span,
-                        format!("incompatible bounds on type parameter {}, \
-                                 bound {} does not allow unsized type",
+                    span_err!(tcx.sess, span, E0157,
+                        "incompatible bounds on type parameter {}, \
+                         bound {} does not allow unsized type",
                         token::get_ident(ident),
-                        ppaux::trait_ref_to_str(tcx, &*trait_ref)));
+                        ppaux::trait_ref_to_str(tcx, &*trait_ref));
                 }
                 true
             });
@@ -1168,8 +1166,8 @@ pub fn ty_of_foreign_fn_decl(ccx: &CrateCtxt,
         match (*i).pat.node {
             ast::PatIdent(_, _, _) => (),
             ast::PatWild => (),
-            _ => ccx.tcx.sess.span_err((*i).pat.span,
-                    "patterns aren't allowed in foreign function declarations")
+            _ => span_err!(ccx.tcx.sess, (*i).pat.span, E0158,
+                           "patterns aren't allowed in foreign function declarations")
         }
     }
 
@@ -350,12 +350,11 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
             infer::IfExpression(_) => "if and else have incompatible types",
         };
 
-        self.tcx.sess.span_err(
-            trace.origin.span(),
-            format!("{}: {} ({})",
-                 message_root_str,
-                 expected_found_str,
-                 ty::type_err_to_str(self.tcx, terr)));
+        span_err!(self.tcx.sess, trace.origin.span(), E0159,
+            "{}: {} ({})",
+            message_root_str,
+            expected_found_str,
+            ty::type_err_to_str(self.tcx, terr));
     }
 
     fn report_and_explain_type_error(&self,
@@ -410,8 +409,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                 self.report_and_explain_type_error(trace, &terr);
             }
             infer::Reborrow(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0160,
                     "lifetime of reference outlines \
                      lifetime of borrowed content...");
                 note_and_explain_region(
@@ -426,11 +424,10 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::ReborrowUpvar(span, ref upvar_id) => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("lifetime of borrowed pointer outlives \
-                            lifetime of captured variable `{}`...",
-                            ty::local_var_name_str(self.tcx, upvar_id.var_id).get().to_str()));
+                span_err!(self.tcx.sess, span, E0161,
+                    "lifetime of borrowed pointer outlives \
+                    lifetime of captured variable `{}`...",
+                    ty::local_var_name_str(self.tcx, upvar_id.var_id).get().to_str());
                 note_and_explain_region(
                     self.tcx,
                     "...the borrowed pointer is valid for ",
@@ -444,8 +441,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::InfStackClosure(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0162,
                     "closure outlives stack frame");
                 note_and_explain_region(
                     self.tcx,
@@ -459,8 +455,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::InvokeClosure(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0163,
                     "cannot invoke closure outside of its lifetime");
                 note_and_explain_region(
                     self.tcx,
@@ -469,8 +464,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::DerefPointer(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0164,
                     "dereference of reference outside its lifetime");
                 note_and_explain_region(
                     self.tcx,
@@ -479,11 +473,10 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::FreeVariable(span, id) => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("captured variable `{}` does not \
-                            outlive the enclosing closure",
-                            ty::local_var_name_str(self.tcx, id).get().to_str()));
+                span_err!(self.tcx.sess, span, E0165,
+                    "captured variable `{}` does not \
+                    outlive the enclosing closure",
+                    ty::local_var_name_str(self.tcx, id).get().to_str());
                 note_and_explain_region(
                     self.tcx,
                     "captured variable is valid for ",
@@ -496,9 +489,8 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::IndexSlice(span) => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("index of slice outside its lifetime"));
+                span_err!(self.tcx.sess, span, E0166,
+                    "index of slice outside its lifetime");
                 note_and_explain_region(
                     self.tcx,
                     "the slice is only valid for ",
@@ -506,8 +498,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::RelateObjectBound(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0167,
                     "lifetime of the source pointer does not outlive \
                      lifetime bound of the object type");
                 note_and_explain_region(
@@ -522,8 +513,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::CallRcvr(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0168,
                     "lifetime of method receiver does not outlive \
                      the method call");
                 note_and_explain_region(
@@ -533,8 +523,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::CallArg(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0169,
                     "lifetime of function argument does not outlive \
                      the function call");
                 note_and_explain_region(
@@ -544,8 +533,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::CallReturn(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0170,
                     "lifetime of return value does not outlive \
                      the function call");
                 note_and_explain_region(
@@ -555,8 +543,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::AddrOf(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0171,
                     "reference is not valid \
                      at the time of borrow");
                 note_and_explain_region(
@@ -566,8 +553,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::AutoBorrow(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0172,
                     "automatically reference is not valid \
                      at the time of borrow");
                 note_and_explain_region(
@@ -577,8 +563,7 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::BindingTypeIsNotValidAtDecl(span) => {
-                self.tcx.sess.span_err(
-                    span,
+                span_err!(self.tcx.sess, span, E0173,
                     "lifetime of variable does not enclose its declaration");
                 note_and_explain_region(
                     self.tcx,
@@ -587,11 +572,10 @@ impl<'a> ErrorReporting for InferCtxt<'a> {
                     "");
             }
             infer::ReferenceOutlivesReferent(ty, span) => {
-                self.tcx.sess.span_err(
-                    span,
-                    format!("in type `{}`, pointer has a longer lifetime than \
-                          the data it references",
-                         ty.user_string(self.tcx)));
+                span_err!(self.tcx.sess, span, E0174,
+                    "in type `{}`, pointer has a longer lifetime than \
+                    the data it references",
+                    ty.user_string(self.tcx));
                 note_and_explain_region(
                     self.tcx,
                     "the pointer is valid for ",
@@ -1243,11 +1227,10 @@ impl<'a> ErrorReportingHelpers for InferCtxt<'a> {
             }
         };
 
-        self.tcx.sess.span_err(
-            var_origin.span(),
-            format!("cannot infer an appropriate lifetime{} \
-                    due to conflicting requirements",
-                    var_description));
+        span_err!(self.tcx.sess, var_origin.span(), E0175,
+            "cannot infer an appropriate lifetime{} \
+            due to conflicting requirements",
+            var_description);
     }
 
     fn note_region_origin(&self, origin: SubregionOrigin) {
@@ -305,8 +305,8 @@ pub fn require_same_types(tcx: &ty::ctxt,
     match result {
         Ok(_) => true,
         Err(ref terr) => {
-            tcx.sess.span_err(span, msg() + ": " +
-                              ty::type_err_to_str(tcx, terr));
+            span_err!(tcx.sess, span, E0178, "{}: {}", msg(),
+                                             ty::type_err_to_str(tcx, terr));
             ty::note_and_explain_type_err(tcx, terr);
             false
         }
@@ -325,8 +325,7 @@ fn check_main_fn_ty(ccx: &CrateCtxt,
                     match it.node {
                         ast::ItemFn(_, _, _, ref ps, _)
                         if ps.is_parameterized() => {
-                            tcx.sess.span_err(
-                                main_span,
+                            span_err!(tcx.sess, main_span, E0179,
                                 "main function is not allowed to have type parameters");
                             return;
                         }
@@ -370,8 +369,7 @@ fn check_start_fn_ty(ccx: &CrateCtxt,
                     match it.node {
                         ast::ItemFn(_,_,_,ref ps,_)
                         if ps.is_parameterized() => {
-                            tcx.sess.span_err(
-                                start_span,
+                            span_err!(tcx.sess, start_span, E0180,
                                 "start function is not allowed to have type parameters");
                             return;
                         }
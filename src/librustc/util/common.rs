@@ -11,6 +11,7 @@
 #![allow(non_camel_case_types)]
 
 use syntax::ast;
+use syntax::parse::token::InternedString;
 use syntax::visit;
 use syntax::visit::Visitor;
 
@@ -111,3 +112,30 @@ pub fn block_query(b: ast::P<ast::Block>, p: |&ast::Expr| -> bool) -> bool {
     visit::walk_block(&mut v, b, ());
     return v.flag;
 }
+
+// Picks the candidate closest in spelling to `name`, for suggesting it in a
+// "did you mean" note. Only candidates within about a third of the length of
+// `name` are considered, and ties go to the alphabetically first.
+pub fn find_best_match_for_name<T: Iterator<InternedString>>(mut candidates: T,
+                                                             name: &str)
+                                                             -> Option<InternedString> {
+    let max_distance = (name.len() + 2) / 3;
+    let mut best: Option<(uint, InternedString)> = None;
+    for candidate in candidates {
+        if candidate.get() == name {
+            continue;
+        }
+        let distance = name.lev_distance(candidate.get());
+        if distance > max_distance || distance >= name.len() {
+            continue;
+        }
+        let better = match best {
+            Some((d, ref b)) => distance < d || (distance == d && candidate.get() < b.get()),
+            None => true,
+        };
+        if better {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
@@ -107,11 +107,12 @@ pub struct ExternalCrate {
 
 impl Clean<ExternalCrate> for cstore::crate_metadata {
     fn clean(&self) -> ExternalCrate {
+        let cx = super::ctxtkey.get().unwrap();
+        let attrs = decoder::expect_decoded(cx.sess(), self.name.as_slice(),
+                                            decoder::get_crate_attributes(self.data()));
         ExternalCrate {
             name: self.name.to_owned(),
-            attrs: decoder::get_crate_attributes(self.data()).clean()
-                                                             .move_iter()
-                                                             .collect(),
+            attrs: attrs.clean().move_iter().collect(),
         }
     }
 }
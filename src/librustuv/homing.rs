@@ -151,6 +151,7 @@ impl Drop for HomingMissile {
 mod test {
     use green::sched;
     use green::{SchedPool, PoolConfig};
+    use green::stack::StackPolicy;
     use std::rt::rtio::RtioUdpSocket;
     use std::io::test::next_test_ip4;
     use std::task::TaskOpts;
@@ -168,6 +169,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::event_loop,
+            stacks: StackPolicy::new(),
         });
 
         pool.spawn(TaskOpts::new(), proc() {
@@ -189,6 +191,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::event_loop,
+            stacks: StackPolicy::new(),
         });
 
         pool.spawn(TaskOpts::new(), proc() {
@@ -411,6 +411,7 @@ pub fn uv_error_to_io_error(uverr: UvError) -> IoError {
             uvll::ECONNABORTED => io::ConnectionAborted,
             uvll::EADDRNOTAVAIL => io::ConnectionRefused,
             uvll::ECANCELED => io::TimedOut,
+            uvll::EINVAL => io::InvalidInput,
             err => {
                 uvdebug!("uverr.code {}", err as int);
                 // FIXME: Need to map remaining uv error types
@@ -9,6 +9,7 @@
 // except according to those terms.
 
 use libc::c_int;
+use std::io::signal;
 use std::io::signal::Signum;
 use std::rt::rtio::RtioSignal;
 
@@ -28,6 +29,10 @@ pub struct SignalWatcher {
 impl SignalWatcher {
     pub fn new(io: &mut UvIoFactory, signum: Signum, channel: Sender<Signum>)
                -> Result<Box<SignalWatcher>, UvError> {
+        let signo = match signo(signum) {
+            Some(signo) => signo,
+            None => return Err(UvError(uvll::EINVAL)),
+        };
         let s = box SignalWatcher {
             handle: UvHandle::alloc(None::<SignalWatcher>, uvll::UV_SIGNAL),
             home: io.make_handle(),
@@ -39,7 +44,7 @@ impl SignalWatcher {
         }, 0);
 
         match unsafe {
-            uvll::uv_signal_start(s.handle, signal_cb, signum as c_int)
+            uvll::uv_signal_start(s.handle, signal_cb, signo)
         } {
             0 => Ok(s.install()),
             n => Err(UvError(n)),
@@ -50,10 +55,41 @@ impl SignalWatcher {
 
 extern fn signal_cb(handle: *uvll::uv_signal_t, signum: c_int) {
     let s: &mut SignalWatcher = unsafe { UvHandle::from_uv_handle(&handle) };
-    assert_eq!(signum as int, s.signal as int);
+    assert_eq!(Some(signum), signo(s.signal));
     let _ = s.channel.send_opt(s.signal);
 }
 
+#[cfg(unix)]
+fn signo(signum: Signum) -> Option<c_int> {
+    use uvll::signal::{SIGPIPE, SIGCHLD, SIGHUP, SIGINT, SIGQUIT, SIGTSTP,
+                       SIGTERM, SIGUSR1, SIGUSR2, SIGWINCH};
+    match signum {
+        signal::Break => None,
+        signal::BrokenPipe => Some(SIGPIPE),
+        signal::ChildExit => Some(SIGCHLD),
+        signal::HangUp => Some(SIGHUP),
+        signal::Interrupt => Some(SIGINT),
+        signal::Quit => Some(SIGQUIT),
+        signal::StopTemporarily => Some(SIGTSTP),
+        signal::Terminate => Some(SIGTERM),
+        signal::User1 => Some(SIGUSR1),
+        signal::User2 => Some(SIGUSR2),
+        signal::WindowSizeChange => Some(SIGWINCH),
+    }
+}
+
+#[cfg(windows)]
+fn signo(signum: Signum) -> Option<c_int> {
+    use uvll::signal::{SIGBREAK, SIGHUP, SIGINT, SIGWINCH};
+    match signum {
+        signal::Break => Some(SIGBREAK),
+        signal::HangUp => Some(SIGHUP),
+        signal::Interrupt => Some(SIGINT),
+        signal::WindowSizeChange => Some(SIGWINCH),
+        _ => None,
+    }
+}
+
 impl HomingIO for SignalWatcher {
     fn home<'r>(&'r mut self) -> &'r mut HomeHandle { &mut self.home }
 }
@@ -38,7 +38,8 @@ use std::rt::global_heap::malloc_raw;
 use libc::uintptr_t;
 
 pub use self::errors::{EACCES, ECONNREFUSED, ECONNRESET, EPIPE, ECONNABORTED,
-                       ECANCELED, EBADF, ENOTCONN, ENOENT, EADDRNOTAVAIL};
+                       ECANCELED, EBADF, ENOTCONN, ENOENT, EADDRNOTAVAIL,
+                       EINVAL};
 
 pub static OK: c_int = 0;
 pub static EOF: c_int = -4095;
@@ -61,6 +62,7 @@ pub mod errors {
     pub static ECANCELED: c_int = -4081;
     pub static EBADF: c_int = -4083;
     pub static EADDRNOTAVAIL: c_int = -4090;
+    pub static EINVAL: c_int = -4071;
 }
 #[cfg(not(windows))]
 pub mod errors {
@@ -77,6 +79,43 @@ pub mod errors {
     pub static ECANCELED : c_int = -libc::ECANCELED;
     pub static EBADF : c_int = -libc::EBADF;
     pub static EADDRNOTAVAIL : c_int = -libc::EADDRNOTAVAIL;
+    pub static EINVAL : c_int = -libc::EINVAL;
+}
+
+// libuv takes the platform's own signal numbers, apart from on windows where it
+// emulates the few signals it supports with the numbers from uv-win.h.
+#[cfg(windows)]
+pub mod signal {
+    use libc::c_int;
+
+    pub static SIGHUP: c_int = 1;
+    pub static SIGINT: c_int = 2;
+    pub static SIGBREAK: c_int = 21;
+    pub static SIGWINCH: c_int = 28;
+}
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+pub mod signal {
+    use libc::c_int;
+
+    pub use libc::consts::os::posix88::{SIGHUP, SIGINT, SIGQUIT, SIGPIPE, SIGTERM};
+    pub static SIGTSTP: c_int = 18;
+    pub static SIGCHLD: c_int = 20;
+    pub static SIGWINCH: c_int = 28;
+    pub static SIGUSR1: c_int = 30;
+    pub static SIGUSR2: c_int = 31;
+}
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+pub mod signal {
+    use libc::c_int;
+
+    pub use libc::consts::os::posix88::{SIGHUP, SIGINT, SIGQUIT, SIGPIPE, SIGTERM};
+    pub static SIGUSR1: c_int = 10;
+    pub static SIGUSR2: c_int = 12;
+    pub static SIGCHLD: c_int = 17;
+    pub static SIGTSTP: c_int = 20;
+    pub static SIGWINCH: c_int = 28;
 }
 
 pub static PROCESS_SETUID: c_int = 1 << 0;
@@ -27,6 +27,9 @@
 //!
 //! An example version number with all five components is
 //! `0.8.1-rc.3.0+20130922.linux`.
+//!
+//! A `VersionReq` describes the versions accepted by a dependency, such as
+//! `>=1.2, <2.0`, `^1.3`, `~0.4.2` or `1.*`.
 
 #![crate_id = "semver#0.11-pre"]
 #![crate_type = "rlib"]
@@ -202,22 +205,58 @@ fn expect(ch: Option<char>, c: char) -> Option<()> {
     }
 }
 
-fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
-    let maybe_vers = take_num(rdr).and_then(|(major, ch)| {
-        expect(ch, '.').and_then(|_| Some(major))
-    }).and_then(|major| {
-        take_num(rdr).and_then(|(minor, ch)| {
-            expect(ch, '.').and_then(|_| Some((major, minor)))
-        })
-    }).and_then(|(major, minor)| {
-        take_num(rdr).and_then(|(patch, ch)| {
-           Some((major, minor, patch, ch))
-        })
-    });
+/// A reason why a version or a version requirement couldn't be parsed.
+#[deriving(Clone, Eq)]
+pub enum ParseError {
+    /// The input contains characters outside of ASCII.
+    NonAsciiIdentifier,
+    /// The named component (`major`, `minor` or `patch`) is missing or
+    /// isn't a decimal number.
+    InvalidComponent(&'static str),
+    /// A pre-release or build identifier is empty or out of range.
+    InvalidIdentifier,
+    /// The input parsed as the given version, but wasn't written in its
+    /// canonical form, or was followed by other characters.
+    IncorrectParse(Version, ~str),
+    /// A requirement, or one of its comma-separated predicates, is empty.
+    EmptyPredicate,
+    /// A wildcard is combined with an operator or a pre-release, or is
+    /// followed by a number.
+    InvalidWildcard,
+    /// A pre-release is given for a version without a patch number.
+    PartialPreRelease,
+}
+
+impl fmt::Show for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NonAsciiIdentifier => write!(f.buf, "version contains non-ASCII characters"),
+            InvalidComponent(name) => write!(f.buf, "expected a {} version number", name),
+            InvalidIdentifier => write!(f.buf, "invalid pre-release or build identifier"),
+            IncorrectParse(ref v, ref s) => {
+                write!(f.buf, "`{}` is not a canonical version (parsed as `{}`)", *s, *v)
+            }
+            EmptyPredicate => write!(f.buf, "empty version requirement"),
+            InvalidWildcard => write!(f.buf, "wildcards must come last and have no operator"),
+            PartialPreRelease => {
+                write!(f.buf, "a pre-release requires a major, minor and patch version")
+            }
+        }
+    }
+}
 
-    let (major, minor, patch, ch) = match maybe_vers {
-        Some((a, b, c, d)) => (a, b, c, d),
-        None => return None
+fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Result<Version, ParseError> {
+    let (major, ch) = match take_num(rdr) {
+        Some(v) => v,
+        None => return Err(InvalidComponent("major")),
+    };
+    let (minor, ch) = match expect(ch, '.').and_then(|_| take_num(rdr)) {
+        Some(v) => v,
+        None => return Err(InvalidComponent("minor")),
+    };
+    let (patch, ch) = match expect(ch, '.').and_then(|_| take_num(rdr)) {
+        Some(v) => v,
+        None => return Err(InvalidComponent("patch")),
     };
 
     let mut pre = vec!();
@@ -228,7 +267,7 @@ fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
         loop {
             let (id, c) = match take_ident(rdr) {
                 Some((id, c)) => (id, c),
-                None => return None
+                None => return Err(InvalidIdentifier)
             };
             pre.push(id);
             ch = c;
@@ -240,7 +279,7 @@ fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
         loop {
             let (id, c) = match take_ident(rdr) {
                 Some((id, c)) => (id, c),
-                None => return None
+                None => return Err(InvalidIdentifier)
             };
             build.push(id);
             ch = c;
@@ -248,7 +287,7 @@ fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
         }
     }
 
-    Some(Version {
+    Ok(Version {
         major: major,
         minor: minor,
         patch: patch,
@@ -259,94 +298,411 @@ fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
 
 
 /// Parse a string into a semver object.
-pub fn parse(s: &str) -> Option<Version> {
+pub fn parse(s: &str) -> Result<Version, ParseError> {
     if !s.is_ascii() {
-        return None;
+        return Err(NonAsciiIdentifier);
     }
     let s = s.trim();
-    let v = parse_iter(&mut s.chars());
-    match v {
-        Some(v) => {
-            if v.to_str().equiv(&s) {
-                Some(v)
+    let v = try!(parse_iter(&mut s.chars()));
+    if v.to_str().equiv(&s) {
+        Ok(v)
+    } else {
+        Err(IncorrectParse(v, s.to_owned()))
+    }
+}
+
+/// The part of a version that a wildcard stands for.
+#[deriving(Clone, Eq)]
+pub enum WildcardVersion {
+    /// `*`: any version.
+    Major,
+    /// `1.*`: any version with the given major number.
+    Minor,
+    /// `1.2.*`: any version with the given major and minor numbers.
+    Patch,
+}
+
+/// The operator of a version requirement predicate.
+#[deriving(Clone, Eq)]
+pub enum Op {
+    /// `=1.2.3`, or just `1.2.3`: exactly this version. Missing minor or
+    /// patch numbers match anything.
+    Ex,
+    /// `>1.2.3`
+    Gt,
+    /// `>=1.2.3`
+    GtEq,
+    /// `<1.2.3`
+    Lt,
+    /// `<=1.2.3`
+    LtEq,
+    /// `~1.2.3`: at least this version, with the same minor version (or the
+    /// same major version if no minor is given).
+    Tilde,
+    /// `^1.2.3`: at least this version, with no change in the leftmost
+    /// non-zero component.
+    Compatible,
+    /// `*`, `1.*` or `1.2.*`
+    Wildcard(WildcardVersion),
+}
+
+/// A single comparison in a version requirement, such as `>=1.2`.
+#[deriving(Clone, Eq)]
+pub struct Predicate {
+    /// The comparison to make.
+    pub op: Op,
+    /// The major version to compare against.
+    pub major: uint,
+    /// The minor version, if given.
+    pub minor: Option<uint>,
+    /// The patch version, if given.
+    pub patch: Option<uint>,
+    /// The pre-release identifiers, which may only be given with a patch
+    /// version.
+    pub pre: Vec<Identifier>,
+}
+
+/// A set of predicates which a version must all satisfy, such as
+/// `>=1.2, <2.0`.
+///
+/// As in other package managers, a pre-release version only matches if one
+/// of the predicates names a pre-release of the same major, minor and patch
+/// version, so that `>=1.2.0-beta` matches `1.2.0-beta.2` and `1.3.0` but
+/// not `1.3.0-alpha`.
+///
+/// # Example
+///
+/// ```rust
+/// use semver::VersionReq;
+///
+/// let req = VersionReq::parse(">=1.2, <2.0").unwrap();
+/// assert!(req.matches(&semver::parse("1.4.1").unwrap()));
+/// assert!(!req.matches(&semver::parse("2.0.0").unwrap()));
+/// ```
+#[deriving(Clone, Eq)]
+pub struct VersionReq {
+    /// The predicates, which must all match.
+    pub predicates: Vec<Predicate>,
+}
+
+// Compares two pre-release lists, where no pre-release is the greatest
+fn pre_is_greater(a: &Vec<Identifier>, b: &Vec<Identifier>) -> bool {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => false,
+        (true, false) => true,
+        (false, true) => false,
+        (false, false) => *b < *a,
+    }
+}
+
+impl Predicate {
+    /// Parses a single predicate, such as `>=1.2.3`, `~1.2` or `1.*`.
+    pub fn parse(s: &str) -> Result<Predicate, ParseError> {
+        if !s.is_ascii() {
+            return Err(NonAsciiIdentifier);
+        }
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(EmptyPredicate);
+        }
+
+        let (op, rest) = if s.starts_with(">=") {
+            (Some(GtEq), s.slice_from(2))
+        } else if s.starts_with("<=") {
+            (Some(LtEq), s.slice_from(2))
+        } else if s.starts_with(">") {
+            (Some(Gt), s.slice_from(1))
+        } else if s.starts_with("<") {
+            (Some(Lt), s.slice_from(1))
+        } else if s.starts_with("=") {
+            (Some(Ex), s.slice_from(1))
+        } else if s.starts_with("~") {
+            (Some(Tilde), s.slice_from(1))
+        } else if s.starts_with("^") {
+            (Some(Compatible), s.slice_from(1))
+        } else {
+            (None, s)
+        };
+        let rest = rest.trim_left();
+
+        // A full version, possibly with a pre-release, is left to `parse`
+        let core_end = rest.find(|c: char| c == '-' || c == '+').unwrap_or(rest.len());
+        let parts: Vec<&str> = rest.slice_to(core_end).split('.').collect();
+        fn is_wildcard(p: &str) -> bool { p == "*" || p == "x" || p == "X" }
+        if parts.len() > 3 {
+            // never a valid predicate, but `parse` can say why
+            return match parse(rest) {
+                Ok(v) => Err(IncorrectParse(v, rest.to_owned())),
+                Err(e) => Err(e),
+            };
+        }
+        if parts.len() == 3 && !parts.iter().any(|&p| is_wildcard(p)) {
+            let v = try!(parse(rest));
+            return Ok(Predicate {
+                op: op.unwrap_or(Ex),
+                major: v.major,
+                minor: Some(v.minor),
+                patch: Some(v.patch),
+                pre: v.pre,
+            });
+        }
+
+        let names = ["major", "minor", "patch"];
+        let mut nums = vec!();
+        let mut wildcard = None;
+        for (i, &part) in parts.iter().enumerate() {
+            if is_wildcard(part) {
+                if wildcard.is_none() {
+                    wildcard = Some(i);
+                }
+            } else if wildcard.is_some() {
+                return Err(InvalidWildcard);
             } else {
-                None
+                match from_str::<uint>(part) {
+                    Some(n) if part.chars().all(char::is_digit) => nums.push(n),
+                    _ => return Err(InvalidComponent(names[i])),
+                }
+            }
+        }
+        if core_end != rest.len() {
+            return Err(if wildcard.is_some() { InvalidWildcard } else { PartialPreRelease });
+        }
+
+        let op = match (op, wildcard) {
+            (None, Some(0)) | (Some(Ex), Some(0)) => Wildcard(Major),
+            (None, Some(1)) | (Some(Ex), Some(1)) => Wildcard(Minor),
+            (None, Some(_)) | (Some(Ex), Some(_)) => Wildcard(Patch),
+            (Some(_), Some(_)) => return Err(InvalidWildcard),
+            (op, None) => op.unwrap_or(Ex),
+        };
+
+        Ok(Predicate {
+            op: op,
+            major: if nums.len() > 0 { *nums.get(0) } else { 0 },
+            minor: if nums.len() > 1 { Some(*nums.get(1)) } else { None },
+            patch: None,
+            pre: vec!(),
+        })
+    }
+
+    /// Returns true if `v` satisfies this predicate, ignoring the rules for
+    /// pre-releases described on `VersionReq`.
+    pub fn matches(&self, v: &Version) -> bool {
+        match self.op {
+            Ex => self.is_exact(v),
+            Gt => self.is_greater(v),
+            GtEq => !self.is_less(v),
+            Lt => self.is_less(v),
+            LtEq => !self.is_greater(v),
+            Tilde => !self.is_less(v) && v.major == self.major &&
+                     self.minor.map_or(true, |m| v.minor == m),
+            Compatible => !self.is_less(v) && self.is_compatible(v),
+            Wildcard(Major) => true,
+            Wildcard(Minor) => v.major == self.major,
+            Wildcard(Patch) => v.major == self.major && Some(v.minor) == self.minor,
+        }
+    }
+
+    fn is_exact(&self, v: &Version) -> bool {
+        v.major == self.major &&
+            self.minor.map_or(true, |m| v.minor == m) &&
+            self.patch.map_or(true, |p| v.patch == p && v.pre == self.pre)
+    }
+
+    // Missing components compare as equal to anything
+    fn is_greater(&self, v: &Version) -> bool {
+        if v.major != self.major {
+            return v.major > self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(m) if v.minor != m => return v.minor > m,
+            Some(_) => {}
+        }
+        match self.patch {
+            None => false,
+            Some(p) if v.patch != p => v.patch > p,
+            Some(_) => pre_is_greater(&v.pre, &self.pre),
+        }
+    }
+
+    fn is_less(&self, v: &Version) -> bool {
+        if v.major != self.major {
+            return v.major < self.major;
+        }
+        match self.minor {
+            None => return false,
+            Some(m) if v.minor != m => return v.minor < m,
+            Some(_) => {}
+        }
+        match self.patch {
+            None => false,
+            Some(p) if v.patch != p => v.patch < p,
+            Some(_) => pre_is_greater(&self.pre, &v.pre),
+        }
+    }
+
+    fn is_compatible(&self, v: &Version) -> bool {
+        if v.major != self.major {
+            return false;
+        }
+        match (self.major, self.minor, self.patch) {
+            (0, None, _) => true,
+            (0, Some(0), None) => v.minor == 0,
+            (0, Some(0), Some(p)) => v.minor == 0 && v.patch == p,
+            (0, Some(m), _) => v.minor == m,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Show for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.op {
+            Wildcard(Major) => return write!(f.buf, "*"),
+            Wildcard(Minor) => return write!(f.buf, "{}.*", self.major),
+            Wildcard(Patch) => {
+                return write!(f.buf, "{}.{}.*", self.major, self.minor.unwrap_or(0))
             }
+            Ex => try!(write!(f.buf, "=")),
+            Gt => try!(write!(f.buf, ">")),
+            GtEq => try!(write!(f.buf, ">=")),
+            Lt => try!(write!(f.buf, "<")),
+            LtEq => try!(write!(f.buf, "<=")),
+            Tilde => try!(write!(f.buf, "~")),
+            Compatible => try!(write!(f.buf, "^")),
+        }
+        try!(write!(f.buf, "{}", self.major));
+        match self.minor {
+            Some(m) => try!(write!(f.buf, ".{}", m)),
+            None => return Ok(()),
+        }
+        match self.patch {
+            Some(p) => try!(write!(f.buf, ".{}", p)),
+            None => return Ok(()),
+        }
+        for (i, x) in self.pre.iter().enumerate() {
+            try!(write!(f.buf, "{}", if i == 0 { "-" } else { "." }));
+            try!(x.fmt(f));
         }
-        None => None
+        Ok(())
+    }
+}
+
+impl VersionReq {
+    /// Parses a comma-separated list of predicates, such as `>=1.2, <2.0`.
+    pub fn parse(s: &str) -> Result<VersionReq, ParseError> {
+        let mut predicates = vec!();
+        for pred in s.split(',') {
+            predicates.push(try!(Predicate::parse(pred)));
+        }
+        Ok(VersionReq { predicates: predicates })
+    }
+
+    /// Returns a requirement which only matches `v`, ignoring its build
+    /// metadata.
+    pub fn exact(v: &Version) -> VersionReq {
+        VersionReq {
+            predicates: vec!(Predicate {
+                op: Ex,
+                major: v.major,
+                minor: Some(v.minor),
+                patch: Some(v.patch),
+                pre: v.pre.clone(),
+            }),
+        }
+    }
+
+    /// Returns true if `v` satisfies every predicate of this requirement.
+    pub fn matches(&self, v: &Version) -> bool {
+        self.predicates.iter().all(|p| p.matches(v)) &&
+            (v.pre.is_empty() || self.predicates.iter().any(|p| {
+                !p.pre.is_empty() && p.major == v.major &&
+                    p.minor == Some(v.minor) && p.patch == Some(v.patch)
+            }))
+    }
+}
+
+impl fmt::Show for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, pred) in self.predicates.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")) };
+            try!(write!(f.buf, "{}", *pred));
+        }
+        Ok(())
     }
 }
 
 #[test]
 fn test_parse() {
-    assert_eq!(parse(""), None);
-    assert_eq!(parse("  "), None);
-    assert_eq!(parse("1"), None);
-    assert_eq!(parse("1.2"), None);
-    assert_eq!(parse("1.2"), None);
-    assert_eq!(parse("1"), None);
-    assert_eq!(parse("1.2"), None);
-    assert_eq!(parse("1.2.3-"), None);
-    assert_eq!(parse("a.b.c"), None);
-    assert_eq!(parse("1.2.3 abc"), None);
-
-    assert!(parse("1.2.3") == Some(Version {
+    assert!(parse("").is_err());
+    assert!(parse("  ").is_err());
+    assert!(parse("1").is_err());
+    assert!(parse("1.2").is_err());
+    assert!(parse("1.2").is_err());
+    assert!(parse("1").is_err());
+    assert!(parse("1.2").is_err());
+    assert!(parse("1.2.3-").is_err());
+    assert!(parse("a.b.c").is_err());
+    assert!(parse("1.2.3 abc").is_err());
+
+    assert!(parse("1.2.3") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(),
         build: vec!(),
     }));
-    assert!(parse("  1.2.3  ") == Some(Version {
+    assert!(parse("  1.2.3  ") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(),
         build: vec!(),
     }));
-    assert!(parse("1.2.3-alpha1") == Some(Version {
+    assert!(parse("1.2.3-alpha1") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(AlphaNumeric("alpha1".to_owned())),
         build: vec!(),
     }));
-    assert!(parse("  1.2.3-alpha1  ") == Some(Version {
+    assert!(parse("  1.2.3-alpha1  ") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(AlphaNumeric("alpha1".to_owned())),
         build: vec!()
     }));
-    assert!(parse("1.2.3+build5") == Some(Version {
+    assert!(parse("1.2.3+build5") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(),
         build: vec!(AlphaNumeric("build5".to_owned()))
     }));
-    assert!(parse("  1.2.3+build5  ") == Some(Version {
+    assert!(parse("  1.2.3+build5  ") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(),
         build: vec!(AlphaNumeric("build5".to_owned()))
     }));
-    assert!(parse("1.2.3-alpha1+build5") == Some(Version {
+    assert!(parse("1.2.3-alpha1+build5") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(AlphaNumeric("alpha1".to_owned())),
         build: vec!(AlphaNumeric("build5".to_owned()))
     }));
-    assert!(parse("  1.2.3-alpha1+build5  ") == Some(Version {
+    assert!(parse("  1.2.3-alpha1+build5  ") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
         pre: vec!(AlphaNumeric("alpha1".to_owned())),
         build: vec!(AlphaNumeric("build5".to_owned()))
     }));
-    assert!(parse("1.2.3-1.alpha1.9+build5.7.3aedf  ") == Some(Version {
+    assert!(parse("1.2.3-1.alpha1.9+build5.7.3aedf  ") == Ok(Version {
         major: 1u,
         minor: 2u,
         patch: 3u,
@@ -393,44 +749,44 @@ fn test_to_str() {
 
 #[test]
 fn test_lt() {
-    assert!(parse("0.0.0")          < parse("1.2.3-alpha2"));
-    assert!(parse("1.0.0")          < parse("1.2.3-alpha2"));
-    assert!(parse("1.2.0")          < parse("1.2.3-alpha2"));
-    assert!(parse("1.2.3-alpha1")   < parse("1.2.3"));
-    assert!(parse("1.2.3-alpha1")   < parse("1.2.3-alpha2"));
-    assert!(!(parse("1.2.3-alpha2") < parse("1.2.3-alpha2")));
-    assert!(!(parse("1.2.3+23")     < parse("1.2.3+42")));
+    assert!(parse("0.0.0").unwrap()          < parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.0.0").unwrap()          < parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.0").unwrap()          < parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.3-alpha1").unwrap()   < parse("1.2.3").unwrap());
+    assert!(parse("1.2.3-alpha1").unwrap()   < parse("1.2.3-alpha2").unwrap());
+    assert!(!(parse("1.2.3-alpha2").unwrap() < parse("1.2.3-alpha2").unwrap()));
+    assert!(!(parse("1.2.3+23").unwrap()     < parse("1.2.3+42").unwrap()));
 }
 
 #[test]
 fn test_le() {
-    assert!(parse("0.0.0")        <= parse("1.2.3-alpha2"));
-    assert!(parse("1.0.0")        <= parse("1.2.3-alpha2"));
-    assert!(parse("1.2.0")        <= parse("1.2.3-alpha2"));
-    assert!(parse("1.2.3-alpha1") <= parse("1.2.3-alpha2"));
-    assert!(parse("1.2.3-alpha2") <= parse("1.2.3-alpha2"));
-    assert!(parse("1.2.3+23")     <= parse("1.2.3+42"));
+    assert!(parse("0.0.0").unwrap()        <= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.0.0").unwrap()        <= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.0").unwrap()        <= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.3-alpha1").unwrap() <= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap() <= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.3+23").unwrap()     <= parse("1.2.3+42").unwrap());
 }
 
 #[test]
 fn test_gt() {
-    assert!(parse("1.2.3-alpha2")   > parse("0.0.0"));
-    assert!(parse("1.2.3-alpha2")   > parse("1.0.0"));
-    assert!(parse("1.2.3-alpha2")   > parse("1.2.0"));
-    assert!(parse("1.2.3-alpha2")   > parse("1.2.3-alpha1"));
-    assert!(parse("1.2.3")          > parse("1.2.3-alpha2"));
-    assert!(!(parse("1.2.3-alpha2") > parse("1.2.3-alpha2")));
-    assert!(!(parse("1.2.3+23")     > parse("1.2.3+42")));
+    assert!(parse("1.2.3-alpha2").unwrap()   > parse("0.0.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap()   > parse("1.0.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap()   > parse("1.2.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap()   > parse("1.2.3-alpha1").unwrap());
+    assert!(parse("1.2.3").unwrap()          > parse("1.2.3-alpha2").unwrap());
+    assert!(!(parse("1.2.3-alpha2").unwrap() > parse("1.2.3-alpha2").unwrap()));
+    assert!(!(parse("1.2.3+23").unwrap()     > parse("1.2.3+42").unwrap()));
 }
 
 #[test]
 fn test_ge() {
-    assert!(parse("1.2.3-alpha2") >= parse("0.0.0"));
-    assert!(parse("1.2.3-alpha2") >= parse("1.0.0"));
-    assert!(parse("1.2.3-alpha2") >= parse("1.2.0"));
-    assert!(parse("1.2.3-alpha2") >= parse("1.2.3-alpha1"));
-    assert!(parse("1.2.3-alpha2") >= parse("1.2.3-alpha2"));
-    assert!(parse("1.2.3+23")     >= parse("1.2.3+42"));
+    assert!(parse("1.2.3-alpha2").unwrap() >= parse("0.0.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap() >= parse("1.0.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap() >= parse("1.2.0").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap() >= parse("1.2.3-alpha1").unwrap());
+    assert!(parse("1.2.3-alpha2").unwrap() >= parse("1.2.3-alpha2").unwrap());
+    assert!(parse("1.2.3+23").unwrap()     >= parse("1.2.3+42").unwrap());
 }
 
 #[test]
@@ -451,3 +807,89 @@ fn test_spec_order() {
         i += 1;
     }
 }
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse("1"), Err(InvalidComponent("minor")));
+    assert_eq!(parse("1.2"), Err(InvalidComponent("patch")));
+    assert_eq!(parse("a.b.c"), Err(InvalidComponent("major")));
+    assert_eq!(parse("1.2.3-"), Err(InvalidIdentifier));
+    assert_eq!(parse("1.2.3-é"), Err(NonAsciiIdentifier));
+    assert!(match parse("01.2.3") { Err(IncorrectParse(..)) => true, _ => false });
+    assert_eq!(parse("1.2.3 abc").err().unwrap().to_str(),
+               "`1.2.3 abc` is not a canonical version (parsed as `1.2.3`)".to_owned());
+}
+
+#[test]
+fn test_req_parse() {
+    let show = |s: &str| VersionReq::parse(s).unwrap().to_str();
+    assert_eq!(show("1.2.3"), "=1.2.3".to_owned());
+    assert_eq!(show(">= 1.2, <2.0"), ">=1.2, <2.0".to_owned());
+    assert_eq!(show("^1.3"), "^1.3".to_owned());
+    assert_eq!(show("~0.4.2"), "~0.4.2".to_owned());
+    assert_eq!(show(" *"), "*".to_owned());
+    assert_eq!(show("1.*"), "1.*".to_owned());
+    assert_eq!(show("1.2.x"), "1.2.*".to_owned());
+    assert_eq!(show("=1.X"), "1.*".to_owned());
+    assert_eq!(show(">1.2.3-beta.2+build"), ">1.2.3-beta.2".to_owned());
+
+    assert_eq!(VersionReq::parse(""), Err(EmptyPredicate));
+    assert_eq!(VersionReq::parse(">=1.2,"), Err(EmptyPredicate));
+    assert_eq!(VersionReq::parse(">1.*"), Err(InvalidWildcard));
+    assert_eq!(VersionReq::parse("1.*.3"), Err(InvalidWildcard));
+    assert_eq!(VersionReq::parse("1.*-beta"), Err(InvalidWildcard));
+    assert_eq!(VersionReq::parse("1.2-beta"), Err(PartialPreRelease));
+    assert_eq!(VersionReq::parse("1.a"), Err(InvalidComponent("minor")));
+    assert_eq!(VersionReq::parse("!1.2"), Err(InvalidComponent("major")));
+    assert!(VersionReq::parse("1.2.3.4").is_err());
+}
+
+#[test]
+fn test_req_matches() {
+    fn check(req: &str, yes: &[&str], no: &[&str]) {
+        let req = VersionReq::parse(req).unwrap();
+        for v in yes.iter() {
+            assert!(req.matches(&parse(*v).unwrap()), "{} should match {}", req, *v);
+        }
+        for v in no.iter() {
+            assert!(!req.matches(&parse(*v).unwrap()), "{} should not match {}", req, *v);
+        }
+    }
+
+    check("1.2.3", ["1.2.3", "1.2.3+build"], ["1.2.4", "1.2.2", "1.2.3-beta"]);
+    check("=1.2", ["1.2.0", "1.2.9"], ["1.3.0", "1.1.9"]);
+    check(">1.2.3", ["1.2.4", "2.0.0"], ["1.2.3", "1.0.0"]);
+    check(">1.2", ["1.3.0", "2.0.0"], ["1.2.9", "1.0.0"]);
+    check(">=1.2, <2.0", ["1.2.0", "1.9.9"], ["1.1.9", "2.0.0"]);
+    check("<=1.2", ["1.2.9", "0.1.0"], ["1.3.0"]);
+    check("~1.2.3", ["1.2.3", "1.2.9"], ["1.2.2", "1.3.0"]);
+    check("~1.2", ["1.2.0", "1.2.9"], ["1.1.0", "1.3.0"]);
+    check("~1", ["1.0.0", "1.9.0"], ["0.9.0", "2.0.0"]);
+    check("^1.3", ["1.3.0", "1.9.9"], ["1.2.9", "2.0.0"]);
+    check("^0.2.3", ["0.2.3", "0.2.9"], ["0.2.2", "0.3.0"]);
+    check("^0.0.3", ["0.0.3"], ["0.0.4", "0.1.0"]);
+    check("^0.0", ["0.0.0", "0.0.9"], ["0.1.0"]);
+    check("^0", ["0.0.0", "0.9.9"], ["1.0.0"]);
+    check("*", ["0.0.0", "9.9.9"], ["1.0.0-alpha"]);
+    check("1.*", ["1.0.0", "1.9.9"], ["0.9.0", "2.0.0"]);
+    check("1.2.*", ["1.2.0", "1.2.9"], ["1.3.0"]);
+}
+
+#[test]
+fn test_req_pre_release() {
+    let req = VersionReq::parse(">=1.2.0-beta").unwrap();
+    assert!(req.matches(&parse("1.2.0-beta").unwrap()));
+    assert!(req.matches(&parse("1.2.0-beta.2").unwrap()));
+    assert!(req.matches(&parse("1.2.0").unwrap()));
+    assert!(req.matches(&parse("1.3.0").unwrap()));
+    assert!(!req.matches(&parse("1.2.0-alpha").unwrap()));
+    assert!(!req.matches(&parse("1.3.0-alpha").unwrap()));
+
+    let req = VersionReq::parse("<1.2.0").unwrap();
+    assert!(!req.matches(&parse("1.2.0-beta").unwrap()));
+
+    let req = VersionReq::exact(&parse("1.2.0-rc.1+build").unwrap());
+    assert_eq!(req.to_str(), "=1.2.0-rc.1".to_owned());
+    assert!(req.matches(&parse("1.2.0-rc.1").unwrap()));
+    assert!(!req.matches(&parse("1.2.0").unwrap()));
+}
@@ -0,0 +1,1547 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Concise Binary Object Representation (CBOR), as specified by RFC 7049.
+//!
+//! CBOR is a self-describing binary format with a data model which is a
+//! superset of JSON's. The `Encoder` in this module writes to any `Writer`,
+//! without needing to seek back, and the `Decoder` works on a `Value` tree
+//! which can be read from a byte slice or from any `Reader`.
+//!
+//! Rust values are mapped onto CBOR as follows:
+//!
+//! * integers are encoded as CBOR integers, in the shortest form possible
+//! * `f32` and `f64` are encoded as single and double precision floats
+//! * `()` and `None` are encoded as `null`, and `Some(x)` as `x`
+//! * strings and `char`s are encoded as text strings
+//! * structs are encoded as maps from field names to values
+//! * vectors, tuples and tuple structs are encoded as arrays
+//! * maps are encoded as maps, with keys of any type
+//! * enum variants without arguments are encoded as the variant's name, and
+//!   other variants as an array of the name followed by the arguments
+//!
+//! # Example
+//!
+//! ```rust
+//! extern crate serialize;
+//! use serialize::cbor;
+//! use serialize::{Decodable, Encodable};
+//!
+//! #[deriving(Decodable, Encodable, Eq, Show)]
+//! pub struct Point { x: int, y: int }
+//!
+//! fn main() {
+//!     let point = Point { x: 1, y: -2 };
+//!     let bytes = cbor::Encoder::buffer_encode(&point);
+//!     let mut decoder = cbor::Decoder::from_bytes(bytes.as_slice()).unwrap();
+//!     let decoded: Point = Decodable::decode(&mut decoder).unwrap();
+//!     assert_eq!(point, decoded);
+//! }
+//! ```
+
+#![allow(missing_doc)]
+
+use std::cast;
+use std::cmp;
+use std::f64;
+use std::fmt;
+use std::io::{BufReader, MemWriter};
+use std::io;
+use std::str;
+use std::u64;
+
+use Encodable;
+use hex::ToHex;
+
+/// Represents a CBOR data item
+#[deriving(Clone, Eq)]
+pub enum Value {
+    /// An unsigned integer
+    Unsigned(u64),
+    /// The negative integer `-1 - n`. This covers the whole range of CBOR
+    /// negative integers, which is larger than that of `i64`.
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(~str),
+    Array(Vec<Value>),
+    /// The pairs of a map, in the order they were read in. Keys may be of any
+    /// type.
+    Map(Vec<(Value, Value)>),
+    /// A tag number, and the tagged item
+    Tag(u64, Box<Value>),
+    Float(f64),
+    Boolean(bool),
+    Null,
+    Undefined,
+    /// A simple value other than `false`, `true`, `null` and `undefined`
+    Simple(u8),
+}
+
+/// The errors that can arise while parsing a CBOR item.
+#[deriving(Clone, Eq)]
+pub enum ErrorCode {
+    EOFWhileParsingValue,
+    InvalidAdditionalInfo,
+    InvalidSimpleValue,
+    InvalidChunk,
+    UnexpectedBreak,
+    TrailingBytes,
+    NotUtf8,
+}
+
+#[deriving(Clone, Eq, Show)]
+pub enum ParserError {
+    /// code, offset of the byte at which the error was detected
+    SyntaxError(ErrorCode, uint),
+    IoError(io::IoErrorKind, &'static str),
+}
+
+#[deriving(Clone, Eq, Show)]
+pub enum DecoderError {
+    ParseError(ParserError),
+    ExpectedError(~str, ~str),
+    MissingFieldError(~str),
+    UnknownVariantError(~str),
+    /// An error reported by a `Decodable` implementation through
+    /// `Decoder::error`.
+    ApplicationError(~str),
+}
+
+/// Returns a readable error string for a given error code.
+pub fn error_str(error: ErrorCode) -> &'static str {
+    match error {
+        EOFWhileParsingValue => "EOF while parsing value",
+        InvalidAdditionalInfo => "invalid additional information",
+        InvalidSimpleValue => "invalid simple value",
+        InvalidChunk => "invalid chunk in indefinite length string",
+        UnexpectedBreak => "unexpected break",
+        TrailingBytes => "trailing bytes",
+        NotUtf8 => "text string not utf-8",
+    }
+}
+
+impl fmt::Show for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        error_str(*self).fmt(f)
+    }
+}
+
+pub type EncodeResult = io::IoResult<()>;
+pub type DecodeResult<T> = Result<T, DecoderError>;
+
+// Major types
+static UNSIGNED: u8 = 0;
+static NEGATIVE: u8 = 1;
+static BYTES: u8 = 2;
+static TEXT: u8 = 3;
+static ARRAY: u8 = 4;
+static MAP: u8 = 5;
+static TAG: u8 = 6;
+
+// Initial bytes of major type 7
+static FALSE: u8 = 0xf4;
+static TRUE: u8 = 0xf5;
+static NULL: u8 = 0xf6;
+static UNDEFINED: u8 = 0xf7;
+static SIMPLE: u8 = 0xf8;
+static FLOAT32: u8 = 0xfa;
+static FLOAT64: u8 = 0xfb;
+static BREAK: u8 = 0xff;
+
+// Writes the initial byte of an item, along with its argument in the shortest
+// form possible.
+fn write_head(wr: &mut io::Writer, major: u8, n: u64) -> EncodeResult {
+    let major = major << 5;
+    if n < 24 {
+        wr.write_u8(major | n as u8)
+    } else if n <= 0xff {
+        try!(wr.write_u8(major | 24));
+        wr.write_u8(n as u8)
+    } else if n <= 0xffff {
+        try!(wr.write_u8(major | 25));
+        wr.write_be_u16(n as u16)
+    } else if n <= 0xffff_ffff {
+        try!(wr.write_u8(major | 26));
+        wr.write_be_u32(n as u32)
+    } else {
+        try!(wr.write_u8(major | 27));
+        wr.write_be_u64(n)
+    }
+}
+
+/// A structure for implementing serialization to CBOR.
+pub struct Encoder<'a> {
+    wr: &'a mut io::Writer,
+}
+
+impl<'a> Encoder<'a> {
+    /// Creates a new CBOR encoder whose output will be written to the writer
+    /// specified.
+    pub fn new<'a>(wr: &'a mut io::Writer) -> Encoder<'a> {
+        Encoder { wr: wr }
+    }
+
+    /// Encode the specified struct into a CBOR [u8]
+    pub fn buffer_encode<T:Encodable<Encoder<'a>, io::IoError>>(to_encode_object: &T) -> Vec<u8> {
+        let mut m = MemWriter::new();
+        {
+            let mut encoder = Encoder::new(&mut m as &mut io::Writer);
+            // MemWriter never Errs
+            let _ = to_encode_object.encode(&mut encoder);
+        }
+        m.unwrap()
+    }
+}
+
+impl<'a> ::Encoder<io::IoError> for Encoder<'a> {
+    fn emit_nil(&mut self) -> EncodeResult { self.wr.write_u8(NULL) }
+
+    fn emit_uint(&mut self, v: uint) -> EncodeResult { self.emit_u64(v as u64) }
+    fn emit_u64(&mut self, v: u64) -> EncodeResult { write_head(self.wr, UNSIGNED, v) }
+    fn emit_u32(&mut self, v: u32) -> EncodeResult { self.emit_u64(v as u64) }
+    fn emit_u16(&mut self, v: u16) -> EncodeResult { self.emit_u64(v as u64) }
+    fn emit_u8(&mut self, v: u8) -> EncodeResult  { self.emit_u64(v as u64) }
+
+    fn emit_int(&mut self, v: int) -> EncodeResult { self.emit_i64(v as i64) }
+    fn emit_i64(&mut self, v: i64) -> EncodeResult {
+        if v < 0 {
+            write_head(self.wr, NEGATIVE, (-1 - v) as u64)
+        } else {
+            write_head(self.wr, UNSIGNED, v as u64)
+        }
+    }
+    fn emit_i32(&mut self, v: i32) -> EncodeResult { self.emit_i64(v as i64) }
+    fn emit_i16(&mut self, v: i16) -> EncodeResult { self.emit_i64(v as i64) }
+    fn emit_i8(&mut self, v: i8) -> EncodeResult  { self.emit_i64(v as i64) }
+
+    fn emit_bool(&mut self, v: bool) -> EncodeResult {
+        self.wr.write_u8(if v { TRUE } else { FALSE })
+    }
+
+    fn emit_f64(&mut self, v: f64) -> EncodeResult {
+        try!(self.wr.write_u8(FLOAT64));
+        self.wr.write_be_f64(v)
+    }
+    fn emit_f32(&mut self, v: f32) -> EncodeResult {
+        try!(self.wr.write_u8(FLOAT32));
+        self.wr.write_be_f32(v)
+    }
+
+    fn emit_char(&mut self, v: char) -> EncodeResult { self.emit_str(str::from_char(v)) }
+    fn emit_str(&mut self, v: &str) -> EncodeResult {
+        try!(write_head(self.wr, TEXT, v.len() as u64));
+        self.wr.write_str(v)
+    }
+
+    fn emit_enum(&mut self,
+                 _name: &str,
+                 f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult { f(self) }
+
+    fn emit_enum_variant(&mut self,
+                         name: &str,
+                         _id: uint,
+                         cnt: uint,
+                         f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        // enums are encoded as strings or arrays:
+        // Foo -> "Foo"
+        // Kangaroo(34,"William") -> ["Kangaroo",34,"William"]
+        if cnt == 0 {
+            self.emit_str(name)
+        } else {
+            try!(write_head(self.wr, ARRAY, cnt as u64 + 1));
+            try!(self.emit_str(name));
+            f(self)
+        }
+    }
+
+    fn emit_enum_variant_arg(&mut self,
+                             _idx: uint,
+                             f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+
+    fn emit_enum_struct_variant(&mut self,
+                                name: &str,
+                                id: uint,
+                                cnt: uint,
+                                f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_enum_variant(name, id, cnt, f)
+    }
+
+    fn emit_enum_struct_variant_field(&mut self,
+                                      _: &str,
+                                      idx: uint,
+                                      f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_enum_variant_arg(idx, f)
+    }
+
+    fn emit_struct(&mut self,
+                   _: &str,
+                   len: uint,
+                   f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        try!(write_head(self.wr, MAP, len as u64));
+        f(self)
+    }
+
+    fn emit_struct_field(&mut self,
+                         name: &str,
+                         _idx: uint,
+                         f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        try!(self.emit_str(name));
+        f(self)
+    }
+
+    fn emit_tuple(&mut self, len: uint, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_seq(len, f)
+    }
+    fn emit_tuple_arg(&mut self,
+                      idx: uint,
+                      f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_seq_elt(idx, f)
+    }
+
+    fn emit_tuple_struct(&mut self,
+                         _name: &str,
+                         len: uint,
+                         f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_seq(len, f)
+    }
+    fn emit_tuple_struct_arg(&mut self,
+                             idx: uint,
+                             f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        self.emit_seq_elt(idx, f)
+    }
+
+    fn emit_option(&mut self, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+    fn emit_option_none(&mut self) -> EncodeResult { self.emit_nil() }
+    fn emit_option_some(&mut self, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+
+    fn emit_seq(&mut self, len: uint, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        try!(write_head(self.wr, ARRAY, len as u64));
+        f(self)
+    }
+
+    fn emit_seq_elt(&mut self, _idx: uint, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+
+    fn emit_map(&mut self, len: uint, f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        try!(write_head(self.wr, MAP, len as u64));
+        f(self)
+    }
+
+    fn emit_map_elt_key(&mut self,
+                        _idx: uint,
+                        f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+
+    fn emit_map_elt_val(&mut self,
+                        _idx: uint,
+                        f: |&mut Encoder<'a>| -> EncodeResult) -> EncodeResult {
+        f(self)
+    }
+}
+
+impl<E: ::Encoder<S>, S> Encodable<E, S> for Value {
+    /// Encodes a CBOR value with any encoder. Byte strings are encoded as
+    /// sequences of `u8`, tags are dropped, and `undefined` and other simple
+    /// values are encoded as nil. Use `to_writer` to write the value itself
+    /// as CBOR.
+    fn encode(&self, e: &mut E) -> Result<(), S> {
+        match *self {
+            Unsigned(n) => e.emit_u64(n),
+            Negative(n) => e.emit_i64(-1 - n as i64),
+            Bytes(ref v) => v.encode(e),
+            Text(ref v) => v.encode(e),
+            Array(ref v) => v.encode(e),
+            Map(ref v) => {
+                e.emit_map(v.len(), |e| {
+                    for (i, &(ref key, ref value)) in v.iter().enumerate() {
+                        try!(e.emit_map_elt_key(i, |e| key.encode(e)));
+                        try!(e.emit_map_elt_val(i, |e| value.encode(e)));
+                    }
+                    Ok(())
+                })
+            }
+            Tag(_, ref v) => v.encode(e),
+            Float(v) => e.emit_f64(v),
+            Boolean(v) => e.emit_bool(v),
+            Null | Undefined | Simple(_) => e.emit_nil(),
+        }
+    }
+}
+
+impl Value {
+    /// Writes a CBOR value to an io::writer.
+    pub fn to_writer(&self, wr: &mut io::Writer) -> EncodeResult {
+        match *self {
+            Unsigned(n) => write_head(wr, UNSIGNED, n),
+            Negative(n) => write_head(wr, NEGATIVE, n),
+            Bytes(ref v) => {
+                try!(write_head(wr, BYTES, v.len() as u64));
+                wr.write(v.as_slice())
+            }
+            Text(ref v) => {
+                try!(write_head(wr, TEXT, v.len() as u64));
+                wr.write_str(v.as_slice())
+            }
+            Array(ref v) => {
+                try!(write_head(wr, ARRAY, v.len() as u64));
+                for item in v.iter() {
+                    try!(item.to_writer(wr));
+                }
+                Ok(())
+            }
+            Map(ref v) => {
+                try!(write_head(wr, MAP, v.len() as u64));
+                for &(ref key, ref value) in v.iter() {
+                    try!(key.to_writer(wr));
+                    try!(value.to_writer(wr));
+                }
+                Ok(())
+            }
+            Tag(tag, ref v) => {
+                try!(write_head(wr, TAG, tag));
+                v.to_writer(wr)
+            }
+            Float(v) => {
+                try!(wr.write_u8(FLOAT64));
+                wr.write_be_f64(v)
+            }
+            Boolean(v) => wr.write_u8(if v { TRUE } else { FALSE }),
+            Null => wr.write_u8(NULL),
+            Undefined => wr.write_u8(UNDEFINED),
+            Simple(v) if v < 24 => wr.write_u8(0xe0 | v),
+            Simple(v) => {
+                try!(wr.write_u8(SIMPLE));
+                wr.write_u8(v)
+            }
+        }
+    }
+
+    /// Encodes a CBOR value into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut m = MemWriter::new();
+        // MemWriter never Errs
+        let _ = self.to_writer(&mut m as &mut io::Writer);
+        m.unwrap()
+    }
+
+    /// If the value is an integer which fits in an `i64`, returns it.
+    /// Returns None otherwise.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Unsigned(n) if n <= i64_max() => Some(n as i64),
+            Negative(n) if n <= i64_max() => Some(-1 - n as i64),
+            _ => None
+        }
+    }
+
+    /// If the value is an unsigned integer, returns it.
+    /// Returns None otherwise.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Unsigned(n) => Some(n),
+            _ => None
+        }
+    }
+
+    /// If the value is a byte string, returns its contents.
+    /// Returns None otherwise.
+    pub fn as_bytes<'a>(&'a self) -> Option<&'a [u8]> {
+        match *self {
+            Bytes(ref v) => Some(v.as_slice()),
+            _ => None
+        }
+    }
+
+    /// If the value is a text string, returns the associated str.
+    /// Returns None otherwise.
+    pub fn as_text<'a>(&'a self) -> Option<&'a str> {
+        match *self {
+            Text(ref s) => Some(s.as_slice()),
+            _ => None
+        }
+    }
+
+    /// If the value is a map, returns the value associated with the first
+    /// key equal to `key`. Returns None otherwise.
+    pub fn find<'a>(&'a self, key: &Value) -> Option<&'a Value> {
+        match *self {
+            Map(ref pairs) => {
+                pairs.iter().find(|&&(ref k, _)| k == key).map(|&(_, ref v)| v)
+            }
+            _ => None
+        }
+    }
+}
+
+fn i64_max() -> u64 { ::std::i64::MAX as u64 }
+
+/// Values are shown in the diagnostic notation of RFC 7049, section 6.
+impl fmt::Show for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Unsigned(n) => write!(f.buf, "{}", n),
+            Negative(n) if n == u64::MAX => write!(f.buf, "-18446744073709551616"),
+            Negative(n) => write!(f.buf, "-{}", n + 1),
+            Bytes(ref v) => write!(f.buf, "h'{}'", v.as_slice().to_hex()),
+            Text(ref s) => write!(f.buf, "\"{}\"", s.escape_default()),
+            Array(ref v) => {
+                try!(write!(f.buf, "["));
+                for (i, item) in v.iter().enumerate() {
+                    if i > 0 { try!(write!(f.buf, ", ")); }
+                    try!(write!(f.buf, "{}", *item));
+                }
+                write!(f.buf, "]")
+            }
+            Map(ref v) => {
+                try!(write!(f.buf, "\\{"));
+                for (i, &(ref key, ref value)) in v.iter().enumerate() {
+                    if i > 0 { try!(write!(f.buf, ", ")); }
+                    try!(write!(f.buf, "{}: {}", *key, *value));
+                }
+                write!(f.buf, "\\}")
+            }
+            Tag(tag, ref v) => write!(f.buf, "{}({})", tag, **v),
+            Float(v) => write!(f.buf, "{}", v),
+            Boolean(v) => write!(f.buf, "{}", v),
+            Null => write!(f.buf, "null"),
+            Undefined => write!(f.buf, "undefined"),
+            Simple(v) => write!(f.buf, "simple({})", v),
+        }
+    }
+}
+
+// Converts an IEEE 754 half precision float.
+fn half_to_f64(half: u16) -> f64 {
+    let exp = ((half >> 10) & 0x1f) as i32;
+    let mant = (half & 0x3ff) as f64;
+    let value = if exp == 0 {
+        mant * 2.0f64.powi(-24)
+    } else if exp != 31 {
+        (mant + 1024.0) * 2.0f64.powi(exp - 25)
+    } else if mant == 0.0 {
+        f64::INFINITY
+    } else {
+        f64::NAN
+    };
+    if half & 0x8000 != 0 { -value } else { value }
+}
+
+// Byte and text strings are read in chunks of at most this size, so that a
+// bogus length can't make the parser allocate much more than the input holds.
+static CHUNK: uint = 64 * 1024;
+
+/// A parser reading CBOR items from a `Reader`.
+pub struct Parser<'a> {
+    rdr: &'a mut io::Reader,
+    offset: uint,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a parser reading from `rdr`.
+    pub fn new<'a>(rdr: &'a mut io::Reader) -> Parser<'a> {
+        Parser { rdr: rdr, offset: 0 }
+    }
+
+    /// Reads the next CBOR item.
+    pub fn parse(&mut self) -> Result<Value, ParserError> {
+        let initial = try!(self.read_byte());
+        if initial == BREAK {
+            return self.error(UnexpectedBreak)
+        }
+        self.parse_item(initial)
+    }
+
+    /// Returns the number of bytes which have been read so far.
+    pub fn offset(&self) -> uint { self.offset }
+
+    fn error<T>(&self, code: ErrorCode) -> Result<T, ParserError> {
+        Err(SyntaxError(code, self.offset))
+    }
+
+    fn io_error<T>(&self, err: io::IoError) -> Result<T, ParserError> {
+        if err.kind == io::EndOfFile {
+            self.error(EOFWhileParsingValue)
+        } else {
+            Err(IoError(err.kind, err.desc))
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<u8, ParserError> {
+        match self.rdr.read_byte() {
+            Ok(b) => {
+                self.offset += 1;
+                Ok(b)
+            }
+            Err(e) => self.io_error(e)
+        }
+    }
+
+    fn read_bytes(&mut self, len: u64, buf: &mut Vec<u8>) -> Result<(), ParserError> {
+        let mut left = len;
+        while left > 0 {
+            let n = cmp::min(left, CHUNK as u64) as uint;
+            match self.rdr.push_exact(buf, n) {
+                Ok(()) => {
+                    self.offset += n;
+                    left -= n as u64;
+                }
+                Err(e) => return self.io_error(e)
+            }
+        }
+        Ok(())
+    }
+
+    fn read_be(&mut self, n: uint) -> Result<u64, ParserError> {
+        let mut v = 0u64;
+        for _ in range(0, n) {
+            v = (v << 8) | try!(self.read_byte()) as u64;
+        }
+        Ok(v)
+    }
+
+    // Reads the argument of an item, given the low five bits of its initial
+    // byte. `None` stands for an indefinite length.
+    fn read_argument(&mut self, info: u8) -> Result<Option<u64>, ParserError> {
+        match info {
+            0..23 => Ok(Some(info as u64)),
+            24 => Ok(Some(try!(self.read_be(1)))),
+            25 => Ok(Some(try!(self.read_be(2)))),
+            26 => Ok(Some(try!(self.read_be(4)))),
+            27 => Ok(Some(try!(self.read_be(8)))),
+            31 => Ok(None),
+            _ => self.error(InvalidAdditionalInfo),
+        }
+    }
+
+    fn parse_item(&mut self, initial: u8) -> Result<Value, ParserError> {
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        if major == 7 {
+            return self.parse_simple(info)
+        }
+        let arg = try!(self.read_argument(info));
+        match (major, arg) {
+            (0, Some(n)) => Ok(Unsigned(n)),
+            (1, Some(n)) => Ok(Negative(n)),
+            (2, len) => Ok(Bytes(try!(self.parse_string(major, len)))),
+            (3, len) => {
+                let bytes = try!(self.parse_string(major, len));
+                match str::from_utf8(bytes.as_slice()) {
+                    Some(s) => Ok(Text(s.to_owned())),
+                    None => self.error(NotUtf8),
+                }
+            }
+            (4, len) => {
+                let mut items = Vec::new();
+                loop {
+                    match try!(self.next_in(len, items.len())) {
+                        Some(initial) => items.push(try!(self.parse_item(initial))),
+                        None => break,
+                    }
+                }
+                Ok(Array(items))
+            }
+            (5, len) => {
+                let mut pairs = Vec::new();
+                loop {
+                    match try!(self.next_in(len, pairs.len())) {
+                        Some(initial) => {
+                            let key = try!(self.parse_item(initial));
+                            let value = try!(self.parse());
+                            pairs.push((key, value));
+                        }
+                        None => break,
+                    }
+                }
+                Ok(Map(pairs))
+            }
+            (6, Some(tag)) => Ok(Tag(tag, box try!(self.parse()))),
+            _ => self.error(InvalidAdditionalInfo),
+        }
+    }
+
+    // Reads the initial byte of the next element of an array or map which
+    // holds `read` elements so far, or returns `None` if there are no more.
+    fn next_in(&mut self, len: Option<u64>, read: uint) -> Result<Option<u8>, ParserError> {
+        match len {
+            Some(len) if read as u64 == len => Ok(None),
+            Some(_) => {
+                let initial = try!(self.read_byte());
+                if initial == BREAK {
+                    return self.error(UnexpectedBreak)
+                }
+                Ok(Some(initial))
+            }
+            None => {
+                let initial = try!(self.read_byte());
+                Ok(if initial == BREAK { None } else { Some(initial) })
+            }
+        }
+    }
+
+    // Reads the contents of a byte or text string. Strings of indefinite
+    // length are made of definite length chunks of the same major type.
+    fn parse_string(&mut self, major: u8, len: Option<u64>) -> Result<Vec<u8>, ParserError> {
+        let mut buf = Vec::new();
+        match len {
+            Some(len) => try!(self.read_bytes(len, &mut buf)),
+            None => loop {
+                let initial = try!(self.read_byte());
+                if initial == BREAK { break }
+                if initial >> 5 != major {
+                    return self.error(InvalidChunk)
+                }
+                match try!(self.read_argument(initial & 0x1f)) {
+                    Some(len) => try!(self.read_bytes(len, &mut buf)),
+                    None => return self.error(InvalidChunk),
+                }
+            }
+        }
+        Ok(buf)
+    }
+
+    fn parse_simple(&mut self, info: u8) -> Result<Value, ParserError> {
+        match info {
+            20 => Ok(Boolean(false)),
+            21 => Ok(Boolean(true)),
+            22 => Ok(Null),
+            23 => Ok(Undefined),
+            0..19 => Ok(Simple(info)),
+            24 => {
+                let v = try!(self.read_byte());
+                if v < 32 {
+                    self.error(InvalidSimpleValue)
+                } else {
+                    Ok(Simple(v))
+                }
+            }
+            25 => Ok(Float(half_to_f64(try!(self.read_be(2)) as u16))),
+            26 => {
+                let bits = try!(self.read_be(4)) as u32;
+                Ok(Float(unsafe { cast::transmute::<u32, f32>(bits) } as f64))
+            }
+            27 => {
+                let bits = try!(self.read_be(8));
+                Ok(Float(unsafe { cast::transmute::<u64, f64>(bits) }))
+            }
+            31 => self.error(UnexpectedBreak),
+            _ => self.error(InvalidAdditionalInfo),
+        }
+    }
+}
+
+/// Reads a single CBOR item from a `Reader`. Anything following the item is
+/// left unread.
+pub fn from_reader(rdr: &mut io::Reader) -> Result<Value, ParserError> {
+    Parser::new(rdr).parse()
+}
+
+/// Reads a CBOR item from a byte slice, which must hold exactly one item.
+pub fn from_bytes(bytes: &[u8]) -> Result<Value, ParserError> {
+    let mut rdr = BufReader::new(bytes);
+    let mut parser = Parser::new(&mut rdr as &mut io::Reader);
+    let value = try!(parser.parse());
+    if parser.offset() != bytes.len() {
+        return parser.error(TrailingBytes)
+    }
+    Ok(value)
+}
+
+/// A structure to decode CBOR to values in rust.
+pub struct Decoder {
+    stack: Vec<Value>,
+}
+
+impl Decoder {
+    /// Creates a new decoder instance for decoding the specified CBOR value.
+    pub fn new(value: Value) -> Decoder {
+        Decoder {
+            stack: vec!(value),
+        }
+    }
+
+    /// Creates a new decoder for the CBOR item held by a byte slice.
+    pub fn from_bytes(bytes: &[u8]) -> DecodeResult<Decoder> {
+        match from_bytes(bytes) {
+            Ok(value) => Ok(Decoder::new(value)),
+            Err(e) => Err(ParseError(e)),
+        }
+    }
+
+    /// Creates a new decoder for the next CBOR item read from a `Reader`.
+    pub fn from_reader(rdr: &mut io::Reader) -> DecodeResult<Decoder> {
+        match from_reader(rdr) {
+            Ok(value) => Ok(Decoder::new(value)),
+            Err(e) => Err(ParseError(e)),
+        }
+    }
+}
+
+impl Decoder {
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+
+    fn read_unsigned(&mut self, max: u64) -> DecodeResult<u64> {
+        match self.pop() {
+            Unsigned(n) if n <= max => Ok(n),
+            value => Err(ExpectedError(format!("Unsigned up to {}", max), format!("{}", value)))
+        }
+    }
+
+    fn read_signed(&mut self, min: i64, max: i64) -> DecodeResult<i64> {
+        match self.pop() {
+            Unsigned(n) if n <= max as u64 => Ok(n as i64),
+            Negative(n) if n <= (-1 - min) as u64 => Ok(-1 - n as i64),
+            value => Err(ExpectedError(format!("Integer from {} to {}", min, max),
+                                       format!("{}", value)))
+        }
+    }
+}
+
+macro_rules! expect(
+    ($e:expr, Null) => ({
+        match $e {
+            Null => Ok(()),
+            other => Err(ExpectedError("Null".to_owned(), format!("{}", other)))
+        }
+    });
+    ($e:expr, $t:ident) => ({
+        match $e {
+            $t(v) => Ok(v),
+            other => Err(ExpectedError(stringify!($t).to_owned(), format!("{}", other)))
+        }
+    })
+)
+
+impl ::Decoder<DecoderError> for Decoder {
+    fn read_nil(&mut self) -> DecodeResult<()> {
+        debug!("read_nil");
+        try!(expect!(self.pop(), Null));
+        Ok(())
+    }
+
+    fn read_u64(&mut self)  -> DecodeResult<u64 > { self.read_unsigned(u64::MAX) }
+    fn read_u32(&mut self)  -> DecodeResult<u32 > {
+        Ok(try!(self.read_unsigned(::std::u32::MAX as u64)) as u32)
+    }
+    fn read_u16(&mut self)  -> DecodeResult<u16 > {
+        Ok(try!(self.read_unsigned(::std::u16::MAX as u64)) as u16)
+    }
+    fn read_u8 (&mut self)  -> DecodeResult<u8  > {
+        Ok(try!(self.read_unsigned(::std::u8::MAX as u64)) as u8)
+    }
+    fn read_uint(&mut self) -> DecodeResult<uint> {
+        Ok(try!(self.read_unsigned(::std::uint::MAX as u64)) as uint)
+    }
+
+    fn read_i64(&mut self) -> DecodeResult<i64> {
+        self.read_signed(::std::i64::MIN, ::std::i64::MAX)
+    }
+    fn read_i32(&mut self) -> DecodeResult<i32> {
+        Ok(try!(self.read_signed(::std::i32::MIN as i64, ::std::i32::MAX as i64)) as i32)
+    }
+    fn read_i16(&mut self) -> DecodeResult<i16> {
+        Ok(try!(self.read_signed(::std::i16::MIN as i64, ::std::i16::MAX as i64)) as i16)
+    }
+    fn read_i8 (&mut self) -> DecodeResult<i8 > {
+        Ok(try!(self.read_signed(::std::i8::MIN as i64, ::std::i8::MAX as i64)) as i8)
+    }
+    fn read_int(&mut self) -> DecodeResult<int> {
+        Ok(try!(self.read_signed(::std::int::MIN as i64, ::std::int::MAX as i64)) as int)
+    }
+
+    fn read_bool(&mut self) -> DecodeResult<bool> {
+        debug!("read_bool");
+        Ok(try!(expect!(self.pop(), Boolean)))
+    }
+
+    fn read_f64(&mut self) -> DecodeResult<f64> {
+        debug!("read_f64");
+        match self.pop() {
+            Float(f) => Ok(f),
+            // Integers are accepted too, since other encoders may well write
+            // floats with an integral value as integers.
+            Unsigned(n) => Ok(n as f64),
+            Negative(n) => Ok(-1.0 - n as f64),
+            value => Err(ExpectedError("Float".to_owned(), format!("{}", value)))
+        }
+    }
+
+    fn read_f32(&mut self) -> DecodeResult<f32> { Ok(try!(self.read_f64()) as f32) }
+
+    fn read_char(&mut self) -> DecodeResult<char> {
+        let s = try!(self.read_str());
+        {
+            let mut it = s.chars();
+            match (it.next(), it.next()) {
+                // exactly one character
+                (Some(c), None) => return Ok(c),
+                _ => ()
+            }
+        }
+        Err(ExpectedError("single character string".to_owned(), format!("{}", Text(s))))
+    }
+
+    fn read_str(&mut self) -> DecodeResult<~str> {
+        debug!("read_str");
+        Ok(try!(expect!(self.pop(), Text)))
+    }
+
+    fn read_enum<T>(&mut self,
+                    name: &str,
+                    f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_enum({})", name);
+        f(self)
+    }
+
+    fn read_enum_variant<T>(&mut self,
+                            names: &[&str],
+                            f: |&mut Decoder, uint| -> DecodeResult<T>)
+                            -> DecodeResult<T> {
+        debug!("read_enum_variant(names={:?})", names);
+        let name = match self.pop() {
+            Text(s) => s,
+            Array(items) => {
+                let mut items = items.move_iter();
+                let name = match items.next() {
+                    Some(Text(s)) => s,
+                    Some(val) => return Err(ExpectedError("Text".to_owned(), format!("{}", val))),
+                    None => return Err(MissingFieldError("variant".to_owned()))
+                };
+                for field in items.rev() {
+                    self.stack.push(field);
+                }
+                name
+            }
+            value => return Err(ExpectedError("Text or Array".to_owned(), format!("{}", value)))
+        };
+        let idx = match names.iter().position(|n| str::eq_slice(*n, name)) {
+            Some(idx) => idx,
+            None => return Err(UnknownVariantError(name))
+        };
+        f(self, idx)
+    }
+
+    fn read_enum_variant_arg<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
+                                -> DecodeResult<T> {
+        debug!("read_enum_variant_arg(idx={})", idx);
+        f(self)
+    }
+
+    fn read_enum_struct_variant<T>(&mut self,
+                                   names: &[&str],
+                                   f: |&mut Decoder, uint| -> DecodeResult<T>)
+                                   -> DecodeResult<T> {
+        debug!("read_enum_struct_variant(names={:?})", names);
+        self.read_enum_variant(names, f)
+    }
+
+    fn read_enum_struct_variant_field<T>(&mut self,
+                                         name: &str,
+                                         idx: uint,
+                                         f: |&mut Decoder| -> DecodeResult<T>)
+                                         -> DecodeResult<T> {
+        debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
+        self.read_enum_variant_arg(idx, f)
+    }
+
+    fn read_struct<T>(&mut self,
+                      name: &str,
+                      len: uint,
+                      f: |&mut Decoder| -> DecodeResult<T>)
+                      -> DecodeResult<T> {
+        debug!("read_struct(name={}, len={})", name, len);
+        let value = try!(f(self));
+        self.pop();
+        Ok(value)
+    }
+
+    fn read_struct_field<T>(&mut self,
+                            name: &str,
+                            idx: uint,
+                            f: |&mut Decoder| -> DecodeResult<T>)
+                            -> DecodeResult<T> {
+        debug!("read_struct_field(name={}, idx={})", name, idx);
+        let mut pairs = try!(expect!(self.pop(), Map));
+
+        let pos = pairs.iter().position(|&(ref key, _)| {
+            key.as_text() == Some(name)
+        });
+        let value = match pos {
+            None => return Err(MissingFieldError(name.to_owned())),
+            Some(pos) => {
+                let (_, value) = pairs.swap_remove(pos).unwrap();
+                self.stack.push(value);
+                try!(f(self))
+            }
+        };
+        self.stack.push(Map(pairs));
+        Ok(value)
+    }
+
+    fn read_tuple<T>(&mut self, f: |&mut Decoder, uint| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_tuple()");
+        self.read_seq(f)
+    }
+
+    fn read_tuple_arg<T>(&mut self,
+                         idx: uint,
+                         f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_tuple_arg(idx={})", idx);
+        self.read_seq_elt(idx, f)
+    }
+
+    fn read_tuple_struct<T>(&mut self,
+                            name: &str,
+                            f: |&mut Decoder, uint| -> DecodeResult<T>)
+                            -> DecodeResult<T> {
+        debug!("read_tuple_struct(name={})", name);
+        self.read_tuple(f)
+    }
+
+    fn read_tuple_struct_arg<T>(&mut self,
+                                idx: uint,
+                                f: |&mut Decoder| -> DecodeResult<T>)
+                                -> DecodeResult<T> {
+        debug!("read_tuple_struct_arg(idx={})", idx);
+        self.read_tuple_arg(idx, f)
+    }
+
+    fn read_option<T>(&mut self, f: |&mut Decoder, bool| -> DecodeResult<T>) -> DecodeResult<T> {
+        match self.pop() {
+            Null | Undefined => f(self, false),
+            value => { self.stack.push(value); f(self, true) }
+        }
+    }
+
+    fn read_seq<T>(&mut self, f: |&mut Decoder, uint| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_seq()");
+        let items = match self.pop() {
+            Array(items) => items,
+            // Byte strings are read as sequences of integers, so that they
+            // can be decoded into vectors of `u8`.
+            Bytes(bytes) => bytes.move_iter().map(|b| Unsigned(b as u64)).collect(),
+            value => return Err(ExpectedError("Array".to_owned(), format!("{}", value)))
+        };
+        let len = items.len();
+        for v in items.move_iter().rev() {
+            self.stack.push(v);
+        }
+        f(self, len)
+    }
+
+    fn read_seq_elt<T>(&mut self,
+                       idx: uint,
+                       f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_seq_elt(idx={})", idx);
+        f(self)
+    }
+
+    fn read_map<T>(&mut self, f: |&mut Decoder, uint| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("read_map()");
+        let pairs = try!(expect!(self.pop(), Map));
+        let len = pairs.len();
+        for (key, value) in pairs.move_iter().rev() {
+            self.stack.push(value);
+            self.stack.push(key);
+        }
+        f(self, len)
+    }
+
+    fn read_map_elt_key<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
+                           -> DecodeResult<T> {
+        debug!("read_map_elt_key(idx={})", idx);
+        f(self)
+    }
+
+    fn read_map_elt_val<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
+                           -> DecodeResult<T> {
+        debug!("read_map_elt_val(idx={})", idx);
+        f(self)
+    }
+
+    fn read_struct_field_opt<T>(&mut self,
+                                name: &str,
+                                idx: uint,
+                                f: |&mut Decoder| -> DecodeResult<T>)
+                                -> DecodeResult<Option<T>> {
+        debug!("read_struct_field_opt(name={}, idx={})", name, idx);
+        let mut pairs = try!(expect!(self.pop(), Map));
+
+        let pos = pairs.iter().position(|&(ref key, _)| {
+            key.as_text() == Some(name)
+        });
+        let value = match pos {
+            None => None,
+            Some(pos) => {
+                let (_, value) = pairs.swap_remove(pos).unwrap();
+                self.stack.push(value);
+                Some(try!(f(self)))
+            }
+        };
+        self.stack.push(Map(pairs));
+        Ok(value)
+    }
+
+    fn try_read<T>(&mut self, f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("try_read()");
+        let value = self.pop();
+        let len = self.stack.len();
+        self.stack.push(value.clone());
+        match f(self) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                self.stack.truncate(len);
+                self.stack.push(value);
+                Err(e)
+            }
+        }
+    }
+
+    fn error(&mut self, err: &str) -> DecoderError {
+        ApplicationError(err.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Encodable, Decodable};
+    use super::{Encoder, Decoder, Value, Unsigned, Negative, Bytes, Text, Array,
+                Map, Tag, Float, Boolean, Null, Undefined, Simple, from_bytes,
+                from_reader, ParseError, ExpectedError, MissingFieldError,
+                UnknownVariantError, DecodeResult, DecoderError, SyntaxError,
+                EOFWhileParsingValue, InvalidAdditionalInfo, InvalidSimpleValue,
+                InvalidChunk, UnexpectedBreak, TrailingBytes, NotUtf8};
+    use hex::{FromHex, ToHex};
+    use std::fmt::Show;
+    use std::io::MemReader;
+    use std::io;
+    use std::{i8, i16, i32, i64, u8, u16, u32, u64, f64};
+    use collections::{HashMap, TreeMap};
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    enum Animal {
+        Dog,
+        Frog(~str, int)
+    }
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    struct Inner {
+        a: (),
+        b: uint,
+        c: ~[~str],
+    }
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    struct Outer {
+        inner: ~[Inner],
+    }
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    enum Shape {
+        Circle { radius: f64 },
+        Rect { width: f32, height: f32 },
+    }
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    struct Pair(Option<~str>, (char, bool));
+
+    #[deriving(Eq, Encodable, Decodable, Show)]
+    struct Unit;
+
+    fn hex(s: &str) -> Vec<u8> {
+        Vec::from_slice(s.from_hex().unwrap())
+    }
+
+    fn encode<'a, T: Encodable<Encoder<'a>, io::IoError>>(value: &T) -> ~str {
+        Encoder::buffer_encode(value).as_slice().to_hex()
+    }
+
+    fn roundtrip<'a, T: Eq + Show + Encodable<Encoder<'a>, io::IoError>
+                                + Decodable<Decoder, DecoderError>>(value: T) {
+        let bytes = Encoder::buffer_encode(&value);
+        let mut decoder = Decoder::from_bytes(bytes.as_slice()).unwrap();
+        let decoded: T = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_rfc_examples() {
+        // Examples from appendix A of RFC 7049
+        let examples = [
+            ("00", Unsigned(0)),
+            ("17", Unsigned(23)),
+            ("1818", Unsigned(24)),
+            ("1903e8", Unsigned(1000)),
+            ("1a000f4240", Unsigned(1000000)),
+            ("1b000000e8d4a51000", Unsigned(1000000000000)),
+            ("1bffffffffffffffff", Unsigned(u64::MAX)),
+            ("3bffffffffffffffff", Negative(u64::MAX)),
+            ("20", Negative(0)),
+            ("3863", Negative(99)),
+            ("3903e7", Negative(999)),
+            ("f4", Boolean(false)),
+            ("f5", Boolean(true)),
+            ("f6", Null),
+            ("f7", Undefined),
+            ("f0", Simple(16)),
+            ("f8ff", Simple(255)),
+            ("c11a514b67b0", Tag(1, box Unsigned(1363896240))),
+            ("4401020304", Bytes(vec!(1, 2, 3, 4))),
+            ("60", Text("".to_owned())),
+            ("6449455446", Text("IETF".to_owned())),
+            ("62c3bc", Text("\u00fc".to_owned())),
+            ("80", Array(vec!())),
+            ("83010203", Array(vec!(Unsigned(1), Unsigned(2), Unsigned(3)))),
+            ("a201020304", Map(vec!((Unsigned(1), Unsigned(2)),
+                                    (Unsigned(3), Unsigned(4))))),
+            ("a26161016162820203",
+             Map(vec!((Text("a".to_owned()), Unsigned(1)),
+                      (Text("b".to_owned()), Array(vec!(Unsigned(2), Unsigned(3))))))),
+        ];
+        for &(ref bytes, ref value) in examples.iter() {
+            assert_eq!(from_bytes(hex(*bytes).as_slice()).unwrap(), *value);
+            assert_eq!(value.to_bytes().as_slice().to_hex(), (*bytes).to_owned());
+        }
+    }
+
+    #[test]
+    fn test_rfc_float_examples() {
+        let examples = [
+            ("f90000", 0.0),
+            ("f93c00", 1.0),
+            ("f93e00", 1.5),
+            ("f97bff", 65504.0),
+            ("f90001", 5.960464477539063e-8),
+            ("f90400", 0.00006103515625),
+            ("f9c400", -4.0),
+            ("fa47c35000", 100000.0),
+            ("fa7f7fffff", 3.4028234663852886e+38),
+            ("fb3ff199999999999a", 1.1),
+            ("fb7e37e43c8800759c", 1.0e+300),
+            ("fbc010666666666666", -4.1),
+            ("f97c00", f64::INFINITY),
+            ("f9fc00", f64::NEG_INFINITY),
+        ];
+        for &(bytes, value) in examples.iter() {
+            assert_eq!(from_bytes(hex(bytes).as_slice()).unwrap(), Float(value));
+        }
+        match from_bytes(hex("f97e00").as_slice()).unwrap() {
+            Float(f) => assert!(f.is_nan()),
+            value => fail!("expected NaN, found {}", value),
+        }
+    }
+
+    #[test]
+    fn test_indefinite_lengths() {
+        let examples = [
+            ("5f42010243030405ff", Bytes(vec!(1, 2, 3, 4, 5))),
+            ("7f657374726561646d696e67ff", Text("streaming".to_owned())),
+            ("9fff", Array(vec!())),
+            ("9f018202039f0405ffff",
+             Array(vec!(Unsigned(1),
+                        Array(vec!(Unsigned(2), Unsigned(3))),
+                        Array(vec!(Unsigned(4), Unsigned(5)))))),
+            ("bf61610161629f0203ffff",
+             Map(vec!((Text("a".to_owned()), Unsigned(1)),
+                      (Text("b".to_owned()), Array(vec!(Unsigned(2), Unsigned(3))))))),
+        ];
+        for &(bytes, ref value) in examples.iter() {
+            assert_eq!(from_bytes(hex(bytes).as_slice()).unwrap(), *value);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let errors = [
+            ("", EOFWhileParsingValue, 0),
+            ("19ff", EOFWhileParsingValue, 2),
+            ("830102", EOFWhileParsingValue, 3),
+            ("644945", EOFWhileParsingValue, 1),
+            ("1c", InvalidAdditionalInfo, 1),
+            ("3f", InvalidAdditionalInfo, 1),
+            ("fc", InvalidAdditionalInfo, 1),
+            ("f818", InvalidSimpleValue, 2),
+            ("ff", UnexpectedBreak, 1),
+            ("8201ff", UnexpectedBreak, 3),
+            ("bf01ff", UnexpectedBreak, 3),
+            ("5f6161ff", InvalidChunk, 2),
+            ("5f5fffff", InvalidChunk, 2),
+            ("62c328", NotUtf8, 3),
+            ("0000", TrailingBytes, 1),
+        ];
+        for &(bytes, code, offset) in errors.iter() {
+            assert_eq!(from_bytes(hex(bytes).as_slice()), Err(SyntaxError(code, offset)));
+        }
+    }
+
+    #[test]
+    fn test_bogus_length() {
+        // a byte string claiming to be 2^64 - 1 bytes long
+        assert_eq!(from_bytes(hex("5bffffffffffffffff00").as_slice()),
+                   Err(SyntaxError(EOFWhileParsingValue, 9)));
+    }
+
+    #[test]
+    fn test_from_reader() {
+        // items may follow each other in a stream
+        let mut rdr = MemReader::new(hex("0163666f6ff6"));
+        let rdr = &mut rdr as &mut io::Reader;
+        assert_eq!(from_reader(rdr), Ok(Unsigned(1)));
+        let mut decoder = Decoder::from_reader(rdr).unwrap();
+        let s: ~str = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(s, "foo".to_owned());
+        assert_eq!(from_reader(rdr), Ok(Null));
+        assert_eq!(from_reader(rdr), Err(SyntaxError(EOFWhileParsingValue, 0)));
+    }
+
+    #[test]
+    fn test_show() {
+        let value = Map(vec!(
+            (Text("a\n".to_owned()), Array(vec!(Unsigned(1), Negative(1), Float(1.5)))),
+            (Unsigned(2), Tag(24, box Bytes(vec!(1, 0xff)))),
+            (Null, Undefined),
+            (Boolean(true), Simple(16)),
+            (Negative(u64::MAX), Text("".to_owned())),
+        ));
+        assert_eq!(format!("{}", value),
+                   "{\"a\\n\": [1, -2, 1.5], 2: 24(h'01ff'), null: undefined, \
+                    true: simple(16), -18446744073709551616: \"\"}".to_owned());
+    }
+
+    #[test]
+    fn test_write_primitives() {
+        assert_eq!(encode(&()), "f6".to_owned());
+        assert_eq!(encode(&true), "f5".to_owned());
+        assert_eq!(encode(&false), "f4".to_owned());
+        assert_eq!(encode(&0u), "00".to_owned());
+        assert_eq!(encode(&500u16), "1901f4".to_owned());
+        assert_eq!(encode(&-1i8), "20".to_owned());
+        assert_eq!(encode(&-500i), "3901f3".to_owned());
+        assert_eq!(encode(&i64::MIN), "3b7fffffffffffffff".to_owned());
+        assert_eq!(encode(&1.5f32), "fa3fc00000".to_owned());
+        assert_eq!(encode(&-4.1f64), "fbc010666666666666".to_owned());
+        assert_eq!(encode(&'x'), "6178".to_owned());
+        assert_eq!(encode(&"IETF".to_owned()), "6449455446".to_owned());
+        assert_eq!(encode(&~[1u, 2, 3]), "83010203".to_owned());
+    }
+
+    #[test]
+    fn test_write_enum() {
+        assert_eq!(encode(&Dog), "63446f67".to_owned());
+        assert_eq!(encode(&Frog("Henry".to_owned(), 349)),
+                   "83 6446726f67 6548656e7279 19015d".replace(" ", ""));
+    }
+
+    #[test]
+    fn test_write_struct() {
+        let value = Inner { a: (), b: 1, c: ~["x".to_owned()] };
+        // {"a": null, "b": 1, "c": ["x"]}
+        assert_eq!(encode(&value), "a3 6161f6 616201 61638161 78".replace(" ", ""));
+    }
+
+    #[test]
+    fn test_write_some_none() {
+        assert_eq!(encode(&Some("jodhpurs".to_owned())), "68 6a6f646870757273".replace(" ", ""));
+        let none: Option<~str> = None;
+        assert_eq!(encode(&none), "f6".to_owned());
+    }
+
+    #[test]
+    fn test_roundtrip_primitives() {
+        roundtrip(());
+        roundtrip(true);
+        roundtrip(false);
+        roundtrip(u8::MAX);
+        roundtrip(u16::MAX);
+        roundtrip(u32::MAX);
+        roundtrip(u64::MAX);
+        roundtrip(12345u);
+        roundtrip(i8::MIN);
+        roundtrip(i16::MIN);
+        roundtrip(i32::MIN);
+        roundtrip(i64::MIN);
+        roundtrip(i64::MAX);
+        roundtrip(-12345i);
+        roundtrip(0.25f32);
+        roundtrip(-1.0e300f64);
+        roundtrip(f64::INFINITY);
+        roundtrip('\u00e9');
+        roundtrip("".to_owned());
+        roundtrip("b\u00e4ckst\u00f8ver".to_owned());
+    }
+
+    #[test]
+    fn test_roundtrip_compound() {
+        roundtrip(Dog);
+        roundtrip(Frog("Henry".to_owned(), -349));
+        roundtrip(Circle { radius: 2.5 });
+        roundtrip(Rect { width: 1.0, height: 0.5 });
+        roundtrip(Pair(None, ('a', true)));
+        roundtrip(Pair(Some("b".to_owned()), ('\n', false)));
+        roundtrip(Unit);
+        roundtrip(Some(Some(1u)));
+        roundtrip(~[Some(1u), None]);
+        roundtrip(vec!(vec!(1u8, 2), vec!()));
+        roundtrip((1u, "two".to_owned(), ~[3.0f64]));
+        roundtrip(Outer {
+            inner: ~[
+                Inner { a: (), b: 2, c: ~["abc".to_owned(), "xyz".to_owned()] }
+            ]
+        });
+    }
+
+    #[test]
+    fn test_roundtrip_maps() {
+        let mut tree = TreeMap::new();
+        tree.insert("a".to_owned(), Dog);
+        tree.insert("b".to_owned(), Frog("Henry".to_owned(), 349));
+        roundtrip(tree);
+
+        let mut hm: HashMap<uint, bool> = HashMap::new();
+        hm.insert(1, true);
+        hm.insert(1000, false);
+        roundtrip(hm);
+
+        let mut keys = TreeMap::new();
+        keys.insert((1i, 'x'), ~[Unit]);
+        roundtrip(keys);
+    }
+
+    #[test]
+    fn test_roundtrip_value() {
+        let value = Array(vec!(Unsigned(1), Negative(7), Text("x".to_owned()), Null,
+                               Map(vec!((Boolean(true), Float(0.5))))));
+        let bytes = value.to_bytes();
+        assert_eq!(from_bytes(bytes.as_slice()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_decode_struct_members_out_of_order() {
+        // {"c": [], "b": 3, "a": null, "d": "ignored"}
+        let bytes = hex("a4616380616203 6161f6 616467 69676e6f726564".replace(" ", "").as_slice());
+        let mut decoder = Decoder::from_bytes(bytes.as_slice()).unwrap();
+        let v: Inner = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(v, Inner { a: (), b: 3, c: ~[] });
+    }
+
+    #[test]
+    fn test_decode_bytes_as_vec() {
+        let mut decoder = Decoder::from_bytes(hex("43010203").as_slice()).unwrap();
+        let v: Vec<u8> = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(v, vec!(1, 2, 3));
+    }
+
+    #[test]
+    fn test_decode_floats_from_integers() {
+        let mut decoder = Decoder::new(Negative(1));
+        let v: f64 = Decodable::decode(&mut decoder).unwrap();
+        assert_eq!(v, -2.0);
+    }
+
+    fn check_err<T: Decodable<Decoder, DecoderError>>(value: Value, expected: DecoderError) {
+        let res: DecodeResult<T> = Decodable::decode(&mut Decoder::new(value.clone()));
+        match res {
+            Ok(_) => fail!("`{}` decoded ok, expecting error `{}`", value, expected),
+            Err(e) => assert_eq!(e, expected),
+        }
+    }
+
+    #[deriving(Decodable)]
+    struct DecodeStruct {
+        x: f64,
+        y: bool,
+        z: ~str,
+        w: ~[DecodeStruct]
+    }
+    #[deriving(Decodable)]
+    enum DecodeEnum {
+        A(f64),
+        B(~str)
+    }
+
+    fn decode_struct(x: Value, y: Value, z: Value, w: Option<Value>) -> Value {
+        let mut pairs = vec!((Text("x".to_owned()), x),
+                             (Text("y".to_owned()), y),
+                             (Text("z".to_owned()), z));
+        match w {
+            Some(w) => pairs.push((Text("w".to_owned()), w)),
+            None => {}
+        }
+        Map(pairs)
+    }
+
+    #[test]
+    fn test_decode_errors_struct() {
+        let empty = || Text("".to_owned());
+        check_err::<DecodeStruct>(Array(vec!()),
+                                  ExpectedError("Map".to_owned(), "[]".to_owned()));
+        check_err::<DecodeStruct>(decode_struct(Boolean(true), Boolean(true), empty(),
+                                                Some(Array(vec!()))),
+                                  ExpectedError("Float".to_owned(), "true".to_owned()));
+        check_err::<DecodeStruct>(decode_struct(Unsigned(1), Array(vec!()), empty(),
+                                                Some(Array(vec!()))),
+                                  ExpectedError("Boolean".to_owned(), "[]".to_owned()));
+        check_err::<DecodeStruct>(decode_struct(Unsigned(1), Boolean(true), Map(vec!()),
+                                                Some(Array(vec!()))),
+                                  ExpectedError("Text".to_owned(), "{}".to_owned()));
+        check_err::<DecodeStruct>(decode_struct(Unsigned(1), Boolean(true), empty(),
+                                                Some(Null)),
+                                  ExpectedError("Array".to_owned(), "null".to_owned()));
+        check_err::<DecodeStruct>(decode_struct(Unsigned(1), Boolean(true), empty(), None),
+                                  MissingFieldError("w".to_owned()));
+    }
+
+    #[test]
+    fn test_decode_errors_enum() {
+        check_err::<DecodeEnum>(Array(vec!()),
+                                MissingFieldError("variant".to_owned()));
+        check_err::<DecodeEnum>(Array(vec!(Unsigned(1))),
+                                ExpectedError("Text".to_owned(), "1".to_owned()));
+        check_err::<DecodeEnum>(Map(vec!()),
+                                ExpectedError("Text or Array".to_owned(), "{}".to_owned()));
+        check_err::<DecodeEnum>(Array(vec!(Text("C".to_owned()))),
+                                UnknownVariantError("C".to_owned()));
+    }
+
+    #[test]
+    fn test_decode_errors_integers() {
+        check_err::<u8>(Unsigned(256),
+                        ExpectedError("Unsigned up to 255".to_owned(), "256".to_owned()));
+        check_err::<uint>(Negative(0),
+                          ExpectedError(format!("Unsigned up to {}", ::std::uint::MAX),
+                                        "-1".to_owned()));
+        check_err::<i8>(Negative(128),
+                        ExpectedError("Integer from -128 to 127".to_owned(), "-129".to_owned()));
+        check_err::<i64>(Negative(u64::MAX),
+                         ExpectedError(format!("Integer from {} to {}", i64::MIN, i64::MAX),
+                                       "-18446744073709551616".to_owned()));
+        check_err::<char>(Text("ab".to_owned()),
+                          ExpectedError("single character string".to_owned(),
+                                        "\"ab\"".to_owned()));
+    }
+
+    #[test]
+    fn test_decode_parse_error() {
+        match Decoder::from_bytes(hex("83").as_slice()) {
+            Err(ParseError(SyntaxError(EOFWhileParsingValue, 1))) => {}
+            _ => fail!("expected a parse error"),
+        }
+    }
+}
@@ -36,13 +36,12 @@ impl<
 
 impl<E, D:Decoder<E>,T:Decodable<D, E>> Decodable<D, E> for DList<T> {
     fn decode(d: &mut D) -> Result<DList<T>, E> {
-        d.read_seq(|d, len| {
-            let mut list = DList::new();
-            for i in range(0u, len) {
-                list.push_back(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(list)
-        })
+        let mut list = DList::new();
+        try!(d.read_seq_elts(|d, _| {
+            list.push_back(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(list)
     }
 }
 
@@ -63,13 +62,12 @@ impl<
 
 impl<E, D:Decoder<E>,T:Decodable<D, E>> Decodable<D, E> for RingBuf<T> {
     fn decode(d: &mut D) -> Result<RingBuf<T>, E> {
-        d.read_seq(|d, len| {
-            let mut deque: RingBuf<T> = RingBuf::new();
-            for i in range(0u, len) {
-                deque.push_back(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(deque)
-        })
+        let mut deque: RingBuf<T> = RingBuf::new();
+        try!(d.read_seq_elts(|d, _| {
+            deque.push_back(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(deque)
     }
 }
 
@@ -99,15 +97,14 @@ impl<
     V: Decodable<D, E> + Eq
 > Decodable<D, E> for TreeMap<K, V> {
     fn decode(d: &mut D) -> Result<TreeMap<K, V>, E> {
-        d.read_map(|d, len| {
-            let mut map = TreeMap::new();
-            for i in range(0u, len) {
-                let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
-                let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
-                map.insert(key, val);
-            }
-            Ok(map)
-        })
+        let mut map = TreeMap::new();
+        try!(d.read_map_elts(|d, i| {
+            let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
+            let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
+            map.insert(key, val);
+            Ok(())
+        }));
+        Ok(map)
     }
 }
 
@@ -134,13 +131,12 @@ impl<
     T: Decodable<D, E> + Eq + TotalOrd
 > Decodable<D, E> for TreeSet<T> {
     fn decode(d: &mut D) -> Result<TreeSet<T>, E> {
-        d.read_seq(|d, len| {
-            let mut set = TreeSet::new();
-            for i in range(0u, len) {
-                set.insert(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(set)
-        })
+        let mut set = TreeSet::new();
+        try!(d.read_seq_elts(|d, _| {
+            set.insert(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(set)
     }
 }
 
@@ -205,16 +201,14 @@ impl<
     H: Hasher<S> + Default
 > Decodable<D, E> for HashMap<K, V, H> {
     fn decode(d: &mut D) -> Result<HashMap<K, V, H>, E> {
-        d.read_map(|d, len| {
-            let hasher = Default::default();
-            let mut map = HashMap::with_capacity_and_hasher(len, hasher);
-            for i in range(0u, len) {
-                let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
-                let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
-                map.insert(key, val);
-            }
-            Ok(map)
-        })
+        let mut map = HashMap::with_hasher(Default::default());
+        try!(d.read_map_elts(|d, i| {
+            let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
+            let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
+            map.insert(key, val);
+            Ok(())
+        }));
+        Ok(map)
     }
 }
 
@@ -245,13 +239,12 @@ impl<
     H: Hasher<S> + Default
 > Decodable<D, E> for HashSet<T, H> {
     fn decode(d: &mut D) -> Result<HashSet<T, H>, E> {
-        d.read_seq(|d, len| {
-            let mut set = HashSet::with_capacity_and_hasher(len, Default::default());
-            for i in range(0u, len) {
-                set.insert(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(set)
-        })
+        let mut set = HashSet::with_hasher(Default::default());
+        try!(d.read_seq_elts(|d, _| {
+            set.insert(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(set)
     }
 }
 
@@ -277,15 +270,14 @@ impl<
     V: Decodable<D, E>
 > Decodable<D, E> for TrieMap<V> {
     fn decode(d: &mut D) -> Result<TrieMap<V>, E> {
-        d.read_map(|d, len| {
-            let mut map = TrieMap::new();
-            for i in range(0u, len) {
-                let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
-                let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
-                map.insert(key, val);
-            }
-            Ok(map)
-        })
+        let mut map = TrieMap::new();
+        try!(d.read_map_elts(|d, i| {
+            let key = try!(d.read_map_elt_key(i, |d| Decodable::decode(d)));
+            let val = try!(d.read_map_elt_val(i, |d| Decodable::decode(d)));
+            map.insert(key, val);
+            Ok(())
+        }));
+        Ok(map)
     }
 }
 
@@ -302,12 +294,11 @@ impl<E, S: Encoder<E>> Encodable<S, E> for TrieSet {
 
 impl<E, D: Decoder<E>> Decodable<D, E> for TrieSet {
     fn decode(d: &mut D) -> Result<TrieSet, E> {
-        d.read_seq(|d, len| {
-            let mut set = TrieSet::new();
-            for i in range(0u, len) {
-                set.insert(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(set)
-        })
+        let mut set = TrieSet::new();
+        try!(d.read_seq_elts(|d, _| {
+            set.insert(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(set)
     }
 }
@@ -10,9 +10,12 @@
 
 #![allow(missing_doc)]
 
+use std::fmt;
 use std::io;
 use std::str;
 
+use DecoderPath;
+
 // Simple Extensible Binary Markup Language (ebml) reader and writer on a
 // cursor model. See the specification here:
 //     http://www.matroska.org/technical/specs/rfc/index.html
@@ -26,16 +29,19 @@ pub struct Doc<'a> {
 }
 
 impl<'doc> Doc<'doc> {
-    pub fn get<'a>(&'a self, tag: uint) -> Doc<'a> {
+    pub fn get<'a>(&'a self, tag: uint) -> reader::DecodeResult<Doc<'a>> {
         reader::get_doc(*self, tag)
     }
 
-    pub fn as_str_slice<'a>(&'a self) -> &'a str {
-        str::from_utf8(self.data.slice(self.start, self.end)).unwrap()
+    pub fn as_str_slice<'a>(&'a self) -> reader::DecodeResult<&'a str> {
+        match str::from_utf8(self.data.slice(self.start, self.end)) {
+            Some(s) => Ok(s),
+            None => Err(Expected(format!("invalid UTF-8 in document at {:#x}", self.start))),
+        }
     }
 
-    pub fn as_str(&self) -> ~str {
-        self.as_str_slice().to_owned()
+    pub fn as_str(&self) -> reader::DecodeResult<~str> {
+        Ok(try!(self.as_str_slice()).to_owned())
     }
 }
 
@@ -77,11 +83,30 @@ pub enum EbmlEncoderTag {
     EsLabel, // Used only when debugging
 }
 
-#[deriving(Show)]
 pub enum Error {
     IntTooBig(uint),
     Expected(~str),
-    IoError(io::IoError)
+    IoError(io::IoError),
+    /// An error reported by a `Decodable` implementation through
+    /// `Decoder::error`.
+    ApplicationError(~str),
+    /// A strict decoder found a document which was never read, at this offset.
+    UnknownField(uint),
+    /// An error along with the path to the part of the value which caused it.
+    PathError(Box<Error>, DecoderPath),
+}
+
+impl fmt::Show for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntTooBig(n) => write!(f.buf, "integer too big: {}", n),
+            Expected(ref msg) => write!(f.buf, "{}", *msg),
+            IoError(ref e) => write!(f.buf, "{}", *e),
+            ApplicationError(ref msg) => write!(f.buf, "{}", *msg),
+            UnknownField(pos) => write!(f.buf, "unread data at offset {:#x}", pos),
+            PathError(ref e, ref path) => write!(f.buf, "{}: {}", *path, **e),
+        }
+    }
 }
 // --------------------------------------
 
@@ -94,28 +119,17 @@ pub mod reader {
     use std::io::extensions::u64_from_be_bytes;
 
     use serialize;
+    use {DecoderPath, PathElement, FieldElement, IndexElement, VariantElement};
 
     use super::{ EsVec, EsMap, EsEnum, EsVecLen, EsVecElt, EsMapLen, EsMapKey,
         EsEnumVid, EsU64, EsU32, EsU16, EsU8, EsInt, EsI64, EsI32, EsI16, EsI8,
         EsBool, EsF64, EsF32, EsChar, EsStr, EsMapVal, EsEnumBody, EsUint,
         EsOpaque, EsLabel, EbmlEncoderTag, Doc, TaggedDoc, Error, IntTooBig,
-        Expected };
+        Expected, ApplicationError, UnknownField, PathError };
 
     pub type DecodeResult<T> = Result<T, Error>;
     // ebml reading
 
-    macro_rules! try_or(
-        ($e:expr, $r:expr) => (
-            match $e {
-                Ok(e) => e,
-                Err(e) => {
-                    debug!("ignored error: {}", e);
-                    return $r
-                }
-            }
-        )
-    )
-
     pub struct Res {
         pub val: uint,
         pub next: uint
@@ -123,35 +137,37 @@ pub mod reader {
 
     #[inline(never)]
     fn vuint_at_slow(data: &[u8], start: uint) -> DecodeResult<Res> {
+        if start >= data.len() {
+            return Err(Expected(format!("truncated EBML, expected a vuint at {:#x}", start)));
+        }
         let a = data[start];
-        if a & 0x80u8 != 0u8 {
-            return Ok(Res {val: (a & 0x7fu8) as uint, next: start + 1u});
-        }
-        if a & 0x40u8 != 0u8 {
-            return Ok(Res {val: ((a & 0x3fu8) as uint) << 8u |
-                        (data[start + 1u] as uint),
-                    next: start + 2u});
-        }
-        if a & 0x20u8 != 0u8 {
-            return Ok(Res {val: ((a & 0x1fu8) as uint) << 16u |
-                        (data[start + 1u] as uint) << 8u |
-                        (data[start + 2u] as uint),
-                    next: start + 3u});
-        }
-        if a & 0x10u8 != 0u8 {
-            return Ok(Res {val: ((a & 0x0fu8) as uint) << 24u |
-                        (data[start + 1u] as uint) << 16u |
-                        (data[start + 2u] as uint) << 8u |
-                        (data[start + 3u] as uint),
-                    next: start + 4u});
-        }
-        Err(IntTooBig(a as uint))
+        let len = if a & 0x80u8 != 0u8 {
+            1u
+        } else if a & 0x40u8 != 0u8 {
+            2u
+        } else if a & 0x20u8 != 0u8 {
+            3u
+        } else if a & 0x10u8 != 0u8 {
+            4u
+        } else {
+            return Err(IntTooBig(a as uint));
+        };
+        if data.len() - start < len {
+            return Err(Expected(format!("truncated EBML, vuint at {:#x} needs {} bytes",
+                                        start, len)));
+        }
+        // Clear the length marker, which is the highest set bit of `a`.
+        let mut val = (a as uint) & ((0x100u >> len) - 1);
+        for i in range(1u, len) {
+            val = val << 8u | (data[start + i] as uint);
+        }
+        Ok(Res { val: val, next: start + len })
     }
 
     pub fn vuint_at(data: &[u8], start: uint) -> DecodeResult<Res> {
         use std::mem::from_be32;
 
-        if data.len() - start < 4 {
+        if start >= data.len() || data.len() - start < 4 {
             return vuint_at_slow(data, start);
         }
 
@@ -183,6 +199,10 @@ pub mod reader {
             let val = from_be32(*ptr);
 
             let i = (val >> 28u) as uint;
+            if i == 0 {
+                // Vuints longer than four bytes aren't supported.
+                return Err(IntTooBig((val >> 24u) as uint));
+            }
             let (shift, mask) = SHIFT_MASK_TABLE[i];
             Ok(Res {
                 val: ((val >> shift) & mask) as uint,
@@ -198,6 +218,11 @@ pub mod reader {
     pub fn doc_at<'a>(data: &'a [u8], start: uint) -> DecodeResult<TaggedDoc<'a>> {
         let elt_tag = try!(vuint_at(data, start));
         let elt_size = try!(vuint_at(data, elt_tag.next));
+        if data.len() - elt_size.next < elt_size.val {
+            return Err(Expected(format!("truncated EBML, document at {:#x} extends to {:#x} \
+                                         but the data ends at {:#x}", start,
+                                        elt_size.next + elt_size.val, data.len())));
+        }
         let end = elt_size.next + elt_size.val;
         Ok(TaggedDoc {
             tag: elt_tag.val,
@@ -205,112 +230,177 @@ pub mod reader {
         })
     }
 
-    pub fn maybe_get_doc<'a>(d: Doc<'a>, tg: uint) -> Option<Doc<'a>> {
+    // Reads the child of `d` at `pos`, which must lie within `d`.
+    fn child_at<'a>(d: Doc<'a>, pos: uint) -> DecodeResult<TaggedDoc<'a>> {
+        let child = try!(doc_at(d.data, pos));
+        if child.doc.end > d.end {
+            return Err(Expected(format!("invalid EBML, child extends to {:#x}, parent to {:#x}",
+                                        child.doc.end, d.end)));
+        }
+        Ok(child)
+    }
+
+    pub fn maybe_get_doc<'a>(d: Doc<'a>, tg: uint) -> DecodeResult<Option<Doc<'a>>> {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = try_or!(vuint_at(d.data, pos), None);
-            let elt_size = try_or!(vuint_at(d.data, elt_tag.next), None);
-            pos = elt_size.next + elt_size.val;
-            if elt_tag.val == tg {
-                return Some(Doc { data: d.data, start: elt_size.next,
-                                  end: pos });
+            let TaggedDoc { tag, doc } = try!(child_at(d, pos));
+            if tag == tg {
+                return Ok(Some(doc));
             }
+            pos = doc.end;
         }
-        None
+        Ok(None)
     }
 
-    pub fn get_doc<'a>(d: Doc<'a>, tg: uint) -> Doc<'a> {
-        match maybe_get_doc(d, tg) {
-            Some(d) => d,
-            None => {
-                error!("failed to find block with tag {}", tg);
-                fail!();
-            }
+    pub fn get_doc<'a>(d: Doc<'a>, tg: uint) -> DecodeResult<Doc<'a>> {
+        match try!(maybe_get_doc(d, tg)) {
+            Some(d) => Ok(d),
+            None => Err(Expected(format!("failed to find block with tag {}", tg))),
         }
     }
 
-    pub fn docs<'a>(d: Doc<'a>, it: |uint, Doc<'a>| -> bool) -> bool {
+    pub fn docs<'a>(d: Doc<'a>, it: |uint, Doc<'a>| -> bool) -> DecodeResult<bool> {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = try_or!(vuint_at(d.data, pos), false);
-            let elt_size = try_or!(vuint_at(d.data, elt_tag.next), false);
-            pos = elt_size.next + elt_size.val;
-            let doc = Doc { data: d.data, start: elt_size.next, end: pos };
-            if !it(elt_tag.val, doc) {
-                return false;
+            let TaggedDoc { tag, doc } = try!(child_at(d, pos));
+            if !it(tag, doc) {
+                return Ok(false);
             }
+            pos = doc.end;
         }
-        return true;
+        Ok(true)
     }
 
-    pub fn tagged_docs<'a>(d: Doc<'a>, tg: uint, it: |Doc<'a>| -> bool) -> bool {
+    pub fn tagged_docs<'a>(d: Doc<'a>, tg: uint, it: |Doc<'a>| -> bool) -> DecodeResult<bool> {
         let mut pos = d.start;
         while pos < d.end {
-            let elt_tag = try_or!(vuint_at(d.data, pos), false);
-            let elt_size = try_or!(vuint_at(d.data, elt_tag.next), false);
-            pos = elt_size.next + elt_size.val;
-            if elt_tag.val == tg {
-                let doc = Doc { data: d.data, start: elt_size.next,
-                                end: pos };
-                if !it(doc) {
-                    return false;
-                }
+            let TaggedDoc { tag, doc } = try!(child_at(d, pos));
+            if tag == tg && !it(doc) {
+                return Ok(false);
             }
+            pos = doc.end;
         }
-        return true;
+        Ok(true)
     }
 
     pub fn with_doc_data<'a, T>(d: Doc<'a>, f: |x: &'a [u8]| -> T) -> T {
         f(d.data.slice(d.start, d.end))
     }
 
+    fn check_len(d: Doc, len: uint) -> DecodeResult<()> {
+        if d.end - d.start != len {
+            return Err(Expected(format!("expected a {}-byte document at {:#x}, found {} bytes",
+                                        len, d.start, d.end - d.start)));
+        }
+        Ok(())
+    }
 
-    pub fn doc_as_u8(d: Doc) -> u8 {
-        assert_eq!(d.end, d.start + 1u);
-        d.data[d.start]
+    pub fn doc_as_u8(d: Doc) -> DecodeResult<u8> {
+        try!(check_len(d, 1u));
+        Ok(d.data[d.start])
     }
 
-    pub fn doc_as_u16(d: Doc) -> u16 {
-        assert_eq!(d.end, d.start + 2u);
-        u64_from_be_bytes(d.data, d.start, 2u) as u16
+    pub fn doc_as_u16(d: Doc) -> DecodeResult<u16> {
+        try!(check_len(d, 2u));
+        Ok(u64_from_be_bytes(d.data, d.start, 2u) as u16)
     }
 
-    pub fn doc_as_u32(d: Doc) -> u32 {
-        assert_eq!(d.end, d.start + 4u);
-        u64_from_be_bytes(d.data, d.start, 4u) as u32
+    pub fn doc_as_u32(d: Doc) -> DecodeResult<u32> {
+        try!(check_len(d, 4u));
+        Ok(u64_from_be_bytes(d.data, d.start, 4u) as u32)
     }
 
-    pub fn doc_as_u64(d: Doc) -> u64 {
-        assert_eq!(d.end, d.start + 8u);
-        u64_from_be_bytes(d.data, d.start, 8u)
+    pub fn doc_as_u64(d: Doc) -> DecodeResult<u64> {
+        try!(check_len(d, 8u));
+        Ok(u64_from_be_bytes(d.data, d.start, 8u))
     }
 
-    pub fn doc_as_i8(d: Doc) -> i8 { doc_as_u8(d) as i8 }
-    pub fn doc_as_i16(d: Doc) -> i16 { doc_as_u16(d) as i16 }
-    pub fn doc_as_i32(d: Doc) -> i32 { doc_as_u32(d) as i32 }
-    pub fn doc_as_i64(d: Doc) -> i64 { doc_as_u64(d) as i64 }
+    pub fn doc_as_i8(d: Doc) -> DecodeResult<i8> { Ok(try!(doc_as_u8(d)) as i8) }
+    pub fn doc_as_i16(d: Doc) -> DecodeResult<i16> { Ok(try!(doc_as_u16(d)) as i16) }
+    pub fn doc_as_i32(d: Doc) -> DecodeResult<i32> { Ok(try!(doc_as_u32(d)) as i32) }
+    pub fn doc_as_i64(d: Doc) -> DecodeResult<i64> { Ok(try!(doc_as_u64(d)) as i64) }
 
     pub struct Decoder<'a> {
         parent: Doc<'a>,
         pos: uint,
+        path: DecoderPath,
+        strict: bool,
     }
 
     pub fn Decoder<'a>(d: Doc<'a>) -> Decoder<'a> {
         Decoder {
             parent: d,
-            pos: d.start
+            pos: d.start,
+            path: DecoderPath::new(),
+            strict: false,
         }
     }
 
     impl<'doc> Decoder<'doc> {
+        /// Sets whether the decoder is strict. A strict decoder fails with
+        /// `UnknownField` if a document has data left over after its value
+        /// was decoded, instead of skipping it.
+        pub fn set_strict(&mut self, strict: bool) {
+            self.strict = strict;
+        }
+
+        // Decodes a part of the current value with `f`, attaching the path to
+        // the part to any error which doesn't have a path yet.
+        fn in_path<T>(&mut self, element: PathElement,
+                      f: |&mut Decoder<'doc>| -> DecodeResult<T>) -> DecodeResult<T> {
+            self.path.push(element);
+            let result = match f(self) {
+                Err(PathError(e, path)) => Err(PathError(e, path)),
+                Err(e) => Err(PathError(box e, self.path.clone())),
+                ok => ok,
+            };
+            self.path.pop();
+            result
+        }
+
+        // Decodes the contents of `doc` with `f`, checking in strict mode that
+        // all of them were read.
+        fn in_doc<T>(&mut self, doc: Doc<'doc>,
+                     f: |&mut Decoder<'doc>| -> DecodeResult<T>) -> DecodeResult<T> {
+            let (old_parent, old_pos) = (self.parent, self.pos);
+            self.parent = doc;
+            self.pos = doc.start;
+            let result = try!(f(self));
+            if self.strict && self.pos < self.parent.end {
+                return Err(UnknownField(self.pos));
+            }
+            self.parent = old_parent;
+            self.pos = old_pos;
+            Ok(result)
+        }
+
+        fn read_variant<T>(&mut self,
+                           names: &[&str],
+                           f: |&mut Decoder<'doc>, uint| -> DecodeResult<T>)
+                           -> DecodeResult<T> {
+            let idx = try!(self._next_uint(EsEnumVid));
+            debug!("  idx={}", idx);
+            let doc = try!(self.next_doc(EsEnumBody));
+            match names.get(idx) {
+                Some(&name) => {
+                    self.in_path(VariantElement(name.to_owned()), |d| {
+                        d.in_doc(doc, |d| f(d, idx))
+                    })
+                }
+                // Only `read_option` doesn't give the names, and checks the
+                // index itself.
+                None if names.is_empty() => self.in_doc(doc, |d| f(d, idx)),
+                None => Err(Expected(format!("invalid variant index {}", idx))),
+            }
+        }
+
         fn _check_label(&mut self, lbl: &str) -> DecodeResult<()> {
             if self.pos < self.parent.end {
-                let TaggedDoc { tag: r_tag, doc: r_doc } =
-                    try!(doc_at(self.parent.data, self.pos));
+                let TaggedDoc { tag: r_tag, doc: r_doc } = try!(child_at(self.parent, self.pos));
 
                 if r_tag == (EsLabel as uint) {
                     self.pos = r_doc.end;
-                    let str = r_doc.as_str_slice();
+                    let str = try!(r_doc.as_str_slice());
                     if lbl != str {
                         return Err(Expected(format!("Expected label {} but found {}", lbl, str)));
                     }
@@ -324,8 +414,7 @@ pub mod reader {
             if self.pos >= self.parent.end {
                 return Err(Expected(format!("no more documents in current node!")));
             }
-            let TaggedDoc { tag: r_tag, doc: r_doc } =
-                try!(doc_at(self.parent.data, self.pos));
+            let TaggedDoc { tag: r_tag, doc: r_doc } = try!(child_at(self.parent, self.pos));
             debug!("self.parent={}-{} self.pos={} r_tag={} r_doc={}-{}",
                    self.parent.start,
                    self.parent.end,
@@ -337,10 +426,6 @@ pub mod reader {
                 return Err(Expected(format!("expected EBML doc with tag {:?} but found tag {:?}",
                        exp_tag, r_tag)));
             }
-            if r_doc.end > self.parent.end {
-                return Err(Expected(format!("invalid EBML, child extends to {:#x}, parent to {:#x}",
-                      r_doc.end, self.parent.end)));
-            }
             self.pos = r_doc.end;
             Ok(r_doc)
         }
@@ -348,22 +433,28 @@ pub mod reader {
         fn push_doc<T>(&mut self, exp_tag: EbmlEncoderTag,
                        f: |&mut Decoder<'doc>| -> DecodeResult<T>) -> DecodeResult<T> {
             let d = try!(self.next_doc(exp_tag));
-            let old_parent = self.parent;
-            let old_pos = self.pos;
-            self.parent = d;
-            self.pos = d.start;
-            let r = try!(f(self));
-            self.parent = old_parent;
-            self.pos = old_pos;
-            Ok(r)
+            self.in_doc(d, f)
         }
 
         fn _next_uint(&mut self, exp_tag: EbmlEncoderTag) -> DecodeResult<uint> {
-            let r = doc_as_u32(try!(self.next_doc(exp_tag)));
+            let r = try!(doc_as_u32(try!(self.next_doc(exp_tag))));
             debug!("_next_uint exp_tag={:?} result={}", exp_tag, r);
             Ok(r as uint)
         }
 
+        // Reads the length of a sequence or map, checking that the current
+        // document has room for that many elements of `docs` documents each,
+        // so that a corrupted length can't cause a huge allocation.
+        fn _next_len(&mut self, exp_tag: EbmlEncoderTag, docs: uint) -> DecodeResult<uint> {
+            let len = try!(self._next_uint(exp_tag));
+            // The smallest document is two bytes long, a tag and a size.
+            if len > (self.parent.end - self.pos) / (2 * docs) {
+                return Err(Expected(format!("invalid EBML, length {} at {:#x} is too large",
+                                            len, self.pos)));
+            }
+            Ok(len)
+        }
+
         pub fn read_opaque<R>(&mut self,
                               op: |&mut Decoder<'doc>, Doc| -> DecodeResult<R>) -> DecodeResult<R> {
             let doc = try!(self.next_doc(EsOpaque));
@@ -383,12 +474,12 @@ pub mod reader {
     impl<'doc> serialize::Decoder<Error> for Decoder<'doc> {
         fn read_nil(&mut self) -> DecodeResult<()> { Ok(()) }
 
-        fn read_u64(&mut self) -> DecodeResult<u64> { Ok(doc_as_u64(try!(self.next_doc(EsU64)))) }
-        fn read_u32(&mut self) -> DecodeResult<u32> { Ok(doc_as_u32(try!(self.next_doc(EsU32)))) }
-        fn read_u16(&mut self) -> DecodeResult<u16> { Ok(doc_as_u16(try!(self.next_doc(EsU16)))) }
-        fn read_u8 (&mut self) -> DecodeResult<u8 > { Ok(doc_as_u8 (try!(self.next_doc(EsU8 )))) }
+        fn read_u64(&mut self) -> DecodeResult<u64> { doc_as_u64(try!(self.next_doc(EsU64))) }
+        fn read_u32(&mut self) -> DecodeResult<u32> { doc_as_u32(try!(self.next_doc(EsU32))) }
+        fn read_u16(&mut self) -> DecodeResult<u16> { doc_as_u16(try!(self.next_doc(EsU16))) }
+        fn read_u8 (&mut self) -> DecodeResult<u8 > { doc_as_u8 (try!(self.next_doc(EsU8 ))) }
         fn read_uint(&mut self) -> DecodeResult<uint> {
-            let v = doc_as_u64(try!(self.next_doc(EsUint)));
+            let v = try!(doc_as_u64(try!(self.next_doc(EsUint))));
             if v > (::std::uint::MAX as u64) {
                 Err(IntTooBig(v as uint))
             } else {
@@ -397,19 +488,19 @@ pub mod reader {
         }
 
         fn read_i64(&mut self) -> DecodeResult<i64> {
-            Ok(doc_as_u64(try!(self.next_doc(EsI64))) as i64)
+            doc_as_i64(try!(self.next_doc(EsI64)))
         }
         fn read_i32(&mut self) -> DecodeResult<i32> {
-            Ok(doc_as_u32(try!(self.next_doc(EsI32))) as i32)
+            doc_as_i32(try!(self.next_doc(EsI32)))
         }
         fn read_i16(&mut self) -> DecodeResult<i16> {
-            Ok(doc_as_u16(try!(self.next_doc(EsI16))) as i16)
+            doc_as_i16(try!(self.next_doc(EsI16)))
         }
         fn read_i8 (&mut self) -> DecodeResult<i8> {
-            Ok(doc_as_u8(try!(self.next_doc(EsI8 ))) as i8)
+            doc_as_i8(try!(self.next_doc(EsI8)))
         }
         fn read_int(&mut self) -> DecodeResult<int> {
-            let v = doc_as_u64(try!(self.next_doc(EsInt))) as i64;
+            let v = try!(doc_as_i64(try!(self.next_doc(EsInt))));
             if v > (int::MAX as i64) || v < (int::MIN as i64) {
                 debug!("FIXME \\#6122: Removing this makes this function miscompile");
                 Err(IntTooBig(v as uint))
@@ -419,22 +510,26 @@ pub mod reader {
         }
 
         fn read_bool(&mut self) -> DecodeResult<bool> {
-            Ok(doc_as_u8(try!(self.next_doc(EsBool))) != 0)
+            Ok(try!(doc_as_u8(try!(self.next_doc(EsBool)))) != 0)
         }
 
         fn read_f64(&mut self) -> DecodeResult<f64> {
-            let bits = doc_as_u64(try!(self.next_doc(EsF64)));
+            let bits = try!(doc_as_u64(try!(self.next_doc(EsF64))));
             Ok(unsafe { transmute(bits) })
         }
         fn read_f32(&mut self) -> DecodeResult<f32> {
-            let bits = doc_as_u32(try!(self.next_doc(EsF32)));
+            let bits = try!(doc_as_u32(try!(self.next_doc(EsF32))));
             Ok(unsafe { transmute(bits) })
         }
         fn read_char(&mut self) -> DecodeResult<char> {
-            Ok(char::from_u32(doc_as_u32(try!(self.next_doc(EsChar)))).unwrap())
+            let v = try!(doc_as_u32(try!(self.next_doc(EsChar))));
+            match char::from_u32(v) {
+                Some(c) => Ok(c),
+                None => Err(Expected(format!("invalid char {:#x}", v))),
+            }
         }
         fn read_str(&mut self) -> DecodeResult<~str> {
-            Ok(try!(self.next_doc(EsStr)).as_str())
+            try!(self.next_doc(EsStr)).as_str()
         }
 
         // Compound types:
@@ -445,65 +540,30 @@ pub mod reader {
             try!(self._check_label(name));
 
             let doc = try!(self.next_doc(EsEnum));
-
-            let (old_parent, old_pos) = (self.parent, self.pos);
-            self.parent = doc;
-            self.pos = self.parent.start;
-
-            let result = try!(f(self));
-
-            self.parent = old_parent;
-            self.pos = old_pos;
-            Ok(result)
+            self.in_doc(doc, f)
         }
 
         fn read_enum_variant<T>(&mut self,
-                                _: &[&str],
+                                names: &[&str],
                                 f: |&mut Decoder<'doc>, uint| -> DecodeResult<T>)
                                 -> DecodeResult<T> {
             debug!("read_enum_variant()");
-            let idx = try!(self._next_uint(EsEnumVid));
-            debug!("  idx={}", idx);
-
-            let doc = try!(self.next_doc(EsEnumBody));
-
-            let (old_parent, old_pos) = (self.parent, self.pos);
-            self.parent = doc;
-            self.pos = self.parent.start;
-
-            let result = try!(f(self, idx));
-
-            self.parent = old_parent;
-            self.pos = old_pos;
-            Ok(result)
+            self.read_variant(names, f)
         }
 
         fn read_enum_variant_arg<T>(&mut self,
                                     idx: uint,
                                     f: |&mut Decoder<'doc>| -> DecodeResult<T>) -> DecodeResult<T> {
             debug!("read_enum_variant_arg(idx={})", idx);
-            f(self)
+            self.in_path(IndexElement(idx), f)
         }
 
         fn read_enum_struct_variant<T>(&mut self,
-                                       _: &[&str],
+                                       names: &[&str],
                                        f: |&mut Decoder<'doc>, uint| -> DecodeResult<T>)
                                        -> DecodeResult<T> {
             debug!("read_enum_struct_variant()");
-            let idx = try!(self._next_uint(EsEnumVid));
-            debug!("  idx={}", idx);
-
-            let doc = try!(self.next_doc(EsEnumBody));
-
-            let (old_parent, old_pos) = (self.parent, self.pos);
-            self.parent = doc;
-            self.pos = self.parent.start;
-
-            let result = try!(f(self, idx));
-
-            self.parent = old_parent;
-            self.pos = old_pos;
-            Ok(result)
+            self.read_variant(names, f)
         }
 
         fn read_enum_struct_variant_field<T>(&mut self,
@@ -512,7 +572,7 @@ pub mod reader {
                                              f: |&mut Decoder<'doc>| -> DecodeResult<T>)
                                              -> DecodeResult<T> {
             debug!("read_enum_struct_variant_arg(name={}, idx={})", name, idx);
-            f(self)
+            self.in_path(FieldElement(name.to_owned()), f)
         }
 
         fn read_struct<T>(&mut self,
@@ -531,7 +591,7 @@ pub mod reader {
                                 -> DecodeResult<T> {
             debug!("read_struct_field(name={}, idx={})", name, idx);
             try!(self._check_label(name));
-            f(self)
+            self.in_path(FieldElement(name.to_owned()), f)
         }
 
         fn read_tuple<T>(&mut self,
@@ -565,8 +625,9 @@ pub mod reader {
         fn read_option<T>(&mut self,
                           f: |&mut Decoder<'doc>, bool| -> DecodeResult<T>) -> DecodeResult<T> {
             debug!("read_option()");
+            // Options are transparent, so no variant is added to the path.
             self.read_enum("Option", |this| {
-                this.read_enum_variant(["None", "Some"], |this, idx| {
+                this.read_variant([], |this, idx| {
                     match idx {
                         0 => f(this, false),
                         1 => f(this, true),
@@ -580,7 +641,7 @@ pub mod reader {
                        f: |&mut Decoder<'doc>, uint| -> DecodeResult<T>) -> DecodeResult<T> {
             debug!("read_seq()");
             self.push_doc(EsVec, |d| {
-                let len = try!(d._next_uint(EsVecLen));
+                let len = try!(d._next_len(EsVecLen, 1));
                 debug!("  len={}", len);
                 f(d, len)
             })
@@ -589,14 +650,14 @@ pub mod reader {
         fn read_seq_elt<T>(&mut self, idx: uint, f: |&mut Decoder<'doc>| -> DecodeResult<T>)
                            -> DecodeResult<T> {
             debug!("read_seq_elt(idx={})", idx);
-            self.push_doc(EsVecElt, f)
+            self.in_path(IndexElement(idx), |d| d.push_doc(EsVecElt, |d| f(d)))
         }
 
         fn read_map<T>(&mut self,
                        f: |&mut Decoder<'doc>, uint| -> DecodeResult<T>) -> DecodeResult<T> {
             debug!("read_map()");
             self.push_doc(EsMap, |d| {
-                let len = try!(d._next_uint(EsMapLen));
+                let len = try!(d._next_len(EsMapLen, 2));
                 debug!("  len={}", len);
                 f(d, len)
             })
@@ -611,7 +672,27 @@ pub mod reader {
         fn read_map_elt_val<T>(&mut self, idx: uint, f: |&mut Decoder<'doc>| -> DecodeResult<T>)
                                -> DecodeResult<T> {
             debug!("read_map_elt_val(idx={})", idx);
-            self.push_doc(EsMapVal, f)
+            self.in_path(IndexElement(idx), |d| d.push_doc(EsMapVal, |d| f(d)))
+        }
+
+        fn try_read<T>(&mut self, f: |&mut Decoder<'doc>| -> DecodeResult<T>)
+                       -> DecodeResult<T> {
+            debug!("try_read()");
+            let (old_parent, old_pos) = (self.parent, self.pos);
+            let result = f(self);
+            if result.is_err() {
+                self.parent = old_parent;
+                self.pos = old_pos;
+            }
+            result
+        }
+
+        fn path(&self) -> DecoderPath {
+            self.path.clone()
+        }
+
+        fn error(&mut self, err: &str) -> Error {
+            ApplicationError(err.to_owned())
         }
     }
 }
@@ -629,16 +710,10 @@ pub mod writer {
         EsOpaque, EsLabel, EbmlEncoderTag };
 
     use serialize;
-
+    use serialize::Encodable;
 
     pub type EncodeResult = io::IoResult<()>;
 
-    // ebml writing
-    pub struct Encoder<'a, W> {
-        pub writer: &'a mut W,
-        size_positions: Vec<uint>,
-    }
-
     fn write_sized_vuint<W: Writer>(w: &mut W, n: uint, size: uint) -> EncodeResult {
         match size {
             1u => w.write(&[0x80u8 | (n as u8)]),
@@ -655,11 +730,12 @@ pub mod writer {
         }
     }
 
-    fn write_vuint<W: Writer>(w: &mut W, n: uint) -> EncodeResult {
-        if n < 0x7f_u { return write_sized_vuint(w, n, 1u); }
-        if n < 0x4000_u { return write_sized_vuint(w, n, 2u); }
-        if n < 0x200000_u { return write_sized_vuint(w, n, 3u); }
-        if n < 0x10000000_u { return write_sized_vuint(w, n, 4u); }
+    // The number of bytes `n` takes up as a vuint.
+    fn vuint_size(n: uint) -> io::IoResult<uint> {
+        if n < 0x7f_u { return Ok(1u); }
+        if n < 0x4000_u { return Ok(2u); }
+        if n < 0x200000_u { return Ok(3u); }
+        if n < 0x10000000_u { return Ok(4u); }
         Err(io::IoError {
             kind: io::OtherIoError,
             desc: "int too big",
@@ -667,6 +743,302 @@ pub mod writer {
         })
     }
 
+    fn write_vuint<W: Writer>(w: &mut W, n: uint) -> EncodeResult {
+        write_sized_vuint(w, n, try!(vuint_size(n)))
+    }
+
+    // Set to true to generate more debugging in EBML code.
+    // Totally lame approach.
+    static DEBUG: bool = true;
+
+    // The parts of an encoder which don't depend on how the sizes of tags
+    // are written, shared by `Encoder` and `StreamEncoder`.
+    macro_rules! encoder_impls(
+        ($Enc:ident, $($bounds:tt)*) => (
+            impl<'a, W: $($bounds)*> $Enc<'a, W> {
+                pub fn wr_tag(&mut self, tag_id: uint, blk: || -> EncodeResult) -> EncodeResult {
+                    try!(self.start_tag(tag_id));
+                    try!(blk());
+                    self.end_tag()
+                }
+
+                pub fn wr_tagged_u64(&mut self, tag_id: uint, v: u64) -> EncodeResult {
+                    u64_to_be_bytes(v, 8u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_u32(&mut self, tag_id: uint, v: u32)  -> EncodeResult{
+                    u64_to_be_bytes(v as u64, 4u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_u16(&mut self, tag_id: uint, v: u16) -> EncodeResult {
+                    u64_to_be_bytes(v as u64, 2u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_u8(&mut self, tag_id: uint, v: u8) -> EncodeResult {
+                    self.wr_tagged_bytes(tag_id, &[v])
+                }
+
+                pub fn wr_tagged_i64(&mut self, tag_id: uint, v: i64) -> EncodeResult {
+                    u64_to_be_bytes(v as u64, 8u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_i32(&mut self, tag_id: uint, v: i32) -> EncodeResult {
+                    u64_to_be_bytes(v as u64, 4u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_i16(&mut self, tag_id: uint, v: i16) -> EncodeResult {
+                    u64_to_be_bytes(v as u64, 2u, |v| {
+                        self.wr_tagged_bytes(tag_id, v)
+                    })
+                }
+
+                pub fn wr_tagged_i8(&mut self, tag_id: uint, v: i8) -> EncodeResult {
+                    self.wr_tagged_bytes(tag_id, &[v as u8])
+                }
+
+                pub fn wr_tagged_str(&mut self, tag_id: uint, v: &str) -> EncodeResult {
+                    self.wr_tagged_bytes(tag_id, v.as_bytes())
+                }
+
+                // used internally to emit things like the vector length and so on
+                fn _emit_tagged_uint(&mut self, t: EbmlEncoderTag, v: uint) -> EncodeResult {
+                    assert!(v <= 0xFFFF_FFFF_u);
+                    self.wr_tagged_u32(t as uint, v as u32)
+                }
+
+                fn _emit_label(&mut self, label: &str) -> EncodeResult {
+                    // There are various strings that we have access to, such as
+                    // the name of a record field, which do not actually appear in
+                    // the encoded EBML (normally).  This is just for
+                    // efficiency.  When debugging, though, we can emit such
+                    // labels and then they will be checked by decoder to
+                    // try and check failures more quickly.
+                    if DEBUG { self.wr_tagged_str(EsLabel as uint, label) }
+                    else { Ok(()) }
+                }
+
+                pub fn emit_opaque(&mut self, f: |&mut $Enc<W>| -> EncodeResult) -> EncodeResult {
+                    try!(self.start_tag(EsOpaque as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+            }
+
+            impl<'a, W: $($bounds)*> serialize::Encoder<io::IoError> for $Enc<'a, W> {
+                fn emit_nil(&mut self) -> EncodeResult {
+                    Ok(())
+                }
+
+                fn emit_uint(&mut self, v: uint) -> EncodeResult {
+                    self.wr_tagged_u64(EsUint as uint, v as u64)
+                }
+                fn emit_u64(&mut self, v: u64) -> EncodeResult {
+                    self.wr_tagged_u64(EsU64 as uint, v)
+                }
+                fn emit_u32(&mut self, v: u32) -> EncodeResult {
+                    self.wr_tagged_u32(EsU32 as uint, v)
+                }
+                fn emit_u16(&mut self, v: u16) -> EncodeResult {
+                    self.wr_tagged_u16(EsU16 as uint, v)
+                }
+                fn emit_u8(&mut self, v: u8) -> EncodeResult {
+                    self.wr_tagged_u8(EsU8 as uint, v)
+                }
+
+                fn emit_int(&mut self, v: int) -> EncodeResult {
+                    self.wr_tagged_i64(EsInt as uint, v as i64)
+                }
+                fn emit_i64(&mut self, v: i64) -> EncodeResult {
+                    self.wr_tagged_i64(EsI64 as uint, v)
+                }
+                fn emit_i32(&mut self, v: i32) -> EncodeResult {
+                    self.wr_tagged_i32(EsI32 as uint, v)
+                }
+                fn emit_i16(&mut self, v: i16) -> EncodeResult {
+                    self.wr_tagged_i16(EsI16 as uint, v)
+                }
+                fn emit_i8(&mut self, v: i8) -> EncodeResult {
+                    self.wr_tagged_i8(EsI8 as uint, v)
+                }
+
+                fn emit_bool(&mut self, v: bool) -> EncodeResult {
+                    self.wr_tagged_u8(EsBool as uint, v as u8)
+                }
+
+                fn emit_f64(&mut self, v: f64) -> EncodeResult {
+                    let bits = unsafe { cast::transmute(v) };
+                    self.wr_tagged_u64(EsF64 as uint, bits)
+                }
+                fn emit_f32(&mut self, v: f32) -> EncodeResult {
+                    let bits = unsafe { cast::transmute(v) };
+                    self.wr_tagged_u32(EsF32 as uint, bits)
+                }
+                fn emit_char(&mut self, v: char) -> EncodeResult {
+                    self.wr_tagged_u32(EsChar as uint, v as u32)
+                }
+
+                fn emit_str(&mut self, v: &str) -> EncodeResult {
+                    self.wr_tagged_str(EsStr as uint, v)
+                }
+
+                fn emit_enum(&mut self,
+                             name: &str,
+                             f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    try!(self._emit_label(name));
+                    try!(self.start_tag(EsEnum as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_enum_variant(&mut self,
+                                     _: &str,
+                                     v_id: uint,
+                                     _: uint,
+                                     f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    try!(self._emit_tagged_uint(EsEnumVid, v_id));
+                    try!(self.start_tag(EsEnumBody as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_enum_variant_arg(&mut self,
+                                         _: uint,
+                                         f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    f(self)
+                }
+
+                fn emit_enum_struct_variant(&mut self,
+                                            v_name: &str,
+                                            v_id: uint,
+                                            cnt: uint,
+                                            f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_enum_variant(v_name, v_id, cnt, f)
+                }
+
+                fn emit_enum_struct_variant_field(&mut self,
+                                                  _: &str,
+                                                  idx: uint,
+                                                  f: |&mut $Enc<'a, W>| -> EncodeResult)
+                    -> EncodeResult {
+                    self.emit_enum_variant_arg(idx, f)
+                }
+
+                fn emit_struct(&mut self,
+                               _: &str,
+                               _len: uint,
+                               f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    f(self)
+                }
+
+                fn emit_struct_field(&mut self,
+                                     name: &str,
+                                     _: uint,
+                                     f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    try!(self._emit_label(name));
+                    f(self)
+                }
+
+                fn emit_tuple(&mut self,
+                              len: uint,
+                              f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_seq(len, f)
+                }
+                fn emit_tuple_arg(&mut self,
+                                  idx: uint,
+                                  f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_seq_elt(idx, f)
+                }
+
+                fn emit_tuple_struct(&mut self,
+                                     _: &str,
+                                     len: uint,
+                                     f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_seq(len, f)
+                }
+                fn emit_tuple_struct_arg(&mut self,
+                                         idx: uint,
+                                         f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_seq_elt(idx, f)
+                }
+
+                fn emit_option(&mut self,
+                               f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    self.emit_enum("Option", f)
+                }
+                fn emit_option_none(&mut self) -> EncodeResult {
+                    self.emit_enum_variant("None", 0, 0, |_| Ok(()))
+                }
+                fn emit_option_some(&mut self,
+                                    f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+
+                    self.emit_enum_variant("Some", 1, 1, f)
+                }
+
+                fn emit_seq(&mut self,
+                            len: uint,
+                            f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+
+                    try!(self.start_tag(EsVec as uint));
+                    try!(self._emit_tagged_uint(EsVecLen, len));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_seq_elt(&mut self,
+                                _idx: uint,
+                                f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+
+                    try!(self.start_tag(EsVecElt as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_map(&mut self,
+                            len: uint,
+                            f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+
+                    try!(self.start_tag(EsMap as uint));
+                    try!(self._emit_tagged_uint(EsMapLen, len));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_map_elt_key(&mut self,
+                                    _idx: uint,
+                                    f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+
+                    try!(self.start_tag(EsMapKey as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+
+                fn emit_map_elt_val(&mut self,
+                                    _idx: uint,
+                                    f: |&mut $Enc<'a, W>| -> EncodeResult) -> EncodeResult {
+                    try!(self.start_tag(EsMapVal as uint));
+                    try!(f(self));
+                    self.end_tag()
+                }
+            }
+        )
+    )
+
+    // ebml writing
+    pub struct Encoder<'a, W> {
+        pub writer: &'a mut W,
+        size_positions: Vec<uint>,
+    }
+
     pub fn Encoder<'a, W: Writer + Seek>(w: &'a mut W) -> Encoder<'a, W> {
         Encoder {
             writer: w,
@@ -708,66 +1080,12 @@ pub mod writer {
             Ok(r)
         }
 
-        pub fn wr_tag(&mut self, tag_id: uint, blk: || -> EncodeResult) -> EncodeResult {
-            try!(self.start_tag(tag_id));
-            try!(blk());
-            self.end_tag()
-        }
-
         pub fn wr_tagged_bytes(&mut self, tag_id: uint, b: &[u8]) -> EncodeResult {
             try!(write_vuint(self.writer, tag_id));
             try!(write_vuint(self.writer, b.len()));
             self.writer.write(b)
         }
 
-        pub fn wr_tagged_u64(&mut self, tag_id: uint, v: u64) -> EncodeResult {
-            u64_to_be_bytes(v, 8u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_u32(&mut self, tag_id: uint, v: u32)  -> EncodeResult{
-            u64_to_be_bytes(v as u64, 4u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_u16(&mut self, tag_id: uint, v: u16) -> EncodeResult {
-            u64_to_be_bytes(v as u64, 2u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_u8(&mut self, tag_id: uint, v: u8) -> EncodeResult {
-            self.wr_tagged_bytes(tag_id, &[v])
-        }
-
-        pub fn wr_tagged_i64(&mut self, tag_id: uint, v: i64) -> EncodeResult {
-            u64_to_be_bytes(v as u64, 8u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_i32(&mut self, tag_id: uint, v: i32) -> EncodeResult {
-            u64_to_be_bytes(v as u64, 4u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_i16(&mut self, tag_id: uint, v: i16) -> EncodeResult {
-            u64_to_be_bytes(v as u64, 2u, |v| {
-                self.wr_tagged_bytes(tag_id, v)
-            })
-        }
-
-        pub fn wr_tagged_i8(&mut self, tag_id: uint, v: i8) -> EncodeResult {
-            self.wr_tagged_bytes(tag_id, &[v as u8])
-        }
-
-        pub fn wr_tagged_str(&mut self, tag_id: uint, v: &str) -> EncodeResult {
-            self.wr_tagged_bytes(tag_id, v.as_bytes())
-        }
-
         pub fn wr_bytes(&mut self, b: &[u8]) -> EncodeResult {
             debug!("Write {} bytes", b.len());
             self.writer.write(b)
@@ -779,234 +1097,135 @@ pub mod writer {
         }
     }
 
-    // FIXME (#2743): optionally perform "relaxations" on end_tag to more
-    // efficiently encode sizes; this is a fixed point iteration
-
-    // Set to true to generate more debugging in EBML code.
-    // Totally lame approach.
-    static DEBUG: bool = true;
-
-    impl<'a, W: Writer + Seek> Encoder<'a, W> {
-        // used internally to emit things like the vector length and so on
-        fn _emit_tagged_uint(&mut self, t: EbmlEncoderTag, v: uint) -> EncodeResult {
-            assert!(v <= 0xFFFF_FFFF_u);
-            self.wr_tagged_u32(t as uint, v as u32)
-        }
+    encoder_impls!(Encoder, Writer + Seek)
+
+    /// An encoder which writes to any `Writer`, rather than one which can
+    /// seek back to fill in the sizes of tags once they are known.
+    ///
+    /// Values are encoded twice by `encode`: first to measure the size of
+    /// every tag, then to write them out. This also lets every size be
+    /// written in as few bytes as possible.
+    pub struct StreamEncoder<'a, W> {
+        writer: &'a mut W,
+        // Whether tags are being measured rather than written.
+        measuring: bool,
+        // The sizes of the tags, in the order they are started.
+        sizes: Vec<uint>,
+        // The entry of `sizes` for the next tag to be written.
+        next_size: uint,
+        // For each open tag, its entry in `sizes` and the position at which
+        // its contents start.
+        open: Vec<(uint, uint)>,
+        // The number of bytes measured or written so far.
+        pos: uint,
+    }
 
-        fn _emit_label(&mut self, label: &str) -> EncodeResult {
-            // There are various strings that we have access to, such as
-            // the name of a record field, which do not actually appear in
-            // the encoded EBML (normally).  This is just for
-            // efficiency.  When debugging, though, we can emit such
-            // labels and then they will be checked by decoder to
-            // try and check failures more quickly.
-            if DEBUG { self.wr_tagged_str(EsLabel as uint, label) }
-            else { Ok(()) }
+    pub fn StreamEncoder<'a, W: Writer>(w: &'a mut W) -> StreamEncoder<'a, W> {
+        StreamEncoder {
+            writer: w,
+            measuring: false,
+            sizes: Vec::new(),
+            next_size: 0,
+            open: Vec::new(),
+            pos: 0,
         }
+    }
 
-        pub fn emit_opaque(&mut self, f: |&mut Encoder<W>| -> EncodeResult) -> EncodeResult {
-            try!(self.start_tag(EsOpaque as uint));
-            try!(f(self));
-            self.end_tag()
+    fn changed_between_passes() -> io::IoError {
+        io::IoError {
+            kind: io::OtherIoError,
+            desc: "value was encoded differently when measured",
+            detail: None,
         }
     }
 
-    impl<'a, W: Writer + Seek> serialize::Encoder<io::IoError> for Encoder<'a, W> {
-        fn emit_nil(&mut self) -> EncodeResult {
+    impl<'a, W: Writer> StreamEncoder<'a, W> {
+        /// Writes `value`. It must encode itself the same way both times it
+        /// is asked to.
+        pub fn encode<T: Encodable<StreamEncoder<'a, W>, io::IoError>>(&mut self, value: &T)
+                                                                       -> EncodeResult {
+            self.measuring = true;
+            self.sizes.truncate(0);
+            self.open.truncate(0);
+            self.pos = 0;
+            try!(value.encode(self));
+
+            self.measuring = false;
+            self.next_size = 0;
+            self.open.truncate(0);
+            self.pos = 0;
+            try!(value.encode(self));
+            if self.next_size != self.sizes.len() {
+                return Err(changed_between_passes());
+            }
             Ok(())
         }
 
-        fn emit_uint(&mut self, v: uint) -> EncodeResult {
-            self.wr_tagged_u64(EsUint as uint, v as u64)
-        }
-        fn emit_u64(&mut self, v: u64) -> EncodeResult {
-            self.wr_tagged_u64(EsU64 as uint, v)
-        }
-        fn emit_u32(&mut self, v: u32) -> EncodeResult {
-            self.wr_tagged_u32(EsU32 as uint, v)
-        }
-        fn emit_u16(&mut self, v: u16) -> EncodeResult {
-            self.wr_tagged_u16(EsU16 as uint, v)
-        }
-        fn emit_u8(&mut self, v: u8) -> EncodeResult {
-            self.wr_tagged_u8(EsU8 as uint, v)
-        }
-
-        fn emit_int(&mut self, v: int) -> EncodeResult {
-            self.wr_tagged_i64(EsInt as uint, v as i64)
-        }
-        fn emit_i64(&mut self, v: i64) -> EncodeResult {
-            self.wr_tagged_i64(EsI64 as uint, v)
-        }
-        fn emit_i32(&mut self, v: i32) -> EncodeResult {
-            self.wr_tagged_i32(EsI32 as uint, v)
-        }
-        fn emit_i16(&mut self, v: i16) -> EncodeResult {
-            self.wr_tagged_i16(EsI16 as uint, v)
-        }
-        fn emit_i8(&mut self, v: i8) -> EncodeResult {
-            self.wr_tagged_i8(EsI8 as uint, v)
-        }
-
-        fn emit_bool(&mut self, v: bool) -> EncodeResult {
-            self.wr_tagged_u8(EsBool as uint, v as u8)
-        }
-
-        fn emit_f64(&mut self, v: f64) -> EncodeResult {
-            let bits = unsafe { cast::transmute(v) };
-            self.wr_tagged_u64(EsF64 as uint, bits)
-        }
-        fn emit_f32(&mut self, v: f32) -> EncodeResult {
-            let bits = unsafe { cast::transmute(v) };
-            self.wr_tagged_u32(EsF32 as uint, bits)
-        }
-        fn emit_char(&mut self, v: char) -> EncodeResult {
-            self.wr_tagged_u32(EsChar as uint, v as u32)
-        }
-
-        fn emit_str(&mut self, v: &str) -> EncodeResult {
-            self.wr_tagged_str(EsStr as uint, v)
-        }
-
-        fn emit_enum(&mut self,
-                     name: &str,
-                     f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            try!(self._emit_label(name));
-            try!(self.start_tag(EsEnum as uint));
-            try!(f(self));
-            self.end_tag()
-        }
-
-        fn emit_enum_variant(&mut self,
-                             _: &str,
-                             v_id: uint,
-                             _: uint,
-                             f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            try!(self._emit_tagged_uint(EsEnumVid, v_id));
-            try!(self.start_tag(EsEnumBody as uint));
-            try!(f(self));
-            self.end_tag()
-        }
-
-        fn emit_enum_variant_arg(&mut self,
-                                 _: uint,
-                                 f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            f(self)
-        }
-
-        fn emit_enum_struct_variant(&mut self,
-                                    v_name: &str,
-                                    v_id: uint,
-                                    cnt: uint,
-                                    f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_enum_variant(v_name, v_id, cnt, f)
-        }
-
-        fn emit_enum_struct_variant_field(&mut self,
-                                          _: &str,
-                                          idx: uint,
-                                          f: |&mut Encoder<'a, W>| -> EncodeResult)
-            -> EncodeResult {
-            self.emit_enum_variant_arg(idx, f)
-        }
-
-        fn emit_struct(&mut self,
-                       _: &str,
-                       _len: uint,
-                       f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            f(self)
-        }
-
-        fn emit_struct_field(&mut self,
-                             name: &str,
-                             _: uint,
-                             f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            try!(self._emit_label(name));
-            f(self)
-        }
-
-        fn emit_tuple(&mut self,
-                      len: uint,
-                      f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_seq(len, f)
-        }
-        fn emit_tuple_arg(&mut self,
-                          idx: uint,
-                          f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_seq_elt(idx, f)
-        }
-
-        fn emit_tuple_struct(&mut self,
-                             _: &str,
-                             len: uint,
-                             f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_seq(len, f)
-        }
-        fn emit_tuple_struct_arg(&mut self,
-                                 idx: uint,
-                                 f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_seq_elt(idx, f)
-        }
-
-        fn emit_option(&mut self,
-                       f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            self.emit_enum("Option", f)
-        }
-        fn emit_option_none(&mut self) -> EncodeResult {
-            self.emit_enum_variant("None", 0, 0, |_| Ok(()))
+        fn write(&mut self, b: &[u8]) -> EncodeResult {
+            self.pos += b.len();
+            if self.measuring { Ok(()) } else { self.writer.write(b) }
         }
-        fn emit_option_some(&mut self,
-                            f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
 
-            self.emit_enum_variant("Some", 1, 1, f)
+        fn write_vuint(&mut self, n: uint) -> EncodeResult {
+            let size = try!(vuint_size(n));
+            self.pos += size;
+            if self.measuring { Ok(()) } else { write_sized_vuint(self.writer, n, size) }
         }
 
-        fn emit_seq(&mut self,
-                    len: uint,
-                    f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-
-            try!(self.start_tag(EsVec as uint));
-            try!(self._emit_tagged_uint(EsVecLen, len));
-            try!(f(self));
-            self.end_tag()
+        pub fn start_tag(&mut self, tag_id: uint) -> EncodeResult {
+            debug!("Start tag {}", tag_id);
+            try!(self.write_vuint(tag_id));
+            if self.measuring {
+                // The size is accounted for once it is known, in `end_tag`.
+                self.open.push((self.sizes.len(), self.pos));
+                self.sizes.push(0);
+                return Ok(());
+            }
+            let idx = self.next_size;
+            let size = match self.sizes.as_slice().get(idx) {
+                Some(&size) => size,
+                None => return Err(changed_between_passes()),
+            };
+            self.next_size += 1;
+            try!(self.write_vuint(size));
+            self.open.push((idx, self.pos));
+            Ok(())
         }
 
-        fn emit_seq_elt(&mut self,
-                        _idx: uint,
-                        f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-
-            try!(self.start_tag(EsVecElt as uint));
-            try!(f(self));
-            self.end_tag()
+        pub fn end_tag(&mut self) -> EncodeResult {
+            let (idx, start) = match self.open.pop() {
+                Some(tag) => tag,
+                None => return Err(changed_between_passes()),
+            };
+            let size = self.pos - start;
+            debug!("End tag (size = {})", size);
+            if self.measuring {
+                *self.sizes.get_mut(idx) = size;
+                self.pos += try!(vuint_size(size));
+            } else if size != *self.sizes.get(idx) {
+                return Err(changed_between_passes());
+            }
+            Ok(())
         }
 
-        fn emit_map(&mut self,
-                    len: uint,
-                    f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-
-            try!(self.start_tag(EsMap as uint));
-            try!(self._emit_tagged_uint(EsMapLen, len));
-            try!(f(self));
-            self.end_tag()
+        pub fn wr_tagged_bytes(&mut self, tag_id: uint, b: &[u8]) -> EncodeResult {
+            try!(self.write_vuint(tag_id));
+            try!(self.write_vuint(b.len()));
+            self.write(b)
         }
 
-        fn emit_map_elt_key(&mut self,
-                            _idx: uint,
-                            f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-
-            try!(self.start_tag(EsMapKey as uint));
-            try!(f(self));
-            self.end_tag()
+        pub fn wr_bytes(&mut self, b: &[u8]) -> EncodeResult {
+            debug!("Write {} bytes", b.len());
+            self.write(b)
         }
 
-        fn emit_map_elt_val(&mut self,
-                            _idx: uint,
-                            f: |&mut Encoder<'a, W>| -> EncodeResult) -> EncodeResult {
-            try!(self.start_tag(EsMapVal as uint));
-            try!(f(self));
-            self.end_tag()
+        pub fn wr_str(&mut self, s: &str) -> EncodeResult {
+            debug!("Write str: {}", s);
+            self.write(s.as_bytes())
         }
     }
+
+    encoder_impls!(StreamEncoder, Writer)
 }
 
 // ___________________________________________________________________________
@@ -1016,8 +1235,10 @@ pub mod writer {
 mod tests {
     use ebml::reader;
     use ebml::writer;
+    use ebml::{Expected, UnknownField, PathError};
     use {Encodable, Decodable};
 
+    use std::io;
     use std::io::MemWriter;
     use std::option::{None, Option, Some};
 
@@ -1089,6 +1310,168 @@ mod tests {
         test_v(None);
         test_v(Some(3));
     }
+
+    #[deriving(Encodable)]
+    struct Wide {
+        a: int,
+        b: int,
+    }
+
+    #[deriving(Decodable, Eq, Show)]
+    struct Narrow {
+        a: int,
+    }
+
+    #[deriving(Encodable, Decodable, Eq, Show)]
+    struct Record {
+        id: u32,
+        name: ~str,
+        tags: Vec<~str>,
+        extra: Option<(int, bool)>,
+        initial: char,
+        score: f64,
+    }
+
+    fn record() -> Record {
+        Record {
+            id: 7,
+            name: "seven".to_owned(),
+            tags: vec!("a".to_owned(), "bc".to_owned()),
+            extra: Some((-1, true)),
+            initial: 's',
+            score: 0.5,
+        }
+    }
+
+    fn encode_record() -> Vec<u8> {
+        let mut wr = MemWriter::new();
+        {
+            let mut ebml_w = writer::Encoder(&mut wr);
+            record().encode(&mut ebml_w).unwrap();
+        }
+        Vec::from_slice(wr.get_ref())
+    }
+
+    fn decode_record(data: &[u8]) -> reader::DecodeResult<Record> {
+        Decodable::decode(&mut reader::Decoder(reader::Doc(data)))
+    }
+
+    // A writer which can't seek.
+    struct Sink {
+        data: Vec<u8>,
+    }
+
+    impl Writer for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::IoResult<()> {
+            self.data.push_all(buf);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stream_encoder() {
+        let mut sink = Sink { data: Vec::new() };
+        {
+            let mut ebml_w = writer::StreamEncoder(&mut sink);
+            ebml_w.encode(&record()).unwrap();
+        }
+        assert_eq!(decode_record(sink.data.as_slice()).unwrap(), record());
+        // sizes take up as little room as possible
+        assert!(sink.data.len() < encode_record().len());
+    }
+
+    #[test]
+    fn test_reader_functions() {
+        assert!(reader::vuint_at([], 0).is_err());
+        assert!(reader::vuint_at([0x40], 0).is_err());
+        assert!(reader::vuint_at([0x80], 1).is_err());
+        assert!(reader::vuint_at([0x01, 0, 0, 0, 0], 0).is_err());
+        // a document whose size runs past the end of the data
+        assert!(reader::doc_at([0x81, 0x85, 0, 0], 0).is_err());
+
+        let data = [0x81, 0x82, 0xab, 0xcd, 0x82, 0x81, 0xef];
+        let doc = reader::Doc(data);
+        let first = reader::get_doc(doc, 1).unwrap();
+        assert_eq!(reader::doc_as_u16(first).unwrap(), 0xabcd);
+        assert!(reader::doc_as_u8(first).is_err());
+        assert!(reader::doc_as_u32(first).is_err());
+        assert!(reader::get_doc(doc, 3).is_err());
+        assert!(reader::maybe_get_doc(doc, 3).unwrap().is_none());
+        let mut tags = Vec::new();
+        assert!(reader::docs(doc, |tag, _| { tags.push(tag); true }).unwrap());
+        assert_eq!(tags, vec!(1, 2));
+
+        // the second document is longer than its parent
+        let truncated = ::ebml::Doc { data: data, start: 0, end: 5 };
+        assert!(reader::tagged_docs(truncated, 2, |_| true).is_err());
+
+        let invalid = [0x81, 0x81, 0xff];
+        assert!(reader::Doc(invalid).get(1).unwrap().as_str().is_err());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let data = encode_record();
+        assert_eq!(decode_record(data.as_slice()).unwrap(), record());
+        for len in range(0, data.len()) {
+            assert!(decode_record(data.slice_to(len)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_corrupted() {
+        let data = encode_record();
+        for i in range(0, data.len()) {
+            for &byte in [0x00u8, 0x01, 0x10, 0x7f, 0x80, 0xff, *data.get(i) ^ 0x40].iter() {
+                let mut corrupted = data.clone();
+                *corrupted.get_mut(i) = byte;
+                // Any result will do, as long as there's no failure.
+                let _ = decode_record(corrupted.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_path() {
+        let mut wr = MemWriter::new();
+        {
+            let mut ebml_w = writer::Encoder(&mut wr);
+            let _ = vec!(Some(1), None).encode(&mut ebml_w);
+        }
+        let ebml_doc = reader::Doc(wr.get_ref());
+        let mut deser = reader::Decoder(ebml_doc);
+        let res: reader::DecodeResult<Vec<Option<~str>>> = Decodable::decode(&mut deser);
+        match res {
+            Err(PathError(box Expected(..), ref path)) => {
+                assert_eq!(path.to_str(), "[0]".to_owned())
+            }
+            other => fail!("unexpected result: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_strict() {
+        let mut wr = MemWriter::new();
+        {
+            let mut ebml_w = writer::Encoder(&mut wr);
+            let _ = vec!(Wide { a: 1, b: 2 }).encode(&mut ebml_w);
+        }
+        let ebml_doc = reader::Doc(wr.get_ref());
+
+        let mut deser = reader::Decoder(ebml_doc);
+        let v: Vec<Narrow> = Decodable::decode(&mut deser).unwrap();
+        assert_eq!(v, vec!(Narrow { a: 1 }));
+
+        let mut deser = reader::Decoder(ebml_doc);
+        deser.set_strict(true);
+        let res: reader::DecodeResult<Vec<Narrow>> = Decodable::decode(&mut deser);
+        match res {
+            Err(PathError(box UnknownField(_), ref path)) => {
+                assert_eq!(path.to_str(), "[0]".to_owned())
+            }
+            other => fail!("unexpected result: {}", other),
+        }
+    }
 }
 
 #[cfg(test)]
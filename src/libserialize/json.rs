@@ -233,18 +233,20 @@ fn main() {
 */
 
 use std::char;
+use std::cmp;
 use std::f64;
 use std::fmt;
 use std::io::MemWriter;
 use std::io;
 use std::mem::swap;
+use std::mem;
 use std::num;
 use std::str::ScalarValue;
 use std::str;
 use std::strbuf::StrBuf;
 use std::vec::Vec;
 
-use Encodable;
+use {Encodable, DecoderPath, PathElement, FieldElement, IndexElement, VariantElement};
 use collections::{HashMap, TreeMap};
 
 /// Represents a json value
@@ -292,12 +294,74 @@ pub enum ParserError {
 // Builder and Parser have the same errors.
 pub type BuilderError = ParserError;
 
-#[deriving(Clone, Eq, Show)]
+#[deriving(Clone, Eq)]
 pub enum DecoderError {
     ParseError(ParserError),
     ExpectedError(~str, ~str),
     MissingFieldError(~str),
     UnknownVariantError(~str),
+    /// An error reported by a `Decodable` implementation through
+    /// `Decoder::error`.
+    ApplicationError(~str),
+    /// A member of an object which doesn't correspond to any field of the
+    /// struct being decoded. Only produced in strict mode.
+    UnknownFieldError(~str),
+    /// A decoding error, and the line and column of the input at which it was
+    /// detected. Only produced by `StreamingDecoder`.
+    PositionedError(Box<DecoderError>, uint, uint),
+    /// A decoding error, and the location of the value in which it was
+    /// detected.
+    PathError(Box<DecoderError>, DecoderPath),
+}
+
+impl fmt::Show for DecoderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError(ref e) => write!(f.buf, "{}", *e),
+            ExpectedError(ref expected, ref found) => {
+                write!(f.buf, "expected {}, found {}", *expected, *found)
+            }
+            MissingFieldError(ref name) => write!(f.buf, "missing field `{}`", *name),
+            UnknownVariantError(ref name) => write!(f.buf, "unknown variant `{}`", *name),
+            ApplicationError(ref msg) => write!(f.buf, "{}", *msg),
+            UnknownFieldError(ref name) => write!(f.buf, "unknown field `{}`", *name),
+            PositionedError(ref e, line, col) => {
+                write!(f.buf, "{} at line {} column {}", **e, line, col)
+            }
+            PathError(ref e, ref path) => write!(f.buf, "{}: {}", *path, **e),
+        }
+    }
+}
+
+/// The errors that can arise while resolving a JSON Pointer (RFC 6901).
+#[deriving(Clone, Eq, Show)]
+pub enum PointerError {
+    /// The pointer is neither empty nor starts with `/`, or it contains a `~`
+    /// which is not followed by `0` or `1`.
+    MalformedPointer(~str),
+    /// An object has no member with this name.
+    MissingMember(~str),
+    /// A reference token applied to a list is not an index.
+    InvalidIndex(~str),
+    /// index, length of the list
+    IndexOutOfBounds(uint, uint),
+    /// A reference token was applied to a value which is neither an object
+    /// nor a list.
+    NotAContainer(~str),
+}
+
+/// The errors that can arise while applying a JSON Patch (RFC 6902).
+#[deriving(Clone, Eq, Show)]
+pub enum PatchError {
+    /// The patch document is not a list of well-formed operations.
+    InvalidPatch(~str),
+    /// The location named by an operation could not be resolved.
+    PatchPointerError(PointerError),
+    /// A `test` operation failed, with the path it was testing.
+    TestFailed(~str),
+    /// A `move` operation tried to move a value into one of its own
+    /// children: from, path
+    MoveIntoChild(~str, ~str),
 }
 
 /// Returns a readable error string for a given error code.
@@ -962,6 +1026,394 @@ impl Json {
             _ => None
         }
     }
+
+    /// Looks up a value by a JSON Pointer, as defined by RFC 6901. For
+    /// example, `/foo/0` names the first element of the list stored in the
+    /// member `foo`, and the empty pointer names the whole value.
+    pub fn pointer<'a>(&'a self, pointer: &str) -> Result<&'a Json, PointerError> {
+        let tokens = try!(parse_pointer(pointer));
+        resolve(self, tokens.as_slice())
+    }
+
+    /// Looks up a value by a JSON Pointer, like `pointer`, returning a
+    /// mutable reference to it.
+    pub fn pointer_mut<'a>(&'a mut self, pointer: &str)
+                           -> Result<&'a mut Json, PointerError> {
+        let tokens = try!(parse_pointer(pointer));
+        resolve_mut(self, tokens.as_slice())
+    }
+
+    /// Applies a JSON Patch, as defined by RFC 6902. The operations are
+    /// applied in order, and if any of them fails the value is left
+    /// untouched.
+    pub fn apply_patch(&mut self, patch: &[PatchOperation]) -> Result<(), PatchError> {
+        let mut target = self.clone();
+        for op in patch.iter() {
+            try!(apply_operation(&mut target, op));
+        }
+        *self = target;
+        Ok(())
+    }
+
+    /// Applies a JSON Merge Patch, as defined by RFC 7386. Members of `patch`
+    /// which are objects are merged recursively, members which are `null`
+    /// are removed, and everything else replaces the existing value.
+    pub fn merge_patch(&mut self, patch: &Json) {
+        let members = match *patch {
+            Object(ref members) => members,
+            _ => {
+                *self = patch.clone();
+                return
+            }
+        };
+        if !self.is_object() {
+            *self = Object(box TreeMap::new());
+        }
+        let map = match *self {
+            Object(ref mut map) => map,
+            _ => unreachable!(),
+        };
+        for (key, value) in members.iter() {
+            if value.is_null() {
+                map.pop(key);
+                continue
+            }
+            match map.find_mut(key) {
+                Some(target) => {
+                    target.merge_patch(value);
+                    continue
+                }
+                None => {}
+            }
+            let mut target = Null;
+            target.merge_patch(value);
+            map.insert(key.clone(), target);
+        }
+    }
+}
+
+/// A single operation of a JSON Patch (RFC 6902). Locations are given as
+/// JSON Pointers.
+#[deriving(Clone, Eq, Show)]
+pub enum PatchOperation {
+    /// path, value
+    PatchAdd(~str, Json),
+    /// path
+    PatchRemove(~str),
+    /// path, value
+    PatchReplace(~str, Json),
+    /// from, path
+    PatchMove(~str, ~str),
+    /// from, path
+    PatchCopy(~str, ~str),
+    /// path, value
+    PatchTest(~str, Json),
+}
+
+impl ToJson for PatchOperation {
+    fn to_json(&self) -> Json {
+        let mut d = box TreeMap::new();
+        let (op, path) = match *self {
+            PatchAdd(ref path, ref value) => {
+                d.insert("value".to_owned(), value.clone());
+                ("add", path)
+            }
+            PatchRemove(ref path) => ("remove", path),
+            PatchReplace(ref path, ref value) => {
+                d.insert("value".to_owned(), value.clone());
+                ("replace", path)
+            }
+            PatchMove(ref from, ref path) => {
+                d.insert("from".to_owned(), String(from.clone()));
+                ("move", path)
+            }
+            PatchCopy(ref from, ref path) => {
+                d.insert("from".to_owned(), String(from.clone()));
+                ("copy", path)
+            }
+            PatchTest(ref path, ref value) => {
+                d.insert("value".to_owned(), value.clone());
+                ("test", path)
+            }
+        };
+        d.insert("op".to_owned(), String(op.to_owned()));
+        d.insert("path".to_owned(), String(path.clone()));
+        Object(d)
+    }
+}
+
+/// Reads the operations of a JSON Patch document, which must be a list of
+/// operation objects.
+pub fn patch_from_json(json: &Json) -> Result<~[PatchOperation], PatchError> {
+    let list = match *json {
+        List(ref list) => list,
+        _ => return Err(InvalidPatch("patch is not a list".to_owned())),
+    };
+    let mut ops = Vec::new();
+    for op in list.iter() {
+        ops.push(try!(operation_from_json(op)));
+    }
+    Ok(ops.move_iter().collect())
+}
+
+fn operation_from_json(json: &Json) -> Result<PatchOperation, PatchError> {
+    let obj = match *json {
+        Object(ref obj) => &**obj,
+        _ => return Err(InvalidPatch("operation is not an object".to_owned())),
+    };
+    let op = try!(patch_string(obj, "op"));
+    let path = try!(patch_string(obj, "path"));
+    match op.as_slice() {
+        "add" => Ok(PatchAdd(path, try!(patch_member(obj, "value")).clone())),
+        "remove" => Ok(PatchRemove(path)),
+        "replace" => Ok(PatchReplace(path, try!(patch_member(obj, "value")).clone())),
+        "move" => Ok(PatchMove(try!(patch_string(obj, "from")), path)),
+        "copy" => Ok(PatchCopy(try!(patch_string(obj, "from")), path)),
+        "test" => Ok(PatchTest(path, try!(patch_member(obj, "value")).clone())),
+        _ => Err(InvalidPatch(format!("unknown operation `{}`", op))),
+    }
+}
+
+fn patch_member<'a>(obj: &'a Object, name: &str) -> Result<&'a Json, PatchError> {
+    match obj.find(&name.to_owned()) {
+        Some(value) => Ok(value),
+        None => Err(InvalidPatch(format!("operation has no `{}` member", name))),
+    }
+}
+
+fn patch_string(obj: &Object, name: &str) -> Result<~str, PatchError> {
+    match *try!(patch_member(obj, name)) {
+        String(ref s) => Ok(s.clone()),
+        _ => Err(InvalidPatch(format!("`{}` member is not a string", name))),
+    }
+}
+
+/// Computes a JSON Patch which turns `from` into `to`.
+///
+/// Objects and lists are compared member by member, so that only the values
+/// which actually differ are replaced. Elements are never moved around
+/// within a list: a list which grew gets new elements appended, and one which
+/// shrank has its trailing elements removed.
+pub fn diff(from: &Json, to: &Json) -> ~[PatchOperation] {
+    let mut ops = Vec::new();
+    diff_into(from, to, "".to_owned(), &mut ops);
+    ops.move_iter().collect()
+}
+
+fn diff_into(from: &Json, to: &Json, path: ~str, ops: &mut Vec<PatchOperation>) {
+    if from == to { return }
+    match (from, to) {
+        (&Object(ref a), &Object(ref b)) => {
+            for (key, value) in a.iter() {
+                let child = path + "/" + escape_token(key.as_slice()).as_slice();
+                match b.find(key) {
+                    Some(other) => diff_into(value, other, child, ops),
+                    None => ops.push(PatchRemove(child)),
+                }
+            }
+            for (key, value) in b.iter() {
+                if !a.contains_key(key) {
+                    let child = path + "/" + escape_token(key.as_slice()).as_slice();
+                    ops.push(PatchAdd(child, value.clone()));
+                }
+            }
+        }
+        (&List(ref a), &List(ref b)) => {
+            let common = cmp::min(a.len(), b.len());
+            for i in range(0, common) {
+                diff_into(&a[i], &b[i], format!("{}/{}", path, i), ops);
+            }
+            // Remove from the back, so that the indices stay valid.
+            for i in range(common, a.len()).rev() {
+                ops.push(PatchRemove(format!("{}/{}", path, i)));
+            }
+            for value in b.slice_from(common).iter() {
+                ops.push(PatchAdd(path + "/-", value.clone()));
+            }
+        }
+        _ => ops.push(PatchReplace(path, to.clone())),
+    }
+}
+
+fn escape_token(token: &str) -> ~str {
+    token.replace("~", "~0").replace("/", "~1")
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<~str>, PointerError> {
+    let mut tokens = Vec::new();
+    if pointer.is_empty() {
+        return Ok(tokens)
+    }
+    if !pointer.starts_with("/") {
+        return Err(MalformedPointer(pointer.to_owned()))
+    }
+    for raw in pointer.slice_from(1).split('/') {
+        let mut token = StrBuf::new();
+        let mut chars = raw.chars();
+        loop {
+            match chars.next() {
+                Some('~') => match chars.next() {
+                    Some('0') => token.push_char('~'),
+                    Some('1') => token.push_char('/'),
+                    _ => return Err(MalformedPointer(pointer.to_owned())),
+                },
+                Some(c) => token.push_char(c),
+                None => break,
+            }
+        }
+        tokens.push(token.into_owned());
+    }
+    Ok(tokens)
+}
+
+// Parses a list index. Leading zeros are not allowed, and neither is `-`,
+// which only means something to `add`.
+fn parse_index(token: &str) -> Result<uint, PointerError> {
+    if token.is_empty() || !token.chars().all(|c| c.is_digit()) ||
+       (token.len() > 1 && token.starts_with("0")) {
+        return Err(InvalidIndex(token.to_owned()))
+    }
+    match num::from_str_radix(token, 10) {
+        Some(idx) => Ok(idx),
+        None => Err(InvalidIndex(token.to_owned())),
+    }
+}
+
+fn resolve<'a>(mut target: &'a Json, tokens: &[~str]) -> Result<&'a Json, PointerError> {
+    for token in tokens.iter() {
+        target = match *target {
+            Object(ref map) => match map.find(token) {
+                Some(value) => value,
+                None => return Err(MissingMember(token.clone())),
+            },
+            List(ref list) => {
+                let idx = try!(parse_index(token.as_slice()));
+                if idx >= list.len() {
+                    return Err(IndexOutOfBounds(idx, list.len()))
+                }
+                &list[idx]
+            }
+            _ => return Err(NotAContainer(token.clone())),
+        };
+    }
+    Ok(target)
+}
+
+fn resolve_mut<'a>(mut target: &'a mut Json, tokens: &[~str])
+                   -> Result<&'a mut Json, PointerError> {
+    for token in tokens.iter() {
+        let current = target;
+        target = match *current {
+            Object(ref mut map) => match map.find_mut(token) {
+                Some(value) => value,
+                None => return Err(MissingMember(token.clone())),
+            },
+            List(ref mut list) => {
+                let idx = try!(parse_index(token.as_slice()));
+                if idx >= list.len() {
+                    return Err(IndexOutOfBounds(idx, list.len()))
+                }
+                &mut list[idx]
+            }
+            _ => return Err(NotAContainer(token.clone())),
+        };
+    }
+    Ok(target)
+}
+
+fn apply_operation(target: &mut Json, op: &PatchOperation) -> Result<(), PatchError> {
+    let res = match *op {
+        PatchAdd(ref path, ref value) => add_value(target, path.as_slice(), value.clone()),
+        PatchRemove(ref path) => remove_value(target, path.as_slice()).map(|_| ()),
+        PatchReplace(ref path, ref value) => {
+            target.pointer_mut(path.as_slice()).map(|t| *t = value.clone())
+        }
+        PatchMove(ref from, ref path) => {
+            if path.starts_with((*from + "/").as_slice()) {
+                return Err(MoveIntoChild(from.clone(), path.clone()))
+            }
+            match remove_value(target, from.as_slice()) {
+                Ok(value) => add_value(target, path.as_slice(), value),
+                Err(e) => Err(e),
+            }
+        }
+        PatchCopy(ref from, ref path) => {
+            let value = target.pointer(from.as_slice()).map(|v| v.clone());
+            match value {
+                Ok(value) => add_value(target, path.as_slice(), value),
+                Err(e) => Err(e),
+            }
+        }
+        PatchTest(ref path, ref value) => {
+            match target.pointer(path.as_slice()) {
+                Ok(actual) if actual == value => Ok(()),
+                Ok(_) => return Err(TestFailed(path.clone())),
+                Err(e) => Err(e),
+            }
+        }
+    };
+    res.map_err(PatchPointerError)
+}
+
+// Adds `value` at `pointer`. The parent of the location must exist, and the
+// last reference token may be one past the end of a list (or `-`).
+fn add_value(root: &mut Json, pointer: &str, value: Json) -> Result<(), PointerError> {
+    let mut tokens = try!(parse_pointer(pointer));
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => {
+            *root = value;
+            return Ok(())
+        }
+    };
+    match *try!(resolve_mut(root, tokens.as_slice())) {
+        Object(ref mut map) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        List(ref mut list) => {
+            let idx = if last.as_slice() == "-" {
+                list.len()
+            } else {
+                try!(parse_index(last.as_slice()))
+            };
+            if idx > list.len() {
+                return Err(IndexOutOfBounds(idx, list.len()))
+            }
+            let mut values: Vec<Json> = mem::replace(list, ~[]).move_iter().collect();
+            values.insert(idx, value);
+            *list = values.move_iter().collect();
+            Ok(())
+        }
+        _ => Err(NotAContainer(last)),
+    }
+}
+
+// Removes and returns the value at `pointer`. Removing the whole document
+// leaves `null` in its place.
+fn remove_value(root: &mut Json, pointer: &str) -> Result<Json, PointerError> {
+    let mut tokens = try!(parse_pointer(pointer));
+    let last = match tokens.pop() {
+        Some(last) => last,
+        None => return Ok(mem::replace(root, Null)),
+    };
+    match *try!(resolve_mut(root, tokens.as_slice())) {
+        Object(ref mut map) => match map.pop(&last) {
+            Some(value) => Ok(value),
+            None => Err(MissingMember(last)),
+        },
+        List(ref mut list) => {
+            let idx = try!(parse_index(last.as_slice()));
+            if idx >= list.len() {
+                return Err(IndexOutOfBounds(idx, list.len()))
+            }
+            let mut values: Vec<Json> = mem::replace(list, ~[]).move_iter().collect();
+            let value = values.remove(idx).unwrap();
+            *list = values.move_iter().collect();
+            Ok(value)
+        }
+        _ => Err(NotAContainer(last)),
+    }
 }
 
 /// The output of the streaming parser.
@@ -1815,8 +2267,15 @@ pub fn from_str(s: &str) -> Result<Json, BuilderError> {
 }
 
 /// A structure to decode JSON to values in rust.
+///
+/// Errors found within a value are reported as a `PathError`, giving the
+/// location of the value, such as `config.servers[3].port`.
 pub struct Decoder {
     stack: Vec<Json>,
+    path: DecoderPath,
+    // The key of the map entry whose value is about to be decoded.
+    map_key: Option<~str>,
+    strict: bool,
 }
 
 impl Decoder {
@@ -1824,14 +2283,46 @@ impl Decoder {
     pub fn new(json: Json) -> Decoder {
         Decoder {
             stack: vec!(json),
+            path: DecoderPath::new(),
+            map_key: None,
+            strict: false,
         }
     }
+
+    /// Sets whether the decoder is strict. A strict decoder fails with an
+    /// `UnknownFieldError` on objects which have members that don't
+    /// correspond to any field of the struct being decoded, instead of
+    /// ignoring them.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
 }
 
 impl Decoder {
     fn pop(&mut self) -> Json {
         self.stack.pop().unwrap()
     }
+
+    // Decodes a part of the current value with `f`, attaching the path to the
+    // part to any error which doesn't have a path yet.
+    fn in_path<T>(&mut self, element: PathElement,
+                  f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        self.path.push(element);
+        let result = match f(self) {
+            Err(e) => Err(with_path(e, &self.path)),
+            ok => ok,
+        };
+        self.path.pop();
+        result
+    }
+}
+
+// Attaches `path` to `err`, unless it already has a more precise one.
+fn with_path(err: DecoderError, path: &DecoderPath) -> DecoderError {
+    match err {
+        PathError(..) => err,
+        err => PathError(box err, path.clone()),
+    }
 }
 
 macro_rules! expect(
@@ -1944,13 +2435,13 @@ impl ::Decoder<DecoderError> for Decoder {
             Some(idx) => idx,
             None => return Err(UnknownVariantError(name))
         };
-        f(self, idx)
+        self.in_path(VariantElement(name), |d| f(d, idx))
     }
 
     fn read_enum_variant_arg<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
                                 -> DecodeResult<T> {
         debug!("read_enum_variant_arg(idx={})", idx);
-        f(self)
+        self.in_path(IndexElement(idx), f)
     }
 
     fn read_enum_struct_variant<T>(&mut self,
@@ -1968,7 +2459,7 @@ impl ::Decoder<DecoderError> for Decoder {
                                          f: |&mut Decoder| -> DecodeResult<T>)
                                          -> DecodeResult<T> {
         debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
-        self.read_enum_variant_arg(idx, f)
+        self.in_path(FieldElement(name.to_owned()), f)
     }
 
     fn read_struct<T>(&mut self,
@@ -1978,7 +2469,16 @@ impl ::Decoder<DecoderError> for Decoder {
                       -> DecodeResult<T> {
         debug!("read_struct(name={}, len={})", name, len);
         let value = try!(f(self));
-        self.pop();
+        match self.pop() {
+            // Members which don't correspond to a field are left over.
+            Object(obj) => if self.strict {
+                match obj.iter().next() {
+                    Some((key, _)) => return Err(UnknownFieldError(key.clone())),
+                    None => {}
+                }
+            },
+            _ => {}
+        }
         Ok(value)
     }
 
@@ -1994,7 +2494,7 @@ impl ::Decoder<DecoderError> for Decoder {
             None => return Err(MissingFieldError(name.to_owned())),
             Some(json) => {
                 self.stack.push(json);
-                try!(f(self))
+                try!(self.in_path(FieldElement(name.to_owned()), f))
             }
         };
         self.stack.push(Object(obj));
@@ -2050,7 +2550,7 @@ impl ::Decoder<DecoderError> for Decoder {
                        idx: uint,
                        f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
         debug!("read_seq_elt(idx={})", idx);
-        f(self)
+        self.in_path(IndexElement(idx), f)
     }
 
     fn read_map<T>(&mut self, f: |&mut Decoder, uint| -> DecodeResult<T>) -> DecodeResult<T> {
@@ -2067,109 +2567,928 @@ impl ::Decoder<DecoderError> for Decoder {
     fn read_map_elt_key<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
                            -> DecodeResult<T> {
         debug!("read_map_elt_key(idx={})", idx);
+        self.map_key = match self.stack.last() {
+            Some(&String(ref key)) => Some(key.clone()),
+            _ => None,
+        };
         f(self)
     }
 
     fn read_map_elt_val<T>(&mut self, idx: uint, f: |&mut Decoder| -> DecodeResult<T>)
                            -> DecodeResult<T> {
         debug!("read_map_elt_val(idx={})", idx);
-        f(self)
+        let element = match self.map_key.take() {
+            Some(key) => FieldElement(key),
+            None => IndexElement(idx),
+        };
+        self.in_path(element, f)
     }
-}
-
-/// Test if two json values are less than one another
-impl Ord for Json {
-    fn lt(&self, other: &Json) -> bool {
-        match *self {
-            Number(f0) => {
-                match *other {
-                    Number(f1) => f0 < f1,
-                    String(_) | Boolean(_) | List(_) | Object(_) |
-                    Null => true
-                }
-            }
-
-            String(ref s0) => {
-                match *other {
-                    Number(_) => false,
-                    String(ref s1) => s0 < s1,
-                    Boolean(_) | List(_) | Object(_) | Null => true
-                }
-            }
 
-            Boolean(b0) => {
-                match *other {
-                    Number(_) | String(_) => false,
-                    Boolean(b1) => b0 < b1,
-                    List(_) | Object(_) | Null => true
-                }
-            }
+    fn read_struct_field_opt<T>(&mut self,
+                                name: &str,
+                                idx: uint,
+                                f: |&mut Decoder| -> DecodeResult<T>)
+                                -> DecodeResult<Option<T>> {
+        debug!("read_struct_field_opt(name={}, idx={})", name, idx);
+        let mut obj = try!(expect!(self.pop(), Object));
 
-            List(ref l0) => {
-                match *other {
-                    Number(_) | String(_) | Boolean(_) => false,
-                    List(ref l1) => (*l0) < (*l1),
-                    Object(_) | Null => true
-                }
+        let value = match obj.pop(&name.to_owned()) {
+            None => None,
+            Some(json) => {
+                self.stack.push(json);
+                Some(try!(self.in_path(FieldElement(name.to_owned()), f)))
             }
+        };
+        self.stack.push(Object(obj));
+        Ok(value)
+    }
 
-            Object(ref d0) => {
-                match *other {
-                    Number(_) | String(_) | Boolean(_) | List(_) => false,
-                    Object(ref d1) => d0 < d1,
-                    Null => true
-                }
-            }
+    fn skip_struct_field(&mut self, name: &str) -> DecodeResult<()> {
+        debug!("skip_struct_field(name={})", name);
+        let mut obj = try!(expect!(self.pop(), Object));
+        obj.pop(&name.to_owned());
+        self.stack.push(Object(obj));
+        Ok(())
+    }
 
-            Null => {
-                match *other {
-                    Number(_) | String(_) | Boolean(_) | List(_) |
-                    Object(_) =>
-                        false,
-                    Null => true
-                }
+    fn try_read<T>(&mut self, f: |&mut Decoder| -> DecodeResult<T>) -> DecodeResult<T> {
+        debug!("try_read()");
+        let value = self.pop();
+        let len = self.stack.len();
+        self.stack.push(value.clone());
+        match f(self) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                self.stack.truncate(len);
+                self.stack.push(value);
+                Err(e)
             }
         }
     }
-}
 
-/// A trait for converting values to JSON
-pub trait ToJson {
-    /// Converts the value of `self` to an instance of JSON
-    fn to_json(&self) -> Json;
-}
+    fn path(&self) -> DecoderPath {
+        self.path.clone()
+    }
 
-impl ToJson for Json {
-    fn to_json(&self) -> Json { (*self).clone() }
+    fn error(&mut self, err: &str) -> DecoderError {
+        ApplicationError(err.to_owned())
+    }
 }
 
-impl ToJson for int {
-    fn to_json(&self) -> Json { Number(*self as f64) }
+// The tokens the streaming decoder works with: the parser's events, with the
+// key of each object member made explicit ahead of the member's value.
+#[deriving(Clone, Eq, Show)]
+enum Token {
+    Event(JsonEvent),
+    MemberKey(~str),
 }
 
-impl ToJson for i8 {
-    fn to_json(&self) -> Json { Number(*self as f64) }
+/// A structure to decode JSON to values in rust directly from a `Parser`'s
+/// events, without building an intermediate `Json` tree.
+///
+/// Strings are moved out of the parser's events without being copied, and
+/// structs and enums are decoded as their members are parsed. If the members
+/// of an object appear in a different order than the struct's fields, the
+/// members which are not yet needed are buffered as events until they are.
+///
+/// Lists and maps read with `read_seq_elts` and `read_map_elts`, as the
+/// standard collections are, are decoded element by element as they are
+/// parsed. Only `read_seq` and `read_map`, which must know the number of
+/// elements up front, buffer the elements as events before decoding them.
+///
+/// Errors other than syntax errors are reported as a `PositionedError`, with
+/// the line and column of the input that the parser had reached when the
+/// error was detected.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate serialize;
+/// use serialize::json;
+/// use serialize::Decodable;
+///
+/// #[deriving(Decodable)]
+/// pub struct Point { x: int, y: int }
+///
+/// fn main() {
+///     let mut decoder = json::StreamingDecoder::new("{\"y\": 2, \"x\": 1}".chars());
+///     let point: Point = Decodable::decode(&mut decoder).unwrap();
+///     decoder.finish().unwrap();
+///     assert_eq!((point.x, point.y), (1, 2));
+/// }
+/// ```
+pub struct StreamingDecoder<T> {
+    parser: Parser<T>,
+    // An event whose member key was returned first.
+    pending: Option<JsonEvent>,
+    // A token which was looked at and put back.
+    peeked: Option<Token>,
+    // Buffered tokens which are decoded before any more input, in reverse.
+    replay: Vec<Token>,
+    // For each struct being decoded, the members which were skipped over while
+    // looking for a field, in case they are asked for later.
+    frames: Vec<Vec<(~str, Vec<Token>)>>,
+    path: DecoderPath,
+    // The key of the map entry whose value is about to be decoded.
+    map_key: Option<~str>,
+    strict: bool,
 }
 
-impl ToJson for i16 {
-    fn to_json(&self) -> Json { Number(*self as f64) }
-}
+impl<T: Iterator<char>> StreamingDecoder<T> {
+    /// Creates a new streaming decoder reading JSON from `rdr`.
+    pub fn new(rdr: T) -> StreamingDecoder<T> {
+        StreamingDecoder {
+            parser: Parser::new(rdr),
+            pending: None,
+            peeked: None,
+            replay: Vec::new(),
+            frames: Vec::new(),
+            path: DecoderPath::new(),
+            map_key: None,
+            strict: false,
+        }
+    }
 
-impl ToJson for i32 {
-    fn to_json(&self) -> Json { Number(*self as f64) }
-}
+    /// Sets whether the decoder is strict, see `Decoder::set_strict`.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
 
-impl ToJson for i64 {
-    fn to_json(&self) -> Json { Number(*self as f64) }
-}
+    /// Provides access to the current position in the logical structure of the
+    /// JSON stream.
+    pub fn stack<'l>(&'l self) -> &'l Stack {
+        self.parser.stack()
+    }
 
-impl ToJson for uint {
-    fn to_json(&self) -> Json { Number(*self as f64) }
-}
+    /// Checks that the input has been completely consumed, after a value has
+    /// been decoded.
+    pub fn finish(mut self) -> DecodeResult<()> {
+        if self.peeked.is_some() || self.replay.len() > 0 ||
+           self.pending.is_some() {
+            return Err(self.positioned(ExpectedError("end of input".to_owned(),
+                                                     "more values".to_owned())))
+        }
+        match self.parser.next() {
+            None => Ok(()),
+            Some(Error(e)) => Err(ParseError(e)),
+            Some(_) => Err(ParseError(SyntaxError(TrailingCharacters,
+                                                  self.parser.line,
+                                                  self.parser.col))),
+        }
+    }
 
-impl ToJson for u8 {
-    fn to_json(&self) -> Json { Number(*self as f64) }
-}
+    fn next_token(&mut self) -> Token {
+        match self.peeked.take() {
+            Some(tok) => return tok,
+            None => {}
+        }
+        match self.replay.pop() {
+            Some(tok) => return tok,
+            None => {}
+        }
+        match self.pending.take() {
+            Some(evt) => return Event(evt),
+            None => {}
+        }
+        match self.parser.next() {
+            None => Event(Error(SyntaxError(EOFWhileParsingValue,
+                                            self.parser.line,
+                                            self.parser.col))),
+            Some(evt) => match evt {
+                ObjectEnd | ListEnd | Error(_) => Event(evt),
+                _ => {
+                    let key = match self.parser.stack().top() {
+                        Some(Key(k)) => k.to_owned(),
+                        _ => return Event(evt),
+                    };
+                    self.pending = Some(evt);
+                    MemberKey(key)
+                }
+            }
+        }
+    }
+
+    fn peek_token<'a>(&'a mut self) -> &'a Token {
+        if self.peeked.is_none() {
+            let tok = self.next_token();
+            self.peeked = Some(tok);
+        }
+        self.peeked.get_ref()
+    }
+
+    // Pushes tokens to be decoded before anything else.
+    fn push_replay(&mut self, tokens: Vec<Token>) {
+        assert!(self.peeked.is_none());
+        for tok in tokens.move_iter().rev() {
+            self.replay.push(tok);
+        }
+    }
+
+    fn positioned(&self, err: DecoderError) -> DecoderError {
+        PositionedError(box err, self.parser.line, self.parser.col)
+    }
+
+    // See `Decoder::in_path`.
+    fn in_path<U>(&mut self, element: PathElement,
+                  f: |&mut StreamingDecoder<T>| -> DecodeResult<U>) -> DecodeResult<U> {
+        self.path.push(element);
+        let result = match f(self) {
+            Err(e) => Err(with_path(e, &self.path)),
+            ok => ok,
+        };
+        self.path.pop();
+        result
+    }
+
+    // Appends the tokens of the complete value which starts with `first`.
+    fn capture(&mut self, first: Token, out: &mut Vec<Token>)
+               -> DecodeResult<()> {
+        let mut depth = 0;
+        let mut tok = first;
+        loop {
+            match tok {
+                Event(Error(e)) => return Err(ParseError(e)),
+                Event(ObjectStart) | Event(ListStart) => depth += 1,
+                Event(ObjectEnd) | Event(ListEnd) => depth -= 1,
+                _ => {}
+            }
+            let is_key = match tok { MemberKey(..) => true, _ => false };
+            out.push(tok);
+            if depth == 0 && !is_key {
+                return Ok(())
+            }
+            tok = self.next_token();
+        }
+    }
+
+    // Skips over the complete value which starts with `first`.
+    fn skip(&mut self, first: Token) -> DecodeResult<()> {
+        let mut depth = 0;
+        let mut tok = first;
+        loop {
+            match tok {
+                Event(Error(e)) => return Err(ParseError(e)),
+                Event(ObjectStart) | Event(ListStart) => depth += 1,
+                Event(ObjectEnd) | Event(ListEnd) => depth -= 1,
+                MemberKey(..) => { tok = self.next_token(); continue }
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(())
+            }
+            tok = self.next_token();
+        }
+    }
+
+    // Skips the remaining members of the current object, and its end.
+    fn skip_members(&mut self) -> DecodeResult<()> {
+        loop {
+            match self.next_token() {
+                Event(ObjectEnd) => return Ok(()),
+                Event(Error(e)) => return Err(ParseError(e)),
+                MemberKey(..) => {
+                    let tok = self.next_token();
+                    try!(self.skip(tok));
+                }
+                tok => fail!("unexpected token {}", tok),
+            }
+        }
+    }
+
+    // Positions the decoder at the value of the member `name` of the struct
+    // being decoded, returning false if the struct has no such member.
+    fn find_member(&mut self, name: &str) -> DecodeResult<bool> {
+        let buffered = {
+            let frame = self.frames.mut_last().unwrap();
+            match frame.iter().position(|&(ref k, _)| k.as_slice() == name) {
+                Some(i) => frame.remove(i),
+                None => None,
+            }
+        };
+        match buffered {
+            Some((_, tokens)) => {
+                self.push_replay(tokens);
+                return Ok(true)
+            }
+            None => {}
+        }
+        loop {
+            match self.next_token() {
+                MemberKey(k) => {
+                    if k.as_slice() == name {
+                        return Ok(true)
+                    }
+                    let mut tokens = Vec::new();
+                    let tok = self.next_token();
+                    try!(self.capture(tok, &mut tokens));
+                    self.frames.mut_last().unwrap().push((k, tokens));
+                }
+                Event(ObjectEnd) => {
+                    self.peeked = Some(Event(ObjectEnd));
+                    return Ok(false)
+                }
+                tok => return self.expected("Object", tok),
+            }
+        }
+    }
+
+    // Reports that `expected` was wanted, but `found` was the next token.
+    fn expected<U>(&mut self, expected: &str, found: Token)
+                   -> DecodeResult<U> {
+        let found = match found {
+            Event(Error(e)) => return Err(ParseError(e)),
+            Event(ObjectEnd) => "}".to_owned(),
+            Event(ListEnd) => "]".to_owned(),
+            MemberKey(k) => escape_str(k.as_slice()),
+            tok => {
+                let mut tokens = Vec::new();
+                try!(self.capture(tok, &mut tokens));
+                let mut tokens = tokens.move_iter();
+                let first = tokens.next().unwrap();
+                format!("{}", tokens_to_json(first, &mut tokens))
+            }
+        };
+        Err(self.positioned(ExpectedError(expected.to_owned(), found)))
+    }
+}
+
+// Builds the value described by a well-formed sequence of tokens, for error
+// messages.
+fn tokens_to_json<I: Iterator<Token>>(first: Token, tokens: &mut I) -> Json {
+    match first {
+        Event(NullValue) => Null,
+        Event(NumberValue(n)) => Number(n),
+        Event(BooleanValue(b)) => Boolean(b),
+        Event(StringValue(s)) => String(s),
+        Event(ListStart) => {
+            let mut values = Vec::new();
+            loop {
+                match tokens.next() {
+                    Some(Event(ListEnd)) | None => break,
+                    Some(tok) => values.push(tokens_to_json(tok, tokens)),
+                }
+            }
+            List(values.move_iter().collect())
+        }
+        Event(ObjectStart) => {
+            let mut values = box TreeMap::new();
+            loop {
+                match tokens.next() {
+                    Some(MemberKey(k)) => {
+                        let first = tokens.next().unwrap();
+                        values.insert(k, tokens_to_json(first, tokens));
+                    }
+                    _ => break,
+                }
+            }
+            Object(values)
+        }
+        tok => fail!("unexpected token {}", tok),
+    }
+}
+
+impl<T: Iterator<char>> ::Decoder<DecoderError> for StreamingDecoder<T> {
+    fn read_nil(&mut self) -> DecodeResult<()> {
+        debug!("read_nil");
+        match self.next_token() {
+            Event(NullValue) => Ok(()),
+            tok => self.expected("Null", tok),
+        }
+    }
+
+    fn read_u64(&mut self)  -> DecodeResult<u64 > { Ok(try!(self.read_f64()) as u64) }
+    fn read_u32(&mut self)  -> DecodeResult<u32 > { Ok(try!(self.read_f64()) as u32) }
+    fn read_u16(&mut self)  -> DecodeResult<u16 > { Ok(try!(self.read_f64()) as u16) }
+    fn read_u8 (&mut self)  -> DecodeResult<u8  > { Ok(try!(self.read_f64()) as u8) }
+    fn read_uint(&mut self) -> DecodeResult<uint> { Ok(try!(self.read_f64()) as uint) }
+
+    fn read_i64(&mut self) -> DecodeResult<i64> { Ok(try!(self.read_f64()) as i64) }
+    fn read_i32(&mut self) -> DecodeResult<i32> { Ok(try!(self.read_f64()) as i32) }
+    fn read_i16(&mut self) -> DecodeResult<i16> { Ok(try!(self.read_f64()) as i16) }
+    fn read_i8 (&mut self) -> DecodeResult<i8 > { Ok(try!(self.read_f64()) as i8) }
+    fn read_int(&mut self) -> DecodeResult<int> { Ok(try!(self.read_f64()) as int) }
+
+    fn read_bool(&mut self) -> DecodeResult<bool> {
+        debug!("read_bool");
+        match self.next_token() {
+            Event(BooleanValue(b)) => Ok(b),
+            tok => self.expected("Boolean", tok),
+        }
+    }
+
+    fn read_f64(&mut self) -> DecodeResult<f64> {
+        use std::from_str::FromStr;
+        debug!("read_f64");
+        match self.next_token() {
+            Event(NumberValue(f)) => Ok(f),
+            Event(StringValue(s)) => {
+                // map keys are strings, see `Decoder::read_f64`
+                match FromStr::from_str(s) {
+                    Some(f) => Ok(f),
+                    None => self.expected("Number", Event(StringValue(s))),
+                }
+            }
+            tok => self.expected("Number", tok),
+        }
+    }
+
+    fn read_f32(&mut self) -> DecodeResult<f32> { Ok(try!(self.read_f64()) as f32) }
+
+    fn read_char(&mut self) -> DecodeResult<char> {
+        let s = try!(self.read_str());
+        {
+            let mut it = s.chars();
+            match (it.next(), it.next()) {
+                // exactly one character
+                (Some(c), None) => return Ok(c),
+                _ => ()
+            }
+        }
+        Err(self.positioned(ExpectedError("single character string".to_owned(),
+                                          format!("{}", s))))
+    }
+
+    fn read_str(&mut self) -> DecodeResult<~str> {
+        debug!("read_str");
+        match self.next_token() {
+            Event(StringValue(s)) => Ok(s),
+            tok => self.expected("String", tok),
+        }
+    }
+
+    fn read_enum<U>(&mut self,
+                    name: &str,
+                    f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                    -> DecodeResult<U> {
+        debug!("read_enum({})", name);
+        f(self)
+    }
+
+    fn read_enum_variant<U>(&mut self,
+                            names: &[&str],
+                            f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                            -> DecodeResult<U> {
+        debug!("read_enum_variant(names={:?})", names);
+        let name = match self.next_token() {
+            Event(StringValue(s)) => {
+                let idx = match names.iter().position(|n| str::eq_slice(*n, s)) {
+                    Some(idx) => idx,
+                    None => return Err(self.positioned(UnknownVariantError(s))),
+                };
+                return self.in_path(VariantElement(s), |d| f(d, idx))
+            }
+            Event(ObjectStart) => {
+                // The fields may come before the name of the variant, in
+                // which case they are buffered.
+                let mut fields = None;
+                let mut name = None;
+                while name.is_none() {
+                    match self.next_token() {
+                        MemberKey(k) => {
+                            let tok = self.next_token();
+                            if k.as_slice() == "variant" {
+                                match tok {
+                                    Event(StringValue(s)) => name = Some(s),
+                                    tok => return self.expected("String", tok),
+                                }
+                            } else if k.as_slice() == "fields" {
+                                let mut tokens = Vec::new();
+                                try!(self.capture(tok, &mut tokens));
+                                fields = Some(tokens);
+                            } else {
+                                try!(self.skip(tok));
+                            }
+                        }
+                        Event(ObjectEnd) => {
+                            let err = MissingFieldError("variant".to_owned());
+                            return Err(self.positioned(err))
+                        }
+                        tok => return self.expected("String or Object", tok),
+                    }
+                }
+                let name = name.unwrap();
+                let idx = match names.iter().position(|n| str::eq_slice(*n, name)) {
+                    Some(idx) => idx,
+                    None => return Err(self.positioned(UnknownVariantError(name))),
+                };
+                match fields {
+                    Some(tokens) => self.push_replay(tokens),
+                    None => loop {
+                        match self.next_token() {
+                            MemberKey(k) => {
+                                if k.as_slice() == "fields" { break }
+                                let tok = self.next_token();
+                                try!(self.skip(tok));
+                            }
+                            Event(ObjectEnd) => {
+                                let err = MissingFieldError("fields".to_owned());
+                                return Err(self.positioned(err))
+                            }
+                            tok => return self.expected("String or Object", tok),
+                        }
+                    }
+                }
+                match self.next_token() {
+                    Event(ListStart) => {}
+                    tok => return self.expected("List", tok),
+                }
+                let value = try!(self.in_path(VariantElement(name), |d| f(d, idx)));
+                // Any unused fields are ignored, as are the other members.
+                loop {
+                    match self.next_token() {
+                        Event(ListEnd) => break,
+                        tok => try!(self.skip(tok)),
+                    }
+                }
+                try!(self.skip_members());
+                return Ok(value)
+            }
+            tok => tok,
+        };
+        self.expected("String or Object", name)
+    }
+
+    fn read_enum_variant_arg<U>(&mut self,
+                                idx: uint,
+                                f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                                -> DecodeResult<U> {
+        debug!("read_enum_variant_arg(idx={})", idx);
+        self.in_path(IndexElement(idx), f)
+    }
+
+    fn read_enum_struct_variant<U>(&mut self,
+                                   names: &[&str],
+                                   f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                                   -> DecodeResult<U> {
+        debug!("read_enum_struct_variant(names={:?})", names);
+        self.read_enum_variant(names, f)
+    }
+
+    fn read_enum_struct_variant_field<U>(&mut self,
+                                         name: &str,
+                                         idx: uint,
+                                         f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                                         -> DecodeResult<U> {
+        debug!("read_enum_struct_variant_field(name={}, idx={})", name, idx);
+        self.in_path(FieldElement(name.to_owned()), f)
+    }
+
+    fn read_struct<U>(&mut self,
+                      name: &str,
+                      len: uint,
+                      f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                      -> DecodeResult<U> {
+        debug!("read_struct(name={}, len={})", name, len);
+        match self.next_token() {
+            Event(ObjectStart) => {}
+            tok => return self.expected("Object", tok),
+        }
+        self.frames.push(Vec::new());
+        let value = try!(f(self));
+        // Members which don't correspond to a field are ignored, unless the
+        // decoder is strict.
+        if self.strict {
+            let unknown = match self.frames.last().unwrap().as_slice().head() {
+                Some(&(ref key, _)) => Some(key.clone()),
+                None => match self.next_token() {
+                    MemberKey(key) => Some(key),
+                    tok => { self.peeked = Some(tok); None }
+                },
+            };
+            match unknown {
+                Some(key) => return Err(self.positioned(UnknownFieldError(key))),
+                None => {}
+            }
+        }
+        try!(self.skip_members());
+        self.frames.pop();
+        Ok(value)
+    }
+
+    fn read_struct_field<U>(&mut self,
+                            name: &str,
+                            idx: uint,
+                            f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                            -> DecodeResult<U> {
+        debug!("read_struct_field(name={}, idx={})", name, idx);
+        if try!(self.find_member(name)) {
+            self.in_path(FieldElement(name.to_owned()), f)
+        } else {
+            Err(self.positioned(MissingFieldError(name.to_owned())))
+        }
+    }
+
+    fn read_tuple<U>(&mut self,
+                     f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                     -> DecodeResult<U> {
+        debug!("read_tuple()");
+        self.read_seq(f)
+    }
+
+    fn read_tuple_arg<U>(&mut self,
+                         idx: uint,
+                         f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                         -> DecodeResult<U> {
+        debug!("read_tuple_arg(idx={})", idx);
+        self.read_seq_elt(idx, f)
+    }
+
+    fn read_tuple_struct<U>(&mut self,
+                            name: &str,
+                            f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                            -> DecodeResult<U> {
+        debug!("read_tuple_struct(name={})", name);
+        self.read_tuple(f)
+    }
+
+    fn read_tuple_struct_arg<U>(&mut self,
+                                idx: uint,
+                                f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                                -> DecodeResult<U> {
+        debug!("read_tuple_struct_arg(idx={})", idx);
+        self.read_tuple_arg(idx, f)
+    }
+
+    fn read_option<U>(&mut self,
+                      f: |&mut StreamingDecoder<T>, bool| -> DecodeResult<U>)
+                      -> DecodeResult<U> {
+        let is_null = *self.peek_token() == Event(NullValue);
+        if is_null {
+            self.next_token();
+            f(self, false)
+        } else {
+            f(self, true)
+        }
+    }
+
+    fn read_seq<U>(&mut self,
+                   f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                   -> DecodeResult<U> {
+        debug!("read_seq()");
+        match self.next_token() {
+            Event(ListStart) => {}
+            tok => return self.expected("List", tok),
+        }
+        let mut tokens = Vec::new();
+        let mut len = 0;
+        loop {
+            match self.next_token() {
+                Event(ListEnd) => break,
+                tok => try!(self.capture(tok, &mut tokens)),
+            }
+            len += 1;
+        }
+        self.push_replay(tokens);
+        f(self, len)
+    }
+
+    fn read_seq_elt<U>(&mut self,
+                       idx: uint,
+                       f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                       -> DecodeResult<U> {
+        debug!("read_seq_elt(idx={})", idx);
+        self.in_path(IndexElement(idx), f)
+    }
+
+    fn read_map<U>(&mut self,
+                   f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<U>)
+                   -> DecodeResult<U> {
+        debug!("read_map()");
+        match self.next_token() {
+            Event(ObjectStart) => {}
+            tok => return self.expected("Object", tok),
+        }
+        let mut tokens = Vec::new();
+        let mut len = 0;
+        loop {
+            match self.next_token() {
+                Event(ObjectEnd) => break,
+                MemberKey(k) => {
+                    tokens.push(Event(StringValue(k)));
+                    let tok = self.next_token();
+                    try!(self.capture(tok, &mut tokens));
+                }
+                tok => return self.expected("Object", tok),
+            }
+            len += 1;
+        }
+        self.push_replay(tokens);
+        f(self, len)
+    }
+
+    fn read_seq_elts(&mut self,
+                     f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<()>)
+                     -> DecodeResult<()> {
+        debug!("read_seq_elts()");
+        match self.next_token() {
+            Event(ListStart) => {}
+            tok => return self.expected("List", tok),
+        }
+        let mut idx = 0;
+        loop {
+            let end = *self.peek_token() == Event(ListEnd);
+            if end {
+                self.next_token();
+                return Ok(())
+            }
+            try!(self.read_seq_elt(idx, |d| f(d, idx)));
+            idx += 1;
+        }
+    }
+
+    fn read_map_elts(&mut self,
+                     f: |&mut StreamingDecoder<T>, uint| -> DecodeResult<()>)
+                     -> DecodeResult<()> {
+        debug!("read_map_elts()");
+        match self.next_token() {
+            Event(ObjectStart) => {}
+            tok => return self.expected("Object", tok),
+        }
+        let mut idx = 0;
+        loop {
+            match self.next_token() {
+                Event(ObjectEnd) => return Ok(()),
+                // The key is read as a string, ahead of the member's value.
+                MemberKey(k) => self.peeked = Some(Event(StringValue(k))),
+                tok => return self.expected("Object", tok),
+            }
+            try!(f(self, idx));
+            idx += 1;
+        }
+    }
+
+    fn read_map_elt_key<U>(&mut self,
+                           idx: uint,
+                           f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                           -> DecodeResult<U> {
+        debug!("read_map_elt_key(idx={})", idx);
+        let key = match *self.peek_token() {
+            Event(StringValue(ref key)) => Some(key.clone()),
+            _ => None,
+        };
+        self.map_key = key;
+        f(self)
+    }
+
+    fn read_map_elt_val<U>(&mut self,
+                           idx: uint,
+                           f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                           -> DecodeResult<U> {
+        debug!("read_map_elt_val(idx={})", idx);
+        let element = match self.map_key.take() {
+            Some(key) => FieldElement(key),
+            None => IndexElement(idx),
+        };
+        self.in_path(element, f)
+    }
+
+    fn read_struct_field_opt<U>(&mut self,
+                                name: &str,
+                                idx: uint,
+                                f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                                -> DecodeResult<Option<U>> {
+        debug!("read_struct_field_opt(name={}, idx={})", name, idx);
+        if try!(self.find_member(name)) {
+            Ok(Some(try!(self.in_path(FieldElement(name.to_owned()), f))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn skip_struct_field(&mut self, name: &str) -> DecodeResult<()> {
+        debug!("skip_struct_field(name={})", name);
+        if try!(self.find_member(name)) {
+            let tok = self.next_token();
+            try!(self.skip(tok));
+        }
+        Ok(())
+    }
+
+    fn try_read<U>(&mut self,
+                   f: |&mut StreamingDecoder<T>| -> DecodeResult<U>)
+                   -> DecodeResult<U> {
+        debug!("try_read()");
+        // The value is buffered, so that it can be replayed if `f` fails.
+        let mut tokens = Vec::new();
+        let tok = self.next_token();
+        try!(self.capture(tok, &mut tokens));
+        let replay = self.replay.len();
+        let frames = self.frames.len();
+        self.push_replay(tokens.clone());
+        match f(self) {
+            Ok(v) => Ok(v),
+            Err(e) => {
+                self.peeked = None;
+                self.replay.truncate(replay);
+                self.frames.truncate(frames);
+                self.push_replay(tokens);
+                Err(e)
+            }
+        }
+    }
+
+    fn path(&self) -> DecoderPath {
+        self.path.clone()
+    }
+
+    fn error(&mut self, err: &str) -> DecoderError {
+        self.positioned(ApplicationError(err.to_owned()))
+    }
+}
+
+/// Test if two json values are less than one another
+impl Ord for Json {
+    fn lt(&self, other: &Json) -> bool {
+        match *self {
+            Number(f0) => {
+                match *other {
+                    Number(f1) => f0 < f1,
+                    String(_) | Boolean(_) | List(_) | Object(_) |
+                    Null => true
+                }
+            }
+
+            String(ref s0) => {
+                match *other {
+                    Number(_) => false,
+                    String(ref s1) => s0 < s1,
+                    Boolean(_) | List(_) | Object(_) | Null => true
+                }
+            }
+
+            Boolean(b0) => {
+                match *other {
+                    Number(_) | String(_) => false,
+                    Boolean(b1) => b0 < b1,
+                    List(_) | Object(_) | Null => true
+                }
+            }
+
+            List(ref l0) => {
+                match *other {
+                    Number(_) | String(_) | Boolean(_) => false,
+                    List(ref l1) => (*l0) < (*l1),
+                    Object(_) | Null => true
+                }
+            }
+
+            Object(ref d0) => {
+                match *other {
+                    Number(_) | String(_) | Boolean(_) | List(_) => false,
+                    Object(ref d1) => d0 < d1,
+                    Null => true
+                }
+            }
+
+            Null => {
+                match *other {
+                    Number(_) | String(_) | Boolean(_) | List(_) |
+                    Object(_) =>
+                        false,
+                    Null => true
+                }
+            }
+        }
+    }
+}
+
+/// A trait for converting values to JSON
+pub trait ToJson {
+    /// Converts the value of `self` to an instance of JSON
+    fn to_json(&self) -> Json;
+}
+
+impl ToJson for Json {
+    fn to_json(&self) -> Json { (*self).clone() }
+}
+
+impl ToJson for int {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for i8 {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for i16 {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for i32 {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for i64 {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for uint {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
+
+impl ToJson for u8 {
+    fn to_json(&self) -> Json { Number(*self as f64) }
+}
 
 impl ToJson for u16 {
     fn to_json(&self) -> Json { Number(*self as f64) }
@@ -2276,15 +3595,21 @@ mod tests {
     extern crate test;
     use self::test::Bencher;
     use {Encodable, Decodable};
+    use {DecoderPath, PathElement, FieldElement, IndexElement};
     use super::{Encoder, Decoder, Error, Boolean, Number, List, String, Null,
                 PrettyEncoder, Object, Json, from_str, ParseError, ExpectedError,
                 MissingFieldError, UnknownVariantError, DecodeResult, DecoderError,
-                JsonEvent, Parser, StackElement,
+                StreamingDecoder, PositionedError, ApplicationError,
+                UnknownFieldError, PathError, JsonEvent, Parser, StackElement,
                 ObjectStart, ObjectEnd, ListStart, ListEnd, BooleanValue, NumberValue, StringValue,
                 NullValue, SyntaxError, Key, Index, Stack,
                 InvalidSyntax, InvalidNumber, EOFWhileParsingObject, EOFWhileParsingList,
                 EOFWhileParsingValue, EOFWhileParsingString, KeyMustBeAString, ExpectedColon,
-                TrailingCharacters};
+                TrailingCharacters, MalformedPointer, MissingMember, InvalidIndex,
+                IndexOutOfBounds, NotAContainer, InvalidPatch, PatchPointerError,
+                TestFailed, MoveIntoChild, PatchAdd, PatchRemove, PatchReplace,
+                PatchMove, PatchCopy, PatchTest, PatchOperation, PatchError,
+                patch_from_json, diff, ToJson};
     use std::io;
     use collections::TreeMap;
 
@@ -2860,17 +4185,32 @@ mod tests {
             }
         }
     }
+    // `err` as reported for the value at `elements`.
+    fn at(err: DecoderError, elements: Vec<PathElement>) -> DecoderError {
+        let mut path = DecoderPath::new();
+        for element in elements.move_iter() {
+            path.push(element);
+        }
+        PathError(box err, path)
+    }
+    fn field(name: &str) -> PathElement {
+        FieldElement(name.to_owned())
+    }
     #[test]
     fn test_decode_errors_struct() {
         check_err::<DecodeStruct>("[]", ExpectedError("Object".to_owned(), "[]".to_owned()));
         check_err::<DecodeStruct>("{\"x\": true, \"y\": true, \"z\": \"\", \"w\": []}",
-                                  ExpectedError("Number".to_owned(), "true".to_owned()));
+                                  at(ExpectedError("Number".to_owned(), "true".to_owned()),
+                                     vec!(field("x"))));
         check_err::<DecodeStruct>("{\"x\": 1, \"y\": [], \"z\": \"\", \"w\": []}",
-                                  ExpectedError("Boolean".to_owned(), "[]".to_owned()));
+                                  at(ExpectedError("Boolean".to_owned(), "[]".to_owned()),
+                                     vec!(field("y"))));
         check_err::<DecodeStruct>("{\"x\": 1, \"y\": true, \"z\": {}, \"w\": []}",
-                                  ExpectedError("String".to_owned(), "{}".to_owned()));
+                                  at(ExpectedError("String".to_owned(), "{}".to_owned()),
+                                     vec!(field("z"))));
         check_err::<DecodeStruct>("{\"x\": 1, \"y\": true, \"z\": \"\", \"w\": null}",
-                                  ExpectedError("List".to_owned(), "null".to_owned()));
+                                  at(ExpectedError("List".to_owned(), "null".to_owned()),
+                                     vec!(field("w"))));
         check_err::<DecodeStruct>("{\"x\": 1, \"y\": true, \"z\": \"\"}",
                                   MissingFieldError("w".to_owned()));
     }
@@ -2888,6 +4228,415 @@ mod tests {
                                 UnknownVariantError("C".to_owned()));
     }
 
+    fn streaming_decode<T: Decodable<StreamingDecoder<::std::str::Chars<'static>>,
+                                     DecoderError>>(s: &'static str)
+                        -> DecodeResult<T> {
+        let mut decoder = StreamingDecoder::new(s.chars());
+        let value = try!(Decodable::decode(&mut decoder));
+        try!(decoder.finish());
+        Ok(value)
+    }
+
+    #[test]
+    fn test_streaming_decode_struct() {
+        let s = "{
+            \"inner\": [
+                { \"c\": [\"abc\", \"xyz\"], \"b\": 2, \"extra\": {\"x\": [1]}, \"a\": null }
+            ]
+        }";
+        let v: Outer = streaming_decode(s).unwrap();
+        assert_eq!(
+            v,
+            Outer {
+                inner: ~[
+                    Inner { a: (), b: 2, c: ~["abc".to_owned(), "xyz".to_owned()] }
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_streaming_decode_option() {
+        let value: Option<~str> = streaming_decode("null").unwrap();
+        assert_eq!(value, None);
+        let value: Option<~str> = streaming_decode("\"jodhpurs\"").unwrap();
+        assert_eq!(value, Some("jodhpurs".to_owned()));
+    }
+
+    #[test]
+    fn test_streaming_decode_enum() {
+        let value: Animal = streaming_decode("\"Dog\"").unwrap();
+        assert_eq!(value, Dog);
+
+        let value: Animal =
+            streaming_decode("{\"variant\":\"Frog\",\"fields\":[\"Henry\",349]}").unwrap();
+        assert_eq!(value, Frog("Henry".to_owned(), 349));
+
+        let value: Animal =
+            streaming_decode("{\"fields\":[\"Henry\",349],\"variant\":\"Frog\"}").unwrap();
+        assert_eq!(value, Frog("Henry".to_owned(), 349));
+    }
+
+    #[test]
+    fn test_streaming_decode_map() {
+        let s = "{\"a\": \"Dog\", \"b\": {\"variant\":\"Frog\",\
+                  \"fields\":[\"Henry\", 349]}}";
+        let mut map: TreeMap<~str, Animal> = streaming_decode(s).unwrap();
+        assert_eq!(map.pop(&"a".to_owned()), Some(Dog));
+        assert_eq!(map.pop(&"b".to_owned()), Some(Frog("Henry".to_owned(), 349)));
+    }
+
+    #[test]
+    fn test_streaming_decode_matches_tree_decoder() {
+        let inputs = [
+            "[]",
+            "[{\"inner\": []}]",
+            "[{\"inner\": [{\"a\": null, \"b\": 1, \"c\": []}]}, {\"inner\": []}]",
+            "[{\"inner\": [{\"b\": 1, \"c\": [\"x\"], \"a\": null}]}]",
+        ];
+        for s in inputs.iter() {
+            let tree: ~[Outer] =
+                Decodable::decode(&mut Decoder::new(from_str(*s).unwrap())).unwrap();
+            let streamed: ~[Outer] = streaming_decode(*s).unwrap();
+            assert_eq!(tree, streamed);
+        }
+    }
+
+    // Removes the position from a streaming decoder error, which is either at
+    // the top or just under the path.
+    fn unposition(err: DecoderError) -> Option<DecoderError> {
+        match err {
+            PositionedError(e, _, _) => Some(*e),
+            PathError(e, path) => match *e {
+                PositionedError(e, _, _) => Some(PathError(e, path)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn check_streaming_err<T: Decodable<StreamingDecoder<::std::str::Chars<'static>>,
+                                        DecoderError>>(to_parse: &'static str,
+                                                       expected: DecoderError) {
+        match streaming_decode::<T>(to_parse) {
+            Ok(_) => fail!("`{}` parsed & decoded ok, expecting error `{}`",
+                           to_parse, expected),
+            Err(e) => match unposition(e.clone()) {
+                Some(e) => assert_eq!(e, expected),
+                None => fail!("`{}` failed with unpositioned error {}", to_parse, e),
+            },
+        }
+    }
+
+    #[test]
+    fn test_streaming_decode_errors() {
+        check_streaming_err::<DecodeStruct>("[]",
+            ExpectedError("Object".to_owned(), "[]".to_owned()));
+        check_streaming_err::<DecodeStruct>("{\"x\": true, \"y\": true, \"z\": \"\", \"w\": []}",
+            at(ExpectedError("Number".to_owned(), "true".to_owned()), vec!(field("x"))));
+        check_streaming_err::<DecodeStruct>("{\"x\": 1, \"y\": true, \"z\": {}, \"w\": []}",
+            at(ExpectedError("String".to_owned(), "{}".to_owned()), vec!(field("z"))));
+        check_streaming_err::<DecodeStruct>("{\"x\": 1, \"y\": true, \"z\": \"\"}",
+            MissingFieldError("w".to_owned()));
+        check_streaming_err::<DecodeEnum>("{}",
+            MissingFieldError("variant".to_owned()));
+        check_streaming_err::<DecodeEnum>("{\"variant\": \"A\"}",
+            MissingFieldError("fields".to_owned()));
+        check_streaming_err::<DecodeEnum>("{\"variant\": \"C\", \"fields\": []}",
+            UnknownVariantError("C".to_owned()));
+    }
+
+    #[test]
+    fn test_streaming_decode_error_position() {
+        let s = "{\n  \"x\": 1,\n  \"y\": 2}";
+        match streaming_decode::<DecodeStruct>(s) {
+            Err(PathError(e, path)) => match *e {
+                PositionedError(e, line, _) => {
+                    assert_eq!(*e, ExpectedError("Boolean".to_owned(), "2".to_owned()));
+                    assert_eq!(path.to_str(), "y".to_owned());
+                    assert_eq!(line, 3);
+                }
+                e => fail!("unexpected error {}", e),
+            },
+            r => fail!("unexpected result {}", r.is_ok()),
+        }
+    }
+
+    #[test]
+    fn test_streaming_decode_elements_as_parsed() {
+        // The bad element is reported before the parser gets to the end of
+        // the list, or the map, which is never reached.
+        check_streaming_err::<~[uint]>("[1, \"x\", 2",
+            at(ExpectedError("Number".to_owned(), "\"x\"".to_owned()),
+               vec!(IndexElement(1))));
+        check_streaming_err::<TreeMap<~str, uint>>("{\"a\": 1, \"b\": [], \"c\": 2",
+            at(ExpectedError("Number".to_owned(), "[]".to_owned()), vec!(field("b"))));
+    }
+
+    #[test]
+    fn test_streaming_decode_syntax_errors() {
+        assert_eq!(streaming_decode::<~[uint]>("[1, 2"),
+                   Err(ParseError(SyntaxError(EOFWhileParsingList, 1, 6))));
+        assert_eq!(streaming_decode::<~[uint]>("[1, 2] 3"),
+                   Err(ParseError(SyntaxError(TrailingCharacters, 1, 8))));
+    }
+
+    #[deriving(Clone, Eq, Show, Encodable, Decodable)]
+    struct Account {
+        #[serialize(rename = "userName")]
+        user_name: ~str,
+        #[serialize(default)]
+        active: bool,
+        #[serialize(optional)]
+        email: Option<~str>,
+        #[serialize(skip)]
+        session: uint,
+    }
+
+    #[deriving(Eq, Show, Encodable, Decodable)]
+    #[serialize(external)]
+    enum External {
+        Unit,
+        #[serialize(rename = "one")]
+        One(uint),
+        Two(uint, ~str),
+        Named { x: uint, #[serialize(rename = "Y")] y: uint },
+    }
+
+    #[deriving(Eq, Show, Encodable, Decodable)]
+    #[serialize(tag = "type")]
+    enum Internal {
+        Empty,
+        Point { x: int, #[serialize(default)] y: int },
+    }
+
+    #[deriving(Eq, Show, Encodable, Decodable)]
+    #[serialize(untagged)]
+    enum Untagged {
+        Missing,
+        Label(~str),
+        Scalar(f64),
+        Pair(f64, f64),
+        Record { name: ~str },
+    }
+
+    fn decode<T: Decodable<Decoder, DecoderError>>(s: &str) -> DecodeResult<T> {
+        Decodable::decode(&mut Decoder::new(from_str(s).unwrap()))
+    }
+
+    // Checks that `value` is encoded as `s`, and that both decoders decode
+    // `s` back to `value`.
+    fn check_roundtrip<'a, T: Eq + ::std::fmt::Show +
+                              Encodable<Encoder<'a>, io::IoError> +
+                              Decodable<Decoder, DecoderError> +
+                              Decodable<StreamingDecoder<::std::str::Chars<'static>>,
+                                        DecoderError>>(value: T, s: &'static str) {
+        assert_eq!(Encoder::str_encode(&value), s.to_owned());
+        assert_eq!(decode::<T>(s).unwrap(), value);
+        assert_eq!(streaming_decode::<T>(s).unwrap(), value);
+    }
+
+    #[test]
+    fn test_field_attributes() {
+        let account = Account {
+            user_name: "ann".to_owned(),
+            active: true,
+            email: Some("ann@example.com".to_owned()),
+            session: 0,
+        };
+        check_roundtrip(account,
+            "{\"userName\":\"ann\",\"active\":true,\"email\":\"ann@example.com\"}");
+
+        // skipped fields aren't encoded
+        let account = Account {
+            user_name: "bob".to_owned(),
+            active: false,
+            email: None,
+            session: 42,
+        };
+        assert_eq!(Encoder::str_encode(&account),
+                   "{\"userName\":\"bob\",\"active\":false,\"email\":null}".to_owned());
+
+        let expected = Account {
+            user_name: "bob".to_owned(),
+            active: false,
+            email: None,
+            session: 0,
+        };
+        let s = "{\"userName\":\"bob\",\"session\":42}";
+        assert_eq!(decode::<Account>(s).unwrap(), expected);
+        assert_eq!(streaming_decode::<Account>(s).unwrap(), expected);
+
+        check_err::<Account>("{\"user_name\":\"bob\"}", MissingFieldError("userName".to_owned()));
+        check_streaming_err::<Account>("{\"user_name\":\"bob\"}",
+                                       MissingFieldError("userName".to_owned()));
+    }
+
+    #[test]
+    fn test_external_enum() {
+        check_roundtrip(Unit, "\"Unit\"");
+        check_roundtrip(One(1), "{\"one\":1}");
+        check_roundtrip(Two(2, "a".to_owned()), "{\"Two\":[2,\"a\"]}");
+        check_roundtrip(Named { x: 1, y: 2 }, "{\"Named\":{\"x\":1,\"Y\":2}}");
+
+        check_err::<External>("\"One\"", ApplicationError("unknown variant One".to_owned()));
+        let err = at(ApplicationError("unknown variant Three".to_owned()), vec!(field("Three")));
+        check_err::<External>("{\"Three\":3}", err.clone());
+        check_err::<External>("1", ExpectedError("Object".to_owned(), "1".to_owned()));
+        check_streaming_err::<External>("{\"Three\":3}", err);
+    }
+
+    #[test]
+    fn test_internal_enum() {
+        check_roundtrip(Empty, "{\"type\":\"Empty\"}");
+        check_roundtrip(Point { x: 1, y: 2 }, "{\"type\":\"Point\",\"x\":1,\"y\":2}");
+
+        let s = "{\"x\":1,\"type\":\"Point\"}";
+        assert_eq!(decode::<Internal>(s).unwrap(), Point { x: 1, y: 0 });
+        assert_eq!(streaming_decode::<Internal>(s).unwrap(), Point { x: 1, y: 0 });
+
+        check_err::<Internal>("{\"type\":\"Line\"}",
+                              ApplicationError("unknown variant Line".to_owned()));
+        check_err::<Internal>("{\"x\":1}", MissingFieldError("type".to_owned()));
+        check_streaming_err::<Internal>("{\"type\":\"Line\"}",
+                                        ApplicationError("unknown variant Line".to_owned()));
+    }
+
+    #[test]
+    fn test_untagged_enum() {
+        check_roundtrip(Missing, "null");
+        check_roundtrip(Label("a".to_owned()), "\"a\"");
+        check_roundtrip(Scalar(1.5), "1.5");
+        check_roundtrip(Pair(1.0, 2.0), "[1,2]");
+        check_roundtrip(Record { name: "a".to_owned() }, "{\"name\":\"a\"}");
+
+        let err = ApplicationError("data matched no variant of `Untagged`".to_owned());
+        check_err::<Untagged>("true", err.clone());
+        check_err::<Untagged>("{\"label\":\"a\"}", err.clone());
+        check_streaming_err::<Untagged>("true", err);
+    }
+
+    #[deriving(Decodable)]
+    struct Config {
+        servers: Vec<Server>,
+    }
+
+    #[deriving(Decodable)]
+    struct Server {
+        host: ~str,
+        port: u16,
+    }
+
+    #[deriving(Decodable)]
+    struct Settings {
+        config: Config,
+    }
+
+    #[test]
+    fn test_decode_error_path() {
+        let s = "{\"config\": {\"servers\": [{\"host\": \"a\", \"port\": 1},
+                                          {\"host\": \"b\", \"port\": \"abc\"}]}}";
+        let expected = at(ExpectedError("Number".to_owned(), "\"abc\"".to_owned()),
+                          vec!(field("config"), field("servers"), IndexElement(1),
+                               field("port")));
+        check_err::<Settings>(s, expected.clone());
+        check_streaming_err::<Settings>(s, expected.clone());
+        assert_eq!(format!("{}", expected),
+                   "config.servers[1].port: expected Number, found \"abc\"".to_owned());
+
+        // errors about a struct as a whole are reported at the struct
+        let s = "{\"config\": {\"servers\": [{\"host\": \"a\"}]}}";
+        let expected = at(MissingFieldError("port".to_owned()),
+                          vec!(field("config"), field("servers"), IndexElement(0)));
+        check_err::<Settings>(s, expected.clone());
+        check_streaming_err::<Settings>(s, expected);
+
+        let s = "{\"config\": {\"servers\": {}}}";
+        check_err::<Settings>(s, at(ExpectedError("List".to_owned(), "{}".to_owned()),
+                                    vec!(field("config"), field("servers"))));
+
+        // map keys and enum variants are part of the path
+        let res: DecodeResult<TreeMap<~str, DecodeEnum>> =
+            decode("{\"a\": {\"variant\": \"A\", \"fields\": [true]}}");
+        assert_eq!(res.err().unwrap().to_str(),
+                   "a::A[0]: expected Number, found true".to_owned());
+    }
+
+    #[test]
+    fn test_strict_decode() {
+        let s = "{\"servers\": [{\"host\": \"a\", \"port\": 1, \"weight\": 2}]}";
+        assert!(decode::<Config>(s).is_ok());
+        assert!(streaming_decode::<Config>(s).is_ok());
+
+        let expected = at(UnknownFieldError("weight".to_owned()),
+                          vec!(field("servers"), IndexElement(0)));
+        let mut decoder = Decoder::new(from_str(s).unwrap());
+        decoder.set_strict(true);
+        let res: DecodeResult<Config> = Decodable::decode(&mut decoder);
+        assert_eq!(res.err().unwrap(), expected);
+
+        // the unknown member may also come before the fields
+        for s in ["{\"servers\": [{\"weight\": 2, \"host\": \"a\", \"port\": 1}]}",
+                  s].iter() {
+            let mut decoder = StreamingDecoder::new(s.chars());
+            decoder.set_strict(true);
+            let res: DecodeResult<Config> = Decodable::decode(&mut decoder);
+            assert_eq!(unposition(res.err().unwrap()), Some(expected.clone()));
+        }
+    }
+
+    #[test]
+    fn test_strict_decode_skipped_field() {
+        let s = "{\"userName\": \"a\", \"session\": 5, \"active\": true}";
+        let expected = Account {
+            user_name: "a".to_owned(),
+            active: true,
+            email: None,
+            session: 0,
+        };
+
+        let mut decoder = Decoder::new(from_str(s).unwrap());
+        decoder.set_strict(true);
+        let res: DecodeResult<Account> = Decodable::decode(&mut decoder);
+        assert_eq!(res, Ok(expected.clone()));
+
+        let mut decoder = StreamingDecoder::new(s.chars());
+        decoder.set_strict(true);
+        let res: DecodeResult<Account> = Decodable::decode(&mut decoder);
+        assert_eq!(res, Ok(expected));
+    }
+
+    // Decodes a number, remembering where it was found.
+    #[deriving(Clone, Eq, Show)]
+    struct Located(~str);
+
+    impl<E, D: ::Decoder<E>> Decodable<D, E> for Located {
+        fn decode(d: &mut D) -> Result<Located, E> {
+            let path = d.path();
+            try!(d.read_uint());
+            Ok(Located(path.to_str()))
+        }
+    }
+
+    #[deriving(Decodable)]
+    struct Locations {
+        first: Located,
+        rest: Vec<Located>,
+    }
+
+    #[test]
+    fn test_decoder_path() {
+        let s = "{\"rest\": [1, 2], \"first\": 0}";
+        let expected = vec!(Located("first".to_owned()),
+                            Located("rest[0]".to_owned()),
+                            Located("rest[1]".to_owned()));
+
+        let l = decode::<Locations>(s).unwrap();
+        assert_eq!(vec!(l.first).append(l.rest.as_slice()), expected);
+        let l = streaming_decode::<Locations>(s).unwrap();
+        assert_eq!(vec!(l.first).append(l.rest.as_slice()), expected);
+    }
+
     #[test]
     fn test_find(){
         let json_value = from_str("{\"dog\" : \"cat\"}").unwrap();
@@ -2994,6 +4743,198 @@ mod tests {
         assert!(json_null.is_some() && json_null.unwrap() == expected_null);
     }
 
+    #[test]
+    fn test_pointer() {
+        // The example document of RFC 6901
+        let json = from_str(r#"{
+            "foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3,
+            "g|h": 4, "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8
+        }"#).unwrap();
+        assert_eq!(json.pointer(""), Ok(&json));
+        assert_eq!(json.pointer("/foo"), Ok(&from_str(r#"["bar", "baz"]"#).unwrap()));
+        assert_eq!(json.pointer("/foo/0"), Ok(&String("bar".to_owned())));
+        assert_eq!(json.pointer("/"), Ok(&Number(0.0)));
+        assert_eq!(json.pointer("/a~1b"), Ok(&Number(1.0)));
+        assert_eq!(json.pointer("/c%d"), Ok(&Number(2.0)));
+        assert_eq!(json.pointer("/e^f"), Ok(&Number(3.0)));
+        assert_eq!(json.pointer("/g|h"), Ok(&Number(4.0)));
+        assert_eq!(json.pointer("/i\\j"), Ok(&Number(5.0)));
+        assert_eq!(json.pointer("/k\"l"), Ok(&Number(6.0)));
+        assert_eq!(json.pointer("/ "), Ok(&Number(7.0)));
+        assert_eq!(json.pointer("/m~0n"), Ok(&Number(8.0)));
+    }
+
+    #[test]
+    fn test_pointer_errors() {
+        let json = from_str(r#"{"a": [1, {"b": true}]}"#).unwrap();
+        assert_eq!(json.pointer("a"), Err(MalformedPointer("a".to_owned())));
+        assert_eq!(json.pointer("/a~2"), Err(MalformedPointer("/a~2".to_owned())));
+        assert_eq!(json.pointer("/a~"), Err(MalformedPointer("/a~".to_owned())));
+        assert_eq!(json.pointer("/b"), Err(MissingMember("b".to_owned())));
+        assert_eq!(json.pointer("/a/01"), Err(InvalidIndex("01".to_owned())));
+        assert_eq!(json.pointer("/a/-"), Err(InvalidIndex("-".to_owned())));
+        assert_eq!(json.pointer("/a/x"), Err(InvalidIndex("x".to_owned())));
+        assert_eq!(json.pointer("/a/2"), Err(IndexOutOfBounds(2, 2)));
+        assert_eq!(json.pointer("/a/0/b"), Err(NotAContainer("b".to_owned())));
+        assert_eq!(json.pointer("/a/1/b"), Ok(&Boolean(true)));
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        let mut json = from_str(r#"{"a": [1, {"b": true}]}"#).unwrap();
+        *json.pointer_mut("/a/1/b").unwrap() = Null;
+        *json.pointer_mut("/a/0").unwrap() = String("x".to_owned());
+        assert_eq!(json, from_str(r#"{"a": ["x", {"b": null}]}"#).unwrap());
+        assert!(json.pointer_mut("/a/2").is_err());
+    }
+
+    fn check_patch(doc: &str, patch: &str, expected: &str) {
+        let mut json = from_str(doc).unwrap();
+        let patch = patch_from_json(&from_str(patch).unwrap()).unwrap();
+        json.apply_patch(patch.as_slice()).unwrap();
+        assert_eq!(json, from_str(expected).unwrap());
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        // Examples from appendix A of RFC 6902
+        check_patch(r#"{"foo": "bar"}"#,
+                    r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                    r#"{"baz": "qux", "foo": "bar"}"#);
+        check_patch(r#"{"foo": ["bar", "baz"]}"#,
+                    r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                    r#"{"foo": ["bar", "qux", "baz"]}"#);
+        check_patch(r#"{"baz": "qux", "foo": "bar"}"#,
+                    r#"[{"op": "remove", "path": "/baz"}]"#,
+                    r#"{"foo": "bar"}"#);
+        check_patch(r#"{"foo": ["bar", "qux", "baz"]}"#,
+                    r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                    r#"{"foo": ["bar", "baz"]}"#);
+        check_patch(r#"{"baz": "qux", "foo": "bar"}"#,
+                    r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                    r#"{"baz": "boo", "foo": "bar"}"#);
+        check_patch(r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                    r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                    r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#);
+        check_patch(r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                    r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                    r#"{"foo": ["all", "cows", "eat", "grass"]}"#);
+        check_patch(r#"{"foo": ["bar"]}"#,
+                    r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                    r#"{"foo": ["bar", ["abc", "def"]]}"#);
+        check_patch(r#"{"foo": {"bar": 1}}"#,
+                    r#"[{"op": "copy", "from": "/foo", "path": "/baz"},
+                        {"op": "test", "path": "/baz/bar", "value": 1}]"#,
+                    r#"{"foo": {"bar": 1}, "baz": {"bar": 1}}"#);
+        check_patch(r#"{"foo": 1}"#,
+                    r#"[{"op": "replace", "path": "", "value": [true]}]"#,
+                    r#"[true]"#);
+    }
+
+    #[test]
+    fn test_apply_patch_errors() {
+        let doc = from_str(r#"{"foo": {"bar": 1}, "list": [1]}"#).unwrap();
+        let check = |patch: &[PatchOperation], err: PatchError| {
+            let mut json = doc.clone();
+            assert_eq!(json.apply_patch(patch), Err(err));
+            // nothing is applied if any operation fails
+            assert_eq!(json, doc);
+        };
+        check(&[PatchAdd("/baz".to_owned(), Null),
+               PatchTest("/foo/bar".to_owned(), Number(2.0))],
+              TestFailed("/foo/bar".to_owned()));
+        check(&[PatchRemove("/baz".to_owned())],
+              PatchPointerError(MissingMember("baz".to_owned())));
+        check(&[PatchAdd("/baz/qux".to_owned(), Null)],
+              PatchPointerError(MissingMember("baz".to_owned())));
+        check(&[PatchAdd("/list/2".to_owned(), Null)],
+              PatchPointerError(IndexOutOfBounds(2, 1)));
+        check(&[PatchReplace("/list/-".to_owned(), Null)],
+              PatchPointerError(InvalidIndex("-".to_owned())));
+        check(&[PatchMove("/foo".to_owned(), "/foo/bar".to_owned())],
+              MoveIntoChild("/foo".to_owned(), "/foo/bar".to_owned()));
+        check(&[PatchCopy("/foo/bar".to_owned(), "/foo/bar/baz".to_owned())],
+              PatchPointerError(NotAContainer("baz".to_owned())));
+    }
+
+    #[test]
+    fn test_patch_from_json() {
+        let ops = [PatchAdd("/a".to_owned(), Number(1.0)),
+                   PatchRemove("/b".to_owned()),
+                   PatchReplace("/c".to_owned(), Null),
+                   PatchMove("/d".to_owned(), "/e".to_owned()),
+                   PatchCopy("/f".to_owned(), "/g".to_owned()),
+                   PatchTest("/h".to_owned(), Boolean(true))];
+        let json = List(ops.iter().map(|op| op.to_json()).collect());
+        assert_eq!(patch_from_json(&json).unwrap().as_slice(), ops.as_slice());
+
+        let check_invalid = |s: &str, msg: &str| {
+            assert_eq!(patch_from_json(&from_str(s).unwrap()),
+                       Err(InvalidPatch(msg.to_owned())));
+        };
+        check_invalid(r#"{}"#, "patch is not a list");
+        check_invalid(r#"[1]"#, "operation is not an object");
+        check_invalid(r#"[{"path": "/a"}]"#, "operation has no `op` member");
+        check_invalid(r#"[{"op": "add", "path": "/a"}]"#, "operation has no `value` member");
+        check_invalid(r#"[{"op": "move", "path": 1}]"#, "`path` member is not a string");
+        check_invalid(r#"[{"op": "frob", "path": "/a"}]"#, "unknown operation `frob`");
+    }
+
+    #[test]
+    fn test_diff() {
+        let pairs = [
+            (r#"{"a": 1, "b": [1, 2, 3], "c": {"d": null}}"#,
+             r#"{"a": 1, "b": [1, 4], "c": {"e": "f"}, "a/~": true}"#),
+            (r#"[1, 2]"#, r#"[1, 2, {"x": []}, 3]"#),
+            (r#"{"a": [1]}"#, r#"{"a": {"0": 1}}"#),
+            (r#"null"#, r#"{"a": 1}"#),
+            (r#"{"a": 1}"#, r#"{"a": 1}"#),
+        ];
+        for &(from, to) in pairs.iter() {
+            let mut json = from_str(from).unwrap();
+            let to = from_str(to).unwrap();
+            let patch = diff(&json, &to);
+            json.apply_patch(patch.as_slice()).unwrap();
+            assert_eq!(json, to);
+        }
+
+        let from = from_str(r#"{"a": 1, "b": [true, 2, 3], "c": 3}"#).unwrap();
+        let to = from_str(r#"{"a": 1, "b": [false], "d": 4}"#).unwrap();
+        assert_eq!(diff(&from, &to).as_slice(),
+                   [PatchReplace("/b/0".to_owned(), Boolean(false)),
+                    PatchRemove("/b/2".to_owned()),
+                    PatchRemove("/b/1".to_owned()),
+                    PatchRemove("/c".to_owned()),
+                    PatchAdd("/d".to_owned(), Number(4.0))].as_slice());
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        // Examples from appendix A of RFC 7386
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (r#"{"a":{"b":"c"}}"#, r#"{"a":{"b":"d","c":null}}"#, r#"{"a":{"b":"d"}}"#),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (r#"{}"#, r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for &(target, patch, expected) in cases.iter() {
+            let mut json = from_str(target).unwrap();
+            json.merge_patch(&from_str(patch).unwrap());
+            assert_eq!(json, from_str(expected).unwrap());
+        }
+    }
+
     #[test]
     fn test_encode_hashmap_with_numeric_key() {
         use std::str::from_utf8;
@@ -3391,4 +5332,40 @@ mod tests {
         let src = big_json();
         b.iter( || { let _ = from_str(src); });
     }
+
+    #[deriving(Decodable)]
+    struct Record {
+        a: bool,
+        b: Option<int>,
+        c: f64,
+        d: ~str,
+        e: Vec<uint>,
+    }
+
+    fn big_records() -> ~str {
+        let mut src = "[\n".to_owned();
+        for i in range(0, 500) {
+            if i > 0 { src = src + ","; }
+            src = src + r#"{ "a": true, "b": null, "c":3.1415, "d": "Hello world", "e": [1,2,3]}"#;
+        }
+        src + "]"
+    }
+
+    #[bench]
+    fn bench_decode_large(b: &mut Bencher) {
+        let src = big_records();
+        b.iter( || {
+            let json = from_str(src).unwrap();
+            let _: Vec<Record> = Decodable::decode(&mut Decoder::new(json)).unwrap();
+        });
+    }
+
+    #[bench]
+    fn bench_streaming_decode_large(b: &mut Bencher) {
+        let src = big_records();
+        b.iter( || {
+            let mut decoder = StreamingDecoder::new(src.chars());
+            let _: Vec<Record> = Decodable::decode(&mut decoder).unwrap();
+        });
+    }
 }
@@ -21,7 +21,7 @@ Core encoding and decoding interfaces.
 #![doc(html_logo_url = "http://www.rust-lang.org/logos/rust-logo-128x128-blk-v2.png",
        html_favicon_url = "http://www.rust-lang.org/favicon.ico",
        html_root_url = "http://static.rust-lang.org/doc/master")]
-#![feature(macro_rules, managed_boxes, default_type_params, phase)]
+#![feature(macro_rules, managed_boxes, default_type_params, phase, struct_variant)]
 
 // test harness access
 #[cfg(test)]
@@ -32,12 +32,14 @@ extern crate log;
 extern crate collections;
 
 pub use self::serialize::{Decoder, Encoder, Decodable, Encodable,
-                          DecoderHelpers, EncoderHelpers};
+                          DecoderHelpers, EncoderHelpers, DecoderPath, PathElement,
+                          FieldElement, IndexElement, VariantElement};
 
 mod serialize;
 mod collection_impls;
 
 pub mod base64;
+pub mod cbor;
 pub mod ebml;
 pub mod hex;
 pub mod json;
@@ -14,6 +14,7 @@
 Core encoding and decoding interfaces.
 */
 
+use std::fmt;
 use std::path;
 use std::rc::Rc;
 
@@ -161,6 +162,143 @@ pub trait Decoder<E> {
     fn read_map<T>(&mut self, f: |&mut Self, uint| -> Result<T, E>) -> Result<T, E>;
     fn read_map_elt_key<T>(&mut self, idx: uint, f: |&mut Self| -> Result<T, E>) -> Result<T, E>;
     fn read_map_elt_val<T>(&mut self, idx: uint, f: |&mut Self| -> Result<T, E>) -> Result<T, E>;
+
+    /// Reads a sequence like `read_seq`, but without asking for its length
+    /// up front: `f` reads each element in turn, given its index, until there
+    /// are no more. Decoders which only find out the length of a sequence by
+    /// reading it can decode its elements as they go.
+    fn read_seq_elts(&mut self, f: |&mut Self, uint| -> Result<(), E>) -> Result<(), E> {
+        self.read_seq(|d, len| {
+            for i in range(0, len) {
+                try!(d.read_seq_elt(i, |d| f(d, i)));
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads a map like `read_seq_elts`, with `f` reading the key and the
+    /// value of each entry with `read_map_elt_key` and `read_map_elt_val`.
+    fn read_map_elts(&mut self, f: |&mut Self, uint| -> Result<(), E>) -> Result<(), E> {
+        self.read_map(|d, len| {
+            for i in range(0, len) {
+                try!(f(d, i));
+            }
+            Ok(())
+        })
+    }
+
+    // Self-describing formats:
+
+    /// Reads a struct field like `read_struct_field`, but returns `None`
+    /// instead of failing if the field is not present. Formats which can't
+    /// tell whether a field is present always read it.
+    fn read_struct_field_opt<T>(&mut self,
+                                f_name: &str,
+                                f_idx: uint,
+                                f: |&mut Self| -> Result<T, E>)
+                                -> Result<Option<T>, E> {
+        self.read_struct_field(f_name, f_idx, |d| f(d).map(|v| Some(v)))
+    }
+
+    /// Passes over the struct field `f_name`, which is not decoded. Strict
+    /// decoders must accept the field if it is present; formats which can't
+    /// tell which field is which don't need to do anything.
+    fn skip_struct_field(&mut self, _f_name: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Attempts to read the next value with `f`. If `f` fails, formats which
+    /// support it rewind so that the value can be read again, in a different
+    /// way; otherwise the decoder is left wherever `f` stopped.
+    fn try_read<T>(&mut self, f: |&mut Self| -> Result<T, E>) -> Result<T, E> {
+        f(self)
+    }
+
+    /// The location of the value being decoded within the outermost value,
+    /// for example to include in errors. Decoders which don't keep track of
+    /// it return an empty path.
+    fn path(&self) -> DecoderPath {
+        DecoderPath::new()
+    }
+
+    /// Creates an error with a custom message, for decoding failures which
+    /// are detected by a `Decodable` implementation rather than the decoder.
+    ///
+    /// There is no way to build an arbitrary `E`, so by default this fails
+    /// the task with the message. Decoders should override it to return one
+    /// of their own errors.
+    fn error(&mut self, err: &str) -> E {
+        fail!("decoding error: {}", err)
+    }
+}
+
+/// A step from a value to one of its parts.
+#[deriving(Clone, Eq)]
+pub enum PathElement {
+    /// A field of a struct or enum variant, or the value of a map entry with
+    /// this key.
+    FieldElement(~str),
+    /// An element of a sequence or tuple, or an argument of an enum variant.
+    IndexElement(uint),
+    /// The variant of an enum.
+    VariantElement(~str),
+}
+
+/// The location of a value within the value being decoded, which decoders
+/// keep track of to say where an error was found.
+///
+/// A path is displayed as `config.servers[3].port`, with variants displayed
+/// as `shape::Circle`.
+#[deriving(Clone, Eq)]
+pub struct DecoderPath {
+    elements: Vec<PathElement>,
+}
+
+impl DecoderPath {
+    /// Creates an empty path, the location of the value being decoded.
+    pub fn new() -> DecoderPath {
+        DecoderPath { elements: Vec::new() }
+    }
+
+    /// Appends a step to the path.
+    pub fn push(&mut self, element: PathElement) {
+        self.elements.push(element)
+    }
+
+    /// Removes the last step of the path.
+    pub fn pop(&mut self) -> Option<PathElement> {
+        self.elements.pop()
+    }
+
+    /// Returns the steps of the path, from the outermost value.
+    pub fn elements<'a>(&'a self) -> &'a [PathElement] {
+        self.elements.as_slice()
+    }
+
+    /// Returns the number of steps in the path.
+    pub fn len(&self) -> uint {
+        self.elements.len()
+    }
+
+    /// Returns true if the path has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+impl fmt::Show for DecoderPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, element) in self.elements.iter().enumerate() {
+            match *element {
+                FieldElement(ref name) if i == 0 => try!(write!(f.buf, "{}", *name)),
+                FieldElement(ref name) => try!(write!(f.buf, ".{}", *name)),
+                IndexElement(idx) => try!(write!(f.buf, "[{}]", idx)),
+                VariantElement(ref name) if i == 0 => try!(write!(f.buf, "{}", *name)),
+                VariantElement(ref name) => try!(write!(f.buf, "::{}", *name)),
+            }
+        }
+        Ok(())
+    }
 }
 
 pub trait Encodable<S:Encoder<E>, E> {
@@ -453,14 +591,12 @@ impl<E, S:Encoder<E>,T:Encodable<S, E>> Encodable<S, E> for ~[T] {
 
 impl<E, D:Decoder<E>,T:Decodable<D, E>> Decodable<D, E> for ~[T] {
     fn decode(d: &mut D) -> Result<~[T], E> {
-        d.read_seq(|d, len| {
-            let mut v: Vec<T> = Vec::with_capacity(len);
-            for i in range(0, len) {
-                v.push(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            let k = v.move_iter().collect::<~[T]>();
-            Ok(k)
-        })
+        let mut v: Vec<T> = Vec::new();
+        try!(d.read_seq_elts(|d, _| {
+            v.push(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(v.move_iter().collect::<~[T]>())
     }
 }
 
@@ -477,13 +613,12 @@ impl<E, S:Encoder<E>,T:Encodable<S, E>> Encodable<S, E> for Vec<T> {
 
 impl<E, D:Decoder<E>,T:Decodable<D, E>> Decodable<D, E> for Vec<T> {
     fn decode(d: &mut D) -> Result<Vec<T>, E> {
-        d.read_seq(|d, len| {
-            let mut v = Vec::with_capacity(len);
-            for i in range(0, len) {
-                v.push(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
-            }
-            Ok(v)
-        })
+        let mut v = Vec::new();
+        try!(d.read_seq_elts(|d, _| {
+            v.push(try!(Decodable::decode(d)));
+            Ok(())
+        }));
+        Ok(v)
     }
 }
 
@@ -605,12 +740,11 @@ pub trait DecoderHelpers<E> {
 
 impl<E, D:Decoder<E>> DecoderHelpers<E> for D {
     fn read_to_vec<T>(&mut self, f: |&mut D| -> Result<T, E>) -> Result<~[T], E> {
-        self.read_seq(|this, len| {
-            let mut v = Vec::with_capacity(len);
-            for i in range(0, len) {
-                v.push(try!(this.read_seq_elt(i, |this| f(this))));
-            }
-            Ok(v.move_iter().collect())
-        })
+        let mut v = Vec::new();
+        try!(self.read_seq_elts(|this, _| {
+            v.push(try!(f(this)));
+            Ok(())
+        }));
+        Ok(v.move_iter().collect())
     }
 }
@@ -0,0 +1,589 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Broadcast channels
+//!
+//! A broadcast channel delivers every message to every subscriber. Messages
+//! are kept in a ring buffer of a fixed capacity, and each subscriber keeps
+//! track of its own position in it, receiving a clone of each message.
+//!
+//! Subscribers which fall behind by more than the capacity of the channel are
+//! handled according to the channel's `LagPolicy`: either the sender blocks
+//! until the slowest subscriber catches up, or the slow subscriber misses the
+//! oldest messages.
+//!
+//! # Example
+//!
+//! ```
+//! use std::comm::{broadcast_channel, DropOldest};
+//!
+//! let (tx, rx) = broadcast_channel(16, DropOldest);
+//! let rx2 = tx.subscribe();
+//! tx.send("hello");
+//! assert_eq!(rx.recv(), "hello");
+//! assert_eq!(rx2.recv(), "hello");
+//! ```
+
+use cell::Cell;
+use clone::Clone;
+use container::Container;
+use iter::Iterator;
+use kinds::{Send, marker};
+use mem;
+use ops::Drop;
+use option::{Some, None, Option};
+use result::{Result, Ok, Err};
+use rt::task::BlockedTask;
+use sync::arc::UnsafeArc;
+use ty::Unsafe;
+use unstable::mutex::{NativeMutex, LockGuard};
+use vec::Vec;
+
+use super::{TryRecvError, Empty, Disconnected};
+use super::select;
+use super::waitqueue;
+use super::waitqueue::{WaitQueue, Selecting, NotSelecting};
+
+/// What a broadcast channel does when a message is sent while the buffer is
+/// full of messages that some subscriber has yet to receive.
+#[deriving(Eq, Clone, Show)]
+pub enum LagPolicy {
+    /// The sender blocks until the slowest subscriber has received the oldest
+    /// message. No subscriber ever misses a message.
+    Block,
+    /// The oldest message is overwritten and the sender carries on. Lagging
+    /// subscribers skip ahead to the oldest message which is still buffered;
+    /// the number of messages they missed is available from
+    /// `Subscriber::missed`.
+    DropOldest,
+}
+
+/// The sending half of a broadcast channel. This half can be cloned to send
+/// from many tasks, and is used to create new subscribers.
+pub struct Broadcaster<T> {
+    inner: UnsafeArc<Packet<T>>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// A receiving half of a broadcast channel. Each subscriber receives every
+/// message sent after it subscribed. Cloning a subscriber creates another
+/// subscriber at the same position in the channel.
+pub struct Subscriber<T> {
+    inner: UnsafeArc<Packet<T>>,
+    id: uint,
+    selecting: Cell<Selecting>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// An iterator over messages on a `Subscriber`. This iterator will block
+/// whenever `next` is called, and `None` is returned once all broadcasters
+/// have hung up and every message has been received.
+pub struct Subscription<'a, T> {
+    rx: &'a Subscriber<T>
+}
+
+/// Creates a new broadcast channel buffering up to `cap` messages, returning
+/// the sending half and an initial subscriber.
+///
+/// # Failure
+///
+/// This function will fail if `cap` is 0.
+pub fn broadcast_channel<T: Send + Clone>(cap: uint, policy: LagPolicy)
+                                          -> (Broadcaster<T>, Subscriber<T>) {
+    assert!(cap > 0, "broadcast channels need a capacity of at least 1");
+    let (a, b) = UnsafeArc::new2(Packet::new(cap, policy));
+    let id = unsafe { (*b.get()).subscribe(None) };
+    (Broadcaster { inner: a, marker: marker::NoShare },
+     Subscriber::new(b, id))
+}
+
+struct Packet<T> {
+    lock: NativeMutex,
+    state: Unsafe<State<T>>,
+}
+
+struct State<T> {
+    buf: Vec<Option<T>>,        // ring buffer, message `n` is at `n % cap`
+    head: uint,                 // oldest message still in the buffer
+    tail: uint,                 // number of the next message to be sent
+    policy: LagPolicy,
+    senders: uint,              // number of live broadcasters
+
+    // (id, position, missed) for each live subscriber. The position is the
+    // number of the next message the subscriber will receive.
+    subscribers: Vec<(uint, uint, uint)>,
+    next_id: uint,
+
+    receivers: WaitQueue,       // subscribers waiting for a message
+    senders_blocked: WaitQueue, // broadcasters waiting for room (`Block`)
+}
+
+impl<T: Send + Clone> Packet<T> {
+    fn new(cap: uint, policy: LagPolicy) -> Packet<T> {
+        Packet {
+            lock: unsafe { NativeMutex::new() },
+            state: Unsafe::new(State {
+                buf: Vec::from_fn(cap, |_| None),
+                head: 0,
+                tail: 0,
+                policy: policy,
+                senders: 1,
+                subscribers: Vec::new(),
+                next_id: 0,
+                receivers: WaitQueue::new(),
+                senders_blocked: WaitQueue::new(),
+            }),
+        }
+    }
+
+    // See sync::Packet::lock
+    fn lock<'a>(&'a self) -> (LockGuard<'a>, &'a mut State<T>) {
+        unsafe {
+            let guard = self.lock.lock();
+            (guard, &mut *self.state.get())
+        }
+    }
+
+    /// Registers a new subscriber, starting at the position of the subscriber
+    /// `from`, or at the next message to be sent.
+    fn subscribe(&self, from: Option<uint>) -> uint {
+        let (_g, state) = self.lock();
+        let pos = match from {
+            Some(id) => state.position(id),
+            None => state.tail,
+        };
+        let id = state.next_id;
+        state.next_id += 1;
+        state.subscribers.push((id, pos, 0));
+        id
+    }
+
+    fn unsubscribe(&self, id: uint) {
+        let (guard, state) = self.lock();
+        let idx = state.index(id);
+        state.subscribers.remove(idx);
+
+        // This may have been the subscriber holding back the senders
+        let tasks = state.senders_blocked.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+    }
+
+    fn send(&self, t: T) -> Result<(), T> {
+        let (guard, state) = self.lock();
+        if state.subscribers.len() == 0 { return Err(t) }
+
+        if state.tail - state.head == state.buf.len() {
+            match state.policy {
+                DropOldest => { state.head += 1; }
+                Block => {
+                    while state.subscribers.len() > 0 &&
+                          state.tail - state.min_position() ==
+                            state.buf.len() {
+                        waitqueue::wait(&mut state.senders_blocked,
+                                        &self.lock);
+                    }
+                    if state.subscribers.len() == 0 { return Err(t) }
+                    state.head = state.min_position();
+                }
+            }
+        }
+
+        let cap = state.buf.len();
+        let tail = state.tail;
+        // The message being overwritten is dropped outside of the lock
+        let _old = mem::replace(state.buf.get_mut(tail % cap), Some(t));
+        state.tail += 1;
+
+        let tasks = state.receivers.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+        Ok(())
+    }
+
+    fn recv(&self, id: uint) -> Result<T, ()> {
+        let (guard, state) = self.lock();
+        loop {
+            match state.take(id) {
+                Some(t) => {
+                    let tasks = state.senders_blocked.wake_all();
+                    mem::drop((state, guard));
+                    waitqueue::reawaken(tasks.move_iter());
+                    return Ok(t)
+                }
+                None if state.senders == 0 => return Err(()),
+                None => waitqueue::wait(&mut state.receivers, &self.lock),
+            }
+        }
+    }
+
+    fn try_recv(&self, id: uint) -> Result<T, TryRecvError> {
+        let (guard, state) = self.lock();
+        match state.take(id) {
+            Some(t) => {
+                let tasks = state.senders_blocked.wake_all();
+                mem::drop((state, guard));
+                waitqueue::reawaken(tasks.move_iter());
+                Ok(t)
+            }
+            None if state.senders == 0 => Err(Disconnected),
+            None => Err(Empty),
+        }
+    }
+
+    fn missed(&self, id: uint) -> uint {
+        let (_g, state) = self.lock();
+        let idx = state.index(id);
+        let &(id, pos, missed) = state.subscribers.get(idx);
+        *state.subscribers.get_mut(idx) = (id, pos, 0);
+        missed
+    }
+
+    fn clone_chan(&self) {
+        let (_g, state) = self.lock();
+        state.senders += 1;
+    }
+
+    fn drop_chan(&self) {
+        let (guard, state) = self.lock();
+        state.senders -= 1;
+        if state.senders > 0 { return }
+
+        let tasks = state.receivers.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+    }
+
+    fn ready(&self, id: uint) -> bool {
+        let (_g, state) = self.lock();
+        state.ready(id)
+    }
+
+    fn start_selection(&self, id: uint, task: BlockedTask,
+                       sel: &Cell<Selecting>) -> Result<(), BlockedTask> {
+        let (_g, state) = self.lock();
+        let ready = state.ready(id);
+        waitqueue::start_selection(&mut state.receivers, sel, ready, task)
+    }
+
+    fn abort_selection(&self, id: uint, sel: &Cell<Selecting>) -> bool {
+        let (guard, state) = self.lock();
+        let ready = state.ready(id);
+        let (ready, task) = waitqueue::abort_selection(&mut state.receivers, sel,
+                                                       ready);
+        mem::drop((state, guard));
+        task.map(|t| t.reawaken());
+        ready
+    }
+}
+
+impl<T: Send + Clone> State<T> {
+    fn index(&self, id: uint) -> uint {
+        self.subscribers.iter().position(|&(i, _, _)| i == id).unwrap()
+    }
+
+    fn position(&self, id: uint) -> uint {
+        let &(_, pos, _) = self.subscribers.get(self.index(id));
+        pos
+    }
+
+    fn min_position(&self) -> uint {
+        self.subscribers.iter().map(|&(_, pos, _)| pos).min().unwrap()
+    }
+
+    fn ready(&self, id: uint) -> bool {
+        self.senders == 0 || self.position(id) < self.tail
+    }
+
+    /// Takes the next message for subscriber `id`, skipping over any messages
+    /// which have been overwritten since it last received.
+    fn take(&mut self, id: uint) -> Option<T> {
+        let idx = self.index(id);
+        let (_, mut pos, mut missed) = *self.subscribers.get(idx);
+        if pos < self.head {
+            missed += self.head - pos;
+            pos = self.head;
+        }
+        let ret = if pos < self.tail {
+            let msg = self.buf.get(pos % self.buf.len()).clone();
+            pos += 1;
+            msg
+        } else {
+            None
+        };
+        *self.subscribers.get_mut(idx) = (id, pos, missed);
+        ret
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for Packet<T> {
+    fn drop(&mut self) {
+        let state = unsafe { &*self.state.get() };
+        assert_eq!(state.senders, 0);
+        assert_eq!(state.subscribers.len(), 0);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Broadcaster
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send + Clone> Broadcaster<T> {
+    /// Sends a value to every current subscriber of this channel.
+    ///
+    /// With the `Block` lag policy this will block while the buffer is full
+    /// of messages which some subscriber has not received yet.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if there are no subscribers left, see
+    /// `Sender::send`.
+    pub fn send(&self, t: T) {
+        if self.send_opt(t).is_err() {
+            fail!("sending on a closed channel");
+        }
+    }
+
+    /// Sends a value to every current subscriber, returning it back if there
+    /// are no subscribers to receive it.
+    ///
+    /// # Failure
+    ///
+    /// This function cannot fail.
+    pub fn send_opt(&self, t: T) -> Result<(), T> {
+        unsafe { (*self.inner.get()).send(t) }
+    }
+
+    /// Creates a new subscriber to this channel, which will receive every
+    /// message sent from now on.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        let id = unsafe { (*self.inner.get()).subscribe(None) };
+        Subscriber::new(self.inner.clone(), id)
+    }
+}
+
+impl<T: Send + Clone> Clone for Broadcaster<T> {
+    fn clone(&self) -> Broadcaster<T> {
+        unsafe { (*self.inner.get()).clone_chan(); }
+        Broadcaster { inner: self.inner.clone(), marker: marker::NoShare }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send + Clone> Drop for Broadcaster<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).drop_chan(); }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Subscriber
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send + Clone> Subscriber<T> {
+    fn new(inner: UnsafeArc<Packet<T>>, id: uint) -> Subscriber<T> {
+        Subscriber {
+            inner: inner,
+            id: id,
+            selecting: Cell::new(NotSelecting),
+            marker: marker::NoShare,
+        }
+    }
+
+    /// Blocks waiting for the next message on this subscriber.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if there are no more messages for this
+    /// subscriber and every broadcaster has hung up, see `Receiver::recv`.
+    pub fn recv(&self) -> T {
+        match self.recv_opt() {
+            Ok(t) => t,
+            Err(()) => fail!("receiving on a closed channel"),
+        }
+    }
+
+    /// Attempts to return the next message for this subscriber without
+    /// blocking.
+    ///
+    /// This function cannot fail.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        unsafe { (*self.inner.get()).try_recv(self.id) }
+    }
+
+    /// Blocks waiting for the next message on this subscriber, returning `Err`
+    /// once every broadcaster has hung up and there are no more messages.
+    ///
+    /// This function cannot fail.
+    pub fn recv_opt(&self) -> Result<T, ()> {
+        unsafe { (*self.inner.get()).recv(self.id) }
+    }
+
+    /// Returns the number of messages this subscriber has skipped because it
+    /// fell too far behind, since the last call to this method. This is always
+    /// 0 for channels with the `Block` lag policy.
+    pub fn missed(&self) -> uint {
+        unsafe { (*self.inner.get()).missed(self.id) }
+    }
+
+    /// Returns an iterator which will block waiting for messages, but never
+    /// `fail!`. It will return `None` when the channel has hung up.
+    pub fn iter<'a>(&'a self) -> Subscription<'a, T> {
+        Subscription { rx: self }
+    }
+}
+
+impl<T: Send + Clone> Clone for Subscriber<T> {
+    fn clone(&self) -> Subscriber<T> {
+        let id = unsafe { (*self.inner.get()).subscribe(Some(self.id)) };
+        Subscriber::new(self.inner.clone(), id)
+    }
+}
+
+impl<T: Send + Clone> select::Packet for Subscriber<T> {
+    fn can_recv(&self) -> bool {
+        unsafe { (*self.inner.get()).ready(self.id) }
+    }
+
+    fn start_selection(&self, task: BlockedTask) -> Result<(), BlockedTask> {
+        unsafe {
+            (*self.inner.get()).start_selection(self.id, task,
+                                                &self.selecting)
+        }
+    }
+
+    fn abort_selection(&self) -> bool {
+        unsafe {
+            (*self.inner.get()).abort_selection(self.id, &self.selecting)
+        }
+    }
+}
+
+impl<T: Send + Clone> select::Receive<T> for Subscriber<T> {
+    fn recv_opt(&self) -> Result<T, ()> { self.recv_opt() }
+}
+
+impl<'a, T: Send + Clone> Iterator<T> for Subscription<'a, T> {
+    fn next(&mut self) -> Option<T> { self.rx.recv_opt().ok() }
+}
+
+#[unsafe_destructor]
+impl<T: Send + Clone> Drop for Subscriber<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).unsubscribe(self.id); }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prelude::*;
+
+    test!(fn smoke() {
+        let (tx, rx) = broadcast_channel(4, Block);
+        tx.send(1);
+        assert_eq!(rx.recv(), 1);
+    })
+
+    test!(fn every_subscriber_sees_every_message() {
+        let (tx, rx1) = broadcast_channel(4, Block);
+        let rx2 = tx.subscribe();
+        for i in range(0, 3) { tx.send(i); }
+        for i in range(0, 3) { assert_eq!(rx1.recv(), i); }
+        for i in range(0, 3) { assert_eq!(rx2.recv(), i); }
+        assert_eq!(rx1.try_recv(), Err(Empty));
+    })
+
+    test!(fn late_subscribers_see_later_messages() {
+        let (tx, rx1) = broadcast_channel(4, Block);
+        tx.send(1);
+        let rx2 = tx.subscribe();
+        tx.send(2);
+        assert_eq!(rx1.recv(), 1);
+        assert_eq!(rx1.recv(), 2);
+        assert_eq!(rx2.recv(), 2);
+    })
+
+    test!(fn clone_keeps_position() {
+        let (tx, rx1) = broadcast_channel(4, Block);
+        tx.send(1);
+        tx.send(2);
+        assert_eq!(rx1.recv(), 1);
+        let rx2 = rx1.clone();
+        assert_eq!(rx1.recv(), 2);
+        assert_eq!(rx2.recv(), 2);
+    })
+
+    test!(fn drop_oldest_reports_missed() {
+        let (tx, rx) = broadcast_channel(2, DropOldest);
+        for i in range(0, 5) { tx.send(i); }
+        assert_eq!(rx.recv(), 3);
+        assert_eq!(rx.missed(), 3);
+        assert_eq!(rx.missed(), 0);
+        assert_eq!(rx.recv(), 4);
+    })
+
+    test!(fn block_waits_for_slowest() {
+        let (tx, rx) = broadcast_channel(1, Block);
+        let (done_tx, done_rx) = channel();
+        tx.send(0);
+        spawn(proc() {
+            tx.send(1);
+            done_tx.send(());
+        });
+        assert!(done_rx.try_recv().is_err());
+        assert_eq!(rx.recv(), 0);
+        assert_eq!(rx.recv(), 1);
+        done_rx.recv();
+        assert_eq!(rx.missed(), 0);
+    })
+
+    test!(fn disconnect() {
+        let (tx, rx) = broadcast_channel(2, Block);
+        tx.send(1);
+        drop(tx);
+        assert_eq!(rx.recv_opt(), Ok(1));
+        assert_eq!(rx.recv_opt(), Err(()));
+        assert_eq!(rx.try_recv(), Err(Disconnected));
+    })
+
+    test!(fn no_subscribers() {
+        let (tx, rx) = broadcast_channel(2, Block);
+        drop(rx);
+        assert_eq!(tx.send_opt(1), Err(1));
+    })
+
+    test!(fn blocked_sender_freed_by_unsubscribe() {
+        let (tx, rx) = broadcast_channel(1, Block);
+        let (done_tx, done_rx) = channel();
+        tx.send(0);
+        spawn(proc() {
+            assert_eq!(tx.send_opt(1), Err(1));
+            done_tx.send(());
+        });
+        drop(rx);
+        done_rx.recv();
+    })
+
+    test!(fn select_over_subscribers() {
+        let (tx, rx1) = broadcast_channel(2, Block);
+        let rx2 = tx.subscribe();
+        let (_tx3, rx3) = channel::<int>();
+        tx.send(5);
+        assert_eq!(rx1.recv(), 5);
+        select! (
+            _a = rx1.recv() => { fail!() },
+            b = rx2.recv() => { assert_eq!(b, 5); },
+            _c = rx3.recv() => { fail!() }
+        )
+    })
+}
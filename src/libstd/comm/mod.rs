@@ -28,7 +28,7 @@
 //! means that if one task is blocked on a channel, other tasks can continue to
 //! make progress.
 //!
-//! Rust channels come in one of two flavors:
+//! Rust channels come in one of two main flavors:
 //!
 //! 1. An asynchronous, infinitely buffered channel. The `channel()` function
 //!    will return a `(Sender, Receiver)` tuple where all sends will be
@@ -43,6 +43,20 @@
 //!    "rendezvous" channel where each sender atomically hands off a message to
 //!    a receiver.
 //!
+//! A few more specialized shapes of channels are also available, all of which
+//! can be used with `Select` alongside the flavors above:
+//!
+//! * `mpmc_channel()` is an asynchronous channel whose receiving half can be
+//!   cloned as well, handing each message to exactly one receiver. This is
+//!   useful for pools of worker tasks.
+//!
+//! * `broadcast_channel()` delivers a clone of every message to every
+//!   subscriber, with a `LagPolicy` determining what happens to subscribers
+//!   which fall behind.
+//!
+//! * `watch_channel()` only holds the latest value sent, and receivers are
+//!   notified whenever it changes.
+//!
 //! ## Failure Propagation
 //!
 //! In addition to being a core primitive for communicating in rust, channels
@@ -287,6 +301,10 @@ use sync::arc::UnsafeArc;
 use ty::Unsafe;
 
 pub use comm::select::{Select, Handle};
+pub use comm::mpmc::{mpmc_channel, MpmcSender, MpmcReceiver, MpmcMessages};
+pub use comm::broadcast::{broadcast_channel, Broadcaster, Subscriber};
+pub use comm::broadcast::{Subscription, LagPolicy, Block, DropOldest};
+pub use comm::watch::{watch_channel, WatchSender, WatchReceiver, Changes};
 
 macro_rules! test (
     { fn $name:ident() $b:block $(#[$a:meta])*} => (
@@ -319,6 +337,10 @@ mod oneshot;
 mod stream;
 mod shared;
 mod sync;
+mod waitqueue;
+mod mpmc;
+mod broadcast;
+mod watch;
 
 // Use a power of 2 to allow LLVM to optimize to something that's not a
 // division, this is hit pretty regularly.
@@ -959,6 +981,10 @@ impl<T: Send> select::Packet for Receiver<T> {
     }
 }
 
+impl<T: Send> select::Receive<T> for Receiver<T> {
+    fn recv_opt(&self) -> Result<T, ()> { self.recv_opt() }
+}
+
 impl<'a, T: Send> Iterator<T> for Messages<'a, T> {
     fn next(&mut self) -> Option<T> { self.rx.recv_opt().ok() }
 }
@@ -0,0 +1,479 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Multi-producer, multi-consumer channels
+//!
+//! This flavor of channel is like the one created by `channel()`, except that
+//! the receiving half can be cloned as well. Each message is delivered to
+//! exactly one of the receivers, whichever asks for it first, which makes
+//! these channels a natural fit for distributing work among a pool of tasks.
+//!
+//! The implementation follows the synchronous channels: the queue of messages
+//! and the queue of blocked receivers are protected by a native mutex, and no
+//! user code is run while the lock is held.
+//!
+//! # Example
+//!
+//! ```
+//! use std::comm::mpmc_channel;
+//!
+//! let (tx, rx) = mpmc_channel();
+//! for _ in range(0, 4) {
+//!     let rx = rx.clone();
+//!     spawn(proc() {
+//!         for job in rx.iter() {
+//!             println!("working on {}", job);
+//!         }
+//!     });
+//! }
+//! for job in range(0, 100) {
+//!     tx.send(job);
+//! }
+//! ```
+
+use cell::Cell;
+use clone::Clone;
+use container::Container;
+use iter::Iterator;
+use kinds::{Send, marker};
+use mem;
+use ops::Drop;
+use option::{Some, None, Option};
+use result::{Result, Ok, Err};
+use rt::task::BlockedTask;
+use sync::arc::UnsafeArc;
+use ty::Unsafe;
+use unstable::mutex::{NativeMutex, LockGuard};
+use vec::Vec;
+
+use super::{TryRecvError, Empty, Disconnected};
+use super::select;
+use super::waitqueue;
+use super::waitqueue::{WaitQueue, Selecting, NotSelecting};
+
+/// The sending half of a multi-consumer channel. This half can be cloned to
+/// send from many tasks.
+pub struct MpmcSender<T> {
+    inner: UnsafeArc<Packet<T>>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// The receiving half of a multi-consumer channel. Unlike `Receiver`, this
+/// half can be cloned, and every message sent is received by exactly one of
+/// the clones.
+pub struct MpmcReceiver<T> {
+    inner: UnsafeArc<Packet<T>>,
+    selecting: Cell<Selecting>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// An iterator over messages on a `MpmcReceiver`. This iterator will block
+/// whenever `next` is called, and `None` is returned once all senders have
+/// hung up and the channel is empty.
+pub struct MpmcMessages<'a, T> {
+    rx: &'a MpmcReceiver<T>
+}
+
+/// Creates a new asynchronous channel whose receiving half can be cloned.
+///
+/// Sends never block, and each message is handed to exactly one receiver.
+pub fn mpmc_channel<T: Send>() -> (MpmcSender<T>, MpmcReceiver<T>) {
+    let (a, b) = UnsafeArc::new2(Packet::new());
+    (MpmcSender { inner: a, marker: marker::NoShare },
+     MpmcReceiver::new(b))
+}
+
+struct Packet<T> {
+    lock: NativeMutex,
+    state: Unsafe<State<T>>,
+}
+
+struct State<T> {
+    buf: Queue<T>,         // messages which haven't been received yet
+    senders: uint,         // number of live senders
+    receivers: uint,       // number of live receivers
+    waiters: WaitQueue,    // receivers blocked waiting for a message
+}
+
+/// An unbounded FIFO queue. Messages are pushed onto `back`, and popped off of
+/// `front`, which holds the oldest messages in reverse order.
+struct Queue<T> {
+    front: Vec<T>,
+    back: Vec<T>,
+}
+
+impl<T: Send> Packet<T> {
+    fn new() -> Packet<T> {
+        Packet {
+            lock: unsafe { NativeMutex::new() },
+            state: Unsafe::new(State {
+                buf: Queue { front: Vec::new(), back: Vec::new() },
+                senders: 1,
+                receivers: 1,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    // See sync::Packet::lock
+    fn lock<'a>(&'a self) -> (LockGuard<'a>, &'a mut State<T>) {
+        unsafe {
+            let guard = self.lock.lock();
+            (guard, &mut *self.state.get())
+        }
+    }
+
+    fn send(&self, t: T) -> Result<(), T> {
+        let (guard, state) = self.lock();
+        if state.receivers == 0 { return Err(t) }
+        state.buf.push(t);
+        let task = state.waiters.wake_one();
+        mem::drop((state, guard));
+        task.map(|t| t.reawaken());
+        Ok(())
+    }
+
+    fn recv(&self) -> Result<T, ()> {
+        let (_g, state) = self.lock();
+        loop {
+            match state.buf.pop() {
+                Some(t) => return Ok(t),
+                None if state.senders == 0 => return Err(()),
+                None => waitqueue::wait(&mut state.waiters, &self.lock),
+            }
+        }
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        let (_g, state) = self.lock();
+        match state.buf.pop() {
+            Some(t) => Ok(t),
+            None if state.senders == 0 => Err(Disconnected),
+            None => Err(Empty),
+        }
+    }
+
+    fn clone_chan(&self) {
+        let (_g, state) = self.lock();
+        state.senders += 1;
+    }
+
+    fn drop_chan(&self) {
+        let (guard, state) = self.lock();
+        state.senders -= 1;
+        if state.senders > 0 { return }
+
+        // Every blocked receiver needs to learn about the disconnection
+        let tasks = state.waiters.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+    }
+
+    fn clone_port(&self) {
+        let (_g, state) = self.lock();
+        state.receivers += 1;
+    }
+
+    fn drop_port(&self) {
+        let (guard, state) = self.lock();
+        state.receivers -= 1;
+        if state.receivers > 0 { return }
+
+        // Nobody can receive the pending messages anymore, so destroy them,
+        // outside of the lock in case their destructors use channels.
+        let _data = mem::replace(&mut state.buf,
+                                 Queue { front: Vec::new(), back: Vec::new() });
+        mem::drop((state, guard));
+    }
+
+    fn can_recv(&self) -> bool {
+        let (_g, state) = self.lock();
+        state.senders == 0 || !state.buf.is_empty()
+    }
+
+    fn start_selection(&self, task: BlockedTask, sel: &Cell<Selecting>)
+                       -> Result<(), BlockedTask> {
+        let (_g, state) = self.lock();
+        let ready = state.senders == 0 || !state.buf.is_empty();
+        waitqueue::start_selection(&mut state.waiters, sel, ready, task)
+    }
+
+    fn abort_selection(&self, sel: &Cell<Selecting>) -> bool {
+        let (guard, state) = self.lock();
+        let ready = state.senders == 0 || !state.buf.is_empty();
+        let (ready, task) = waitqueue::abort_selection(&mut state.waiters, sel,
+                                                       ready);
+        mem::drop((state, guard));
+        task.map(|t| t.reawaken());
+        ready
+    }
+}
+
+impl<T> Queue<T> {
+    fn push(&mut self, t: T) {
+        self.back.push(t);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.front.len() == 0 {
+            while self.back.len() > 0 {
+                self.front.push(self.back.pop().unwrap());
+            }
+        }
+        self.front.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.front.len() == 0 && self.back.len() == 0
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// MpmcSender
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send> MpmcSender<T> {
+    /// Sends a value along this channel to be received by one of the
+    /// receivers.
+    ///
+    /// This method will never block the current task.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if every receiver has hung up, see
+    /// `Sender::send`.
+    pub fn send(&self, t: T) {
+        if self.send_opt(t).is_err() {
+            fail!("sending on a closed channel");
+        }
+    }
+
+    /// Attempts to send a value on this channel, returning it back if it could
+    /// not be sent because every receiver has hung up.
+    ///
+    /// # Failure
+    ///
+    /// This function cannot fail.
+    pub fn send_opt(&self, t: T) -> Result<(), T> {
+        unsafe { (*self.inner.get()).send(t) }
+    }
+}
+
+impl<T: Send> Clone for MpmcSender<T> {
+    fn clone(&self) -> MpmcSender<T> {
+        unsafe { (*self.inner.get()).clone_chan(); }
+        MpmcSender { inner: self.inner.clone(), marker: marker::NoShare }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for MpmcSender<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).drop_chan(); }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// MpmcReceiver
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send> MpmcReceiver<T> {
+    fn new(inner: UnsafeArc<Packet<T>>) -> MpmcReceiver<T> {
+        MpmcReceiver {
+            inner: inner,
+            selecting: Cell::new(NotSelecting),
+            marker: marker::NoShare,
+        }
+    }
+
+    /// Blocks waiting for a value on this receiver.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if the channel is empty and every sender has
+    /// hung up, see `Receiver::recv`.
+    pub fn recv(&self) -> T {
+        match self.recv_opt() {
+            Ok(t) => t,
+            Err(()) => fail!("receiving on a closed channel"),
+        }
+    }
+
+    /// Attempts to return a pending value on this receiver without blocking.
+    ///
+    /// This function cannot fail.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        unsafe { (*self.inner.get()).try_recv() }
+    }
+
+    /// Blocks waiting for a value on this receiver, returning `Err` once the
+    /// channel is empty and every sender has hung up.
+    ///
+    /// This function cannot fail.
+    pub fn recv_opt(&self) -> Result<T, ()> {
+        unsafe { (*self.inner.get()).recv() }
+    }
+
+    /// Returns an iterator which will block waiting for messages, but never
+    /// `fail!`. It will return `None` when the channel has hung up.
+    pub fn iter<'a>(&'a self) -> MpmcMessages<'a, T> {
+        MpmcMessages { rx: self }
+    }
+}
+
+impl<T: Send> Clone for MpmcReceiver<T> {
+    fn clone(&self) -> MpmcReceiver<T> {
+        unsafe { (*self.inner.get()).clone_port(); }
+        MpmcReceiver::new(self.inner.clone())
+    }
+}
+
+impl<T: Send> select::Packet for MpmcReceiver<T> {
+    fn can_recv(&self) -> bool {
+        unsafe { (*self.inner.get()).can_recv() }
+    }
+
+    fn start_selection(&self, task: BlockedTask) -> Result<(), BlockedTask> {
+        unsafe { (*self.inner.get()).start_selection(task, &self.selecting) }
+    }
+
+    fn abort_selection(&self) -> bool {
+        unsafe { (*self.inner.get()).abort_selection(&self.selecting) }
+    }
+}
+
+impl<T: Send> select::Receive<T> for MpmcReceiver<T> {
+    fn recv_opt(&self) -> Result<T, ()> { self.recv_opt() }
+}
+
+impl<'a, T: Send> Iterator<T> for MpmcMessages<'a, T> {
+    fn next(&mut self) -> Option<T> { self.rx.recv_opt().ok() }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for MpmcReceiver<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).drop_port(); }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prelude::*;
+
+    test!(fn smoke() {
+        let (tx, rx) = mpmc_channel();
+        tx.send(1);
+        assert_eq!(rx.recv(), 1);
+    })
+
+    test!(fn fifo() {
+        let (tx, rx) = mpmc_channel();
+        for i in range(0, 10) { tx.send(i); }
+        let rx2 = rx.clone();
+        for i in range(0, 10) {
+            let r = if i % 2 == 0 { &rx } else { &rx2 };
+            assert_eq!(r.recv(), i);
+        }
+    })
+
+    test!(fn each_message_received_once() {
+        let (tx, rx) = mpmc_channel();
+        let (done_tx, done_rx) = channel();
+        for _ in range(0, 4) {
+            let rx = rx.clone();
+            let done_tx = done_tx.clone();
+            spawn(proc() {
+                let mut sum = 0;
+                for i in rx.iter() { sum += i; }
+                done_tx.send(sum);
+            });
+        }
+        drop(rx);
+        for i in range(0, 1000) { tx.send(i); }
+        drop(tx);
+        let mut total = 0;
+        for _ in range(0, 4) { total += done_rx.recv(); }
+        assert_eq!(total, 999 * 1000 / 2);
+    })
+
+    test!(fn disconnect_after_drain() {
+        let (tx, rx) = mpmc_channel();
+        tx.send(1);
+        drop(tx);
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(Disconnected));
+        assert_eq!(rx.recv_opt(), Err(()));
+    })
+
+    test!(fn blocked_receivers_see_disconnect() {
+        let (tx, rx) = mpmc_channel::<int>();
+        let (done_tx, done_rx) = channel();
+        for _ in range(0, 3) {
+            let rx = rx.clone();
+            let done_tx = done_tx.clone();
+            spawn(proc() {
+                assert!(rx.recv_opt().is_err());
+                done_tx.send(());
+            });
+        }
+        drop(tx);
+        for _ in range(0, 3) { done_rx.recv(); }
+    })
+
+    test!(fn send_to_dropped_receivers() {
+        let (tx, rx) = mpmc_channel();
+        let rx2 = rx.clone();
+        drop(rx);
+        assert!(tx.send_opt(1).is_ok());
+        drop(rx2);
+        assert_eq!(tx.send_opt(2), Err(2));
+    })
+
+    test!(fn select_with_receiver() {
+        let (tx1, rx1) = mpmc_channel::<int>();
+        let (tx2, rx2) = channel::<int>();
+        tx1.send(1);
+        select! (
+            a = rx1.recv() => { assert_eq!(a, 1); },
+            _b = rx2.recv() => { fail!() }
+        )
+        spawn(proc() { tx2.send(2); });
+        select! (
+            _a = rx1.recv() => { fail!() },
+            b = rx2.recv() => { assert_eq!(b, 2); }
+        )
+        drop(tx1);
+    })
+
+    test!(fn select_over_two_receivers_with_one_send() {
+        let (tx1, rx1) = mpmc_channel::<int>();
+        let (tx2, rx2) = mpmc_channel::<int>();
+        let rx3 = rx1.clone();
+        let (done_tx, done_rx) = channel();
+
+        // Competes with the select below for the only message, so that the
+        // loser must block again rather than report `rx1` as ready.
+        spawn(proc() { done_tx.send(rx3.recv_opt()); });
+        spawn(proc() {
+            for _ in range(0, 10) { task::deschedule(); }
+            tx1.send(1);
+        });
+        let a = select! (
+            a = rx1.recv_opt() => { a },
+            _b = rx2.recv_opt() => { fail!() }
+        );
+        let b = done_rx.recv();
+        assert!((a, b) == (Ok(1), Err(())) || (a, b) == (Err(()), Ok(1)));
+        drop(tx2);
+    })
+}
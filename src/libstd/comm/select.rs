@@ -57,7 +57,6 @@ use ptr::RawPtr;
 use result::{Ok, Err, Result};
 use rt::local::Local;
 use rt::task::{Task, BlockedTask};
-use super::Receiver;
 use uint;
 
 /// The "receiver set" of the select interface. This structure is used to manage
@@ -84,7 +83,7 @@ pub struct Handle<'rx, T> {
 
     // due to our fun transmutes, we be sure to place this at the end. (nothing
     // previous relies on T)
-    rx: &'rx Receiver<T>,
+    rx: &'rx Receive<T>,
 }
 
 struct Packets { cur: *mut Handle<'static, ()> }
@@ -96,6 +95,14 @@ pub trait Packet {
     fn abort_selection(&self) -> bool;
 }
 
+/// The receiving halves of channels which can be selected over. Every kind of
+/// receiver in this module implements this, so a single `Select` can wait on
+/// any mix of them.
+#[doc(hidden)]
+pub trait Receive<T>: Packet {
+    fn recv_opt(&self) -> Result<T, ()>;
+}
+
 impl Select {
     /// Creates a new selection structure. This set is initially empty and
     /// `wait` will fail!() if called.
@@ -114,7 +121,8 @@ impl Select {
     /// Creates a new handle into this receiver set for a new receiver. Note
     /// that this does *not* add the receiver to the receiver set, for that you
     /// must call the `add` method on the handle itself.
-    pub fn handle<'a, T: Send>(&'a self, rx: &'a Receiver<T>) -> Handle<'a, T> {
+    pub fn handle<'a, T: Send, R: Receive<T>>(&'a self,
+                                              rx: &'a R) -> Handle<'a, T> {
         let id = self.next_id.get();
         self.next_id.set(id + 1);
         Handle {
@@ -123,8 +131,8 @@ impl Select {
             next: 0 as *mut Handle<'static, ()>,
             prev: 0 as *mut Handle<'static, ()>,
             added: false,
-            rx: rx,
-            packet: rx,
+            rx: rx as &'a Receive<T>,
+            packet: rx as &'a Packet,
         }
     }
 
@@ -170,56 +178,67 @@ impl Select {
             }
             assert!(amt > 0);
 
-            let mut ready_index = amt;
-            let mut ready_id = uint::MAX;
-            let mut iter = self.iter().enumerate();
-
-            // Acquire a number of blocking contexts, and block on each one
-            // sequentially until one fails. If one fails, then abort
-            // immediately so we can go unblock on all the other receivers.
-            let task: Box<Task> = Local::take();
-            task.deschedule(amt, |task| {
-                // Prepare for the block
-                let (i, handle) = iter.next().unwrap();
-                match (*handle).packet.start_selection(task) {
-                    Ok(()) => Ok(()),
-                    Err(task) => {
-                        ready_index = i;
+            // A receiver which looked ready while blocking may have had its
+            // data taken by another receiver of the same channel by the time
+            // we get around to returning (mpmc channels), so a handle is only
+            // reported once it has been re-checked, blocking again otherwise.
+            loop {
+                let mut ready_index = amt;
+                let mut ready_id = uint::MAX;
+                let mut iter = self.iter().enumerate();
+
+                // Acquire a number of blocking contexts, and block on each one
+                // sequentially until one fails. If one fails, then abort
+                // immediately so we can go unblock on all the other receivers.
+                let task: Box<Task> = Local::take();
+                task.deschedule(amt, |task| {
+                    // Prepare for the block
+                    let (i, handle) = iter.next().unwrap();
+                    match (*handle).packet.start_selection(task) {
+                        Ok(()) => Ok(()),
+                        Err(task) => {
+                            ready_index = i;
+                            ready_id = (*handle).id;
+                            Err(task)
+                        }
+                    }
+                });
+
+                // Abort the selection process on each receiver. If the abort
+                // process returns `true`, then that means that the receiver is
+                // ready to receive some data. Note that this also means that
+                // the receiver may have yet to have fully read the `to_wake`
+                // field and woken us up (although the wakeup is guaranteed to
+                // fail).
+                //
+                // This situation happens in the window of where a sender
+                // invokes increment(), sees -1, and then decides to wake up the
+                // task. After all this is done, the sending thread will set
+                // `selecting` to `false`. Until this is done, we cannot return.
+                // If we were to return, then a sender could wake up a receiver
+                // which has gone back to sleep after this call to `select`.
+                //
+                // Note that it is a "fairly small window" in which an
+                // increment() views that it should wake a thread up until the
+                // `selecting` bit is set to false. For now, the implementation
+                // currently just spins in a yield loop. This is very
+                // distasteful, but this implementation is already nowhere near
+                // what it should ideally be. A rewrite should focus on avoiding
+                // a yield loop, and for now this implementation is tying us
+                // over to a more efficient "don't iterate over everything every
+                // time" implementation.
+                for handle in self.iter().take(ready_index) {
+                    if (*handle).packet.abort_selection() {
                         ready_id = (*handle).id;
-                        Err(task)
                     }
                 }
-            });
-
-            // Abort the selection process on each receiver. If the abort
-            // process returns `true`, then that means that the receiver is
-            // ready to receive some data. Note that this also means that the
-            // receiver may have yet to have fully read the `to_wake` field and
-            // woken us up (although the wakeup is guaranteed to fail).
-            //
-            // This situation happens in the window of where a sender invokes
-            // increment(), sees -1, and then decides to wake up the task. After
-            // all this is done, the sending thread will set `selecting` to
-            // `false`. Until this is done, we cannot return. If we were to
-            // return, then a sender could wake up a receiver which has gone
-            // back to sleep after this call to `select`.
-            //
-            // Note that it is a "fairly small window" in which an increment()
-            // views that it should wake a thread up until the `selecting` bit
-            // is set to false. For now, the implementation currently just spins
-            // in a yield loop. This is very distasteful, but this
-            // implementation is already nowhere near what it should ideally be.
-            // A rewrite should focus on avoiding a yield loop, and for now this
-            // implementation is tying us over to a more efficient "don't
-            // iterate over everything every time" implementation.
-            for handle in self.iter().take(ready_index) {
-                if (*handle).packet.abort_selection() {
-                    ready_id = (*handle).id;
+
+                assert!(ready_id != uint::MAX);
+                if self.iter().any(|h| (*h).id == ready_id &&
+                                       (*h).packet.can_recv()) {
+                    return ready_id;
                 }
             }
-
-            assert!(ready_id != uint::MAX);
-            return ready_id;
         }
     }
 
@@ -233,7 +252,12 @@ impl<'rx, T: Send> Handle<'rx, T> {
 
     /// Receive a value on the underlying receiver. Has the same semantics as
     /// `Receiver.recv`
-    pub fn recv(&mut self) -> T { self.rx.recv() }
+    pub fn recv(&mut self) -> T {
+        match self.rx.recv_opt() {
+            Ok(t) => t,
+            Err(()) => fail!("receiving on a closed channel"),
+        }
+    }
     /// Block to receive a value on the underlying receiver, returning `Some` on
     /// success or `None` if the channel disconnects. This function has the same
     /// semantics as `Receiver.recv_opt`
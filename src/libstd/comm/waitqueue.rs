@@ -0,0 +1,157 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Queues of blocked tasks for the mutex-based channel flavors
+///
+/// The mpmc, broadcast and watch channels all protect their state with a
+/// native mutex, in the same way as the synchronous channels do. Unlike
+/// synchronous channels, however, any number of tasks may be blocked on the
+/// receiving half at once, and a selecting task must be able to find and
+/// remove its own entry again. This module provides the list of blocked tasks
+/// that these flavors share.
+///
+/// All functions here must be called with the channel's lock held.
+
+use cell::Cell;
+use iter::Iterator;
+use mem;
+use option::{Some, None, Option};
+use owned::Box;
+use result::{Result, Ok, Err};
+use rt::local::Local;
+use rt::task::{Task, BlockedTask};
+use unstable::mutex::NativeMutex;
+use vec::Vec;
+
+pub struct WaitQueue {
+    tasks: Vec<(uint, BlockedTask)>,
+    next_id: uint,
+}
+
+impl WaitQueue {
+    pub fn new() -> WaitQueue {
+        WaitQueue { tasks: Vec::new(), next_id: 0 }
+    }
+
+    /// Enqueues a blocked task, returning a token which can later be used to
+    /// remove it again.
+    pub fn push(&mut self, task: BlockedTask) -> uint {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push((id, task));
+        id
+    }
+
+    /// Removes the task enqueued under `id`, if it hasn't been dequeued yet.
+    pub fn remove(&mut self, id: uint) -> Option<BlockedTask> {
+        match self.tasks.iter().position(|&(i, _)| i == id) {
+            Some(idx) => self.tasks.remove(idx).map(|(_, task)| task),
+            None => None,
+        }
+    }
+
+    /// Wakes up the task which has been waiting the longest. Tasks which were
+    /// already woken by another channel (selecting tasks) are skipped over, so
+    /// that the wakeup is not lost.
+    ///
+    /// The returned task must be reawakened after the lock is released.
+    pub fn wake_one(&mut self) -> Option<Box<Task>> {
+        while self.tasks.len() > 0 {
+            let (_, task) = self.tasks.remove(0).unwrap();
+            match task.wake() {
+                Some(task) => return Some(task),
+                None => {}
+            }
+        }
+        None
+    }
+
+    /// Wakes up every waiting task. The returned tasks must be reawakened
+    /// after the lock is released.
+    pub fn wake_all(&mut self) -> Vec<Box<Task>> {
+        let tasks = mem::replace(&mut self.tasks, Vec::new());
+        tasks.move_iter().filter_map(|(_, task)| task.wake()).collect()
+    }
+}
+
+#[unsafe_destructor]
+impl Drop for WaitQueue {
+    fn drop(&mut self) {
+        assert!(self.tasks.len() == 0);
+    }
+}
+
+/// Atomically blocks the current task on `queue`, unlocking `lock` in the
+/// meantime. This re-locks the mutex upon returning.
+///
+/// Wakeups may be spurious, so callers re-check their condition in a loop.
+pub fn wait(queue: &mut WaitQueue, lock: &NativeMutex) {
+    let me: Box<Task> = Local::take();
+    me.deschedule(1, |task| {
+        queue.push(task);
+        unsafe { lock.unlock_noguard(); }
+        Ok(())
+    });
+    unsafe { lock.lock_noguard(); }
+}
+
+/// Reawakens tasks which were woken while the lock was held. This must only be
+/// called once the lock has been released, in case it incurs a context switch.
+pub fn reawaken<I: Iterator<Box<Task>>>(mut tasks: I) {
+    for task in tasks {
+        task.reawaken();
+    }
+}
+
+/// The state of a task selecting over a receiver of one of these flavors. It
+/// lives on the receiver itself, which is only ever used by one task.
+#[deriving(Clone)]
+pub enum Selecting {
+    NotSelecting,
+    Waiting(uint),
+}
+
+/// Helper for `start_selection`: if `ready`, the task is handed back,
+/// otherwise it is enqueued and the token recorded in `sel`.
+pub fn start_selection(queue: &mut WaitQueue, sel: &Cell<Selecting>,
+                       ready: bool, task: BlockedTask)
+                       -> Result<(), BlockedTask> {
+    if ready {
+        return Err(task)
+    }
+    sel.set(Waiting(queue.push(task)));
+    Ok(())
+}
+
+/// Helper for `abort_selection`: removes the task enqueued by
+/// `start_selection`, returning whether this receiver should be considered
+/// ready.
+///
+/// If the task had already been dequeued to be woken up, the select may still
+/// end up picking another handle and never receive from this one. The wakeup
+/// is then passed on to the next waiter, which must be reawakened after the
+/// lock is released; should that wakeup turn out to be spurious, the waiter
+/// simply blocks again.
+pub fn abort_selection(queue: &mut WaitQueue, sel: &Cell<Selecting>,
+                       ready: bool) -> (bool, Option<Box<Task>>) {
+    let prev = sel.get();
+    sel.set(NotSelecting);
+    match prev {
+        NotSelecting => (ready, None),
+        Waiting(id) => match queue.remove(id) {
+            Some(task) => { task.trash(); (ready, None) }
+            // The data we were woken up for may have been taken by another
+            // receiver in the meantime, in which case there is nothing to
+            // forward.
+            None if ready => (true, queue.wake_one()),
+            None => (false, None),
+        }
+    }
+}
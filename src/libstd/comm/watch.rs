@@ -0,0 +1,438 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Watch channels
+//!
+//! A watch channel holds a single value, the latest one sent. Sending never
+//! blocks and simply replaces the value, and receivers are woken up whenever
+//! it changes. Receivers which don't keep up only ever see the newest value,
+//! which makes these channels suitable for broadcasting state such as
+//! configuration or progress, rather than a stream of events.
+//!
+//! # Example
+//!
+//! ```
+//! use std::comm::watch_channel;
+//!
+//! let (tx, rx) = watch_channel(0);
+//! spawn(proc() {
+//!     for i in range(1, 10) {
+//!         tx.send(i);
+//!     }
+//! });
+//! // Receives the values that were current whenever the receiver woke up,
+//! // which may skip some of them.
+//! for i in rx.iter() {
+//!     println!("now at {}", i);
+//! }
+//! assert_eq!(rx.get(), 9);
+//! ```
+
+use cell::Cell;
+use clone::Clone;
+use iter::Iterator;
+use kinds::{Send, marker};
+use mem;
+use ops::Drop;
+use option::{Some, None, Option};
+use result::{Result, Ok, Err};
+use rt::task::BlockedTask;
+use sync::arc::UnsafeArc;
+use ty::Unsafe;
+use unstable::mutex::{NativeMutex, LockGuard};
+
+use super::{TryRecvError, Empty, Disconnected};
+use super::select;
+use super::waitqueue;
+use super::waitqueue::{WaitQueue, Selecting, NotSelecting};
+
+/// The sending half of a watch channel. This half can be cloned to update the
+/// value from many tasks.
+pub struct WatchSender<T> {
+    inner: UnsafeArc<Packet<T>>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// The receiving half of a watch channel. This half can be cloned, and each
+/// clone independently keeps track of which value it has seen last.
+pub struct WatchReceiver<T> {
+    inner: UnsafeArc<Packet<T>>,
+    seen: Cell<uint>,
+    selecting: Cell<Selecting>,
+    // can't share in an arc
+    marker: marker::NoShare,
+}
+
+/// An iterator over the changing values of a `WatchReceiver`. This iterator
+/// will block whenever `next` is called until a newer value is available, and
+/// `None` is returned once all senders have hung up.
+pub struct Changes<'a, T> {
+    rx: &'a WatchReceiver<T>
+}
+
+/// Creates a new watch channel holding the value `init`.
+///
+/// The receiver considers `init` to have been seen already, so the first call
+/// to `recv` will wait for a value to be sent.
+pub fn watch_channel<T: Send + Clone>(init: T)
+                                      -> (WatchSender<T>, WatchReceiver<T>) {
+    let (a, b) = UnsafeArc::new2(Packet::new(init));
+    (WatchSender { inner: a, marker: marker::NoShare },
+     WatchReceiver::new(b, 0))
+}
+
+struct Packet<T> {
+    lock: NativeMutex,
+    state: Unsafe<State<T>>,
+}
+
+struct State<T> {
+    value: T,
+    version: uint,       // bumped every time the value is replaced
+    senders: uint,       // number of live senders
+    receivers: uint,     // number of live receivers
+    waiters: WaitQueue,  // receivers waiting for a newer value
+}
+
+impl<T: Send + Clone> Packet<T> {
+    fn new(init: T) -> Packet<T> {
+        Packet {
+            lock: unsafe { NativeMutex::new() },
+            state: Unsafe::new(State {
+                value: init,
+                version: 0,
+                senders: 1,
+                receivers: 1,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    // See sync::Packet::lock
+    fn lock<'a>(&'a self) -> (LockGuard<'a>, &'a mut State<T>) {
+        unsafe {
+            let guard = self.lock.lock();
+            (guard, &mut *self.state.get())
+        }
+    }
+
+    fn send(&self, t: T) -> Result<(), T> {
+        let (guard, state) = self.lock();
+        if state.receivers == 0 { return Err(t) }
+        // The previous value is dropped outside of the lock
+        let _old = mem::replace(&mut state.value, t);
+        state.version += 1;
+        let tasks = state.waiters.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+        Ok(())
+    }
+
+    fn get(&self, seen: &Cell<uint>) -> T {
+        let (_g, state) = self.lock();
+        seen.set(state.version);
+        state.value.clone()
+    }
+
+    fn recv(&self, seen: &Cell<uint>) -> Result<T, ()> {
+        let (_g, state) = self.lock();
+        loop {
+            if state.version != seen.get() {
+                seen.set(state.version);
+                return Ok(state.value.clone())
+            }
+            if state.senders == 0 { return Err(()) }
+            waitqueue::wait(&mut state.waiters, &self.lock);
+        }
+    }
+
+    fn try_recv(&self, seen: &Cell<uint>) -> Result<T, TryRecvError> {
+        let (_g, state) = self.lock();
+        if state.version != seen.get() {
+            seen.set(state.version);
+            Ok(state.value.clone())
+        } else if state.senders == 0 {
+            Err(Disconnected)
+        } else {
+            Err(Empty)
+        }
+    }
+
+    fn version(&self) -> uint {
+        let (_g, state) = self.lock();
+        state.version
+    }
+
+    fn clone_chan(&self) {
+        let (_g, state) = self.lock();
+        state.senders += 1;
+    }
+
+    fn drop_chan(&self) {
+        let (guard, state) = self.lock();
+        state.senders -= 1;
+        if state.senders > 0 { return }
+
+        let tasks = state.waiters.wake_all();
+        mem::drop((state, guard));
+        waitqueue::reawaken(tasks.move_iter());
+    }
+
+    fn clone_port(&self) {
+        let (_g, state) = self.lock();
+        state.receivers += 1;
+    }
+
+    fn drop_port(&self) {
+        let (_g, state) = self.lock();
+        state.receivers -= 1;
+    }
+
+    fn ready(&self, seen: uint) -> bool {
+        let (_g, state) = self.lock();
+        state.senders == 0 || state.version != seen
+    }
+
+    fn start_selection(&self, seen: uint, task: BlockedTask,
+                       sel: &Cell<Selecting>) -> Result<(), BlockedTask> {
+        let (_g, state) = self.lock();
+        let ready = state.senders == 0 || state.version != seen;
+        waitqueue::start_selection(&mut state.waiters, sel, ready, task)
+    }
+
+    fn abort_selection(&self, seen: uint, sel: &Cell<Selecting>) -> bool {
+        let (guard, state) = self.lock();
+        let ready = state.senders == 0 || state.version != seen;
+        let (ready, task) = waitqueue::abort_selection(&mut state.waiters, sel,
+                                                       ready);
+        mem::drop((state, guard));
+        task.map(|t| t.reawaken());
+        ready
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// WatchSender
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send + Clone> WatchSender<T> {
+    /// Replaces the value held by this channel, waking up every receiver.
+    ///
+    /// This method will never block the current task.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if every receiver has hung up, see
+    /// `Sender::send`.
+    pub fn send(&self, t: T) {
+        if self.send_opt(t).is_err() {
+            fail!("sending on a closed channel");
+        }
+    }
+
+    /// Replaces the value held by this channel, returning the new value back
+    /// if every receiver has hung up.
+    ///
+    /// # Failure
+    ///
+    /// This function cannot fail.
+    pub fn send_opt(&self, t: T) -> Result<(), T> {
+        unsafe { (*self.inner.get()).send(t) }
+    }
+
+    /// Creates a new receiver for this channel. The current value counts as
+    /// seen by the new receiver.
+    pub fn subscribe(&self) -> WatchReceiver<T> {
+        unsafe { (*self.inner.get()).clone_port(); }
+        let version = unsafe { (*self.inner.get()).version() };
+        WatchReceiver::new(self.inner.clone(), version)
+    }
+}
+
+impl<T: Send + Clone> Clone for WatchSender<T> {
+    fn clone(&self) -> WatchSender<T> {
+        unsafe { (*self.inner.get()).clone_chan(); }
+        WatchSender { inner: self.inner.clone(), marker: marker::NoShare }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send + Clone> Drop for WatchSender<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).drop_chan(); }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// WatchReceiver
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T: Send + Clone> WatchReceiver<T> {
+    fn new(inner: UnsafeArc<Packet<T>>, seen: uint) -> WatchReceiver<T> {
+        WatchReceiver {
+            inner: inner,
+            seen: Cell::new(seen),
+            selecting: Cell::new(NotSelecting),
+            marker: marker::NoShare,
+        }
+    }
+
+    /// Returns a copy of the current value, marking it as seen. This never
+    /// blocks, and it keeps working after the senders have hung up.
+    pub fn get(&self) -> T {
+        unsafe { (*self.inner.get()).get(&self.seen) }
+    }
+
+    /// Blocks until the value changes from the one this receiver saw last,
+    /// returning the new value.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if there is no newer value and every sender
+    /// has hung up, see `Receiver::recv`.
+    pub fn recv(&self) -> T {
+        match self.recv_opt() {
+            Ok(t) => t,
+            Err(()) => fail!("receiving on a closed channel"),
+        }
+    }
+
+    /// Returns the current value if it hasn't been seen by this receiver yet,
+    /// without blocking.
+    ///
+    /// This function cannot fail.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        unsafe { (*self.inner.get()).try_recv(&self.seen) }
+    }
+
+    /// Blocks until the value changes from the one this receiver saw last,
+    /// returning `Err` if every sender has hung up without changing it.
+    ///
+    /// This function cannot fail.
+    pub fn recv_opt(&self) -> Result<T, ()> {
+        unsafe { (*self.inner.get()).recv(&self.seen) }
+    }
+
+    /// Returns an iterator over the changes to this channel's value. It will
+    /// return `None` when the channel has hung up.
+    pub fn iter<'a>(&'a self) -> Changes<'a, T> {
+        Changes { rx: self }
+    }
+}
+
+impl<T: Send + Clone> Clone for WatchReceiver<T> {
+    fn clone(&self) -> WatchReceiver<T> {
+        unsafe { (*self.inner.get()).clone_port(); }
+        WatchReceiver::new(self.inner.clone(), self.seen.get())
+    }
+}
+
+impl<T: Send + Clone> select::Packet for WatchReceiver<T> {
+    fn can_recv(&self) -> bool {
+        unsafe { (*self.inner.get()).ready(self.seen.get()) }
+    }
+
+    fn start_selection(&self, task: BlockedTask) -> Result<(), BlockedTask> {
+        unsafe {
+            (*self.inner.get()).start_selection(self.seen.get(), task,
+                                                &self.selecting)
+        }
+    }
+
+    fn abort_selection(&self) -> bool {
+        unsafe {
+            (*self.inner.get()).abort_selection(self.seen.get(),
+                                                &self.selecting)
+        }
+    }
+}
+
+impl<T: Send + Clone> select::Receive<T> for WatchReceiver<T> {
+    fn recv_opt(&self) -> Result<T, ()> { self.recv_opt() }
+}
+
+impl<'a, T: Send + Clone> Iterator<T> for Changes<'a, T> {
+    fn next(&mut self) -> Option<T> { self.rx.recv_opt().ok() }
+}
+
+#[unsafe_destructor]
+impl<T: Send + Clone> Drop for WatchReceiver<T> {
+    fn drop(&mut self) {
+        unsafe { (*self.inner.get()).drop_port(); }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prelude::*;
+
+    test!(fn smoke() {
+        let (tx, rx) = watch_channel(0);
+        assert_eq!(rx.get(), 0);
+        tx.send(1);
+        assert_eq!(rx.recv(), 1);
+    })
+
+    test!(fn only_latest_value() {
+        let (tx, rx) = watch_channel(0);
+        tx.send(1);
+        tx.send(2);
+        tx.send(3);
+        assert_eq!(rx.recv(), 3);
+        assert_eq!(rx.try_recv(), Err(Empty));
+        assert_eq!(rx.get(), 3);
+    })
+
+    test!(fn clones_track_independently() {
+        let (tx, rx1) = watch_channel(0);
+        let rx2 = rx1.clone();
+        tx.send(1);
+        assert_eq!(rx1.recv(), 1);
+        assert_eq!(rx1.try_recv(), Err(Empty));
+        assert_eq!(rx2.try_recv(), Ok(1));
+        let rx3 = tx.subscribe();
+        assert_eq!(rx3.try_recv(), Err(Empty));
+    })
+
+    test!(fn wakes_blocked_receiver() {
+        let (tx, rx) = watch_channel(0);
+        spawn(proc() {
+            tx.send(10);
+        });
+        assert_eq!(rx.recv(), 10);
+    })
+
+    test!(fn disconnect() {
+        let (tx, rx) = watch_channel(0);
+        tx.send(1);
+        drop(tx);
+        assert_eq!(rx.recv_opt(), Ok(1));
+        assert_eq!(rx.recv_opt(), Err(()));
+        assert_eq!(rx.try_recv(), Err(Disconnected));
+        assert_eq!(rx.get(), 1);
+    })
+
+    test!(fn no_receivers() {
+        let (tx, rx) = watch_channel(0);
+        drop(rx);
+        assert_eq!(tx.send_opt(1), Err(1));
+    })
+
+    test!(fn select_with_receiver() {
+        let (tx1, rx1) = watch_channel(0);
+        let (_tx2, rx2) = channel::<int>();
+        tx1.send(4);
+        select! (
+            a = rx1.recv() => { assert_eq!(a, 4); },
+            _b = rx2.recv() => { fail!() }
+        )
+    })
+}
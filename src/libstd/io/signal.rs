@@ -38,6 +38,13 @@ use vec::Vec;
 pub enum Signum {
     /// Equivalent to SIGBREAK, delivered when the user presses Ctrl-Break.
     Break = 21i,
+    /// Equivalent to SIGPIPE, delivered when writing to a pipe or socket
+    /// whose other end has been closed. The runtime ignores this signal by
+    /// default, so it is only received once a listener is registered for it.
+    BrokenPipe = 13i,
+    /// Equivalent to SIGCHLD, delivered when a child process exits or is
+    /// stopped.
+    ChildExit = 17i,
     /// Equivalent to SIGHUP, delivered when the user closes the terminal
     /// window. On delivery of HangUp, the program is given approximately
     /// 10 seconds to perform any cleanup. After that, Windows will
@@ -49,6 +56,9 @@ pub enum Signum {
     Quit = 3i,
     /// Equivalent to SIGTSTP, delivered when the user presses Ctrl-z.
     StopTemporarily = 20i,
+    /// Equivalent to SIGTERM, the polite request to terminate sent by `kill`
+    /// and by service managers on shutdown.
+    Terminate = 15i,
     /// Equivalent to SIGUSR1.
     User1 = 10i,
     /// Equivalent to SIGUSR2.
@@ -154,7 +164,7 @@ mod test_unix {
     use libc;
     use comm::Empty;
     use io::timer;
-    use super::{Listener, Interrupt};
+    use super::{Listener, Interrupt, HangUp, Terminate};
 
     fn sigint() {
         unsafe {
@@ -192,6 +202,20 @@ mod test_unix {
         }
     }
 
+    #[test] #[cfg(not(target_os="android"))] // FIXME(#10378)
+    fn test_io_signal_one_listener_many_signums() {
+        let mut s = Listener::new();
+        s.register(HangUp).unwrap();
+        s.register(Terminate).unwrap();
+        unsafe {
+            libc::funcs::posix88::signal::kill(libc::getpid(), libc::SIGTERM);
+        }
+        match s.rx.recv() {
+            Terminate => (),
+            s => fail!("Expected Terminate, got {:?}", s),
+        }
+    }
+
     #[test] #[cfg(not(target_os="android"))] // FIXME(#10378)
     fn test_io_signal_unregister() {
         let mut s1 = Listener::new();
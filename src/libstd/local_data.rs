@@ -33,22 +33,33 @@ key_vector.replace(Some(~[4]));
 assert_eq!(*key_vector.get().unwrap(), ~[4]);
 ```
 
+A value can also be set for the duration of a closure with `set_for`, after
+which the previous value is restored. Keys declared with
+`local_data_key!(inherited name: T)` have their values cloned into each task
+spawned by the task which set them.
+
+When a task exits, its local data is destroyed in the reverse of the order in
+which it was set.
+
 */
 
 // Casting 'Arcane Sight' reveals an overwhelming aura of Transmutation
 // magic.
 
 use cast;
+use clone::Clone;
+use container::Container;
 use iter::{Iterator};
 use kinds::Send;
 use kinds::marker;
-use mem::replace;
+use mem::{replace, drop};
 use ops::{Drop, Deref};
 use option::{None, Option, Some};
 use owned::Box;
 use raw;
 use rt::task::{Task, LocalStorage};
 use slice::{ImmutableVector, MutableVector};
+use unstable::finally::Finally;
 use vec::Vec;
 
 /**
@@ -67,6 +78,22 @@ pub type Key<T> = &'static KeyValue<T>;
 #[allow(missing_doc)]
 pub enum KeyValue<T> { Key }
 
+/**
+ * Indexes a task-local data slot whose value is inherited by child tasks.
+ *
+ * Whenever a task is spawned with `TaskBuilder::spawn` (or `task::spawn`), the
+ * values of all of the spawning task's inherited keys are cloned into the new
+ * task before it starts running. This is useful for context such as request
+ * identifiers or logging state which should follow work into the tasks it
+ * spawns.
+ *
+ * Inherited keys are declared with `local_data_key!(inherited name: T)`.
+ */
+pub type InheritedKey<T> = &'static InheritedKeyValue<T>;
+
+#[allow(missing_doc)]
+pub enum InheritedKeyValue<T> { InheritedKey }
+
 #[doc(hidden)]
 trait LocalData {}
 impl<T: 'static> LocalData for T {}
@@ -92,8 +119,21 @@ impl<T: 'static> LocalData for T {}
 // n.b. If TLS is used heavily in future, this could be made more efficient with
 //      a proper map.
 #[doc(hidden)]
-pub type Map = Vec<Option<(*u8, TLSValue, uint)>>;
+pub type Map = Vec<Option<Slot>>;
 type TLSValue = Box<LocalData:Send>;
+type Cloner = fn(&TLSValue) -> TLSValue;
+
+#[doc(hidden)]
+pub struct Slot {
+    key: *u8,
+    value: TLSValue,
+    loans: uint,
+    // When this slot was filled, relative to the other slots. Values are
+    // destroyed in the reverse of this order when the task exits.
+    seq: uint,
+    // Present for inherited keys, used to copy the value into child tasks.
+    clone: Option<Cloner>,
+}
 
 // Gets the map from the runtime. Lazily initialises if not done so already.
 unsafe fn get_local_map() -> &mut Map {
@@ -122,13 +162,16 @@ fn key_to_key_value<T: 'static>(key: Key<T>) -> *u8 {
     key as *KeyValue<T> as *u8
 }
 
+fn inherited_key_to_key_value<T: 'static>(key: InheritedKey<T>) -> *u8 {
+    key as *InheritedKeyValue<T> as *u8
+}
+
 /// An RAII immutable reference to a task-local value.
 ///
 /// The task-local data can be accessed through this value, and when this
 /// structure is dropped it will return the borrow on the data.
 pub struct Ref<T> {
     ptr: &'static T,
-    key: Key<T>,
     index: uint,
     nosend: marker::NoSend,
 }
@@ -154,53 +197,7 @@ impl<T: 'static> KeyValue<T> {
     /// assert_eq!(foo.replace(None), Some(4));
     /// ```
     pub fn replace(&'static self, data: Option<T>) -> Option<T> {
-        let map = unsafe { get_local_map() };
-        let keyval = key_to_key_value(self);
-
-        // When the task-local map is destroyed, all the data needs to be
-        // cleaned up. For this reason we can't do some clever tricks to store
-        // '~T' as a '*c_void' or something like that. To solve the problem, we
-        // cast everything to a trait (LocalData) which is then stored inside
-        // the map.  Upon destruction of the map, all the objects will be
-        // destroyed and the traits have enough information about them to
-        // destroy themselves.
-        //
-        // Additionally, the type of the local data map must ascribe to Send, so
-        // we do the transmute here to add the Send bound back on. This doesn't
-        // actually matter because TLS will always own the data (until its moved
-        // out) and we're not actually sending it to other schedulers or
-        // anything.
-        let newval = data.map(|d| {
-            let d = box d as Box<LocalData>;
-            let d: Box<LocalData:Send> = unsafe { cast::transmute(d) };
-            (keyval, d, 0)
-        });
-
-        let pos = match self.find(map) {
-            Some((i, _, &0)) => Some(i),
-            Some((_, _, _)) => fail!("TLS value cannot be replaced because it \
-                                      is already borrowed"),
-            None => map.iter().position(|entry| entry.is_none()),
-        };
-
-        match pos {
-            Some(i) => {
-                replace(map.get_mut(i), newval).map(|(_, data, _)| {
-                    // Move `data` into transmute to get out the memory that it
-                    // owns, we must free it manually later.
-                    let t: raw::TraitObject = unsafe { cast::transmute(data) };
-                    let alloc: Box<T> = unsafe { cast::transmute(t.data) };
-
-                    // Now that we own `alloc`, we can just move out of it as we
-                    // would with any other data.
-                    *alloc
-                })
-            }
-            None => {
-                map.push(newval);
-                None
-            }
-        }
+        replace_value(key_to_key_value(self), data, None)
     }
 
     /// Borrows a value from TLS.
@@ -221,33 +218,217 @@ impl<T: 'static> KeyValue<T> {
     /// assert_eq!(*key.get().unwrap(), 3);
     /// ```
     pub fn get(&'static self) -> Option<Ref<T>> {
-        let map = unsafe { get_local_map() };
+        get_value(key_to_key_value(self))
+    }
 
-        self.find(map).map(|(pos, data, loan)| {
-            *loan += 1;
-
-            // data was created with `~T as ~LocalData`, so we extract
-            // pointer part of the trait, (as ~T), and then use
-            // compiler coercions to achieve a '&' pointer.
-            let ptr = unsafe {
-                let data = data as *Box<LocalData:Send> as *raw::TraitObject;
-                &mut *((*data).data as *mut T)
-            };
-            Ref { ptr: ptr, index: pos, nosend: marker::NoSend, key: self }
-        })
+    /// Sets this key to `value` for the duration of `f`, restoring the
+    /// previous value (or absence of one) afterwards, even if `f` fails.
+    ///
+    /// # Failure
+    ///
+    /// This function will fail if this key is currently on loan with the `get`
+    /// method, either on entry or when `f` returns.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// local_data_key!(depth: int)
+    ///
+    /// depth.replace(Some(1));
+    /// depth.set_for(2, || {
+    ///     assert_eq!(*depth.get().unwrap(), 2);
+    /// });
+    /// assert_eq!(*depth.get().unwrap(), 1);
+    /// ```
+    pub fn set_for<R>(&'static self, value: T, f: || -> R) -> R {
+        set_value_for(|data| self.replace(data), value, f)
+    }
+}
+
+impl<T: 'static + Clone + Send> InheritedKeyValue<T> {
+    /// Replaces a value in task local storage, see `KeyValue::replace`.
+    ///
+    /// The new value will be cloned into every task spawned by this task from
+    /// now on.
+    pub fn replace(&'static self, data: Option<T>) -> Option<T> {
+        replace_value(inherited_key_to_key_value(self), data,
+                      Some(clone_value::<T>))
     }
 
-    fn find<'a>(&'static self,
-                map: &'a mut Map) -> Option<(uint, &'a TLSValue, &'a mut uint)>{
-        let key_value = key_to_key_value(self);
-        map.mut_iter().enumerate().filter_map(|(i, entry)| {
-            match *entry {
-                Some((k, ref data, ref mut loan)) if k == key_value => {
-                    Some((i, data, loan))
-                }
-                _ => None
+    /// Borrows a value from TLS, see `KeyValue::get`.
+    pub fn get(&'static self) -> Option<Ref<T>> {
+        get_value(inherited_key_to_key_value(self))
+    }
+
+    /// Sets this key to `value` for the duration of `f`, restoring the
+    /// previous value afterwards, see `KeyValue::set_for`.
+    ///
+    /// Tasks spawned from within `f` inherit `value`.
+    pub fn set_for<R>(&'static self, value: T, f: || -> R) -> R {
+        set_value_for(|data| self.replace(data), value, f)
+    }
+}
+
+fn set_value_for<T, R>(replace: |Option<T>| -> Option<T>, value: T,
+                       f: || -> R) -> R {
+    let mut prev = Some(replace(Some(value)));
+    f.finally(|| { replace(prev.take_unwrap()); })
+}
+
+fn replace_value<T: 'static>(keyval: *u8, data: Option<T>,
+                             clone: Option<Cloner>) -> Option<T> {
+    let map = unsafe { get_local_map() };
+
+    // When the task-local map is destroyed, all the data needs to be
+    // cleaned up. For this reason we can't do some clever tricks to store
+    // '~T' as a '*c_void' or something like that. To solve the problem, we
+    // cast everything to a trait (LocalData) which is then stored inside
+    // the map.  Upon destruction of the map, all the objects will be
+    // destroyed and the traits have enough information about them to
+    // destroy themselves.
+    //
+    // Additionally, the type of the local data map must ascribe to Send, so
+    // we do the transmute here to add the Send bound back on. This doesn't
+    // actually matter because TLS will always own the data (until its moved
+    // out) and we're not actually sending it to other schedulers or
+    // anything.
+    let seq = next_seq(map);
+    let newval = data.map(|d| {
+        let d = box d as Box<LocalData>;
+        let d: Box<LocalData:Send> = unsafe { cast::transmute(d) };
+        Slot { key: keyval, value: d, loans: 0, seq: seq, clone: clone }
+    });
+
+    let pos = match find(keyval, map) {
+        Some((i, &0)) => Some(i),
+        Some((_, _)) => fail!("TLS value cannot be replaced because it \
+                               is already borrowed"),
+        None => map.iter().position(|entry| entry.is_none()),
+    };
+
+    match pos {
+        Some(i) => {
+            replace(map.get_mut(i), newval).map(|slot| {
+                // Move `data` into transmute to get out the memory that it
+                // owns, we must free it manually later.
+                let t: raw::TraitObject = unsafe {
+                    cast::transmute(slot.value)
+                };
+                let alloc: Box<T> = unsafe { cast::transmute(t.data) };
+
+                // Now that we own `alloc`, we can just move out of it as we
+                // would with any other data.
+                *alloc
+            })
+        }
+        None => {
+            map.push(newval);
+            None
+        }
+    }
+}
+
+fn get_value<T: 'static>(keyval: *u8) -> Option<Ref<T>> {
+    let map = unsafe { get_local_map() };
+
+    find(keyval, map).map(|(pos, loan)| {
+        *loan += 1;
+
+        // data was created with `~T as ~LocalData`, so we extract
+        // pointer part of the trait, (as ~T), and then use
+        // compiler coercions to achieve a '&' pointer.
+        let ptr = unsafe {
+            let data = &map.get(pos).get_ref().value;
+            &*(value_ptr(data) as *T)
+        };
+        Ref { ptr: ptr, index: pos, nosend: marker::NoSend }
+    })
+}
+
+fn find<'a>(keyval: *u8, map: &'a mut Map) -> Option<(uint, &'a mut uint)> {
+    map.mut_iter().enumerate().filter_map(|(i, entry)| {
+        match *entry {
+            Some(ref mut slot) if slot.key == keyval => {
+                Some((i, &mut slot.loans))
             }
-        }).next()
+            _ => None
+        }
+    }).next()
+}
+
+fn next_seq(map: &Map) -> uint {
+    map.iter().filter_map(|e| e.as_ref().map(|s| s.seq + 1)).max()
+       .unwrap_or(0)
+}
+
+unsafe fn value_ptr(data: &TLSValue) -> *u8 {
+    let data = data as *Box<LocalData:Send> as *raw::TraitObject;
+    (*data).data as *u8
+}
+
+fn clone_value<T: 'static + Clone + Send>(data: &TLSValue) -> TLSValue {
+    let t: &T = unsafe { &*(value_ptr(data) as *T) };
+    let d = box t.clone() as Box<LocalData>;
+    unsafe { cast::transmute(d) }
+}
+
+/// The values of the inherited keys of one task, on their way to a child task.
+#[doc(hidden)]
+pub struct Inherited {
+    // The keys are only ever compared, they are stored as integers so that
+    // this structure can be sent to the child.
+    values: Vec<(uint, TLSValue, Cloner)>,
+}
+
+/// Clones the values of all of the current task's inherited keys.
+#[doc(hidden)]
+pub fn inherited() -> Inherited {
+    let map = unsafe { get_local_map() };
+    let mut slots: Vec<&Slot> = map.iter().filter_map(|e| e.as_ref())
+                                   .filter(|s| s.clone.is_some()).collect();
+    slots.sort_by(|a, b| a.seq.cmp(&b.seq));
+    Inherited {
+        values: slots.iter().map(|s| {
+            let clone = s.clone.unwrap();
+            (s.key as uint, clone(&s.value), clone)
+        }).collect()
+    }
+}
+
+impl Inherited {
+    /// Wraps the body of a new task so that it installs these values in its
+    /// task-local storage before running.
+    pub fn wrap(self, f: proc():Send) -> proc():Send {
+        if self.values.len() == 0 { return f }
+        proc() {
+            let Inherited { values } = self;
+            let map = unsafe { get_local_map() };
+            for (key, value, clone) in values.move_iter() {
+                let seq = next_seq(map);
+                map.push(Some(Slot {
+                    key: key as *u8,
+                    value: value,
+                    loans: 0,
+                    seq: seq,
+                    clone: Some(clone),
+                }));
+            }
+            f()
+        }
+    }
+}
+
+/// Destroys the values in a task's local storage, in the reverse of the order
+/// in which they were set.
+///
+/// The map has already been detached from the task by the time this is called,
+/// so destructors which access local data through a key see it as unset.
+#[doc(hidden)]
+pub fn destroy(map: Map) {
+    let mut slots: Vec<Slot> = map.move_iter().filter_map(|e| e).collect();
+    slots.sort_by(|a, b| b.seq.cmp(&a.seq));
+    for slot in slots.move_iter() {
+        drop(slot);
     }
 }
 
@@ -260,8 +441,8 @@ impl<T: 'static> Drop for Ref<T> {
     fn drop(&mut self) {
         let map = unsafe { get_local_map() };
 
-        let (_, _, ref mut loan) = *map.get_mut(self.index).get_mut_ref();
-        *loan -= 1;
+        let slot = map.get_mut(self.index).get_mut_ref();
+        slot.loans -= 1;
     }
 }
 
@@ -423,4 +604,92 @@ mod tests {
         let _k = key.get();
         key.replace(Some(4));
     }
+
+    #[test]
+    fn test_set_for() {
+        static key: Key<int> = &Key;
+        assert_eq!(key.set_for(1, || *key.get().unwrap() + 1), 2);
+        assert!(key.get().is_none());
+
+        key.replace(Some(3));
+        key.set_for(4, || {
+            key.set_for(5, || assert_eq!(*key.get().unwrap(), 5));
+            assert_eq!(*key.get().unwrap(), 4);
+        });
+        assert_eq!(*key.get().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_set_for_restores_on_failure() {
+        static key: Key<int> = &Key;
+        struct Check(Sender<int>);
+        impl Drop for Check {
+            fn drop(&mut self) {
+                let Check(ref tx) = *self;
+                tx.send(*key.get().unwrap());
+            }
+        }
+
+        let (tx, rx) = channel();
+        let r = task::try(proc() {
+            key.replace(Some(1));
+            let _check = Check(tx);
+            key.set_for(2, || fail!());
+        });
+        assert!(r.is_err());
+        assert_eq!(rx.recv(), 1);
+    }
+
+    #[test]
+    fn test_inherited() {
+        static inherited: InheritedKey<~str> = &InheritedKey;
+        static plain: Key<~str> = &Key;
+        inherited.replace(Some("parent".to_owned()));
+        plain.replace(Some("parent".to_owned()));
+
+        let (tx, rx) = channel();
+        task::spawn(proc() {
+            assert!(plain.get().is_none());
+            assert_eq!(inherited.get().unwrap().as_slice(), "parent");
+            inherited.replace(Some("child".to_owned()));
+            task::spawn(proc() {
+                tx.send(inherited.get().unwrap().clone());
+            });
+        });
+        assert_eq!(rx.recv(), "child".to_owned());
+        assert_eq!(inherited.get().unwrap().as_slice(), "parent");
+
+        let (tx, rx) = channel();
+        inherited.set_for("scoped".to_owned(), || {
+            task::spawn(proc() {
+                tx.send(inherited.get().unwrap().clone());
+            });
+        });
+        assert_eq!(rx.recv(), "scoped".to_owned());
+        assert_eq!(inherited.get().unwrap().as_slice(), "parent");
+    }
+
+    #[test]
+    fn test_destruction_order() {
+        struct Noisy(uint, Sender<uint>);
+        impl Drop for Noisy {
+            fn drop(&mut self) {
+                let Noisy(n, ref tx) = *self;
+                tx.send(n);
+            }
+        }
+        static key1: Key<Noisy> = &Key;
+        static key2: Key<Noisy> = &Key;
+        static key3: Key<Noisy> = &Key;
+
+        let (tx, rx) = channel();
+        task::spawn(proc() {
+            key2.replace(Some(Noisy(1, tx.clone())));
+            key1.replace(Some(Noisy(2, tx.clone())));
+            key3.replace(Some(Noisy(3, tx.clone())));
+            key3.replace(None);
+            key3.replace(Some(Noisy(4, tx.clone())));
+        });
+        assert_eq!(rx.iter().collect::<Vec<uint>>(), vec!(3, 4, 2, 1));
+    }
 }
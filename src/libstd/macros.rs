@@ -294,6 +294,18 @@ macro_rules! println(
 /// my_integer.replace(Some(2));
 /// println!("{}", my_integer.get().map(|a| *a));
 /// ```
+///
+/// Keys declared as `inherited` have their values cloned into every task
+/// spawned by the task which set them:
+///
+/// ```
+/// local_data_key!(inherited request_id: uint)
+///
+/// request_id.replace(Some(7));
+/// spawn(proc() {
+///     assert_eq!(request_id.get().map(|a| *a), Some(7));
+/// });
+/// ```
 #[macro_export]
 macro_rules! local_data_key(
     ($name:ident: $ty:ty) => (
@@ -302,6 +314,14 @@ macro_rules! local_data_key(
     (pub $name:ident: $ty:ty) => (
         pub static $name: ::std::local_data::Key<$ty> = &::std::local_data::Key;
     );
+    (inherited $name:ident: $ty:ty) => (
+        static $name: ::std::local_data::InheritedKey<$ty> =
+            &::std::local_data::InheritedKey;
+    );
+    (pub inherited $name:ident: $ty:ty) => (
+        pub static $name: ::std::local_data::InheritedKey<$ty> =
+            &::std::local_data::InheritedKey;
+    );
 )
 
 /// Helper macro for unwrapping `Result` values while returning early with an
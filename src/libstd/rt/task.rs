@@ -165,7 +165,7 @@ impl Task {
                     optmap.take()
                 };
                 drop(task);
-                drop(storage_map);
+                storage_map.map(local_data::destroy);
 
                 // Destroy remaining boxes. Also may run user dtors.
                 unsafe { cleanup::annihilate(); }
@@ -40,6 +40,7 @@ use any::Any;
 use comm::{Sender, Receiver, channel};
 use io::Writer;
 use kinds::{Send, marker};
+use local_data;
 use option::{None, Some, Option};
 use owned::Box;
 use result::{Result, Ok, Err};
@@ -168,6 +169,9 @@ impl TaskBuilder {
      * Sets up a new task with its own call stack and schedules it to run
      * the provided unique closure. The task has the properties and behavior
      * specified by the task_builder.
+     *
+     * The values of the current task's inherited task-local keys are cloned
+     * into the new task before it runs, see `std::local_data::InheritedKey`.
      */
     pub fn spawn(mut self, f: proc():Send) {
         let gen_body = self.gen_body.take();
@@ -175,6 +179,7 @@ impl TaskBuilder {
             Some(gen) => gen(f),
             None => f
         };
+        let f = local_data::inherited().wrap(f);
         let t: Box<Task> = Local::take();
         t.spawn_sibling(self.opts, f);
     }
@@ -0,0 +1,211 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A cell holding an `Arc` which can be atomically loaded and replaced.
+
+use std::cast;
+use std::sync::atomics::{AtomicPtr, SeqCst};
+
+use arc::Arc;
+use epoch::Collector;
+
+/// A shared, atomically replaceable `Arc<T>`.
+///
+/// This is useful for state which is read often and replaced rarely, such as
+/// configuration. Readers get their own reference to the current value
+/// without taking any locks, and keep using it even if it is replaced in the
+/// meantime.
+///
+/// # Example
+///
+/// ```rust
+/// use sync::{Arc, AtomicArc};
+///
+/// // a timeout, in seconds
+/// let timeout = Arc::new(AtomicArc::new(Arc::new(30u)));
+/// let timeout2 = timeout.clone();
+/// spawn(proc() {
+///     let current = timeout2.load();
+///     println!("waiting for {} seconds", *current);
+/// });
+/// timeout.store(Arc::new(60u));
+/// ```
+pub struct AtomicArc<T> {
+    // A boxed `Arc` which owns one reference. Replaced boxes are retired,
+    // since readers may be in the middle of cloning them.
+    ptr: AtomicPtr<Arc<T>>,
+    collector: Collector,
+}
+
+impl<T: Share + Send> AtomicArc<T> {
+    /// Creates a new cell holding `value`.
+    pub fn new(value: Arc<T>) -> AtomicArc<T> {
+        AtomicArc {
+            ptr: AtomicPtr::new(unsafe { cast::transmute(box value) }),
+            collector: Collector::new(),
+        }
+    }
+
+    /// Returns a new reference to the current value.
+    pub fn load(&self) -> Arc<T> {
+        let handle = self.collector.register();
+        let _guard = handle.pin();
+        unsafe { (*self.ptr.load(SeqCst)).clone() }
+    }
+
+    /// Replaces the current value.
+    pub fn store(&self, value: Arc<T>) {
+        drop(self.swap(value));
+    }
+
+    /// Replaces the current value, returning the previous one.
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        let new: *mut Arc<T> = unsafe { cast::transmute(box value) };
+        let handle = self.collector.register();
+        let guard = handle.pin();
+        let old = self.ptr.swap(new, SeqCst);
+        unsafe {
+            let ret = (*old).clone();
+            guard.retire(old);
+            ret
+        }
+    }
+
+    /// Replaces the current value with `new` if it is still `current`,
+    /// compared by pointer. On success `Ok` is returned, otherwise `new` is
+    /// handed back along with the actual current value.
+    pub fn compare_and_swap(&self, current: &Arc<T>, new: Arc<T>)
+                            -> Result<(), (Arc<T>, Arc<T>)> {
+        let new: *mut Arc<T> = unsafe { cast::transmute(box new) };
+        let handle = self.collector.register();
+        let guard = handle.pin();
+        loop {
+            let old = self.ptr.load(SeqCst);
+            unsafe {
+                if !same(&*old, current) {
+                    let new: Box<Arc<T>> = cast::transmute(new);
+                    return Err((*new, (*old).clone()))
+                }
+                if self.ptr.compare_and_swap(old, new, SeqCst) == old {
+                    guard.retire(old);
+                    return Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn same<T: Share + Send>(a: &Arc<T>, b: &Arc<T>) -> bool {
+    &**a as *T == &**b as *T
+}
+
+#[unsafe_destructor]
+impl<T: Share + Send> Drop for AtomicArc<T> {
+    fn drop(&mut self) {
+        let _: Box<Arc<T>> = unsafe { cast::transmute(self.ptr.load(SeqCst)) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use native;
+    use std::sync::atomics::{AtomicUint, SeqCst};
+
+    use arc::Arc;
+    use super::AtomicArc;
+
+    struct Counted(Arc<AtomicUint>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            let Counted(ref dropped) = *self;
+            dropped.fetch_add(1, SeqCst);
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        let a = AtomicArc::new(Arc::new(1));
+        assert_eq!(*a.load(), 1);
+        a.store(Arc::new(2));
+        assert_eq!(*a.load(), 2);
+        assert_eq!(*a.swap(Arc::new(3)), 2);
+        assert_eq!(*a.load(), 3);
+    }
+
+    #[test]
+    fn readers_keep_old_value() {
+        let a = AtomicArc::new(Arc::new(box 1));
+        let old = a.load();
+        a.store(Arc::new(box 2));
+        assert_eq!(**old, 1);
+        assert_eq!(**a.load(), 2);
+    }
+
+    #[test]
+    fn compare_and_swap() {
+        let first = Arc::new(1);
+        let a = AtomicArc::new(first.clone());
+        let stale = Arc::new(1);
+        match a.compare_and_swap(&stale, Arc::new(2)) {
+            Ok(()) => fail!(),
+            Err((new, cur)) => {
+                assert_eq!(*new, 2);
+                assert_eq!(*cur, 1);
+            }
+        }
+        assert!(a.compare_and_swap(&first, Arc::new(3)).is_ok());
+        assert_eq!(*a.load(), 3);
+    }
+
+    #[test]
+    fn replaced_values_are_dropped() {
+        static AMT: uint = 1000;
+        let dropped = Arc::new(AtomicUint::new(0));
+        let a = AtomicArc::new(Arc::new(Counted(dropped.clone())));
+        for _ in range(0, AMT) {
+            a.store(Arc::new(Counted(dropped.clone())));
+        }
+        // Old values go away while the cell is still alive, not only when it
+        // is dropped.
+        assert!(dropped.load(SeqCst) > AMT - AMT / 10);
+        drop(a);
+        assert_eq!(dropped.load(SeqCst), AMT + 1);
+    }
+
+    #[test]
+    fn stress() {
+        static READERS: uint = 4;
+        static SWAPS: uint = 5000;
+        let a = Arc::new(AtomicArc::new(Arc::new(0u)));
+        let (tx, rx) = channel();
+
+        for _ in range(0, READERS) {
+            let a = a.clone();
+            let tx = tx.clone();
+            native::task::spawn(proc() {
+                let mut last = 0;
+                while last < SWAPS {
+                    let cur = *a.load();
+                    // values are only ever replaced with larger ones
+                    assert!(cur >= last);
+                    last = cur;
+                }
+                tx.send(());
+            });
+        }
+        drop(tx);
+        for i in range(1, SWAPS + 1) {
+            a.store(Arc::new(i));
+        }
+        for _ in range(0, READERS) {
+            rx.recv();
+        }
+    }
+}
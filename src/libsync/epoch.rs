@@ -0,0 +1,521 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Epoch-based memory reclamation
+//!
+//! Lock-free data structures unlink nodes with a single atomic operation, but
+//! at that point other tasks may still be in the middle of reading the node
+//! that was just unlinked. The node can only be freed once all of these
+//! readers are done with it. This module tracks when that is the case.
+//!
+//! Every task which wants to access a structure registers itself with the
+//! structure's `Collector`, receiving a `Handle`. All accesses to shared nodes
+//! happen while the handle is *pinned*, and pinning records the collector's
+//! current global epoch. Unlinked nodes are handed to `Guard::retire` which
+//! tags them with the global epoch at that time.
+//!
+//! The global epoch is only advanced when every pinned participant has
+//! observed the current epoch. A task which was pinned before a node was
+//! unlinked therefore has an epoch no greater than the node's tag, so once the
+//! global epoch has advanced twice past the tag no such task can remain and
+//! the node is freed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sync::epoch::Collector;
+//! use std::sync::atomics::{AtomicPtr, SeqCst};
+//!
+//! let collector = Collector::new();
+//! let shared = AtomicPtr::new(unsafe { std::cast::transmute(box 1) });
+//!
+//! let handle = collector.register();
+//! let guard = handle.pin();
+//! let new: *mut int = unsafe { std::cast::transmute(box 2) };
+//! let old = shared.swap(new, SeqCst);
+//! // Other tasks may still be reading `old`, so it can't be freed yet.
+//! unsafe { guard.retire(old) }
+//! # drop(guard);
+//! # let _: Box<int> = unsafe { std::cast::transmute(shared.load(SeqCst)) };
+//! ```
+
+use std::cast;
+use std::cell::Cell;
+use std::kinds::marker;
+use std::sync::atomics::{AtomicUint, AtomicBool, AtomicPtr, SeqCst};
+use std::ty::Unsafe;
+
+/// The number of objects retired, by all participants together, between
+/// attempts to advance the global epoch.
+static ADVANCE_INTERVAL: uint = 32;
+
+static PINNED: uint = 1;
+
+struct Garbage {
+    epoch: uint,
+    ptr: *mut u8,
+    free: unsafe fn(*mut u8),
+}
+
+struct Participant {
+    // `(epoch << 1) | PINNED` while pinned, 0 otherwise
+    state: AtomicUint,
+    // whether a `Handle` currently owns this participant
+    in_use: AtomicBool,
+    // only touched by the owner of this participant, or by the collector once
+    // no handles remain
+    garbage: Unsafe<Vec<Garbage>>,
+    next: *mut Participant,
+}
+
+/// The shared state of the epoch-based reclamation scheme for a data structure.
+///
+/// A collector is normally embedded in the structure it manages memory for.
+/// All objects still awaiting reclamation are freed when the collector is
+/// dropped.
+pub struct Collector {
+    epoch: AtomicUint,
+    participants: AtomicPtr<Participant>,
+    // Kept here rather than in the handles, which are often only registered
+    // for a single operation.
+    retired: AtomicUint,
+}
+
+/// A task's registration with a `Collector`.
+///
+/// Registration is fairly cheap: the records of handles which have been
+/// dropped are reused. A handle may only be used by one task at a time.
+pub struct Handle<'a> {
+    collector: &'a Collector,
+    participant: *mut Participant,
+    pins: Cell<uint>,
+    noshare: marker::NoShare,
+}
+
+/// An RAII guard indicating that the current task is pinned, see
+/// `Handle::pin`.
+///
+/// Shared objects loaded while a guard is alive remain valid until the guard
+/// is dropped, even if they are concurrently retired by another task.
+pub struct Guard<'a> {
+    handle: &'a Handle<'a>,
+}
+
+impl Collector {
+    /// Creates a new collector, with no participants.
+    pub fn new() -> Collector {
+        Collector {
+            epoch: AtomicUint::new(0),
+            participants: AtomicPtr::new(0 as *mut Participant),
+            retired: AtomicUint::new(0),
+        }
+    }
+
+    /// Registers the current task as a participant of this collector.
+    pub fn register<'a>(&'a self) -> Handle<'a> {
+        let participant = self.acquire();
+        Handle {
+            collector: self,
+            participant: participant,
+            pins: Cell::new(0),
+            noshare: marker::NoShare,
+        }
+    }
+
+    fn acquire(&self) -> *mut Participant {
+        // Reuse an idle participant if there is one
+        let mut cur = self.participants.load(SeqCst);
+        while !cur.is_null() {
+            let p = unsafe { &*cur };
+            if !p.in_use.load(SeqCst) && !p.in_use.swap(true, SeqCst) {
+                return cur
+            }
+            cur = p.next;
+        }
+
+        // Otherwise push a new one on the front of the list. Participants are
+        // never unlinked, so this is the only modification of the list.
+        let new: *mut Participant = unsafe {
+            cast::transmute(box Participant {
+                state: AtomicUint::new(0),
+                in_use: AtomicBool::new(true),
+                garbage: Unsafe::new(Vec::new()),
+                next: 0 as *mut Participant,
+            })
+        };
+        loop {
+            let head = self.participants.load(SeqCst);
+            unsafe { (*new).next = head; }
+            if self.participants.compare_and_swap(head, new, SeqCst) == head {
+                return new
+            }
+        }
+    }
+
+    /// Advances the global epoch if every pinned participant has observed the
+    /// current one, returning whether the epoch was advanced.
+    fn try_advance(&self) -> bool {
+        let epoch = self.epoch.load(SeqCst);
+        let mut cur = self.participants.load(SeqCst);
+        while !cur.is_null() {
+            let p = unsafe { &*cur };
+            let state = p.state.load(SeqCst);
+            if state & PINNED != 0 && state >> 1 != epoch {
+                return false
+            }
+            cur = p.next;
+        }
+        self.epoch.compare_and_swap(epoch, epoch + 1, SeqCst) == epoch
+    }
+
+    /// Returns the number of retired objects which have not been freed yet.
+    #[cfg(test)]
+    pub fn garbage_len(&self) -> uint {
+        let mut len = 0;
+        let mut cur = self.participants.load(SeqCst);
+        while !cur.is_null() {
+            let p = unsafe { &*cur };
+            len += unsafe { (*p.garbage.get()).len() };
+            cur = p.next;
+        }
+        len
+    }
+}
+
+#[unsafe_destructor]
+impl Drop for Collector {
+    fn drop(&mut self) {
+        // The handles borrow the collector, so none of them can be alive and
+        // nothing can be reading the retired objects anymore.
+        let mut cur = self.participants.load(SeqCst);
+        while !cur.is_null() {
+            let p: Box<Participant> = unsafe { cast::transmute(cur) };
+            let Participant { garbage, next, .. } = *p;
+            cur = next;
+            for g in unsafe { garbage.unwrap() }.move_iter() {
+                unsafe { (g.free)(g.ptr) }
+            }
+        }
+    }
+}
+
+impl<'a> Handle<'a> {
+    /// Pins the current task, returning a guard which unpins it when dropped.
+    ///
+    /// Pins may be nested, in which case the task stays pinned until the
+    /// outermost guard is dropped.
+    pub fn pin<'b>(&'b self) -> Guard<'b> {
+        let pins = self.pins.get();
+        self.pins.set(pins + 1);
+        if pins == 0 {
+            let p = self.participant();
+            let epoch = self.collector.epoch.load(SeqCst);
+            p.state.store((epoch << 1) | PINNED, SeqCst);
+            self.collect(epoch);
+        }
+        Guard { handle: self }
+    }
+
+    fn participant<'b>(&'b self) -> &'b Participant {
+        unsafe { &*self.participant }
+    }
+
+    // Frees all of this participant's garbage which nobody can be reading at
+    // `epoch`.
+    fn collect(&self, epoch: uint) {
+        let garbage = unsafe { &mut *self.participant().garbage.get() };
+        let mut i = 0;
+        while i < garbage.len() {
+            if garbage.get(i).epoch + 2 <= epoch {
+                let g = garbage.swap_remove(i).unwrap();
+                unsafe { (g.free)(g.ptr) }
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn unpin(&self) {
+        let pins = self.pins.get() - 1;
+        self.pins.set(pins);
+        if pins == 0 {
+            self.participant().state.store(0, SeqCst);
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for Handle<'a> {
+    fn drop(&mut self) {
+        // Any garbage left behind is inherited by the next handle to use this
+        // participant, or freed with the collector.
+        let p = self.participant();
+        p.state.store(0, SeqCst);
+        p.in_use.store(false, SeqCst);
+    }
+}
+
+impl<'a> Guard<'a> {
+    /// Schedules `ptr`, which must have been created from a `Box<T>`, to be
+    /// freed once no pinned task can still be reading it.
+    ///
+    /// This function is unsafe because the object must have already been
+    /// unlinked from the shared structure, so that no task which pins itself
+    /// from now on can reach it, and it must not be retired twice.
+    pub unsafe fn retire<T: Send>(&self, ptr: *mut T) {
+        unsafe fn free<T>(ptr: *mut u8) {
+            let _: Box<T> = cast::transmute(ptr);
+        }
+        let handle = self.handle;
+        let garbage = &mut *handle.participant().garbage.get();
+        garbage.push(Garbage {
+            epoch: handle.collector.epoch.load(SeqCst),
+            ptr: ptr as *mut u8,
+            free: free::<T>,
+        });
+
+        let retired = handle.collector.retired.fetch_add(1, SeqCst) + 1;
+        if retired % ADVANCE_INTERVAL == 0 {
+            self.flush();
+        }
+    }
+
+    /// Attempts to advance the global epoch and free this task's garbage.
+    ///
+    /// This happens automatically every so often, so there is normally no
+    /// need to call this. Note that the epoch cannot advance more than once
+    /// while this task stays pinned, so objects retired under this guard are
+    /// never freed before it is dropped.
+    pub fn flush(&self) {
+        let handle = self.handle;
+        handle.collector.try_advance();
+        // Every pinned participant is at most one epoch behind the global
+        // epoch, so anything tagged two epochs back is unreachable.
+        handle.collect(handle.collector.epoch.load(SeqCst));
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for Guard<'a> {
+    fn drop(&mut self) {
+        self.handle.unpin();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomics::{AtomicBool, AtomicPtr, SeqCst};
+    use std::cast;
+    use std::ptr;
+
+    use arc::Arc;
+    use super::{Collector, Guard, ADVANCE_INTERVAL};
+
+    struct Canary(Arc<AtomicBool>);
+    impl Drop for Canary {
+        fn drop(&mut self) {
+            let Canary(ref freed) = *self;
+            assert!(!freed.swap(true, SeqCst));
+        }
+    }
+
+    fn canary() -> (*mut Canary, Arc<AtomicBool>) {
+        let freed = Arc::new(AtomicBool::new(false));
+        let ptr = unsafe { cast::transmute(box Canary(freed.clone())) };
+        (ptr, freed)
+    }
+
+    #[test]
+    fn retire_eventually_frees() {
+        let collector = Collector::new();
+        let (ptr, freed) = canary();
+        let handle = collector.register();
+        unsafe { handle.pin().retire(ptr) }
+        for _ in range(0, 2) {
+            assert!(!freed.load(SeqCst));
+            handle.pin().flush();
+        }
+        assert!(freed.load(SeqCst));
+    }
+
+    #[test]
+    fn pinned_task_blocks_reclamation() {
+        let collector = Collector::new();
+        let (ptr, freed) = canary();
+        let reader = collector.register();
+        let writer = collector.register();
+
+        let _pinned = reader.pin();
+        unsafe { writer.pin().retire(ptr) }
+        for _ in range(0, 10) {
+            writer.pin().flush();
+        }
+        assert!(!freed.load(SeqCst));
+
+        drop(_pinned);
+        for _ in range(0, 3) {
+            writer.pin().flush();
+        }
+        assert!(freed.load(SeqCst));
+    }
+
+    #[test]
+    fn nested_pins() {
+        let collector = Collector::new();
+        let reader = collector.register();
+        let writer = collector.register();
+        let (ptr, freed) = canary();
+
+        let outer = reader.pin();
+        drop(reader.pin());
+        unsafe { writer.pin().retire(ptr) }
+        for _ in range(0, 10) {
+            writer.pin().flush();
+        }
+        assert!(!freed.load(SeqCst));
+        drop(outer);
+    }
+
+    #[test]
+    fn collector_frees_leftovers() {
+        let (ptr, freed) = canary();
+        {
+            let collector = Collector::new();
+            let handle = collector.register();
+            unsafe { handle.pin().retire(ptr) }
+        }
+        assert!(freed.load(SeqCst));
+    }
+
+    #[test]
+    fn short_lived_handles_advance() {
+        // Structures register a handle for every operation, so retiring
+        // through fresh handles must still advance the epoch.
+        let collector = Collector::new();
+        let mut all = Vec::new();
+        for _ in range(0, 10 * ADVANCE_INTERVAL) {
+            let (ptr, freed) = canary();
+            let handle = collector.register();
+            unsafe { handle.pin().retire(ptr) }
+            all.push(freed);
+        }
+        assert!(collector.garbage_len() <= 2 * ADVANCE_INTERVAL);
+        assert!(all.iter().filter(|f| f.load(SeqCst)).count() >=
+                8 * ADVANCE_INTERVAL);
+    }
+
+    #[test]
+    fn participants_are_reused() {
+        let collector = Collector::new();
+        let a = collector.register().participant;
+        let b = collector.register().participant;
+        assert_eq!(a, b);
+        let h1 = collector.register();
+        let h2 = collector.register();
+        assert!(h1.participant != h2.participant);
+    }
+
+    // Runs every interleaving of two scripts, at the granularity of the
+    // collector's operations. Each operation is atomic with respect to the
+    // others, so this explores all of the schedules that two tasks could
+    // produce.
+    fn interleavings(a: uint, b: uint, f: |&[bool]|) {
+        fn go(a: uint, b: uint, cur: &mut Vec<bool>, f: &mut |&[bool]|) {
+            if a == 0 && b == 0 {
+                return (*f)(cur.as_slice())
+            }
+            if a > 0 {
+                cur.push(true);
+                go(a - 1, b, cur, f);
+                cur.pop();
+            }
+            if b > 0 {
+                cur.push(false);
+                go(a, b - 1, cur, f);
+                cur.pop();
+            }
+        }
+        let mut f = f;
+        go(a, b, &mut Vec::new(), &mut f);
+    }
+
+    #[test]
+    fn exhaustive_reader_writer() {
+        // The reader pins, loads the shared pointer, checks that the object
+        // is still alive and unpins. The writer unlinks the object and then
+        // repeatedly tries to reclaim it.
+        static READER: uint = 4;
+        static WRITER: uint = 9;
+        let mut schedules = 0;
+        interleavings(READER, WRITER, |schedule| {
+            schedules += 1;
+            let collector = Collector::new();
+            let (ptr, freed) = canary();
+            let shared = AtomicPtr::new(ptr);
+            let reader = collector.register();
+            let writer = collector.register();
+
+            let mut rguard: Option<Guard> = None;
+            let mut wguard: Option<Guard> = None;
+            let mut loaded = ptr::mut_null();
+            let (mut r, mut w) = (0, 0);
+            for &is_reader in schedule.iter() {
+                if is_reader {
+                    match r {
+                        0 => rguard = Some(reader.pin()),
+                        1 => loaded = shared.load(SeqCst),
+                        2 => if !loaded.is_null() {
+                            let &Canary(ref f) = unsafe { &*loaded };
+                            assert!(!f.load(SeqCst));
+                        },
+                        _ => rguard = None,
+                    }
+                    r += 1;
+                } else {
+                    match w {
+                        0 => wguard = Some(writer.pin()),
+                        1 => {
+                            let old = shared.swap(ptr::mut_null(), SeqCst);
+                            unsafe { wguard.get_ref().retire(old) }
+                        }
+                        n if n % 2 == 0 => wguard = None,
+                        _ => wguard = Some(writer.pin()),
+                    }
+                    if w >= 3 && w % 2 == 1 {
+                        wguard.get_ref().flush();
+                    }
+                    w += 1;
+                }
+            }
+            drop(wguard);
+            drop(rguard);
+
+            // Once nobody is pinned, reclamation must make progress.
+            for _ in range(0, 3) {
+                writer.pin().flush();
+            }
+            assert!(freed.load(SeqCst));
+        });
+        assert_eq!(schedules, 715);
+    }
+
+    #[test]
+    fn epoch_stays_within_one_of_pinned() {
+        let collector = Collector::new();
+        let a = collector.register();
+        let b = collector.register();
+        let _g = a.pin();
+        let pinned_at = collector.epoch.load(SeqCst);
+        for _ in range(0, 10) {
+            b.pin().flush();
+        }
+        assert!(collector.epoch.load(SeqCst) <= pinned_at + 1);
+    }
+}
@@ -26,11 +26,15 @@
 
 #[cfg(test)]
 #[phase(syntax, link)] extern crate log;
+#[cfg(test)] extern crate native;
 
 pub use comm::{DuplexStream, duplex};
 pub use task_pool::TaskPool;
 pub use future::Future;
 pub use arc::{Arc, Weak};
+pub use atomic_arc::AtomicArc;
+pub use queue::Queue;
+pub use stack::Stack;
 pub use lock::{Mutex, MutexGuard, Condvar, Barrier,
                RWLock, RWLockReadGuard, RWLockWriteGuard};
 
@@ -38,12 +42,16 @@ pub use lock::{Mutex, MutexGuard, Condvar, Barrier,
 pub use raw::{Semaphore, SemaphoreGuard};
 
 mod arc;
+mod atomic_arc;
 mod comm;
 mod future;
 mod lock;
 mod mpsc_intrusive;
+mod queue;
+mod stack;
 mod task_pool;
 
+pub mod epoch;
 pub mod raw;
 pub mod mutex;
 pub mod one;
@@ -0,0 +1,229 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A lock-free multi-producer, multi-consumer queue.
+//!
+//! This is the queue of Michael and Scott, with memory reclaimed through the
+//! `epoch` module instead of relying on a garbage collector.
+
+// http://www.cs.rochester.edu/research/synchronization/pseudocode/queues.html
+
+use std::cast;
+use std::sync::atomics::{AtomicPtr, SeqCst};
+
+use epoch::Collector;
+
+struct Node<T> {
+    value: Option<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T: Send> Node<T> {
+    fn new(value: Option<T>) -> *mut Node<T> {
+        unsafe {
+            cast::transmute(box Node {
+                value: value,
+                next: AtomicPtr::new(0 as *mut Node<T>),
+            })
+        }
+    }
+}
+
+/// A lock-free, unbounded, first-in first-out queue which may be shared by any
+/// number of producers and consumers.
+///
+/// # Example
+///
+/// ```rust
+/// use sync::{Arc, Queue};
+///
+/// let queue = Arc::new(Queue::new());
+/// for i in range(0, 10) {
+///     let queue = queue.clone();
+///     spawn(proc() queue.push(i));
+/// }
+/// ```
+pub struct Queue<T> {
+    // `head` always points at a dummy node, whose successor holds the first
+    // value in the queue.
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    collector: Collector,
+}
+
+impl<T: Send> Queue<T> {
+    /// Creates a new, empty queue.
+    pub fn new() -> Queue<T> {
+        let stub = Node::new(None);
+        Queue {
+            head: AtomicPtr::new(stub),
+            tail: AtomicPtr::new(stub),
+            collector: Collector::new(),
+        }
+    }
+
+    /// Pushes a value onto the back of the queue.
+    pub fn push(&self, t: T) {
+        let handle = self.collector.register();
+        let _guard = handle.pin();
+        let n = Node::new(Some(t));
+        unsafe {
+            loop {
+                let tail = self.tail.load(SeqCst);
+                let next = (*tail).next.load(SeqCst);
+                if tail != self.tail.load(SeqCst) { continue }
+                if next.is_null() {
+                    if (*tail).next.compare_and_swap(next, n, SeqCst) == next {
+                        // Failing to swing the tail is fine, someone else
+                        // already helped us.
+                        self.tail.compare_and_swap(tail, n, SeqCst);
+                        return
+                    }
+                } else {
+                    // The tail is lagging behind, help it along.
+                    self.tail.compare_and_swap(tail, next, SeqCst);
+                }
+            }
+        }
+    }
+
+    /// Pops a value from the front of the queue, returning `None` if the queue
+    /// is empty.
+    pub fn pop(&self) -> Option<T> {
+        let handle = self.collector.register();
+        let guard = handle.pin();
+        unsafe {
+            loop {
+                let head = self.head.load(SeqCst);
+                let tail = self.tail.load(SeqCst);
+                let next = (*head).next.load(SeqCst);
+                if head != self.head.load(SeqCst) { continue }
+                if next.is_null() {
+                    return None
+                }
+                if head == tail {
+                    self.tail.compare_and_swap(tail, next, SeqCst);
+                    continue
+                }
+                if self.head.compare_and_swap(head, next, SeqCst) == head {
+                    // `next` is the new dummy node. Only the winner of the
+                    // above exchange takes its value, nobody else touches it.
+                    let ret = (*next).value.take();
+                    guard.retire(head);
+                    return ret
+                }
+            }
+        }
+    }
+
+    /// Returns whether the queue was empty at the time of the call.
+    pub fn is_empty(&self) -> bool {
+        let handle = self.collector.register();
+        let _guard = handle.pin();
+        unsafe { (*self.head.load(SeqCst)).next.load(SeqCst).is_null() }
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for Queue<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head.load(SeqCst);
+        while !cur.is_null() {
+            unsafe {
+                let next = (*cur).next.load(SeqCst);
+                let _: Box<Node<T>> = cast::transmute(cur);
+                cur = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use native;
+
+    use arc::Arc;
+    use super::Queue;
+
+    #[test]
+    fn smoke() {
+        let q = Queue::new();
+        assert!(q.is_empty());
+        assert_eq!(q.pop(), None);
+        q.push(1);
+        q.push(2);
+        assert!(!q.is_empty());
+        assert_eq!(q.pop(), Some(1));
+        assert_eq!(q.pop(), Some(2));
+        assert_eq!(q.pop(), None);
+    }
+
+    #[test]
+    fn drop_full() {
+        let q = Queue::new();
+        q.push(box 1);
+        q.push(box 2);
+    }
+
+    #[test]
+    fn popped_nodes_are_freed() {
+        static AMT: uint = 1000;
+        let q = Queue::new();
+        for i in range(0, AMT) {
+            q.push(i);
+            assert_eq!(q.pop(), Some(i));
+        }
+        // Every pop retires a node, only the most recent ones may be left
+        assert!(q.collector.garbage_len() < AMT / 10);
+    }
+
+    #[test]
+    fn stress() {
+        static PRODUCERS: uint = 4;
+        static CONSUMERS: uint = 4;
+        static AMT: uint = 10000;
+        let q = Arc::new(Queue::new());
+        let (tx, rx) = channel();
+
+        for i in range(0, PRODUCERS) {
+            let q = q.clone();
+            native::task::spawn(proc() {
+                for j in range(0, AMT) {
+                    q.push((i, j));
+                }
+            });
+        }
+        for _ in range(0, CONSUMERS) {
+            let q = q.clone();
+            let tx = tx.clone();
+            native::task::spawn(proc() {
+                let mut seen = Vec::from_elem(PRODUCERS, 0u);
+                let mut got = 0;
+                while got < AMT * PRODUCERS / CONSUMERS {
+                    match q.pop() {
+                        Some((i, j)) => {
+                            // values from one producer come out in order
+                            assert!(j >= *seen.get(i));
+                            *seen.get_mut(i) = j;
+                            got += 1;
+                        }
+                        None => {}
+                    }
+                }
+                tx.send(());
+            });
+        }
+        drop(tx);
+        for _ in range(0, CONSUMERS) {
+            rx.recv();
+        }
+        assert_eq!(q.pop(), None);
+    }
+}
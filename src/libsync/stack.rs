@@ -0,0 +1,170 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A lock-free stack.
+//!
+//! This is Treiber's stack. Popped nodes are reclaimed through the `epoch`
+//! module, which also rules out the ABA problem: a node's memory cannot be
+//! reused while a task which might compare against it is still pinned.
+
+use std::cast;
+use std::sync::atomics::{AtomicPtr, SeqCst};
+
+use epoch::Collector;
+
+struct Node<T> {
+    value: Option<T>,
+    next: *mut Node<T>,
+}
+
+/// A lock-free, last-in first-out stack which may be shared by any number of
+/// tasks.
+///
+/// # Example
+///
+/// ```rust
+/// use sync::Stack;
+///
+/// let stack = Stack::new();
+/// stack.push(1);
+/// stack.push(2);
+/// assert_eq!(stack.pop(), Some(2));
+/// ```
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    collector: Collector,
+}
+
+impl<T: Send> Stack<T> {
+    /// Creates a new, empty stack.
+    pub fn new() -> Stack<T> {
+        Stack {
+            head: AtomicPtr::new(0 as *mut Node<T>),
+            collector: Collector::new(),
+        }
+    }
+
+    /// Pushes a value onto the top of the stack.
+    pub fn push(&self, t: T) {
+        let n: *mut Node<T> = unsafe {
+            cast::transmute(box Node { value: Some(t), next: 0 as *mut Node<T> })
+        };
+        loop {
+            let head = self.head.load(SeqCst);
+            unsafe { (*n).next = head; }
+            if self.head.compare_and_swap(head, n, SeqCst) == head {
+                return
+            }
+        }
+    }
+
+    /// Pops the value from the top of the stack, returning `None` if the stack
+    /// is empty.
+    pub fn pop(&self) -> Option<T> {
+        let handle = self.collector.register();
+        let guard = handle.pin();
+        loop {
+            let head = self.head.load(SeqCst);
+            if head.is_null() {
+                return None
+            }
+            unsafe {
+                let next = (*head).next;
+                if self.head.compare_and_swap(head, next, SeqCst) == head {
+                    let ret = (*head).value.take();
+                    guard.retire(head);
+                    return ret
+                }
+            }
+        }
+    }
+
+    /// Returns whether the stack was empty at the time of the call.
+    pub fn is_empty(&self) -> bool {
+        self.head.load(SeqCst).is_null()
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Send> Drop for Stack<T> {
+    fn drop(&mut self) {
+        let mut cur = self.head.load(SeqCst);
+        while !cur.is_null() {
+            let node: Box<Node<T>> = unsafe { cast::transmute(cur) };
+            cur = node.next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use native;
+
+    use arc::Arc;
+    use super::Stack;
+
+    #[test]
+    fn smoke() {
+        let s = Stack::new();
+        assert!(s.is_empty());
+        assert_eq!(s.pop(), None);
+        s.push(1);
+        s.push(2);
+        assert!(!s.is_empty());
+        assert_eq!(s.pop(), Some(2));
+        assert_eq!(s.pop(), Some(1));
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn drop_full() {
+        let s = Stack::new();
+        s.push(box 1);
+        s.push(box 2);
+    }
+
+    #[test]
+    fn popped_nodes_are_freed() {
+        static AMT: uint = 1000;
+        let s = Stack::new();
+        for i in range(0, AMT) {
+            s.push(i);
+            assert_eq!(s.pop(), Some(i));
+        }
+        // Every pop retires a node, only the most recent ones may be left
+        assert!(s.collector.garbage_len() < AMT / 10);
+    }
+
+    #[test]
+    fn stress() {
+        static TASKS: uint = 8;
+        static AMT: uint = 10000;
+        let s = Arc::new(Stack::new());
+        let (tx, rx) = channel();
+
+        for _ in range(0, TASKS) {
+            let s = s.clone();
+            let tx = tx.clone();
+            native::task::spawn(proc() {
+                let mut sum = 0;
+                for i in range(0, AMT) {
+                    s.push(i);
+                    // there is at least one value on the stack, ours
+                    sum += s.pop().unwrap();
+                }
+                tx.send(sum);
+            });
+        }
+        drop(tx);
+        let total = rx.iter().fold(0, |a, b| a + b);
+        assert_eq!(total, TASKS * AMT * (AMT - 1) / 2);
+        assert!(s.is_empty());
+    }
+}
@@ -18,6 +18,8 @@ use ast::{MetaItem, Item, Expr, MutMutable, Ident};
 use codemap::Span;
 use ext::base::ExtCtxt;
 use ext::build::AstBuilder;
+use ext::deriving::encodable::{DefaultRepr, ExternalRepr, InternalRepr, UntaggedRepr,
+                               FieldAttrs, SerializeAttrs, serialize_attrs};
 use ext::deriving::generic::*;
 use parse::token::InternedString;
 use parse::token;
@@ -27,6 +29,7 @@ pub fn expand_deriving_decodable(cx: &mut ExtCtxt,
                                  mitem: @MetaItem,
                                  item: @Item,
                                  push: |@Item|) {
+    let attrs = serialize_attrs(cx, item);
     let trait_def = TraitDef {
         span: span,
         attributes: Vec::new(),
@@ -54,7 +57,7 @@ pub fn expand_deriving_decodable(cx: &mut ExtCtxt,
                 attributes: Vec::new(),
                 const_nonmatching: true,
                 combine_substructure: combine_substructure(|a, b, c| {
-                    decodable_substructure(a, b, c)
+                    decodable_substructure(a, b, c, &attrs)
                 }),
             })
     };
@@ -63,7 +66,7 @@ pub fn expand_deriving_decodable(cx: &mut ExtCtxt,
 }
 
 fn decodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
-                          substr: &Substructure) -> @Expr {
+                          substr: &Substructure, attrs: &SerializeAttrs) -> @Expr {
     let decoder = substr.nonself_args[0];
     let recurse = vec!(cx.ident_of("serialize"),
                     cx.ident_of("Decodable"),
@@ -76,34 +79,43 @@ fn decodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
 
     return match *substr.fields {
         StaticStruct(_, ref summary) => {
-            let nfields = match *summary {
-                Unnamed(ref fields) => fields.len(),
-                Named(ref fields) => fields.len()
-            };
-            let read_struct_field = cx.ident_of("read_struct_field");
-
-            let result = decode_static_fields(cx,
-                                              trait_span,
-                                              substr.type_ident,
-                                              summary,
-                                              |cx, span, name, field| {
-                cx.expr_try(span,
-                    cx.expr_method_call(span, blkdecoder, read_struct_field,
-                                        vec!(cx.expr_str(span, name),
-                                          cx.expr_uint(span, field),
-                                          lambdadecode)))
-            });
+            let attrs = &attrs.fields;
+            let result = decode_struct_fields(cx, trait_span, substr.type_ident,
+                                              summary, attrs.as_slice(), 0);
             let result = cx.expr_ok(trait_span, result);
             cx.expr_method_call(trait_span,
                                 decoder,
                                 cx.ident_of("read_struct"),
                                 vec!(
                 cx.expr_str(trait_span, token::get_ident(substr.type_ident)),
-                cx.expr_uint(trait_span, nfields),
+                cx.expr_uint(trait_span, count_fields(attrs.as_slice())),
                 cx.lambda_expr_1(trait_span, result, blkarg)
             ))
         }
         StaticEnum(_, ref fields) => {
+            let repr = &attrs.repr;
+            let names = &attrs.variant_names;
+            let attrs = &attrs.variant_fields;
+
+            match *repr {
+                DefaultRepr => {}
+                ExternalRepr => {
+                    return decode_external(cx, trait_span, substr.type_ident, decoder,
+                                           names.as_slice(), fields.as_slice(),
+                                           attrs.as_slice())
+                }
+                InternalRepr(ref key) => {
+                    return decode_internal(cx, trait_span, substr.type_ident, decoder,
+                                           key.clone(), names.as_slice(),
+                                           fields.as_slice(), attrs.as_slice())
+                }
+                UntaggedRepr => {
+                    return decode_untagged(cx, trait_span, substr.type_ident, decoder,
+                                           names.as_slice(), fields.as_slice(),
+                                           attrs.as_slice())
+                }
+            }
+
             let variant = cx.ident_of("i");
 
             let mut arms = Vec::new();
@@ -111,7 +123,7 @@ fn decodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
             let rvariant_arg = cx.ident_of("read_enum_variant_arg");
 
             for (i, &(name, v_span, ref parts)) in fields.iter().enumerate() {
-                variants.push(cx.expr_str(v_span, token::get_ident(name)));
+                variants.push(cx.expr_str(v_span, names.get(i).clone()));
 
                 let decoded = decode_static_fields(cx,
                                                    v_span,
@@ -151,6 +163,330 @@ fn decodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
     };
 }
 
+/// The number of fields which are not skipped.
+fn count_fields(attrs: &[FieldAttrs]) -> uint {
+    attrs.iter().count(|a| !a.skip)
+}
+
+/// Create a decoder for the fields of a struct or struct-like variant, read
+/// with `read_struct_field` according to their attributes. The fields which
+/// aren't skipped are numbered from `first`.
+fn decode_struct_fields(cx: &mut ExtCtxt,
+                        trait_span: Span,
+                        outer_pat_ident: Ident,
+                        fields: &StaticFields,
+                        attrs: &[FieldAttrs],
+                        first: uint)
+                        -> @Expr {
+    let blkarg = cx.ident_of("_d");
+    let blkdecoder = cx.expr_ident(trait_span, blkarg);
+    let recurse = vec!(cx.ident_of("serialize"),
+                    cx.ident_of("Decodable"),
+                    cx.ident_of("decode"));
+    let calldecode = cx.expr_call_global(trait_span, recurse, vec!(blkdecoder));
+    let lambdadecode = cx.lambda_expr_1(trait_span, calldecode, blkarg);
+
+    let mut indices = Vec::new();
+    let mut next = first;
+    for a in attrs.iter() {
+        indices.push(next);
+        if !a.skip { next += 1 }
+    }
+
+    decode_static_fields(cx, trait_span, outer_pat_ident, fields, |cx, span, name, field| {
+        let attrs = &attrs[field];
+        let name = match attrs.rename {
+            Some(ref s) => s.clone(),
+            None => name,
+        };
+        if attrs.skip {
+            // The field may still be present in the input, so the decoder is
+            // told to pass over it.
+            let skip = cx.expr_method_call(span, blkdecoder,
+                                           cx.ident_of("skip_struct_field"),
+                                           vec!(cx.expr_str(span, name)));
+            let default = vec!(cx.ident_of("std"),
+                               cx.ident_of("default"),
+                               cx.ident_of("Default"),
+                               cx.ident_of("default"));
+            let default = cx.expr_call_global(span, default, Vec::new());
+            return cx.expr_block(cx.block(span,
+                                          vec!(cx.stmt_expr(cx.expr_try(span, skip))),
+                                          Some(default)))
+        }
+        let args = vec!(cx.expr_str(span, name),
+                        cx.expr_uint(span, *indices.get(field)),
+                        lambdadecode);
+        if attrs.default || attrs.optional {
+            let call = cx.expr_try(span,
+                cx.expr_method_call(span, blkdecoder,
+                                    cx.ident_of("read_struct_field_opt"), args));
+            if attrs.default {
+                cx.expr_method_call(span, call, cx.ident_of("unwrap_or_default"),
+                                    Vec::new())
+            } else {
+                cx.expr_method_call(span, call, cx.ident_of("unwrap_or"),
+                                    vec!(cx.expr_none(span)))
+            }
+        } else {
+            cx.expr_try(span,
+                cx.expr_method_call(span, blkdecoder,
+                                    cx.ident_of("read_struct_field"), args))
+        }
+    })
+}
+
+/// Create a decoder for the contents of a variant in the `external` and
+/// `untagged` representations, returning a `Result`.
+fn decode_variant(cx: &mut ExtCtxt,
+                  trait_span: Span,
+                  name: Ident,
+                  renamed: InternedString,
+                  parts: &StaticFields,
+                  attrs: &[FieldAttrs])
+                  -> @Expr {
+    let blkarg = cx.ident_of("_d");
+    let blkdecoder = cx.expr_ident(trait_span, blkarg);
+    let recurse = vec!(cx.ident_of("serialize"),
+                    cx.ident_of("Decodable"),
+                    cx.ident_of("decode"));
+    let calldecode = cx.expr_call_global(trait_span, recurse, vec!(blkdecoder));
+    let lambdadecode = cx.lambda_expr_1(trait_span, calldecode, blkarg);
+
+    match *parts {
+        Named(..) => {
+            let result = decode_struct_fields(cx, trait_span, name, parts, attrs, 0);
+            let result = cx.expr_ok(trait_span, result);
+            cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("read_struct"),
+                                vec!(cx.expr_str(trait_span, renamed),
+                                  cx.expr_uint(trait_span, count_fields(attrs)),
+                                  cx.lambda_expr_1(trait_span, result, blkarg)))
+        }
+        Unnamed(ref spans) if spans.len() == 0 => {
+            let read_nil = cx.expr_method_call(trait_span, blkdecoder,
+                                               cx.ident_of("read_nil"), Vec::new());
+            let stmt = cx.stmt_expr(cx.expr_try(trait_span, read_nil));
+            let result = cx.expr_ok(trait_span, cx.expr_ident(trait_span, name));
+            cx.expr_block(cx.block(trait_span, vec!(stmt), Some(result)))
+        }
+        Unnamed(ref spans) if spans.len() == 1 => {
+            let decoded = decode_static_fields(cx, trait_span, name, parts, |cx, span, _, _| {
+                cx.expr_try(span, calldecode)
+            });
+            cx.expr_ok(trait_span, decoded)
+        }
+        Unnamed(..) => {
+            let rseq_elt = cx.ident_of("read_seq_elt");
+            let decoded = decode_static_fields(cx, trait_span, name, parts,
+                                               |cx, span, _, field| {
+                cx.expr_try(span,
+                    cx.expr_method_call(span, blkdecoder, rseq_elt,
+                                        vec!(cx.expr_uint(span, field), lambdadecode)))
+            });
+            let result = cx.expr_ok(trait_span, decoded);
+            let len = cx.ident_of("_len");
+            let lambda = cx.lambda_expr(trait_span, vec!(blkarg, len), result);
+            cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("read_seq"),
+                                vec!(lambda))
+        }
+    }
+}
+
+/// Create an arm returning an error for any variant name not matched by the
+/// other arms of a `match` on `tag`.
+fn unknown_variant_arm(cx: &mut ExtCtxt, span: Span, tag: @Expr) -> ast::Arm {
+    let blkdecoder = cx.expr_ident(span, cx.ident_of("_d"));
+    let prefix = cx.expr_method_call(span,
+                                     cx.expr_str(span,
+                                                 InternedString::new("unknown variant ")),
+                                     cx.ident_of("to_owned"), Vec::new());
+    let msg = cx.expr_binary(span, ast::BiAdd, prefix, tag);
+    let msg = cx.expr_method_call(span, msg, cx.ident_of("as_slice"), Vec::new());
+    let err = cx.expr_method_call(span, blkdecoder, cx.ident_of("error"), vec!(msg));
+    cx.arm(span, vec!(cx.pat_wild(span)), cx.expr_err(span, err))
+}
+
+/// Decodes an enum in the `external` representation: unit variants from
+/// their name, and other variants from a map with a single entry.
+fn decode_external(cx: &mut ExtCtxt,
+                   trait_span: Span,
+                   type_ident: Ident,
+                   decoder: @Expr,
+                   names: &[InternedString],
+                   fields: &[(Ident, Span, StaticFields)],
+                   attrs: &[Vec<FieldAttrs>])
+                   -> @Expr {
+    let blkarg = cx.ident_of("_d");
+    let blkdecoder = cx.expr_ident(trait_span, blkarg);
+    let tag = cx.ident_of("__tag");
+    let tag_slice = cx.expr_method_call(trait_span, cx.expr_ident(trait_span, tag),
+                                        cx.ident_of("as_slice"), Vec::new());
+
+    let mut unit_arms = Vec::new();
+    let mut arms = Vec::new();
+    for (i, &(name, v_span, ref parts)) in fields.iter().enumerate() {
+        let pat = cx.pat_lit(v_span, cx.expr_str(v_span, names[i].clone()));
+        match *parts {
+            Unnamed(ref spans) if spans.len() == 0 => {
+                let result = cx.expr_ok(v_span, cx.expr_ident(v_span, name));
+                unit_arms.push(cx.arm(v_span, vec!(pat), result));
+            }
+            _ => {
+                let result = decode_variant(cx, v_span, name, names[i].clone(), parts,
+                                            attrs[i].as_slice());
+                arms.push(cx.arm(v_span, vec!(pat), result));
+            }
+        }
+    }
+    unit_arms.push(unknown_variant_arm(cx, trait_span, tag_slice));
+    arms.push(unknown_variant_arm(cx, trait_span, tag_slice));
+
+    // { let __tag = try!(_d.read_map_elt_key(0, |_d| _d.read_str()));
+    //   _d.read_map_elt_val(0, |_d| match __tag.as_slice() { ... }) }
+    let read_str = cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("read_str"),
+                                       Vec::new());
+    let read_key = cx.expr_method_call(trait_span, blkdecoder,
+                                       cx.ident_of("read_map_elt_key"),
+                                       vec!(cx.expr_uint(trait_span, 0),
+                                         cx.lambda_expr_1(trait_span, read_str, blkarg)));
+    let let_tag = cx.stmt_let(trait_span, false, tag, cx.expr_try(trait_span, read_key));
+    let body = cx.expr_match(trait_span, tag_slice, arms);
+    let read_val = cx.expr_method_call(trait_span, blkdecoder,
+                                       cx.ident_of("read_map_elt_val"),
+                                       vec!(cx.expr_uint(trait_span, 0),
+                                         cx.lambda_expr_1(trait_span, body, blkarg)));
+    let blk = cx.expr_block(cx.block(trait_span, vec!(let_tag), Some(read_val)));
+    let len = cx.ident_of("_len");
+    let read_map = cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("read_map"),
+                                       vec!(cx.lambda_expr(trait_span, vec!(blkarg, len),
+                                                           blk)));
+
+    // match _d.try_read(|_d| _d.read_str()) {
+    //     Ok(__tag) => match __tag.as_slice() { ... },
+    //     Err(_) => _d.read_map(|_d, _len| ...),
+    // }
+    let try_read = cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("try_read"),
+                                       vec!(cx.lambda_expr_1(trait_span, read_str, blkarg)));
+    let ok_arm = cx.arm(trait_span,
+                        vec!(cx.pat_enum(trait_span,
+                                         cx.path_ident(trait_span, cx.ident_of("Ok")),
+                                         vec!(cx.pat_ident(trait_span, tag)))),
+                        cx.expr_match(trait_span, tag_slice, unit_arms));
+    let err_arm = cx.arm(trait_span,
+                         vec!(cx.pat_enum(trait_span,
+                                          cx.path_ident(trait_span, cx.ident_of("Err")),
+                                          vec!(cx.pat_wild(trait_span)))),
+                         read_map);
+    let result = cx.expr_match(trait_span, try_read, vec!(ok_arm, err_arm));
+    cx.expr_method_call(trait_span,
+                        decoder,
+                        cx.ident_of("read_enum"),
+                        vec!(
+        cx.expr_str(trait_span, token::get_ident(type_ident)),
+        cx.lambda_expr_1(trait_span, result, blkarg)
+    ))
+}
+
+/// Decodes an enum in the `tag = "key"` representation, from a struct whose
+/// field `key` names the variant.
+fn decode_internal(cx: &mut ExtCtxt,
+                   trait_span: Span,
+                   type_ident: Ident,
+                   decoder: @Expr,
+                   key: InternedString,
+                   names: &[InternedString],
+                   fields: &[(Ident, Span, StaticFields)],
+                   attrs: &[Vec<FieldAttrs>])
+                   -> @Expr {
+    let blkarg = cx.ident_of("_d");
+    let blkdecoder = cx.expr_ident(trait_span, blkarg);
+    let tag = cx.ident_of("__tag");
+    let tag_slice = cx.expr_method_call(trait_span, cx.expr_ident(trait_span, tag),
+                                        cx.ident_of("as_slice"), Vec::new());
+
+    let mut arms = Vec::new();
+    for (i, &(name, v_span, ref parts)) in fields.iter().enumerate() {
+        // tuple-like variants with fields were reported by `serialize_attrs`
+        let decoded = decode_struct_fields(cx, v_span, name, parts,
+                                           attrs[i].as_slice(), 1);
+        arms.push(cx.arm(v_span,
+                         vec!(cx.pat_lit(v_span, cx.expr_str(v_span, names[i].clone()))),
+                         cx.expr_ok(v_span, decoded)));
+    }
+    arms.push(unknown_variant_arm(cx, trait_span, tag_slice));
+
+    // let __tag = try!(_d.read_struct_field("key", 0, |_d| _d.read_str()));
+    let read_str = cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("read_str"),
+                                       Vec::new());
+    let read_tag = cx.expr_method_call(trait_span, blkdecoder,
+                                       cx.ident_of("read_struct_field"),
+                                       vec!(cx.expr_str(trait_span, key),
+                                         cx.expr_uint(trait_span, 0),
+                                         cx.lambda_expr_1(trait_span, read_str, blkarg)));
+    let let_tag = cx.stmt_let(trait_span, false, tag, cx.expr_try(trait_span, read_tag));
+    let body = cx.expr_match(trait_span, tag_slice, arms);
+    let blk = cx.expr_block(cx.block(trait_span, vec!(let_tag), Some(body)));
+    let len = attrs.iter().map(|a| count_fields(a.as_slice())).max().unwrap_or(0);
+    cx.expr_method_call(trait_span,
+                        decoder,
+                        cx.ident_of("read_struct"),
+                        vec!(
+        cx.expr_str(trait_span, token::get_ident(type_ident)),
+        cx.expr_uint(trait_span, len + 1),
+        cx.lambda_expr_1(trait_span, blk, blkarg)
+    ))
+}
+
+/// Decodes an enum in the `untagged` representation, trying each variant in
+/// turn.
+fn decode_untagged(cx: &mut ExtCtxt,
+                   trait_span: Span,
+                   type_ident: Ident,
+                   decoder: @Expr,
+                   names: &[InternedString],
+                   fields: &[(Ident, Span, StaticFields)],
+                   attrs: &[Vec<FieldAttrs>])
+                   -> @Expr {
+    let blkarg = cx.ident_of("_d");
+    let blkdecoder = cx.expr_ident(trait_span, blkarg);
+    let value = cx.ident_of("__value");
+
+    // match _d.try_read(|_d| ...) { Ok(__value) => return Ok(__value), Err(_) => {} }
+    let mut stmts = Vec::new();
+    for (i, &(name, v_span, ref parts)) in fields.iter().enumerate() {
+        let decoded = decode_variant(cx, v_span, name, names[i].clone(), parts,
+                                     attrs[i].as_slice());
+        let try_read = cx.expr_method_call(v_span, blkdecoder, cx.ident_of("try_read"),
+                                           vec!(cx.lambda_expr_1(v_span, decoded, blkarg)));
+        let ret = cx.expr(v_span, ast::ExprRet(Some(
+            cx.expr_ok(v_span, cx.expr_ident(v_span, value)))));
+        let ok_arm = cx.arm(v_span,
+                            vec!(cx.pat_enum(v_span,
+                                             cx.path_ident(v_span, cx.ident_of("Ok")),
+                                             vec!(cx.pat_ident(v_span, value)))),
+                            ret);
+        let err_arm = cx.arm(v_span,
+                             vec!(cx.pat_enum(v_span,
+                                              cx.path_ident(v_span, cx.ident_of("Err")),
+                                              vec!(cx.pat_wild(v_span)))),
+                             cx.expr_block(cx.block(v_span, Vec::new(), None)));
+        stmts.push(cx.stmt_expr(cx.expr_match(v_span, try_read, vec!(ok_arm, err_arm))));
+    }
+
+    let msg = format!("data matched no variant of `{}`", token::get_ident(type_ident));
+    let msg = cx.expr_str(trait_span, token::intern_and_get_ident(msg));
+    let err = cx.expr_method_call(trait_span, blkdecoder, cx.ident_of("error"), vec!(msg));
+    let result = cx.expr_err(trait_span, err);
+    let blk = cx.expr_block(cx.block(trait_span, stmts, Some(result)));
+    cx.expr_method_call(trait_span,
+                        decoder,
+                        cx.ident_of("read_enum"),
+                        vec!(
+        cx.expr_str(trait_span, token::get_ident(type_ident)),
+        cx.lambda_expr_1(trait_span, blk, blkarg)
+    ))
+}
+
 /// Create a decoder for a single enum variant/struct:
 /// - `outer_pat_ident` is the name of this enum variant/struct
 /// - `getarg` should retrieve the `uint`-th field with name `@str`.
@@ -80,21 +80,240 @@ would yield functions like:
         }
     }
 ```
+
+The generated code can be adjusted with `#[serialize(...)]` attributes. On
+fields (including those of struct-like enum variants):
+
+* `rename = "name"` uses `name` instead of the field's name.
+* `skip` neither encodes nor decodes the field, which is decoded as
+  `Default::default()` instead.
+* `default` decodes the field as `Default::default()` if it is missing.
+* `optional`, for fields of type `Option`, decodes the field as `None` if it
+  is missing.
+
+Missing fields can only be detected by self-describing formats such as JSON;
+other formats always read the field.
+
+Enum variants may be renamed with `rename = "name"` too. By default enums are
+encoded with `emit_enum`, which leaves their representation to the encoder.
+Instead, one of these attributes on the enum encodes them using other
+primitives, which is useful to match an existing schema:
+
+* `external` encodes unit variants as their name, and other variants as a
+  map with a single entry, from the name of the variant to its contents: a
+  single field, a sequence of fields, or a struct for struct-like variants.
+  In JSON, `"Unit"`, `{"Tuple":[1,2]}` and `{"Struct":{"x":1}}`.
+* `tag = "key"` encodes unit and struct-like variants as structs, with an
+  additional field `key` holding the name of the variant. Tuple variants are
+  not supported. In JSON, `{"key":"Struct","x":1}`.
+* `untagged` encodes only the contents of the variants, and decodes the first
+  variant which can be decoded from the data. In JSON, `null`, `[1,2]` and
+  `{"x":1}`.
+
+For example:
+
+```ignore
+#[deriving(Encodable, Decodable)]
+#[serialize(tag = "type")]
+enum Shape {
+    Circle { radius: f64 },
+    #[serialize(rename = "rectangle")]
+    Rect {
+        #[serialize(rename = "topLeft")] top_left: Point,
+        #[serialize(optional)] size: Option<Size>,
+    },
+}
+```
+
 */
 
 use ast;
 use ast::{MetaItem, Item, Expr, ExprRet, MutMutable, LitNil};
+use attr::AttrMetaMethods;
 use codemap::Span;
 use ext::base::ExtCtxt;
 use ext::build::AstBuilder;
 use ext::deriving::generic::*;
+use parse::token::InternedString;
 use parse::token;
 
+/// How an enum is encoded, as chosen by the attributes on it.
+pub enum EnumRepr {
+    /// With `emit_enum`.
+    DefaultRepr,
+    /// The name of the variant, mapped to its contents.
+    ExternalRepr,
+    /// A struct with a field holding the name of the variant.
+    InternalRepr(InternedString),
+    /// Only the contents of the variant.
+    UntaggedRepr,
+}
+
+/// The attributes on a field.
+pub struct FieldAttrs {
+    pub rename: Option<InternedString>,
+    pub skip: bool,
+    pub default: bool,
+    pub optional: bool,
+}
+
+impl FieldAttrs {
+    fn is_empty(&self) -> bool {
+        self.rename.is_none() && !self.skip && !self.default && !self.optional
+    }
+}
+
+// The items of all the `#[serialize(...)]` attributes in `attrs`.
+fn serialize_items(attrs: &[ast::Attribute]) -> Vec<@MetaItem> {
+    let mut items = Vec::new();
+    for attr in attrs.iter().filter(|at| at.name().equiv(&("serialize"))) {
+        match attr.meta_item_list() {
+            Some(list) => items.push_all(list),
+            None => {}
+        }
+    }
+    items
+}
+
+fn unknown_attr(cx: &mut ExtCtxt, mi: @MetaItem, what: &str) {
+    cx.span_err(mi.span, format!("unknown `serialize` attribute `{}` on {}",
+                                 mi.name(), what).as_slice());
+}
+
+/// Reads the representation of an enum from its attributes.
+fn enum_repr(cx: &mut ExtCtxt, item: &Item) -> EnumRepr {
+    let mut repr = DefaultRepr;
+    for &mi in serialize_items(item.attrs.as_slice()).iter() {
+        let name = mi.name();
+        match (name.get(), mi.value_str()) {
+            ("external", None) => repr = ExternalRepr,
+            ("untagged", None) => repr = UntaggedRepr,
+            ("tag", Some(key)) => repr = InternalRepr(key),
+            _ => unknown_attr(cx, mi, "an enum"),
+        }
+    }
+    repr
+}
+
+/// Reads the attributes of each of the fields in `def`.
+fn field_attrs(cx: &mut ExtCtxt, def: &ast::StructDef) -> Vec<FieldAttrs> {
+    def.fields.iter().map(|field| {
+        let mut attrs = FieldAttrs {
+            rename: None,
+            skip: false,
+            default: false,
+            optional: false,
+        };
+        for &mi in serialize_items(field.node.attrs.as_slice()).iter() {
+            let name = mi.name();
+            match (name.get(), mi.value_str()) {
+                ("rename", Some(s)) => attrs.rename = Some(s),
+                ("skip", None) => attrs.skip = true,
+                ("default", None) => attrs.default = true,
+                ("optional", None) => attrs.optional = true,
+                _ => unknown_attr(cx, mi, "a field"),
+            }
+        }
+        attrs
+    }).collect()
+}
+
+/// Reads the attributes of the fields of `variant`, none for tuple-like
+/// variants.
+fn variant_field_attrs(cx: &mut ExtCtxt, variant: &ast::Variant)
+                       -> Vec<FieldAttrs> {
+    match variant.node.kind {
+        ast::StructVariantKind(def) => field_attrs(cx, &*def),
+        ast::TupleVariantKind(ref args) => {
+            args.iter().map(|_| {
+                FieldAttrs { rename: None, skip: false, default: false, optional: false }
+            }).collect()
+        }
+    }
+}
+
+/// The name `variant` is encoded with.
+fn variant_name(cx: &mut ExtCtxt, variant: &ast::Variant) -> InternedString {
+    let mut name = token::get_ident(variant.node.name);
+    for &mi in serialize_items(variant.node.attrs.as_slice()).iter() {
+        let attr = mi.name();
+        match (attr.get(), mi.value_str()) {
+            ("rename", Some(s)) => name = s,
+            _ => unknown_attr(cx, mi, "a variant"),
+        }
+    }
+    name
+}
+
+/// The `serialize` attributes of an item, read and checked once for all the
+/// methods being derived.
+pub struct SerializeAttrs {
+    pub repr: EnumRepr,
+    /// The attributes of the fields of a struct.
+    pub fields: Vec<FieldAttrs>,
+    /// The names of the variants of an enum.
+    pub variant_names: Vec<InternedString>,
+    /// The attributes of the fields of each variant of an enum.
+    pub variant_fields: Vec<Vec<FieldAttrs>>,
+}
+
+/// Reads the attributes of `item`, reporting any which are unknown or which
+/// don't fit the representation of the enum.
+pub fn serialize_attrs(cx: &mut ExtCtxt, item: &Item) -> SerializeAttrs {
+    let mut attrs = SerializeAttrs {
+        repr: DefaultRepr,
+        fields: Vec::new(),
+        variant_names: Vec::new(),
+        variant_fields: Vec::new(),
+    };
+    match item.node {
+        ast::ItemStruct(def, _) => attrs.fields = field_attrs(cx, &*def),
+        ast::ItemEnum(ref def, _) => {
+            attrs.repr = enum_repr(cx, item);
+            for variant in def.variants.iter() {
+                let fields = variant_field_attrs(cx, &**variant);
+                match attrs.repr {
+                    DefaultRepr if !fields.iter().all(|a| a.is_empty()) => {
+                        cx.span_err(variant.span,
+                                    "`serialize` attributes on the fields of a \
+                                     variant need `external`, `tag` or \
+                                     `untagged` on the enum");
+                    }
+                    InternalRepr(..) => match variant.node.kind {
+                        ast::TupleVariantKind(ref args) if args.len() > 0 => {
+                            cx.span_err(variant.span,
+                                        "enums with a `tag` can't have \
+                                         tuple-like variants");
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+                attrs.variant_names.push(variant_name(cx, &**variant));
+                attrs.variant_fields.push(fields);
+            }
+        }
+        _ => {}
+    }
+    attrs
+}
+
+/// The name a field is encoded with.
+pub fn field_name(name: Option<ast::Ident>, i: uint, attrs: &FieldAttrs)
+                  -> InternedString {
+    match (&attrs.rename, name) {
+        (&Some(ref s), _) => s.clone(),
+        (&None, Some(id)) => token::get_ident(id),
+        (&None, None) => token::intern_and_get_ident(format!("_field{}", i)),
+    }
+}
+
 pub fn expand_deriving_encodable(cx: &mut ExtCtxt,
                                  span: Span,
                                  mitem: @MetaItem,
                                  item: @Item,
                                  push: |@Item|) {
+    let attrs = serialize_attrs(cx, item);
     let trait_def = TraitDef {
         span: span,
         attributes: Vec::new(),
@@ -124,7 +343,7 @@ pub fn expand_deriving_encodable(cx: &mut ExtCtxt,
                 attributes: Vec::new(),
                 const_nonmatching: true,
                 combine_substructure: combine_substructure(|a, b, c| {
-                    encodable_substructure(a, b, c)
+                    encodable_substructure(a, b, c, &attrs)
                 }),
             })
     };
@@ -133,7 +352,7 @@ pub fn expand_deriving_encodable(cx: &mut ExtCtxt,
 }
 
 fn encodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
-                          substr: &Substructure) -> @Expr {
+                          substr: &Substructure, attrs: &SerializeAttrs) -> @Expr {
     let encoder = substr.nonself_args[0];
     // throw an underscore in front to suppress unused variable warnings
     let blkarg = cx.ident_of("_e");
@@ -142,47 +361,9 @@ fn encodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
 
     return match *substr.fields {
         Struct(ref fields) => {
-            let emit_struct_field = cx.ident_of("emit_struct_field");
-            let mut stmts = Vec::new();
-            let last = fields.len() - 1;
-            for (i, &FieldInfo {
-                    name,
-                    self_,
-                    span,
-                    ..
-                }) in fields.iter().enumerate() {
-                let name = match name {
-                    Some(id) => token::get_ident(id),
-                    None => {
-                        token::intern_and_get_ident(format!("_field{}", i))
-                    }
-                };
-                let enc = cx.expr_method_call(span, self_, encode, vec!(blkencoder));
-                let lambda = cx.lambda_expr_1(span, enc, blkarg);
-                let call = cx.expr_method_call(span, blkencoder,
-                                               emit_struct_field,
-                                               vec!(cx.expr_str(span, name),
-                                                 cx.expr_uint(span, i),
-                                                 lambda));
-
-                // last call doesn't need a try!
-                let call = if i != last {
-                    cx.expr_try(span, call)
-                } else {
-                    cx.expr(span, ExprRet(Some(call)))
-                };
-                stmts.push(cx.stmt_expr(call));
-            }
-
-            let blk = cx.lambda_stmts_1(trait_span, stmts, blkarg);
-            cx.expr_method_call(trait_span,
-                                encoder,
-                                cx.ident_of("emit_struct"),
-                                vec!(
-                cx.expr_str(trait_span, token::get_ident(substr.type_ident)),
-                cx.expr_uint(trait_span, fields.len()),
-                blk
-            ))
+            encode_struct(cx, trait_span, encoder,
+                          token::get_ident(substr.type_ident), None,
+                          fields.as_slice(), attrs.fields.as_slice())
         }
 
         EnumMatching(idx, variant, ref fields) => {
@@ -192,51 +373,195 @@ fn encodable_substructure(cx: &mut ExtCtxt, trait_span: Span,
             // actually exist.
             let me = cx.stmt_let(trait_span, false, blkarg, encoder);
             let encoder = cx.expr_ident(trait_span, blkarg);
-            let emit_variant_arg = cx.ident_of("emit_enum_variant_arg");
-            let mut stmts = Vec::new();
-            let last = fields.len() - 1;
-            for (i, &FieldInfo { self_, span, .. }) in fields.iter().enumerate() {
-                let enc = cx.expr_method_call(span, self_, encode, vec!(blkencoder));
-                let lambda = cx.lambda_expr_1(span, enc, blkarg);
-                let call = cx.expr_method_call(span, blkencoder,
-                                               emit_variant_arg,
-                                               vec!(cx.expr_uint(span, i),
-                                                 lambda));
-                let call = if i != last {
-                    cx.expr_try(span, call)
-                } else {
-                    cx.expr(span, ExprRet(Some(call)))
-                };
-                stmts.push(cx.stmt_expr(call));
-            }
+            let repr = &attrs.repr;
+            let name = attrs.variant_names.get(idx).clone();
+            let attrs = attrs.variant_fields.get(idx);
+            let is_struct = match variant.node.kind {
+                ast::StructVariantKind(..) => true,
+                ast::TupleVariantKind(..) => false,
+            };
 
-            // enums with no fields need to return Ok()
-            if stmts.len() == 0 {
-                let ret_ok = cx.expr(trait_span,
-                                     ExprRet(Some(cx.expr_ok(trait_span,
-                                                             cx.expr_lit(trait_span, LitNil)))));
-                stmts.push(cx.stmt_expr(ret_ok));
-            }
+            let ret = match *repr {
+                DefaultRepr => {
+                    let emit_variant_arg = cx.ident_of("emit_enum_variant_arg");
+                    let mut calls = Vec::new();
+                    for (i, &FieldInfo { self_, span, .. }) in fields.iter().enumerate() {
+                        let enc = cx.expr_method_call(span, self_, encode, vec!(blkencoder));
+                        let lambda = cx.lambda_expr_1(span, enc, blkarg);
+                        calls.push(cx.expr_method_call(span, blkencoder,
+                                                       emit_variant_arg,
+                                                       vec!(cx.expr_uint(span, i),
+                                                         lambda)));
+                    }
 
-            let blk = cx.lambda_stmts_1(trait_span, stmts, blkarg);
-            let name = cx.expr_str(trait_span, token::get_ident(variant.node.name));
-            let call = cx.expr_method_call(trait_span, blkencoder,
-                                           cx.ident_of("emit_enum_variant"),
-                                           vec!(name,
-                                             cx.expr_uint(trait_span, idx),
-                                             cx.expr_uint(trait_span, fields.len()),
-                                             blk));
-            let blk = cx.lambda_expr_1(trait_span, call, blkarg);
-            let ret = cx.expr_method_call(trait_span,
-                                          encoder,
-                                          cx.ident_of("emit_enum"),
-                                          vec!(
-                cx.expr_str(trait_span, token::get_ident(substr.type_ident)),
-                blk
-            ));
+                    let blk = cx.lambda_stmts_1(trait_span,
+                                                sequence(cx, trait_span, calls),
+                                                blkarg);
+                    let call = cx.expr_method_call(trait_span, blkencoder,
+                                                   cx.ident_of("emit_enum_variant"),
+                                                   vec!(cx.expr_str(trait_span, name),
+                                                     cx.expr_uint(trait_span, idx),
+                                                     cx.expr_uint(trait_span, fields.len()),
+                                                     blk));
+                    let blk = cx.lambda_expr_1(trait_span, call, blkarg);
+                    cx.expr_method_call(trait_span,
+                                        encoder,
+                                        cx.ident_of("emit_enum"),
+                                        vec!(
+                        cx.expr_str(trait_span, token::get_ident(substr.type_ident)),
+                        blk
+                    ))
+                }
+                ExternalRepr if fields.len() == 0 => {
+                    cx.expr_method_call(trait_span, encoder, cx.ident_of("emit_str"),
+                                        vec!(cx.expr_str(trait_span, name)))
+                }
+                ExternalRepr => {
+                    let contents = if is_struct {
+                        encode_struct(cx, trait_span, blkencoder, name.clone(), None,
+                                      fields.as_slice(), attrs.as_slice())
+                    } else {
+                        encode_tuple(cx, trait_span, blkencoder, fields.as_slice())
+                    };
+                    let key = cx.expr_method_call(trait_span, blkencoder,
+                                                  cx.ident_of("emit_str"),
+                                                  vec!(cx.expr_str(trait_span, name)));
+                    let calls = vec!(
+                        cx.expr_method_call(trait_span, blkencoder,
+                                            cx.ident_of("emit_map_elt_key"),
+                                            vec!(cx.expr_uint(trait_span, 0),
+                                              cx.lambda_expr_1(trait_span, key, blkarg))),
+                        cx.expr_method_call(trait_span, blkencoder,
+                                            cx.ident_of("emit_map_elt_val"),
+                                            vec!(cx.expr_uint(trait_span, 0),
+                                              cx.lambda_expr_1(trait_span, contents,
+                                                               blkarg))));
+                    let blk = cx.lambda_stmts_1(trait_span,
+                                                sequence(cx, trait_span, calls),
+                                                blkarg);
+                    cx.expr_method_call(trait_span, encoder, cx.ident_of("emit_map"),
+                                        vec!(cx.expr_uint(trait_span, 1), blk))
+                }
+                InternalRepr(ref key) => {
+                    encode_struct(cx, trait_span, encoder, name.clone(),
+                                  Some((key.clone(), name)),
+                                  fields.as_slice(), attrs.as_slice())
+                }
+                UntaggedRepr if fields.len() == 0 => {
+                    cx.expr_method_call(trait_span, encoder, cx.ident_of("emit_nil"),
+                                        Vec::new())
+                }
+                UntaggedRepr => {
+                    if is_struct {
+                        encode_struct(cx, trait_span, encoder, name, None,
+                                      fields.as_slice(), attrs.as_slice())
+                    } else {
+                        encode_tuple(cx, trait_span, encoder, fields.as_slice())
+                    }
+                }
+            };
             cx.expr_block(cx.block(trait_span, vec!(me), Some(ret)))
         }
 
         _ => cx.bug("expected Struct or EnumMatching in deriving(Encodable)")
     };
 }
+
+/// Turns calls returning `Result<(), E>` into statements making them in
+/// turn, and returning the first error or the result of the last call.
+fn sequence(cx: &mut ExtCtxt, span: Span, calls: Vec<@Expr>) -> Vec<@ast::Stmt> {
+    // no calls need to return Ok()
+    if calls.len() == 0 {
+        let ret_ok = cx.expr(span, ExprRet(Some(cx.expr_ok(span,
+                                                           cx.expr_lit(span, LitNil)))));
+        return vec!(cx.stmt_expr(ret_ok))
+    }
+    let last = calls.len() - 1;
+    calls.move_iter().enumerate().map(|(i, call)| {
+        // last call doesn't need a try!
+        let call = if i != last {
+            cx.expr_try(span, call)
+        } else {
+            cx.expr(span, ExprRet(Some(call)))
+        };
+        cx.stmt_expr(call)
+    }).collect()
+}
+
+/// Encodes `fields` with `emit_struct`, preceded by a field holding the name
+/// of the variant if `tag` gives its key and value.
+fn encode_struct(cx: &mut ExtCtxt, trait_span: Span, encoder: @Expr,
+                 name: InternedString,
+                 tag: Option<(InternedString, InternedString)>,
+                 fields: &[FieldInfo], attrs: &[FieldAttrs]) -> @Expr {
+    let blkarg = cx.ident_of("_e");
+    let blkencoder = cx.expr_ident(trait_span, blkarg);
+    let encode = cx.ident_of("encode");
+    let emit_struct_field = cx.ident_of("emit_struct_field");
+    let mut calls = Vec::new();
+    match tag {
+        Some((key, value)) => {
+            let enc = cx.expr_method_call(trait_span, blkencoder, cx.ident_of("emit_str"),
+                                          vec!(cx.expr_str(trait_span, value)));
+            let lambda = cx.lambda_expr_1(trait_span, enc, blkarg);
+            calls.push(cx.expr_method_call(trait_span, blkencoder, emit_struct_field,
+                                           vec!(cx.expr_str(trait_span, key),
+                                             cx.expr_uint(trait_span, 0),
+                                             lambda)));
+        }
+        None => {}
+    }
+    for (i, &FieldInfo {
+            name,
+            self_,
+            span,
+            ..
+        }) in fields.iter().enumerate() {
+        if attrs[i].skip {
+            continue
+        }
+        let name = field_name(name, i, &attrs[i]);
+        let enc = cx.expr_method_call(span, self_, encode, vec!(blkencoder));
+        let lambda = cx.lambda_expr_1(span, enc, blkarg);
+        let idx = calls.len();
+        calls.push(cx.expr_method_call(span, blkencoder,
+                                       emit_struct_field,
+                                       vec!(cx.expr_str(span, name),
+                                         cx.expr_uint(span, idx),
+                                         lambda)));
+    }
+
+    let len = calls.len();
+    let blk = cx.lambda_stmts_1(trait_span, sequence(cx, trait_span, calls), blkarg);
+    cx.expr_method_call(trait_span,
+                        encoder,
+                        cx.ident_of("emit_struct"),
+                        vec!(
+        cx.expr_str(trait_span, name),
+        cx.expr_uint(trait_span, len),
+        blk
+    ))
+}
+
+/// Encodes the fields of a tuple-like variant: a single field by itself, and
+/// several as a sequence.
+fn encode_tuple(cx: &mut ExtCtxt, trait_span: Span, encoder: @Expr,
+                fields: &[FieldInfo]) -> @Expr {
+    let blkarg = cx.ident_of("_e");
+    let blkencoder = cx.expr_ident(trait_span, blkarg);
+    let encode = cx.ident_of("encode");
+    if fields.len() == 1 {
+        return cx.expr_method_call(fields[0].span, fields[0].self_, encode,
+                                   vec!(encoder))
+    }
+    let emit_seq_elt = cx.ident_of("emit_seq_elt");
+    let calls = fields.iter().enumerate().map(|(i, &FieldInfo { self_, span, .. })| {
+        let enc = cx.expr_method_call(span, self_, encode, vec!(blkencoder));
+        let lambda = cx.lambda_expr_1(span, enc, blkarg);
+        cx.expr_method_call(span, blkencoder, emit_seq_elt,
+                            vec!(cx.expr_uint(span, i), lambda))
+    }).collect();
+    let blk = cx.lambda_stmts_1(trait_span, sequence(cx, trait_span, calls), blkarg);
+    cx.expr_method_call(trait_span, encoder, cx.ident_of("emit_seq"),
+                        vec!(cx.expr_uint(trait_span, fields.len()), blk))
+}
@@ -0,0 +1,380 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A diagnostic emitter that writes JSON, for tools that consume the
+//! compiler's errors (`--error-format=json`).
+//!
+//! Each diagnostic is written as one JSON object on its own line. Notes that
+//! follow an error or warning are attached to it as `children` rather than
+//! written on their own, so a diagnostic is only written once the next one
+//! that isn't a note arrives, or the emitter is dropped.
+
+use codemap::{CodeMap, Span, Pos, MacroAttribute, MacroBang, NameAndSpan};
+use diagnostic::{Emitter, Level, RenderSpan, FullSpan, FileLine, Bug, Fatal, Note};
+
+use serialize::json;
+use std::io;
+use std::strbuf::StrBuf;
+
+pub struct JsonEmitter {
+    dst: Box<Writer:Send>,
+    // The last error or warning, waiting for any notes that belong to it
+    pending: Option<Diagnostic>,
+}
+
+impl JsonEmitter {
+    pub fn stderr() -> JsonEmitter {
+        JsonEmitter::new(box io::stderr())
+    }
+
+    pub fn new(dst: Box<Writer:Send>) -> JsonEmitter {
+        JsonEmitter { dst: dst, pending: None }
+    }
+
+    fn push(&mut self, diag: Diagnostic, lvl: Level) -> io::IoResult<()> {
+        if lvl == Note && self.pending.is_some() {
+            self.pending.get_mut_ref().children.push(diag);
+            return Ok(());
+        }
+        try!(self.flush());
+        match lvl {
+            // Nothing follows a fatal error or a bug; the compiler stops.
+            Bug | Fatal | Note => self.write(&diag),
+            _ => {
+                self.pending = Some(diag);
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::IoResult<()> {
+        match self.pending.take() {
+            Some(diag) => self.write(&diag),
+            None => Ok(()),
+        }
+    }
+
+    fn write(&mut self, diag: &Diagnostic) -> io::IoResult<()> {
+        try!(self.dst.write_str(json::Encoder::str_encode(diag)));
+        try!(self.dst.write_char('\n'));
+        self.dst.flush()
+    }
+}
+
+impl Emitter for JsonEmitter {
+    fn emit(&mut self,
+            cmsp: Option<(&CodeMap, Span)>,
+            msg: &str,
+            code: Option<&str>,
+            lvl: Level) {
+        let spans = match cmsp {
+            Some((cm, sp)) => DiagnosticSpan::from_primary_span(cm, sp),
+            None => Vec::new(),
+        };
+        match self.push(Diagnostic::new(msg, code, lvl, spans), lvl) {
+            Ok(()) => {}
+            Err(e) => fail!("failed to print diagnostics: {}", e),
+        }
+    }
+
+    fn custom_emit(&mut self, cm: &CodeMap,
+                   sp: RenderSpan, msg: &str, lvl: Level) {
+        let sp = match sp {
+            FullSpan(sp) | FileLine(sp) => sp,
+        };
+        let spans = DiagnosticSpan::from_primary_span(cm, sp);
+        match self.push(Diagnostic::new(msg, None, lvl, spans), lvl) {
+            Ok(()) => {}
+            Err(e) => fail!("failed to print diagnostics: {}", e),
+        }
+    }
+}
+
+impl Drop for JsonEmitter {
+    fn drop(&mut self) {
+        // This may run while unwinding from a fatal error, so don't fail
+        let _ = self.flush();
+    }
+}
+
+// The structs below define the JSON format; the field names are the keys.
+
+#[deriving(Encodable)]
+struct Diagnostic {
+    message: StrBuf,
+    // An error code such as "E0001"; `rustc --explain` describes it
+    code: Option<StrBuf>,
+    // "error", "warning", "note" or "error: internal compiler error"
+    level: StrBuf,
+    spans: Vec<DiagnosticSpan>,
+    // Notes about this diagnostic
+    children: Vec<Diagnostic>,
+}
+
+#[deriving(Encodable)]
+struct DiagnosticSpan {
+    file_name: StrBuf,
+    // Byte offsets into the file, end exclusive
+    byte_start: uint,
+    byte_end: uint,
+    // 1-based lines and columns, with columns counted in characters and
+    // column_end exclusive
+    line_start: uint,
+    line_end: uint,
+    column_start: uint,
+    column_end: uint,
+    // Whether the message is about this span, rather than it giving context
+    is_primary: bool,
+    // What a secondary span shows, such as "in this expansion of foo!"
+    label: Option<StrBuf>,
+    // The source lines the span covers
+    text: Vec<DiagnosticSpanLine>,
+    // The macro invocation that produced this span, if any
+    expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
+}
+
+#[deriving(Encodable)]
+struct DiagnosticSpanLine {
+    text: StrBuf,
+    // 1-based character columns of the span on this line, end exclusive
+    highlight_start: uint,
+    highlight_end: uint,
+}
+
+#[deriving(Encodable)]
+struct DiagnosticSpanMacroExpansion {
+    // Where the macro was invoked
+    span: DiagnosticSpan,
+    // The macro's name as written, such as "println!" or "#[deriving]"
+    macro_decl_name: StrBuf,
+    // Where the macro was defined, if known
+    def_site_span: Option<DiagnosticSpan>,
+}
+
+impl Diagnostic {
+    fn new(msg: &str, code: Option<&str>, lvl: Level,
+           spans: Vec<DiagnosticSpan>) -> Diagnostic {
+        Diagnostic {
+            message: msg.to_strbuf(),
+            code: code.map(|code| code.to_strbuf()),
+            level: lvl.to_str().to_strbuf(),
+            spans: spans,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl DiagnosticSpan {
+    // The span a diagnostic is about, followed by secondary spans for each of
+    // the macro invocations it was expanded from, innermost first. These are
+    // what the human-readable output shows as "in expansion of" notes.
+    fn from_primary_span(cm: &CodeMap, sp: Span) -> Vec<DiagnosticSpan> {
+        let mut spans: Vec<DiagnosticSpan> =
+            DiagnosticSpan::from_span(cm, sp, true).move_iter().collect();
+        let mut expn_info = sp.expn_info;
+        loop {
+            let ei = match expn_info {
+                Some(ei) => ei,
+                None => break,
+            };
+            match DiagnosticSpan::from_span(cm, ei.call_site, false) {
+                Some(mut span) => {
+                    span.label = Some(format!("in this expansion of {}",
+                                              macro_name(&ei.callee)).to_strbuf());
+                    spans.push(span);
+                }
+                None => {}
+            }
+            expn_info = ei.call_site.expn_info;
+        }
+        spans
+    }
+
+    fn from_span(cm: &CodeMap, sp: Span, is_primary: bool) -> Option<DiagnosticSpan> {
+        // Spans can't be looked up before any file has been loaded
+        if cm.files.borrow().len() == 0 {
+            return None;
+        }
+
+        let lo = cm.lookup_char_pos(sp.lo);
+        let hi = cm.lookup_char_pos(sp.hi);
+        let fm = &*lo.file;
+        let text = cm.span_to_lines(sp).lines.iter().map(|&line| {
+            let text = fm.get_line(line as int);
+            DiagnosticSpanLine {
+                highlight_start: if line + 1 == lo.line { lo.col.to_uint() + 1 } else { 1 },
+                highlight_end: if line + 1 == hi.line {
+                    hi.col.to_uint() + 1
+                } else {
+                    text.as_slice().char_len() + 1
+                },
+                text: text,
+            }
+        }).collect();
+
+        let expansion = sp.expn_info.map(|ei| {
+            box DiagnosticSpanMacroExpansion {
+                span: DiagnosticSpan::from_span(cm, ei.call_site, false).unwrap(),
+                macro_decl_name: macro_name(&ei.callee).to_strbuf(),
+                def_site_span: ei.callee.span.as_ref().and_then(|span| {
+                    DiagnosticSpan::from_span(cm, *span, false)
+                }),
+            }
+        });
+
+        Some(DiagnosticSpan {
+            file_name: fm.name.clone(),
+            byte_start: sp.lo.to_uint() - fm.start_pos.to_uint(),
+            byte_end: sp.hi.to_uint() - fm.start_pos.to_uint(),
+            line_start: lo.line,
+            line_end: hi.line,
+            column_start: lo.col.to_uint() + 1,
+            column_end: hi.col.to_uint() + 1,
+            is_primary: is_primary,
+            label: None,
+            text: text,
+            expansion: expansion,
+        })
+    }
+}
+
+// The name of a macro as written, such as "println!" or "#[deriving]".
+fn macro_name(callee: &NameAndSpan) -> ~str {
+    let (pre, post) = match callee.format {
+        MacroAttribute => ("#[", "]"),
+        MacroBang => ("", "!"),
+    };
+    format!("{}{}{}", pre, callee.name, post)
+}
+
+#[cfg(test)]
+mod test {
+    use super::JsonEmitter;
+    use codemap::{CodeMap, BytePos, Span, ExpnInfo, NameAndSpan, MacroBang};
+    use diagnostic::{Emitter, Error, Fatal, Note, Warning, FileLine};
+
+    use serialize::json;
+    use std::io;
+
+    fn emit_all(f: |&mut JsonEmitter, &CodeMap|) -> Vec<json::Json> {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap("foo.rs".to_strbuf(),
+                                "fn main() {\n    let x = 1\n}\n".to_strbuf());
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(12));
+        fm.next_line(BytePos(26));
+
+        let (tx, rx) = channel();
+        {
+            let mut emitter = JsonEmitter::new(box io::ChanWriter::new(tx));
+            f(&mut emitter, &cm);
+        }
+        let out = io::ChanReader::new(rx).read_to_str().unwrap();
+        out.lines().map(|line| json::from_str(line).unwrap()).collect()
+    }
+
+    fn sp(lo: u32, hi: u32) -> Span {
+        Span { lo: BytePos(lo), hi: BytePos(hi), expn_info: None }
+    }
+
+    fn string<'a>(j: &'a json::Json, pointer: &str) -> &'a str {
+        j.pointer(pointer).unwrap().as_string().unwrap()
+    }
+
+    fn number(j: &json::Json, pointer: &str) -> f64 {
+        j.pointer(pointer).unwrap().as_number().unwrap()
+    }
+
+    #[test]
+    fn test_span() {
+        let out = emit_all(|e, cm| {
+            e.emit(Some((cm, sp(20, 21))), "unused variable", None, Warning)
+        });
+        assert_eq!(out.len(), 1);
+        let d = out.get(0);
+        assert_eq!(string(d, "/message"), "unused variable");
+        assert_eq!(string(d, "/level"), "warning");
+        assert!(d.pointer("/code").unwrap().is_null());
+        assert_eq!(string(d, "/spans/0/file_name"), "foo.rs");
+        assert_eq!(number(d, "/spans/0/byte_start"), 20.0);
+        assert_eq!(number(d, "/spans/0/byte_end"), 21.0);
+        assert_eq!(number(d, "/spans/0/line_start"), 2.0);
+        assert_eq!(number(d, "/spans/0/line_end"), 2.0);
+        assert_eq!(number(d, "/spans/0/column_start"), 9.0);
+        assert_eq!(number(d, "/spans/0/column_end"), 10.0);
+        assert_eq!(string(d, "/spans/0/text/0/text"), "    let x = 1");
+        assert_eq!(number(d, "/spans/0/text/0/highlight_start"), 9.0);
+        assert!(d.pointer("/spans/0/expansion").unwrap().is_null());
+    }
+
+    #[test]
+    fn test_macro_backtrace() {
+        let out = emit_all(|e, cm| {
+            let expn = @ExpnInfo {
+                call_site: sp(20, 21),
+                callee: NameAndSpan { name: "foo".to_strbuf(), format: MacroBang, span: None },
+            };
+            let span = Span { lo: BytePos(3), hi: BytePos(7), expn_info: Some(expn) };
+            e.emit(Some((cm, span)), "unresolved name", None, Error)
+        });
+        let d = out.get(0);
+        assert_eq!(d.pointer("/spans").unwrap().as_list().unwrap().len(), 2);
+        assert_eq!(d.pointer("/spans/0/is_primary").unwrap().as_boolean(), Some(true));
+        assert!(d.pointer("/spans/0/label").unwrap().is_null());
+        assert_eq!(string(d, "/spans/0/expansion/macro_decl_name"), "foo!");
+        assert_eq!(d.pointer("/spans/1/is_primary").unwrap().as_boolean(), Some(false));
+        assert_eq!(string(d, "/spans/1/label"), "in this expansion of foo!");
+        assert_eq!(number(d, "/spans/1/line_start"), 2.0);
+    }
+
+    #[test]
+    fn test_multiline_span() {
+        let out = emit_all(|e, cm| {
+            e.emit(Some((cm, sp(3, 27))), "mismatched types", Some("E0308"), Error)
+        });
+        let d = out.get(0);
+        assert_eq!(string(d, "/code"), "E0308");
+        assert_eq!(d.pointer("/spans/0/text").unwrap().as_list().unwrap().len(), 3);
+        assert_eq!(number(d, "/spans/0/text/0/highlight_start"), 4.0);
+        assert_eq!(number(d, "/spans/0/text/0/highlight_end"), 12.0);
+        assert_eq!(number(d, "/spans/0/text/1/highlight_start"), 1.0);
+        assert_eq!(number(d, "/spans/0/text/2/highlight_end"), 2.0);
+    }
+
+    #[test]
+    fn test_children() {
+        let out = emit_all(|e, cm| {
+            e.emit(None, "a warning", None, Warning);
+            e.emit(Some((cm, sp(20, 21))), "an error", None, Error);
+            e.emit(Some((cm, sp(0, 2))), "first note", None, Note);
+            e.custom_emit(cm, FileLine(sp(26, 27)), "second note", Note);
+            e.emit(None, "aborting due to previous error", None, Fatal);
+        });
+        assert_eq!(out.len(), 3);
+        assert_eq!(string(out.get(0), "/message"), "a warning");
+        assert!(out.get(0).pointer("/spans/0").is_err());
+        assert_eq!(string(out.get(1), "/message"), "an error");
+        assert_eq!(string(out.get(1), "/children/0/message"), "first note");
+        assert_eq!(string(out.get(1), "/children/0/level"), "note");
+        assert_eq!(number(out.get(1), "/children/1/spans/0/line_start"), 3.0);
+        assert_eq!(string(out.get(2), "/level"), "error");
+        assert!(out.get(2).pointer("/children/0").is_err());
+    }
+
+    #[test]
+    fn test_lone_note() {
+        let out = emit_all(|e, _| {
+            e.emit(None, "just a note", None, Note);
+            e.emit(None, "another note", None, Note);
+        });
+        assert_eq!(out.len(), 2);
+        assert_eq!(string(out.get(1), "/message"), "another note");
+    }
+}
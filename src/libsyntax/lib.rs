@@ -52,6 +52,7 @@ pub mod syntax {
 pub mod owned_slice;
 pub mod attr;
 pub mod diagnostic;
+pub mod json;
 pub mod codemap;
 pub mod abi;
 pub mod ast;
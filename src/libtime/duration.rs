@@ -0,0 +1,213 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Signed spans of time
+
+use std::fmt;
+
+use {div_floor, mod_floor};
+
+static NANOS_PER_SEC: i64 = 1_000_000_000;
+static SECS_PER_DAY: i64 = 86400;
+
+/// A signed span of time with nanosecond precision.
+///
+/// Durations are exact: a day is always 86400 seconds. To move a `Tm` by
+/// calendar days or months, which may be longer or shorter around daylight
+/// saving time changes, use `Tm::add_days` and `Tm::add_months`.
+#[deriving(Clone, Eq, TotalEq, Ord, TotalOrd, Encodable, Decodable)]
+pub struct Duration {
+    secs: i64,
+    // always in [0, NANOS_PER_SEC)
+    nanos: i32,
+}
+
+impl Duration {
+    fn new(secs: i64, nanos: i64) -> Duration {
+        Duration {
+            secs: secs + div_floor(nanos, NANOS_PER_SEC),
+            nanos: mod_floor(nanos, NANOS_PER_SEC) as i32,
+        }
+    }
+
+    /// The empty duration.
+    pub fn zero() -> Duration { Duration { secs: 0, nanos: 0 } }
+
+    /// A duration of `n` weeks of 7 days.
+    pub fn weeks(n: i64) -> Duration { Duration::seconds(n * 7 * SECS_PER_DAY) }
+
+    /// A duration of `n` days of 86400 seconds.
+    pub fn days(n: i64) -> Duration { Duration::seconds(n * SECS_PER_DAY) }
+
+    /// A duration of `n` hours.
+    pub fn hours(n: i64) -> Duration { Duration::seconds(n * 3600) }
+
+    /// A duration of `n` minutes.
+    pub fn minutes(n: i64) -> Duration { Duration::seconds(n * 60) }
+
+    /// A duration of `n` seconds.
+    pub fn seconds(n: i64) -> Duration { Duration { secs: n, nanos: 0 } }
+
+    /// A duration of `n` milliseconds.
+    pub fn milliseconds(n: i64) -> Duration { Duration::new(0, n * 1_000_000) }
+
+    /// A duration of `n` microseconds.
+    pub fn microseconds(n: i64) -> Duration { Duration::new(0, n * 1_000) }
+
+    /// A duration of `n` nanoseconds.
+    pub fn nanoseconds(n: i64) -> Duration { Duration::new(0, n) }
+
+    /// The number of whole days, rounded towards zero.
+    pub fn num_days(&self) -> i64 { self.num_seconds() / SECS_PER_DAY }
+
+    /// The number of whole hours, rounded towards zero.
+    pub fn num_hours(&self) -> i64 { self.num_seconds() / 3600 }
+
+    /// The number of whole minutes, rounded towards zero.
+    pub fn num_minutes(&self) -> i64 { self.num_seconds() / 60 }
+
+    /// The number of whole seconds, rounded towards zero.
+    pub fn num_seconds(&self) -> i64 {
+        if self.secs < 0 && self.nanos > 0 { self.secs + 1 } else { self.secs }
+    }
+
+    /// The number of whole milliseconds, rounded towards zero.
+    pub fn num_milliseconds(&self) -> i64 {
+        self.num_seconds() * 1000 + self.subsec_nanos() as i64 / 1_000_000
+    }
+
+    /// The total number of nanoseconds, or `None` if it doesn't fit in an
+    /// `i64` (about 292 years).
+    pub fn num_nanoseconds(&self) -> Option<i64> {
+        self.secs.checked_mul(&NANOS_PER_SEC)
+            .and_then(|n| n.checked_add(&(self.nanos as i64)))
+    }
+
+    // The nanoseconds not counted by `num_seconds`, with the sign of the
+    // duration
+    fn subsec_nanos(&self) -> i32 {
+        if self.secs < 0 && self.nanos > 0 {
+            self.nanos - NANOS_PER_SEC as i32
+        } else {
+            self.nanos
+        }
+    }
+
+    /// Returns true if the duration is less than zero.
+    pub fn is_negative(&self) -> bool { self.secs < 0 }
+
+    /// The seconds and nanoseconds, with the nanoseconds in
+    /// `[0, 1_000_000_000)`, as used by `Timespec`.
+    pub fn to_parts(&self) -> (i64, i32) { (self.secs, self.nanos) }
+}
+
+impl Neg<Duration> for Duration {
+    fn neg(&self) -> Duration {
+        Duration::new(-self.secs, -(self.nanos as i64))
+    }
+}
+
+impl Add<Duration, Duration> for Duration {
+    fn add(&self, rhs: &Duration) -> Duration {
+        Duration::new(self.secs + rhs.secs, self.nanos as i64 + rhs.nanos as i64)
+    }
+}
+
+impl Sub<Duration, Duration> for Duration {
+    fn sub(&self, rhs: &Duration) -> Duration {
+        Duration::new(self.secs - rhs.secs, self.nanos as i64 - rhs.nanos as i64)
+    }
+}
+
+impl Mul<i64, Duration> for Duration {
+    fn mul(&self, rhs: &i64) -> Duration {
+        Duration::new(self.secs * *rhs, self.nanos as i64 * *rhs)
+    }
+}
+
+impl fmt::Show for Duration {
+    /// Formats the duration as in ISO 8601, for example `P1DT2.5S` or
+    /// `-PT0.001S`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (secs, nanos) = (self.num_seconds(), self.subsec_nanos());
+        if secs < 0 || nanos < 0 {
+            try!(write!(f.buf, "-"));
+        }
+        let (secs, nanos) = (secs.abs(), nanos.abs());
+        let (days, secs) = (secs / SECS_PER_DAY, secs % SECS_PER_DAY);
+
+        try!(write!(f.buf, "P"));
+        if days != 0 {
+            try!(write!(f.buf, "{}D", days));
+        }
+        if secs == 0 && nanos == 0 && days != 0 {
+            return Ok(());
+        }
+        if nanos == 0 {
+            write!(f.buf, "T{}S", secs)
+        } else {
+            let frac = format!("{:09d}", nanos);
+            write!(f.buf, "T{}.{}S", secs, frac.trim_right_chars(&'0'))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Duration;
+    use std::i64;
+
+    #[test]
+    fn test_constructors() {
+        assert_eq!(Duration::weeks(1), Duration::days(7));
+        assert_eq!(Duration::days(1), Duration::hours(24));
+        assert_eq!(Duration::hours(1), Duration::minutes(60));
+        assert_eq!(Duration::minutes(1), Duration::seconds(60));
+        assert_eq!(Duration::seconds(1), Duration::milliseconds(1000));
+        assert_eq!(Duration::milliseconds(1), Duration::microseconds(1000));
+        assert_eq!(Duration::microseconds(1), Duration::nanoseconds(1000));
+        assert_eq!(Duration::nanoseconds(-1).to_parts(), (-1, 999_999_999));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let d = Duration::seconds(1) + Duration::milliseconds(500);
+        assert_eq!(d.num_milliseconds(), 1500);
+        assert_eq!((d - Duration::seconds(2)).num_milliseconds(), -500);
+        assert_eq!((-d).num_milliseconds(), -1500);
+        assert_eq!((d * 4).num_seconds(), 6);
+        assert_eq!((d * -1), -d);
+        assert!(-d < Duration::zero());
+        assert!((-d).is_negative());
+        assert!(Duration::milliseconds(999) < Duration::seconds(1));
+    }
+
+    #[test]
+    fn test_num() {
+        let d = Duration::days(1) + Duration::hours(2) + Duration::nanoseconds(3);
+        assert_eq!(d.num_days(), 1);
+        assert_eq!(d.num_hours(), 26);
+        assert_eq!(d.num_minutes(), 26 * 60);
+        assert_eq!(d.num_nanoseconds(), Some(93600_000_000_003));
+        assert_eq!((-d).num_hours(), -26);
+        assert_eq!(Duration::milliseconds(-1500).num_seconds(), -1);
+        assert_eq!(Duration::seconds(i64::MAX).num_nanoseconds(), None);
+    }
+
+    #[test]
+    fn test_show() {
+        assert_eq!(Duration::zero().to_str(), "PT0S".to_owned());
+        assert_eq!(Duration::days(2).to_str(), "P2D".to_owned());
+        assert_eq!((Duration::days(1) + Duration::milliseconds(2500)).to_str(),
+                   "P1DT2.5S".to_owned());
+        assert_eq!(Duration::milliseconds(-1).to_str(), "-PT0.001S".to_owned());
+        assert_eq!(Duration::hours(-25).to_str(), "-P1DT3600S".to_owned());
+    }
+}
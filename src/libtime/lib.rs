@@ -22,12 +22,20 @@
 extern crate serialize;
 extern crate libc;
 
+use std::cmp;
 use std::io::BufReader;
 use std::num;
 use std::strbuf::StrBuf;
 use std::str;
 
+pub use duration::Duration;
+pub use tz::{Tz, LocalTimeType};
+
+mod duration;
+mod tz;
+
 static NSEC_PER_SEC: i32 = 1_000_000_000_i32;
+static SECS_PER_DAY: i64 = 86400;
 
 mod rustrt {
     use super::Tm;
@@ -36,8 +44,6 @@ mod rustrt {
         pub fn rust_tzset();
         pub fn rust_gmtime(sec: i64, nsec: i32, result: &mut Tm);
         pub fn rust_localtime(sec: i64, nsec: i32, result: &mut Tm);
-        pub fn rust_timegm(tm: &Tm) -> i64;
-        pub fn rust_mktime(tm: &Tm) -> i64;
     }
 }
 
@@ -84,6 +90,31 @@ impl Timespec {
     }
 }
 
+impl Add<Duration, Timespec> for Timespec {
+    fn add(&self, rhs: &Duration) -> Timespec {
+        let (sec, nsec) = rhs.to_parts();
+        let nsec = self.nsec + nsec;
+        if nsec >= NSEC_PER_SEC {
+            Timespec::new(self.sec + sec + 1, nsec - NSEC_PER_SEC)
+        } else {
+            Timespec::new(self.sec + sec, nsec)
+        }
+    }
+}
+
+impl Sub<Duration, Timespec> for Timespec {
+    fn sub(&self, rhs: &Duration) -> Timespec {
+        *self + -*rhs
+    }
+}
+
+impl Sub<Timespec, Duration> for Timespec {
+    fn sub(&self, rhs: &Timespec) -> Duration {
+        Duration::seconds(self.sec - rhs.sec) +
+            Duration::nanoseconds(self.nsec as i64 - rhs.nsec as i64)
+    }
+}
+
 /**
  * Returns the current time as a `timespec` containing the seconds and
  * nanoseconds since 1970-01-01T00:00:00Z.
@@ -287,18 +318,196 @@ pub fn now() -> Tm {
     at(get_time())
 }
 
+/// Returns the specified time at a fixed offset of `offset` seconds east of
+/// UTC. Unlike `at`, this doesn't depend on the `TZ` environment variable.
+pub fn at_offset(clock: Timespec, offset: i32) -> Tm {
+    let local = clock.sec + offset as i64;
+    let secs = mod_floor(local, SECS_PER_DAY) as i32;
+    let mut tm = Tm {
+        tm_sec: secs % 60,
+        tm_min: secs / 60 % 60,
+        tm_hour: secs / 3600,
+        tm_mday: 0,
+        tm_mon: 0,
+        tm_year: 0,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_gmtoff: offset,
+        tm_zone: if offset == 0 { "UTC".to_owned() } else { "".to_owned() },
+        tm_nsec: clock.nsec,
+    };
+    tm.set_days(div_floor(local, SECS_PER_DAY));
+    tm
+}
+
+/// Returns true if `year` (e.g. 2012, not 112) is a leap year in the
+/// proleptic Gregorian calendar.
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days in month `mon` (0 = January, as in `tm_mon`)
+/// of `year`.
+pub fn days_in_month(year: i64, mon: i32) -> i32 {
+    match mon {
+        1 if is_leap_year(year) => 29,
+        1 => 28,
+        3 | 5 | 8 | 10 => 30,
+        _ => 31,
+    }
+}
+
+fn div_floor(a: i64, b: i64) -> i64 {
+    let d = a / b;
+    if (a % b != 0) && ((a < 0) != (b < 0)) { d - 1 } else { d }
+}
+
+fn mod_floor(a: i64, b: i64) -> i64 {
+    a - div_floor(a, b) * b
+}
+
+// Days since 1970-01-01 of the given proleptic Gregorian date. `mon` counts
+// from 0 and may be out of range, as may `mday`.
+//
+// See http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, mon: i32, mday: i32) -> i64 {
+    let year = year + div_floor(mon as i64, 12);
+    let mon = mod_floor(mon as i64, 12) + 1;
+    let y = if mon <= 2 { year - 1 } else { year };
+    let era = div_floor(y, 400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((mon + 9) % 12) + 2) / 5 + mday as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The inverse of `days_from_civil`: (year, month from 0, day of month)
+fn civil_from_days(days: i64) -> (i64, i32, i32) {
+    let z = days + 719468;
+    let era = div_floor(z, 146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let mday = doy - (153 * mp + 2) / 5 + 1;
+    let mon = if mp < 10 { mp + 2 } else { mp - 10 };
+    let year = yoe + era * 400 + if mon <= 1 { 1 } else { 0 };
+    (year, mon as i32, mday as i32)
+}
+
+// Seconds since the epoch of the time of day and date in `tm`, as if it
+// were UTC
+fn local_seconds(tm: &Tm) -> i64 {
+    let days = days_from_civil(tm.tm_year as i64 + 1900, tm.tm_mon, tm.tm_mday);
+    days * SECS_PER_DAY + tm.tm_hour as i64 * 3600 + tm.tm_min as i64 * 60 +
+        tm.tm_sec as i64
+}
+
+fn days_in_year(year: int) -> i32 {
+    if is_leap_year(year as i64) {
+        366    /* Days in a leap year */
+    } else {
+        365    /* Days in a non-leap year */
+    }
+}
+
+fn iso_week_days(yday: i32, wday: i32) -> int {
+    /* The number of days from the first day of the first ISO week of this
+    * year to the year day YDAY with week day WDAY.
+    * ISO weeks start on Monday. The first ISO week has the year's first
+    * Thursday.
+    * YDAY may be as small as yday_minimum.
+    */
+    let yday: int = yday as int;
+    let wday: int = wday as int;
+    let iso_week_start_wday: int = 1;                     /* Monday */
+    let iso_week1_wday: int = 4;                          /* Thursday */
+    let yday_minimum: int = 366;
+    /* Add enough to the first operand of % to make it nonnegative. */
+    let big_enough_multiple_of_7: int = (yday_minimum / 7 + 2) * 7;
+
+    yday - (yday - wday + iso_week1_wday + big_enough_multiple_of_7) % 7
+        + iso_week1_wday - iso_week_start_wday
+}
+
 
 impl Tm {
-    /// Convert time to the seconds from January 1, 1970
+    /// Convert time to the seconds from January 1, 1970, using `tm_gmtoff`
+    /// as the offset from UTC
     pub fn to_timespec(&self) -> Timespec {
-        unsafe {
-            let sec = match self.tm_gmtoff {
-                0_i32 => rustrt::rust_timegm(self),
-                _     => rustrt::rust_mktime(self)
-            };
+        Timespec::new(local_seconds(self) - self.tm_gmtoff as i64, self.tm_nsec)
+    }
+
+    /**
+     * Returns the same time of day `n` calendar days later (or earlier, if
+     * `n` is negative).
+     *
+     * `tm_gmtoff` is kept as it is. To get the offset in effect on the new
+     * date, pass the result through `Tz::from_local` and `Tz::at`.
+     */
+    pub fn add_days(&self, n: i64) -> Tm {
+        let days = days_from_civil(self.tm_year as i64 + 1900, self.tm_mon, self.tm_mday);
+        let mut tm = self.clone();
+        tm.set_days(days + n);
+        tm
+    }
+
+    /**
+     * Returns the same time of day `n` calendar months later (or earlier, if
+     * `n` is negative). The day of the month is clamped to the length of the
+     * new month, so January 31 plus one month is February 28 or 29.
+     *
+     * As with `add_days`, `tm_gmtoff` is kept as it is.
+     */
+    pub fn add_months(&self, n: i32) -> Tm {
+        let months = (self.tm_year as i64 + 1900) * 12 + self.tm_mon as i64 + n as i64;
+        let (year, mon) = (div_floor(months, 12), mod_floor(months, 12) as i32);
+        let mday = cmp::min(self.tm_mday, days_in_month(year, mon));
+        let mut tm = self.clone();
+        tm.set_days(days_from_civil(year, mon, mday));
+        tm
+    }
+
+    /**
+     * Returns the ISO 8601 week date of this day as (year, week, weekday),
+     * with weeks numbered from 1 and weekdays from 1 (Monday) to 7 (Sunday).
+     *
+     * The year is the one the week belongs to, which differs from `tm_year`
+     * for a few days around the new year: 2010-01-03 is (2009, 53, 7).
+     */
+    pub fn iso_week(&self) -> (i32, i32, i32) {
+        let mut year: int = self.tm_year as int + 1900;
+        let mut days: int = iso_week_days (self.tm_yday, self.tm_wday);
 
-            Timespec::new(sec, self.tm_nsec)
+        if days < 0 {
+            /* This ISO week belongs to the previous year. */
+            year -= 1;
+            days = iso_week_days (self.tm_yday + (days_in_year(year)), self.tm_wday);
+        } else {
+            let d: int = iso_week_days (self.tm_yday - (days_in_year(year)),
+                                        self.tm_wday);
+            if 0 <= d {
+                /* This ISO week belongs to the next year. */
+                year += 1;
+                days = d;
+            }
         }
+
+        let weekday = if self.tm_wday == 0 { 7 } else { self.tm_wday };
+        (year as i32, (days / 7 + 1) as i32, weekday)
+    }
+
+    // Sets the date fields, including the weekday and the day of the year,
+    // to the given number of days since 1970-01-01
+    fn set_days(&mut self, days: i64) {
+        let (year, mon, mday) = civil_from_days(days);
+        self.tm_year = (year - 1900) as i32;
+        self.tm_mon = mon;
+        self.tm_mday = mday;
+        // 1970-01-01 was a Thursday
+        self.tm_wday = mod_floor(days + 4, 7) as i32;
+        self.tm_yday = (days - days_from_civil(year, 0, 1)) as i32;
     }
 
     /// Convert time to the local timezone
@@ -366,6 +575,103 @@ impl Tm {
     }
 }
 
+/**
+ * Parses a time in the format of RFC 3339, such as
+ * "2012-02-22T07:53:18.25-07:00" or "2012-02-22 14:53:18Z".
+ *
+ * The result keeps the offset given in the string in `tm_gmtoff`, and has a
+ * `tm_zone` of "UTC" if that offset is zero.
+ */
+pub fn parse_rfc3339(s: &str) -> Result<Tm, ~str> {
+    fn num(b: &[u8], pos: uint, len: uint, min: i32, max: i32) -> Result<i32, ()> {
+        if b.len() < pos + len {
+            return Err(());
+        }
+        let mut n = 0_i32;
+        for &c in b.slice(pos, pos + len).iter() {
+            if c < '0' as u8 || c > '9' as u8 {
+                return Err(());
+            }
+            n = n * 10_i32 + (c - '0' as u8) as i32;
+        }
+        if n < min || n > max { Err(()) } else { Ok(n) }
+    }
+
+    fn sep(b: &[u8], pos: uint, allowed: &[u8]) -> Result<(), ()> {
+        if pos < b.len() && allowed.contains(&b[pos]) { Ok(()) } else { Err(()) }
+    }
+
+    fn parse(b: &[u8]) -> Result<Tm, ()> {
+        let year = try!(num(b, 0, 4, 0, 9999));
+        try!(sep(b, 4, ['-' as u8]));
+        let mon = try!(num(b, 5, 2, 1, 12));
+        try!(sep(b, 7, ['-' as u8]));
+        let mday = try!(num(b, 8, 2, 1, days_in_month(year as i64, mon - 1)));
+        try!(sep(b, 10, ['T' as u8, 't' as u8, ' ' as u8]));
+        let hour = try!(num(b, 11, 2, 0, 23));
+        try!(sep(b, 13, [':' as u8]));
+        let min = try!(num(b, 14, 2, 0, 59));
+        try!(sep(b, 16, [':' as u8]));
+        // 60 is a leap second
+        let sec = try!(num(b, 17, 2, 0, 60));
+
+        let mut pos = 19u;
+        let mut nsec = 0_i32;
+        if pos < b.len() && b[pos] == '.' as u8 {
+            pos += 1;
+            let start = pos;
+            let mut multiplier = NSEC_PER_SEC / 10;
+            while pos < b.len() && b[pos] >= '0' as u8 && b[pos] <= '9' as u8 {
+                // This will drop digits after the nanoseconds place
+                nsec += (b[pos] - '0' as u8) as i32 * multiplier;
+                multiplier /= 10;
+                pos += 1;
+            }
+            if pos == start {
+                return Err(());
+            }
+        }
+
+        try!(sep(b, pos, ['Z' as u8, 'z' as u8, '+' as u8, '-' as u8]));
+        let gmtoff = if b[pos] == 'Z' as u8 || b[pos] == 'z' as u8 {
+            pos += 1;
+            0
+        } else {
+            let sign = if b[pos] == '-' as u8 { -1 } else { 1 };
+            let h = try!(num(b, pos + 1, 2, 0, 23));
+            try!(sep(b, pos + 3, [':' as u8]));
+            let m = try!(num(b, pos + 4, 2, 0, 59));
+            pos += 6;
+            sign * (h * 3600 + m * 60)
+        };
+        if pos != b.len() {
+            return Err(());
+        }
+
+        let mut tm = Tm {
+            tm_sec: sec,
+            tm_min: min,
+            tm_hour: hour,
+            tm_mday: 0,
+            tm_mon: 0,
+            tm_year: 0,
+            tm_wday: 0,
+            tm_yday: 0,
+            tm_isdst: 0,
+            tm_gmtoff: gmtoff,
+            tm_zone: if gmtoff == 0 { "UTC".to_owned() } else { "".to_owned() },
+            tm_nsec: nsec,
+        };
+        tm.set_days(days_from_civil(year as i64, mon - 1, mday));
+        Ok(tm)
+    }
+
+    match parse(s.as_bytes()) {
+        Ok(tm) => Ok(tm),
+        Err(()) => Err(format!("Invalid RFC 3339 time: {}", s)),
+    }
+}
+
 /// Parses the time from the string according to the format string.
 pub fn strptime(s: &str, format: &str) -> Result<Tm, ~str> {
     fn match_str(s: &str, pos: uint, needle: &str) -> bool {
@@ -754,11 +1060,18 @@ pub fn strptime(s: &str, format: &str) -> Result<Tm, ~str> {
             let range = s.char_range_at(pos);
 
             if range.ch == '+' || range.ch == '-' {
-                match match_digits(s, range.next, 4u, false) {
-                  Some(item) => {
-                    let (v, pos) = item;
-                    if v == 0_i32 {
-                        tm.tm_gmtoff = 0_i32;
+                let sign = if range.ch == '+' { 1_i32 } else { -1_i32 };
+                let (h, pos) = match match_digits_in_range(s, range.next, 2u, false,
+                                                           0_i32, 23_i32) {
+                  Some(item) => item,
+                  None => return Err("Invalid zone offset".to_owned())
+                };
+                // Both +hhmm and +hh:mm are accepted
+                let pos = if pos < s.len() && s[pos] == ':' as u8 { pos + 1 } else { pos };
+                match match_digits_in_range(s, pos, 2u, false, 0_i32, 59_i32) {
+                  Some((m, pos)) => {
+                    tm.tm_gmtoff = sign * (h * 3600_i32 + m * 60_i32);
+                    if tm.tm_gmtoff == 0_i32 {
                         tm.tm_zone = "UTC".to_owned();
                     }
 
@@ -844,55 +1157,13 @@ pub fn strptime(s: &str, format: &str) -> Result<Tm, ~str> {
 
 /// Formats the time according to the format string.
 pub fn strftime(format: &str, tm: &Tm) -> ~str {
-    fn days_in_year(year: int) -> i32 {
-        if (year % 4 == 0) && ((year % 100 != 0) || (year % 400 == 0)) {
-            366    /* Days in a leap year */
-        } else {
-            365    /* Days in a non-leap year */
-        }
-    }
-
-    fn iso_week_days(yday: i32, wday: i32) -> int {
-        /* The number of days from the first day of the first ISO week of this
-        * year to the year day YDAY with week day WDAY.
-        * ISO weeks start on Monday. The first ISO week has the year's first
-        * Thursday.
-        * YDAY may be as small as yday_minimum.
-        */
-        let yday: int = yday as int;
-        let wday: int = wday as int;
-        let iso_week_start_wday: int = 1;                     /* Monday */
-        let iso_week1_wday: int = 4;                          /* Thursday */
-        let yday_minimum: int = 366;
-        /* Add enough to the first operand of % to make it nonnegative. */
-        let big_enough_multiple_of_7: int = (yday_minimum / 7 + 2) * 7;
-
-        yday - (yday - wday + iso_week1_wday + big_enough_multiple_of_7) % 7
-            + iso_week1_wday - iso_week_start_wday
-    }
-
     fn iso_week(ch:char, tm: &Tm) -> ~str {
-        let mut year: int = tm.tm_year as int + 1900;
-        let mut days: int = iso_week_days (tm.tm_yday, tm.tm_wday);
-
-        if days < 0 {
-            /* This ISO week belongs to the previous year. */
-            year -= 1;
-            days = iso_week_days (tm.tm_yday + (days_in_year(year)), tm.tm_wday);
-        } else {
-            let d: int = iso_week_days (tm.tm_yday - (days_in_year(year)),
-                                        tm.tm_wday);
-            if 0 <= d {
-                /* This ISO week belongs to the next year. */
-                year += 1;
-                days = d;
-            }
-        }
+        let (year, week, _) = tm.iso_week();
 
         match ch {
             'G' => format!("{}", year),
             'g' => format!("{:02d}", (year % 100 + 100) % 100),
-            'V' => format!("{:02d}", days / 7 + 1),
+            'V' => format!("{:02d}", week),
             _ => "".to_owned()
         }
     }
@@ -1072,8 +1343,9 @@ pub fn strftime(format: &str, tm: &Tm) -> ~str {
 
 #[cfg(test)]
 mod tests {
-    use super::{Timespec, get_time, precise_time_ns, precise_time_s, tzset,
-                at_utc, at, strptime};
+    use super::{Timespec, Duration, get_time, precise_time_ns, precise_time_s, tzset,
+                at_utc, at, at_offset, strptime, parse_rfc3339, is_leap_year,
+                days_in_month, days_from_civil, civil_from_days};
 
     use std::f64;
     use std::result::{Err, Ok};
@@ -1371,7 +1643,13 @@ mod tests {
         assert!(strptime("-0000", "%z").unwrap().tm_gmtoff ==
             0);
         assert!(strptime("-0800", "%z").unwrap().tm_gmtoff ==
-            0);
+            -28800);
+        assert!(strptime("+05:30", "%z").unwrap().tm_gmtoff ==
+            19800);
+        assert!(strptime("+2400", "%z").is_err());
+        assert!(strptime("+08", "%z").is_err());
+        let tm = strptime("2009-02-13 15:31:30 -0800", "%Y-%m-%d %T %z").unwrap();
+        assert_eq!(tm.to_timespec(), Timespec::new(1234567890, 0));
         assert!(test("%", "%%"));
 
         // Test for #7256
@@ -1495,6 +1773,104 @@ mod tests {
         assert!(d.gt(c));
     }
 
+    fn test_calendar() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2012));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2013));
+        assert_eq!(days_in_month(2012, 1), 29);
+        assert_eq!(days_in_month(2013, 1), 28);
+        assert_eq!(days_in_month(2013, 3), 30);
+        assert_eq!(days_in_month(2013, 11), 31);
+
+        assert_eq!(days_from_civil(1970, 0, 1), 0);
+        assert_eq!(days_from_civil(2009, 1, 13), 14288);
+        assert_eq!(days_from_civil(1969, 11, 31), -1);
+        assert_eq!(days_from_civil(2008, 13, 13), 14288);
+        for &days in [-719468i64, -1, 0, 59, 14288, 2932896].iter() {
+            let (year, mon, mday) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, mon, mday), days);
+        }
+        assert_eq!(civil_from_days(-1), (1969, 11, 31));
+        assert_eq!(civil_from_days(11016), (2000, 1, 29));
+    }
+
+    fn test_at_offset() {
+        let time = Timespec::new(1234567890, 54321);
+        let tm = at_offset(time, -28800);
+        assert_eq!(tm.tm_hour, 15_i32);
+        assert_eq!(tm.tm_wday, 5_i32);
+        assert_eq!(tm.tm_yday, 43_i32);
+        assert_eq!(tm.tm_nsec, 54321_i32);
+        assert_eq!(tm.rfc3339(), "2009-02-13T15:31:30-08:00".to_owned());
+        assert_eq!(tm.to_timespec(), time);
+
+        let tm = at_offset(Timespec::new(-1, 0), 0);
+        assert_eq!(tm.rfc3339(), "1969-12-31T23:59:59Z".to_owned());
+        assert_eq!(tm.tm_wday, 3_i32);
+        assert_eq!(tm.tm_yday, 364_i32);
+        assert_eq!(tm.tm_zone, "UTC".to_owned());
+    }
+
+    fn test_add_days_months() {
+        let tm = at_offset(Timespec::new(1234567890, 0), 3600);
+        assert_eq!(tm.add_days(16).rfc3339(), "2009-03-02T00:31:30+01:00".to_owned());
+        assert_eq!(tm.add_days(-45).rfc3339(), "2008-12-31T00:31:30+01:00".to_owned());
+        assert_eq!(tm.add_days(16).tm_wday, 1_i32);
+        assert_eq!(tm.add_months(12).rfc3339(), "2010-02-14T00:31:30+01:00".to_owned());
+        assert_eq!(tm.add_months(-14).rfc3339(), "2007-12-14T00:31:30+01:00".to_owned());
+
+        let jan31 = parse_rfc3339("2012-01-31T12:00:00Z").unwrap();
+        assert_eq!(jan31.add_months(1).strftime("%F %a"), "2012-02-29 Wed".to_owned());
+        assert_eq!(jan31.add_months(2).strftime("%F"), "2012-03-31".to_owned());
+        assert_eq!(jan31.add_months(13).strftime("%F"), "2013-02-28".to_owned());
+        assert_eq!(jan31.add_months(-2).strftime("%F"), "2011-11-30".to_owned());
+        assert_eq!(jan31.add_months(1).tm_yday, 59_i32);
+    }
+
+    fn test_iso_week() {
+        fn week(s: &str) -> (i32, i32, i32) {
+            parse_rfc3339(format!("{}T00:00:00Z", s).as_slice()).unwrap().iso_week()
+        }
+        assert_eq!(week("2009-02-13"), (2009, 7, 5));
+        assert_eq!(week("2008-12-29"), (2009, 1, 1));
+        assert_eq!(week("2010-01-03"), (2009, 53, 7));
+        assert_eq!(week("2012-01-01"), (2011, 52, 7));
+        assert_eq!(week("2015-12-31"), (2015, 53, 4));
+    }
+
+    fn test_parse_rfc3339() {
+        let tm = parse_rfc3339("2009-02-13T15:31:30.0123-08:00").unwrap();
+        assert_eq!(tm.tm_hour, 15_i32);
+        assert_eq!(tm.tm_wday, 5_i32);
+        assert_eq!(tm.tm_yday, 43_i32);
+        assert_eq!(tm.tm_gmtoff, -28800_i32);
+        assert_eq!(tm.tm_nsec, 12300000_i32);
+        assert_eq!(tm.to_timespec(), Timespec::new(1234567890, 12300000));
+        assert_eq!(tm.rfc3339(), "2009-02-13T15:31:30-08:00".to_owned());
+
+        let tm = parse_rfc3339("2009-02-13 23:31:30z").unwrap();
+        assert_eq!(tm.tm_zone, "UTC".to_owned());
+        assert_eq!(tm.to_timespec(), Timespec::new(1234567890, 0));
+        assert_eq!(parse_rfc3339("2009-02-14t02:31:30+03:00").unwrap().to_timespec(),
+                   Timespec::new(1234567890, 0));
+
+        for s in ["", "2009-02-13", "2009-02-13T23:31:30", "2009-02-29T23:31:30Z",
+                  "2009-02-13T24:31:30Z", "2009-02-13T23:31:30.Z", "2009-02-13T23:31:30+0800",
+                  "2009-02-13T23:31:30Z ", "2009-2-13T23:31:30Z"].iter() {
+            assert!(parse_rfc3339(*s).is_err(), "{}", *s);
+        }
+    }
+
+    fn test_timespec_duration() {
+        let t = Timespec::new(1234567890, 900_000_000);
+        assert_eq!(t + Duration::milliseconds(200), Timespec::new(1234567891, 100_000_000));
+        assert_eq!(t - Duration::milliseconds(1000), Timespec::new(1234567889, 900_000_000));
+        assert_eq!(t - Duration::days(1), Timespec::new(1234481490, 900_000_000));
+        assert_eq!(Timespec::new(-1, 0) - t, -Duration::nanoseconds(1234567891_900_000_000));
+        assert_eq!(t - Timespec::new(1234567890, 0), Duration::milliseconds(900));
+    }
+
     #[test]
     #[ignore(cfg(target_os = "android"))] // FIXME #10958
     fn run_tests() {
@@ -1510,5 +1886,11 @@ mod tests {
         test_ctime();
         test_strftime();
         test_timespec_eq_ord();
+        test_calendar();
+        test_at_offset();
+        test_add_days_months();
+        test_iso_week();
+        test_parse_rfc3339();
+        test_timespec_duration();
     }
 }
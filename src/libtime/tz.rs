@@ -0,0 +1,725 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Time zones read from TZif files (RFC 8536), as found in
+//! `/usr/share/zoneinfo`.
+//!
+//! Unlike `at` and `Tm::to_local`, which go through the C library and the
+//! process-wide `TZ` variable, a `Tz` is a plain value: any number of zones
+//! can be used at once and conversions don't race on `tzset`.
+
+use std::i32;
+use std::io::File;
+use std::os;
+use std::str;
+
+use {Timespec, Tm, at_offset, local_seconds, days_from_civil, civil_from_days,
+     days_in_month, is_leap_year, div_floor, mod_floor};
+
+static SECS_PER_DAY: i64 = 86400;
+
+/// An offset from UTC and the name used for it, such as `-18000` and `EST`.
+#[deriving(Clone, Eq, Show)]
+pub struct LocalTimeType {
+    /// Seconds east of UTC
+    pub utoff: i32,
+    /// Whether this is a daylight saving time offset
+    pub is_dst: bool,
+    /// The abbreviated name, such as "EST" or "CEST"
+    pub abbrev: ~str,
+}
+
+/// A time zone: the history of its offsets from UTC, and optionally a rule
+/// for the offsets after its last recorded transition.
+#[deriving(Clone, Show)]
+pub struct Tz {
+    name: ~str,
+    // UTC seconds at which each transition takes effect, ascending
+    transitions: Vec<i64>,
+    // for each transition, the index into `types` in effect after it
+    indices: Vec<u8>,
+    types: Vec<LocalTimeType>,
+    rule: Option<Rule>,
+}
+
+// A POSIX TZ string such as "EST5EDT,M3.2.0,M11.1.0", giving the offsets
+// used after the last transition of a TZif file
+#[deriving(Clone, Show)]
+struct Rule {
+    std: LocalTimeType,
+    dst: Option<(LocalTimeType, RuleDate, i32, RuleDate, i32)>,
+}
+
+// The day of the year a daylight saving time change happens on
+#[deriving(Clone, Show)]
+enum RuleDate {
+    // Jn: day 1 to 365, never counting February 29
+    Julian(i32),
+    // n: day 0 to 365, counting February 29
+    ZeroBased(i32),
+    // Mm.w.d: day `d` (0 = Sunday) of week `w` (5 = last) of month `m`
+    MonthWeekDay(i32, i32, i32),
+}
+
+impl Tz {
+    /// The UTC time zone.
+    pub fn utc() -> Tz {
+        Tz {
+            name: "UTC".to_owned(),
+            transitions: Vec::new(),
+            indices: Vec::new(),
+            types: vec!(LocalTimeType { utoff: 0, is_dst: false, abbrev: "UTC".to_owned() }),
+            rule: None,
+        }
+    }
+
+    /// Loads the zone named `name`, such as "Europe/Paris", from the
+    /// directory named by `$TZDIR`, or `/usr/share/zoneinfo` if it isn't set.
+    pub fn load(name: &str) -> Result<Tz, ~str> {
+        if name.len() == 0 || name.starts_with("/") ||
+           name.split('/').any(|part| part == "..") {
+            return Err(format!("invalid time zone name `{}`", name));
+        }
+        let dir = match os::getenv("TZDIR") {
+            Some(dir) => Path::new(dir),
+            None => Path::new("/usr/share/zoneinfo"),
+        };
+        let bytes = match File::open(&dir.join(name)).and_then(|mut f| f.read_to_end()) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(format!("couldn't read time zone `{}`: {}", name, e)),
+        };
+        Tz::from_tzif(name, bytes.as_slice())
+    }
+
+    /// Reads a zone from the TZif file at `path`.
+    pub fn from_file(path: &Path) -> Result<Tz, ~str> {
+        let bytes = match File::open(path).and_then(|mut f| f.read_to_end()) {
+            Ok(bytes) => bytes,
+            Err(e) => return Err(format!("couldn't read {}: {}", path.display(), e)),
+        };
+        Tz::from_tzif(path.filename_str().unwrap_or(""), bytes.as_slice())
+    }
+
+    /// Parses the contents of a TZif file of any version.
+    pub fn from_tzif(name: &str, bytes: &[u8]) -> Result<Tz, ~str> {
+        let mut rdr = Reader { buf: bytes, pos: 0 };
+        let v1 = try!(read_header(&mut rdr, 4));
+        if v1.version == 0 {
+            return read_body(name, &mut rdr, &v1, 4);
+        }
+
+        // Version 2 and later files repeat the data with 64-bit times after
+        // the version 1 block, then end with a TZ string for later times.
+        try!(rdr.take(v1.body_len(4)));
+        let v2 = try!(read_header(&mut rdr, 8));
+        let mut tz = try!(read_body(name, &mut rdr, &v2, 8));
+        if try!(rdr.byte()) != '\n' as u8 {
+            return Err("TZif footer doesn't start with a newline".to_owned());
+        }
+        let start = rdr.pos;
+        while try!(rdr.byte()) != '\n' as u8 {}
+        let footer = bytes.slice(start, rdr.pos - 1);
+        if footer.len() > 0 {
+            let footer = match str::from_utf8(footer) {
+                Some(s) => s,
+                None => return Err("TZif footer isn't valid UTF-8".to_owned()),
+            };
+            tz.rule = match parse_rule(footer) {
+                Ok(rule) => Some(rule),
+                Err(()) => return Err(format!("invalid TZ rule `{}` in TZif footer", footer)),
+            };
+        }
+        Ok(tz)
+    }
+
+    /// The name the zone was loaded with.
+    pub fn name<'a>(&'a self) -> &'a str {
+        self.name.as_slice()
+    }
+
+    /// The offset from UTC in effect in this zone at `clock`.
+    pub fn offset_at(&self, clock: Timespec) -> LocalTimeType {
+        let t = clock.sec;
+        let n = self.transitions.len();
+        if n == 0 || t >= *self.transitions.get(n - 1) {
+            match self.rule {
+                Some(ref rule) => return rule.offset_at(t),
+                None if n == 0 => return self.types.get(0).clone(),
+                None => {}
+            }
+        }
+        if n == 0 || t < *self.transitions.get(0) {
+            return self.types.get(0).clone();
+        }
+
+        // Find the last transition at or before `t`
+        let (mut lo, mut hi) = (0u, n);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if *self.transitions.get(mid) <= t { lo = mid } else { hi = mid }
+        }
+        self.types.get(*self.indices.get(lo) as uint).clone()
+    }
+
+    /// Converts `clock` to a broken-down time in this zone.
+    pub fn at(&self, clock: Timespec) -> Tm {
+        let ltt = self.offset_at(clock);
+        let mut tm = at_offset(clock, ltt.utoff);
+        tm.tm_isdst = if ltt.is_dst { 1 } else { 0 };
+        tm.tm_zone = ltt.abbrev;
+        tm
+    }
+
+    /// Converts a broken-down time in this zone to a `Timespec`, ignoring
+    /// `tm.tm_gmtoff`.
+    ///
+    /// A local time that happens twice, when clocks go back, resolves to the
+    /// daylight saving time one if `tm.tm_isdst` is positive, the standard
+    /// time one if it is zero, and otherwise the earlier one. A local time
+    /// that is skipped when clocks go forward is read with the offset from
+    /// before the change, so 02:30 becomes 03:30 if clocks skip 02:00-03:00.
+    pub fn from_local(&self, tm: &Tm) -> Timespec {
+        let local = local_seconds(tm);
+        let mut offsets: Vec<i32> = self.types.iter().map(|t| t.utoff).collect();
+        match self.rule {
+            Some(ref rule) => {
+                offsets.push(rule.std.utoff);
+                match rule.dst {
+                    Some((ref dst, _, _, _, _)) => offsets.push(dst.utoff),
+                    None => {}
+                }
+            }
+            None => {}
+        }
+        offsets.sort();
+        offsets.dedup();
+
+        // Every offset that maps `local` back onto itself is a valid reading
+        let mut found: Option<(i64, bool)> = None;
+        let mut before_gap: Option<i32> = None;
+        for &off in offsets.iter() {
+            let t = local - off as i64;
+            let ltt = self.offset_at(Timespec::new(t, 0));
+            if ltt.utoff == off {
+                found = match found {
+                    None => Some((t, ltt.is_dst)),
+                    Some((prev, prev_dst)) => {
+                        let pick_new = if tm.tm_isdst > 0 {
+                            ltt.is_dst && !prev_dst
+                        } else if tm.tm_isdst == 0 {
+                            !ltt.is_dst && prev_dst
+                        } else {
+                            t < prev
+                        };
+                        if pick_new { Some((t, ltt.is_dst)) } else { Some((prev, prev_dst)) }
+                    }
+                };
+            } else if ltt.utoff > off {
+                before_gap = Some(off);
+            }
+        }
+
+        let sec = match (found, before_gap) {
+            (Some((t, _)), _) => t,
+            (None, Some(off)) => local - off as i64,
+            (None, None) => local - self.types.get(0).utoff as i64,
+        };
+        Timespec::new(sec, tm.tm_nsec)
+    }
+}
+
+impl Rule {
+    fn offset_at(&self, t: i64) -> LocalTimeType {
+        let (dst, start, start_time, end, end_time) = match self.dst {
+            Some((ref dst, ref start, start_time, ref end, end_time)) => {
+                (dst, start, start_time, end, end_time)
+            }
+            None => return self.std.clone(),
+        };
+
+        // The transitions are given in local time: the start in standard
+        // time, the end in daylight saving time.
+        let (year, _, _) = civil_from_days(div_floor(t + self.std.utoff as i64,
+                                                     SECS_PER_DAY));
+        let start = start.days_since_epoch(year) * SECS_PER_DAY + start_time as i64
+                    - self.std.utoff as i64;
+        let end = end.days_since_epoch(year) * SECS_PER_DAY + end_time as i64
+                  - dst.utoff as i64;
+        let in_dst = if start < end {
+            start <= t && t < end
+        } else {
+            // Southern hemisphere: daylight saving time spans the new year
+            !(end <= t && t < start)
+        };
+        if in_dst { dst.clone() } else { self.std.clone() }
+    }
+}
+
+impl RuleDate {
+    fn days_since_epoch(&self, year: i64) -> i64 {
+        match *self {
+            Julian(n) => {
+                let leap_day = if is_leap_year(year) && n >= 60 { 1 } else { 0 };
+                days_from_civil(year, 0, 1) + n as i64 - 1 + leap_day
+            }
+            ZeroBased(n) => days_from_civil(year, 0, 1) + n as i64,
+            MonthWeekDay(m, w, d) => {
+                let first = days_from_civil(year, m - 1, 1);
+                // 1970-01-01 was a Thursday
+                let first_wday = mod_floor(first + 4, 7) as i32;
+                let mut mday = 1 + (d - first_wday + 7) % 7 + (w - 1) * 7;
+                while mday > days_in_month(year, m - 1) {
+                    mday -= 7;
+                }
+                first + mday as i64 - 1
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: uint,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: uint) -> Result<&'a [u8], ~str> {
+        if self.buf.len() - self.pos < n {
+            return Err("unexpected end of TZif data".to_owned());
+        }
+        let buf = self.buf;
+        let s = buf.slice(self.pos, self.pos + n);
+        self.pos += n;
+        Ok(s)
+    }
+
+    fn byte(&mut self) -> Result<u8, ~str> {
+        Ok(try!(self.take(1))[0])
+    }
+
+    fn be(&mut self, n: uint) -> Result<u64, ~str> {
+        let bytes = try!(self.take(n));
+        Ok(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+}
+
+struct Header {
+    version: u8,
+    isutcnt: uint,
+    isstdcnt: uint,
+    leapcnt: uint,
+    timecnt: uint,
+    typecnt: uint,
+    charcnt: uint,
+}
+
+impl Header {
+    fn body_len(&self, time_size: uint) -> uint {
+        self.timecnt * time_size + self.timecnt + self.typecnt * 6 + self.charcnt +
+            self.leapcnt * (time_size + 4) + self.isstdcnt + self.isutcnt
+    }
+}
+
+fn read_header(rdr: &mut Reader, time_size: uint) -> Result<Header, ~str> {
+    if try!(rdr.take(4)) != "TZif".as_bytes() {
+        return Err("not a TZif file".to_owned());
+    }
+    let version = match try!(rdr.byte()) {
+        0 => 0,
+        v @ 0x32 .. 0x39 => v - 0x30,
+        v => return Err(format!("unknown TZif version {}", v)),
+    };
+    try!(rdr.take(15));
+    let mut counts = [0u, ..6];
+    for c in counts.mut_iter() {
+        *c = try!(rdr.be(4)) as uint;
+    }
+    let header = Header {
+        version: version,
+        isutcnt: counts[0],
+        isstdcnt: counts[1],
+        leapcnt: counts[2],
+        timecnt: counts[3],
+        typecnt: counts[4],
+        charcnt: counts[5],
+    };
+    if header.typecnt == 0 || header.typecnt > 256 || header.charcnt == 0 ||
+       (header.isutcnt != 0 && header.isutcnt != header.typecnt) ||
+       (header.isstdcnt != 0 && header.isstdcnt != header.typecnt) {
+        return Err("invalid TZif header".to_owned());
+    }
+    // The counts are used to size allocations, so make sure that the data
+    // they describe is actually there first. Each count is checked on its
+    // own first so that the total can't overflow.
+    let left = rdr.buf.len() - rdr.pos;
+    if counts.iter().any(|&c| c > left) || header.body_len(time_size) > left {
+        return Err("unexpected end of TZif data".to_owned());
+    }
+    Ok(header)
+}
+
+fn read_body(name: &str, rdr: &mut Reader, h: &Header, time_size: uint)
+             -> Result<Tz, ~str> {
+    let mut transitions = Vec::with_capacity(h.timecnt);
+    for _ in range(0, h.timecnt) {
+        let t = try!(rdr.be(time_size));
+        // sign-extend 32-bit times
+        let t = if time_size == 4 { t as i32 as i64 } else { t as i64 };
+        if transitions.last().map_or(false, |&prev| prev >= t) {
+            return Err("TZif transition times aren't ascending".to_owned());
+        }
+        transitions.push(t);
+    }
+    let indices = Vec::from_slice(try!(rdr.take(h.timecnt)));
+    if indices.iter().any(|&i| i as uint >= h.typecnt) {
+        return Err("TZif transition refers to a missing type".to_owned());
+    }
+
+    let mut raw_types = Vec::with_capacity(h.typecnt);
+    for _ in range(0, h.typecnt) {
+        let utoff = try!(rdr.be(4)) as i32;
+        let is_dst = try!(rdr.byte());
+        let abbrev_idx = try!(rdr.byte()) as uint;
+        if utoff == i32::MIN || is_dst > 1 || abbrev_idx >= h.charcnt {
+            return Err("invalid TZif local time type".to_owned());
+        }
+        raw_types.push((utoff, is_dst == 1, abbrev_idx));
+    }
+    let chars = try!(rdr.take(h.charcnt));
+    let mut types = Vec::with_capacity(h.typecnt);
+    for &(utoff, is_dst, idx) in raw_types.iter() {
+        let len = match chars.slice_from(idx).iter().position(|&c| c == 0) {
+            Some(len) => len,
+            None => return Err("unterminated TZif time zone abbreviation".to_owned()),
+        };
+        let abbrev = match str::from_utf8(chars.slice(idx, idx + len)) {
+            Some(s) => s.to_owned(),
+            None => return Err("TZif abbreviation isn't valid UTF-8".to_owned()),
+        };
+        types.push(LocalTimeType { utoff: utoff, is_dst: is_dst, abbrev: abbrev });
+    }
+
+    // Leap second records and the standard/wall and UT/local indicators
+    // don't affect conversions.
+    try!(rdr.take(h.leapcnt * (time_size + 4) + h.isstdcnt + h.isutcnt));
+
+    Ok(Tz {
+        name: name.to_owned(),
+        transitions: transitions,
+        indices: indices,
+        types: types,
+        rule: None,
+    })
+}
+
+// Parses a POSIX TZ string as used in TZif footers, such as
+// "CET-1CEST,M3.5.0,M10.5.0/3".
+fn parse_rule(s: &str) -> Result<Rule, ()> {
+    let mut p = RuleParser { s: s.as_bytes(), pos: 0 };
+
+    let std_name = try!(p.name());
+    // POSIX offsets are west of UTC
+    let std_off = -try!(p.time());
+    let std = LocalTimeType { utoff: std_off, is_dst: false, abbrev: std_name };
+    if p.done() {
+        return Ok(Rule { std: std, dst: None });
+    }
+
+    let dst_name = try!(p.name());
+    let dst_off = if p.peek() == Some(',' as u8) {
+        std_off + 3600
+    } else {
+        -try!(p.time())
+    };
+    let dst = LocalTimeType { utoff: dst_off, is_dst: true, abbrev: dst_name };
+
+    // Without dates, POSIX leaves the rule implementation-defined; use the
+    // current US rules like glibc does.
+    let (start, start_time, end, end_time) = if p.done() {
+        (MonthWeekDay(3, 2, 0), 7200, MonthWeekDay(11, 1, 0), 7200)
+    } else {
+        try!(p.expect(',' as u8));
+        let (start, start_time) = try!(p.date_time());
+        try!(p.expect(',' as u8));
+        let (end, end_time) = try!(p.date_time());
+        (start, start_time, end, end_time)
+    };
+    if !p.done() {
+        return Err(());
+    }
+    Ok(Rule { std: std, dst: Some((dst, start, start_time, end, end_time)) })
+}
+
+struct RuleParser<'a> {
+    s: &'a [u8],
+    pos: uint,
+}
+
+impl<'a> RuleParser<'a> {
+    fn done(&self) -> bool { self.pos == self.s.len() }
+
+    fn peek(&self) -> Option<u8> {
+        if self.done() { None } else { Some(self.s[self.pos]) }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) { self.pos += 1; true } else { false }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ()> {
+        if self.eat(c) { Ok(()) } else { Err(()) }
+    }
+
+    // Either alphabetic, or quoted in angle brackets like "<+0330>"
+    fn name(&mut self) -> Result<~str, ()> {
+        let (s, start) = (self.s, self.pos);
+        let name = if self.eat('<' as u8) {
+            while self.peek().map_or(false, |c| c != '>' as u8) { self.pos += 1 }
+            let name = s.slice(start + 1, self.pos);
+            try!(self.expect('>' as u8));
+            name
+        } else {
+            while self.peek().map_or(false, |c| (c as char).is_alphabetic()) {
+                self.pos += 1
+            }
+            s.slice(start, self.pos)
+        };
+        if name.len() < 3 { return Err(()) }
+        match str::from_utf8(name) {
+            Some(name) => Ok(name.to_owned()),
+            None => Err(()),
+        }
+    }
+
+    fn number(&mut self) -> Result<i32, ()> {
+        let start = self.pos;
+        let mut n = 0i32;
+        while self.peek().map_or(false, |c| c >= '0' as u8 && c <= '9' as u8) {
+            n = n * 10 + (self.s[self.pos] - '0' as u8) as i32;
+            self.pos += 1;
+            if n > 1000 { return Err(()) }
+        }
+        if self.pos == start { Err(()) } else { Ok(n) }
+    }
+
+    // [+-]hh[:mm[:ss]], in seconds
+    fn time(&mut self) -> Result<i32, ()> {
+        let sign = if self.eat('-' as u8) { -1 } else { self.eat('+' as u8); 1 };
+        let mut secs = try!(self.number()) * 3600;
+        if self.eat(':' as u8) {
+            secs += try!(self.number()) * 60;
+            if self.eat(':' as u8) {
+                secs += try!(self.number());
+            }
+        }
+        Ok(sign * secs)
+    }
+
+    fn date_time(&mut self) -> Result<(RuleDate, i32), ()> {
+        let date = if self.eat('J' as u8) {
+            let n = try!(self.number());
+            if n < 1 || n > 365 { return Err(()) }
+            Julian(n)
+        } else if self.eat('M' as u8) {
+            let m = try!(self.number());
+            try!(self.expect('.' as u8));
+            let w = try!(self.number());
+            try!(self.expect('.' as u8));
+            let d = try!(self.number());
+            if m < 1 || m > 12 || w < 1 || w > 5 || d > 6 { return Err(()) }
+            MonthWeekDay(m, w, d)
+        } else {
+            let n = try!(self.number());
+            if n > 365 { return Err(()) }
+            ZeroBased(n)
+        };
+        let time = if self.eat('/' as u8) { try!(self.time()) } else { 7200 };
+        Ok((date, time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tz, LocalTimeType};
+    use {Timespec, Tm, empty_tm};
+
+    // Builds a version 2 TZif file for a zone that is at -05:00 ("EST")
+    // until 2014-03-09T07:00:00Z, at -04:00 ("EDT") until
+    // 2014-11-02T06:00:00Z, and follows `footer` afterwards.
+    fn tzif(footer: &str) -> Vec<u8> {
+        fn be(out: &mut Vec<u8>, n: u64, size: uint) {
+            for i in range(0, size).rev() {
+                out.push((n >> (8 * i) as u64) as u8);
+            }
+        }
+        fn block(out: &mut Vec<u8>, size: uint) {
+            out.push_all("TZif2".as_bytes());
+            out.push_all([0u8, ..15]);
+            for &n in [0u64, 0, 0, 2, 2, 8].iter() {
+                be(out, n, 4);
+            }
+            be(out, 1394348400, size);
+            be(out, 1414908000, size);
+            out.push_all([1u8, 0]);
+            be(out, -18000i64 as u64, 4);
+            out.push_all([0u8, 4]);
+            be(out, -14400i64 as u64, 4);
+            out.push_all([1u8, 0]);
+            out.push_all("EDT\0EST\0".as_bytes());
+        }
+        let mut out = Vec::new();
+        block(&mut out, 4);
+        block(&mut out, 8);
+        out.push('\n' as u8);
+        out.push_all(footer.as_bytes());
+        out.push('\n' as u8);
+        out
+    }
+
+    fn new_york() -> Tz {
+        Tz::from_tzif("America/New_York", tzif("EST5EDT,M3.2.0,M11.1.0").as_slice()).unwrap()
+    }
+
+    fn local(year: i32, mon: i32, mday: i32, hour: i32, min: i32) -> Tm {
+        let mut tm = empty_tm();
+        tm.tm_year = year - 1900;
+        tm.tm_mon = mon - 1;
+        tm.tm_mday = mday;
+        tm.tm_hour = hour;
+        tm.tm_min = min;
+        tm.tm_isdst = -1;
+        tm
+    }
+
+    #[test]
+    fn test_offset_at() {
+        let tz = new_york();
+        let est = LocalTimeType { utoff: -18000, is_dst: false, abbrev: "EST".to_owned() };
+        let edt = LocalTimeType { utoff: -14400, is_dst: true, abbrev: "EDT".to_owned() };
+
+        // before, between and after the file's transitions
+        assert_eq!(tz.offset_at(Timespec::new(0, 0)), est);
+        assert_eq!(tz.offset_at(Timespec::new(1394348399, 0)), est);
+        assert_eq!(tz.offset_at(Timespec::new(1394348400, 0)), edt);
+        assert_eq!(tz.offset_at(Timespec::new(1414907999, 0)), edt);
+        assert_eq!(tz.offset_at(Timespec::new(1414908000, 0)), est);
+
+        // from the footer rule: 2020-03-08T07:00:00Z and 2020-11-01T06:00:00Z
+        assert_eq!(tz.offset_at(Timespec::new(1583650799, 0)), est);
+        assert_eq!(tz.offset_at(Timespec::new(1583650800, 0)), edt);
+        assert_eq!(tz.offset_at(Timespec::new(1604210399, 0)), edt);
+        assert_eq!(tz.offset_at(Timespec::new(1604210400, 0)), est);
+    }
+
+    #[test]
+    fn test_at() {
+        let tz = new_york();
+        let tm = tz.at(Timespec::new(1404219600, 5));
+        assert_eq!(tm.rfc3339(), "2014-07-01T09:00:00-04:00".to_owned());
+        assert_eq!(tm.tm_zone, "EDT".to_owned());
+        assert_eq!(tm.tm_isdst, 1);
+        assert_eq!(tm.tm_wday, 2);
+        assert_eq!(tm.tm_yday, 181);
+        assert_eq!(tm.tm_nsec, 5);
+        assert_eq!(tm.to_timespec(), Timespec::new(1404219600, 5));
+    }
+
+    #[test]
+    fn test_from_local() {
+        let tz = new_york();
+        assert_eq!(tz.from_local(&local(2014, 7, 1, 9, 0)), Timespec::new(1404219600, 0));
+        assert_eq!(tz.from_local(&local(2020, 1, 1, 0, 0)), Timespec::new(1577854800, 0));
+
+        // 2020-11-01 01:30 happens twice
+        let mut tm = local(2020, 11, 1, 1, 30);
+        assert_eq!(tz.from_local(&tm), Timespec::new(1604208600, 0));
+        tm.tm_isdst = 1;
+        assert_eq!(tz.from_local(&tm), Timespec::new(1604208600, 0));
+        tm.tm_isdst = 0;
+        assert_eq!(tz.from_local(&tm), Timespec::new(1604212200, 0));
+
+        // 2020-03-08 02:30 never happens; it reads as 03:30 EDT
+        let t = tz.from_local(&local(2020, 3, 8, 2, 30));
+        assert_eq!(t, Timespec::new(1583652600, 0));
+        assert_eq!(tz.at(t).strftime("%H:%M %Z"), "03:30 EDT".to_owned());
+    }
+
+    #[test]
+    fn test_southern_rule() {
+        // Australia/Sydney: +10:00, +11:00 from October to April
+        let tz = Tz::from_tzif("Australia/Sydney",
+                               tzif("AEST-10AEDT,M10.1.0,M4.1.0/3").as_slice()).unwrap();
+        assert_eq!(tz.at(Timespec::new(1577836800, 0)).rfc3339(),
+                   "2020-01-01T11:00:00+11:00".to_owned());
+        assert_eq!(tz.at(Timespec::new(1593561600, 0)).rfc3339(),
+                   "2020-07-01T10:00:00+10:00".to_owned());
+    }
+
+    #[test]
+    fn test_rules() {
+        for footer in ["<+0330>-3:30", "UTC0", "IST-5:30", "<-03>3<-02>,M3.5.0/-2,M10.5.0/-1",
+                       "WET0WEST,M3.5.0/1,M10.5.0", "XXX3YYY,J60/2,300/25"].iter() {
+            assert!(Tz::from_tzif("", tzif(*footer).as_slice()).is_ok(), "{}", footer);
+        }
+        for footer in ["EST", "5", "EST5EDT,M3.2.0", "EST5EDT,M13.2.0,M11.1.0",
+                       "EST5EDT,M3.2.0,M11.1.0 "].iter() {
+            assert!(Tz::from_tzif("", tzif(*footer).as_slice()).is_err(), "{}", footer);
+        }
+        let tz = Tz::from_tzif("", tzif("<+0330>-3:30").as_slice()).unwrap();
+        let tm = tz.at(Timespec::new(1577836800, 0));
+        assert_eq!(tm.tm_gmtoff, 12600);
+        assert_eq!(tm.tm_zone, "+0330".to_owned());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let good = tzif("EST5EDT,M3.2.0,M11.1.0");
+        assert!(Tz::from_tzif("", []).is_err());
+        assert!(Tz::from_tzif("", "TZif".as_bytes()).is_err());
+        assert!(Tz::from_tzif("", good.slice_to(good.len() - 1)).is_err());
+        assert!(Tz::from_tzif("", good.slice_to(60)).is_err());
+
+        let mut bad_index = good.clone();
+        *bad_index.get_mut(52) = 7;
+        assert!(Tz::from_tzif("", bad_index.as_slice()).is_err());
+
+        // a transition count far larger than the file
+        let mut bad_count = good.clone();
+        for i in range(32u, 36) {
+            *bad_count.get_mut(i) = 0xff;
+        }
+        assert!(Tz::from_tzif("", bad_count.as_slice()).is_err());
+
+        assert!(Tz::load("../etc/passwd").is_err());
+        assert!(Tz::load("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_utc() {
+        let tz = Tz::utc();
+        let tm = tz.at(Timespec::new(1234567890, 0));
+        assert_eq!(tm.rfc3339(), "2009-02-13T23:31:30Z".to_owned());
+        assert_eq!(tm.tm_zone, "UTC".to_owned());
+        assert_eq!(tz.from_local(&tm), Timespec::new(1234567890, 0));
+    }
+
+    #[test]
+    fn test_system_zone() {
+        // Only checked where the system has a zoneinfo database
+        let tz = match Tz::load("America/New_York") {
+            Ok(tz) => tz,
+            Err(..) => return,
+        };
+        assert_eq!(tz.name(), "America/New_York");
+        assert_eq!(tz.at(Timespec::new(1404219600, 0)).tm_gmtoff, -14400);
+        assert_eq!(tz.at(Timespec::new(1234567890, 0)).tm_gmtoff, -18000);
+        assert_eq!(tz.at(Timespec::new(4102444800, 0)).tm_zone, "EST".to_owned());
+    }
+}
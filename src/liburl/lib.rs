@@ -22,11 +22,14 @@
 extern crate collections;
 
 use collections::HashMap;
+use std::ascii::StrAsciiExt;
 use std::cmp::Eq;
 use std::fmt;
 use std::from_str::FromStr;
 use std::hash::Hash;
 use std::io::BufReader;
+use std::io::net::ip::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num;
 use std::strbuf::StrBuf;
 use std::uint;
 
@@ -54,7 +57,9 @@ pub struct Url {
     pub scheme: ~str,
     /// A URL subcomponent for user authentication.  `username` in the above example.
     pub user: Option<UserInfo>,
-    /// A domain name or IP address.  For example, `example.com`.
+    /// A domain name or IP address.  For example, `example.com`.  IPv6
+    /// addresses are stored without their brackets; see `Url::host` for the
+    /// parsed form.
     pub host: ~str,
     /// A TCP port number, for example `8080`.
     pub port: Option<~str>,
@@ -90,6 +95,18 @@ pub struct UserInfo {
 /// Represents the query component of a URI.
 pub type Query = Vec<(~str, ~str)>;
 
+/// The host of a URL, as found in the `host` field of a `Url`.
+#[deriving(Clone, Eq, TotalEq)]
+pub enum Host {
+    /// A registered name such as `example.com`, in lowercase ASCII.
+    /// Internationalized names are stored in their `xn--` (punycode) form.
+    Domain(~str),
+    /// An IPv4 address such as `127.0.0.1`.
+    Ipv4(u8, u8, u8, u8),
+    /// An IPv6 address such as `::1`, written in brackets in a URL.
+    Ipv6(u16, u16, u16, u16, u16, u16, u16, u16),
+}
+
 impl Url {
     pub fn new(scheme: ~str,
                user: Option<UserInfo>,
@@ -109,6 +126,162 @@ impl Url {
             fragment: fragment,
         }
     }
+
+    /// Parses the `host` field into a `Host`.
+    pub fn host(&self) -> Result<Host, ~str> {
+        Host::parse(self.host)
+    }
+
+    /// Parses the `port` field as a TCP port number.
+    pub fn port(&self) -> Result<Option<u16>, ~str> {
+        match self.port {
+            Some(ref port) => parse_port(*port).map(|p| Some(p)),
+            None => Ok(None),
+        }
+    }
+
+    /// Sets the scheme, which is stored in lowercase.
+    pub fn set_scheme(&mut self, scheme: &str) -> Result<(), ~str> {
+        match get_scheme(format!("{}:", scheme)) {
+            Ok((s, rest)) => {
+                if !rest.is_empty() {
+                    return Err("url: Invalid character in scheme.".to_owned());
+                }
+                self.scheme = s.to_ascii_lower();
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sets the host. IP addresses are stored in their canonical form.
+    pub fn set_host(&mut self, host: Host) {
+        self.host = match host {
+            Domain(name) => name,
+            Ipv4(a, b, c, d) => format!("{}.{}.{}.{}", a, b, c, d),
+            Ipv6(a, b, c, d, e, f, g, h) => ipv6_to_str([a, b, c, d, e, f, g, h]),
+        };
+        if !self.host.is_empty() && !self.path.is_empty() && !self.path.starts_with("/") {
+            self.path = "/".to_owned() + self.path;
+        }
+    }
+
+    /// Sets the port, or removes it if `port` is `None`.
+    pub fn set_port(&mut self, port: Option<u16>) {
+        self.port = port.map(|p| p.to_str());
+    }
+
+    /**
+     * Sets the (decoded) path.
+     *
+     * A `/` is prepended to a relative path if the URL has a host, and a
+     * path starting with `//` in a URL without a host is prefixed with `/.`
+     * so that it isn't read back as an authority.
+     */
+    pub fn set_path(&mut self, path: &str) {
+        self.path = if !self.host.is_empty() && !path.is_empty() && !path.starts_with("/") {
+            "/".to_owned() + path
+        } else if self.host.is_empty() && path.starts_with("//") {
+            "/.".to_owned() + path
+        } else {
+            path.to_owned()
+        };
+    }
+
+    /**
+     * Resolves a URI reference against this URL, as described in section 5
+     * of RFC 3986.
+     *
+     * # Example
+     *
+     * ```rust
+     * let base = url::from_str("http://example.com/a/b/c").unwrap();
+     * let url = base.join("../d?q=v").unwrap();
+     * assert_eq!(url.to_str(), "http://example.com/a/d?q=v".to_owned());
+     * ```
+     */
+    pub fn join(&self, reference: &str) -> Result<Url, ~str> {
+        if get_scheme(reference).is_ok() || reference.starts_with("//") {
+            let absolute = if reference.starts_with("//") {
+                format!("{}:{}", self.scheme, reference)
+            } else {
+                reference.to_owned()
+            };
+            let mut url = try!(from_str(absolute));
+            url.path = remove_dot_segments(url.path);
+            return Ok(url);
+        }
+
+        let (path, rest) = try!(get_path(reference, false));
+        let (query, fragment) = try!(get_query_fragment(rest));
+        let (path, query) = if path.is_empty() {
+            let query = if rest.starts_with("?") { query } else { self.query.clone() };
+            (self.path.clone(), query)
+        } else if path.starts_with("/") {
+            (remove_dot_segments(path), query)
+        } else {
+            (remove_dot_segments(self.merge(path)), query)
+        };
+
+        Ok(Url::new(self.scheme.clone(), self.user.clone(), self.host.clone(),
+                    self.port.clone(), path, query, fragment))
+    }
+
+    // merges a relative path with the path of this URL (RFC 3986 section 5.2.3)
+    fn merge(&self, path: &str) -> ~str {
+        if !self.host.is_empty() && self.path.is_empty() {
+            return "/".to_owned() + path;
+        }
+        match self.path.rfind('/') {
+            Some(i) => self.path.slice_to(i + 1).to_owned() + path,
+            None => path.to_owned(),
+        }
+    }
+
+    /**
+     * Returns the normalized form of this URL, as described in section 6.2.2
+     * of RFC 3986.
+     *
+     * The scheme and domain names are lowercased, IP addresses are
+     * canonicalized, the default port of the scheme is removed, dot segments
+     * are removed from the path and an empty path becomes `/` when there is
+     * a host. Percent-encoding is normalized when the URL is formatted, since
+     * the path, query and fragment are stored decoded.
+     */
+    pub fn normalize(&self) -> Url {
+        let mut url = self.clone();
+        url.scheme = url.scheme.to_ascii_lower();
+        if !url.host.is_empty() {
+            match url.host() {
+                Ok(host) => url.set_host(host),
+                Err(..) => {}
+            }
+        }
+
+        let default_port = match url.scheme.as_slice() {
+            "http" | "ws" => Some(80),
+            "https" | "wss" => Some(443),
+            "ftp" => Some(21),
+            _ => None,
+        };
+        match url.port() {
+            Ok(None) => {}
+            Ok(port) if port == default_port => url.port = None,
+            Ok(port) => url.set_port(port),
+            Err(..) => {}
+        }
+
+        url.path = remove_dot_segments(url.path);
+        if url.path.is_empty() && !url.host.is_empty() {
+            url.path = "/".to_owned();
+        }
+        url
+    }
+
+    /// Returns true if both URLs have the same normalized form.
+    pub fn equivalent(&self, other: &Url) -> bool {
+        self.normalize() == other.normalize()
+    }
 }
 
 impl Path {
@@ -131,6 +304,227 @@ impl UserInfo {
     }
 }
 
+impl Host {
+    /**
+     * Parses a host, as written in the authority of a URL.
+     *
+     * IPv6 addresses may be given with or without brackets. Domain names
+     * are lowercased, and labels which aren't ASCII are converted to their
+     * punycode form. This is the encoding step of IDNA, without the Unicode
+     * mapping of RFC 3491 beyond lowercasing.
+     *
+     * # Example
+     *
+     * ```rust
+     * use url::{Host, Domain, Ipv4};
+     *
+     * assert_eq!(Host::parse("Example.COM"), Ok(Domain("example.com".to_owned())));
+     * assert_eq!(Host::parse("bücher.de"), Ok(Domain("xn--bcher-kva.de".to_owned())));
+     * assert_eq!(Host::parse("10.0.0.1"), Ok(Ipv4(10, 0, 0, 1)));
+     * ```
+     */
+    pub fn parse(s: &str) -> Result<Host, ~str> {
+        let s = if s.starts_with("[") && s.ends_with("]") {
+            s.slice(1, s.len() - 1)
+        } else if s.starts_with("[") || s.ends_with("]") {
+            return Err("Unbalanced brackets in host.".to_owned());
+        } else {
+            s
+        };
+        let addr: Option<IpAddr> = FromStr::from_str(s);
+        if s.contains_char(':') {
+            return match addr {
+                Some(Ipv6Addr(a, b, c, d, e, f, g, h)) => Ok(Ipv6(a, b, c, d, e, f, g, h)),
+                _ => Err("Invalid IPv6 address.".to_owned()),
+            };
+        }
+        if s.contains_char('.') && s.chars().all(|c| c == '.' || c.is_digit()) {
+            return match addr {
+                Some(Ipv4Addr(a, b, c, d)) => Ok(Ipv4(a, b, c, d)),
+                _ => Err("Invalid IPv4 address.".to_owned()),
+            };
+        }
+
+        let mut labels = Vec::new();
+        for label in s.split('.') {
+            if label.bytes().all(|b| b < 0x80) {
+                labels.push(label.to_ascii_lower());
+                continue;
+            }
+            let chars: Vec<char> = label.chars().map(|c| c.to_lowercase()).collect();
+            match punycode_encode(chars.as_slice()) {
+                Some(encoded) => labels.push("xn--".to_owned() + encoded),
+                None => return Err("Invalid internationalized domain name.".to_owned()),
+            }
+        }
+        Ok(Domain(labels.connect(".")))
+    }
+}
+
+impl fmt::Show for Host {
+    /// Formats the host as it is written in a URL, with IPv6 addresses in
+    /// brackets and compressed as described in RFC 5952.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Domain(ref name) => write!(f.buf, "{}", *name),
+            Ipv4(a, b, c, d) => write!(f.buf, "{}.{}.{}.{}", a, b, c, d),
+            Ipv6(a, b, c, d, e, g, h, i) => {
+                write!(f.buf, "[{}]", ipv6_to_str([a, b, c, d, e, g, h, i]))
+            }
+        }
+    }
+}
+
+// Formats the groups of an IPv6 address, with the longest run of two or more
+// zero groups replaced by `::`.
+fn ipv6_to_str(groups: [u16, ..8]) -> ~str {
+    let (mut best_start, mut best_len) = (0u, 0u);
+    let mut i = 0u;
+    while i < 8 {
+        let mut j = i;
+        while j < 8 && groups[j] == 0 {
+            j += 1;
+        }
+        if j - i > best_len {
+            best_start = i;
+            best_len = j - i;
+        }
+        i = j + 1;
+    }
+
+    fn hex(groups: &[u16]) -> ~str {
+        groups.iter().map(|g| format!("{:x}", *g)).collect::<Vec<~str>>().connect(":")
+    }
+    if best_len < 2 {
+        return hex(groups.as_slice());
+    }
+    format!("{}::{}", hex(groups.slice_to(best_start)),
+            hex(groups.slice_from(best_start + best_len)))
+}
+
+// Encodes a label with the punycode algorithm of RFC 3492, returning `None`
+// on overflow.
+fn punycode_encode(input: &[char]) -> Option<~str> {
+    static BASE: u32 = 36;
+    static TMIN: u32 = 1;
+    static TMAX: u32 = 26;
+
+    fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+        static SKEW: u32 = 38;
+        static DAMP: u32 = 700;
+        let mut delta = if first { delta / DAMP } else { delta / 2 };
+        delta += delta / points;
+        let mut k = 0;
+        while delta > ((BASE - TMIN) * TMAX) / 2 {
+            delta /= BASE - TMIN;
+            k += BASE;
+        }
+        k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+    }
+
+    fn digit(d: u32) -> char {
+        if d < 26 { ('a' as u32 + d) as u8 as char } else { ('0' as u32 + d - 26) as u8 as char }
+    }
+
+    let mut output = StrBuf::new();
+    for &c in input.iter() {
+        if (c as u32) < 0x80 {
+            output.push_char(c);
+        }
+    }
+    let basic = output.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        output.push_char('-');
+    }
+
+    let (mut n, mut delta, mut bias) = (0x80u32, 0u32, 72u32);
+    while (handled as uint) < input.len() {
+        let m = match input.iter().map(|&c| c as u32).filter(|&c| c >= n).min() {
+            Some(m) => m,
+            None => return None,
+        };
+        delta = match (m - n).checked_mul(&(handled + 1)).and_then(|d| delta.checked_add(&d)) {
+            Some(d) => d,
+            None => return None,
+        };
+        n = m;
+        for &c in input.iter() {
+            let c = c as u32;
+            if c < n {
+                delta = match delta.checked_add(&1) {
+                    Some(d) => d,
+                    None => return None,
+                };
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        TMIN
+                    } else if k >= bias + TMAX {
+                        TMAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    output.push_char(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push_char(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(output.into_owned())
+}
+
+// removes `.` and `..` segments from a path (RFC 3986 section 5.2.4)
+fn remove_dot_segments(path: &str) -> ~str {
+    let absolute = path.starts_with("/");
+    let segments: Vec<&str> = path.split('/').collect();
+    let start = if absolute { 1 } else { 0 };
+    let mut output: Vec<&str> = Vec::new();
+
+    for (i, &segment) in segments.iter().enumerate().skip(start) {
+        let last = i == segments.len() - 1;
+        match segment {
+            "." => {}
+            ".." => { output.pop(); }
+            s => { output.push(s); continue; }
+        }
+        if last {
+            output.push("");
+        }
+    }
+
+    let path = output.connect("/");
+    if absolute { "/".to_owned() + path } else { path }
+}
+
+// percent-encodes everything in a decoded path that can't appear in a path
+// segment, leaving the `/` separators alone
+fn encode_path(s: &str) -> ~str {
+    let mut out = StrBuf::new();
+    for b in s.bytes() {
+        match b as char {
+          'A' .. 'Z' | 'a' .. 'z' | '0' .. '9' | '-' | '.' | '_' | '~' |
+          '!' | '$' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | ';' | '=' |
+          ':' | '@' | '/' => out.push_char(b as char),
+          _ => out.push_str(format!("%{:02X}", b)),
+        }
+    }
+    out.into_owned()
+}
+
 fn encode_inner(s: &str, full_url: bool) -> ~str {
     let mut rdr = BufReader::new(s.as_bytes());
     let mut out = StrBuf::new();
@@ -460,8 +854,12 @@ pub fn query_to_str(query: &Query) -> ~str {
     let mut writer = MemWriter::new();
     for (i, &(ref k, ref v)) in query.iter().enumerate() {
         if i != 0 { write!(&mut writer, "&"); }
-        write!(&mut writer, "{}={}", encode_component(*k),
-               encode_component(*v));
+        if v.is_empty() {
+            write!(&mut writer, "{}", encode_component(*k));
+        } else {
+            write!(&mut writer, "{}={}", encode_component(*k),
+                   encode_component(*v));
+        }
     }
     str::from_utf8_lossy(writer.unwrap().as_slice()).into_owned()
 }
@@ -524,6 +922,21 @@ fn get_authority(rawurl: &str) ->
         return Ok((None, "".to_owned(), None, rawurl.to_str()));
     }
 
+    // a bracketed IPv6 literal can't be told apart from a port by the state
+    // machine below, so it's split off first.
+    let len = rawurl.len();
+    let end = match rawurl.slice_from(2).find(|c: char| c == '/' || c == '?' || c == '#') {
+        Some(i) => i + 2,
+        None => len,
+    };
+    let host_start = match rawurl.slice(2, end).rfind('@') {
+        Some(i) => i + 3,
+        None => 2,
+    };
+    if rawurl.slice(host_start, end).starts_with("[") {
+        return get_bracketed_authority(rawurl, host_start, end);
+    }
+
     enum State {
         Start, // starting state
         PassHostPort, // could be in user or port
@@ -533,7 +946,6 @@ fn get_authority(rawurl: &str) ->
         InPort // are in port
     }
 
-    let len = rawurl.len();
     let mut st = Start;
     let mut input = Digit; // most restricted, start here.
 
@@ -546,7 +958,7 @@ fn get_authority(rawurl: &str) ->
     let mut begin = 2;
     let mut end = len;
 
-    for (i,c) in rawurl.chars().enumerate() {
+    for (i,c) in rawurl.char_indices() {
         if i < 2 { continue; } // ignore the leading //
 
         // deal with input class first
@@ -564,6 +976,10 @@ fn get_authority(rawurl: &str) ->
           ':' | '@' | '?' | '#' | '/' => {
             // separators, don't change anything
           }
+          _ if c as u32 >= 0x80 => {
+            // internationalized host names are checked by `Host::parse`
+            input = Unreserved;
+          }
           _ => {
             return Err("Illegal character in authority".to_owned());
           }
@@ -674,6 +1090,46 @@ fn get_authority(rawurl: &str) ->
     return Ok((userinfo, host, port, rest));
 }
 
+// parses an authority whose host, starting at `host_start`, is a bracketed
+// IPv6 literal. The brackets are not part of the returned host.
+fn get_bracketed_authority(rawurl: &str, host_start: uint, end: uint) ->
+    Result<(Option<UserInfo>, ~str, Option<~str>, ~str), ~str> {
+    let userinfo = if host_start > 2 {
+        let (user, pass) = split_char_first(rawurl.slice(2, host_start - 1), ':');
+        let has_pass = rawurl.slice(2, host_start - 1).contains_char(':');
+        Some(UserInfo::new(user, if has_pass { Some(pass) } else { None }))
+    } else {
+        None
+    };
+
+    let hostport = rawurl.slice(host_start, end);
+    let close = match hostport.find(']') {
+        Some(i) => i,
+        None => return Err("Unterminated IPv6 address in authority.".to_owned()),
+    };
+    let host = hostport.slice(1, close);
+    if !host.chars().all(|c| c == ':' || c == '.' || c.is_digit_radix(16)) {
+        return Err("Illegal characters in IPv6 address.".to_owned());
+    }
+
+    let port = match hostport.slice_from(close + 1) {
+        "" => None,
+        p if p.starts_with(":") && p.slice_from(1).chars().all(|c| c.is_digit()) => {
+            Some(p.slice_from(1).to_owned())
+        }
+        _ => return Err("Invalid characters after IPv6 address.".to_owned()),
+    };
+
+    Ok((userinfo, host.to_owned(), port, rawurl.slice_from(end).to_owned()))
+}
+
+
+fn parse_port(port: &str) -> Result<u16, ~str> {
+    match num::from_str_radix::<u16>(port, 10) {
+        Some(p) if port.chars().all(|c| c.is_digit()) => Ok(p),
+        _ => Err(format!("Invalid port number: {}", port)),
+    }
+}
 
 // returns the path and unparsed part of url, or an error
 fn get_path(rawurl: &str, authority: bool) ->
@@ -744,11 +1200,28 @@ pub fn from_str(rawurl: &str) -> Result<Url, ~str> {
     };
 
     // authority
-    let (userinfo, host, port, rest) = match get_authority(rest) {
+    let (userinfo, mut host, mut port, rest) = match get_authority(rest) {
         Ok(val) => val,
         Err(e) => return Err(e),
     };
 
+    // host and port
+    if !host.is_empty() {
+        match Host::parse(host) {
+            Ok(Domain(name)) => if host.bytes().any(|b| b >= 0x80) { host = name },
+            Ok(_) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    port = match port {
+        Some(ref p) if p.is_empty() => None,
+        Some(p) => match parse_port(p) {
+            Ok(_) => Some(p),
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+
     // path
     let has_authority = if host == "".to_owned() { false } else { true };
     let (path, rest) = match get_path(rest, has_authority) {
@@ -822,14 +1295,18 @@ impl fmt::Show for Url {
                 Some(ref user) => try!(write!(f.buf, "{}", *user)),
                 None => {}
             }
+            if self.host.contains_char(':') {
+                try!(write!(f.buf, "[{}]", self.host));
+            } else {
+                try!(write!(f.buf, "{}", self.host));
+            }
             match self.port {
-                Some(ref port) => try!(write!(f.buf, "{}:{}", self.host,
-                                                *port)),
-                None => try!(write!(f.buf, "{}", self.host)),
+                Some(ref port) => try!(write!(f.buf, ":{}", *port)),
+                None => {}
             }
         }
 
-        try!(write!(f.buf, "{}", self.path));
+        try!(write!(f.buf, "{}", encode_path(self.path)));
 
         if !self.query.is_empty() {
             try!(write!(f.buf, "?{}", query_to_str(&self.query)));
@@ -845,9 +1322,9 @@ impl fmt::Show for Url {
 
 impl fmt::Show for Path {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f.buf, "{}", self.path));
+        try!(write!(f.buf, "{}", encode_path(self.path)));
         if !self.query.is_empty() {
-            try!(write!(f.buf, "?{}", self.query))
+            try!(write!(f.buf, "?{}", query_to_str(&self.query)))
         }
 
         match self.fragment {
@@ -956,11 +1433,70 @@ fn test_get_path() {
     assert!(get_path("something?q", true).is_err());
 }
 
+#[test]
+fn test_get_authority_bracketed_ipv6() {
+    let (u, h, p, r) = get_authority("//us:p@[2001:db8::1]:8000/path").unwrap();
+    assert_eq!(u, Some(UserInfo::new("us".to_owned(), Some("p".to_owned()))));
+    assert_eq!(h, "2001:db8::1".to_owned());
+    assert_eq!(p, Some("8000".to_owned()));
+    assert_eq!(r, "/path".to_owned());
+
+    let (u, h, p, r) = get_authority("//[::1]?q").unwrap();
+    assert!(u.is_none());
+    assert_eq!(h, "::1".to_owned());
+    assert!(p.is_none());
+    assert_eq!(r, "?q".to_owned());
+
+    assert!(get_authority("//[::1").is_err());
+    assert!(get_authority("//[::1]x").is_err());
+    assert!(get_authority("//[::1]:80a").is_err());
+    assert!(get_authority("//[::g]").is_err());
+}
+
+#[test]
+fn test_remove_dot_segments() {
+    assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g".to_owned());
+    assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6".to_owned());
+    assert_eq!(remove_dot_segments("/b/c/.."), "/b/".to_owned());
+    assert_eq!(remove_dot_segments("/../g"), "/g".to_owned());
+    assert_eq!(remove_dot_segments("../g"), "g".to_owned());
+    assert_eq!(remove_dot_segments("/"), "/".to_owned());
+    assert_eq!(remove_dot_segments(""), "".to_owned());
+}
+
+#[test]
+fn test_encode_path() {
+    assert_eq!(encode_path("/a b/c?d#e"), "/a%20b/c%3Fd%23e".to_owned());
+    assert_eq!(encode_path("/gen/:addr=;x@y"), "/gen/:addr=;x@y".to_owned());
+    assert_eq!(encode_path("/100%\n"), "/100%25%0A".to_owned());
+    assert_eq!(encode_path("/é"), "/%C3%A9".to_owned());
+}
+
+#[test]
+fn test_punycode_encode() {
+    let encode = |s: &str| {
+        let chars: Vec<char> = s.chars().collect();
+        punycode_encode(chars.as_slice()).unwrap()
+    };
+    assert_eq!(encode("bücher"), "bcher-kva".to_owned());
+    assert_eq!(encode("münchen"), "mnchen-3ya".to_owned());
+    assert_eq!(encode("他们为什么不说中文"), "ihqwcrb4cv8a8dqg056pqjye".to_owned());
+}
+
+#[test]
+fn test_ipv6_to_str() {
+    assert_eq!(ipv6_to_str([0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]), "2001:db8::1".to_owned());
+    assert_eq!(ipv6_to_str([0, 0, 0, 0, 0, 0, 0, 1]), "::1".to_owned());
+    assert_eq!(ipv6_to_str([0, 0, 0, 0, 0, 0, 0, 0]), "::".to_owned());
+    assert_eq!(ipv6_to_str([0x2001, 0xdb8, 0, 1, 1, 1, 1, 1]), "2001:db8:0:1:1:1:1:1".to_owned());
+    assert_eq!(ipv6_to_str([0x2001, 0, 0, 1, 0, 0, 0, 1]), "2001:0:0:1::1".to_owned());
+}
+
 #[cfg(test)]
 mod tests {
     use {encode_form_urlencoded, decode_form_urlencoded,
          decode, encode, from_str, encode_component, decode_component,
-         path_from_str, UserInfo, get_scheme};
+         path_from_str, UserInfo, get_scheme, Host, Domain, Ipv4, Ipv6};
 
     use collections::HashMap;
 
@@ -1285,4 +1821,124 @@ mod tests {
         assert_eq!(form.get(&"a".to_owned()), &vec!("1".to_owned()));
         assert_eq!(form.get(&"foo bar".to_owned()), &vec!("abc".to_owned(), "12 = 34".to_owned()));
     }
+
+    #[test]
+    fn test_host_parse() {
+        assert_eq!(Host::parse("Rust-Lang.ORG"), Ok(Domain("rust-lang.org".to_owned())));
+        assert_eq!(Host::parse("bücher.example"), Ok(Domain("xn--bcher-kva.example".to_owned())));
+        assert_eq!(Host::parse("127.0.0.1"), Ok(Ipv4(127, 0, 0, 1)));
+        assert_eq!(Host::parse("[::1]"), Ok(Ipv6(0, 0, 0, 0, 0, 0, 0, 1)));
+        assert_eq!(Host::parse("2001:db8::ff00:42:8329"),
+                   Ok(Ipv6(0x2001, 0xdb8, 0, 0, 0, 0xff00, 0x42, 0x8329)));
+        assert!(Host::parse("256.0.0.1").is_err());
+        assert!(Host::parse("1.2.3").is_err());
+        assert!(Host::parse("[::1").is_err());
+        assert!(Host::parse("::g").is_err());
+
+        assert_eq!(Ipv4(10, 0, 0, 1).to_str(), "10.0.0.1".to_owned());
+        assert_eq!(Ipv6(0xfe80, 0, 0, 0, 0, 0, 0, 0xA).to_str(), "[fe80::a]".to_owned());
+    }
+
+    #[test]
+    fn test_url_host_and_port() {
+        let u = from_str("http://[2001:DB8::1]:8080/").unwrap();
+        assert_eq!(u.host, "2001:DB8::1".to_owned());
+        assert_eq!(u.host(), Ok(Ipv6(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)));
+        assert_eq!(u.port(), Ok(Some(8080u16)));
+        assert_eq!(u.to_str(), "http://[2001:DB8::1]:8080/".to_owned());
+
+        let u = from_str("http://bücher.example/").unwrap();
+        assert_eq!(u.host, "xn--bcher-kva.example".to_owned());
+
+        let u = from_str("http://rust-lang.org:/").unwrap();
+        assert!(u.port.is_none());
+        assert_eq!(u.port(), Ok(None));
+
+        assert!(from_str("http://rust-lang.org:65536/").is_err());
+        assert!(from_str("http://[::1]:99999/").is_err());
+        assert!(from_str("http://300.1.1.1/").is_err());
+    }
+
+    #[test]
+    fn test_join() {
+        let base = from_str("http://a/b/c/d;p?q").unwrap();
+        let join = |r: &str| base.join(r).unwrap().to_str();
+
+        // RFC 3986, section 5.4.1
+        assert_eq!(join("g:h"), "g:h".to_owned());
+        assert_eq!(join("g"), "http://a/b/c/g".to_owned());
+        assert_eq!(join("./g"), "http://a/b/c/g".to_owned());
+        assert_eq!(join("g/"), "http://a/b/c/g/".to_owned());
+        assert_eq!(join("/g"), "http://a/g".to_owned());
+        assert_eq!(join("//g"), "http://g".to_owned());
+        assert_eq!(join("?y"), "http://a/b/c/d;p?y".to_owned());
+        assert_eq!(join("g?y"), "http://a/b/c/g?y".to_owned());
+        assert_eq!(join("#s"), "http://a/b/c/d;p?q#s".to_owned());
+        assert_eq!(join("g#s"), "http://a/b/c/g#s".to_owned());
+        assert_eq!(join("g?y#s"), "http://a/b/c/g?y#s".to_owned());
+        assert_eq!(join(";x"), "http://a/b/c/;x".to_owned());
+        assert_eq!(join("g;x"), "http://a/b/c/g;x".to_owned());
+        assert_eq!(join("g;x?y#s"), "http://a/b/c/g;x?y#s".to_owned());
+        assert_eq!(join(""), "http://a/b/c/d;p?q".to_owned());
+        assert_eq!(join("."), "http://a/b/c/".to_owned());
+        assert_eq!(join("./"), "http://a/b/c/".to_owned());
+        assert_eq!(join(".."), "http://a/b/".to_owned());
+        assert_eq!(join("../"), "http://a/b/".to_owned());
+        assert_eq!(join("../g"), "http://a/b/g".to_owned());
+        assert_eq!(join("../.."), "http://a/".to_owned());
+        assert_eq!(join("../../"), "http://a/".to_owned());
+        assert_eq!(join("../../g"), "http://a/g".to_owned());
+
+        // RFC 3986, section 5.4.2
+        assert_eq!(join("../../../g"), "http://a/g".to_owned());
+        assert_eq!(join("/./g"), "http://a/g".to_owned());
+        assert_eq!(join("/../g"), "http://a/g".to_owned());
+        assert_eq!(join("g."), "http://a/b/c/g.".to_owned());
+        assert_eq!(join("..g"), "http://a/b/c/..g".to_owned());
+        assert_eq!(join("./../g"), "http://a/b/g".to_owned());
+        assert_eq!(join("g/./h"), "http://a/b/c/g/h".to_owned());
+        assert_eq!(join("g/../h"), "http://a/b/c/h".to_owned());
+        assert_eq!(join("g;x=1/./y"), "http://a/b/c/g;x=1/y".to_owned());
+        assert_eq!(join("g;x=1/../y"), "http://a/b/c/y".to_owned());
+        assert_eq!(join("http:g"), "http:g".to_owned());
+
+        assert!(base.join("http://[::1").is_err());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let u = from_str("HTTP://Rust-Lang.ORG:80/a/./b/../c%7e?q#f").unwrap();
+        assert_eq!(u.normalize().to_str(), "http://rust-lang.org/a/c~?q#f".to_owned());
+
+        let u = from_str("https://[2001:0DB8:0:0:0:0:0:1]:8443").unwrap();
+        assert_eq!(u.normalize().to_str(), "https://[2001:db8::1]:8443/".to_owned());
+
+        let a = from_str("http://example.com/%61b").unwrap();
+        let b = from_str("http://EXAMPLE.com:80/x/../ab").unwrap();
+        assert!(a.equivalent(&b));
+        assert!(!a.equivalent(&from_str("https://example.com/ab").unwrap()));
+    }
+
+    #[test]
+    fn test_setters() {
+        let mut u = from_str("http://rust-lang.org/doc").unwrap();
+        u.set_scheme("HTTPS").unwrap();
+        u.set_host(Ipv6(0, 0, 0, 0, 0, 0, 0, 1));
+        u.set_port(Some(8443));
+        u.set_path("a b/c");
+        assert_eq!(u.to_str(), "https://[::1]:8443/a%20b/c".to_owned());
+        assert_eq!(from_str(u.to_str()).unwrap(), u);
+
+        u.set_port(None);
+        u.set_host(Domain("example.com".to_owned()));
+        assert_eq!(u.to_str(), "https://example.com/a%20b/c".to_owned());
+
+        assert!(u.set_scheme("1http").is_err());
+        assert!(u.set_scheme("ht:tp").is_err());
+        assert_eq!(u.scheme, "https".to_owned());
+
+        let mut u = from_str("mailto:test@email.com").unwrap();
+        u.set_path("//not-a-host");
+        assert_eq!(u.to_str(), "mailto:/.//not-a-host".to_owned());
+    }
 }
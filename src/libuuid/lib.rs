@@ -37,6 +37,21 @@ fn main() {
 }
 ```
 
+Name-based (V3 and V5) UUIDs are derived from a namespace UUID and a name, so
+the same name always produces the same UUID:
+
+```rust
+use uuid::{Uuid, NAMESPACE_DNS};
+
+fn main() {
+    let uuid = Uuid::new_v5(&NAMESPACE_DNS, "rust-lang.org");
+    assert_eq!(uuid, Uuid::new_v5(&NAMESPACE_DNS, "rust-lang.org"));
+}
+```
+
+Time-based (V1) UUIDs are created with `Uuid::new_v1`, and their timestamp can
+be recovered with `get_timestamp`.
+
 # Strings
 
 Examples of string representations:
@@ -70,6 +85,7 @@ extern crate test;
 
 extern crate rand;
 extern crate serialize;
+extern crate time;
 
 use std::cast::{transmute,transmute_copy};
 use std::char::Char;
@@ -80,11 +96,15 @@ use std::hash::Hash;
 use std::num::FromStrRadix;
 use std::str;
 use std::slice;
+use std::unstable::mutex::{StaticNativeMutex, NATIVE_MUTEX_INIT};
 
 use rand::Rng;
 
 use serialize::{Encoder, Encodable, Decoder, Decodable};
 
+mod md5;
+mod sha1;
+
 /// A 128-bit (16 byte) buffer containing the ID
 pub type UuidBytes = [u8, ..16];
 
@@ -122,6 +142,39 @@ pub struct Uuid {
     bytes: UuidBytes
 }
 
+/// The namespace for fully-qualified domain names
+pub static NAMESPACE_DNS: Uuid = Uuid { bytes: [0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1,
+                                                 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for URLs
+pub static NAMESPACE_URL: Uuid = Uuid { bytes: [0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1,
+                                                 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for ISO object identifiers (OIDs)
+pub static NAMESPACE_OID: Uuid = Uuid { bytes: [0x6b, 0xa7, 0xb8, 0x12, 0x9d, 0xad, 0x11, 0xd1,
+                                                 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for X.500 distinguished names (in DER or text format)
+pub static NAMESPACE_X500: Uuid = Uuid { bytes: [0x6b, 0xa7, 0xb8, 0x14, 0x9d, 0xad, 0x11, 0xd1,
+                                                  0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+// The number of 100ns intervals between the start of the Gregorian calendar
+// (1582-10-15), which is the epoch of V1 timestamps, and the Unix epoch
+static GREGORIAN_TO_UNIX_TICKS: u64 = 0x01B21DD213814000;
+
+// The clock sequence and node shared by all the V1 UUIDs of this process
+struct ClockState {
+    initialized: bool,
+    last_timestamp: u64,
+    clock_seq: u16,
+    node: [u8, ..6],
+}
+
+static mut CLOCK_LOCK: StaticNativeMutex = NATIVE_MUTEX_INIT;
+static mut CLOCK_STATE: ClockState = ClockState {
+    initialized: false,
+    last_timestamp: 0,
+    clock_seq: 0,
+    node: [0, ..6],
+};
+
 impl<S: Writer> Hash<S> for Uuid {
     fn hash(&self, state: &mut S) {
         self.bytes.hash(state)
@@ -181,13 +234,89 @@ impl Uuid {
     }
 
     /// Create a new UUID of the specified version
+    ///
+    /// Returns `None` for the DCE Security version, which isn't supported,
+    /// and for the name-based versions, which need a namespace and a name
+    /// (see `new_v3` and `new_v5`).
     pub fn new(v: UuidVersion) -> Option<Uuid> {
         match v {
+            Version1Mac => Some(Uuid::new_v1()),
             Version4Random => Some(Uuid::new_v4()),
             _ => None
         }
     }
 
+    /// Creates a new time-based UUID
+    ///
+    /// The node ID is chosen at random once per process, with the multicast
+    /// bit set so that it can't clash with a real MAC address, as described
+    /// in section 4.5 of RFC 4122.
+    pub fn new_v1() -> Uuid {
+        Uuid::new_v1_inner(None)
+    }
+
+    /// Creates a new time-based UUID with the given node ID, which is
+    /// usually the MAC address of a network interface
+    pub fn new_v1_with_node(node: [u8, ..6]) -> Uuid {
+        Uuid::new_v1_inner(Some(node))
+    }
+
+    fn new_v1_inner(node: Option<[u8, ..6]>) -> Uuid {
+        let now = time::get_time();
+        let timestamp = (now.sec as u64) * 10000000 + (now.nsec as u64) / 100 +
+                        GREGORIAN_TO_UNIX_TICKS;
+
+        // The clock sequence starts at a random value and is bumped whenever
+        // the clock hasn't moved forward since the last UUID, so that two
+        // UUIDs never share a timestamp and clock sequence.
+        let (clock_seq, node) = unsafe {
+            let _guard = CLOCK_LOCK.lock();
+            if !CLOCK_STATE.initialized {
+                let mut rng = rand::task_rng();
+                CLOCK_STATE.clock_seq = rng.gen();
+                rng.fill_bytes(CLOCK_STATE.node.as_mut_slice());
+                CLOCK_STATE.node[0] |= 0x01;
+                CLOCK_STATE.initialized = true;
+            } else if timestamp <= CLOCK_STATE.last_timestamp {
+                CLOCK_STATE.clock_seq += 1;
+            }
+            CLOCK_STATE.last_timestamp = timestamp;
+            (CLOCK_STATE.clock_seq, node.unwrap_or(CLOCK_STATE.node))
+        };
+
+        let d4 = [(clock_seq >> 8) as u8, clock_seq as u8,
+                  node[0], node[1], node[2], node[3], node[4], node[5]];
+        let mut uuid = Uuid::from_fields(timestamp as u32,
+                                         (timestamp >> 32) as u16,
+                                         (timestamp >> 48) as u16,
+                                         d4.as_slice());
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(Version1Mac);
+        uuid
+    }
+
+    /// Creates a name-based UUID using MD5 hashing
+    ///
+    /// Version 5 UUIDs should be preferred unless compatibility with
+    /// existing version 3 UUIDs is needed.
+    pub fn new_v3(namespace: &Uuid, name: &str) -> Uuid {
+        let digest = md5::digest(name_bytes(namespace, name).as_slice());
+        Uuid::from_hash(digest.as_slice(), Version3Md5)
+    }
+
+    /// Creates a name-based UUID using SHA-1 hashing
+    pub fn new_v5(namespace: &Uuid, name: &str) -> Uuid {
+        let digest = sha1::digest(name_bytes(namespace, name).as_slice());
+        Uuid::from_hash(digest.slice_to(16), Version5Sha1)
+    }
+
+    fn from_hash(hash: &[u8], v: UuidVersion) -> Uuid {
+        let mut uuid = Uuid::from_bytes(hash).unwrap();
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(v);
+        uuid
+    }
+
     /// Creates a new random UUID
     ///
     /// Uses the `rand` module's default RNG task as the source
@@ -288,9 +417,10 @@ impl Uuid {
     ///
     /// This represents the algorithm used to generate the contents.
     ///
-    /// Currently only the Random (V4) algorithm is supported by this
-    /// module.  There are security and privacy implications for using
-    /// older versions - see [Wikipedia: Universally Unique Identifier](
+    /// The time-based (V1), name-based (V3 and V5) and random (V4)
+    /// algorithms are supported by this module.  There are security and
+    /// privacy implications for using older versions - see
+    /// [Wikipedia: Universally Unique Identifier](
     /// http://en.wikipedia.org/wiki/Universally_unique_identifier) for
     /// details.
     ///
@@ -314,6 +444,40 @@ impl Uuid {
         }
     }
 
+    /// Returns the time at which a V1 UUID was created
+    ///
+    /// Returns `None` if this isn't a V1 UUID.
+    pub fn get_timestamp(&self) -> Option<time::Timespec> {
+        if self.get_version() != Some(Version1Mac) {
+            return None;
+        }
+
+        let b = &self.bytes;
+        let ticks = ((b[6] & 0x0f) as u64 << 56) | (b[7] as u64 << 48) |
+                    (b[4] as u64 << 40) | (b[5] as u64 << 32) |
+                    (b[0] as u64 << 24) | (b[1] as u64 << 16) |
+                    (b[2] as u64 << 8) | (b[3] as u64);
+        let ticks = ticks as i64 - GREGORIAN_TO_UNIX_TICKS as i64;
+
+        // Timespec wants a positive nsec field for times before the epoch
+        let (mut sec, mut rem) = (ticks / 10000000, ticks % 10000000);
+        if rem < 0 {
+            sec -= 1;
+            rem += 10000000;
+        }
+        Some(time::Timespec::new(sec, (rem * 100) as i32))
+    }
+
+    /// Returns the 14-bit clock sequence of a V1 UUID
+    ///
+    /// Returns `None` if this isn't a V1 UUID.
+    pub fn get_clock_seq(&self) -> Option<u16> {
+        if self.get_version() != Some(Version1Mac) {
+            return None;
+        }
+        Some(((self.bytes[8] & 0x3f) as u16 << 8) | (self.bytes[9] as u16))
+    }
+
     /// Return an array of 16 octets containing the UUID data
     pub fn as_bytes<'a>(&'a self) -> &'a [u8] {
         self.bytes.as_slice()
@@ -445,6 +609,13 @@ impl Uuid {
     }
 }
 
+// The data hashed for a name-based UUID: the namespace followed by the name
+fn name_bytes(namespace: &Uuid, name: &str) -> Vec<u8> {
+    let mut data = Vec::from_slice(namespace.as_bytes());
+    data.push_all(name.as_bytes());
+    data
+}
+
 impl Default for Uuid {
     /// Returns the nil UUID, which is all zeroes
     fn default() -> Uuid {
@@ -489,6 +660,22 @@ impl Eq for Uuid {
 
 impl TotalEq for Uuid {}
 
+/// Compare two UUIDs byte by byte
+///
+/// This is the same order as comparing their hexadecimal strings. Note that
+/// V1 UUIDs do not sort by creation time in this order.
+impl Ord for Uuid {
+    fn lt(&self, other: &Uuid) -> bool {
+        self.bytes.as_slice() < other.bytes.as_slice()
+    }
+}
+
+impl TotalOrd for Uuid {
+    fn cmp(&self, other: &Uuid) -> Ordering {
+        self.bytes.as_slice().cmp(&other.bytes.as_slice())
+    }
+}
+
 // FIXME #9845: Test these more thoroughly
 impl<T: Encoder<E>, E> Encodable<T, E> for Uuid {
     /// Encode a UUID as a hypenated string
@@ -521,10 +708,12 @@ impl rand::Rand for Uuid {
 mod test {
     extern crate collections;
     extern crate rand;
+    extern crate time;
 
     use super::{Uuid, VariantMicrosoft, VariantNCS, VariantRFC4122,
                 Version1Mac, Version2Dce, Version3Md5, Version4Random,
-                Version5Sha1};
+                Version5Sha1, NAMESPACE_DNS, NAMESPACE_OID, NAMESPACE_URL,
+                NAMESPACE_X500};
     use std::str;
     use std::io::MemWriter;
 
@@ -546,8 +735,10 @@ mod test {
         assert!(s.len() == 32);
         assert!(uuid1.get_version().unwrap() == Version4Random);
 
+        let uuid2 = Uuid::new(Version1Mac).unwrap();
+        assert!(uuid2.get_version().unwrap() == Version1Mac);
+
         // Test unsupported versions
-        assert!(Uuid::new(Version1Mac) == None);
         assert!(Uuid::new(Version2Dce) == None);
         assert!(Uuid::new(Version3Md5) == None);
         assert!(Uuid::new(Version5Sha1) == None);
@@ -561,6 +752,78 @@ mod test {
         assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
     }
 
+    #[test]
+    fn test_new_v1() {
+        let before = time::get_time();
+        let uuid1 = Uuid::new_v1();
+        let uuid2 = Uuid::new_v1();
+        let after = time::get_time();
+
+        assert!(uuid1.get_version().unwrap() == Version1Mac);
+        assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
+        assert!(uuid1 != uuid2);
+
+        // The random node has the multicast bit set
+        assert!(uuid1.as_bytes()[10] & 0x01 == 0x01);
+
+        // Timestamps are truncated to 100ns
+        let ts = uuid1.get_timestamp().unwrap();
+        assert!(ts.sec >= before.sec && ts <= after);
+    }
+
+    #[test]
+    fn test_new_v1_unique() {
+        let mut set = collections::HashSet::new();
+        for _ in range(0, 1000) {
+            assert!(set.insert(Uuid::new_v1()));
+        }
+
+        let node = [0x00, 0xa0, 0xc9, 0x1e, 0x6b, 0xf6];
+        let u = Uuid::new_v1_with_node(node);
+        assert!(u.as_bytes().slice_from(10) == node.as_slice());
+    }
+
+    #[test]
+    fn test_get_timestamp() {
+        // The example from RFC 4122, with its variant fixed
+        let u = Uuid::parse_string("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap();
+        assert_eq!(u.get_timestamp(), Some(time::Timespec::new(854991792, 216875000)));
+        assert_eq!(u.get_clock_seq(), Some(0x2765));
+
+        let u = Uuid::parse_string("00000000-0000-1000-8000-000000000000").unwrap();
+        assert_eq!(u.get_timestamp(), Some(time::Timespec::new(-12219292800, 0)));
+
+        assert!(Uuid::new_v4().get_timestamp().is_none());
+        assert!(Uuid::new_v4().get_clock_seq().is_none());
+    }
+
+    #[test]
+    fn test_new_v3() {
+        let u = Uuid::new_v3(&NAMESPACE_DNS, "python.org");
+        assert!(u.get_version().unwrap() == Version3Md5);
+        assert!(u.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(u.to_hyphenated_str(), "6fa459ea-ee8a-3ca4-894e-db77e160355e".to_owned());
+
+        let u = Uuid::new_v3(&NAMESPACE_OID, "1.3.6.1");
+        assert_eq!(u.to_hyphenated_str(), "dd1a1cef-13d5-368a-ad82-eca71acd4cd1".to_owned());
+    }
+
+    #[test]
+    fn test_new_v5() {
+        let u = Uuid::new_v5(&NAMESPACE_DNS, "python.org");
+        assert!(u.get_version().unwrap() == Version5Sha1);
+        assert!(u.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(u.to_hyphenated_str(), "886313e1-3b8a-5372-9b90-0c9aee199e5d".to_owned());
+
+        let u = Uuid::new_v5(&NAMESPACE_URL, "http://rust-lang.org/");
+        assert_eq!(u.to_hyphenated_str(), "91e0633a-eff5-5ab4-8e35-adaca6246e04".to_owned());
+
+        assert!(Uuid::new_v5(&NAMESPACE_DNS, "rust-lang.org") ==
+                Uuid::new_v5(&NAMESPACE_DNS, "rust-lang.org"));
+        assert!(Uuid::new_v5(&NAMESPACE_DNS, "rust-lang.org") !=
+                Uuid::new_v5(&NAMESPACE_X500, "rust-lang.org"));
+    }
+
     #[test]
     fn test_get_version() {
         let uuid1 = Uuid::new_v4();
@@ -718,6 +981,23 @@ mod test {
         assert!(uuid2 != uuid1);
     }
 
+    #[test]
+    fn test_ordering() {
+        let u1 = Uuid::parse_string("00000000-0000-0000-0000-0000000000ff").unwrap();
+        let u2 = Uuid::parse_string("00000000-0000-0000-0000-000000000100").unwrap();
+        let u3 = Uuid::parse_string("ff000000-0000-0000-0000-000000000000").unwrap();
+
+        assert!(Uuid::nil() < u1);
+        assert!(u1 < u2);
+        assert!(u2 < u3);
+        assert!(u3 >= u3);
+        assert_eq!(u2.cmp(&u1), Greater);
+
+        let mut uuids = vec!(u3, u1, u2);
+        uuids.sort();
+        assert!(uuids == vec!(u1, u2, u3));
+    }
+
     #[test]
     fn test_from_fields() {
         let d1: u32 = 0xa1a2a3a4;
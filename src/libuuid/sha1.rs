@@ -0,0 +1,94 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A minimal SHA-1 (FIPS 180-4) implementation, used for version 5 UUIDs.
+
+#[inline]
+fn rotl(x: u32, n: u32) -> u32 {
+    (x << n) | (x >> (32 - n))
+}
+
+/// Computes the SHA-1 digest of `input`
+pub fn digest(input: &[u8]) -> [u8, ..20] {
+    let mut state = [0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+    // Pad with a 1 bit, zeroes, and the big-endian message length in bits
+    let mut msg = Vec::from_slice(input);
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    let bits = (input.len() as u64) * 8;
+    for i in range(0u, 8) {
+        msg.push((bits >> (56 - 8 * i) as u64) as u8);
+    }
+
+    for block in msg.as_slice().chunks(64) {
+        let mut w = [0u32, ..80];
+        for i in range(0u, 16) {
+            w[i] = (block[i * 4] as u32 << 24) |
+                   (block[i * 4 + 1] as u32 << 16) |
+                   (block[i * 4 + 2] as u32 << 8) |
+                   (block[i * 4 + 3] as u32);
+        }
+        for i in range(16u, 80) {
+            w[i] = rotl(w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16], 1);
+        }
+
+        let (mut a, mut b, mut c, mut d, mut e) =
+            (state[0], state[1], state[2], state[3], state[4]);
+        for i in range(0u, 80) {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5a827999),
+                1 => (b ^ c ^ d, 0x6ed9eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let tmp = rotl(a, 5) + f + e + k + w[i];
+            e = d;
+            d = c;
+            c = rotl(b, 30);
+            b = a;
+            a = tmp;
+        }
+
+        state[0] += a;
+        state[1] += b;
+        state[2] += c;
+        state[3] += d;
+        state[4] += e;
+    }
+
+    let mut out = [0u8, ..20];
+    for i in range(0u, 20) {
+        out[i] = (state[i / 4] >> (24 - 8 * (i % 4)) as u32) as u8;
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::digest;
+
+    fn hex(bytes: &[u8]) -> ~str {
+        bytes.iter().map(|b| format!("{:02x}", *b)).collect::<Vec<~str>>().concat()
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(hex(digest("".as_bytes())),
+                   "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_owned());
+        assert_eq!(hex(digest("abc".as_bytes())),
+                   "a9993e364706816aba3e25717850c26c9cd0d89d".to_owned());
+        let two_blocks = "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        assert_eq!(hex(digest(two_blocks.as_bytes())),
+                   "84983e441c3bd26ebaae4aa1f95129e5e54670f1".to_owned());
+    }
+}
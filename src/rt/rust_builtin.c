@@ -151,19 +151,6 @@ typedef struct {
     int32_t tm_nsec;
 } rust_tm;
 
-void rust_tm_to_tm(rust_tm* in_tm, struct tm* out_tm) {
-    memset(out_tm, 0, sizeof(struct tm));
-    out_tm->tm_sec = in_tm->tm_sec;
-    out_tm->tm_min = in_tm->tm_min;
-    out_tm->tm_hour = in_tm->tm_hour;
-    out_tm->tm_mday = in_tm->tm_mday;
-    out_tm->tm_mon = in_tm->tm_mon;
-    out_tm->tm_year = in_tm->tm_year;
-    out_tm->tm_wday = in_tm->tm_wday;
-    out_tm->tm_yday = in_tm->tm_yday;
-    out_tm->tm_isdst = in_tm->tm_isdst;
-}
-
 void tm_to_rust_tm(struct tm* in_tm, rust_tm* out_tm, int32_t gmtoff,
                    const char *zone, int32_t nsec) {
     out_tm->tm_sec = in_tm->tm_sec;
@@ -191,7 +178,6 @@ void tm_to_rust_tm(struct tm* in_tm, rust_tm* out_tm, int32_t gmtoff,
 #if defined(_MSC_VER) && (_MSC_VER >= 1400)
 #define GMTIME(clock, result) gmtime_s((result), (clock))
 #define LOCALTIME(clock, result) localtime_s((result), (clock))
-#define TIMEGM(result) _mkgmtime64(result)
 #else
 struct tm* GMTIME(const time_t *clock, struct tm *result) {
     struct tm* t = gmtime(clock);
@@ -205,13 +191,11 @@ struct tm* LOCALTIME(const time_t *clock, struct tm *result) {
     *result = *t;
     return result;
 }
-#define TIMEGM(result) mktime((result)) - _timezone
 #endif
 #else
 #define TZSET() tzset()
 #define GMTIME(clock, result) gmtime_r((clock), (result))
 #define LOCALTIME(clock, result) localtime_r((clock), (result))
-#define TIMEGM(result) timegm(result)
 #endif
 
 void
@@ -236,7 +220,13 @@ rust_localtime(int64_t sec, int32_t nsec, rust_tm *timeptr) {
 
     const char* zone = NULL;
 #if defined(__WIN32__)
-    int32_t gmtoff = -timezone;
+    // `timezone` is the offset of standard time, so the daylight saving
+    // bias has to be added in while it is in effect.
+    long bias = timezone;
+    if (tm.tm_isdst > 0) {
+        bias += _dstbias;
+    }
+    int32_t gmtoff = -bias;
     wchar_t wbuffer[64] = {0};
     char buffer[256] = {0};
     // strftime("%Z") can contain non-UTF-8 characters on non-English locale (issue #9418),
@@ -258,20 +248,6 @@ rust_localtime(int64_t sec, int32_t nsec, rust_tm *timeptr) {
     tm_to_rust_tm(&tm, timeptr, gmtoff, zone, nsec);
 }
 
-int64_t
-rust_timegm(rust_tm* timeptr) {
-    struct tm t;
-    rust_tm_to_tm(timeptr, &t);
-    return TIMEGM(&t);
-}
-
-int64_t
-rust_mktime(rust_tm* timeptr) {
-    struct tm t;
-    rust_tm_to_tm(timeptr, &t);
-    return mktime(&t);
-}
-
 #ifndef _WIN32
 
 DIR*
@@ -387,6 +363,182 @@ rust_unset_sigprocmask() {
 
 #endif
 
+#if defined(__WIN32__)
+
+void
+rust_stack_guard_install(void (*cb)(void)) {
+    // Guard page faults on windows surface as access violations which we do
+    // not intercept yet.
+}
+
+void
+rust_stack_guard_set(uintptr_t lo, uintptr_t hi) {
+}
+
+#else
+
+// The guard region of the green stack currently running on a thread. It is
+// written on every context switch and read by the fault handler, and is kept
+// in a pthread key rather than `__thread` storage, which is not available on
+// every platform we build for.
+typedef struct {
+    uintptr_t lo;
+    uintptr_t hi;
+} rust_guard_t;
+
+static pthread_key_t rust_guard_key;
+static pthread_once_t rust_guard_once = PTHREAD_ONCE_INIT;
+static int rust_guard_key_valid = 0;
+static void (*rust_guard_callback)(void) = NULL;
+
+// The actions which were in place before the handler was installed. Faults
+// outside of a guard page are passed on to them.
+static struct sigaction rust_guard_prev_segv;
+static struct sigaction rust_guard_prev_bus;
+
+// The handler calls back into rust to report which task overflowed before
+// aborting, and rust functions are not written with SIGSTKSZ in mind. This is
+// the room left for the handler on top of what the system itself needs.
+#define RUST_GUARD_HANDLER_SIZE (64 * 1024)
+
+static void
+rust_stack_guard_chain(int signum, siginfo_t *info, void *context) {
+    struct sigaction *prev = signum == SIGSEGV ? &rust_guard_prev_segv
+                                               : &rust_guard_prev_bus;
+    if (prev->sa_flags & SA_SIGINFO) {
+        prev->sa_sigaction(signum, info, context);
+    } else if (prev->sa_handler == SIG_DFL) {
+        // Reset to the default action and return. The faulting instruction is
+        // re-executed and the process dies as it would have without this
+        // handler.
+        sigaction(signum, prev, NULL);
+    } else if (prev->sa_handler != SIG_IGN) {
+        prev->sa_handler(signum);
+    }
+}
+
+static void
+rust_stack_guard_handler(int signum, siginfo_t *info, void *context) {
+    uintptr_t addr = (uintptr_t) info->si_addr;
+    rust_guard_t *guard = (rust_guard_t*) pthread_getspecific(rust_guard_key);
+    if (guard != NULL && addr >= guard->lo && addr < guard->hi) {
+        // does not return
+        rust_guard_callback();
+    }
+    rust_stack_guard_chain(signum, info, context);
+}
+
+static void
+rust_stack_guard_init() {
+    if (pthread_key_create(&rust_guard_key, NULL) != 0) {
+        return;
+    }
+    rust_guard_key_valid = 1;
+
+    struct sigaction action;
+    memset(&action, 0, sizeof(action));
+    action.sa_sigaction = rust_stack_guard_handler;
+    action.sa_flags = SA_SIGINFO | SA_ONSTACK;
+    sigemptyset(&action.sa_mask);
+    sigaction(SIGSEGV, &action, &rust_guard_prev_segv);
+    sigaction(SIGBUS, &action, &rust_guard_prev_bus);
+}
+
+void
+rust_stack_guard_install(void (*cb)(void)) {
+    rust_guard_callback = cb;
+    pthread_once(&rust_guard_once, rust_stack_guard_init);
+    if (!rust_guard_key_valid) {
+        return;
+    }
+
+    // The handler has to run on an alternate stack because the stack that
+    // faulted has no room left. Alternate stacks are per-thread, so this is
+    // called once by each scheduler thread and the allocations are leaked
+    // along with the thread.
+    size_t size = MINSIGSTKSZ + RUST_GUARD_HANDLER_SIZE;
+    stack_t ss;
+    ss.ss_sp = malloc(size);
+    ss.ss_size = size;
+    ss.ss_flags = 0;
+    if (ss.ss_sp == NULL || sigaltstack(&ss, NULL) != 0) {
+        free(ss.ss_sp);
+        return;
+    }
+
+    rust_guard_t *guard = (rust_guard_t*) calloc(1, sizeof(rust_guard_t));
+    if (guard != NULL && pthread_setspecific(rust_guard_key, guard) != 0) {
+        free(guard);
+    }
+}
+
+void
+rust_stack_guard_set(uintptr_t lo, uintptr_t hi) {
+    if (!rust_guard_key_valid) {
+        return;
+    }
+    rust_guard_t *guard = (rust_guard_t*) pthread_getspecific(rust_guard_key);
+    if (guard != NULL) {
+        guard->lo = lo;
+        guard->hi = hi;
+    }
+}
+
+#endif
+
+#if !defined(__WIN32__)
+
+#include <errno.h>
+
+#ifndef NSIG
+#define NSIG 65
+#endif
+
+// The write end of libnative's signal pipe, and the actions which were in
+// place before a handler was installed so they can be restored once nobody is
+// listening anymore. Installation and removal are serialized by the caller.
+static int rust_signal_fd = -1;
+static struct sigaction rust_signal_prev[NSIG];
+
+static void
+rust_signal_handler(int signum) {
+    // Only async-signal-safe functions may be called here. The pipe is
+    // non-blocking, so if it is full the signal is dropped rather than
+    // deadlocking; the helper thread is already going to wake up.
+    int saved = errno;
+    unsigned char byte = (unsigned char) signum;
+    ssize_t ret = write(rust_signal_fd, &byte, 1);
+    (void) ret;
+    errno = saved;
+}
+
+int
+rust_signal_install(int signum, int fd) {
+    if (signum <= 0 || signum >= NSIG) {
+        errno = EINVAL;
+        return -1;
+    }
+    rust_signal_fd = fd;
+
+    struct sigaction action;
+    memset(&action, 0, sizeof(action));
+    action.sa_handler = rust_signal_handler;
+    action.sa_flags = SA_RESTART;
+    sigemptyset(&action.sa_mask);
+    return sigaction(signum, &action, &rust_signal_prev[signum]);
+}
+
+int
+rust_signal_uninstall(int signum) {
+    if (signum <= 0 || signum >= NSIG) {
+        errno = EINVAL;
+        return -1;
+    }
+    return sigaction(signum, &rust_signal_prev[signum], NULL);
+}
+
+#endif
+
 //
 // Local Variables:
 // mode: C++
@@ -123,6 +123,25 @@ extern "C" void LLVMAddColdAttribute(LLVMValueRef Fn) {
 extern "C" void LLVMAddColdAttribute(LLVMValueRef Fn) {}
 #endif
 
+// The values of `SanitizeAttribute` in `rustc::lib::llvm`.
+enum RustSanitizeAttribute {
+  RustSanitizeAddress = 0,
+  RustSanitizeThread = 1,
+  RustSanitizeMemory = 2,
+};
+
+extern "C" void LLVMRustAddSanitizeAttribute(LLVMValueRef Fn,
+                                             RustSanitizeAttribute Kind) {
+  Attribute::AttrKind Attr;
+  switch (Kind) {
+  case RustSanitizeAddress: Attr = Attribute::SanitizeAddress; break;
+  case RustSanitizeThread:  Attr = Attribute::SanitizeThread; break;
+  case RustSanitizeMemory:  Attr = Attribute::SanitizeMemory; break;
+  default: llvm_unreachable("bad sanitize attribute");
+  }
+  unwrap<Function>(Fn)->addFnAttr(Attr);
+}
+
 extern "C" LLVMValueRef LLVMBuildAtomicLoad(LLVMBuilderRef B,
                                             LLVMValueRef source,
                                             const char* Name,
@@ -212,7 +231,7 @@ extern "C" void LLVMDIBuilderFinalize(DIBuilderRef Builder) {
     Builder->finalize();
 }
 
-extern "C" void LLVMDIBuilderCreateCompileUnit(
+extern "C" LLVMValueRef LLVMDIBuilderCreateCompileUnit(
     DIBuilderRef Builder,
     unsigned Lang,
     const char* File,
@@ -222,8 +241,8 @@ extern "C" void LLVMDIBuilderCreateCompileUnit(
     const char* Flags,
     unsigned RuntimeVer,
     const char* SplitName) {
-    Builder->createCompileUnit(Lang, File, Dir, Producer, isOptimized,
-        Flags, RuntimeVer, SplitName);
+    return wrap(Builder->createCompileUnit(Lang, File, Dir, Producer, isOptimized,
+        Flags, RuntimeVer, SplitName));
 }
 
 extern "C" LLVMValueRef LLVMDIBuilderCreateFile(
@@ -652,6 +671,65 @@ LLVMRustLinkInExternalBitcode(LLVMModuleRef dst, char *bc, size_t len) {
 }
 #endif
 
+// Serializes a module to bitcode, from which `LLVMRustParseBitcodeInContext`
+// can make copies of it in other contexts. Going through bitcode keeps the
+// order of the module's functions and globals intact.
+extern "C" LLVMMemoryBufferRef
+LLVMRustWriteBitcodeToMemoryBuffer(LLVMModuleRef M) {
+    std::string Bitcode;
+    raw_string_ostream OS(Bitcode);
+    WriteBitcodeToFile(unwrap(M), OS);
+    OS.flush();
+    return wrap(MemoryBuffer::getMemBufferCopy(Bitcode, ""));
+}
+
+// Parses the bitcode in `Buf` into a new module of the context `C`, leaving
+// `Buf` alone so that several threads can parse it at once. Returns NULL if
+// the bitcode can't be parsed.
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeInContext(LLVMContextRef C, LLVMMemoryBufferRef Buf) {
+    MemoryBuffer *buf = MemoryBuffer::getMemBuffer(unwrap(Buf)->getBuffer(),
+                                                   "", false);
+#if LLVM_VERSION_MINOR >= 5
+    ErrorOr<Module *> Dst = llvm::parseBitcodeFile(buf, *unwrap(C));
+    delete buf;
+    if (!Dst) {
+        return NULL;
+    }
+    return wrap(*Dst);
+#else
+    std::string Err;
+    Module *Dst = llvm::ParseBitcodeFile(buf, *unwrap(C), &Err);
+    delete buf;
+    return wrap(Dst);
+#endif
+}
+
+// Turns the definition of a function or global variable into an external
+// declaration.
+extern "C" void
+LLVMRustDropDefinition(LLVMValueRef V) {
+    GlobalValue *GV = unwrap<GlobalValue>(V);
+    if (Function *F = dyn_cast<Function>(GV)) {
+        F->deleteBody();
+    } else if (GlobalVariable *G = dyn_cast<GlobalVariable>(GV)) {
+        G->setInitializer(NULL);
+    }
+    GV->setLinkage(GlobalValue::ExternalLinkage);
+}
+
+// Removes a function or global variable from its module if nothing but dead
+// constants refer to it any more.
+extern "C" bool
+LLVMRustEraseUnusedGlobal(LLVMValueRef V) {
+    GlobalValue *GV = unwrap<GlobalValue>(V);
+    GV->removeDeadConstantUsers();
+    if (!GV->use_empty())
+        return false;
+    GV->eraseFromParent();
+    return true;
+}
+
 #if LLVM_VERSION_MINOR >= 5
 extern "C" void*
 LLVMRustOpenArchive(char *path) {
@@ -0,0 +1,50 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(struct_variant)]
+
+extern crate serialize;
+
+#[deriving(Encodable)]
+struct A {
+    #[serialize(renamed = "b")] //~ ERROR unknown `serialize` attribute `renamed` on a field
+    a: int,
+}
+
+#[deriving(Encodable)]
+#[serialize(tagged)] //~ ERROR unknown `serialize` attribute `tagged` on an enum
+enum B {
+    B1,
+}
+
+#[deriving(Encodable)]
+#[serialize(tag = "type")]
+enum C {
+    C1(int), //~ ERROR enums with a `tag` can't have tuple-like variants
+}
+
+#[deriving(Encodable)]
+enum D {
+    D1 { #[serialize(skip)] x: int }, //~ ERROR need `external`, `tag` or `untagged` on the enum
+}
+
+#[deriving(Encodable)]
+#[serialize(external)]
+enum E {
+    E1 { #[serialize(renamed = "y")] y: int }, //~ ERROR unknown `serialize` attribute `renamed`
+    E2,
+    E3,
+}
+
+#[deriving(Encodable)]
+#[serialize(untagged, tagged)] //~ ERROR unknown `serialize` attribute `tagged` on an enum
+enum F {}
+
+pub fn main() {}
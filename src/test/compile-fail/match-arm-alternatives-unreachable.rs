@@ -0,0 +1,27 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let x = Some(1);
+    match x {
+        Some(1) | Some(1) if true => (), //~ ERROR unreachable pattern
+        None | Some(_) | None => () //~ ERROR unreachable pattern
+    }
+    match (x, true) {
+        (None, _) | (_, true) | (Some(_), true) => (), //~ ERROR unreachable pattern
+        (Some(_), false) => ()
+    }
+    // A guard only keeps an arm's alternatives from shadowing later arms
+    match x {
+        Some(_) | None if true => (),
+        None => (),
+        Some(_) => ()
+    }
+}
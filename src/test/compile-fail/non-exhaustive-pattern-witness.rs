@@ -0,0 +1,149 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(struct_variant)]
+
+struct Foo {
+    first: bool,
+    second: Option<[uint, ..4]>
+}
+
+enum Color {
+    Red,
+    Green,
+    CustomRGBA { a: bool, r: u8, g: u8, b: u8 }
+}
+
+enum Direction {
+    North, East, South, West
+}
+
+enum ExcessiveEnum {
+    First, Second, Third, Fourth, Fifth, Sixth
+}
+
+enum Shape {
+    Circle(Option<Direction>, bool),
+    Point
+}
+
+fn struct_with_a_nested_enum_and_vector() {
+    match (Foo { first: true, second: None }) {
+    //~^ ERROR non-exhaustive patterns: `Foo{first: false, second: Some([_, _, _, _])}` not covered
+        Foo { first: true, second: None } => (),
+        Foo { first: true, second: Some(_) } => (),
+        Foo { first: false, second: None } => (),
+        Foo { first: false, second: Some([1u, 2u, 3u, 4u]) } => ()
+    }
+}
+
+fn enum_with_multiple_missing_variants() {
+    match Red {
+    //~^ ERROR non-exhaustive patterns: `Green` and `CustomRGBA{..}` not covered
+        Red => ()
+    }
+}
+
+fn enum_with_more_missing_variants_than_listed() {
+    match First {
+    //~^ ERROR non-exhaustive patterns: `Second`, `Third`, `Fourth` and more not covered
+        First => ()
+    }
+}
+
+fn enum_struct_variant() {
+    match Red {
+    //~^ ERROR non-exhaustive patterns: `CustomRGBA{a: true, ..}` not covered
+        Red => (),
+        Green => (),
+        CustomRGBA { a: false, r: _, g: _, b: 0 } => (),
+        CustomRGBA { a: false, r: _, g: _, b: _ } => ()
+    }
+}
+
+fn nested_enums() {
+    match Point {
+    //~^ ERROR non-exhaustive patterns: `Circle(None, false)` not covered
+        Point => (),
+        Circle(Some(_), _) => (),
+        Circle(None, true) => ()
+    }
+}
+
+fn enums_in_wildcard_columns() {
+    match (North, Some(North)) {
+    //~^ ERROR non-exhaustive patterns: `(_, None)` not covered
+        (_, Some(_)) => ()
+    }
+}
+
+fn tuples() {
+    match (true, false) {
+    //~^ ERROR non-exhaustive patterns: `(false, true)` not covered
+        (true, false) => (),
+        (true, true) => (),
+        (false, false) => ()
+    }
+}
+
+fn vectors_with_nested_enums() {
+    let x: &'static [Option<Direction>] = &[Some(North), None];
+    match x {
+    //~^ ERROR non-exhaustive patterns: `[Some(_), None]` not covered
+        [] => (),
+        [_] => (),
+        [Some(_), Some(_)] => (),
+        [None, _] => (),
+        [_, _, _, ..tail] => ()
+    }
+}
+
+fn vectors_of_any_length() {
+    let x: &'static [bool] = &[true];
+    match x {
+    //~^ ERROR non-exhaustive patterns: `[_, _, ..]` not covered
+        [] => (),
+        [_] => ()
+    }
+}
+
+fn boxes_and_references() {
+    match (box Some(1), &true) {
+    //~^ ERROR non-exhaustive patterns: `(box None, &false)` not covered
+        (box Some(_), _) => (),
+        (_, &true) => ()
+    }
+}
+
+fn literals() {
+    match 'a' {
+    //~^ ERROR non-exhaustive patterns: `_` not covered
+        'a' => (),
+        'b' => ()
+    }
+    match "foo" {
+    //~^ ERROR non-exhaustive patterns: `_` not covered
+        "foo" => ()
+    }
+}
+
+fn main() {
+    struct_with_a_nested_enum_and_vector();
+    enum_with_multiple_missing_variants();
+    enum_with_more_missing_variants_than_listed();
+    enum_struct_variant();
+    nested_enums();
+    enums_in_wildcard_columns();
+    tuples();
+    vectors_with_nested_enums();
+    vectors_of_any_length();
+    boxes_and_references();
+    literals();
+}
@@ -0,0 +1,28 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod foo {
+    pub mod bar {
+        pub fn frobnicate() {}
+    }
+
+    pub fn quux() {}
+}
+
+use foo::quxx;
+//~^ ERROR unresolved import: there is no `quxx` in `foo`
+//~^^ NOTE did you mean `foo::quux`?
+//~^^^ ERROR failed to resolve import
+use foo::frobnicate;
+//~^ ERROR unresolved import: there is no `frobnicate` in `foo`
+//~^^ NOTE did you mean `foo::bar::frobnicate`?
+//~^^^ ERROR failed to resolve import
+
+fn main() {}
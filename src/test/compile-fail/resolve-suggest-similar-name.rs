@@ -0,0 +1,35 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod foo {
+    pub fn bar() {}
+}
+
+struct Point {
+    x: int,
+    y: int,
+}
+
+fn main() {
+    let counter = 1;
+    let _ = countr; //~ ERROR unresolved name `countr`
+    //~^ NOTE did you mean `counter`?
+    foo::baz(); //~ ERROR unresolved name `foo::baz`
+    //~^ NOTE did you mean `foo::bar`?
+    let _ = Smoe(counter); //~ ERROR unresolved name `Smoe`
+    //~^ NOTE did you mean `Some`?
+    let _: Pont = Point { x: 1, y: 2 }; //~ ERROR use of undeclared type name `Pont`
+    //~^ NOTE did you mean `Point`?
+    match Some(counter) {
+        Soem(_) => (), //~ ERROR unresolved enum variant, struct or const `Soem`
+        //~^ NOTE did you mean `Some`?
+        _ => ()
+    }
+}
@@ -12,7 +12,7 @@ struct Foo(int, int);
 
 fn main() {
     let x = Foo(1, 2);
-    match x {   //~ ERROR non-exhaustive
+    match x {   //~ ERROR non-exhaustive patterns: `Foo(_, _)` not covered
         Foo(1, b) => println!("{}", b),
         Foo(2, b) => println!("{}", b)
     }
@@ -0,0 +1,42 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+struct Rectangle {
+    width: uint,
+    height: uint,
+}
+
+impl Rectangle {
+    fn area(&self) -> uint { self.width * self.height }
+}
+
+trait Shape {
+    fn perimeter(&self) -> uint;
+}
+
+impl Shape for Rectangle {
+    fn perimeter(&self) -> uint { 2 * (self.width + self.height) }
+}
+
+fn main() {
+    let _ = Rectangle { widht: 1, height: 2 };
+    //~^ ERROR structure `Rectangle` has no field named `widht`
+    //~^^ NOTE did you mean `width`?
+    let r = Rectangle { width: 1, height: 2 };
+    let _ = r.heigth; //~ ERROR attempted access of field `heigth`
+    //~^ NOTE did you mean `height`?
+    let _ = r.are(); //~ ERROR does not implement any method in scope named `are`
+    //~^ NOTE did you mean `area`?
+    let _ = r.perimiter(); //~ ERROR does not implement any method in scope named `perimiter`
+    //~^ NOTE did you mean `perimeter`?
+    let Rectangle { hieght: _, .. } = r;
+    //~^ ERROR struct `Rectangle` does not have a field named `hieght`
+    //~^^ NOTE did you mean `height`?
+}
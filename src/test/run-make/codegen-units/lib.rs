@@ -0,0 +1,27 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "rlib"]
+
+static mut CALLS: uint = 0;
+
+fn count() -> uint {
+    unsafe {
+        CALLS += 1;
+        CALLS
+    }
+}
+
+#[inline]
+pub fn add(a: uint, b: uint) -> uint { a + b }
+
+pub fn first() -> uint { add(count(), 1) }
+pub fn second() -> uint { add(count(), 2) }
+pub fn calls() -> uint { unsafe { CALLS } }
@@ -0,0 +1,28 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+pub fn sign(x: int) -> int {
+    if x < 0 {
+        -1
+    } else if x > 0 {
+        1
+    } else {
+        // never reached by the tests
+        0
+    }
+}
+
+#[test]
+fn test_sign() {
+    assert_eq!(sign(-5), -1);
+    assert_eq!(sign(5), 1);
+}
@@ -0,0 +1,26 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_id = "foo"]
+
+pub fn bar(v: Vec<int>) -> int {
+    let mut sum = 0;
+    let r = &v;
+    for x in r.iter() {
+        if *x < 0 {
+            break;
+        }
+        sum += *x;
+    }
+    let w = v;
+    sum + w.len() as int
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The Makefile replaces ANSWER before each compilation.
+mod math {
+    pub fn double(x: int) -> int {
+        x * 2
+    }
+
+    pub fn answer() -> int {
+        ANSWER
+    }
+
+    pub fn offset() -> int {
+        0
+    }
+}
+
+fn main() {
+    let args = std::os::args();
+    let expected: int = from_str(args[1]).unwrap();
+    assert_eq!(math::double(math::answer()) + math::offset(), expected);
+}
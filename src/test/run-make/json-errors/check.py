@@ -0,0 +1,32 @@
+# Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution and at
+# http://rust-lang.org/COPYRIGHT.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+import json
+import sys
+
+diags = [json.loads(line) for line in sys.stdin if line.strip()]
+assert len(diags) > 0
+
+if sys.argv[1] == 'span':
+    error = diags[0]
+    assert error['level'] == 'error', error
+    assert 'unresolved name' in error['message'], error
+    primary, call_site = error['spans']
+    assert primary['is_primary'] and primary['label'] is None, primary
+    assert primary['file_name'].endswith('foo.rs'), primary
+    assert primary['line_start'] == 13, primary
+    assert primary['expansion']['macro_decl_name'] == 'use_y!', primary
+    assert not call_site['is_primary'], call_site
+    assert call_site['label'] == 'in this expansion of use_y!', call_site
+    assert call_site['line_start'] == 16, call_site
+elif sys.argv[1] == 'early':
+    assert diags[0]['level'] == 'error', diags[0]
+    assert diags[0]['message'] == 'unknown crate type: `bogus`', diags[0]
+    assert diags[0]['spans'] == [], diags[0]
@@ -0,0 +1,25 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::mem;
+
+// The standard library isn't instrumented, so the uninitialized read has to
+// happen in this crate.
+#[inline(never)]
+fn read_uninit() -> bool {
+    let xs: [int, ..4] = unsafe { mem::uninit() };
+    xs[1] == 42
+}
+
+fn main() {
+    if read_uninit() {
+        unsafe { std::intrinsics::abort() }
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+static mut COUNTER: int = 0;
+
+fn main() {
+    let (tx, rx) = channel();
+    spawn(proc() {
+        unsafe { COUNTER += 1; }
+        tx.send(());
+    });
+    // Nothing orders this increment with the one in the other task.
+    unsafe { COUNTER += 1; }
+    rx.recv();
+}
@@ -0,0 +1,46 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_id = "foo"]
+
+use sum_areas = shapes::total;
+use std::cmp::max;
+
+pub struct Point {
+    pub x: int,
+    pub y: int,
+}
+
+pub trait Shape {
+    fn area(&self) -> int;
+}
+
+impl Shape for Point {
+    fn area(&self) -> int {
+        max(self.x * self.y, 0)
+    }
+}
+
+mod shapes {
+    use super::Shape;
+
+    pub fn total<S: Shape>(shapes: &[S]) -> int {
+        let mut sum = 0;
+        for shape in shapes.iter() {
+            sum += shape.area();
+        }
+        sum
+    }
+}
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    println!("{}", sum_areas([p]));
+}
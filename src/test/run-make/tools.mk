@@ -5,6 +5,29 @@ RUSTC := $(RUSTC) --out-dir $(TMPDIR) -L $(TMPDIR)
 CC := $(CC) -L $(TMPDIR)
 
 RUN = $(TMPDIR)/$(1)
+
+# Tests of `-Z sanitizer` and `-Z profile` need the compiler-rt runtime that
+# programs built with them link to, which is only built for x86_64 Linux.
+# Such a test names the runtime with `NEEDS_RUNTIME := asan` before including
+# this file. Likewise, a test which runs a program that doesn't come with
+# rust names it with `NEEDS_TOOL := llvm-cov`. Where the runtime isn't built
+# or the program isn't installed, the test is skipped and says why.
+ifdef NEEDS_RUNTIME
+RUNTIME_DIR := $(dir $(firstword $(RUSTC)))../lib/rustlib/x86_64-unknown-linux-gnu/lib
+ifeq ($(wildcard $(RUNTIME_DIR)/libcompiler-rt-$(NEEDS_RUNTIME).a),)
+SKIP_REASON := the $(NEEDS_RUNTIME) runtime of compiler-rt isn't built
+endif
+endif
+ifdef NEEDS_TOOL
+ifeq ($(shell which $(NEEDS_TOOL) 2> /dev/null),)
+SKIP_REASON := $(NEEDS_TOOL) isn't installed
+endif
+endif
+ifdef SKIP_REASON
+.DEFAULT_GOAL := skip
+skip:
+	@echo "skipped: $(SKIP_REASON)"
+endif
 FAILS = $(TMPDIR)/$(1) && exit 1 || exit 0
 
 RLIB_GLOB = lib$(1)*.rlib
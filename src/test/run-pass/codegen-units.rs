@@ -0,0 +1,57 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C codegen-units=3
+
+// Private functions, statics, string constants and `#[inline]` functions
+// used from functions that end up in different codegen units.
+
+static mut COUNTER: uint = 0;
+static GREETING: &'static str = "hello";
+
+fn bump() -> uint {
+    unsafe {
+        COUNTER += 1;
+        COUNTER
+    }
+}
+
+#[inline]
+fn double(x: uint) -> uint { x * 2 }
+
+#[inline]
+pub fn triple(x: uint) -> uint { x * 3 }
+
+fn apply<T>(f: fn(T) -> T, x: T) -> T { f(x) }
+
+mod a {
+    pub fn one() -> uint { super::bump() + super::double(1) }
+    pub fn greeting() -> &'static str { super::GREETING }
+}
+
+mod b {
+    pub fn two() -> uint { super::bump() + super::triple(2) }
+    pub fn apply_double(x: uint) -> uint { super::apply(super::double, x) }
+}
+
+mod c {
+    pub fn three() -> uint { super::bump() + super::a::one() + super::b::two() }
+    pub fn greeting_len() -> uint { super::GREETING.len() }
+}
+
+pub fn main() {
+    assert_eq!(a::one(), 3);
+    assert_eq!(b::two(), 8);
+    assert_eq!(c::three(), 3 + 6 + 11);
+    assert_eq!(b::apply_double(21), 42);
+    assert_eq!(a::greeting(), "hello");
+    assert_eq!(c::greeting_len(), 5);
+    assert_eq!(unsafe { COUNTER }, 5);
+}